@@ -0,0 +1,17 @@
+#version 410 core
+
+in vec2 v_uv;
+
+uniform sampler2DArray u_stages;
+uniform float u_stage;
+
+out vec4 frag_color;
+
+void main() {
+    vec4 color = texture(u_stages, vec3(v_uv, u_stage));
+    if (color.a < 0.5) {
+        discard;
+    }
+    // Multiplied in twice by the blending, mid gray is no change
+    frag_color = vec4(color.rgb, 1.0);
+}
@@ -0,0 +1,17 @@
+#version 410 core
+
+layout(location = 0) in vec3 a_position;
+layout(location = 1) in vec2 a_uv;
+
+uniform mat4 u_view_projection;
+uniform vec3 u_block;
+// Fraction of the depth range the cracks are pulled towards the camera
+uniform float u_depth_bias;
+
+out vec2 v_uv;
+
+void main() {
+    v_uv = a_uv;
+    gl_Position = u_view_projection * vec4(u_block + a_position, 1.0);
+    gl_Position.z -= u_depth_bias * gl_Position.w;
+}
@@ -0,0 +1,15 @@
+#version 410 core
+
+in vec2 v_corner;
+
+uniform vec4 u_color;
+
+out vec4 frag_color;
+
+void main() {
+    float distance = length(v_corner);
+    // A solid disc in the middle with a glow fading out to the edge of the quad
+    float disc = 1.0 - smoothstep(0.45, 0.5, distance);
+    float glow = 0.35 * pow(max(1.0 - distance, 0.0), 2.0);
+    frag_color = vec4(u_color.rgb, u_color.a * max(disc, glow));
+}
@@ -0,0 +1,20 @@
+#version 410 core
+
+// From -1 to 1 across the billboard
+layout(location = 0) in vec2 a_corner;
+
+uniform mat4 u_view_projection;
+uniform vec3 u_camera_position;
+// Center of the billboard relative to the camera
+uniform vec3 u_offset;
+// Half extents of the billboard along its two sides
+uniform vec3 u_right;
+uniform vec3 u_up;
+
+out vec2 v_corner;
+
+void main() {
+    v_corner = a_corner;
+    vec3 position = u_camera_position + u_offset + u_right * a_corner.x + u_up * a_corner.y;
+    gl_Position = u_view_projection * vec4(position, 1.0);
+}
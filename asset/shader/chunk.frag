@@ -0,0 +1,67 @@
+#version 410 core
+
+#include "lighting.glsl"
+
+in vec3 v_uv;
+in float v_shade;
+in vec2 v_light;
+// Biome color for grass and leaves, white for everything else
+in vec3 v_tint;
+in float v_emissive;
+in float v_distance;
+in vec3 v_world_position;
+in vec3 v_normal;
+flat in uint v_water;
+
+uniform sampler2DArray u_blocks;
+// Start and end of the fade in blocks
+uniform vec2 u_fog_range;
+uniform vec3 u_fog_color;
+// Fog by distance in every direction, not only horizontally
+uniform bool u_fog_spherical;
+// Set for the blended pass, otherwise textures are cut out at half alpha
+uniform bool u_translucent;
+// Set when drawing into the G-buffer, lighting and fog are left to the resolve pass
+uniform bool u_deferred;
+uniform vec3 u_camera_position;
+
+#include "water.glsl"
+
+// Lit color, or albedo in the G-buffer
+layout(location = 0) out vec4 frag_color;
+// G-buffer only, laid out as DeferredRenderer in deferred.rs reads them
+layout(location = 1) out vec4 frag_normal;
+layout(location = 2) out vec4 frag_light;
+
+void main() {
+    vec4 color = texture(u_blocks, v_uv);
+    // Cutout textures like leaves are all or nothing, translucent ones keep their alpha
+    float alpha = u_translucent ? color.a : 1.0;
+    if (color.a < (u_translucent ? 1.0 / 255.0 : 0.5)) {
+        discard;
+    }
+    if (u_deferred) {
+        frag_color = vec4(color.rgb * v_tint, 1.0);
+        frag_normal = vec4(v_normal * 0.5 + 0.5, 1.0);
+        frag_light = vec4(v_light, v_shade, v_emissive);
+        return;
+    }
+    float distance = length(v_world_position - u_camera_position);
+    float sun = sunlight(v_world_position, v_normal, distance);
+    vec3 albedo = color.rgb * v_tint;
+    // Point lights add to the light levels the same way block light does, the brighter wins
+    vec3 light = max(vec3(v_shade * brightness(v_light, sun)),
+        v_shade * point_lights(v_world_position, v_normal));
+    vec3 lit = albedo * light + glow(albedo, v_emissive);
+    // Only the surface seen from above, from below it's left as it is
+    if (u_translucent && u_fancy_water && v_water == 1u && v_normal.y > 0.5
+            && u_camera_position.y > v_world_position.y) {
+        vec4 water = water_surface(lit, alpha, v_world_position);
+        lit = water.rgb;
+        alpha = water.a;
+    }
+    lit = debug_shading(lit, albedo, light, v_normal);
+    float fog_distance = u_fog_spherical ? distance : v_distance;
+    float fog = smoothstep(u_fog_range.x, u_fog_range.y, fog_distance);
+    frag_color = vec4(mix(lit, u_fog_color, fog), alpha);
+}
@@ -0,0 +1,65 @@
+#version 410 core
+
+// Packed by BlockVertex in mesher.rs
+layout(location = 0) in uint a_position_uv;
+layout(location = 1) in uint a_layer_light;
+layout(location = 2) in uint a_fraction;
+layout(location = 3) in uint a_tint;
+
+uniform mat4 u_view_projection;
+uniform vec3 u_chunk_origin;
+uniform vec3 u_camera_position;
+
+out vec3 v_uv;
+// Face direction and ambient occlusion, the light itself is applied per fragment
+out float v_shade;
+// Sky and block light from 0 to 1
+out vec2 v_light;
+out vec3 v_tint;
+// How much the face glows, from 0 to 1
+out float v_emissive;
+out float v_distance;
+out vec3 v_world_position;
+out vec3 v_normal;
+// 1 on water faces
+flat out uint v_water;
+
+// In Face::ALL order
+const vec3 NORMALS[6] = vec3[](
+    vec3(1.0, 0.0, 0.0),
+    vec3(-1.0, 0.0, 0.0),
+    vec3(0.0, 1.0, 0.0),
+    vec3(0.0, -1.0, 0.0),
+    vec3(0.0, 0.0, 1.0),
+    vec3(0.0, 0.0, -1.0)
+);
+// Vertex brightness for each ambient occlusion level, from a corner boxed in on both sides
+// to an open one
+const float AO_BRIGHTNESS[4] = float[](0.45, 0.65, 0.82, 1.0);
+
+void main() {
+    uint packed = a_position_uv;
+    vec3 position = vec3(packed & 31u, (packed >> 5) & 31u, (packed >> 10) & 31u);
+    vec2 uv = vec2((packed >> 15) & 31u, (packed >> 20) & 31u);
+    // Sixteenths of a block, only block models have any
+    position += vec3(a_fraction & 15u, (a_fraction >> 4) & 15u, (a_fraction >> 8) & 15u) / 16.0;
+    uv += vec2((a_fraction >> 12) & 15u, (a_fraction >> 16) & 15u) / 16.0;
+    vec3 normal = NORMALS[(packed >> 25) & 7u];
+    float ao = AO_BRIGHTNESS[(packed >> 28) & 3u];
+    float layer = float(a_layer_light & 0xFFFFu);
+    v_light = vec2((a_layer_light >> 16) & 255u, a_layer_light >> 24) / 255.0;
+
+    v_uv = vec3(uv, layer);
+    // Fixed brightness per face direction on top of the light, so edges stay readable
+    float direction = 0.8 + 0.2 * normal.y - 0.1 * abs(normal.z);
+    v_shade = direction * ao;
+    v_normal = normal;
+    v_water = (packed >> 30) & 1u;
+    v_tint = vec3(a_tint & 255u, (a_tint >> 8) & 255u, (a_tint >> 16) & 255u) / 255.0;
+    v_emissive = float(a_tint >> 24) / 255.0;
+    vec3 world_position = u_chunk_origin + position;
+    // Horizontal only, chunks load in columns around the player so the edge is a ring
+    v_distance = length(world_position.xz - u_camera_position.xz);
+    v_world_position = world_position;
+    gl_Position = u_view_projection * vec4(world_position, 1.0);
+}
@@ -0,0 +1,49 @@
+#version 410 core
+
+in vec2 v_position;
+in vec2 v_offset;
+
+uniform float u_range;
+// Blocks the pattern has moved along X
+uniform float u_wind;
+uniform float u_cell_size;
+// Fraction of the sky covered, from 0 to 1
+uniform float u_density;
+uniform float u_daylight;
+uniform vec3 u_fog_color;
+
+out vec4 frag_color;
+
+// Cells before the pattern repeats, has to match the renderer
+const float PATTERN_CELLS = 256.0;
+const vec3 DAY_COLOR = vec3(1.0, 1.0, 1.0);
+const vec3 NIGHT_COLOR = vec3(0.1, 0.1, 0.15);
+
+float hash(vec2 cell) {
+    cell = mod(cell, PATTERN_CELLS);
+    return fract(sin(dot(cell, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+// Smooth noise from 0 to 1 over a grid of `scale` cells, which has to divide PATTERN_CELLS
+float value_noise(vec2 cell, float scale) {
+    vec2 position = cell / scale;
+    vec2 corner = floor(position);
+    vec2 t = smoothstep(0.0, 1.0, position - corner);
+    float a = hash(corner * scale);
+    float b = hash((corner + vec2(1.0, 0.0)) * scale);
+    float c = hash((corner + vec2(0.0, 1.0)) * scale);
+    float d = hash((corner + vec2(1.0, 1.0)) * scale);
+    return mix(mix(a, b, t.x), mix(c, d, t.x), t.y);
+}
+
+void main() {
+    // Everything in one cell gets the same value, which keeps the edges blocky
+    vec2 cell = floor((v_position + vec2(u_wind, 0.0)) / u_cell_size);
+    float noise = 0.65 * value_noise(cell, 8.0) + 0.35 * value_noise(cell, 4.0);
+    if (noise < 1.0 - u_density) {
+        discard;
+    }
+    vec3 color = mix(NIGHT_COLOR, DAY_COLOR, u_daylight);
+    float fog = smoothstep(u_range * 0.5, u_range, length(v_offset));
+    frag_color = vec4(mix(color, u_fog_color, fog), 0.8 * (1.0 - fog));
+}
@@ -0,0 +1,20 @@
+#version 410 core
+
+// From -1 to 1 across the layer
+layout(location = 0) in vec2 a_corner;
+
+uniform mat4 u_view_projection;
+uniform vec3 u_camera_position;
+// Half the width of the layer, centered on the camera
+uniform float u_range;
+uniform float u_height;
+
+out vec2 v_position;
+// Horizontal offset from the camera, the distance has to be taken per fragment
+out vec2 v_offset;
+
+void main() {
+    v_position = u_camera_position.xz + a_corner * u_range;
+    v_offset = a_corner * u_range;
+    gl_Position = u_view_projection * vec4(v_position.x, u_height, v_position.y, 1.0);
+}
@@ -0,0 +1,65 @@
+#version 410 core
+
+#include "lighting.glsl"
+
+// From -1 to 1 across the screen
+in vec2 v_screen;
+
+// Written by the chunk pass, see chunk.frag
+uniform sampler2D u_albedo;
+uniform sampler2D u_normal;
+// Sky light, block light, face shading and glow
+uniform sampler2D u_light;
+uniform sampler2D u_depth;
+uniform mat4 u_inverse_view_projection;
+uniform vec3 u_camera_position;
+// Start and end of the fade in blocks
+uniform vec2 u_fog_range;
+uniform vec3 u_fog_color;
+// Fog by distance in every direction, not only horizontally
+uniform bool u_fog_spherical;
+// From the SSAO pass, only read when u_ssao is set
+uniform sampler2D u_occlusion;
+uniform bool u_ssao;
+
+out vec4 frag_color;
+
+// 4x4 average of the SSAO result, which turns its samples differently every pixel
+float occlusion(vec2 uv) {
+    vec2 texel = 1.0 / vec2(textureSize(u_occlusion, 0));
+    float sum = 0.0;
+    for (int x = -2; x < 2; x++) {
+        for (int y = -2; y < 2; y++) {
+            sum += texture(u_occlusion, uv + (vec2(x, y) + 0.5) * texel).r;
+        }
+    }
+    return sum / 16.0;
+}
+
+void main() {
+    ivec2 pixel = ivec2(gl_FragCoord.xy);
+    float depth = texelFetch(u_depth, pixel, 0).r;
+    // Nothing drawn here, the sky behind shows through
+    if (depth >= 1.0) {
+        discard;
+    }
+    vec4 clip = vec4(v_screen, depth * 2.0 - 1.0, 1.0);
+    vec4 world = u_inverse_view_projection * clip;
+    vec3 world_position = world.xyz / world.w;
+    vec3 albedo = texelFetch(u_albedo, pixel, 0).rgb;
+    vec3 normal = normalize(texelFetch(u_normal, pixel, 0).xyz * 2.0 - 1.0);
+    vec4 light = texelFetch(u_light, pixel, 0);
+
+    float distance = length(world_position - u_camera_position);
+    float sun = sunlight(world_position, normal, distance);
+    float shade = light.z * (u_ssao ? occlusion(v_screen * 0.5 + 0.5) : 1.0);
+    vec3 lighting = max(vec3(shade * brightness(light.xy, sun)),
+        shade * point_lights(world_position, normal));
+    vec3 lit = albedo * lighting + glow(albedo, light.w);
+    lit = debug_shading(lit, albedo, lighting, normal);
+    // Like the forward pass
+    float horizontal = length(world_position.xz - u_camera_position.xz);
+    float fog_distance = u_fog_spherical ? distance : horizontal;
+    float fog = smoothstep(u_fog_range.x, u_fog_range.y, fog_distance);
+    frag_color = vec4(mix(lit, u_fog_color, fog), 1.0);
+}
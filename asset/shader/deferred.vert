@@ -0,0 +1,11 @@
+#version 410 core
+
+// Screen position, the triangle overhangs the screen so it covers every pixel
+layout(location = 0) in vec2 a_position;
+
+out vec2 v_screen;
+
+void main() {
+    v_screen = a_position;
+    gl_Position = vec4(a_position, 0.0, 1.0);
+}
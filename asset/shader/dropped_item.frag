@@ -0,0 +1,34 @@
+#version 410 core
+
+#include "lighting.glsl"
+
+in vec3 v_uv;
+in vec3 v_color;
+in vec3 v_world_position;
+
+// Block or item textures, whichever the item's layers are in
+uniform sampler2DArray u_textures;
+// Sky and block light from 0 to 1 where the item lies
+uniform vec2 u_light;
+// How far towards white it's drawn, for primed TNT flashing
+uniform float u_flash;
+// Like chunk.frag
+uniform vec2 u_fog_range;
+uniform vec3 u_fog_color;
+uniform bool u_fog_spherical;
+uniform vec3 u_camera_position;
+
+out vec4 frag_color;
+
+void main() {
+    vec4 color = texture(u_textures, v_uv);
+    if (color.a < 0.5) {
+        discard;
+    }
+    vec3 lit = mix(color.rgb * v_color * brightness(u_light, 1.0), vec3(1.0), u_flash);
+    float distance = length(v_world_position - u_camera_position);
+    float horizontal = length(v_world_position.xz - u_camera_position.xz);
+    float fog_distance = u_fog_spherical ? distance : horizontal;
+    float fog = smoothstep(u_fog_range.x, u_fog_range.y, fog_distance);
+    frag_color = vec4(mix(lit, u_fog_color, fog), 1.0);
+}
@@ -0,0 +1,23 @@
+#version 410 core
+
+// IconVertex in icons.rs
+layout(location = 0) in vec3 a_position;
+layout(location = 1) in vec3 a_uv;
+layout(location = 2) in float a_shade;
+layout(location = 3) in vec3 a_tint;
+
+uniform mat4 u_view_projection;
+// The item's own units placed, bobbing and turning, where it lies
+uniform mat4 u_model;
+
+out vec3 v_uv;
+out vec3 v_color;
+out vec3 v_world_position;
+
+void main() {
+    vec4 world_position = u_model * vec4(a_position, 1.0);
+    v_uv = a_uv;
+    v_color = a_tint * a_shade;
+    v_world_position = world_position.xyz;
+    gl_Position = u_view_projection * world_position;
+}
@@ -0,0 +1,38 @@
+#version 410 core
+
+#include "lighting.glsl"
+
+in vec2 v_uv;
+in float v_shade;
+in vec3 v_world_position;
+in vec3 v_normal;
+
+uniform sampler2D u_texture;
+// Sky and block light from 0 to 1 where the entity stands, the whole model shares it
+uniform vec2 u_light;
+// Like chunk.frag
+uniform vec2 u_fog_range;
+uniform vec3 u_fog_color;
+uniform bool u_fog_spherical;
+uniform vec3 u_camera_position;
+// 1 while the entity flashes red from a hit, 0 otherwise
+uniform float u_hurt;
+
+out vec4 frag_color;
+
+void main() {
+    vec4 color = texture(u_texture, v_uv);
+    if (color.a < 0.5) {
+        discard;
+    }
+    color.rgb = mix(color.rgb, vec3(1.0, 0.0, 0.0), 0.4 * u_hurt);
+    float distance = length(v_world_position - u_camera_position);
+    float sun = sunlight(v_world_position, v_normal, distance);
+    vec3 light = max(vec3(v_shade * brightness(u_light, sun)),
+        v_shade * point_lights(v_world_position, v_normal));
+    vec3 lit = debug_shading(color.rgb * light, color.rgb, light, v_normal);
+    float horizontal = length(v_world_position.xz - u_camera_position.xz);
+    float fog_distance = u_fog_spherical ? distance : horizontal;
+    float fog = smoothstep(u_fog_range.x, u_fog_range.y, fog_distance);
+    frag_color = vec4(mix(lit, u_fog_color, fog), 1.0);
+}
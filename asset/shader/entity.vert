@@ -0,0 +1,28 @@
+#version 410 core
+
+// EntityVertex in entity_model.rs
+layout(location = 0) in vec3 a_position;
+layout(location = 1) in vec3 a_normal;
+layout(location = 2) in vec2 a_uv;
+
+uniform mat4 u_view_projection;
+// The part's pose placed where the entity stands
+uniform mat4 u_model;
+
+out vec2 v_uv;
+// Face direction only, entities have no ambient occlusion
+out float v_shade;
+out vec3 v_world_position;
+out vec3 v_normal;
+
+void main() {
+    vec4 world_position = u_model * vec4(a_position, 1.0);
+    // Parts are only ever turned, never scaled
+    vec3 normal = normalize(mat3(u_model) * a_normal);
+    v_uv = a_uv;
+    // The same fixed brightness per direction as chunks, turned parts blend between them
+    v_shade = 0.8 + 0.2 * normal.y - 0.1 * abs(normal.z);
+    v_world_position = world_position.xyz;
+    v_normal = normal;
+    gl_Position = u_view_projection * world_position;
+}
@@ -0,0 +1,20 @@
+#version 410 core
+
+#include "lighting.glsl"
+
+in vec3 v_uv;
+in vec3 v_color;
+
+uniform sampler2DArray u_blocks;
+// Sky and block light from 0 to 1 where the camera is
+uniform vec2 u_light;
+
+out vec4 frag_color;
+
+void main() {
+    vec4 color = texture(u_blocks, v_uv);
+    if (color.a < 0.5) {
+        discard;
+    }
+    frag_color = vec4(color.rgb * v_color * brightness(u_light, 1.0), 1.0);
+}
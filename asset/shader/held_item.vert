@@ -0,0 +1,19 @@
+#version 410 core
+
+// IconVertex in icons.rs
+layout(location = 0) in vec3 a_position;
+layout(location = 1) in vec3 a_uv;
+layout(location = 2) in float a_shade;
+layout(location = 3) in vec3 a_tint;
+
+// Straight from block units to the screen, the held block doesn't live in the world
+uniform mat4 u_view_projection;
+
+out vec3 v_uv;
+out vec3 v_color;
+
+void main() {
+    v_uv = a_uv;
+    v_color = a_tint * a_shade;
+    gl_Position = u_view_projection * vec4(a_position, 1.0);
+}
@@ -0,0 +1,16 @@
+#version 410 core
+
+in vec4 v_color;
+in vec2 v_uv;
+// 1 for icons, 0 for plain rectangles
+in float v_textured;
+
+// IconAtlas in icons.rs
+uniform sampler2D u_icons;
+
+out vec4 frag_color;
+
+void main() {
+    vec4 icon = v_textured > 0.5 ? texture(u_icons, v_uv) : vec4(1.0);
+    frag_color = v_color * icon;
+}
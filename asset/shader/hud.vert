@@ -0,0 +1,19 @@
+#version 410 core
+
+layout(location = 0) in vec2 a_position;
+layout(location = 1) in vec4 a_color;
+layout(location = 2) in vec2 a_uv;
+layout(location = 3) in float a_textured;
+
+uniform mat4 u_projection;
+
+out vec4 v_color;
+out vec2 v_uv;
+out float v_textured;
+
+void main() {
+    v_color = a_color;
+    v_uv = a_uv;
+    v_textured = a_textured;
+    gl_Position = u_projection * vec4(a_position, 0.0, 1.0);
+}
@@ -0,0 +1,17 @@
+#version 410 core
+
+in vec3 v_uv;
+in vec3 v_color;
+
+uniform sampler2DArray u_blocks;
+
+out vec4 frag_color;
+
+void main() {
+    vec4 color = texture(u_blocks, v_uv);
+    // Cutouts and translucent blocks alike keep their alpha, the HUD blends them
+    if (color.a < 1.0 / 255.0) {
+        discard;
+    }
+    frag_color = vec4(color.rgb * v_color, color.a);
+}
@@ -0,0 +1,113 @@
+// Block lighting shared by the forward chunk pass and the deferred resolve, included after
+// the #version line
+
+// Scales the sky light with the time of day
+uniform float u_daylight;
+// DebugShading in debug_view.rs, 0 draws normally
+uniform int u_debug_shading;
+
+// Filled in by ShadowMap in shadows.rs, a strength of 0 turns shadows off
+uniform sampler2DArrayShadow u_shadow_map;
+uniform mat4 u_shadow_matrices[3];
+// Far end of each cascade in blocks from the camera
+uniform vec3 u_cascade_ends;
+// Blocks per shadow map pixel in each cascade
+uniform vec3 u_shadow_texels;
+uniform float u_shadow_strength;
+// Toward the sun or moon, whichever is up
+uniform vec3 u_light_direction;
+
+// Filled in by LightBuffer in lights.rs
+struct PointLight {
+    // Position, then the radius in blocks
+    vec4 position_radius;
+    vec4 color;
+};
+layout(std140) uniform Lights {
+    // Only x is used
+    ivec4 u_light_count;
+    PointLight u_lights[32];
+};
+
+// Sky light left in full shadow, the rest of the sky still lights it
+const float SHADOWED_SKY_LIGHT = 0.55;
+// Glowing faces go this far past full brightness, enough for the bloom pass to pick them up
+const float EMISSIVE_BOOST = 1.5;
+// Depth bias on top of the normal offset, in the map's 0 to 1 depth range
+const float SHADOW_BIAS = 0.0004;
+
+// 1 in full light from the sun or moon, 0 in full shadow. `distance` is from the camera.
+float sunlight(vec3 world_position, vec3 normal, float distance) {
+    if (u_shadow_strength <= 0.0 || distance >= u_cascade_ends.z) {
+        return 1.0;
+    }
+    // Faces turned away from the light are in their own shadow
+    if (dot(normal, u_light_direction) < -0.01) {
+        return 1.0 - u_shadow_strength;
+    }
+    int cascade = distance < u_cascade_ends.x ? 0 : (distance < u_cascade_ends.y ? 1 : 2);
+    // Pushed out along the normal so a face doesn't shadow itself at grazing angles
+    vec3 position = world_position + normal * u_shadow_texels[cascade] * 1.5;
+    vec3 coords = (u_shadow_matrices[cascade] * vec4(position, 1.0)).xyz * 0.5 + 0.5;
+    vec2 texel = 1.0 / vec2(textureSize(u_shadow_map, 0).xy);
+    // 3x3 percentage closer filtering, each tap already blends 2x2 with linear filtering
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec2 offset = vec2(x, y) * texel;
+            lit += texture(
+                u_shadow_map,
+                vec4(coords.xy + offset, float(cascade), coords.z - SHADOW_BIAS)
+            );
+        }
+    }
+    return mix(1.0, lit / 9.0, u_shadow_strength);
+}
+
+// Sky and block light from 0 to 1 as a brightness multiplier, `sun` from sunlight()
+float brightness(vec2 light_levels, float sun) {
+    float sky = light_levels.x * u_daylight * mix(SHADOWED_SKY_LIGHT, 1.0, sun);
+    // Each level down is 80% as bright, with a little left over in complete darkness
+    float light = max(sky, light_levels.y);
+    return mix(0.05, 1.0, pow(0.8, 15.0 * (1.0 - light)));
+}
+
+// What a face with `emissive` from 0 to 1 adds on top of its lit color
+vec3 glow(vec3 albedo, float emissive) {
+    return albedo * emissive * EMISSIVE_BOOST;
+}
+
+// Light from the point lights reaching a face at `world_position`, as a color to multiply
+// with
+vec3 point_lights(vec3 world_position, vec3 normal) {
+    vec3 total = vec3(0.0);
+    for (int i = 0; i < u_light_count.x; i++) {
+        vec3 offset = u_lights[i].position_radius.xyz - world_position;
+        float radius = u_lights[i].position_radius.w;
+        float distance = length(offset);
+        if (distance >= radius) {
+            continue;
+        }
+        // Each block further is 80% as bright like block light, reaching 0 at the radius
+        float falloff = pow(0.8, distance) * (1.0 - distance / radius);
+        // Faces turned away still get some, block light doesn't care about direction at all
+        float facing = 0.6 + 0.4 * max(dot(normal, offset / max(distance, 0.001)), 0.0);
+        total += u_lights[i].color.rgb * falloff * facing;
+    }
+    return total;
+}
+
+// `lit` swapped for one part of it while a debug view asks for that, `light` is everything
+// the albedo was multiplied with
+vec3 debug_shading(vec3 lit, vec3 albedo, vec3 light, vec3 normal) {
+    switch (u_debug_shading) {
+        case 1:
+            return albedo;
+        case 2:
+            return light;
+        case 3:
+            return normal * 0.5 + 0.5;
+        default:
+            return lit;
+    }
+}
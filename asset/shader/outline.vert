@@ -0,0 +1,14 @@
+#version 410 core
+
+layout(location = 0) in vec3 a_position;
+
+uniform mat4 u_view_projection;
+uniform vec3 u_block;
+// Fraction of the depth range the outline is pulled towards the camera
+uniform float u_depth_bias;
+
+void main() {
+    gl_Position = u_view_projection * vec4(u_block + a_position, 1.0);
+    // Scaled by w so the bias is the same in window depth at any distance
+    gl_Position.z -= u_depth_bias * gl_Position.w;
+}
@@ -0,0 +1,19 @@
+#version 410 core
+
+in vec2 v_corner;
+in vec4 v_color;
+
+// Daylight, with a floor so nothing goes fully black at night
+uniform float u_brightness;
+
+out vec4 frag_color;
+
+void main() {
+    // Round puffs that fade out towards their edges
+    float distance = length(v_corner);
+    if (distance > 1.0) {
+        discard;
+    }
+    float alpha = v_color.a * (1.0 - distance * distance);
+    frag_color = vec4(v_color.rgb * u_brightness, alpha);
+}
@@ -0,0 +1,18 @@
+#version 410 core
+
+// ParticleVertex in particles.rs, already turned to face the camera
+layout(location = 0) in vec3 a_position;
+// From -1 to 1 across the particle
+layout(location = 1) in vec2 a_corner;
+layout(location = 2) in vec4 a_color;
+
+uniform mat4 u_view_projection;
+
+out vec2 v_corner;
+out vec4 v_color;
+
+void main() {
+    v_corner = a_corner;
+    v_color = a_color;
+    gl_Position = u_view_projection * vec4(a_position, 1.0);
+}
@@ -0,0 +1,15 @@
+#version 410 core
+
+in vec2 v_uv;
+
+uniform sampler2D u_scene;
+// The bright parts of the scene, blurred at half resolution
+uniform sampler2D u_bloom;
+uniform float u_strength;
+
+out vec4 frag_color;
+
+void main() {
+    vec3 color = texture(u_scene, v_uv).rgb + texture(u_bloom, v_uv).rgb * u_strength;
+    frag_color = vec4(color, 1.0);
+}
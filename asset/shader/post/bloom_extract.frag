@@ -0,0 +1,21 @@
+#version 410 core
+
+in vec2 v_uv;
+
+uniform sampler2D u_scene;
+
+out vec4 frag_color;
+
+// Brightness where bloom starts, only glowing blocks and the sun go past full white
+const float THRESHOLD = 1.0;
+// How far below the threshold it starts fading in, so the cutoff doesn't show
+const float KNEE = 0.5;
+
+void main() {
+    vec3 color = texture(u_scene, v_uv).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+    float soft = clamp(brightness - THRESHOLD + KNEE, 0.0, 2.0 * KNEE);
+    soft = soft * soft / (4.0 * KNEE);
+    float weight = max(soft, brightness - THRESHOLD) / max(brightness, 0.0001);
+    frag_color = vec4(color * weight, 1.0);
+}
@@ -0,0 +1,24 @@
+#version 410 core
+
+in vec2 v_uv;
+
+uniform sampler2D u_scene;
+// One pixel along the direction of the blur, it runs horizontally and vertically in turn
+uniform vec2 u_direction;
+
+out vec4 frag_color;
+
+// A 9 pixel gaussian in 5 taps, the outer ones land between two pixels and let linear
+// filtering blend them
+const float OFFSETS[3] = float[](0.0, 1.3846153846, 3.2307692308);
+const float WEIGHTS[3] = float[](0.2270270270, 0.3162162162, 0.0702702703);
+
+void main() {
+    vec3 color = texture(u_scene, v_uv).rgb * WEIGHTS[0];
+    for (int i = 1; i < 3; i++) {
+        vec2 offset = u_direction * OFFSETS[i];
+        color += texture(u_scene, v_uv + offset).rgb * WEIGHTS[i];
+        color += texture(u_scene, v_uv - offset).rgb * WEIGHTS[i];
+    }
+    frag_color = vec4(color, 1.0);
+}
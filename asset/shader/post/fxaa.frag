@@ -0,0 +1,60 @@
+#version 410 core
+
+in vec2 v_uv;
+
+uniform sampler2D u_scene;
+
+out vec4 frag_color;
+
+// Contrast below which a pixel isn't taken for an edge, absolute and relative to the
+// brightest neighbor
+const float EDGE_THRESHOLD_MIN = 1.0 / 32.0;
+const float EDGE_THRESHOLD = 1.0 / 8.0;
+// Keeps the blur direction finite on flat gradients
+const float REDUCE_MIN = 1.0 / 128.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+// Furthest along an edge that gets sampled, in pixels
+const float SPAN_MAX = 8.0;
+
+float luma(vec3 color) {
+    return dot(color, vec3(0.299, 0.587, 0.114));
+}
+
+// Fast approximate antialiasing: find the edge direction from the diagonal neighbors' luma
+// and blend a few samples along it
+void main() {
+    vec2 texel = 1.0 / vec2(textureSize(u_scene, 0));
+    vec3 middle = texture(u_scene, v_uv).rgb;
+    float luma_nw = luma(texture(u_scene, v_uv + vec2(-1.0, 1.0) * texel).rgb);
+    float luma_ne = luma(texture(u_scene, v_uv + vec2(1.0, 1.0) * texel).rgb);
+    float luma_sw = luma(texture(u_scene, v_uv + vec2(-1.0, -1.0) * texel).rgb);
+    float luma_se = luma(texture(u_scene, v_uv + vec2(1.0, -1.0) * texel).rgb);
+    float luma_m = luma(middle);
+    float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+    if (luma_max - luma_min < max(EDGE_THRESHOLD_MIN, luma_max * EDGE_THRESHOLD)) {
+        frag_color = vec4(middle, 1.0);
+        return;
+    }
+
+    vec2 direction = vec2(
+        (luma_sw + luma_se) - (luma_nw + luma_ne),
+        (luma_nw + luma_sw) - (luma_ne + luma_se)
+    );
+    float reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    float scale = 1.0 / (min(abs(direction.x), abs(direction.y)) + reduce);
+    direction = clamp(direction * scale, -SPAN_MAX, SPAN_MAX) * texel;
+
+    vec3 near = 0.5 * (
+        texture(u_scene, v_uv + direction * (1.0 / 3.0 - 0.5)).rgb +
+        texture(u_scene, v_uv + direction * (2.0 / 3.0 - 0.5)).rgb
+    );
+    vec3 far = near * 0.5 + 0.25 * (
+        texture(u_scene, v_uv - direction * 0.5).rgb +
+        texture(u_scene, v_uv + direction * 0.5).rgb
+    );
+    // The wider blend overshoots when it crossed into another edge
+    float luma_far = luma(far);
+    bool overshot = luma_far < luma_min || luma_far > luma_max;
+    frag_color = vec4(overshot ? near : far, 1.0);
+}
@@ -0,0 +1,14 @@
+#version 410 core
+
+in vec2 v_uv;
+
+uniform sampler2D u_scene;
+// Above 1 brightens the dark parts, below 1 darkens them
+uniform float u_gamma;
+
+out vec4 frag_color;
+
+void main() {
+    vec3 color = texture(u_scene, v_uv).rgb;
+    frag_color = vec4(pow(color, vec3(1.0 / u_gamma)), 1.0);
+}
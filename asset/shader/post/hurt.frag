@@ -0,0 +1,23 @@
+#version 410 core
+
+in vec2 v_uv;
+
+uniform sampler2D u_scene;
+// From 1 right after a hit down to 0
+uniform float u_strength;
+
+out vec4 frag_color;
+
+const vec3 RED = vec3(0.8, 0.05, 0.05);
+// How far toward red the edges go right after a hit
+const float MAX_TINT = 0.6;
+// The middle of the screen goes this much of the way the edges do
+const float CENTER = 0.25;
+
+void main() {
+    vec3 color = texture(u_scene, v_uv).rgb;
+    // 1 in the corners
+    float edge = length(v_uv - 0.5) * 1.4142;
+    float amount = u_strength * MAX_TINT * mix(CENTER, 1.0, smoothstep(0.2, 1.0, edge));
+    frag_color = vec4(mix(color, RED, amount), 1.0);
+}
@@ -0,0 +1,25 @@
+#version 410 core
+
+in vec2 v_uv;
+
+uniform sampler2D u_scene;
+// Seconds, for the swaying
+uniform float u_time;
+// From 0 to 1
+uniform float u_strength;
+
+out vec4 frag_color;
+
+void main() {
+    // Squash and stretch around the center while slowly rocking side to side
+    vec2 centered = v_uv - 0.5;
+    float sway = sin(u_time * 1.3) * u_strength;
+    float angle = sin(u_time * 0.7) * 0.06 * u_strength;
+    centered *= vec2(1.0 + 0.08 * sway, 1.0 - 0.08 * sway);
+    centered = mat2(cos(angle), sin(angle), -sin(angle), cos(angle)) * centered;
+    vec2 uv = clamp(centered + 0.5, 0.0, 1.0);
+    // A second, drifting copy for the double vision
+    vec2 ghost = clamp(uv + vec2(sin(u_time * 2.3), cos(u_time * 1.9)) * 0.01 * u_strength, 0.0, 1.0);
+    vec3 color = mix(texture(u_scene, uv).rgb, texture(u_scene, ghost).rgb, 0.35 * u_strength);
+    frag_color = vec4(color, 1.0);
+}
@@ -0,0 +1,12 @@
+// Vertex stage of every post-processing pass, included after the #version line
+
+// Screen position, the triangle overhangs the screen so it covers every pixel
+layout(location = 0) in vec2 a_position;
+
+// From 0 to 1 across the screen, for sampling the previous pass
+out vec2 v_uv;
+
+void main() {
+    v_uv = a_position * 0.5 + 0.5;
+    gl_Position = vec4(a_position, 0.0, 1.0);
+}
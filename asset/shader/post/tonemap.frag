@@ -0,0 +1,18 @@
+#version 410 core
+
+in vec2 v_uv;
+
+uniform sampler2D u_scene;
+uniform float u_exposure;
+
+out vec4 frag_color;
+
+// Below this colors pass through unchanged, above it they roll off smoothly toward 1
+const float KNEE = 0.8;
+
+void main() {
+    vec3 color = texture(u_scene, v_uv).rgb * u_exposure;
+    vec3 over = max(color - KNEE, 0.0);
+    vec3 mapped = min(color, KNEE) + (1.0 - KNEE) * over / (over + (1.0 - KNEE));
+    frag_color = vec4(mapped, 1.0);
+}
@@ -0,0 +1,30 @@
+#version 410 core
+
+in vec2 v_uv;
+
+uniform sampler2D u_scene;
+// Seconds, for moving the ripples
+uniform float u_time;
+// How far the picture wobbles and how much it's tinted, from 0 to 1
+uniform float u_strength;
+// Color of the fluid the camera is in
+uniform vec3 u_tint;
+
+out vec4 frag_color;
+
+// Screen fraction the picture moves at full strength
+const float AMPLITUDE = 0.004;
+// How far toward the fluid's color everything is tinted at full strength
+const float TINT = 0.5;
+
+void main() {
+    vec2 ripple = vec2(
+        sin(v_uv.y * 23.0 + u_time * 2.1),
+        cos(v_uv.x * 19.0 + u_time * 1.7)
+    );
+    vec2 uv = clamp(v_uv + ripple * AMPLITUDE * u_strength, 0.0, 1.0);
+    // Scaled to full brightness so the tint shifts the hue without darkening everything
+    vec3 tint = u_tint / max(max(u_tint.r, u_tint.g), max(u_tint.b, 0.001));
+    vec3 color = texture(u_scene, uv).rgb * mix(vec3(1.0), tint, TINT * u_strength);
+    frag_color = vec4(color, 1.0);
+}
@@ -0,0 +1,3 @@
+#version 410 core
+
+#include "screen.glsl"
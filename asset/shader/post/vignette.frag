@@ -0,0 +1,16 @@
+#version 410 core
+
+in vec2 v_uv;
+
+uniform sampler2D u_scene;
+// How dark the corners get, from 0 to 1
+uniform float u_strength;
+
+out vec4 frag_color;
+
+void main() {
+    vec3 color = texture(u_scene, v_uv).rgb;
+    // 1 in the corners
+    float edge = length(v_uv - 0.5) * 1.4142;
+    frag_color = vec4(color * (1.0 - u_strength * smoothstep(0.4, 1.0, edge)), 1.0);
+}
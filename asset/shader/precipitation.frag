@@ -0,0 +1,17 @@
+#version 410 core
+
+in vec2 v_corner;
+in float v_fade;
+
+uniform vec4 u_color;
+// Non-zero cuts the particle to a disc
+uniform int u_round;
+
+out vec4 frag_color;
+
+void main() {
+    if (v_fade <= 0.0 || (u_round != 0 && length(v_corner) > 1.0)) {
+        discard;
+    }
+    frag_color = vec4(u_color.rgb, u_color.a * v_fade);
+}
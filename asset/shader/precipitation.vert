@@ -0,0 +1,43 @@
+#version 410 core
+
+// From -1 to 1 across the particle
+layout(location = 0) in vec2 a_corner;
+// Start position in the box, and a threshold against the strength
+layout(location = 1) in vec4 a_seed;
+
+uniform mat4 u_view_projection;
+uniform vec3 u_camera_position;
+uniform vec3 u_box_size;
+// Seconds, wraps now and then
+uniform float u_time;
+uniform float u_fall_speed;
+uniform float u_sway;
+// Half extents of each particle
+uniform vec2 u_size;
+uniform float u_strength;
+
+out vec2 v_corner;
+out float v_fade;
+
+void main() {
+    v_corner = a_corner;
+    // Each particle falls at a slightly different speed so they don't move as a sheet
+    vec3 offset = a_seed.xyz * u_box_size;
+    offset.y -= u_time * u_fall_speed * (0.8 + 0.4 * a_seed.w);
+    offset.x += sin(u_time + a_seed.z * 6.2832) * u_sway;
+    offset.z += cos(u_time * 0.7 + a_seed.x * 6.2832) * u_sway;
+    // Wrapped into the box around the camera, in world space so walking doesn't drag them
+    vec3 low = u_camera_position - 0.5 * u_box_size;
+    vec3 center = low + mod(offset - low, u_box_size);
+
+    // Turned to face the camera around the vertical axis, streaks stay upright
+    vec3 toward_camera = u_camera_position - center;
+    vec3 right = normalize(cross(vec3(0.0, 1.0, 0.0), toward_camera + vec3(0.0001, 0.0, 0.0)));
+    vec3 position = center + right * a_corner.x * u_size.x + vec3(0.0, a_corner.y * u_size.y, 0.0);
+
+    // Fewer particles at lower strengths, and none right at the box's edges where they wrap
+    vec3 edge = abs(center - u_camera_position) / (0.5 * u_box_size);
+    float visible = step(a_seed.w, u_strength);
+    v_fade = visible * (1.0 - smoothstep(0.7, 1.0, max(edge.x, max(edge.y, edge.z))));
+    gl_Position = u_view_projection * vec4(position, 1.0);
+}
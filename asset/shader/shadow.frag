@@ -0,0 +1,12 @@
+#version 410 core
+
+in vec3 v_uv;
+
+uniform sampler2DArray u_blocks;
+
+void main() {
+    // Light shines through the holes in cutout textures like leaves
+    if (texture(u_blocks, v_uv).a < 0.5) {
+        discard;
+    }
+}
@@ -0,0 +1,22 @@
+#version 410 core
+
+// Packed by BlockVertex in mesher.rs, only what the depth needs is unpacked
+layout(location = 0) in uint a_position_uv;
+layout(location = 1) in uint a_layer_light;
+layout(location = 2) in uint a_fraction;
+
+// The light's view-projection for the cascade being drawn
+uniform mat4 u_view_projection;
+uniform vec3 u_chunk_origin;
+
+out vec3 v_uv;
+
+void main() {
+    uint packed = a_position_uv;
+    vec3 position = vec3(packed & 31u, (packed >> 5) & 31u, (packed >> 10) & 31u);
+    vec2 uv = vec2((packed >> 15) & 31u, (packed >> 20) & 31u);
+    position += vec3(a_fraction & 15u, (a_fraction >> 4) & 15u, (a_fraction >> 8) & 15u) / 16.0;
+    uv += vec2((a_fraction >> 12) & 15u, (a_fraction >> 16) & 15u) / 16.0;
+    v_uv = vec3(uv, float(a_layer_light & 0xFFFFu));
+    gl_Position = u_view_projection * vec4(u_chunk_origin + position, 1.0);
+}
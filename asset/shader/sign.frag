@@ -0,0 +1,17 @@
+#version 410 core
+
+in vec2 v_uv;
+in vec4 v_color;
+
+// The text renderer's glyph atlas, coverage in the red channel
+uniform sampler2D u_atlas;
+
+out vec4 frag_color;
+
+void main() {
+    float alpha = v_color.a * texture(u_atlas, v_uv).r;
+    if (alpha <= 0.0) {
+        discard;
+    }
+    frag_color = vec4(v_color.rgb, alpha);
+}
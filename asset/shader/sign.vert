@@ -0,0 +1,17 @@
+#version 410 core
+
+// SignVertex in signs.rs, already laid out on the board
+layout(location = 0) in vec3 a_position;
+layout(location = 1) in vec2 a_uv;
+layout(location = 2) in vec4 a_color;
+
+uniform mat4 u_view_projection;
+
+out vec2 v_uv;
+out vec4 v_color;
+
+void main() {
+    v_uv = a_uv;
+    v_color = a_color;
+    gl_Position = u_view_projection * vec4(a_position, 1.0);
+}
@@ -0,0 +1,40 @@
+#version 410 core
+
+in vec3 v_direction;
+
+uniform vec3 u_zenith_color;
+uniform vec3 u_horizon_color;
+uniform vec3 u_sunset_color;
+uniform vec3 u_sun_direction;
+// Strength of the sunrise and sunset glow, 0 while the sun is high or well below the horizon
+uniform float u_sunset;
+// Star visibility, 0 during the day
+uniform float u_stars;
+
+out vec4 frag_color;
+
+// Cells per unit of direction, the higher the smaller the stars
+const float STAR_GRID = 300.0;
+// Fraction of cells with a star
+const float STAR_DENSITY = 0.003;
+
+float hash(vec3 cell) {
+    return fract(sin(dot(cell, vec3(12.9898, 78.233, 37.719))) * 43758.5453);
+}
+
+void main() {
+    vec3 direction = normalize(v_direction);
+    // Below the horizon stays at the horizon color, it's hidden by the world or fog anyway
+    float height = max(direction.y, 0.0);
+    vec3 color = mix(u_horizon_color, u_zenith_color, pow(height, 0.5));
+
+    // The glow hugs the horizon and is strongest toward the sun, which rises and sets along X
+    float toward_sun = max(direction.x * sign(u_sun_direction.x), 0.0);
+    float glow = u_sunset * pow(toward_sun, 3.0) * pow(1.0 - height, 4.0);
+    color = mix(color, u_sunset_color, glow * 0.8);
+
+    float star = step(1.0 - STAR_DENSITY, hash(floor(direction * STAR_GRID)));
+    // Stars fade out toward the horizon where the haze is
+    color += vec3(star * u_stars * smoothstep(0.0, 0.2, direction.y));
+    frag_color = vec4(color, 1.0);
+}
@@ -0,0 +1,15 @@
+#version 410 core
+
+// Screen position, the triangle overhangs the screen so it covers every pixel
+layout(location = 0) in vec2 a_position;
+
+// Without the camera's translation, so unprojecting gives a direction
+uniform mat4 u_inverse_view_projection;
+
+out vec3 v_direction;
+
+void main() {
+    vec4 far = u_inverse_view_projection * vec4(a_position, 1.0, 1.0);
+    v_direction = far.xyz / far.w;
+    gl_Position = vec4(a_position, 0.0, 1.0);
+}
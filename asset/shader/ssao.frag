@@ -0,0 +1,91 @@
+#version 410 core
+
+// From -1 to 1 across the screen
+in vec2 v_screen;
+
+// Written by the chunk pass, see chunk.frag
+uniform sampler2D u_normal;
+uniform sampler2D u_depth;
+uniform mat4 u_view_projection;
+uniform mat4 u_inverse_view_projection;
+uniform vec3 u_camera_position;
+// Points tested around each pixel, up to MAX_SAMPLES
+uniform int u_samples;
+// Blocks around the pixel that can occlude it
+uniform float u_radius;
+
+// 1 for open, lower the more the pixel is boxed in
+out float frag_occlusion;
+
+const int MAX_SAMPLES = 32;
+// How dark a fully boxed in pixel gets
+const float STRENGTH = 0.8;
+// Blocks a surface has to be in front of a point to hide it, so flat faces don't occlude
+// themselves
+const float BIAS = 0.03;
+const float GOLDEN_ANGLE = 2.3999632;
+const float TAU = 6.2831853;
+
+// Read straight from the G-buffer, which can be bigger than this pass's target
+ivec2 gbuffer_pixel(vec2 uv) {
+    vec2 size = vec2(textureSize(u_depth, 0));
+    return clamp(ivec2(uv * size), ivec2(0), ivec2(size) - 1);
+}
+
+vec3 world_position(vec2 uv) {
+    float depth = texelFetch(u_depth, gbuffer_pixel(uv), 0).r;
+    vec4 world = u_inverse_view_projection * vec4(uv * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    return world.xyz / world.w;
+}
+
+// Interleaved gradient noise, turns the samples differently for neighbouring pixels so the
+// resolve's blur can even the result out
+float noise(vec2 pixel) {
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+void main() {
+    vec2 uv = v_screen * 0.5 + 0.5;
+    ivec2 pixel = gbuffer_pixel(uv);
+    // Nothing drawn here
+    if (texelFetch(u_depth, pixel, 0).r >= 1.0) {
+        frag_occlusion = 1.0;
+        return;
+    }
+    vec3 position = world_position(uv);
+    vec3 normal = normalize(texelFetch(u_normal, pixel, 0).xyz * 2.0 - 1.0);
+    vec3 helper = abs(normal.y) < 0.99 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    vec3 tangent = normalize(cross(helper, normal));
+    vec3 bitangent = cross(normal, tangent);
+    float rotation = noise(gl_FragCoord.xy) * TAU;
+    float distance_here = distance(position, u_camera_position);
+
+    float occluded = 0.0;
+    for (int i = 0; i < MAX_SAMPLES; i++) {
+        if (i >= u_samples) {
+            break;
+        }
+        // Spiral over the hemisphere above the face, the first points close to the normal and
+        // close to the pixel where occlusion matters most
+        float t = (float(i) + 0.5) / float(u_samples);
+        float angle = float(i) * GOLDEN_ANGLE + rotation;
+        float sin_theta = sqrt(t);
+        vec3 direction = (tangent * cos(angle) + bitangent * sin(angle)) * sin_theta
+            + normal * sqrt(1.0 - t);
+        vec3 point = position + direction * u_radius * mix(0.1, 1.0, t * t);
+
+        vec4 clip = u_view_projection * vec4(point, 1.0);
+        vec2 point_uv = clip.xy / clip.w * 0.5 + 0.5;
+        if (any(lessThan(point_uv, vec2(0.0))) || any(greaterThan(point_uv, vec2(1.0)))) {
+            continue;
+        }
+        float surface = distance(world_position(point_uv), u_camera_position);
+        // Surfaces far in front don't count, a pillar near the camera doesn't darken the
+        // ground behind it
+        float range = smoothstep(0.0, 1.0, u_radius / max(abs(distance_here - surface), 0.0001));
+        if (surface < distance(point, u_camera_position) - BIAS) {
+            occluded += range;
+        }
+    }
+    frag_occlusion = 1.0 - STRENGTH * occluded / float(max(u_samples, 1));
+}
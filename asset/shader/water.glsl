@@ -0,0 +1,82 @@
+// Fancy water surface for the translucent chunk pass, included after u_camera_position and
+// u_fog_color are declared. Filled in by WaterSurface in water.rs.
+
+uniform bool u_fancy_water;
+// Seconds, for the waves
+uniform float u_time;
+// Everything opaque as it was before the translucent pass
+uniform sampler2D u_reflection_color;
+uniform sampler2D u_reflection_depth;
+uniform mat4 u_view_projection;
+// Straight up, reflected with the horizon's fog color where the screen has nothing to offer
+uniform vec3 u_sky_color;
+
+// Direction, wavelength in blocks, height and speed of each wave the surface is made of
+const vec4 WAVES[4] = vec4[](
+    vec4(1.0, 0.3, 6.0, 0.05),
+    vec4(-0.4, 1.0, 3.7, 0.04),
+    vec4(0.7, -0.8, 2.1, 0.025),
+    vec4(-0.9, -0.2, 1.3, 0.015)
+);
+const float WAVE_SPEED = 1.2;
+// Reflectance looking straight down, water reflects more and more toward grazing angles
+const float BASE_REFLECTANCE = 0.02;
+const int REFLECTION_STEPS = 24;
+// Length of the first step in blocks, each one after is longer
+const float REFLECTION_STEP = 0.5;
+const float REFLECTION_STEP_GROWTH = 1.15;
+
+// Normal of the rippled surface at `position` on it, from the slopes of a few sine waves
+vec3 wave_normal(vec2 position) {
+    vec2 slope = vec2(0.0);
+    for (int i = 0; i < 4; i++) {
+        vec2 direction = normalize(WAVES[i].xy);
+        float frequency = 6.2831853 / WAVES[i].z;
+        float phase = dot(direction, position) * frequency + u_time * WAVE_SPEED * frequency;
+        slope += direction * WAVES[i].w * frequency * cos(phase);
+    }
+    return normalize(vec3(-slope.x, 1.0, -slope.y));
+}
+
+vec3 sky_reflection(vec3 direction) {
+    return mix(u_fog_color, u_sky_color, sqrt(clamp(direction.y, 0.0, 1.0)));
+}
+
+// Follow the reflected ray across the screen until it passes behind something drawn, the
+// sky if it never does or leaves the screen first
+vec3 reflection(vec3 origin, vec3 direction) {
+    float travelled = 0.0;
+    float step = REFLECTION_STEP;
+    for (int i = 0; i < REFLECTION_STEPS; i++) {
+        travelled += step;
+        step *= REFLECTION_STEP_GROWTH;
+        vec4 clip = u_view_projection * vec4(origin + direction * travelled, 1.0);
+        if (clip.w <= 0.0) {
+            break;
+        }
+        vec3 ndc = clip.xyz / clip.w;
+        vec2 uv = ndc.xy * 0.5 + 0.5;
+        if (any(lessThan(uv, vec2(0.0))) || any(greaterThan(uv, vec2(1.0)))) {
+            break;
+        }
+        float scene_depth = texture(u_reflection_depth, uv).r;
+        if (scene_depth < 1.0 && ndc.z * 0.5 + 0.5 > scene_depth) {
+            // Blend into the sky toward the edges of the screen, where hits suddenly stop
+            vec2 edge = min(uv, 1.0 - uv);
+            float fade = smoothstep(0.0, 0.1, min(edge.x, edge.y));
+            vec3 hit = texture(u_reflection_color, uv).rgb;
+            return mix(sky_reflection(direction), hit, fade);
+        }
+    }
+    return sky_reflection(direction);
+}
+
+// `lit` and `alpha` as the surface would be drawn without fancy water
+vec4 water_surface(vec3 lit, float alpha, vec3 world_position) {
+    vec3 view = normalize(world_position - u_camera_position);
+    vec3 normal = wave_normal(world_position.xz);
+    float facing = max(dot(-view, normal), 0.0);
+    float fresnel = BASE_REFLECTANCE + (1.0 - BASE_REFLECTANCE) * pow(1.0 - facing, 5.0);
+    vec3 reflected = reflection(world_position, reflect(view, normal));
+    return vec4(mix(lit, reflected, fresnel), mix(alpha, 1.0, fresnel));
+}
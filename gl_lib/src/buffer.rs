@@ -0,0 +1,215 @@
+use gl::types::*;
+use std::mem;
+use std::ops::Drop;
+
+use crate::stats::count_upload;
+use crate::{Attribute, AttributeKind};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BufferTarget {
+    Array,
+    ElementArray,
+    Uniform,
+}
+
+impl BufferTarget {
+    fn to_opengl(self) -> GLenum {
+        match self {
+            Self::Array => gl::ARRAY_BUFFER,
+            Self::ElementArray => gl::ELEMENT_ARRAY_BUFFER,
+            Self::Uniform => gl::UNIFORM_BUFFER,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BufferUsage {
+    Static,
+    Dynamic,
+    Stream,
+}
+
+impl BufferUsage {
+    fn to_opengl(self) -> GLenum {
+        match self {
+            Self::Static => gl::STATIC_DRAW,
+            Self::Dynamic => gl::DYNAMIC_DRAW,
+            Self::Stream => gl::STREAM_DRAW,
+        }
+    }
+}
+
+pub struct Buffer {
+    id: u32,
+    target: BufferTarget,
+}
+
+impl Buffer {
+    pub fn new(target: BufferTarget) -> Self {
+        let mut id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+        }
+        Buffer { id, target }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindBuffer(self.target.to_opengl(), self.id);
+        }
+    }
+
+    /// Binds the buffer and replaces its whole content
+    pub fn set_data<T: Copy>(&self, data: &[T], usage: BufferUsage) {
+        self.bind();
+        unsafe {
+            gl::BufferData(
+                self.target.to_opengl(),
+                mem::size_of_val(data) as GLsizeiptr,
+                data.as_ptr() as *const _,
+                usage.to_opengl(),
+            );
+        }
+        count_upload(mem::size_of_val(data));
+    }
+
+    /// Binds the buffer and gives it `bytes` of undefined content, for filling in pieces
+    /// with [`Buffer::set_sub_data`]
+    pub fn allocate(&self, bytes: usize, usage: BufferUsage) {
+        self.bind();
+        unsafe {
+            gl::BufferData(
+                self.target.to_opengl(),
+                bytes as GLsizeiptr,
+                std::ptr::null(),
+                usage.to_opengl(),
+            );
+        }
+    }
+
+    /// Binds the buffer and overwrites part of it, `offset` is in bytes
+    pub fn set_sub_data<T: Copy>(&self, offset: usize, data: &[T]) {
+        self.bind();
+        unsafe {
+            gl::BufferSubData(
+                self.target.to_opengl(),
+                offset as GLintptr,
+                mem::size_of_val(data) as GLsizeiptr,
+                data.as_ptr() as *const _,
+            );
+        }
+        count_upload(mem::size_of_val(data));
+    }
+
+    /// Attach the whole buffer to the indexed binding point `index` of its target, where
+    /// uniform blocks bound to the same index read from it
+    pub fn bind_base(&self, index: u32) {
+        unsafe {
+            gl::BindBufferBase(self.target.to_opengl(), index, self.id);
+        }
+    }
+
+    pub fn target(&self) -> BufferTarget {
+        self.target
+    }
+
+    /// # Safety
+    /// Do not delete the buffer, it will automatically get deleted when it's dropped
+    pub unsafe fn get_id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
+
+pub struct VertexArray {
+    id: u32,
+}
+
+impl VertexArray {
+    pub fn new() -> Self {
+        let mut id = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut id);
+        }
+        VertexArray { id }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindVertexArray(self.id);
+        }
+    }
+
+    /// Describe a float attribute sourced from the currently bound ARRAY_BUFFER,
+    /// stride and offset are in bytes
+    pub fn attrib_f32(&self, index: u32, components: i32, stride: usize, offset: usize) {
+        self.bind();
+        unsafe {
+            gl::VertexAttribPointer(
+                index,
+                components,
+                gl::FLOAT,
+                gl::FALSE,
+                stride as GLsizei,
+                offset as *const _,
+            );
+            gl::EnableVertexAttribArray(index);
+        }
+    }
+
+    /// Describe every attribute of an interleaved vertex `stride` bytes long, sourced from
+    /// the currently bound ARRAY_BUFFER. Locations follow the slice order.
+    pub fn set_attributes(&self, attributes: &[Attribute], stride: usize) {
+        for (index, attribute) in attributes.iter().enumerate() {
+            let (index, components, offset) =
+                (index as u32, attribute.components, attribute.offset);
+            match attribute.kind {
+                AttributeKind::Float => self.attrib_f32(index, components, stride, offset),
+                AttributeKind::UnsignedInt => self.attrib_u32(index, components, stride, offset),
+            }
+        }
+    }
+
+    /// Like [`VertexArray::attrib_f32`] for unsigned integers, which reach the shader
+    /// unconverted
+    pub fn attrib_u32(&self, index: u32, components: i32, stride: usize, offset: usize) {
+        self.bind();
+        unsafe {
+            gl::VertexAttribIPointer(
+                index,
+                components,
+                gl::UNSIGNED_INT,
+                stride as GLsizei,
+                offset as *const _,
+            );
+            gl::EnableVertexAttribArray(index);
+        }
+    }
+
+    /// # Safety
+    /// Do not delete the vertex array, it will automatically get deleted when it's dropped
+    pub unsafe fn get_id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Default for VertexArray {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for VertexArray {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.id);
+        }
+    }
+}
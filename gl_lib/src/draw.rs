@@ -0,0 +1,60 @@
+use gl::types::*;
+use std::ptr;
+
+use crate::stats::count_draw;
+
+/// Draw `count` vertices of the bound vertex array as triangles, starting at `first`
+pub fn draw_triangles(first: usize, count: usize) {
+    unsafe {
+        gl::DrawArrays(gl::TRIANGLES, first as GLint, count as GLsizei);
+    }
+    count_draw(count / 3);
+}
+
+/// Draw triangles from `count` u32 indices of the bound element buffer starting at index
+/// `first`, with `base_vertex` added to each index. Lets many meshes share one buffer.
+pub fn draw_indexed_triangles_range(count: usize, first: usize, base_vertex: usize) {
+    unsafe {
+        gl::DrawElementsBaseVertex(
+            gl::TRIANGLES,
+            count as GLsizei,
+            gl::UNSIGNED_INT,
+            (first * std::mem::size_of::<u32>()) as *const _,
+            base_vertex as GLint,
+        );
+    }
+    count_draw(count / 3);
+}
+
+/// Draw triangles from the first `count` u32 indices of the bound element buffer
+pub fn draw_indexed_triangles(count: usize) {
+    unsafe {
+        gl::DrawElements(
+            gl::TRIANGLES,
+            count as GLsizei,
+            gl::UNSIGNED_INT,
+            ptr::null(),
+        );
+    }
+    count_draw(count / 3);
+}
+
+/// Draw lines from the first `count` u32 indices of the bound element buffer, each pair of
+/// indices is one line
+pub fn draw_indexed_lines(count: usize) {
+    unsafe {
+        gl::DrawElements(gl::LINES, count as GLsizei, gl::UNSIGNED_INT, ptr::null());
+    }
+    count_draw(0);
+}
+
+/// Rasterize triangles as their outlines until it's turned off again, for seeing how a mesh
+/// is built
+pub fn set_wireframe(enabled: bool) {
+    unsafe {
+        gl::PolygonMode(
+            gl::FRONT_AND_BACK,
+            if enabled { gl::LINE } else { gl::FILL },
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use gl::types::*;
+use std::error;
+use std::fmt;
+
+use crate::ShaderType;
+
+#[derive(Debug)]
+pub enum Error {
+    /// OpenGL refused to hand out a new object name
+    CreateObject(&'static str),
+    Compile {
+        shader_type: ShaderType,
+        log: Option<String>,
+    },
+    Link {
+        log: Option<String>,
+    },
+    /// Pixel data length doesn't match the requested dimensions
+    TextureSize {
+        width: u32,
+        height: u32,
+        expected: usize,
+        len: usize,
+    },
+    /// Status returned by glCheckFramebufferStatus
+    FramebufferIncomplete(GLenum),
+    /// Anything reported by glGetError
+    Gl(GLenum),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::CreateObject(kind) => write!(f, "Unable to create {kind} object"),
+            Self::Compile {
+                shader_type,
+                log: Some(log),
+            } => write!(f, "Failed to compile {shader_type}: {}", log.trim_end()),
+            Self::Compile {
+                shader_type,
+                log: None,
+            } => write!(f, "Failed to compile {shader_type}, no info log available"),
+            Self::Link { log: Some(log) } => {
+                write!(f, "Failed to link shader program: {}", log.trim_end())
+            }
+            Self::Link { log: None } => {
+                write!(f, "Failed to link shader program, no info log available")
+            }
+            Self::TextureSize {
+                width,
+                height,
+                expected,
+                len,
+            } => write!(
+                f,
+                "Texture of {width}x{height} needs {expected} bytes, got {len}"
+            ),
+            Self::FramebufferIncomplete(status) => {
+                write!(f, "Framebuffer is incomplete, status 0x{status:04X}")
+            }
+            Self::Gl(code) => write!(f, "OpenGL error 0x{code:04X}"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// Turn the oldest pending glGetError into an [`Error`]
+pub fn check_error() -> Result<(), Error> {
+    let code = unsafe { gl::GetError() };
+    if code == gl::NO_ERROR {
+        Ok(())
+    } else {
+        Err(Error::Gl(code))
+    }
+}
@@ -0,0 +1,498 @@
+use gl::types::*;
+use std::ops::Drop;
+
+use crate::stats::count_texture_bind;
+use crate::{check_error, Error, Texture2D, TextureFormat};
+
+/// Which framebuffer draws go to, to get back to it after drawing somewhere else or to blit
+/// into it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FramebufferBinding(u32);
+
+impl FramebufferBinding {
+    /// The window's own framebuffer
+    pub const DEFAULT: FramebufferBinding = FramebufferBinding(0);
+
+    /// Whatever is bound for drawing right now
+    pub fn current() -> Self {
+        let mut id = 0;
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut id);
+        }
+        FramebufferBinding(id as u32)
+    }
+
+    pub fn bind(self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.0);
+        }
+    }
+}
+
+/// Copy `mask` of the whole `width` by `height` area from one framebuffer to another, then
+/// bind the destination
+fn blit(from: u32, to: FramebufferBinding, width: u32, height: u32, mask: GLbitfield) {
+    unsafe {
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, from);
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, to.0);
+        gl::BlitFramebuffer(
+            0,
+            0,
+            width as GLint,
+            height as GLint,
+            0,
+            0,
+            width as GLint,
+            height as GLint,
+            mask,
+            gl::NEAREST,
+        );
+    }
+    to.bind();
+}
+
+/// Offscreen render target backed by renderbuffers, used for multisampling the scene before
+/// resolving it for post-processing
+pub struct Framebuffer {
+    id: u32,
+    color: u32,
+    depth: u32,
+    width: u32,
+    height: u32,
+    samples: u32,
+}
+
+impl Framebuffer {
+    /// RGBA16F color plus 24 bit depth / 8 bit stencil, `samples` of 0 means no multisampling.
+    /// The color format matches [`TextureFormat::Rgba16f`] so it can be resolved into one.
+    pub fn new(width: u32, height: u32, samples: u32) -> Result<Self, Error> {
+        unsafe {
+            gl::GetError();
+
+            let mut id = 0;
+            gl::GenFramebuffers(1, &mut id);
+            if id == 0 {
+                return Err(Error::CreateObject("framebuffer"));
+            }
+            let mut renderbuffers = [0; 2];
+            gl::GenRenderbuffers(2, renderbuffers.as_mut_ptr());
+            let framebuffer = Framebuffer {
+                id,
+                color: renderbuffers[0],
+                depth: renderbuffers[1],
+                width,
+                height,
+                samples,
+            };
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, id);
+            for (renderbuffer, format, attachment) in [
+                (framebuffer.color, gl::RGBA16F, gl::COLOR_ATTACHMENT0),
+                (
+                    framebuffer.depth,
+                    gl::DEPTH24_STENCIL8,
+                    gl::DEPTH_STENCIL_ATTACHMENT,
+                ),
+            ] {
+                gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+                gl::RenderbufferStorageMultisample(
+                    gl::RENDERBUFFER,
+                    samples as GLsizei,
+                    format,
+                    width as GLsizei,
+                    height as GLsizei,
+                );
+                gl::FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER,
+                    attachment,
+                    gl::RENDERBUFFER,
+                    renderbuffer,
+                );
+            }
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(Error::FramebufferIncomplete(status));
+            }
+            check_error()?;
+            Ok(framebuffer)
+        }
+    }
+
+    /// Make this the target of every following draw
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+        }
+    }
+
+    /// Resolve the color and depth buffers into `target`, which has to be the same size and
+    /// color format, and bind that
+    pub fn resolve_to(&self, target: FramebufferBinding) {
+        blit(
+            self.id,
+            target,
+            self.width,
+            self.height,
+            gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT,
+        );
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// # Safety
+    /// Do not delete the framebuffer, it will automatically get deleted when it's dropped
+    pub unsafe fn get_id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.id);
+            let renderbuffers = [self.color, self.depth];
+            gl::DeleteRenderbuffers(2, renderbuffers.as_ptr());
+        }
+    }
+}
+
+/// Render target whose attachments are textures, for passes that read back what an earlier
+/// pass drew. Every color attachment is drawn to at once, in order, plus optionally a
+/// depth/stencil one.
+pub struct TextureFramebuffer {
+    id: u32,
+    colors: Vec<Texture2D>,
+    depth: Option<Texture2D>,
+    width: u32,
+    height: u32,
+}
+
+impl TextureFramebuffer {
+    pub fn new(
+        width: u32,
+        height: u32,
+        colors: &[TextureFormat],
+        depth: bool,
+    ) -> Result<Self, Error> {
+        let colors = colors
+            .iter()
+            .map(|format| Texture2D::empty(width, height, *format))
+            .collect::<Result<Vec<_>, _>>()?;
+        let depth = match depth {
+            true => Some(Texture2D::empty(
+                width,
+                height,
+                TextureFormat::Depth24Stencil8,
+            )?),
+            false => None,
+        };
+        unsafe {
+            gl::GetError();
+
+            let mut id = 0;
+            gl::GenFramebuffers(1, &mut id);
+            if id == 0 {
+                return Err(Error::CreateObject("framebuffer"));
+            }
+            let framebuffer = TextureFramebuffer {
+                id,
+                colors,
+                depth,
+                width,
+                height,
+            };
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, id);
+            for (index, texture) in framebuffer.colors.iter().enumerate() {
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0 + index as GLenum,
+                    gl::TEXTURE_2D,
+                    texture.get_id(),
+                    0,
+                );
+            }
+            if let Some(depth) = &framebuffer.depth {
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::DEPTH_STENCIL_ATTACHMENT,
+                    gl::TEXTURE_2D,
+                    depth.get_id(),
+                    0,
+                );
+            }
+            framebuffer.set_draw_buffers();
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(Error::FramebufferIncomplete(status));
+            }
+            check_error()?;
+            Ok(framebuffer)
+        }
+    }
+
+    /// Make this the target of every following draw
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+        }
+    }
+
+    /// Copy the first color attachment into `target` and bind that
+    pub fn blit_color_to(&self, target: FramebufferBinding) {
+        blit(
+            self.id,
+            target,
+            self.width,
+            self.height,
+            gl::COLOR_BUFFER_BIT,
+        );
+    }
+
+    /// Copy the depth buffer into `target` and bind that, so whatever is drawn there
+    /// afterwards is still hidden behind this target's geometry
+    pub fn blit_depth_to(&self, target: FramebufferBinding) {
+        blit(
+            self.id,
+            target,
+            self.width,
+            self.height,
+            gl::DEPTH_BUFFER_BIT,
+        );
+    }
+
+    /// Copy the color and depth of `source`, which has to be the same size and formats, into
+    /// this target, then bind `source` again to keep drawing into it
+    pub fn copy_from(&self, source: FramebufferBinding) {
+        blit(
+            source.0,
+            self.binding(),
+            self.width,
+            self.height,
+            gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT,
+        );
+        source.bind();
+    }
+
+    /// For binding it again later or blitting into it
+    pub fn binding(&self) -> FramebufferBinding {
+        FramebufferBinding(self.id)
+    }
+
+    pub fn color(&self, index: usize) -> &Texture2D {
+        &self.colors[index]
+    }
+
+    pub fn depth(&self) -> Option<&Texture2D> {
+        self.depth.as_ref()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Framebuffer has to be bound
+    unsafe fn set_draw_buffers(&self) {
+        let buffers: Vec<GLenum> = (0..self.colors.len())
+            .map(|index| gl::COLOR_ATTACHMENT0 + index as GLenum)
+            .collect();
+        gl::DrawBuffers(buffers.len() as GLsizei, buffers.as_ptr());
+    }
+}
+
+impl Drop for TextureFramebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.id);
+        }
+    }
+}
+
+/// Square depth-only render target with one layer per view, sampled afterwards as a
+/// `sampler2DArrayShadow` with linear filtering for hardware depth comparison
+pub struct DepthArrayTarget {
+    id: u32,
+    texture: u32,
+    size: u32,
+    layers: u32,
+}
+
+impl DepthArrayTarget {
+    pub fn new(size: u32, layers: u32) -> Result<Self, Error> {
+        unsafe {
+            gl::GetError();
+
+            let mut id = 0;
+            gl::GenFramebuffers(1, &mut id);
+            if id == 0 {
+                return Err(Error::CreateObject("framebuffer"));
+            }
+            let mut texture = 0;
+            gl::GenTextures(1, &mut texture);
+            let target = DepthArrayTarget {
+                id,
+                texture,
+                size,
+                layers,
+            };
+            if texture == 0 {
+                return Err(Error::CreateObject("texture array"));
+            }
+
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, texture);
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                gl::DEPTH_COMPONENT24 as GLint,
+                size as GLsizei,
+                size as GLsizei,
+                layers as GLsizei,
+                0,
+                gl::DEPTH_COMPONENT,
+                gl::FLOAT,
+                std::ptr::null(),
+            );
+            for (parameter, value) in [
+                (gl::TEXTURE_MIN_FILTER, gl::LINEAR),
+                (gl::TEXTURE_MAG_FILTER, gl::LINEAR),
+                (gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER),
+                (gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER),
+                (gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE),
+                (gl::TEXTURE_COMPARE_FUNC, gl::LEQUAL),
+            ] {
+                gl::TexParameteri(gl::TEXTURE_2D_ARRAY, parameter, value as GLint);
+            }
+            // Outside the map counts as lit
+            let border = [1.0f32; 4];
+            gl::TexParameterfv(
+                gl::TEXTURE_2D_ARRAY,
+                gl::TEXTURE_BORDER_COLOR,
+                border.as_ptr(),
+            );
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, id);
+            gl::FramebufferTextureLayer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, texture, 0, 0);
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(Error::FramebufferIncomplete(status));
+            }
+            check_error()?;
+            Ok(target)
+        }
+    }
+
+    /// Draw into `layer` from now on, with the viewport covering it and its depth cleared
+    pub fn bind_layer(&self, layer: u32) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            gl::FramebufferTextureLayer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                self.texture,
+                0,
+                layer as GLint,
+            );
+            gl::Viewport(0, 0, self.size as GLsizei, self.size as GLsizei);
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+        }
+    }
+
+    pub fn bind_texture(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.texture);
+        }
+        count_texture_bind();
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+}
+
+impl Drop for DepthArrayTarget {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.id);
+            gl::DeleteTextures(1, &self.texture);
+        }
+    }
+}
+
+/// Go back to drawing into the window, over all of it
+pub fn bind_default_framebuffer(width: u32, height: u32) {
+    unsafe {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        gl::Viewport(0, 0, width as GLsizei, height as GLsizei);
+    }
+}
+
+/// Draw into `width` by `height` pixels from the bottom left corner of whatever is bound,
+/// for targets that aren't the window's size
+pub fn set_viewport(width: u32, height: u32) {
+    unsafe {
+        gl::Viewport(0, 0, width as GLsizei, height as GLsizei);
+    }
+}
+
+/// Draw into the part of whatever is bound that's `width` by `height` pixels from `x`, `y`
+/// counted from the bottom left, e.g. one cell of an atlas
+pub fn set_viewport_rect(x: u32, y: u32, width: u32, height: u32) {
+    unsafe {
+        gl::Viewport(
+            x as GLsizei,
+            y as GLsizei,
+            width as GLsizei,
+            height as GLsizei,
+        );
+    }
+}
+
+/// Read RGBA8 pixels back from the window's framebuffer, rows go bottom to top.
+/// Call it before swapping buffers, the back buffer is what gets read.
+pub fn read_default_framebuffer(width: u32, height: u32) -> Result<Vec<u8>, Error> {
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    unsafe {
+        gl::GetError();
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+        gl::ReadBuffer(gl::BACK);
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(
+            0,
+            0,
+            width as GLsizei,
+            height as GLsizei,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            pixels.as_mut_ptr() as *mut _,
+        );
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+    }
+    check_error()?;
+    Ok(pixels)
+}
@@ -0,0 +1,32 @@
+use gl::types::*;
+use std::ffi::CStr;
+
+/// Driver strings of the current context, mostly for bug reports
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContextInfo {
+    pub vendor: String,
+    pub renderer: String,
+    pub version: String,
+}
+
+impl ContextInfo {
+    pub fn query() -> Self {
+        ContextInfo {
+            vendor: get_string(gl::VENDOR),
+            renderer: get_string(gl::RENDERER),
+            version: get_string(gl::VERSION),
+        }
+    }
+}
+
+fn get_string(name: GLenum) -> String {
+    unsafe {
+        let string = gl::GetString(name);
+        if string.is_null() {
+            return String::from("unknown");
+        }
+        CStr::from_ptr(string as *const _)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
@@ -1,2 +1,22 @@
+mod buffer;
+mod draw;
+mod error;
+mod framebuffer;
+mod info;
+mod mesh;
+mod program;
+mod render_state;
 mod shader;
+mod stats;
+mod texture;
+pub use buffer::*;
+pub use draw::*;
+pub use error::*;
+pub use framebuffer::*;
+pub use info::*;
+pub use mesh::*;
+pub use program::*;
+pub use render_state::*;
 pub use shader::*;
+pub use stats::*;
+pub use texture::*;
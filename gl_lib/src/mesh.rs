@@ -0,0 +1,87 @@
+use std::mem;
+
+use crate::{
+    draw_indexed_lines, draw_indexed_triangles, Buffer, BufferTarget, BufferUsage, VertexArray,
+};
+
+/// How the shader sees an attribute's components
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AttributeKind {
+    Float,
+    /// Read as `uint` in the shader, for packed data
+    UnsignedInt,
+}
+
+/// One attribute of an interleaved vertex, attribute locations follow the slice order
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    pub components: i32,
+    /// Bytes from the start of the vertex
+    pub offset: usize,
+    pub kind: AttributeKind,
+}
+
+impl Attribute {
+    /// 32-bit floats
+    pub const fn float(components: i32, offset: usize) -> Self {
+        Attribute {
+            components,
+            offset,
+            kind: AttributeKind::Float,
+        }
+    }
+
+    /// 32-bit unsigned integers
+    pub const fn unsigned(components: i32, offset: usize) -> Self {
+        Attribute {
+            components,
+            offset,
+            kind: AttributeKind::UnsignedInt,
+        }
+    }
+}
+
+/// Indexed triangles, or lines, in their own vertex array, uploaded once
+pub struct Mesh {
+    vertex_array: VertexArray,
+    _vertex_buffer: Buffer,
+    _index_buffer: Buffer,
+    index_count: usize,
+}
+
+impl Mesh {
+    pub fn new<T: Copy>(vertices: &[T], indices: &[u32], attributes: &[Attribute]) -> Self {
+        let vertex_array = VertexArray::new();
+        vertex_array.bind();
+        let vertex_buffer = Buffer::new(BufferTarget::Array);
+        vertex_buffer.set_data(vertices, BufferUsage::Static);
+        // The element buffer binding is part of the vertex array's state
+        let index_buffer = Buffer::new(BufferTarget::ElementArray);
+        index_buffer.set_data(indices, BufferUsage::Static);
+
+        let stride = mem::size_of::<T>();
+        vertex_array.set_attributes(attributes, stride);
+
+        Mesh {
+            vertex_array,
+            _vertex_buffer: vertex_buffer,
+            _index_buffer: index_buffer,
+            index_count: indices.len(),
+        }
+    }
+
+    pub fn index_count(&self) -> usize {
+        self.index_count
+    }
+
+    pub fn draw(&self) {
+        self.vertex_array.bind();
+        draw_indexed_triangles(self.index_count);
+    }
+
+    /// For meshes built as pairs of line ends instead of triangles
+    pub fn draw_lines(&self) {
+        self.vertex_array.bind();
+        draw_indexed_lines(self.index_count);
+    }
+}
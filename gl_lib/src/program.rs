@@ -0,0 +1,153 @@
+use gl::types::*;
+use std::ffi::CStr;
+use std::ops::Drop;
+
+use crate::{check_error, Error, Shader};
+
+pub struct ShaderProgram {
+    id: u32,
+}
+
+impl ShaderProgram {
+    /// Link the given shaders into a program, the shaders can be dropped afterwards
+    pub fn new(shaders: &[&dyn Shader]) -> Result<Self, Error> {
+        unsafe {
+            gl::GetError();
+
+            let id = gl::CreateProgram();
+            if id == 0 {
+                return Err(Error::CreateObject("program"));
+            }
+            // Wrap it right away so every early return cleans up after itself
+            let program = ShaderProgram { id };
+
+            for shader in shaders {
+                gl::AttachShader(id, shader.get_id());
+            }
+            gl::LinkProgram(id);
+            for shader in shaders {
+                gl::DetachShader(id, shader.get_id());
+            }
+
+            let mut status = 0;
+            gl::GetProgramiv(id, gl::LINK_STATUS, &mut status as *mut GLint);
+            if status as GLboolean == gl::TRUE {
+                check_error()?;
+                return Ok(program);
+            }
+
+            let mut info_length = 0;
+            gl::GetProgramiv(id, gl::INFO_LOG_LENGTH, &mut info_length as *mut GLint);
+            let log = if info_length > 0 {
+                let mut info_log: Vec<u8> = vec![0; info_length as usize];
+                let mut written = 0;
+                gl::GetProgramInfoLog(
+                    id,
+                    info_length as GLsizei,
+                    &mut written as *mut GLsizei,
+                    info_log.as_mut_ptr() as *mut GLchar,
+                );
+                info_log.truncate(written.max(0) as usize);
+                Some(String::from_utf8_lossy(&info_log).into_owned())
+            } else {
+                None
+            };
+
+            check_error()?;
+            Err(Error::Link { log })
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::UseProgram(self.id);
+        }
+    }
+
+    /// Read the uniform block `name` from the buffer bound at `binding`, see
+    /// [`Buffer::bind_base`](crate::Buffer::bind_base). Blocks the program doesn't use are
+    /// skipped.
+    pub fn bind_uniform_block(&self, name: &CStr, binding: u32) {
+        unsafe {
+            let index = gl::GetUniformBlockIndex(self.id, name.as_ptr());
+            if index != gl::INVALID_INDEX {
+                gl::UniformBlockBinding(self.id, index, binding);
+            }
+        }
+    }
+
+    /// Returns -1 if the uniform doesn't exist or got optimized away
+    pub fn uniform_location(&self, name: &CStr) -> i32 {
+        unsafe { gl::GetUniformLocation(self.id, name.as_ptr()) }
+    }
+
+    pub fn set_int(&self, name: &CStr, value: i32) {
+        unsafe {
+            gl::ProgramUniform1i(self.id, self.uniform_location(name), value);
+        }
+    }
+
+    pub fn set_float(&self, name: &CStr, value: f32) {
+        unsafe {
+            gl::ProgramUniform1f(self.id, self.uniform_location(name), value);
+        }
+    }
+
+    pub fn set_vec2(&self, name: &CStr, value: [f32; 2]) {
+        unsafe {
+            gl::ProgramUniform2f(self.id, self.uniform_location(name), value[0], value[1]);
+        }
+    }
+
+    pub fn set_vec3(&self, name: &CStr, value: [f32; 3]) {
+        unsafe {
+            gl::ProgramUniform3f(
+                self.id,
+                self.uniform_location(name),
+                value[0],
+                value[1],
+                value[2],
+            );
+        }
+    }
+
+    pub fn set_vec4(&self, name: &CStr, value: [f32; 4]) {
+        unsafe {
+            gl::ProgramUniform4f(
+                self.id,
+                self.uniform_location(name),
+                value[0],
+                value[1],
+                value[2],
+                value[3],
+            );
+        }
+    }
+
+    /// Column major, which is what cgmath hands out
+    pub fn set_mat4(&self, name: &CStr, value: &[[f32; 4]; 4]) {
+        unsafe {
+            gl::ProgramUniformMatrix4fv(
+                self.id,
+                self.uniform_location(name),
+                1,
+                gl::FALSE,
+                value.as_ptr() as *const GLfloat,
+            );
+        }
+    }
+
+    /// # Safety
+    /// Do not delete the program, it will automatically get deleted when it's dropped
+    pub unsafe fn get_id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Drop for ShaderProgram {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.id);
+        }
+    }
+}
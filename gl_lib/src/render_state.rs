@@ -0,0 +1,105 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Classic `src * a + dst * (1 - a)`
+    Alpha,
+    Additive,
+    /// Inverts whatever is behind, readable on any background
+    Invert,
+    /// `2 * src * dst`, so mid gray leaves what's behind alone and darker shades darken it
+    Multiply,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CullMode {
+    None,
+    Back,
+    Front,
+}
+
+/// Fixed function state a pass depends on, applied as a whole so passes don't leak state
+/// into each other
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RenderState {
+    pub depth_test: bool,
+    pub depth_write: bool,
+    pub blend: Option<BlendMode>,
+    pub cull: CullMode,
+}
+
+impl RenderState {
+    /// Depth tested opaque geometry
+    pub const OPAQUE: RenderState = RenderState {
+        depth_test: true,
+        depth_write: true,
+        blend: None,
+        cull: CullMode::Back,
+    };
+
+    /// See-through geometry blended over what's already drawn, sorted back to front. Depth is
+    /// tested but not written so surfaces behind it still blend in, and both sides show.
+    pub const TRANSLUCENT: RenderState = RenderState {
+        depth_test: true,
+        depth_write: false,
+        blend: Some(BlendMode::Alpha),
+        cull: CullMode::None,
+    };
+
+    /// Screen space layers drawn on top of everything
+    pub const OVERLAY: RenderState = RenderState {
+        depth_test: false,
+        depth_write: false,
+        blend: Some(BlendMode::Alpha),
+        cull: CullMode::None,
+    };
+
+    pub const fn with_blend(self, blend: Option<BlendMode>) -> Self {
+        RenderState { blend, ..self }
+    }
+
+    pub fn apply(&self) {
+        unsafe {
+            if self.depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            } else {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+            gl::DepthMask(if self.depth_write {
+                gl::TRUE
+            } else {
+                gl::FALSE
+            });
+
+            match self.blend {
+                None => gl::Disable(gl::BLEND),
+                Some(mode) => {
+                    gl::Enable(gl::BLEND);
+                    match mode {
+                        BlendMode::Alpha => gl::BlendFuncSeparate(
+                            gl::SRC_ALPHA,
+                            gl::ONE_MINUS_SRC_ALPHA,
+                            gl::ONE,
+                            gl::ONE_MINUS_SRC_ALPHA,
+                        ),
+                        BlendMode::Additive => gl::BlendFunc(gl::SRC_ALPHA, gl::ONE),
+                        BlendMode::Invert => {
+                            gl::BlendFunc(gl::ONE_MINUS_DST_COLOR, gl::ONE_MINUS_SRC_COLOR)
+                        }
+                        BlendMode::Multiply => gl::BlendFunc(gl::DST_COLOR, gl::SRC_COLOR),
+                    }
+                }
+            }
+
+            match self.cull {
+                CullMode::None => gl::Disable(gl::CULL_FACE),
+                CullMode::Back => {
+                    gl::Enable(gl::CULL_FACE);
+                    gl::CullFace(gl::BACK);
+                }
+                CullMode::Front => {
+                    gl::Enable(gl::CULL_FACE);
+                    gl::CullFace(gl::FRONT);
+                }
+            }
+        }
+    }
+}
@@ -2,10 +2,11 @@ use gl::types::*;
 use std::ffi::CStr;
 use std::fmt;
 use std::ops::Drop;
-use std::ptr;
+
+use crate::{check_error, Error};
 
 pub trait Shader: private::Sealed {
-    fn from_cstr(source: &CStr) -> Result<Self, String>
+    fn from_cstr(source: &CStr) -> Result<Self, Error>
     where
         Self: Sized;
 
@@ -43,12 +44,9 @@ impl private::Sealed for GeometryShader {}
 impl private::Sealed for FragmentShader {}
 
 impl Shader for VertexShader {
-    fn from_cstr(source: &CStr) -> Result<Self, String> {
-        let result = create_shader(source, ShaderType::Vertex);
-        match result {
-            Ok(id) => Ok(VertexShader { id }),
-            Err(string) => Err(string),
-        }
+    fn from_cstr(source: &CStr) -> Result<Self, Error> {
+        let id = create_shader(source, ShaderType::Vertex)?;
+        Ok(VertexShader { id })
     }
     unsafe fn from_id(id: u32) -> Self {
         VertexShader { id }
@@ -59,12 +57,9 @@ impl Shader for VertexShader {
 }
 
 impl Shader for TessControlShader {
-    fn from_cstr(source: &CStr) -> Result<Self, String> {
-        let result = create_shader(source, ShaderType::TessControl);
-        match result {
-            Ok(id) => Ok(TessControlShader { id }),
-            Err(string) => Err(string),
-        }
+    fn from_cstr(source: &CStr) -> Result<Self, Error> {
+        let id = create_shader(source, ShaderType::TessControl)?;
+        Ok(TessControlShader { id })
     }
     unsafe fn from_id(id: u32) -> Self {
         TessControlShader { id }
@@ -75,12 +70,9 @@ impl Shader for TessControlShader {
 }
 
 impl Shader for TessEvaluationShader {
-    fn from_cstr(source: &CStr) -> Result<Self, String> {
-        let result = create_shader(source, ShaderType::TessEvaluation);
-        match result {
-            Ok(id) => Ok(TessEvaluationShader { id }),
-            Err(string) => Err(string),
-        }
+    fn from_cstr(source: &CStr) -> Result<Self, Error> {
+        let id = create_shader(source, ShaderType::TessEvaluation)?;
+        Ok(TessEvaluationShader { id })
     }
     unsafe fn from_id(id: u32) -> Self {
         TessEvaluationShader { id }
@@ -91,12 +83,9 @@ impl Shader for TessEvaluationShader {
 }
 
 impl Shader for GeometryShader {
-    fn from_cstr(source: &CStr) -> Result<Self, String> {
-        let result = create_shader(source, ShaderType::Geometry);
-        match result {
-            Ok(id) => Ok(GeometryShader { id }),
-            Err(string) => Err(string),
-        }
+    fn from_cstr(source: &CStr) -> Result<Self, Error> {
+        let id = create_shader(source, ShaderType::Geometry)?;
+        Ok(GeometryShader { id })
     }
     unsafe fn from_id(id: u32) -> Self {
         GeometryShader { id }
@@ -107,12 +96,9 @@ impl Shader for GeometryShader {
 }
 
 impl Shader for FragmentShader {
-    fn from_cstr(source: &CStr) -> Result<Self, String> {
-        let result = create_shader(source, ShaderType::Fragment);
-        match result {
-            Ok(id) => Ok(FragmentShader { id }),
-            Err(string) => Err(string),
-        }
+    fn from_cstr(source: &CStr) -> Result<Self, Error> {
+        let id = create_shader(source, ShaderType::Fragment)?;
+        Ok(FragmentShader { id })
     }
     unsafe fn from_id(id: u32) -> Self {
         FragmentShader { id }
@@ -166,7 +152,7 @@ mod private {
     pub trait Sealed {}
 }
 
-fn create_shader(source: &CStr, shader_type: ShaderType) -> Result<u32, String> {
+fn create_shader(source: &CStr, shader_type: ShaderType) -> Result<u32, Error> {
     unsafe {
         // Reset any error beforehand
         gl::GetError();
@@ -174,7 +160,7 @@ fn create_shader(source: &CStr, shader_type: ShaderType) -> Result<u32, String>
         // Create shader object
         let id = gl::CreateShader(shader_type.to_opengl());
         if id == 0 {
-            panic!("Unable to create shader object");
+            return Err(Error::CreateObject("shader"));
         }
 
         // Send data to the GPU
@@ -197,7 +183,10 @@ fn create_shader(source: &CStr, shader_type: ShaderType) -> Result<u32, String>
 
         // No error
         if status as GLboolean == gl::TRUE {
-            assert_eq!(gl::NO_ERROR, gl::GetError());
+            if let Err(error) = check_error() {
+                gl::DeleteShader(id);
+                return Err(error);
+            }
             return Ok(id);
         }
 
@@ -208,34 +197,31 @@ fn create_shader(source: &CStr, shader_type: ShaderType) -> Result<u32, String>
             &mut info_length as *mut GLint,
         );
 
-        if info_length == 0 {
-            assert_eq!(gl::NO_ERROR, gl::GetError());
-            return Err(format!(
-                "Failed to compile {shader_type}, no info log available."
-            ));
-        }
+        let log = if info_length > 0 {
+            let mut info_log: Vec<u8> = vec![0; info_length as usize];
+            let mut written = 0;
+            gl::GetShaderInfoLog(
+                id as GLuint,
+                info_length as GLsizei,
+                &mut written as *mut GLsizei,
+                info_log.as_mut_ptr() as *mut GLchar,
+            );
+            info_log.truncate(written.max(0) as usize);
 
-        let mut info_log: Vec<u8> = Vec::with_capacity((info_length - 1) as usize);
-        gl::GetShaderInfoLog(
-            id as GLuint,
-            (info_length - 1) as GLsizei,
-            ptr::null_mut() as *mut GLsizei,
-            info_log.as_mut_ptr() as *mut GLchar,
-        );
-
-        assert_eq!(gl::NO_ERROR, gl::GetError());
-        if let Ok(str) = String::from_utf8(info_log) {
-            Err(format!("Failed to compile {shader_type}: {str}"))
+            // A log that isn't UTF-8 is still better than nothing
+            Some(String::from_utf8_lossy(&info_log).into_owned())
         } else {
-            Err(format!(
-                "Failed to compile {shader_type}, info log cannot be parsed to UTF-8."
-            ))
-        }
+            None
+        };
+
+        gl::DeleteShader(id);
+        check_error()?;
+        Err(Error::Compile { shader_type, log })
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-enum ShaderType {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ShaderType {
     // ComputeShader, // Only for OpenGL 4.3+
     Vertex,
     TessControl,
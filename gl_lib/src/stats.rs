@@ -0,0 +1,39 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static DRAW_CALLS: AtomicU64 = AtomicU64::new(0);
+static TRIANGLES: AtomicU64 = AtomicU64::new(0);
+static UPLOAD_BYTES: AtomicU64 = AtomicU64::new(0);
+static TEXTURE_BINDS: AtomicU64 = AtomicU64::new(0);
+
+/// What the GL wrappers did since the stats were last taken, normally over one frame
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    pub draw_calls: u64,
+    pub triangles: u64,
+    /// Bytes sent to buffers and textures
+    pub upload_bytes: u64,
+    pub texture_binds: u64,
+}
+
+/// Read the counters and start them over from zero
+pub fn take_render_stats() -> RenderStats {
+    RenderStats {
+        draw_calls: DRAW_CALLS.swap(0, Ordering::Relaxed),
+        triangles: TRIANGLES.swap(0, Ordering::Relaxed),
+        upload_bytes: UPLOAD_BYTES.swap(0, Ordering::Relaxed),
+        texture_binds: TEXTURE_BINDS.swap(0, Ordering::Relaxed),
+    }
+}
+
+pub(crate) fn count_draw(triangles: usize) {
+    DRAW_CALLS.fetch_add(1, Ordering::Relaxed);
+    TRIANGLES.fetch_add(triangles as u64, Ordering::Relaxed);
+}
+
+pub(crate) fn count_upload(bytes: usize) {
+    UPLOAD_BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+pub(crate) fn count_texture_bind() {
+    TEXTURE_BINDS.fetch_add(1, Ordering::Relaxed);
+}
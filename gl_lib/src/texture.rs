@@ -0,0 +1,378 @@
+use gl::types::*;
+use std::ops::Drop;
+use std::ptr;
+
+use crate::stats::{count_texture_bind, count_upload};
+use crate::{check_error, Error};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextureFormat {
+    Rgba8,
+    /// Single channel, sampled as `.r`
+    R8,
+    /// Half float color that can go past 1, for render targets rather than uploads
+    Rgba16f,
+    /// 24 bit depth with 8 bit stencil, for render targets rather than uploads
+    Depth24Stencil8,
+}
+
+impl TextureFormat {
+    fn internal_format(self) -> GLint {
+        match self {
+            Self::Rgba8 => gl::RGBA8 as GLint,
+            Self::R8 => gl::R8 as GLint,
+            Self::Rgba16f => gl::RGBA16F as GLint,
+            Self::Depth24Stencil8 => gl::DEPTH24_STENCIL8 as GLint,
+        }
+    }
+
+    fn pixel_format(self) -> GLenum {
+        match self {
+            Self::Rgba8 | Self::Rgba16f => gl::RGBA,
+            Self::R8 => gl::RED,
+            Self::Depth24Stencil8 => gl::DEPTH_STENCIL,
+        }
+    }
+
+    fn pixel_type(self) -> GLenum {
+        match self {
+            Self::Rgba8 | Self::R8 => gl::UNSIGNED_BYTE,
+            Self::Rgba16f => gl::HALF_FLOAT,
+            Self::Depth24Stencil8 => gl::UNSIGNED_INT_24_8,
+        }
+    }
+
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Rgba8 | Self::Depth24Stencil8 => 4,
+            Self::R8 => 1,
+            Self::Rgba16f => 8,
+        }
+    }
+}
+
+pub struct Texture2D {
+    id: u32,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+}
+
+impl Texture2D {
+    /// Upload tightly packed RGBA8 pixels, first row is the bottom of the texture
+    pub fn from_rgba8(width: u32, height: u32, data: &[u8]) -> Result<Self, Error> {
+        check_size(width, height, TextureFormat::Rgba8, data)?;
+        let texture = Self::create(width, height, TextureFormat::Rgba8, data.as_ptr())?;
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as GLint);
+            gl::TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_MIN_FILTER,
+                gl::NEAREST_MIPMAP_LINEAR as GLint,
+            );
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+        }
+        check_error()?;
+        Ok(texture)
+    }
+
+    /// Uninitialized, linearly filtered texture without mipmaps, meant to be written piece by
+    /// piece with [`Texture2D::update_region`] (glyph atlases and the like)
+    pub fn empty(width: u32, height: u32, format: TextureFormat) -> Result<Self, Error> {
+        let texture = Self::create(width, height, format, ptr::null())?;
+        unsafe {
+            gl::TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_WRAP_S,
+                gl::CLAMP_TO_EDGE as GLint,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_WRAP_T,
+                gl::CLAMP_TO_EDGE as GLint,
+            );
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+        }
+        check_error()?;
+        Ok(texture)
+    }
+
+    /// Overwrite a rectangle of the base level, rows in `data` are tightly packed
+    pub fn update_region(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        check_size(width, height, self.format, data)?;
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                x as GLint,
+                y as GLint,
+                width as GLsizei,
+                height as GLsizei,
+                self.format.pixel_format(),
+                gl::UNSIGNED_BYTE,
+                data.as_ptr() as *const _,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        }
+        count_upload(data.len());
+        check_error()
+    }
+
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+        }
+        count_texture_bind();
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// # Safety
+    /// Do not delete the texture, it will automatically get deleted when it's dropped
+    pub unsafe fn get_id(&self) -> u32 {
+        self.id
+    }
+
+    /// Allocates the base level and leaves the texture bound
+    fn create(
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        data: *const u8,
+    ) -> Result<Self, Error> {
+        unsafe {
+            gl::GetError();
+
+            let mut id = 0;
+            gl::GenTextures(1, &mut id);
+            if id == 0 {
+                return Err(Error::CreateObject("texture"));
+            }
+            let texture = Texture2D {
+                id,
+                width,
+                height,
+                format,
+            };
+
+            gl::BindTexture(gl::TEXTURE_2D, id);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                format.internal_format(),
+                width as GLsizei,
+                height as GLsizei,
+                0,
+                format.pixel_format(),
+                format.pixel_type(),
+                data as *const _,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+            if !data.is_null() {
+                count_upload(width as usize * height as usize * format.bytes_per_pixel());
+            }
+
+            check_error()?;
+            Ok(texture)
+        }
+    }
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+/// Layers of equally sized RGBA8 images sampled as one texture, so geometry using different
+/// images can still go out in a single draw
+pub struct Texture2DArray {
+    id: u32,
+    width: u32,
+    height: u32,
+    layers: u32,
+}
+
+impl Texture2DArray {
+    /// Each layer is tightly packed RGBA8, first row at the bottom. Repeats and uses nearest
+    /// filtering with mipmaps, the way block textures want it.
+    pub fn from_rgba8_layers(width: u32, height: u32, layers: &[&[u8]]) -> Result<Self, Error> {
+        for layer in layers {
+            check_size(width, height, TextureFormat::Rgba8, layer)?;
+        }
+        unsafe {
+            gl::GetError();
+
+            let mut id = 0;
+            gl::GenTextures(1, &mut id);
+            if id == 0 {
+                return Err(Error::CreateObject("texture array"));
+            }
+            let texture = Texture2DArray {
+                id,
+                width,
+                height,
+                layers: layers.len() as u32,
+            };
+
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, id);
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                gl::RGBA8 as GLint,
+                width as GLsizei,
+                height as GLsizei,
+                layers.len() as GLsizei,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                ptr::null(),
+            );
+            for (index, layer) in layers.iter().enumerate() {
+                gl::TexSubImage3D(
+                    gl::TEXTURE_2D_ARRAY,
+                    0,
+                    0,
+                    0,
+                    index as GLint,
+                    width as GLsizei,
+                    height as GLsizei,
+                    1,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    layer.as_ptr() as *const _,
+                );
+                count_upload(layer.len());
+            }
+            gl::TexParameteri(
+                gl::TEXTURE_2D_ARRAY,
+                gl::TEXTURE_WRAP_S,
+                gl::REPEAT as GLint,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_2D_ARRAY,
+                gl::TEXTURE_WRAP_T,
+                gl::REPEAT as GLint,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_2D_ARRAY,
+                gl::TEXTURE_MIN_FILTER,
+                gl::NEAREST_MIPMAP_LINEAR as GLint,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_2D_ARRAY,
+                gl::TEXTURE_MAG_FILTER,
+                gl::NEAREST as GLint,
+            );
+            gl::GenerateMipmap(gl::TEXTURE_2D_ARRAY);
+
+            check_error()?;
+            Ok(texture)
+        }
+    }
+
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id);
+        }
+        count_texture_bind();
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// Overwrite the base level of one layer, the mipmaps keep the old content until
+    /// [`Texture2DArray::generate_mipmaps`]
+    pub fn update_layer(&self, layer: u32, data: &[u8]) -> Result<(), Error> {
+        check_size(self.width, self.height, TextureFormat::Rgba8, data)?;
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id);
+            gl::TexSubImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                0,
+                0,
+                layer as GLint,
+                self.width as GLsizei,
+                self.height as GLsizei,
+                1,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                data.as_ptr() as *const _,
+            );
+        }
+        count_upload(data.len());
+        check_error()
+    }
+
+    pub fn generate_mipmaps(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id);
+            gl::GenerateMipmap(gl::TEXTURE_2D_ARRAY);
+        }
+    }
+
+    /// # Safety
+    /// Do not delete the texture, it will automatically get deleted when it's dropped
+    pub unsafe fn get_id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Drop for Texture2DArray {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+fn check_size(width: u32, height: u32, format: TextureFormat, data: &[u8]) -> Result<(), Error> {
+    let expected = width as usize * height as usize * format.bytes_per_pixel();
+    if data.len() == expected {
+        Ok(())
+    } else {
+        Err(Error::TextureSize {
+            width,
+            height,
+            expected,
+            len: data.len(),
+        })
+    }
+}
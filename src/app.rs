@@ -0,0 +1,1800 @@
+use anyhow::{anyhow, Context as _};
+use cgmath::{Deg, Point3};
+use gl_lib::{Framebuffer, RenderState};
+use glfw::{Action, Context, CursorMode, Key, MouseButton, WindowEvent};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::assets::{self, Assets};
+use crate::audio::music::Scene;
+use crate::audio::{Audio, Listener};
+use crate::camera::Camera;
+use crate::console::commands::CommandContext;
+use crate::console::Console;
+use crate::crash;
+use crate::debug_overlay::{DebugInfo, DebugOverlay};
+#[cfg(feature = "debug-ui")]
+use crate::debug_ui::{DebugUi, Inspected};
+use crate::display::Display;
+use crate::frame_limiter::FrameLimiter;
+use crate::game::components::GameMode;
+use crate::game::crafting::{RecipeBook, INVENTORY_GRID_SIZE, MAX_GRID_SIZE};
+use crate::game::experience;
+use crate::game::health::HURT_TICKS;
+use crate::game::inventory::Inventory;
+use crate::game::item::ItemRegistry;
+use crate::game::mob::MobRegistry;
+use crate::game::sleeping::CantSleep;
+use crate::game::smelting::SmeltingBook;
+use crate::game::{is_daytime, Game, GameEvent, PlayerInput, DAY_LENGTH, REACH, TICK_SECONDS};
+use crate::i18n::{self, tr, tr_args};
+use crate::input::{self, Binding, InputAction};
+use crate::messages::Messages;
+use crate::profiler;
+use crate::render::breaking::BreakingOverlay;
+use crate::render::clouds::CloudRenderer;
+use crate::render::debug_view::{ChunkBorders, DebugView};
+use crate::render::deferred::{DeferredRenderer, SsaoQuality};
+use crate::render::dropped_items::{DroppedItemRenderer, ItemTextureSet};
+use crate::render::entities::{EntityRenderer, EntityScene};
+use crate::render::fog::Fog;
+use crate::render::held_item::HeldItemRenderer;
+use crate::render::hud::{Crosshair, Hotbar, Hud, HudContext, LevelBar, SleepFade, Vitals};
+use crate::render::icons::IconAtlas;
+use crate::render::item_textures::ItemTextures;
+use crate::render::lights::{LightBuffer, PointLight};
+use crate::render::outline::SelectionOutline;
+use crate::render::particles::ParticleRenderer;
+use crate::render::post::{PostParams, PostProcessor};
+use crate::render::precipitation::PrecipitationRenderer;
+use crate::render::shadows::ShadowMap;
+use crate::render::signs::SignRenderer;
+use crate::render::sky::{DayCycle, SkyRenderer};
+use crate::render::stats::FrameStats;
+use crate::render::text::TextRenderer;
+use crate::render::water::WaterSurface;
+use crate::render::world::{WorldPasses, WorldRenderer};
+use crate::save::anvil::{self, BlockMapping};
+use crate::save::schematic::Schematic;
+use crate::save::WorldInfo;
+use crate::screenshot;
+use crate::settings::{Settings, SETTINGS_PATH};
+use crate::state::{GameState, StateStack};
+use crate::ui::death_screen::{self, DeathAction};
+use crate::ui::inventory_screen::{InventoryScreen, ItemContext};
+use crate::ui::main_menu::{MainMenu, MainMenuAction};
+use crate::ui::pause_menu::{self, PauseAction};
+use crate::ui::settings_menu::{SettingsAction, SettingsMenu};
+use crate::ui::sign_editor::SignEditor;
+use crate::ui::{self, Ui, UiInput};
+use crate::world::block::{FluidProperties, Interaction};
+use crate::world::generator::Features;
+use crate::world::light::Light;
+use crate::world::ores;
+use crate::world::raycast::{self, RayHit};
+use crate::world::structures;
+use crate::world::BlockRegistry;
+
+const GAME_NAME: &str = "RustCraft";
+const ICON_PATH: &str = "icon.png";
+const TITLE_INTERVAL: Duration = Duration::from_millis(500);
+const WINDOW_WIDTH: u32 = 800;
+const WINDOW_HEIGHT: u32 = 600;
+/// Radians per pixel at a sensitivity of 100%
+const MOUSE_SENSITIVITY: f32 = 0.002;
+/// Ticks to catch up on at most per frame, the rest is dropped after a long stall
+const MAX_TICKS_PER_FRAME: u32 = 10;
+/// How much wider the view gets while sprinting
+const SPRINT_FOV_SCALE: f32 = 1.15;
+/// How quickly the view eases in and out of the sprint FOV, per second
+const FOV_EASE_RATE: f32 = 10.0;
+/// How much the corners of the world are darkened
+const VIGNETTE: f32 = 0.25;
+/// Seconds after which the clock of animated effects starts over
+const EFFECT_TIME_WRAP: f64 = 3600.0;
+/// Warmer than white, held light sources are mostly torches
+const HELD_LIGHT_COLOR: [f32; 3] = [1.0, 0.85, 0.6];
+
+pub struct App {
+    glfw: glfw::Glfw,
+    window: glfw::PWindow,
+    events: glfw::GlfwReceiver<(f64, WindowEvent)>,
+    display: Display,
+    frame_limiter: FrameLimiter,
+    last_title_update: Instant,
+    /// The world being played, if any
+    game: Option<Game>,
+    blocks: Arc<BlockRegistry>,
+    items: Arc<ItemRegistry>,
+    recipes: RecipeBook,
+    smelting: Arc<SmeltingBook>,
+    mobs: Arc<MobRegistry>,
+    features: Arc<Features>,
+    /// Seconds of game time not yet simulated, always less than a tick after updating
+    tick_accumulator: f32,
+    states: StateStack,
+    camera: Camera,
+    last_cursor: Option<(f64, f64)>,
+    /// Cursor motion while captured that mouse look hasn't used yet
+    mouse_delta: [f32; 2],
+    ui_input: UiInput,
+    main_menu: MainMenu,
+    settings_menu: SettingsMenu,
+    inventory_screen: InventoryScreen,
+    /// While a sign is being written on
+    sign_editor: Option<SignEditor>,
+    console: Console,
+    /// The schematic /schem last saved or loaded, what it pastes
+    clipboard: Option<Schematic>,
+    settings: Settings,
+    /// Multisampled target the world is drawn into, None while MSAA is off
+    scene_target: Option<Framebuffer>,
+    /// Effects applied to the world once it's drawn, before the overlays
+    post: PostProcessor,
+    /// Sun and moon shadows for the world, None while they're off
+    shadow_map: Option<ShadowMap>,
+    /// G-buffer and lighting pass for the world, None while it's drawn forward
+    deferred: Option<DeferredRenderer>,
+    /// Waves and reflections on water, None while fancy water is off
+    water: Option<WaterSurface>,
+    /// Point lights for this frame, like the one around a held torch
+    lights: LightBuffer,
+    assets: Assets,
+    world_renderer: WorldRenderer,
+    entity_renderer: EntityRenderer,
+    dropped_items: DroppedItemRenderer,
+    held_item: HeldItemRenderer,
+    outline: SelectionOutline,
+    breaking: BreakingOverlay,
+    chunk_borders: ChunkBorders,
+    /// Wireframe, chunk borders and the like, toggled with F-keys or /debugview
+    debug_view: DebugView,
+    sky: SkyRenderer,
+    clouds: CloudRenderer,
+    precipitation: PrecipitationRenderer,
+    particles: ParticleRenderer,
+    signs: SignRenderer,
+    hud: Hud,
+    /// Pictures of the items for the HUD
+    icons: IconAtlas,
+    item_textures: ItemTextures,
+    text: TextRenderer,
+    audio: Audio,
+    debug_overlay: DebugOverlay,
+    #[cfg(feature = "debug-ui")]
+    debug_ui: DebugUi,
+    messages: Messages,
+    screenshot_requested: bool,
+    /// Counting up for the frame being drawn
+    stats: FrameStats,
+    /// What the debug screens show, one frame behind
+    last_stats: FrameStats,
+    /// Set from the Ctrl-C handler's thread
+    interrupted: Arc<AtomicBool>,
+    /// Block under the crosshair as of this frame's camera
+    target: Option<RayHit>,
+    /// Fluid the camera is inside as of this frame
+    submerged: Option<FluidProperties>,
+    /// Minecraft world being imported in the background
+    import: Option<JoinHandle<anyhow::Result<WorldInfo>>>,
+}
+
+impl App {
+    pub fn new() -> anyhow::Result<Self> {
+        let mut glfw = glfw::init(glfw::log_errors)
+            .map_err(|err| anyhow!("Failed to initialize GLFW: {err:?}"))?;
+        glfw.window_hint(glfw::WindowHint::ContextVersion(4, 1));
+        glfw.window_hint(glfw::WindowHint::OpenGlProfile(
+            glfw::OpenGlProfileHint::Core,
+        ));
+        glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+
+        let (mut window, events) = glfw
+            .create_window(
+                WINDOW_WIDTH,
+                WINDOW_HEIGHT,
+                GAME_NAME,
+                glfw::WindowMode::Windowed,
+            )
+            .context("Failed to create a window with an OpenGL 4.1 core context")?;
+        window.make_current();
+        window.set_key_polling(true);
+        window.set_cursor_pos_polling(true);
+        window.set_mouse_button_polling(true);
+        window.set_scroll_polling(true);
+        window.set_char_polling(true);
+        window.set_framebuffer_size_polling(true);
+
+        gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
+        let gl_info = gl_lib::ContextInfo::query();
+        log::info!(
+            "OpenGL {} on {} ({})",
+            gl_info.version,
+            gl_info.renderer,
+            gl_info.vendor
+        );
+        crash::set_gl_info(gl_info);
+
+        let settings = Settings::load(Path::new(SETTINGS_PATH))?;
+        crash::set_settings(&settings);
+        let mut display = Display::new();
+        if settings.fullscreen {
+            display.enter_fullscreen(
+                &mut glfw,
+                &mut window,
+                settings.fullscreen_mode,
+                settings.monitor,
+            );
+        }
+        let mut assets = Assets::new("asset", &settings.resource_packs);
+        let mut messages = Messages::new();
+        if let Err(err) = i18n::set_language(&assets, &settings.language) {
+            messages.push(format!("{err:#}"));
+        }
+        let blocks = Arc::new(BlockRegistry::load(&assets).context("Failed to load blocks")?);
+        let items = Arc::new(ItemRegistry::load(&assets, &blocks).context("Failed to load items")?);
+        let recipes = RecipeBook::load(&assets, &items).context("Failed to load recipes")?;
+        let smelting = Arc::new(
+            SmeltingBook::load(&assets, &items).context("Failed to load smelting recipes")?,
+        );
+        let mobs =
+            Arc::new(MobRegistry::load(&assets, &blocks, &items).context("Failed to load mobs")?);
+        let features = Arc::new(Features {
+            ores: ores::load_ores(&assets, &blocks).context("Failed to load ores")?,
+            structures: structures::load_structures(&assets, &blocks)
+                .context("Failed to load structures")?,
+        });
+        let world_renderer = WorldRenderer::new(
+            &mut assets,
+            Arc::clone(&blocks),
+            settings.meshing,
+            settings.smooth_lighting,
+            settings.lod_distance,
+        )?;
+        let item_textures = ItemTextures::load(&assets, &items)?;
+        let icons = IconAtlas::bake(
+            &mut assets,
+            &blocks,
+            &items,
+            world_renderer.textures(),
+            &item_textures,
+        )
+        .context("Failed to draw the item icons")?;
+        let entity_renderer = EntityRenderer::new(&mut assets)?;
+        let dropped_items = DroppedItemRenderer::new(&mut assets)?;
+        let held_item = HeldItemRenderer::new(&mut assets)?;
+        let outline = SelectionOutline::new(&mut assets)?;
+        let breaking = BreakingOverlay::new(&mut assets)?;
+        let chunk_borders = ChunkBorders::new(&mut assets)?;
+        let sky = SkyRenderer::new(&mut assets)?;
+        let precipitation = PrecipitationRenderer::new(&mut assets)?;
+        let particles = ParticleRenderer::new(&mut assets)?;
+        let signs = SignRenderer::new(&mut assets)?;
+        let clouds =
+            CloudRenderer::new(&mut assets, settings.cloud_height, settings.cloud_density)?;
+        let (width, height) = window.get_framebuffer_size();
+        let post = PostProcessor::new(&mut assets, width.max(1) as u32, height.max(1) as u32)?;
+        let mut hud = Hud::new(&mut assets)?;
+        hud.push(SleepFade);
+        hud.push(Crosshair);
+        hud.push(Hotbar);
+        hud.push(Vitals);
+        hud.push(LevelBar);
+        let text = TextRenderer::new(&mut assets, "font/default.ttf", 16.0)?;
+        let audio = Audio::new(&assets, settings.volume).context("Failed to load sounds")?;
+        #[cfg(feature = "debug-ui")]
+        let debug_ui = DebugUi::new(&mut window)?;
+
+        // Ctrl-C in the terminal closes the window the normal way, so the world gets saved
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let handler = {
+            let interrupted = Arc::clone(&interrupted);
+            move || interrupted.store(true, Ordering::Relaxed)
+        };
+        if let Err(err) = ctrlc::set_handler(handler) {
+            log::warn!("Ctrl-C will quit without saving: {err}");
+        }
+
+        let mut camera = Camera::new(
+            Point3::new(0.0, 0.0, 2.0),
+            width as f32 / height.max(1) as f32,
+        );
+        camera.fov = Deg(settings.fov);
+
+        unsafe {
+            gl::Viewport(0, 0, width, height);
+        }
+
+        Ok(App {
+            glfw,
+            window,
+            events,
+            display,
+            frame_limiter: FrameLimiter::new(),
+            last_title_update: Instant::now(),
+            game: None,
+            blocks,
+            items,
+            recipes,
+            smelting,
+            mobs,
+            features,
+            tick_accumulator: 0.0,
+            states: StateStack::new(GameState::MainMenu),
+            camera,
+            last_cursor: None,
+            mouse_delta: [0.0; 2],
+            ui_input: UiInput::default(),
+            main_menu: MainMenu::new(),
+            settings_menu: SettingsMenu::new(Vec::new(), Vec::new()),
+            inventory_screen: InventoryScreen::new(INVENTORY_GRID_SIZE),
+            sign_editor: None,
+            console: Console::new(),
+            clipboard: None,
+            settings,
+            scene_target: None,
+            post,
+            shadow_map: None,
+            deferred: None,
+            water: None,
+            lights: LightBuffer::new(),
+            assets,
+            world_renderer,
+            entity_renderer,
+            dropped_items,
+            held_item,
+            outline,
+            breaking,
+            chunk_borders,
+            debug_view: DebugView::default(),
+            sky,
+            clouds,
+            precipitation,
+            particles,
+            signs,
+            hud,
+            icons,
+            item_textures,
+            text,
+            audio,
+            debug_overlay: DebugOverlay::new(),
+            #[cfg(feature = "debug-ui")]
+            debug_ui,
+            messages,
+            screenshot_requested: false,
+            stats: FrameStats::default(),
+            last_stats: FrameStats::default(),
+            interrupted,
+            target: None,
+            submerged: None,
+            import: None,
+        })
+    }
+
+    pub fn run(&mut self) -> anyhow::Result<()> {
+        let mut last_frame = self.glfw.get_time();
+        self.apply_video_settings();
+        self.update_deferred();
+        self.update_scene_target();
+        self.update_shadow_map();
+        self.update_water();
+        self.load_icon();
+        self.update_title();
+        self.sync_cursor();
+        while !self.window.should_close() {
+            let now = self.glfw.get_time();
+            let delta = (now - last_frame) as f32;
+            last_frame = now;
+            self.debug_overlay
+                .record_frame(Duration::from_secs_f32(delta.max(0.0)));
+            #[cfg(feature = "debug-ui")]
+            self.debug_ui.record_frame(delta);
+            profiler::begin_frame();
+
+            {
+                let _scope = profiler::scope("input");
+                self.glfw.poll_events();
+                let events: Vec<_> = glfw::flush_messages(&self.events)
+                    .map(|(_, event)| event)
+                    .collect();
+                for event in events {
+                    self.handle_event(event);
+                }
+                if self.interrupted.load(Ordering::Relaxed) {
+                    self.window.set_should_close(true);
+                }
+                self.apply_mouse_look(delta);
+            }
+
+            {
+                let _scope = profiler::scope("assets");
+                self.assets.hot_reload();
+            }
+            if self.last_title_update.elapsed() >= TITLE_INTERVAL {
+                self.update_title();
+            }
+            self.poll_import();
+            if self.states.current().simulates() {
+                let _scope = profiler::scope("update");
+                self.update(delta);
+            }
+            {
+                let _scope = profiler::scope("render");
+                self.render();
+            }
+            self.update_audio(delta);
+            self.ui_input.end_frame();
+            if self.states.current() == GameState::Loading {
+                // Nothing to wait on yet, the loading screen just gets its one frame
+                self.states.reset(GameState::Playing);
+                self.sync_cursor();
+            }
+            if self.screenshot_requested {
+                self.screenshot_requested = false;
+                self.take_screenshot();
+            }
+            {
+                let _scope = profiler::scope("swap");
+                self.window.swap_buffers();
+            }
+            self.last_stats = self.stats.finish();
+
+            let _scope = profiler::scope("sleep");
+            let fps_cap = if self.window.is_focused() {
+                self.settings.max_fps
+            } else {
+                self.settings.background_fps
+            };
+            self.frame_limiter.wait(fps_cap);
+        }
+        if self.game.is_some() {
+            self.close_world();
+        }
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: WindowEvent) {
+        #[cfg(feature = "debug-ui")]
+        if self.debug_ui.handle_event(&event) {
+            return;
+        }
+        match event {
+            WindowEvent::Key(Key::Escape, _, Action::Press, _) => match self.states.current() {
+                GameState::Playing => self.set_paused(true),
+                GameState::Paused => self.set_paused(false),
+                GameState::Settings => {
+                    if !self.settings_menu.cancel_rebind() {
+                        self.close_settings();
+                    }
+                }
+                GameState::Console => self.close_console(),
+                GameState::Inventory => self.close_inventory(),
+                GameState::EditSign => self.close_sign_editor(),
+                GameState::MainMenu | GameState::Loading | GameState::Dead => {}
+            },
+            WindowEvent::Key(key, _, action, _) => self.handle_key(key, action),
+            WindowEvent::CursorPos(x, y) => {
+                if let Some((last_x, last_y)) = self.last_cursor {
+                    if self.captures_cursor() {
+                        self.mouse_delta[0] += (x - last_x) as f32;
+                        self.mouse_delta[1] += (y - last_y) as f32;
+                    }
+                }
+                self.last_cursor = Some((x, y));
+
+                // Cursor positions are in screen coordinates, which differ from framebuffer
+                // pixels on high DPI displays
+                let (window_width, window_height) = self.window.get_size();
+                let (width, height) = self.window.get_framebuffer_size();
+                let scale = self.gui_scale();
+                self.ui_input.cursor = [
+                    x as f32 * width as f32 / window_width.max(1) as f32 / scale,
+                    y as f32 * height as f32 / window_height.max(1) as f32 / scale,
+                ];
+            }
+            WindowEvent::MouseButton(button, Action::Press, _) => {
+                match button {
+                    MouseButton::Button1 => {
+                        self.ui_input.clicked = true;
+                        self.ui_input.mouse_down = true;
+                    }
+                    MouseButton::Button2 => self.ui_input.right_clicked = true,
+                    _ => {}
+                }
+                self.press(Binding::Mouse(button));
+            }
+            WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) => {
+                self.ui_input.mouse_down = false;
+            }
+            WindowEvent::Scroll(_, y) => {
+                self.ui_input.scroll += y as f32;
+                // Scrolling down moves right along the hotbar
+                if y != 0.0 {
+                    self.change_hotbar(|inventory| inventory.scroll(-y.signum() as i32));
+                }
+            }
+            WindowEvent::Char(character) => {
+                self.ui_input.text.push(character);
+            }
+            WindowEvent::FramebufferSize(width, height) => {
+                unsafe {
+                    gl::Viewport(0, 0, width, height);
+                }
+                self.camera.aspect = width as f32 / height.max(1) as f32;
+                self.update_scene_target();
+                self.resize_deferred();
+                if let Err(err) = self.post.resize(width.max(1) as u32, height.max(1) as u32) {
+                    self.messages.push(format!("{err:#}"));
+                }
+                self.resize_water();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key(&mut self, key: Key, action: Action) {
+        match (key, action) {
+            (Key::Backspace, Action::Press | Action::Repeat) => self.ui_input.backspace += 1,
+            (Key::Enter | Key::KpEnter, Action::Press) => self.ui_input.enter = true,
+            _ => {}
+        }
+        if action != Action::Release {
+            self.ui_input.keys.push(key);
+        }
+        if action == Action::Press {
+            if let Some(slot) = input::HOTBAR_KEYS.iter().position(|hotbar| *hotbar == key) {
+                self.change_hotbar(|inventory| inventory.select(slot));
+            }
+            self.press(Binding::Key(key));
+        }
+    }
+
+    /// Move the hotbar selection, only while the game has the cursor
+    fn change_hotbar(&mut self, change: impl FnOnce(&mut Inventory)) {
+        if !self.captures_cursor() {
+            return;
+        }
+        if let Some(inventory) = self.game.as_mut().and_then(Game::inventory_mut) {
+            change(inventory);
+        }
+    }
+
+    /// Fire whatever is bound to a key or button that just went down
+    fn press(&mut self, binding: Binding) {
+        self.ui_input.pressed.get_or_insert(binding);
+        // The settings screen wants the raw key for rebinding and the console and signs want
+        // to type it, none of them want its current meaning
+        if matches!(
+            self.states.current(),
+            GameState::Settings | GameState::Console | GameState::EditSign
+        ) {
+            return;
+        }
+
+        let actions: Vec<_> = self.settings.key_bindings.actions_for(binding).collect();
+        for action in actions {
+            match action {
+                InputAction::OpenInventory => match self.states.current() {
+                    GameState::Playing => {
+                        self.open_inventory(InventoryScreen::new(INVENTORY_GRID_SIZE))
+                    }
+                    GameState::Inventory => self.close_inventory(),
+                    _ => {}
+                },
+                InputAction::OpenConsole => self.open_console(),
+                InputAction::Screenshot => self.screenshot_requested = true,
+                InputAction::ToggleDebug => self.debug_overlay.toggle(),
+                InputAction::ToggleDebugUi => self.toggle_debug_ui(),
+                InputAction::ToggleProfiler => profiler::toggle(),
+                InputAction::ToggleWireframe => {
+                    self.debug_view.wireframe = !self.debug_view.wireframe;
+                    self.apply_debug_view();
+                }
+                InputAction::ToggleChunkBorders => {
+                    self.debug_view.chunk_borders = !self.debug_view.chunk_borders;
+                }
+                InputAction::CycleDebugShading => {
+                    self.debug_view.shading = self.debug_view.shading.next();
+                    self.apply_debug_view();
+                    let mode = self.debug_view.shading.name();
+                    self.messages
+                        .push(tr_args("message.debug_shading", &[("mode", &mode)]));
+                }
+                InputAction::Fullscreen => self.toggle_fullscreen(),
+                InputAction::BreakBlock | InputAction::PlaceBlock | InputAction::PickBlock => {
+                    self.use_block(action)
+                }
+                // Held actions are polled every update instead
+                InputAction::MoveForward
+                | InputAction::MoveBack
+                | InputAction::MoveLeft
+                | InputAction::MoveRight
+                | InputAction::Jump
+                | InputAction::Sneak
+                | InputAction::Sprint => {}
+            }
+        }
+    }
+
+    /// Break, place or pick the block under the crosshair, only while the game has the cursor
+    fn use_block(&mut self, action: InputAction) {
+        if !self.captures_cursor() {
+            return;
+        }
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        match action {
+            // Breaking itself goes on in the ticks for as long as it's held
+            InputAction::BreakBlock => {
+                self.held_item.swing();
+                game.attack();
+            }
+            InputAction::PlaceBlock => {
+                if let Some((interaction, position)) = game.interaction() {
+                    let screen = match interaction {
+                        Interaction::Ignite => {
+                            self.held_item.swing();
+                            game.ignite(position);
+                            return;
+                        }
+                        Interaction::Switch => {
+                            self.held_item.swing();
+                            game.switch(position);
+                            return;
+                        }
+                        Interaction::Sleep => {
+                            self.messages.push(tr("message.spawn_set"));
+                            let key = match game.sleep(position) {
+                                Ok(()) => return,
+                                Err(CantSleep::Day) => "message.sleep_day",
+                                Err(CantSleep::Monsters) => "message.sleep_monsters",
+                            };
+                            self.messages.push(tr(key));
+                            return;
+                        }
+                        Interaction::Sign => {
+                            self.open_sign_editor(position);
+                            return;
+                        }
+                        Interaction::Open => {
+                            self.held_item.swing();
+                            game.toggle(position);
+                            return;
+                        }
+                        Interaction::Crafting => InventoryScreen::new(MAX_GRID_SIZE),
+                        Interaction::Enchanting => InventoryScreen::enchanting(),
+                        Interaction::Chest | Interaction::Furnace => {
+                            InventoryScreen::block(position)
+                        }
+                    };
+                    self.open_inventory(screen);
+                    return;
+                }
+                if game.use_held_item() {
+                    self.held_item.swing();
+                    return;
+                }
+                if game.held_block().is_some() {
+                    self.held_item.swing();
+                }
+                // A sign is written on as soon as it's placed
+                let placed = game.place_block().filter(|position| {
+                    let block = game
+                        .world
+                        .block(*position)
+                        .and_then(|id| self.blocks.get(id));
+                    block.is_some_and(|block| block.interaction == Some(Interaction::Sign))
+                });
+                if let Some(position) = placed {
+                    self.open_sign_editor(position);
+                }
+            }
+            InputAction::PickBlock => game.pick_block(),
+            _ => {}
+        }
+    }
+
+    /// Run as many fixed ticks as the frame time adds up to
+    fn update(&mut self, delta: f32) {
+        let captures_cursor = self.captures_cursor();
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        let down = |action| self.settings.key_bindings.is_down(&self.window, action);
+        let axis = |positive, negative| down(positive) as i32 as f32 - down(negative) as i32 as f32;
+        let input = PlayerInput {
+            forward: axis(InputAction::MoveForward, InputAction::MoveBack),
+            strafe: axis(InputAction::MoveRight, InputAction::MoveLeft),
+            jump: down(InputAction::Jump),
+            sneak: down(InputAction::Sneak),
+            sprint: down(InputAction::Sprint),
+            mining: captures_cursor && down(InputAction::BreakBlock),
+            using: captures_cursor && down(InputAction::PlaceBlock),
+        };
+
+        self.tick_accumulator += delta;
+        let mut ticks = 0;
+        while self.tick_accumulator >= TICK_SECONDS {
+            if ticks == MAX_TICKS_PER_FRAME {
+                self.tick_accumulator = 0.0;
+                break;
+            }
+            let start = Instant::now();
+            game.tick(&input);
+            self.debug_overlay.record_tick(start.elapsed());
+            self.tick_accumulator -= TICK_SECONDS;
+            ticks += 1;
+            if game.is_dead() {
+                self.tick_accumulator = 0.0;
+                break;
+            }
+        }
+        // Blocks sound like their material, e.g. `block.wood.place`
+        let block_sound = |block, action| {
+            let material = self.blocks.get(block)?.sound.name();
+            Some(format!("block.{material}.{action}"))
+        };
+        let middle = |position: Point3<i32>| position.map(|value| value as f32 + 0.5);
+        for event in game.take_events() {
+            match event {
+                GameEvent::Explosion { center, power } => {
+                    self.particles.explosion(center, power);
+                    self.audio.play_at("entity.generic.explode", center);
+                }
+                GameEvent::BlockBroken { position, block } => {
+                    if let Some(sound) = block_sound(block, "break") {
+                        self.audio.play_at(&sound, middle(position));
+                    }
+                }
+                GameEvent::BlockPlaced { position, block } => {
+                    if let Some(sound) = block_sound(block, "place") {
+                        self.audio.play_at(&sound, middle(position));
+                    }
+                }
+                GameEvent::Footstep { position, block } => {
+                    if let Some(sound) = block_sound(block, "step") {
+                        self.audio.play_at(&sound, position);
+                    }
+                }
+            }
+        }
+        self.particles.update(delta);
+        let died = game.is_dead() && self.states.current() == GameState::Playing;
+
+        let target_scale = if game.player_movement().sprinting {
+            SPRINT_FOV_SCALE
+        } else {
+            1.0
+        };
+        let blend = 1.0 - (-FOV_EASE_RATE * delta).exp();
+        self.camera.fov_scale += (target_scale - self.camera.fov_scale) * blend;
+        if (game.mining().is_some() || game.is_eating()) && !self.held_item.is_swinging() {
+            self.held_item.swing();
+        }
+        self.held_item.update(
+            delta,
+            game.held_item(),
+            &self.items,
+            &self.blocks,
+            self.world_renderer.textures(),
+            &self.item_textures,
+        );
+
+        // The chest or furnace being looked into and the sign being written on can go away
+        // under the screen, e.g. with their chunk unloaded
+        let container_gone = self.states.current() == GameState::Inventory
+            && self
+                .inventory_screen
+                .block_position()
+                .is_some_and(|position| game.container_mut(position).is_none());
+        let sign_gone = self.states.current() == GameState::EditSign
+            && self
+                .sign_editor
+                .as_ref()
+                .is_none_or(|editor| game.sign_text_mut(editor.position()).is_none());
+        if container_gone {
+            self.close_inventory();
+        }
+        if sign_gone {
+            self.close_sign_editor();
+        }
+        if died {
+            self.states.push(GameState::Dead);
+            self.sync_cursor();
+        }
+    }
+
+    /// Turn the player by the mouse motion gathered over the last `seconds`. Motion is summed
+    /// per frame first so acceleration sees the actual speed rather than the event rate.
+    fn apply_mouse_look(&mut self, seconds: f32) {
+        let [x, y] = mem::take(&mut self.mouse_delta);
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        if x == 0.0 && y == 0.0 {
+            return;
+        }
+        let gain = input::acceleration_gain(x.hypot(y), seconds, self.settings.mouse_acceleration);
+        let sensitivity = MOUSE_SENSITIVITY * self.settings.mouse_sensitivity * gain;
+        game.rotate_player(x * sensitivity, -y * sensitivity);
+    }
+
+    /// Put the camera at the player's eyes, blended between the last two ticks, and find
+    /// what it's looking at from there
+    fn sync_camera(&mut self) {
+        self.target = None;
+        self.submerged = None;
+        let Some(game) = &self.game else {
+            return;
+        };
+        let (eye, look) = game.player_eye(self.tick_accumulator / TICK_SECONDS);
+        self.camera.position = eye;
+        self.camera.yaw = look.yaw;
+        self.camera.pitch = look.pitch;
+        self.target = raycast::raycast(&game.world, &game.blocks, eye, look.forward(), REACH);
+        self.submerged = game
+            .world
+            .block(eye.map(|value| value.floor() as i32))
+            .and_then(|block| game.blocks.get(block)?.fluid)
+            .map(|fluid| fluid.properties);
+    }
+
+    /// Hear from where the camera is, sounds follow it while they play. The music goes with
+    /// the biome the camera is in and the time of day, or the menus without a world.
+    fn update_audio(&mut self, delta: f32) {
+        self.audio.set_listener(Listener {
+            position: self.camera.position,
+            yaw: self.camera.yaw,
+        });
+        let scene = match &self.game {
+            Some(game) => {
+                let position = self.camera.position.map(|value| value.floor() as i32);
+                Scene::World {
+                    biome: game.generator.biome(position.x, position.z),
+                    day: is_daytime(game.info.time),
+                }
+            }
+            None => Scene::Menu,
+        };
+        self.audio.update(delta, scene);
+    }
+
+    /// What the world fades into this frame, thick fog in the fluid's color with the camera
+    /// inside one
+    fn fog(&self, cycle: &DayCycle) -> Fog {
+        match &self.submerged {
+            Some(fluid) => Fog::fluid(fluid),
+            None => Fog::sky(cycle, self.settings.render_distance),
+        }
+    }
+
+    fn render(&mut self) {
+        let (width, height) = self.window.get_framebuffer_size();
+        let scale = self.gui_scale();
+        // Everything 2D is laid out in GUI units
+        let screen_size = [width as f32 / scale, height as f32 / scale];
+        self.text.set_scale(scale);
+
+        // Depth writes have to be on for the clear to reach the depth buffer
+        RenderState::OPAQUE.apply();
+        let cycle = self.day_cycle();
+        unsafe {
+            let [red, green, blue] = cycle.horizon_color;
+            gl::ClearColor(red, green, blue, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        // Menus without a world behind them just get the clear color
+        let state = self.states.current();
+        if self.states.shows_world() {
+            let _scope = profiler::scope("world");
+            self.sync_camera();
+            self.update_meshes();
+            // The world's own clears show through where nothing is drawn, which is all fog
+            // inside a fluid
+            let [red, green, blue] = self.fog(&cycle).color;
+            unsafe {
+                gl::ClearColor(red, green, blue, 1.0);
+            }
+            if let Some(shadow_map) = &mut self.shadow_map {
+                let _scope = profiler::scope("shadows");
+                shadow_map.render(&self.assets, &self.camera, &cycle, &self.world_renderer);
+                gl_lib::bind_default_framebuffer(width as u32, height as u32);
+            }
+            match &self.scene_target {
+                Some(target) => {
+                    target.bind();
+                    unsafe {
+                        gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                    }
+                }
+                None => self.post.begin(),
+            }
+            self.render_world(&cycle);
+            // Resolve before the overlays, text and HUD don't need multisampling
+            if let Some(target) = &self.scene_target {
+                target.resolve_to(self.post.scene());
+            }
+            let _scope = profiler::scope("post");
+            self.post.finish(&self.assets, &self.post_params());
+        }
+
+        let ui_scope = profiler::scope("ui");
+        if state == GameState::Playing {
+            let inventory = self.game.as_ref().and_then(Game::inventory);
+            // Nothing can hurt or starve a creative player and they don't need experience, so
+            // there's nothing to show
+            let survival = self
+                .game
+                .as_ref()
+                .filter(|game| game.game_mode() == GameMode::Survival);
+            let context = HudContext {
+                screen_size,
+                icons: &self.icons,
+                items: &self.items,
+                inventory: inventory.as_deref(),
+                health: survival.and_then(Game::player_health),
+                breath: survival.and_then(Game::player_breath),
+                hunger: survival.and_then(Game::player_hunger),
+                experience: survival.map(Game::experience),
+                sleep: self
+                    .game
+                    .as_ref()
+                    .and_then(Game::sleep_progress)
+                    .unwrap_or(0.0),
+            };
+            self.hud.draw(&self.assets, &mut self.text, &context);
+        }
+        self.draw_menus(state, screen_size);
+
+        if self.debug_overlay.visible {
+            let info = DebugInfo {
+                camera: &self.camera,
+                stats: &self.last_stats,
+                gpu_memory: self.assets.gpu_memory_estimate(),
+                game: self.game.as_ref(),
+                target: self.target,
+            };
+            self.debug_overlay.draw(&mut self.text, &info);
+        }
+        self.messages.draw(&mut self.text, screen_size);
+        profiler::draw(&mut self.text, screen_size);
+        drop(ui_scope);
+        {
+            let _scope = profiler::scope("text");
+            self.text.flush(&self.assets, screen_size);
+        }
+
+        #[cfg(feature = "debug-ui")]
+        if self.debug_ui.visible {
+            let inspected = Inspected {
+                camera: &mut self.camera,
+                game: self.game.as_mut(),
+                fps: self.debug_overlay.fps(),
+                draw_calls: self.last_stats.gl.draw_calls,
+            };
+            self.debug_ui.draw(&mut self.window, inspected);
+        }
+    }
+
+    /// The sky as of this frame. Menus without a world get the noon sky.
+    fn day_cycle(&self) -> DayCycle {
+        let (Some(game), Some(time)) = (&self.game, self.world_time()) else {
+            return DayCycle::at((DAY_LENGTH / 4) as f64);
+        };
+        DayCycle::at(time).overcast(game.info.weather.strength)
+    }
+
+    /// Seconds of world time for animated effects. Wrapped so it keeps its precision in old
+    /// worlds, the effects loop well within it.
+    fn effect_time(&self) -> f32 {
+        let seconds = self
+            .world_time()
+            .map_or(0.0, |time| time * TICK_SECONDS as f64);
+        (seconds % EFFECT_TIME_WRAP) as f32
+    }
+
+    fn post_params(&self) -> PostParams {
+        PostParams {
+            time: self.effect_time(),
+            gamma: self.settings.gamma,
+            vignette: VIGNETTE,
+            underwater: if self.submerged.is_some() { 1.0 } else { 0.0 },
+            fluid_color: self.submerged.map_or([0.0; 3], |fluid| fluid.fog_color),
+            nausea: 0.0,
+            hurt: self
+                .game
+                .as_ref()
+                .and_then(Game::player_health)
+                .map_or(0.0, |health| health.hurt as f32 / HURT_TICKS as f32),
+            fxaa: self.settings.fxaa,
+            bloom: self.settings.bloom,
+        }
+    }
+
+    /// Ticks since the world was created, blended between ticks like the camera
+    fn world_time(&self) -> Option<f64> {
+        let game = self.game.as_ref()?;
+        let partial = self.tick_accumulator / TICK_SECONDS;
+        Some(game.info.time as f64 + partial as f64)
+    }
+
+    /// Remesh what changed and move block animations along, before anything of the world is
+    /// drawn this frame
+    fn update_meshes(&mut self) {
+        if let Some(game) = &mut self.game {
+            self.stats.mesh_rebuilds +=
+                self.world_renderer
+                    .update(&mut game.world, &game.generator, &self.camera);
+        }
+        self.stats.mesh_queue = self.world_renderer.pending() as u32;
+        self.stats.mesh_unstarted = self.world_renderer.unstarted() as u32;
+        if let Some(time) = self.world_time() {
+            self.world_renderer.animate(time as u64);
+        }
+    }
+
+    fn render_world(&mut self, cycle: &DayCycle) {
+        // Inside a fluid the fog hides the sky, it's all the clear color
+        let submerged = self.submerged.is_some();
+        if !submerged {
+            self.sky.draw(&self.assets, &self.camera, cycle);
+        }
+        let fog = self.fog(cycle);
+        let time = self.effect_time();
+        if let Some(water) = &mut self.water {
+            water.set_time(time);
+        }
+        self.lights
+            .upload(&self.point_lights(), self.camera.position);
+        let item_textures = ItemTextureSet {
+            blocks: self.world_renderer.textures(),
+            items: &self.item_textures,
+        };
+        if let Some(game) = &self.game {
+            self.entity_renderer
+                .prepare(&mut self.assets, &game.entities);
+            self.dropped_items
+                .prepare(&game.entities, &self.items, &self.blocks, &item_textures);
+        }
+        let entity_scene = self.game.as_ref().map(|game| EntityScene {
+            camera: &self.camera,
+            cycle,
+            shadows: self.shadow_map.as_ref(),
+            fog: &fog,
+            world: &game.world,
+            entities: &game.entities,
+            alpha: self.tick_accumulator / TICK_SECONDS,
+            time,
+        });
+        let draw_entities = entity_scene.as_ref().map(|scene| {
+            || {
+                self.entity_renderer.draw(&self.assets, scene);
+                self.dropped_items.draw(&self.assets, scene, &item_textures);
+            }
+        });
+        let passes = WorldPasses {
+            shadows: self.shadow_map.as_ref(),
+            deferred: self.deferred.as_ref(),
+            water: self.water.as_ref(),
+            entities: draw_entities.as_ref().map(|draw| draw as &dyn Fn()),
+        };
+        let (drawn, culled) = self.world_renderer.draw(
+            &self.assets,
+            &self.camera,
+            cycle,
+            &passes,
+            &fog,
+            self.settings.render_distance,
+        );
+        self.stats.chunks_drawn = drawn;
+        self.stats.chunks_culled = culled;
+        if let (false, Some(time)) = (submerged, self.world_time()) {
+            self.clouds.draw(
+                &self.assets,
+                &self.camera,
+                cycle,
+                time,
+                self.settings.render_distance,
+            );
+        }
+        if let Some(target) = &self.target {
+            self.outline.draw(&self.assets, &self.camera, target);
+            if let Some(mining) = self.game.as_ref().and_then(Game::mining) {
+                self.breaking
+                    .draw(&self.assets, &self.camera, target, mining);
+            }
+        }
+        if self.debug_view.chunk_borders {
+            self.chunk_borders.draw(&self.assets, &self.camera);
+        }
+        let falling = self
+            .game
+            .as_ref()
+            .and_then(|game| game.precipitation(self.camera.position));
+        if let (Some((kind, strength)), Some(time)) = (falling, self.world_time()) {
+            let seconds = time * TICK_SECONDS as f64;
+            self.precipitation
+                .draw(&self.assets, &self.camera, kind, strength, seconds);
+        }
+        self.particles
+            .draw(&self.assets, &self.camera, cycle.daylight);
+        if let Some(game) = &self.game {
+            self.signs.draw(
+                &self.assets,
+                &mut self.text,
+                &self.camera,
+                &game.world,
+                &self.blocks,
+            );
+        }
+        if let Some(game) = &self.game {
+            let light = game
+                .world
+                .light(self.camera.position.map(|value| value.floor() as i32))
+                .unwrap_or(Light::OPEN);
+            self.held_item.draw(
+                &self.assets,
+                self.world_renderer.textures(),
+                &self.item_textures,
+                self.camera.aspect,
+                light,
+                cycle.daylight,
+            );
+        }
+    }
+
+    /// A held light source lights up the blocks around the player straight away, the light
+    /// levels only change once it's placed
+    fn point_lights(&self) -> Vec<PointLight> {
+        let held = self
+            .game
+            .as_ref()
+            .and_then(Game::held_block)
+            .and_then(|id| self.blocks.get(id));
+        match held {
+            Some(block) if block.light > 0 => vec![PointLight {
+                position: self.camera.position,
+                color: HELD_LIGHT_COLOR,
+                radius: block.light as f32,
+            }],
+            _ => Vec::new(),
+        }
+    }
+
+    fn draw_menus(&mut self, state: GameState, screen_size: [f32; 2]) {
+        let mut ui = Ui::new(&mut self.text, &self.ui_input, screen_size);
+        match state {
+            GameState::MainMenu => match self.main_menu.draw(&mut ui) {
+                Some(MainMenuAction::Play(world)) => self.open_world(world),
+                Some(MainMenuAction::Import { name, source }) => self.start_import(name, source),
+                Some(MainMenuAction::OpenSettings) => self.open_settings(),
+                Some(MainMenuAction::Quit) => self.window.set_should_close(true),
+                None => {}
+            },
+            GameState::Loading => {
+                let y = (screen_size[1] / 2.0).round();
+                ui.label_centered(&tr("menu.loading_world"), y, [1.0, 1.0, 1.0, 1.0]);
+            }
+            GameState::Dead => match death_screen::draw(&mut ui) {
+                Some(DeathAction::Respawn) => self.respawn(),
+                Some(DeathAction::QuitToTitle) => self.close_world(),
+                None => {}
+            },
+            GameState::Paused => match pause_menu::draw(&mut ui) {
+                Some(PauseAction::Resume) => self.set_paused(false),
+                Some(PauseAction::OpenSettings) => self.open_settings(),
+                Some(PauseAction::QuitToTitle) => self.close_world(),
+                None => {}
+            },
+            GameState::Console => {
+                let line = self.console.draw(&mut ui, self.game.as_ref());
+                if let (Some(line), Some(game)) = (line, &mut self.game) {
+                    let mut context = CommandContext {
+                        game,
+                        debug_view: &mut self.debug_view,
+                        clipboard: &mut self.clipboard,
+                    };
+                    self.console.execute(&mut context, &line);
+                    self.apply_debug_view();
+                }
+            }
+            GameState::Settings => {
+                let previous = self.settings.clone();
+                let action = self.settings_menu.draw(&mut ui, &mut self.settings);
+                if self.settings != previous {
+                    self.apply_settings(&previous);
+                }
+                if action == Some(SettingsAction::Done) {
+                    self.close_settings();
+                }
+            }
+            GameState::Inventory => {
+                let Some(game) = &mut self.game else {
+                    return;
+                };
+                let levels = (game.game_mode() == GameMode::Survival)
+                    .then(|| experience::level(game.experience()));
+                let (inventory, block_entity) = match self.inventory_screen.block_position() {
+                    Some(position) => match game.container_mut(position) {
+                        Some((inventory, entity)) => (inventory, Some(entity)),
+                        None => return,
+                    },
+                    None => match game.inventory_mut() {
+                        Some(inventory) => (inventory, None),
+                        None => return,
+                    },
+                };
+                let context = HudContext {
+                    screen_size,
+                    icons: &self.icons,
+                    items: &self.items,
+                    inventory: None,
+                    health: None,
+                    breath: None,
+                    hunger: None,
+                    experience: None,
+                    sleep: 0.0,
+                };
+                let screen = &mut self.inventory_screen;
+                let input = &self.ui_input;
+                let items = ItemContext {
+                    icons: &self.icons,
+                    items: &self.items,
+                    recipes: &self.recipes,
+                    levels,
+                };
+                let mut spent = 0;
+                self.hud
+                    .draw_with(&self.assets, &mut self.text, &context, |batch| {
+                        spent =
+                            screen.draw(batch, input, screen_size, inventory, block_entity, &items)
+                    });
+                if spent > 0 {
+                    game.spend_levels(spent);
+                }
+            }
+            GameState::EditSign => {
+                if self
+                    .sign_editor
+                    .as_mut()
+                    .is_some_and(|editor| editor.draw(&mut ui))
+                {
+                    self.close_sign_editor();
+                }
+            }
+            GameState::Playing => {}
+        }
+    }
+
+    fn open_world(&mut self, mut world: WorldInfo) {
+        world.touch();
+        if let Err(err) = world.save() {
+            self.messages.push(format!("{err:#}"));
+        }
+        self.game = Some(Game::new(
+            world,
+            Arc::clone(&self.blocks),
+            Arc::clone(&self.items),
+            Arc::clone(&self.smelting),
+            Arc::clone(&self.mobs),
+            Arc::clone(&self.features),
+            self.settings.render_distance,
+        ));
+        self.tick_accumulator = 0.0;
+        self.states.reset(GameState::Loading);
+        self.sync_cursor();
+        self.update_title();
+    }
+
+    /// Runs on its own thread, the world opens once it's done
+    fn start_import(&mut self, name: String, source: PathBuf) {
+        if self.import.is_some() {
+            self.messages.push(tr("worlds.import_busy"));
+            return;
+        }
+        let mapping = match BlockMapping::load(&self.assets, &self.blocks) {
+            Ok(mapping) => mapping,
+            Err(err) => {
+                self.messages.push(format!("{err:#}"));
+                return;
+            }
+        };
+        self.messages
+            .push(tr_args("worlds.importing", &[("name", &name)]));
+        let blocks = Arc::clone(&self.blocks);
+        let items = Arc::clone(&self.items);
+        let spawned = thread::Builder::new()
+            .name(String::from("importer"))
+            .spawn(move || anvil::import_world(&source, &name, blocks, items, &mapping));
+        match spawned {
+            Ok(thread) => self.import = Some(thread),
+            Err(err) => self.messages.push(format!("{err}")),
+        }
+    }
+
+    /// Opens the imported world once it's ready, unless the player went into another one
+    /// meanwhile
+    fn poll_import(&mut self) {
+        if !self.import.as_ref().is_some_and(JoinHandle::is_finished) {
+            return;
+        }
+        let Some(thread) = self.import.take() else {
+            return;
+        };
+        match thread.join() {
+            Ok(Ok(world)) if self.states.current() == GameState::MainMenu => self.open_world(world),
+            Ok(Ok(world)) => self
+                .messages
+                .push(tr_args("worlds.imported", &[("name", &world.name)])),
+            Ok(Err(err)) => self.messages.push(format!("{err:#}")),
+            // The crash hook already reported it
+            Err(_) => log::error!("The importer thread panicked"),
+        }
+    }
+
+    fn close_world(&mut self) {
+        self.put_back_held_stack();
+        if let Some(mut game) = self.game.take() {
+            if let Err(err) = game.save() {
+                self.messages.push(format!("{err:#}"));
+            }
+        }
+        self.world_renderer.clear();
+        self.entity_renderer.clear(&mut self.assets);
+        self.particles.clear();
+        self.audio.stop_all();
+        self.main_menu = MainMenu::new();
+        self.states.reset(GameState::MainMenu);
+        self.sync_cursor();
+        self.update_title();
+    }
+
+    /// Last chance to keep the open world's progress after a panic, the rest of the app may
+    /// be in any state so this touches nothing but the save
+    pub fn emergency_save(&mut self) {
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        match game.save() {
+            Ok(()) => log::info!("Saved '{}' after the crash", game.info.name),
+            Err(err) => log::error!("Emergency save failed: {err:#}"),
+        }
+    }
+
+    /// Only while playing, commands act on the open world
+    fn open_console(&mut self) {
+        if self.states.current() != GameState::Playing {
+            return;
+        }
+        self.console.open();
+        self.states.push(GameState::Console);
+        self.sync_cursor();
+    }
+
+    fn close_console(&mut self) {
+        self.states.pop();
+        self.sync_cursor();
+    }
+
+    fn open_inventory(&mut self, screen: InventoryScreen) {
+        self.inventory_screen = screen;
+        self.states.push(GameState::Inventory);
+        self.sync_cursor();
+    }
+
+    fn close_inventory(&mut self) {
+        self.put_back_held_stack();
+        self.states.pop();
+        self.sync_cursor();
+    }
+
+    fn open_sign_editor(&mut self, position: Point3<i32>) {
+        let Some(lines) = self
+            .game
+            .as_mut()
+            .and_then(|game| game.sign_text_mut(position))
+        else {
+            return;
+        };
+        self.sign_editor = Some(SignEditor::new(position, lines));
+        self.states.push(GameState::EditSign);
+        self.sync_cursor();
+    }
+
+    /// Write what was typed onto the sign, if it's still there
+    fn close_sign_editor(&mut self) {
+        if let (Some(editor), Some(game)) = (self.sign_editor.take(), &mut self.game) {
+            if let Some(lines) = game.sign_text_mut(editor.position()) {
+                for (line, text) in lines.iter_mut().zip(editor.lines) {
+                    *line = text;
+                }
+            }
+        }
+        self.states.pop();
+        self.sync_cursor();
+    }
+
+    /// Whatever the inventory screen had picked up or laid out for crafting goes back into
+    /// the inventory, what doesn't fit is dropped
+    fn put_back_held_stack(&mut self) {
+        let Some(game) = &mut self.game else {
+            return;
+        };
+        let Some(inventory) = game.inventory_mut() else {
+            return;
+        };
+        for stack in self.inventory_screen.close(inventory, &self.items) {
+            game.drop_stack(stack);
+        }
+    }
+
+    fn open_settings(&mut self) {
+        let languages = i18n::languages(&self.assets).unwrap_or_else(|err| {
+            self.messages.push(format!("{err:#}"));
+            Vec::new()
+        });
+        self.settings_menu = SettingsMenu::new(languages, assets::available_packs());
+        self.states.push(GameState::Settings);
+        self.sync_cursor();
+    }
+
+    /// Changes are already live by now, closing only writes them out
+    fn close_settings(&mut self) {
+        self.settings_menu.cancel_rebind();
+        crash::set_settings(&self.settings);
+        if let Err(err) = self.settings.save(Path::new(SETTINGS_PATH)) {
+            self.messages.push(format!("{err:#}"));
+        }
+        self.states.pop();
+        self.sync_cursor();
+    }
+
+    /// Swap in the chosen resource packs and redo what was built from their files. Blocks,
+    /// items and the rest of the game data stay as they are until the next start.
+    fn apply_resource_packs(&mut self) {
+        self.assets
+            .set_resource_packs(&self.settings.resource_packs);
+        if let Err(err) = i18n::set_language(&self.assets, &self.settings.language) {
+            self.messages.push(format!("{err:#}"));
+        }
+        let result = self
+            .world_renderer
+            .reload_textures(&self.assets, &self.blocks)
+            .and_then(|()| ItemTextures::load(&self.assets, &self.items))
+            .and_then(|item_textures| {
+                self.item_textures = item_textures;
+                IconAtlas::bake(
+                    &mut self.assets,
+                    &self.blocks,
+                    &self.items,
+                    self.world_renderer.textures(),
+                    &self.item_textures,
+                )
+                .context("Failed to draw the item icons")
+            });
+        match result {
+            Ok(icons) => self.icons = icons,
+            Err(err) => self.messages.push(format!("{err:#}")),
+        }
+        // Baking the icons leaves its own viewport behind
+        let (width, height) = self.window.get_framebuffer_size();
+        unsafe {
+            gl::Viewport(0, 0, width, height);
+        }
+    }
+
+    /// Push whatever differs from `previous` to the window and renderer
+    fn apply_settings(&mut self, previous: &Settings) {
+        if self.settings.vsync != previous.vsync {
+            self.apply_video_settings();
+        }
+        if self.settings.deferred != previous.deferred {
+            self.update_deferred();
+        } else if self.settings.ssao != previous.ssao {
+            self.update_ssao();
+        }
+        if self.settings.msaa_samples != previous.msaa_samples
+            || self.settings.deferred != previous.deferred
+        {
+            self.update_scene_target();
+        }
+        if self.settings.shadows != previous.shadows {
+            self.update_shadow_map();
+        }
+        if self.settings.fancy_water != previous.fancy_water {
+            self.update_water();
+        }
+        self.camera.fov = Deg(self.settings.fov);
+        self.audio.set_volume(self.settings.volume);
+        if let Some(game) = &mut self.game {
+            game.set_render_distance(self.settings.render_distance);
+        }
+        let meshing_changed = self.settings.meshing != previous.meshing
+            || self.settings.smooth_lighting != previous.smooth_lighting;
+        if meshing_changed {
+            self.world_renderer.set_meshing(self.settings.meshing);
+            self.world_renderer
+                .set_smooth_lighting(self.settings.smooth_lighting);
+            if let Some(game) = &mut self.game {
+                game.world.mark_all_dirty();
+            }
+        }
+        self.world_renderer
+            .set_lod_distance(self.settings.lod_distance);
+        self.clouds
+            .set_layer(self.settings.cloud_height, self.settings.cloud_density);
+        if self.settings.resource_packs != previous.resource_packs {
+            self.apply_resource_packs();
+        } else if self.settings.language != previous.language {
+            if let Err(err) = i18n::set_language(&self.assets, &self.settings.language) {
+                self.messages.push(format!("{err:#}"));
+            }
+        }
+        if self.settings.fullscreen_mode != previous.fullscreen_mode && self.display.is_fullscreen()
+        {
+            // Go back through windowed so the new mode starts from the saved window rect
+            self.display.leave_fullscreen(&mut self.window);
+            self.display.enter_fullscreen(
+                &mut self.glfw,
+                &mut self.window,
+                self.settings.fullscreen_mode,
+                self.settings.monitor,
+            );
+        }
+    }
+
+    /// Leave the death screen, back on their bed or at the world spawn
+    fn respawn(&mut self) {
+        if let Some(game) = &mut self.game {
+            if !game.respawn() {
+                self.messages.push(tr("message.bed_missing"));
+            }
+        }
+        self.states.pop();
+        self.sync_cursor();
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        match (self.states.current(), paused) {
+            (GameState::Playing, true) => self.states.push(GameState::Paused),
+            (GameState::Paused, false) => self.states.pop(),
+            _ => return,
+        }
+        self.sync_cursor();
+    }
+
+    #[cfg(feature = "debug-ui")]
+    fn toggle_debug_ui(&mut self) {
+        self.debug_ui.toggle();
+        self.sync_cursor();
+    }
+
+    #[cfg(not(feature = "debug-ui"))]
+    fn toggle_debug_ui(&mut self) {
+        self.messages.push(tr("message.no_debug_ui"));
+    }
+
+    /// Mouse look is off while a menu or the debug tools need the pointer
+    fn captures_cursor(&self) -> bool {
+        #[cfg(feature = "debug-ui")]
+        if self.debug_ui.visible {
+            return false;
+        }
+        self.states.current().captures_cursor()
+    }
+
+    /// Lock or release the cursor to match the current state, forgetting the last cursor
+    /// position so the camera doesn't jump when it gets captured again
+    fn sync_cursor(&mut self) {
+        let mode = if self.captures_cursor() {
+            CursorMode::Disabled
+        } else {
+            CursorMode::Normal
+        };
+        self.window.set_cursor_mode(mode);
+        // Raw motion only ever applies to a disabled cursor
+        if self.glfw.supports_raw_motion() {
+            self.window.set_raw_mouse_motion(
+                mode == CursorMode::Disabled && self.settings.raw_mouse_motion,
+            );
+        }
+        self.last_cursor = None;
+        self.mouse_delta = [0.0; 2];
+    }
+
+    fn gui_scale(&self) -> f32 {
+        let (width, height) = self.window.get_framebuffer_size();
+        ui::gui_scale([width as f32, height as f32], self.settings.gui_scale)
+    }
+
+    /// Push the video part of the settings to the window, safe to call again after they change
+    fn apply_video_settings(&mut self) {
+        self.glfw.set_swap_interval(if self.settings.vsync {
+            glfw::SwapInterval::Sync(1)
+        } else {
+            glfw::SwapInterval::None
+        });
+    }
+
+    /// (Re)create the multisampled world target to match the window and MSAA setting. The
+    /// deferred path can't resolve into a multisampled target, so there's none while it's on.
+    fn update_scene_target(&mut self) {
+        self.scene_target = None;
+        let samples = self.settings.msaa_samples;
+        if samples == 0 || self.deferred.is_some() {
+            return;
+        }
+        let (width, height) = self.window.get_framebuffer_size();
+        match Framebuffer::new(width.max(1) as u32, height.max(1) as u32, samples) {
+            Ok(target) => self.scene_target = Some(target),
+            Err(err) => self
+                .messages
+                .push(format!("{samples}x MSAA is not available: {err}")),
+        }
+    }
+
+    /// Create or drop the G-buffer to match the setting
+    fn update_deferred(&mut self) {
+        self.deferred = None;
+        if !self.settings.deferred {
+            return;
+        }
+        let (width, height) = self.window.get_framebuffer_size();
+        match DeferredRenderer::new(
+            &mut self.assets,
+            width.max(1) as u32,
+            height.max(1) as u32,
+            self.settings.ssao,
+        ) {
+            Ok(mut deferred) => {
+                deferred.set_debug_shading(self.debug_view.shading);
+                self.deferred = Some(deferred);
+            }
+            Err(err) => self
+                .messages
+                .push(format!("Deferred rendering is not available: {err:#}")),
+        }
+    }
+
+    /// Keep the G-buffer the size of the window, falling back to forward if that fails
+    fn resize_deferred(&mut self) {
+        let Some(deferred) = &mut self.deferred else {
+            return;
+        };
+        let (width, height) = self.window.get_framebuffer_size();
+        if let Err(err) = deferred.resize(width.max(1) as u32, height.max(1) as u32) {
+            self.deferred = None;
+            self.messages.push(format!("{err:#}"));
+            self.update_scene_target();
+        }
+    }
+
+    /// Turn ambient occlusion off when its target can't be made, deferred rendering still
+    /// works without it
+    fn update_ssao(&mut self) {
+        let Some(deferred) = &mut self.deferred else {
+            return;
+        };
+        if let Err(err) = deferred.set_ssao(self.settings.ssao) {
+            self.messages.push(format!("{err:#}"));
+            self.settings.ssao = SsaoQuality::Off;
+        }
+    }
+
+    /// Hand the debug view to the renderers after it changed
+    fn apply_debug_view(&mut self) {
+        self.world_renderer.set_debug_view(self.debug_view);
+        self.entity_renderer.set_debug_view(self.debug_view);
+        if let Some(deferred) = &mut self.deferred {
+            deferred.set_debug_shading(self.debug_view.shading);
+        }
+    }
+
+    /// Create or drop the shadow maps to match the setting
+    fn update_shadow_map(&mut self) {
+        self.shadow_map = None;
+        if !self.settings.shadows {
+            return;
+        }
+        match ShadowMap::new(&mut self.assets) {
+            Ok(shadow_map) => self.shadow_map = Some(shadow_map),
+            Err(err) => self
+                .messages
+                .push(format!("Shadows are not available: {err:#}")),
+        }
+    }
+
+    /// Create or drop the water reflections to match the setting
+    fn update_water(&mut self) {
+        self.water = None;
+        if !self.settings.fancy_water {
+            return;
+        }
+        let (width, height) = self.window.get_framebuffer_size();
+        match WaterSurface::new(width.max(1) as u32, height.max(1) as u32) {
+            Ok(water) => self.water = Some(water),
+            Err(err) => self
+                .messages
+                .push(format!("Fancy water is not available: {err:#}")),
+        }
+    }
+
+    /// Keep the reflections the size of the window, falling back to plain water if that fails
+    fn resize_water(&mut self) {
+        let Some(water) = &mut self.water else {
+            return;
+        };
+        let (width, height) = self.window.get_framebuffer_size();
+        if let Err(err) = water.resize(width.max(1) as u32, height.max(1) as u32) {
+            self.water = None;
+            self.messages.push(format!("{err:#}"));
+        }
+    }
+
+    fn update_title(&mut self) {
+        self.last_title_update = Instant::now();
+        let fps = self.debug_overlay.fps().round();
+        let title = match &self.game {
+            Some(game) => format!("{GAME_NAME} - {} - {fps} FPS", game.info.name),
+            None => format!("{GAME_NAME} - {fps} FPS"),
+        };
+        self.window.set_title(&title);
+    }
+
+    /// The icon goes through the resource packs like any other asset, a missing one only
+    /// costs a message
+    fn load_icon(&mut self) {
+        let icon = self.assets.read(ICON_PATH).and_then(|bytes| {
+            image::load_from_memory(&bytes).with_context(|| format!("Failed to decode {ICON_PATH}"))
+        });
+        let icon = match icon {
+            Ok(icon) => icon.into_rgba8(),
+            Err(err) => {
+                self.messages.push(format!("No window icon: {err:#}"));
+                return;
+            }
+        };
+
+        let pixels = icon
+            .pixels()
+            .map(|pixel| u32::from_le_bytes(pixel.0))
+            .collect();
+        self.window.set_icon_from_pixels(vec![glfw::PixelImage {
+            width: icon.width(),
+            height: icon.height(),
+            pixels,
+        }]);
+    }
+
+    fn toggle_fullscreen(&mut self) {
+        if self.display.is_fullscreen() {
+            self.display.leave_fullscreen(&mut self.window);
+        } else if !self.display.enter_fullscreen(
+            &mut self.glfw,
+            &mut self.window,
+            self.settings.fullscreen_mode,
+            self.settings.monitor,
+        ) {
+            self.messages.push(tr("message.no_monitor"));
+        }
+
+        self.settings.fullscreen = self.display.is_fullscreen();
+        if let Err(err) = self.settings.save(Path::new(SETTINGS_PATH)) {
+            self.messages.push(format!("{err:#}"));
+        }
+    }
+
+    /// Captures the frame that was just rendered, so it has to run before swapping
+    fn take_screenshot(&mut self) {
+        let (width, height) = self.window.get_framebuffer_size();
+        if let Err(err) = screenshot::capture(width as u32, height as u32, self.messages.sender()) {
+            self.messages.push(format!("{err:#}"));
+        }
+    }
+}
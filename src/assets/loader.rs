@@ -0,0 +1,94 @@
+use anyhow::{bail, Context};
+use gl_lib::{FragmentShader, Shader, ShaderProgram, Texture2D, VertexShader};
+use std::ffi::CString;
+
+use super::{Asset, Assets, PackStack, Storage};
+
+impl Asset for Texture2D {
+    fn load(packs: &PackStack, path: &str) -> anyhow::Result<Self> {
+        let bytes = packs.read(path)?;
+        let image = image::load_from_memory(&bytes)
+            .with_context(|| format!("Failed to decode texture {path}"))?
+            .flipv()
+            .into_rgba8();
+        Texture2D::from_rgba8(image.width(), image.height(), image.as_raw())
+            .with_context(|| format!("Failed to upload texture {path}"))
+    }
+
+    fn storage(assets: &Assets) -> &Storage<Self> {
+        &assets.textures
+    }
+
+    fn storage_mut(assets: &mut Assets) -> &mut Storage<Self> {
+        &mut assets.textures
+    }
+}
+
+/// A program is addressed without extension, `shader/chunk` links `shader/chunk.vert` with
+/// `shader/chunk.frag`
+impl Asset for ShaderProgram {
+    fn load(packs: &PackStack, path: &str) -> anyhow::Result<Self> {
+        let vertex_path = format!("{path}.vert");
+        let fragment_path = format!("{path}.frag");
+        let vertex: VertexShader = load_shader(packs, &vertex_path)?;
+        let fragment: FragmentShader = load_shader(packs, &fragment_path)?;
+        ShaderProgram::new(&[&vertex, &fragment])
+            .with_context(|| format!("Failed to link shader program {path}"))
+    }
+
+    fn depends_on(path: &str, file: &str) -> bool {
+        file.strip_prefix(path)
+            .is_some_and(|extension| extension == ".vert" || extension == ".frag")
+    }
+
+    fn storage(assets: &Assets) -> &Storage<Self> {
+        &assets.programs
+    }
+
+    fn storage_mut(assets: &mut Assets) -> &mut Storage<Self> {
+        &mut assets.programs
+    }
+}
+
+/// How deep `#include`s may nest before it's taken for a cycle
+const MAX_INCLUDE_DEPTH: u32 = 8;
+
+fn load_shader<T: Shader>(packs: &PackStack, path: &str) -> anyhow::Result<T> {
+    let source = read_shader_source(packs, path, 0)?;
+    let source =
+        CString::new(source).with_context(|| format!("Shader {path} contains a nul byte"))?;
+    T::from_cstr(&source).with_context(|| format!("Failed to build shader {path}"))
+}
+
+/// Source with every `#include "file"` line replaced by that file, relative to the including
+/// one. Editing an included file doesn't reload the programs using it.
+fn read_shader_source(packs: &PackStack, path: &str, depth: u32) -> anyhow::Result<String> {
+    if depth > MAX_INCLUDE_DEPTH {
+        bail!("Shader includes nest too deep at {path}");
+    }
+    let source = packs.read_to_string(path)?;
+    let directory = path.rsplit_once('/').map_or("", |(directory, _)| directory);
+    let mut resolved = String::with_capacity(source.len());
+    for (number, line) in source.lines().enumerate() {
+        let Some(include) = line.trim().strip_prefix("#include") else {
+            resolved.push_str(line);
+            resolved.push('\n');
+            continue;
+        };
+        let Some(file) = include
+            .trim()
+            .strip_prefix('"')
+            .and_then(|rest| rest.strip_suffix('"'))
+        else {
+            bail!("Malformed include on line {} of {path}", number + 1);
+        };
+        let included = match directory {
+            "" => file.to_string(),
+            directory => format!("{directory}/{file}"),
+        };
+        let text = read_shader_source(packs, &included, depth + 1)
+            .with_context(|| format!("Failed to include {included} in {path}"))?;
+        resolved.push_str(&text);
+    }
+    Ok(resolved)
+}
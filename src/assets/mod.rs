@@ -0,0 +1,138 @@
+mod loader;
+mod pack;
+mod storage;
+mod watcher;
+
+use gl_lib::{ShaderProgram, Texture2D};
+use std::path::PathBuf;
+
+pub use pack::{available_packs, Pack, PackStack, RESOURCE_PACK_DIR};
+pub use storage::{Handle, Storage};
+use watcher::Watcher;
+
+/// Anything [`Assets`] knows how to load from a logical path
+pub trait Asset: Sized + 'static {
+    fn load(packs: &PackStack, path: &str) -> anyhow::Result<Self>;
+
+    /// Whether a change to `file` means the asset loaded from `path` is outdated
+    fn depends_on(path: &str, file: &str) -> bool {
+        path == file
+    }
+
+    fn storage(assets: &Assets) -> &Storage<Self>;
+    fn storage_mut(assets: &mut Assets) -> &mut Storage<Self>;
+}
+
+/// Owns every loaded asset, keyed by its path relative to the asset directory
+/// (e.g. `icon.png` or `shader/chunk`) and resolved through the resource pack stack
+pub struct Assets {
+    builtin: PathBuf,
+    packs: PackStack,
+    watchers: Vec<Watcher>,
+    textures: Storage<Texture2D>,
+    programs: Storage<ShaderProgram>,
+}
+
+impl Assets {
+    /// `resource_packs` are names inside [`pack::RESOURCE_PACK_DIR`], highest priority first
+    pub fn new(builtin: impl Into<PathBuf>, resource_packs: &[String]) -> Self {
+        let builtin = builtin.into();
+        let packs = PackStack::new(&builtin, resource_packs);
+        Assets {
+            watchers: watch(&packs),
+            builtin,
+            packs,
+            textures: Storage::new(),
+            programs: Storage::new(),
+        }
+    }
+
+    /// Swap the pack stack and reload everything already loaded from it
+    pub fn set_resource_packs(&mut self, resource_packs: &[String]) {
+        self.packs = PackStack::new(&self.builtin, resource_packs);
+        self.watchers = watch(&self.packs);
+        self.reload_matching::<Texture2D>(None);
+        self.reload_matching::<ShaderProgram>(None);
+    }
+
+    /// Loading the same path twice hands back the same handle
+    pub fn load<T: Asset>(&mut self, path: &str) -> anyhow::Result<Handle<T>> {
+        if let Some(handle) = T::storage(self).find(path) {
+            return Ok(handle);
+        }
+        let value = T::load(&self.packs, path)?;
+        Ok(T::storage_mut(self).insert(path, value))
+    }
+
+    /// None once the asset got unloaded
+    pub fn get<T: Asset>(&self, handle: Handle<T>) -> Option<&T> {
+        T::storage(self).get(handle)
+    }
+
+    /// Frees the asset, every copy of the handle goes stale
+    pub fn unload<T: Asset>(&mut self, handle: Handle<T>) -> bool {
+        T::storage_mut(self).remove(handle).is_some()
+    }
+
+    /// Rough VRAM used by textures, assuming each has a full mip chain
+    pub fn gpu_memory_estimate(&self) -> usize {
+        self.textures
+            .values()
+            .map(|texture| {
+                let bytes = texture.format().bytes_per_pixel();
+                texture.width() as usize * texture.height() as usize * bytes * 4 / 3
+            })
+            .sum()
+    }
+
+    /// Raw bytes of any file in the pack stack, for assets that aren't cached
+    pub fn read(&self, path: &str) -> anyhow::Result<Vec<u8>> {
+        self.packs.read(path)
+    }
+
+    /// Reload every asset whose files changed on disk, handles stay valid and keep pointing
+    /// at the old version if the new one fails to load
+    pub fn hot_reload(&mut self) {
+        let changed: Vec<String> = self
+            .watchers
+            .iter_mut()
+            .flat_map(|watcher| watcher.poll())
+            .collect();
+        if changed.is_empty() {
+            return;
+        }
+        self.reload_matching::<Texture2D>(Some(&changed));
+        self.reload_matching::<ShaderProgram>(Some(&changed));
+    }
+
+    /// Reload assets depending on any of the `changed` files, or all of them
+    fn reload_matching<T: Asset>(&mut self, changed: Option<&[String]>) {
+        let outdated: Vec<String> = T::storage(self)
+            .paths()
+            .filter(|path| {
+                changed.is_none_or(|changed| changed.iter().any(|file| T::depends_on(path, file)))
+            })
+            .map(str::to_owned)
+            .collect();
+
+        for path in outdated {
+            match T::load(&self.packs, &path) {
+                Ok(value) => {
+                    T::storage_mut(self).replace(&path, value);
+                    log::info!("Reloaded {path}");
+                }
+                Err(err) => log::warn!("Failed to reload {path}: {err:#}"),
+            }
+        }
+    }
+}
+
+/// Zipped packs are read once, only directories get watched
+fn watch(packs: &PackStack) -> Vec<Watcher> {
+    packs
+        .packs()
+        .iter()
+        .filter_map(Pack::directory)
+        .map(Watcher::new)
+        .collect()
+}
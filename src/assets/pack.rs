@@ -0,0 +1,129 @@
+use anyhow::{anyhow, Context};
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+pub const RESOURCE_PACK_DIR: &str = "resourcepacks";
+
+/// A directory or zip file laid out like `asset/`
+pub struct Pack {
+    name: String,
+    kind: PackKind,
+}
+
+enum PackKind {
+    Directory(PathBuf),
+    Zip(Mutex<ZipArchive<File>>),
+}
+
+impl Pack {
+    pub fn open(name: &str, path: &Path) -> anyhow::Result<Self> {
+        let kind = if path.is_dir() {
+            PackKind::Directory(path.to_owned())
+        } else {
+            let file = File::open(path)
+                .with_context(|| format!("Failed to open resource pack {}", path.display()))?;
+            let archive = ZipArchive::new(file)
+                .with_context(|| format!("Resource pack {} is not a zip", path.display()))?;
+            PackKind::Zip(Mutex::new(archive))
+        };
+        Ok(Pack {
+            name: name.to_owned(),
+            kind,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Only directories can be watched for changes
+    pub fn directory(&self) -> Option<&Path> {
+        match &self.kind {
+            PackKind::Directory(path) => Some(path),
+            PackKind::Zip(_) => None,
+        }
+    }
+
+    /// None when this pack doesn't provide `path`
+    fn read(&self, path: &str) -> Option<io::Result<Vec<u8>>> {
+        match &self.kind {
+            PackKind::Directory(root) => match fs::read(root.join(path)) {
+                Err(err) if err.kind() == ErrorKind::NotFound => None,
+                result => Some(result),
+            },
+            PackKind::Zip(archive) => {
+                let mut archive = archive.lock().unwrap_or_else(|err| err.into_inner());
+                let mut file = match archive.by_name(path) {
+                    Ok(file) => file,
+                    Err(ZipError::FileNotFound) => return None,
+                    Err(err) => return Some(Err(io::Error::other(err))),
+                };
+                let mut bytes = Vec::with_capacity(file.size() as usize);
+                Some(file.read_to_end(&mut bytes).map(|_| bytes))
+            }
+        }
+    }
+}
+
+/// Names of the directories and zip files in [`RESOURCE_PACK_DIR`] in alphabetical order,
+/// none if it doesn't exist
+pub fn available_packs() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(RESOURCE_PACK_DIR) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| {
+            let path = entry.path();
+            path.is_dir() || path.extension().is_some_and(|extension| extension == "zip")
+        })
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Resource packs in priority order, the built-in assets are always at the bottom so every
+/// lookup has a defined fallback
+pub struct PackStack {
+    packs: Vec<Pack>,
+}
+
+impl PackStack {
+    pub fn new(builtin: &Path, selected: &[String]) -> Self {
+        let mut packs = Vec::with_capacity(selected.len() + 1);
+        for name in selected {
+            match Pack::open(name, &Path::new(RESOURCE_PACK_DIR).join(name)) {
+                Ok(pack) => packs.push(pack),
+                Err(err) => log::warn!("Skipping resource pack {name}: {err:#}"),
+            }
+        }
+        packs.push(Pack {
+            name: String::from("built-in"),
+            kind: PackKind::Directory(builtin.to_owned()),
+        });
+        PackStack { packs }
+    }
+
+    pub fn packs(&self) -> &[Pack] {
+        &self.packs
+    }
+
+    pub fn read(&self, path: &str) -> anyhow::Result<Vec<u8>> {
+        for pack in &self.packs {
+            if let Some(result) = pack.read(path) {
+                return result
+                    .with_context(|| format!("Failed to read {path} from {}", pack.name()));
+            }
+        }
+        Err(anyhow!("{path} is missing from every resource pack"))
+    }
+
+    pub fn read_to_string(&self, path: &str) -> anyhow::Result<String> {
+        String::from_utf8(self.read(path)?).with_context(|| format!("{path} is not valid UTF-8"))
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Cheap reference to an asset owned by [`super::Assets`], it goes stale once the asset is
+/// unloaded even if the slot gets reused
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+// Derives would put the bounds on T, which is never stored
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Handle({}v{})", self.index, self.generation)
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    path: String,
+    value: Option<T>,
+}
+
+pub struct Storage<T> {
+    slots: Vec<Slot<T>>,
+    by_path: HashMap<String, Handle<T>>,
+    free: Vec<u32>,
+}
+
+impl<T> Storage<T> {
+    pub fn new() -> Self {
+        Storage {
+            slots: Vec::new(),
+            by_path: HashMap::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub fn find(&self, path: &str) -> Option<Handle<T>> {
+        self.by_path.get(path).copied()
+    }
+
+    pub fn insert(&mut self, path: &str, value: T) -> Handle<T> {
+        let index = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.path = path.to_owned();
+                slot.value = Some(value);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    path: path.to_owned(),
+                    value: Some(value),
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+
+        let handle = Handle {
+            index,
+            generation: self.slots[index as usize].generation,
+            _marker: PhantomData,
+        };
+        self.by_path.insert(path.to_owned(), handle);
+        handle
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.slot(handle).and_then(|slot| slot.value.as_ref())
+    }
+
+    /// Swap the value behind every handle pointing at `path`
+    pub fn replace(&mut self, path: &str, value: T) -> bool {
+        match self.by_path.get(path) {
+            Some(handle) => {
+                self.slots[handle.index as usize].value = Some(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().filter_map(|slot| slot.value.as_ref())
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.by_path.keys().map(String::as_str)
+    }
+
+    pub fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.by_path.remove(&slot.path);
+        self.free.push(handle.index);
+        Some(value)
+    }
+
+    fn slot(&self, handle: Handle<T>) -> Option<&Slot<T>> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+    }
+}
+
+impl<T> Default for Storage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
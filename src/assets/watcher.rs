@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+const SCAN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls modification times under a directory, cheap enough to call every frame since it
+/// only touches the file system every [`SCAN_INTERVAL`]
+pub struct Watcher {
+    root: PathBuf,
+    modified: HashMap<String, SystemTime>,
+    last_scan: Instant,
+}
+
+impl Watcher {
+    pub fn new(root: &Path) -> Self {
+        let mut modified = HashMap::new();
+        scan(root, root, &mut modified);
+        Watcher {
+            root: root.to_owned(),
+            modified,
+            last_scan: Instant::now(),
+        }
+    }
+
+    /// Paths relative to the root, with `/` separators, that were created or modified since
+    /// the previous scan
+    pub fn poll(&mut self) -> Vec<String> {
+        if self.last_scan.elapsed() < SCAN_INTERVAL {
+            return Vec::new();
+        }
+        self.last_scan = Instant::now();
+
+        let mut current = HashMap::new();
+        scan(&self.root, &self.root, &mut current);
+        let changed = current
+            .iter()
+            .filter(|(path, time)| self.modified.get(*path) != Some(*time))
+            .map(|(path, _)| path.clone())
+            .collect();
+        self.modified = current;
+        changed
+    }
+}
+
+fn scan(root: &Path, dir: &Path, modified: &mut HashMap<String, SystemTime>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            scan(root, &path, modified);
+        } else if let (Ok(time), Ok(relative)) = (metadata.modified(), path.strip_prefix(root)) {
+            let relative = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            modified.insert(relative, time);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+pub mod music;
+pub mod sounds;
+
+use cgmath::{InnerSpace, Point3, Vector3};
+use rodio::{OutputStream, OutputStreamHandle, SpatialSink};
+
+use crate::assets::Assets;
+use crate::settings::Volume;
+use crate::world::noise;
+use music::{Music, Scene};
+use sounds::{Category, SoundBank};
+
+/// Most sounds playing at once, new ones are dropped past it
+const MAX_PLAYING: usize = 64;
+/// Blocks between the ears, for telling left from right
+const EAR_DISTANCE: f32 = 0.2;
+/// How far from the middle of the head sources are put, close enough that rodio's own
+/// falloff stays at full volume and only the direction is left for it to work with
+const EMITTER_DISTANCE: f32 = 0.5;
+/// Sounds closer than this many blocks are at full volume
+const FULL_VOLUME_DISTANCE: f32 = 1.0;
+
+/// Where sounds are heard from, the camera while a world is open
+#[derive(Debug, Copy, Clone)]
+pub struct Listener {
+    pub position: Point3<f32>,
+    /// Radians, the same as the camera's
+    pub yaw: f32,
+}
+
+/// A sound that's still playing
+struct Playing {
+    sink: SpatialSink,
+    category: Category,
+    /// Volume of its event before distance and the settings
+    volume: f32,
+    position: Point3<f32>,
+    range: f32,
+}
+
+/// Plays sounds from [`SoundBank`], fading them with distance from the listener and panning
+/// them towards the side they come from, along with the [`Music`]. Without an audio device
+/// everything still works, just silently.
+pub struct Audio {
+    /// Has to stay alive for anything to be heard
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    sounds: SoundBank,
+    music: Music,
+    playing: Vec<Playing>,
+    listener: Listener,
+    volume: Volume,
+    /// Sounds started so far, each one's random picks come from its number
+    started: u64,
+}
+
+impl Audio {
+    pub fn new(assets: &Assets, volume: Volume) -> anyhow::Result<Self> {
+        let sounds = SoundBank::load(assets)?;
+        let music = Music::load(assets)?;
+        let output = OutputStream::try_default()
+            .inspect_err(|err| log::warn!("Playing without sound: {err}"))
+            .ok();
+        Ok(Audio {
+            output,
+            sounds,
+            music,
+            playing: Vec::new(),
+            listener: Listener {
+                position: Point3::new(0.0, 0.0, 0.0),
+                yaw: 0.0,
+            },
+            volume,
+            started: 0,
+        })
+    }
+
+    /// Play the sound event `name` at `position` in the world, e.g. `block.stone.break`.
+    /// Nothing happens for sounds out of earshot.
+    pub fn play_at(&mut self, name: &str, position: Point3<f32>) {
+        let Some((_, handle)) = &self.output else {
+            return;
+        };
+        let Some(event) = self.sounds.get(name) else {
+            log::warn!("Unknown sound event {name}");
+            return;
+        };
+        let distance = (position - self.listener.position).magnitude();
+        if self.playing.len() >= MAX_PLAYING || distance >= event.range {
+            return;
+        }
+
+        let sound = self.started as i64;
+        self.started += 1;
+        let random = |salt| noise::hash_unit(0, &[sound, salt]) as f32;
+        let clip = &event.clips[(random(0) * event.clips.len() as f32) as usize];
+        let [low, high] = event.pitch;
+        let [left, right] = self.ears();
+        let sink = match SpatialSink::try_new(handle, self.emitter(position), left, right) {
+            Ok(sink) => sink,
+            Err(err) => {
+                log::warn!("Failed to play {name}: {err}");
+                return;
+            }
+        };
+        sink.set_speed(low + (high - low) * random(1));
+        sink.append(clip.clone());
+        let playing = Playing {
+            sink,
+            category: event.category,
+            volume: event.volume,
+            position,
+            range: event.range,
+        };
+        playing.sink.set_volume(self.gain(&playing));
+        self.playing.push(playing);
+    }
+
+    /// Move the ears, sounds already playing follow along on the next [`Audio::update`]
+    pub fn set_listener(&mut self, listener: Listener) {
+        self.listener = listener;
+    }
+
+    pub fn set_volume(&mut self, volume: Volume) {
+        self.volume = volume;
+    }
+
+    /// Forget finished sounds and bring the rest in line with the listener and the volume
+    /// settings, once a frame. The music moves on by `delta` seconds to fit `scene`.
+    pub fn update(&mut self, delta: f32, scene: Scene) {
+        if let Some((_, handle)) = &self.output {
+            let volume = self.volume.master * self.volume.music;
+            self.music.update(handle, scene, delta, volume);
+        }
+        self.playing.retain(|playing| !playing.sink.empty());
+        let ears = self.ears();
+        for playing in &self.playing {
+            set_positions(&playing.sink, ears, self.emitter(playing.position));
+            playing.sink.set_volume(self.gain(playing));
+        }
+    }
+
+    /// Cut every sound off, e.g. when leaving a world. The music carries on.
+    pub fn stop_all(&mut self) {
+        for playing in self.playing.drain(..) {
+            playing.sink.stop();
+        }
+    }
+
+    /// Left and right ear, either side of the listener's position
+    fn ears(&self) -> [[f32; 3]; 2] {
+        let right = Vector3::new(self.listener.yaw.cos(), 0.0, self.listener.yaw.sin());
+        let offset = right * EAR_DISTANCE / 2.0;
+        [
+            (self.listener.position - offset).into(),
+            (self.listener.position + offset).into(),
+        ]
+    }
+
+    /// Where to tell rodio a sound at `position` is, only its direction from the listener
+    /// counts. Sounds right at the listener are in the middle of the head.
+    fn emitter(&self, position: Point3<f32>) -> [f32; 3] {
+        let center = self.listener.position;
+        let offset = position - center;
+        let direction = if offset.magnitude2() > 0.0 {
+            offset.normalize()
+        } else {
+            offset
+        };
+        (center + direction * EMITTER_DISTANCE).into()
+    }
+
+    /// The volume settings times how loud the event is, falling off in a straight line from
+    /// full volume close by to nothing at its range
+    fn gain(&self, playing: &Playing) -> f32 {
+        let category = match playing.category {
+            Category::Music => self.volume.music,
+            Category::Blocks => self.volume.blocks,
+            Category::Ambient => self.volume.ambient,
+        };
+        let distance = (playing.position - self.listener.position).magnitude();
+        let fade = (distance - FULL_VOLUME_DISTANCE) / (playing.range - FULL_VOLUME_DISTANCE);
+        self.volume.master * category * playing.volume * (1.0 - fade.clamp(0.0, 1.0))
+    }
+}
+
+fn set_positions(sink: &SpatialSink, ears: [[f32; 3]; 2], emitter: [f32; 3]) {
+    sink.set_emitter_position(emitter);
+    sink.set_left_ear_position(ears[0]);
+    sink.set_right_ear_position(ears[1]);
+}
@@ -0,0 +1,252 @@
+use anyhow::{bail, Context};
+use rodio::{Decoder, OutputStreamHandle, Sink};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::io::Cursor;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::assets::Assets;
+use crate::world::biome::Biome;
+use crate::world::noise;
+
+const PLAYLISTS_PATH: &str = "music/playlists.json";
+
+/// What's going on that the music should fit, worked out every frame
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Scene {
+    /// The title screen and the menus in front of it
+    Menu,
+    /// A world is open, with the listener in `biome`
+    World { biome: Biome, day: bool },
+}
+
+/// When in the day a playlist is for
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Time {
+    Day,
+    Night,
+}
+
+/// `music/playlists.json`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PlaylistsFile {
+    /// Seconds one track takes to fade into the next when the playlist changes
+    crossfade: f32,
+    /// Least and most seconds of silence after a track ends before the next one starts
+    pause: [f32; 2],
+    /// How many other tracks have to play before one comes up again, as far as the playlist
+    /// has that many
+    cooldown: usize,
+    /// Checked in order, the first one that fits the scene plays
+    playlists: Vec<Playlist>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Playlist {
+    /// Plays on the title screen instead of in worlds
+    #[serde(default)]
+    menu: bool,
+    /// Only plays in these biomes, any biome if left out
+    #[serde(default)]
+    biomes: Vec<Biome>,
+    /// Only plays at this time of day, any time if left out
+    time: Option<Time>,
+    /// Under `music/`, shuffled
+    tracks: Vec<String>,
+}
+
+impl Playlist {
+    fn fits(&self, scene: Scene) -> bool {
+        match scene {
+            Scene::Menu => self.menu,
+            Scene::World { biome, day } => {
+                !self.menu
+                    && (self.biomes.is_empty() || self.biomes.contains(&biome))
+                    && self.time.is_none_or(|time| (time == Time::Day) == day)
+            }
+        }
+    }
+}
+
+/// A track that's playing, fading in or out
+struct Track {
+    sink: Sink,
+    /// Index of the playlist it came from
+    playlist: usize,
+    /// From 0 silent to 1 at the music volume
+    fade: f32,
+}
+
+/// Shuffles through the playlist that fits the scene, one track at a time with a pause in
+/// between. Changing to another playlist crossfades straight into it. Tracks are decoded
+/// while they play rather than up front like sound effects.
+pub struct Music {
+    file: PlaylistsFile,
+    /// Every track's file as it is on disk, still compressed. Playing one shares the bytes
+    /// with its decoder instead of copying them.
+    tracks: HashMap<String, Arc<[u8]>>,
+    current: Option<Track>,
+    /// Tracks on their way out after the playlist changed
+    fading: Vec<Track>,
+    /// Seconds of silence left before the next track
+    wait: f32,
+    /// Names of the tracks played last, the latest at the back
+    recent: VecDeque<String>,
+    /// Every run shuffles differently
+    seed: u64,
+    /// Random picks so far
+    picks: i64,
+}
+
+impl Music {
+    pub fn load(assets: &Assets) -> anyhow::Result<Self> {
+        let bytes = assets.read(PLAYLISTS_PATH)?;
+        let file: PlaylistsFile = serde_json::from_slice(&bytes)
+            .with_context(|| format!("Failed to parse {PLAYLISTS_PATH}"))?;
+        if file.crossfade <= 0.0 {
+            bail!("The crossfade in {PLAYLISTS_PATH} has to be above 0");
+        }
+        if file.pause[0] < 0.0 || file.pause[0] > file.pause[1] {
+            bail!("The pause in {PLAYLISTS_PATH} can't be negative and goes lowest first");
+        }
+        if let Some(index) = file
+            .playlists
+            .iter()
+            .position(|playlist| playlist.tracks.is_empty())
+        {
+            bail!("Playlist {index} in {PLAYLISTS_PATH} has no tracks");
+        }
+        let mut tracks = HashMap::new();
+        for name in file.playlists.iter().flat_map(|playlist| &playlist.tracks) {
+            if !tracks.contains_key(name) {
+                tracks.insert(name.clone(), read(assets, name)?.into());
+            }
+        }
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_nanos() as u64);
+        Ok(Music {
+            file,
+            tracks,
+            current: None,
+            fading: Vec::new(),
+            wait: 0.0,
+            recent: VecDeque::new(),
+            seed,
+            picks: 0,
+        })
+    }
+
+    /// Move the fades along by `delta` seconds and start the next track once the pause is
+    /// over. `volume` is the master and music volume together, nothing new starts at 0.
+    pub fn update(&mut self, handle: &OutputStreamHandle, scene: Scene, delta: f32, volume: f32) {
+        let playlist = self
+            .file
+            .playlists
+            .iter()
+            .position(|playlist| playlist.fits(scene))
+            .filter(|_| volume > 0.0);
+        if let Some(current) = self.current.take() {
+            if current.sink.empty() {
+                self.wait = self.random_pause();
+            } else if Some(current.playlist) != playlist {
+                // The next playlist comes in right away instead of after a pause
+                self.fading.push(current);
+                self.wait = 0.0;
+            } else {
+                self.current = Some(current);
+            }
+        }
+        if let (None, Some(playlist)) = (&self.current, playlist) {
+            // Going back and forth over a biome border picks up where it left off
+            let returning = self
+                .fading
+                .iter()
+                .position(|track| track.playlist == playlist);
+            self.wait -= delta;
+            if let Some(index) = returning {
+                self.current = Some(self.fading.swap_remove(index));
+            } else if self.wait <= 0.0 {
+                self.current = self.start(handle, playlist);
+            }
+        }
+
+        let step = delta / self.file.crossfade;
+        if let Some(current) = &mut self.current {
+            current.fade = (current.fade + step).min(1.0);
+            current.sink.set_volume(volume * current.fade);
+        }
+        self.fading.retain_mut(|track| {
+            track.fade -= step;
+            track.sink.set_volume(volume * track.fade.max(0.0));
+            track.fade > 0.0 && !track.sink.empty()
+        });
+    }
+
+    /// Shuffle a track out of `playlist` that hasn't played lately and start fading it in.
+    /// None if it couldn't be played, another is tried after a pause.
+    fn start(&mut self, handle: &OutputStreamHandle, playlist: usize) -> Option<Track> {
+        let pick = self.random();
+        let tracks = &self.file.playlists[playlist].tracks;
+        let fresh: Vec<&String> = tracks
+            .iter()
+            .filter(|track| !self.recent.contains(track))
+            .collect();
+        let candidates: Vec<&String> = if fresh.is_empty() {
+            tracks.iter().collect()
+        } else {
+            fresh
+        };
+        let name = candidates[(pick * candidates.len() as f64) as usize].clone();
+        let remembered = self.file.cooldown.min(tracks.len() - 1);
+
+        self.recent.push_back(name.clone());
+        while self.recent.len() > remembered {
+            self.recent.pop_front();
+        }
+        match play(handle, Arc::clone(&self.tracks[&name])) {
+            Ok(sink) => Some(Track {
+                sink,
+                playlist,
+                fade: 0.0,
+            }),
+            Err(err) => {
+                log::warn!("Failed to play music {name}: {err:#}");
+                self.wait = self.random_pause();
+                None
+            }
+        }
+    }
+
+    fn random_pause(&mut self) -> f32 {
+        let [least, most] = self.file.pause;
+        least + (most - least) * self.random() as f32
+    }
+
+    /// From 0 up to but not including 1, different every time
+    fn random(&mut self) -> f64 {
+        self.picks += 1;
+        noise::hash_unit(self.seed, &[self.picks])
+    }
+}
+
+/// `music/<name>.ogg` or `music/<name>.wav`, whichever there is
+fn read(assets: &Assets, name: &str) -> anyhow::Result<Vec<u8>> {
+    assets
+        .read(&format!("music/{name}.ogg"))
+        .or_else(|_| assets.read(&format!("music/{name}.wav")))
+        .with_context(|| format!("Failed to read music track {name}"))
+}
+
+/// A silent sink already playing `bytes`, decoding them as it goes
+fn play(handle: &OutputStreamHandle, bytes: Arc<[u8]>) -> anyhow::Result<Sink> {
+    let decoder = Decoder::new(Cursor::new(bytes))?;
+    let sink = Sink::try_new(handle)?;
+    sink.set_volume(0.0);
+    sink.append(decoder);
+    Ok(sink)
+}
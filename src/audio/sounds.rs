@@ -0,0 +1,125 @@
+use anyhow::{bail, Context};
+use rodio::source::{Buffered, SamplesConverter};
+use rodio::{Decoder, Source};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use crate::assets::Assets;
+
+const EVENTS_PATH: &str = "sounds/events.json";
+
+/// A decoded sound file, cloning it plays it again from the start without decoding it twice
+pub type Clip = Buffered<SamplesConverter<Decoder<Cursor<Vec<u8>>>, f32>>;
+
+/// Which volume setting a sound follows, on top of the master volume
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Music,
+    Blocks,
+    Ambient,
+}
+
+/// One entry of `sounds/events.json`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EventFile {
+    category: Category,
+    /// Under `sounds/`, one is picked at random each time
+    sounds: Vec<String>,
+    #[serde(default = "full_volume")]
+    volume: f32,
+    /// Lowest and highest playback speed, also picked at random
+    #[serde(default = "unchanged_pitch")]
+    pitch: [f32; 2],
+    /// Blocks away it fades out completely at
+    #[serde(default = "default_range")]
+    range: f32,
+}
+
+fn full_volume() -> f32 {
+    1.0
+}
+
+fn unchanged_pitch() -> [f32; 2] {
+    [1.0, 1.0]
+}
+
+fn default_range() -> f32 {
+    16.0
+}
+
+/// Something that can make a sound, like `block.stone.break`
+pub struct SoundEvent {
+    pub category: Category,
+    pub clips: Vec<Clip>,
+    pub volume: f32,
+    pub pitch: [f32; 2],
+    pub range: f32,
+}
+
+/// Every sound event from `sounds/events.json` by name, with their files decoded
+pub struct SoundBank {
+    events: HashMap<String, SoundEvent>,
+}
+
+impl SoundBank {
+    pub fn load(assets: &Assets) -> anyhow::Result<Self> {
+        let bytes = assets.read(EVENTS_PATH)?;
+        let files: HashMap<String, EventFile> = serde_json::from_slice(&bytes)
+            .with_context(|| format!("Failed to parse {EVENTS_PATH}"))?;
+        let mut events = HashMap::new();
+        // Events often share files, e.g. placing and breaking the same block
+        let mut decoded: HashMap<String, Clip> = HashMap::new();
+        for (name, file) in files {
+            if file.sounds.is_empty() {
+                bail!("{name} in {EVENTS_PATH} has no sounds");
+            }
+            if file.pitch[0] <= 0.0 || file.pitch[0] > file.pitch[1] {
+                bail!("{name} in {EVENTS_PATH} needs a pitch range above 0, lowest first");
+            }
+            if file.range <= 1.0 {
+                bail!("{name} in {EVENTS_PATH} has to carry further than a block");
+            }
+            let mut clips = Vec::new();
+            for sound in &file.sounds {
+                if !decoded.contains_key(sound) {
+                    decoded.insert(sound.clone(), decode(assets, sound)?);
+                }
+                clips.push(decoded[sound].clone());
+            }
+            events.insert(
+                name,
+                SoundEvent {
+                    category: file.category,
+                    clips,
+                    volume: file.volume,
+                    pitch: file.pitch,
+                    range: file.range,
+                },
+            );
+        }
+        Ok(SoundBank { events })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SoundEvent> {
+        self.events.get(name)
+    }
+}
+
+/// `sounds/<name>.ogg` or `sounds/<name>.wav`, whichever there is
+fn decode(assets: &Assets, name: &str) -> anyhow::Result<Clip> {
+    let ogg = format!("sounds/{name}.ogg");
+    let (path, bytes) = match assets.read(&ogg) {
+        Ok(bytes) => (ogg, bytes),
+        Err(_) => {
+            let wav = format!("sounds/{name}.wav");
+            let bytes = assets.read(&wav)?;
+            (wav, bytes)
+        }
+    };
+    let decoder =
+        Decoder::new(Cursor::new(bytes)).with_context(|| format!("Failed to decode {path}"))?;
+    Ok(decoder.convert_samples().buffered())
+}
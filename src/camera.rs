@@ -0,0 +1,47 @@
+use cgmath::{perspective, Deg, Matrix4, Point3, Vector3};
+
+/// Where the world is drawn from, follows the player while a world is open
+pub struct Camera {
+    pub position: Point3<f32>,
+    /// Radians, 0 looks toward -Z and it increases clockwise seen from above
+    pub yaw: f32,
+    /// Radians, positive looks up
+    pub pitch: f32,
+    pub fov: Deg<f32>,
+    /// Multiplies `fov`, for effects like sprinting widening the view
+    pub fov_scale: f32,
+    pub aspect: f32,
+}
+
+impl Camera {
+    pub fn new(position: Point3<f32>, aspect: f32) -> Self {
+        Camera {
+            position,
+            yaw: 0.0,
+            pitch: 0.0,
+            fov: Deg(70.0),
+            fov_scale: 1.0,
+            aspect,
+        }
+    }
+
+    pub fn forward(&self) -> Vector3<f32> {
+        Vector3::new(
+            self.yaw.sin() * self.pitch.cos(),
+            self.pitch.sin(),
+            -self.yaw.cos() * self.pitch.cos(),
+        )
+    }
+
+    pub fn view(&self) -> Matrix4<f32> {
+        Matrix4::look_to_rh(self.position, self.forward(), Vector3::unit_y())
+    }
+
+    pub fn projection(&self) -> Matrix4<f32> {
+        perspective(self.fov * self.fov_scale, self.aspect, 0.1, 1000.0)
+    }
+
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        self.projection() * self.view()
+    }
+}
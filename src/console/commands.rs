@@ -0,0 +1,596 @@
+use anyhow::{anyhow, bail, Context};
+use cgmath::Point3;
+use std::f32::consts::{PI, TAU};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::game::components::{GameMode, Look, Model, Position, PreviousPosition, WalkCycle};
+use crate::game::inventory::{ItemStack, SLOTS};
+use crate::game::spawning;
+use crate::game::{Game, DAY_LENGTH};
+use crate::profiler;
+use crate::render::debug_view::{DebugShading, DebugView};
+use crate::render::mesher::{self, ChunkSnapshot, Lod, MeshingMode};
+use crate::save::schematic::Schematic;
+use crate::world::BlockId;
+
+/// Blocks in front of the player /summon puts entities
+const SUMMON_DISTANCE: f32 = 2.5;
+
+/// What a command is allowed to touch. Anything a command can run against goes in here so the
+/// same registry can serve the console and, later, chat messages from other players.
+pub struct CommandContext<'a> {
+    pub game: &'a mut Game,
+    pub debug_view: &'a mut DebugView,
+    /// What /schem pastes
+    pub clipboard: &'a mut Option<Schematic>,
+}
+
+/// Runs with everything after the command name split on whitespace, the returned line is shown
+/// to whoever ran it
+pub type CommandFn = fn(&mut CommandContext, &[&str]) -> anyhow::Result<String>;
+
+/// Everything the next argument could be, given the ones typed before it
+pub type CompleteFn = fn(&Game, &[&str]) -> Vec<String>;
+
+pub struct Command {
+    pub name: &'static str,
+    /// Arguments after the name, shown by /help and on bad input
+    pub usage: &'static str,
+    pub description: &'static str,
+    run: CommandFn,
+    complete: Option<CompleteFn>,
+}
+
+/// Every command the game knows, looked up by name without the leading slash
+pub struct CommandRegistry {
+    commands: Vec<Command>,
+}
+
+impl CommandRegistry {
+    /// Starts out with the built in commands
+    pub fn new() -> Self {
+        let mut registry = CommandRegistry {
+            commands: Vec::new(),
+        };
+        registry.register("tp", "<x> <y> <z>", "Teleport, ~ is relative", teleport);
+        registry.register(
+            "time",
+            "set <ticks|day|noon|night|midnight> | add <ticks> | query",
+            "Change the time of day",
+            time,
+        );
+        registry.register(
+            "weather",
+            "clear|rain [ticks] | query",
+            "Change the weather",
+            weather,
+        );
+        registry.register(
+            "gamemode",
+            "survival|creative | query",
+            "Change the rules you play by",
+            game_mode,
+        );
+        registry.register("seed", "", "Show the world seed", seed);
+        registry.register(
+            "give",
+            "<item> [count]",
+            "Put items in your inventory, what doesn't fit is dropped",
+            give,
+        );
+        registry.complete_with("give", complete_give);
+        registry.register(
+            "setblock",
+            "<x> <y> <z> <block>",
+            "Replace a block, ~ is relative",
+            set_block,
+        );
+        registry.register(
+            "schem",
+            "save <name> <x1> <y1> <z1> <x2> <y2> <z2> | load <name> | paste [<x> <y> <z>] \
+             [0|90|180|270]",
+            "Copy a box of blocks to a schematic file, or paste the last one turned clockwise",
+            schematic,
+        );
+        registry.register(
+            "summon",
+            "<mob|model>",
+            "Put a mob, or just an entity model to see how it looks, in front of you",
+            summon,
+        );
+        registry.register(
+            "meshbench",
+            "",
+            "Mesh the loaded chunks both ways and compare",
+            mesh_bench,
+        );
+        registry.register(
+            "debugview",
+            "wireframe | chunks | shading [normal|unlit|lighting|normals]",
+            "Toggle debug drawing of the world",
+            debug_view,
+        );
+        registry.register(
+            "profile",
+            "start | stop",
+            "Record a trace for chrome://tracing",
+            profile,
+        );
+        registry
+    }
+
+    /// Later registrations replace earlier ones with the same name
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        description: &'static str,
+        run: CommandFn,
+    ) {
+        self.commands.retain(|command| command.name != name);
+        self.commands.push(Command {
+            name,
+            usage,
+            description,
+            run,
+            complete: None,
+        });
+        self.commands.sort_by_key(|command| command.name);
+    }
+
+    /// Let tab complete the arguments of an already registered command
+    pub fn complete_with(&mut self, name: &str, complete: CompleteFn) {
+        if let Some(command) = self
+            .commands
+            .iter_mut()
+            .find(|command| command.name == name)
+        {
+            command.complete = Some(complete);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        self.commands.iter().find(|command| command.name == name)
+    }
+
+    /// Parse and run one line, the leading slash is optional
+    pub fn execute(&self, context: &mut CommandContext, line: &str) -> anyhow::Result<String> {
+        let line = line.trim();
+        let line = line.strip_prefix('/').unwrap_or(line);
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            bail!("Type a command, /help lists them");
+        };
+        let arguments: Vec<_> = words.collect();
+        if name == "help" {
+            return self.help(&arguments);
+        }
+
+        let command = self
+            .get(name)
+            .ok_or_else(|| anyhow!("Unknown command /{name}, /help lists them"))?;
+        (command.run)(context, &arguments)
+            .with_context(|| format!("Usage: /{} {}", command.name, command.usage))
+    }
+
+    /// Names that start with the typed prefix, in order
+    pub fn complete<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'static str> + 'a {
+        let names = self.commands.iter().map(|command| command.name);
+        std::iter::once("help")
+            .chain(names)
+            .filter(move |name| name.starts_with(prefix))
+    }
+
+    /// What the argument after `arguments` could be that starts with `prefix`, in order.
+    /// Nothing for commands that don't complete their arguments.
+    pub fn complete_argument(
+        &self,
+        game: &Game,
+        name: &str,
+        arguments: &[&str],
+        prefix: &str,
+    ) -> Vec<String> {
+        let Some(complete) = self.get(name).and_then(|command| command.complete) else {
+            return Vec::new();
+        };
+        let mut matches: Vec<_> = complete(game, arguments)
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(prefix))
+            .collect();
+        matches.sort();
+        matches
+    }
+
+    fn help(&self, arguments: &[&str]) -> anyhow::Result<String> {
+        match arguments {
+            [] => Ok(self
+                .complete("")
+                .map(|name| format!("/{name}"))
+                .collect::<Vec<_>>()
+                .join(" ")),
+            [name] => {
+                let name = name.strip_prefix('/').unwrap_or(name);
+                if name == "help" {
+                    return Ok(String::from(
+                        "/help [command] - List commands or describe one",
+                    ));
+                }
+                let command = self
+                    .get(name)
+                    .ok_or_else(|| anyhow!("Unknown command /{name}"))?;
+                Ok(format!(
+                    "/{} {} - {}",
+                    command.name, command.usage, command.description
+                ))
+            }
+            _ => bail!("Usage: /help [command]"),
+        }
+    }
+}
+
+impl Default for CommandRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn teleport(context: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    let [x, y, z] = arguments else {
+        bail!("Expected 3 coordinates");
+    };
+    let feet = context.game.player_feet();
+    let target = Point3::new(
+        parse_coordinate(x, feet.x)?,
+        parse_coordinate(y, feet.y)?,
+        parse_coordinate(z, feet.z)?,
+    );
+    context.game.teleport_player(target);
+    Ok(format!(
+        "Teleported to {:.2} {:.2} {:.2}",
+        target.x, target.y, target.z
+    ))
+}
+
+fn time(context: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    let world = &mut context.game.info;
+    match arguments {
+        ["query"] => Ok(format!(
+            "The time is {} (day {})",
+            world.time % DAY_LENGTH,
+            world.time / DAY_LENGTH
+        )),
+        ["set", value] => {
+            let time_of_day = match *value {
+                "day" => 1000,
+                "noon" => 6000,
+                "night" => 13000,
+                "midnight" => 18000,
+                ticks => parse_ticks(ticks)? % DAY_LENGTH,
+            };
+            // Keep the day count, only move within the current day
+            world.time = world.time - world.time % DAY_LENGTH + time_of_day;
+            Ok(format!("Set the time to {time_of_day}"))
+        }
+        ["add", value] => {
+            world.time = world.time.saturating_add(parse_ticks(value)?);
+            Ok(format!("The time is now {}", world.time % DAY_LENGTH))
+        }
+        _ => bail!("Expected set, add or query"),
+    }
+}
+
+fn weather(context: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    let weather = &mut context.game.info.weather;
+    let (raining, duration) = match arguments {
+        ["query"] => {
+            let state = if weather.raining { "raining" } else { "clear" };
+            return Ok(format!(
+                "It's {state} for another {} ticks",
+                weather.remaining
+            ));
+        }
+        [kind] => (*kind, None),
+        [kind, ticks] => (*kind, Some(parse_ticks(ticks)?)),
+        _ => bail!("Expected clear, rain or query"),
+    };
+    let raining = match raining {
+        "clear" => false,
+        "rain" => true,
+        other => bail!("Unknown weather {other}"),
+    };
+    // Half a day unless told otherwise
+    weather.set(raining, duration.unwrap_or(DAY_LENGTH / 2));
+    let state = if raining { "rain" } else { "clear skies" };
+    Ok(format!("Set the weather to {state}"))
+}
+
+fn game_mode(context: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    let [name] = arguments else {
+        bail!("Expected a game mode or query");
+    };
+    if *name == "query" {
+        return Ok(format!("You're in {}", context.game.game_mode().name()));
+    }
+    let Some(mode) = GameMode::parse(name) else {
+        bail!("Unknown game mode {name}");
+    };
+    context.game.set_game_mode(mode);
+    Ok(format!("Set your game mode to {}", mode.name()))
+}
+
+fn seed(context: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    if !arguments.is_empty() {
+        bail!("No arguments expected");
+    }
+    Ok(format!("Seed: {}", context.game.info.seed as i64))
+}
+
+fn give(context: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    let (name, count) = match arguments {
+        [name] => (*name, None),
+        [name, count] => (*name, Some(*count)),
+        _ => bail!("Expected an item and a count"),
+    };
+    let items = Arc::clone(&context.game.items);
+    let item = items
+        .id(name)
+        .ok_or_else(|| anyhow!("Unknown item {name:?}"))?;
+    // A full inventory's worth at most, anything past that would only pile up on the ground
+    let most = SLOTS as u32 * items.max_stack(item);
+    let count = match count {
+        None => 1,
+        Some(count) => count
+            .parse()
+            .ok()
+            .filter(|count| (1..=most).contains(count))
+            .ok_or_else(|| anyhow!("{count:?} is not a count from 1 to {most}"))?,
+    };
+    let inventory = context
+        .game
+        .inventory_mut()
+        .ok_or_else(|| anyhow!("You have no inventory"))?;
+    let Some(left) = inventory.add(ItemStack::new(item, count), &items) else {
+        return Ok(format!("Gave you {count} {name}"));
+    };
+    let mut dropped = left.count;
+    while dropped > 0 {
+        let stack = dropped.min(items.max_stack(item));
+        context.game.drop_stack(left.with_count(stack));
+        dropped -= stack;
+    }
+    Ok(format!(
+        "Gave you {count} {name}, {} didn't fit and were dropped",
+        left.count
+    ))
+}
+
+/// Item names for the first argument
+fn complete_give(game: &Game, arguments: &[&str]) -> Vec<String> {
+    if !arguments.is_empty() {
+        return Vec::new();
+    }
+    game.items
+        .iter()
+        .map(|(_, item)| item.name.clone())
+        .collect()
+}
+
+fn set_block(context: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    let [x, y, z, name] = arguments else {
+        bail!("Expected 3 coordinates and a block");
+    };
+    let feet = context.game.player_feet();
+    let position = Point3::new(
+        parse_coordinate(x, feet.x)?.floor() as i32,
+        parse_coordinate(y, feet.y)?.floor() as i32,
+        parse_coordinate(z, feet.z)?.floor() as i32,
+    );
+    let blocks = &context.game.blocks;
+    let block = blocks
+        .id(name)
+        .or_else(|| name.parse().ok().map(BlockId))
+        .filter(|block| blocks.get(*block).is_some())
+        .ok_or_else(|| anyhow!("Unknown block {name:?}"))?;
+    let previous = context
+        .game
+        .world
+        .set_block(position, block)
+        .ok_or_else(|| anyhow!("That position isn't loaded"))?;
+    let name_of = |block: BlockId| {
+        blocks
+            .get(block)
+            .map_or_else(|| format!("#{}", block.0), |block| block.name.clone())
+    };
+    Ok(format!(
+        "Replaced {} with {} at {} {} {}",
+        name_of(previous),
+        name_of(block),
+        position.x,
+        position.y,
+        position.z
+    ))
+}
+
+fn schematic(context: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    let game = &mut context.game;
+    let feet = game.player_feet();
+    let block_at = |[x, y, z]: [&str; 3]| -> anyhow::Result<Point3<i32>> {
+        Ok(Point3::new(
+            parse_coordinate(x, feet.x)?.floor() as i32,
+            parse_coordinate(y, feet.y)?.floor() as i32,
+            parse_coordinate(z, feet.z)?.floor() as i32,
+        ))
+    };
+    match arguments {
+        ["save", name, x1, y1, z1, x2, y2, z2] => {
+            let corners = [block_at([*x1, *y1, *z1])?, block_at([*x2, *y2, *z2])?];
+            let schematic = Schematic::capture(&game.world, &game.blocks, &game.items, corners)?;
+            let path = schematic.save(name)?;
+            let [x, y, z] = schematic.size;
+            *context.clipboard = Some(schematic);
+            Ok(format!("Saved {x}x{y}x{z} blocks as {}", path.display()))
+        }
+        ["load", name] => {
+            let schematic = Schematic::load(name)?;
+            let [x, y, z] = schematic.size;
+            *context.clipboard = Some(schematic);
+            Ok(format!(
+                "Loaded {x}x{y}x{z} blocks, /schem paste puts them down"
+            ))
+        }
+        ["paste", rest @ ..] => {
+            let (origin, angle) = match rest {
+                [] => (block_at(["~"; 3])?, None),
+                [angle] => (block_at(["~"; 3])?, Some(angle)),
+                [x, y, z] => (block_at([*x, *y, *z])?, None),
+                [x, y, z, angle] => (block_at([*x, *y, *z])?, Some(angle)),
+                _ => bail!("Expected a position, an angle or both"),
+            };
+            let turns = match angle.map(|angle| angle.parse::<u32>()) {
+                None => 0,
+                Some(Ok(degrees)) if degrees % 90 == 0 => degrees / 90 % 4,
+                Some(_) => bail!("Schematics turn in steps of 90 degrees"),
+            };
+            let Some(schematic) = context.clipboard else {
+                bail!("Nothing to paste, save or load a schematic first");
+            };
+            let placed =
+                schematic.paste(&mut game.world, &game.blocks, &game.items, origin, turns)?;
+            Ok(format!(
+                "Pasted {placed} blocks at {} {} {}",
+                origin.x, origin.y, origin.z
+            ))
+        }
+        _ => bail!("Expected save, load or paste"),
+    }
+}
+
+/// A mob by that name is spawned like any other. Otherwise the entity only has a model and
+/// doesn't move by itself, it's for looking at a model from all sides.
+fn summon(context: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    let [model] = arguments else {
+        bail!("Expected a mob or model name");
+    };
+    let (_, look) = context.game.player_eye(1.0);
+    let feet = context.game.player_feet() + look.horizontal_forward() * SUMMON_DISTANCE;
+    // Turned around to face the player
+    let facing = Look {
+        yaw: (look.yaw + PI).rem_euclid(TAU),
+        pitch: 0.0,
+    };
+    let mobs = Arc::clone(&context.game.mobs);
+    if let Some((id, mob)) = mobs.id(model).and_then(|id| Some((id, mobs.get(id)?))) {
+        spawning::spawn(&mut context.game.entities, id, mob, feet, facing.yaw);
+        return Ok(format!(
+            "Summoned a {} at {:.2} {:.2} {:.2}",
+            mob.name, feet.x, feet.y, feet.z
+        ));
+    }
+    context.game.entities.spawn((
+        Model(model.to_string()),
+        Position(feet),
+        PreviousPosition(feet),
+        facing,
+        WalkCycle::default(),
+    ));
+    Ok(format!(
+        "Summoned {model} at {:.2} {:.2} {:.2}",
+        feet.x, feet.y, feet.z
+    ))
+}
+
+fn mesh_bench(context: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    if !arguments.is_empty() {
+        bail!("No arguments expected");
+    }
+    let game = &context.game;
+    let snapshots: Vec<_> = game
+        .world
+        .chunk_positions()
+        .filter_map(|position| ChunkSnapshot::capture(&game.world, &game.generator, position))
+        .collect();
+    // Faces only merge within a block, so the texture layers don't change the counts
+    let [(naive, naive_ms), (greedy, greedy_ms)] =
+        [MeshingMode::Naive, MeshingMode::Greedy].map(|mode| {
+            let start = Instant::now();
+            let vertices: usize = snapshots
+                .iter()
+                .map(|snapshot| {
+                    let meshes =
+                        mesher::mesh_chunk(snapshot, &game.blocks, &[], mode, true, Lod::Full);
+                    meshes.opaque.vertices.len() + meshes.translucent.vertices.len()
+                })
+                .sum();
+            (vertices, start.elapsed().as_secs_f32() * 1000.0)
+        });
+    Ok(format!(
+        "{} chunks: naive {naive} vertices in {naive_ms:.1} ms, greedy {greedy} vertices \
+         ({:.0}%) in {greedy_ms:.1} ms",
+        snapshots.len(),
+        greedy as f32 * 100.0 / naive.max(1) as f32
+    ))
+}
+
+fn profile(_: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    match arguments {
+        ["start"] => {
+            profiler::start_recording()?;
+            Ok(String::from("Recording, /profile stop writes the trace"))
+        }
+        ["stop"] => {
+            let path = profiler::stop_recording()?;
+            Ok(format!("Saved trace as {}", path.display()))
+        }
+        _ => bail!("Expected start or stop"),
+    }
+}
+
+fn debug_view(context: &mut CommandContext, arguments: &[&str]) -> anyhow::Result<String> {
+    let view = &mut context.debug_view;
+    let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+    match arguments {
+        ["wireframe"] => {
+            view.wireframe = !view.wireframe;
+            Ok(format!("Wireframe {}", on_off(view.wireframe)))
+        }
+        ["chunks"] => {
+            view.chunk_borders = !view.chunk_borders;
+            Ok(format!("Chunk borders {}", on_off(view.chunk_borders)))
+        }
+        ["shading"] => {
+            view.shading = view.shading.next();
+            Ok(format!("Shading {}", view.shading.name()))
+        }
+        ["shading", name] => {
+            view.shading =
+                DebugShading::from_name(name).ok_or_else(|| anyhow!("Unknown shading {name}"))?;
+            Ok(format!("Shading {}", view.shading.name()))
+        }
+        _ => bail!("Expected wireframe, chunks or shading"),
+    }
+}
+
+/// A plain number, or `~` / `~offset` relative to `current`
+fn parse_coordinate(argument: &str, current: f32) -> anyhow::Result<f32> {
+    let (relative, number) = match argument.strip_prefix('~') {
+        Some(rest) => (true, rest),
+        None => (false, argument),
+    };
+    let value = if relative && number.is_empty() {
+        0.0
+    } else {
+        number
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| anyhow!("{argument:?} is not a coordinate"))?
+    };
+    Ok(if relative { current + value } else { value })
+}
+
+fn parse_ticks(argument: &str) -> anyhow::Result<u64> {
+    argument
+        .parse()
+        .map_err(|_| anyhow!("{argument:?} is not a number of ticks"))
+}
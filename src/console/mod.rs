@@ -0,0 +1,211 @@
+pub mod commands;
+
+use glfw::Key;
+use std::collections::VecDeque;
+
+use crate::game::Game;
+use crate::render::text::{Color, WHITE};
+use crate::ui::{Ui, MAX_FIELD_LENGTH};
+use commands::{CommandContext, CommandRegistry};
+
+const MAX_OUTPUT: usize = 100;
+const MAX_HISTORY: usize = 50;
+const BACKGROUND: Color = [0.0, 0.0, 0.0, 0.75];
+const INPUT_BACKGROUND: Color = [0.1, 0.1, 0.1, 0.9];
+const ERROR_COLOR: Color = [1.0, 0.4, 0.4, 1.0];
+const ECHO_COLOR: Color = [0.7, 0.7, 0.7, 1.0];
+const HINT_COLOR: Color = [0.5, 0.5, 0.5, 1.0];
+const MARGIN: f32 = 4.0;
+
+/// Drop-down command line over the top half of the screen
+pub struct Console {
+    commands: CommandRegistry,
+    input: String,
+    /// Oldest first
+    output: VecDeque<(String, Color)>,
+    history: Vec<String>,
+    /// Position while stepping through `history` with the arrow keys
+    history_index: Option<usize>,
+    /// The key that opened the console also types a character, which shouldn't land in the
+    /// input line
+    just_opened: bool,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console {
+            commands: CommandRegistry::new(),
+            input: String::new(),
+            output: VecDeque::new(),
+            history: Vec::new(),
+            history_index: None,
+            just_opened: false,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.input.clear();
+        self.history_index = None;
+        self.just_opened = true;
+    }
+
+    pub fn print(&mut self, line: impl Into<String>, color: Color) {
+        self.output.push_back((line.into(), color));
+        while self.output.len() > MAX_OUTPUT {
+            self.output.pop_front();
+        }
+    }
+
+    /// Run one line as if it was typed in, echoing it and its result
+    pub fn execute(&mut self, context: &mut CommandContext, line: &str) {
+        self.print(line, ECHO_COLOR);
+        match self.commands.execute(context, line) {
+            Ok(message) => self.print(message, WHITE),
+            Err(err) => self.print(format!("{err:#}"), ERROR_COLOR),
+        }
+        if self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_owned());
+            if self.history.len() > MAX_HISTORY {
+                self.history.remove(0);
+            }
+        }
+    }
+
+    /// Handle this frame's typing and draw the console. Returns a line to execute once Enter
+    /// is pressed. Arguments only tab complete while a `game` is open.
+    pub fn draw(&mut self, ui: &mut Ui, game: Option<&Game>) -> Option<String> {
+        let input = ui.input().clone();
+        if self.just_opened {
+            self.just_opened = false;
+        } else {
+            for character in input.text.chars() {
+                if !character.is_control() && self.input.chars().count() < 2 * MAX_FIELD_LENGTH {
+                    self.input.push(character);
+                }
+            }
+        }
+        for _ in 0..input.backspace {
+            self.input.pop();
+        }
+        for key in &input.keys {
+            match key {
+                Key::Up => self.step_history(true),
+                Key::Down => self.step_history(false),
+                Key::Tab => self.complete(game),
+                _ => {}
+            }
+        }
+
+        let [width, height] = ui.screen_size();
+        let line_height = ui.line_height();
+        let panel_height = (height / 2.0).round();
+        let input_top = panel_height - line_height - 2.0 * MARGIN;
+        ui.rect([0.0, 0.0], [width, panel_height], BACKGROUND);
+        ui.rect(
+            [0.0, input_top],
+            [width, line_height + 2.0 * MARGIN],
+            INPUT_BACKGROUND,
+        );
+
+        let mut y = input_top - line_height;
+        for (line, color) in self.output.iter().rev() {
+            if y < 0.0 {
+                break;
+            }
+            ui.label(line, [MARGIN, y], *color);
+            y -= line_height;
+        }
+
+        let prompt = format!("> {}_", self.input);
+        ui.label(&prompt, [MARGIN, input_top + MARGIN], WHITE);
+        if let Some(hint) = self.hint() {
+            ui.label(&hint, [MARGIN, panel_height + MARGIN], HINT_COLOR);
+        }
+
+        if input.enter && !self.input.trim().is_empty() {
+            self.history_index = None;
+            return Some(std::mem::take(&mut self.input));
+        }
+        None
+    }
+
+    fn step_history(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let index = match (self.history_index, older) {
+            (None, true) => self.history.len() - 1,
+            (None, false) => return,
+            (Some(index), true) => index.saturating_sub(1),
+            (Some(index), false) if index + 1 < self.history.len() => index + 1,
+            (Some(_), false) => {
+                self.history_index = None;
+                self.input.clear();
+                return;
+            }
+        };
+        self.history_index = Some(index);
+        self.input = self.history[index].clone();
+    }
+
+    /// Finish the command name or the argument being typed, or as much of it as all matches
+    /// share
+    fn complete(&mut self, game: Option<&Game>) {
+        // Start of the last word, the one being typed
+        let start = self
+            .input
+            .trim_end_matches(|character: char| !character.is_whitespace())
+            .len();
+        let matches: Vec<String> = if start == 0 {
+            let typed = self.input.strip_prefix('/').unwrap_or(&self.input);
+            self.commands.complete(typed).map(String::from).collect()
+        } else {
+            let Some(game) = game else {
+                return;
+            };
+            let mut words = self.input[..start].split_whitespace();
+            let name = words.next().unwrap_or_default();
+            let name = name.strip_prefix('/').unwrap_or(name);
+            let arguments: Vec<_> = words.collect();
+            self.commands
+                .complete_argument(game, name, &arguments, &self.input[start..])
+        };
+        let Some(first) = matches.first() else {
+            return;
+        };
+        let common = matches.iter().fold(first.len(), |length, name| {
+            first
+                .chars()
+                .zip(name.chars())
+                .take(length)
+                .take_while(|(a, b)| a == b)
+                .count()
+        });
+        self.input.truncate(start);
+        self.input.push_str(&first[..common]);
+        if matches.len() == 1 {
+            self.input.push(' ');
+        }
+    }
+
+    /// Usage of the command being typed, or the names it could complete to
+    fn hint(&self) -> Option<String> {
+        let name = self.input.split_whitespace().next()?;
+        let name = name.strip_prefix('/').unwrap_or(name);
+        if let Some(command) = self.commands.get(name) {
+            return Some(format!("/{} {}", command.name, command.usage));
+        }
+        let matches: Vec<_> = self
+            .commands
+            .complete(name)
+            .map(|name| format!("/{name}"))
+            .collect();
+        (!matches.is_empty()).then(|| matches.join(" "))
+    }
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::Context;
+use gl_lib::ContextInfo;
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::logger;
+use crate::settings::Settings;
+
+pub const CRASH_REPORT_DIR: &str = "crash-reports";
+
+/// What the report needs that the panicking code can't be asked for
+static STATE: Mutex<CrashState> = Mutex::new(CrashState {
+    gl: None,
+    settings: None,
+});
+
+struct CrashState {
+    gl: Option<ContextInfo>,
+    /// Already serialized, the settings may be half updated by the time something panics
+    settings: Option<String>,
+}
+
+/// Write a crash report for every panic, on top of the usual message on stderr
+pub fn install() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        match write_report(info) {
+            Ok(path) => log::error!("Saved a crash report to {}", path.display()),
+            Err(err) => log::error!("Failed to write a crash report: {err:#}"),
+        }
+    }));
+}
+
+pub fn set_gl_info(info: ContextInfo) {
+    let mut state = STATE.lock().unwrap_or_else(|err| err.into_inner());
+    state.gl = Some(info);
+}
+
+pub fn set_settings(settings: &Settings) {
+    let mut state = STATE.lock().unwrap_or_else(|err| err.into_inner());
+    state.settings = serde_json::to_string_pretty(settings).ok();
+}
+
+fn write_report(info: &PanicHookInfo) -> anyhow::Result<PathBuf> {
+    let message = if let Some(message) = info.payload().downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "Box<dyn Any>"
+    };
+    let location = info
+        .location()
+        .map(|location| location.to_string())
+        .unwrap_or_else(|| String::from("unknown"));
+    let now = chrono::Local::now();
+
+    let mut report = String::new();
+    let _ = writeln!(report, "---- RustCraft Crash Report ----");
+    let _ = writeln!(report, "Time: {}", now.format("%Y-%m-%d %H:%M:%S"));
+    let _ = writeln!(report, "Version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        report,
+        "Thread: {}",
+        thread::current().name().unwrap_or("unnamed")
+    );
+    let _ = writeln!(report, "Panic: {message}");
+    let _ = writeln!(report, "Location: {location}");
+
+    let _ = writeln!(report, "\n-- System --");
+    let _ = writeln!(
+        report,
+        "OS: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    // The panic may have come from inside one of the setters, don't wait on ourselves
+    match STATE.try_lock() {
+        Ok(state) => {
+            match &state.gl {
+                Some(gl) => {
+                    let _ = writeln!(report, "GL vendor: {}", gl.vendor);
+                    let _ = writeln!(report, "GL renderer: {}", gl.renderer);
+                    let _ = writeln!(report, "GL version: {}", gl.version);
+                }
+                None => {
+                    let _ = writeln!(report, "GL: no context yet");
+                }
+            }
+            let _ = writeln!(report, "\n-- Settings --");
+            let _ = writeln!(
+                report,
+                "{}",
+                state.settings.as_deref().unwrap_or("not loaded yet")
+            );
+        }
+        Err(_) => {
+            let _ = writeln!(report, "GL and settings unavailable");
+        }
+    }
+
+    let _ = writeln!(report, "\n-- Recent Log --");
+    match logger::recent_lines() {
+        Some(lines) => {
+            for line in lines {
+                let _ = writeln!(report, "{line}");
+            }
+        }
+        None => {
+            let _ = writeln!(report, "Unavailable, the panic happened while logging");
+        }
+    }
+
+    let _ = writeln!(report, "\n-- Backtrace --");
+    let _ = writeln!(report, "{}", Backtrace::force_capture());
+
+    let directory = Path::new(CRASH_REPORT_DIR);
+    fs::create_dir_all(directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+    let path = directory.join(format!("crash_{}.txt", now.format("%Y-%m-%d_%H.%M.%S")));
+    fs::write(&path, report).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
@@ -0,0 +1,236 @@
+use cgmath::{Deg, Point3, Rad};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::camera::Camera;
+use crate::game::{Game, TICKS_PER_SECOND};
+use crate::render::stats::FrameStats;
+use crate::render::text::{Color, TextRenderer, WHITE};
+use crate::world::chunk::ChunkPos;
+use crate::world::raycast::RayHit;
+
+const FRAME_WINDOW: Duration = Duration::from_secs(1);
+/// A second's worth of ticks when the game keeps up
+const TICK_WINDOW: usize = TICKS_PER_SECOND as usize;
+const MARGIN: f32 = 4.0;
+const BACKGROUND: Color = [0.3, 0.3, 0.3, 0.6];
+
+/// Per-frame numbers the overlay can't work out by itself
+pub struct DebugInfo<'a> {
+    pub camera: &'a Camera,
+    pub stats: &'a FrameStats,
+    pub gpu_memory: usize,
+    /// None outside of a world
+    pub game: Option<&'a Game>,
+    pub target: Option<RayHit>,
+}
+
+/// The F3 screen, it keeps a rolling second of frame and tick times and formats everything
+/// into lines of text
+pub struct DebugOverlay {
+    pub visible: bool,
+    frame_times: VecDeque<Duration>,
+    window_total: Duration,
+    /// How long each of the last [`TICK_WINDOW`] game ticks took to run
+    tick_times: VecDeque<Duration>,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        DebugOverlay {
+            visible: false,
+            frame_times: VecDeque::new(),
+            window_total: Duration::ZERO,
+            tick_times: VecDeque::new(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Called every frame even while hidden so the numbers are warm when it opens
+    pub fn record_frame(&mut self, frame_time: Duration) {
+        self.frame_times.push_back(frame_time);
+        self.window_total += frame_time;
+        while self.window_total > FRAME_WINDOW && self.frame_times.len() > 1 {
+            if let Some(oldest) = self.frame_times.pop_front() {
+                self.window_total -= oldest;
+            }
+        }
+    }
+
+    /// Called for every game tick with the time it took
+    pub fn record_tick(&mut self, tick_time: Duration) {
+        self.tick_times.push_back(tick_time);
+        if self.tick_times.len() > TICK_WINDOW {
+            self.tick_times.pop_front();
+        }
+    }
+
+    pub fn fps(&self) -> f32 {
+        if self.window_total.is_zero() {
+            return 0.0;
+        }
+        self.frame_times.len() as f32 / self.window_total.as_secs_f32()
+    }
+
+    /// Queue the overlay into `text`, it shows up on the next flush
+    pub fn draw(&self, text: &mut TextRenderer, info: &DebugInfo) {
+        let line_height = text.line_height();
+        for (index, line) in self.lines(info).iter().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let position = [MARGIN, MARGIN + index as f32 * line_height];
+            let [width, _] = text.measure(line);
+            text.queue_rect(
+                [position[0] - 2.0, position[1]],
+                [width + 4.0, line_height],
+                BACKGROUND,
+            );
+            text.queue(line, position, WHITE);
+        }
+    }
+
+    pub fn lines(&self, info: &DebugInfo) -> Vec<String> {
+        let (average, worst) = milliseconds(&self.frame_times);
+        let (tick_average, tick_worst) = milliseconds(&self.tick_times);
+
+        let position = info.camera.position;
+        let block = position.map(|coordinate| coordinate.floor() as i32);
+        let (chunk, _) = ChunkPos::of_block(block);
+        let yaw = Deg::from(Rad(info.camera.yaw)).0;
+        let pitch = Deg::from(Rad(info.camera.pitch)).0;
+
+        vec![
+            format!("RustCraft {}", env!("CARGO_PKG_VERSION")),
+            format!(
+                "{:.0} fps ({average:.2} ms avg, {worst:.2} ms max)",
+                self.fps()
+            ),
+            format!("Tick: {tick_average:.2} ms avg, {tick_worst:.2} ms max"),
+            String::new(),
+            format!(
+                "XYZ: {:.3} / {:.3} / {:.3}",
+                position.x, position.y, position.z
+            ),
+            format!("Block: {} {} {}", block.x, block.y, block.z),
+            format!("Chunk: {} {} {}", chunk.x, chunk.y, chunk.z),
+            format!(
+                "Facing: {} ({yaw:.1} / {pitch:.1})",
+                facing(info.camera.yaw)
+            ),
+            String::new(),
+            format!(
+                "Draw calls: {}, triangles: {}",
+                info.stats.gl.draw_calls, info.stats.gl.triangles
+            ),
+            format!(
+                "Uploads: {:.1} KiB, texture binds: {}",
+                info.stats.gl.upload_bytes as f32 / 1024.0,
+                info.stats.gl.texture_binds
+            ),
+            format!(
+                "Mesh rebuilds: {}, queued: {} ({} not started)",
+                info.stats.mesh_rebuilds, info.stats.mesh_queue, info.stats.mesh_unstarted
+            ),
+            format!(
+                "Chunks drawn: {}, culled: {}",
+                info.stats.chunks_drawn, info.stats.chunks_culled
+            ),
+            loaded_chunks(info.game, block),
+            targeted(info.game, info.target),
+            biome(info.game, block),
+            light(info.game, block),
+            format!(
+                "GPU memory: ~{:.1} MiB",
+                info.gpu_memory as f32 / (1024.0 * 1024.0)
+            ),
+        ]
+    }
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Average and longest of `times` in milliseconds, 0 when there are none
+fn milliseconds(times: &VecDeque<Duration>) -> (f32, f32) {
+    let total: Duration = times.iter().sum();
+    let average = total.as_secs_f32() * 1000.0 / times.len().max(1) as f32;
+    let worst = times
+        .iter()
+        .max()
+        .map_or(0.0, |time| time.as_secs_f32() * 1000.0);
+    (average, worst)
+}
+
+/// Compass direction for a yaw where 0 faces -Z
+fn facing(yaw: f32) -> &'static str {
+    let quadrant = (yaw / std::f32::consts::FRAC_PI_2).round() as i32;
+    match quadrant.rem_euclid(4) {
+        0 => "north (towards negative Z)",
+        1 => "east (towards positive X)",
+        2 => "south (towards positive Z)",
+        _ => "west (towards negative X)",
+    }
+}
+
+fn biome(game: Option<&Game>, camera_block: Point3<i32>) -> String {
+    let Some(game) = game else {
+        return String::new();
+    };
+    let column = game.generator.column(camera_block.x, camera_block.z);
+    format!(
+        "Biome: {}, surface at {}",
+        column.biome.name(),
+        column.height
+    )
+}
+
+fn light(game: Option<&Game>, camera_block: Point3<i32>) -> String {
+    let Some(light) = game.and_then(|game| game.world.light(camera_block)) else {
+        return String::new();
+    };
+    format!("Light: {} sky, {} block", light.sky, light.block)
+}
+
+fn targeted(game: Option<&Game>, target: Option<RayHit>) -> String {
+    let (Some(game), Some(target)) = (game, target) else {
+        return String::new();
+    };
+    let block = target.block;
+    let name = game
+        .world
+        .block(block)
+        .and_then(|id| game.blocks.get(id))
+        .map_or("unknown", |block| block.name.as_str());
+    let face = target
+        .face
+        .map_or(String::from("inside"), |face| format!("{face:?}"));
+    format!(
+        "Targeted block: {} {} {} ({name}, {face}, {:.2} away)",
+        block.x, block.y, block.z, target.distance
+    )
+}
+
+fn loaded_chunks(game: Option<&Game>, camera_block: Point3<i32>) -> String {
+    let Some(game) = game else {
+        return String::from("No world");
+    };
+    let inside = match game.world.block(camera_block) {
+        Some(block) => match game.blocks.get(block) {
+            Some(block) => format!("in {}", block.name),
+            None => format!("in unknown block #{}", block.0),
+        },
+        None => String::from("outside loaded chunks"),
+    };
+    format!(
+        "Chunks: {} loaded, {} queued, camera {inside}",
+        game.world.chunk_count(),
+        game.streamer.queued()
+    )
+}
@@ -0,0 +1,289 @@
+use anyhow::anyhow;
+use cgmath::Deg;
+use glfw::{Action, Key, Modifiers, MouseButton, WindowEvent};
+use std::collections::VecDeque;
+use std::mem;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::camera::Camera;
+use crate::game::components::{Look, Position};
+use crate::game::Game;
+
+/// Frames kept for the frame time graph
+const GRAPH_FRAMES: usize = 240;
+/// Frame time at the top of the graph, in milliseconds
+const GRAPH_MAX_MS: f32 = 33.3;
+const GRAPH_SIZE: egui::Vec2 = egui::vec2(240.0, 60.0);
+
+/// Game state the debug windows can look at and poke
+pub struct Inspected<'a> {
+    pub camera: &'a mut Camera,
+    pub game: Option<&'a mut Game>,
+    pub fps: f32,
+    pub draw_calls: u64,
+}
+
+/// Developer overlay built on egui, drawn on top of everything else. Unlike the game's own
+/// menus it favours quick to write widgets over looks.
+pub struct DebugUi {
+    pub visible: bool,
+    context: egui::Context,
+    painter: egui_glow::Painter,
+    events: Vec<egui::Event>,
+    modifiers: egui::Modifiers,
+    pointer: egui::Pos2,
+    start: Instant,
+    frame_times: VecDeque<f32>,
+}
+
+impl DebugUi {
+    pub fn new(window: &mut glfw::Window) -> anyhow::Result<Self> {
+        let gl = unsafe {
+            glow::Context::from_loader_function(|symbol| {
+                window.get_proc_address(symbol) as *const _
+            })
+        };
+        let painter = egui_glow::Painter::new(Arc::new(gl), "", None, false)
+            .map_err(|err| anyhow!("Failed to create the egui painter: {err}"))?;
+        Ok(DebugUi {
+            visible: false,
+            context: egui::Context::default(),
+            painter,
+            events: Vec::new(),
+            modifiers: egui::Modifiers::default(),
+            pointer: egui::Pos2::ZERO,
+            start: Instant::now(),
+            frame_times: VecDeque::with_capacity(GRAPH_FRAMES),
+        })
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn record_frame(&mut self, frame_time: f32) {
+        if self.frame_times.len() == GRAPH_FRAMES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time * 1000.0);
+    }
+
+    /// Forward a window event to egui. Returns true when egui wants it to itself, the game
+    /// should ignore it then.
+    pub fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        if !self.visible {
+            return false;
+        }
+        match *event {
+            WindowEvent::CursorPos(x, y) => {
+                self.pointer = egui::pos2(x as f32, y as f32);
+                self.events.push(egui::Event::PointerMoved(self.pointer));
+                self.context.wants_pointer_input()
+            }
+            WindowEvent::MouseButton(button, action, modifiers) => {
+                self.modifiers = convert_modifiers(modifiers);
+                let button = match button {
+                    MouseButton::Button1 => egui::PointerButton::Primary,
+                    MouseButton::Button2 => egui::PointerButton::Secondary,
+                    MouseButton::Button3 => egui::PointerButton::Middle,
+                    _ => return false,
+                };
+                self.events.push(egui::Event::PointerButton {
+                    pos: self.pointer,
+                    button,
+                    pressed: action == Action::Press,
+                    modifiers: self.modifiers,
+                });
+                self.context.wants_pointer_input()
+            }
+            WindowEvent::Scroll(x, y) => {
+                self.events.push(egui::Event::MouseWheel {
+                    unit: egui::MouseWheelUnit::Line,
+                    delta: egui::vec2(x as f32, y as f32),
+                    modifiers: self.modifiers,
+                });
+                self.context.wants_pointer_input()
+            }
+            WindowEvent::Char(character) => {
+                self.events.push(egui::Event::Text(character.to_string()));
+                self.context.wants_keyboard_input()
+            }
+            WindowEvent::Key(key, _, action, modifiers) => {
+                self.modifiers = convert_modifiers(modifiers);
+                if let Some(key) = convert_key(key) {
+                    self.events.push(egui::Event::Key {
+                        key,
+                        physical_key: None,
+                        pressed: action != Action::Release,
+                        repeat: action == Action::Repeat,
+                        modifiers: self.modifiers,
+                    });
+                }
+                self.context.wants_keyboard_input()
+            }
+            _ => false,
+        }
+    }
+
+    /// Run the debug windows and paint them into the window's framebuffer
+    pub fn draw(&mut self, window: &mut glfw::Window, mut inspected: Inspected) {
+        let (width, height) = window.get_framebuffer_size();
+        let (window_width, _) = window.get_size();
+        let pixels_per_point = width as f32 / window_width.max(1) as f32;
+
+        let mut input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(width as f32, height as f32) / pixels_per_point,
+            )),
+            time: Some(self.start.elapsed().as_secs_f64()),
+            modifiers: self.modifiers,
+            events: mem::take(&mut self.events),
+            focused: window.is_focused(),
+            ..Default::default()
+        };
+        input
+            .viewports
+            .entry(egui::ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(pixels_per_point);
+
+        let frame_times = &self.frame_times;
+        let output = self.context.run(input, |context| {
+            camera_window(context, inspected.camera);
+            if let Some(game) = inspected.game.as_deref_mut() {
+                player_window(context, game);
+            }
+            performance_window(context, inspected.fps, inspected.draw_calls, frame_times);
+        });
+
+        if !output.platform_output.copied_text.is_empty() {
+            window.set_clipboard_string(&output.platform_output.copied_text);
+        }
+        let primitives = self
+            .context
+            .tessellate(output.shapes, output.pixels_per_point);
+        self.painter.paint_and_update_textures(
+            [width as u32, height as u32],
+            output.pixels_per_point,
+            &primitives,
+            &output.textures_delta,
+        );
+    }
+}
+
+impl Drop for DebugUi {
+    fn drop(&mut self) {
+        self.painter.destroy();
+    }
+}
+
+fn camera_window(context: &egui::Context, camera: &mut Camera) {
+    egui::Window::new("Camera").show(context, |ui| {
+        let position = camera.position;
+        ui.label(format!(
+            "Eye at {:.2} {:.2} {:.2}",
+            position.x, position.y, position.z
+        ));
+        let Deg(mut fov) = camera.fov;
+        ui.add(egui::Slider::new(&mut fov, 30.0..=110.0).text("FOV"));
+        camera.fov = Deg(fov);
+    });
+}
+
+fn player_window(context: &egui::Context, game: &mut Game) {
+    egui::Window::new("Player").show(context, |ui| {
+        ui.label(format!("{} entities", game.entities.len()));
+        let Ok((position, look)) = game
+            .entities
+            .query_one_mut::<(&mut Position, &mut Look)>(game.player)
+        else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label("Feet");
+            ui.add(egui::DragValue::new(&mut position.0.x).speed(0.1));
+            ui.add(egui::DragValue::new(&mut position.0.y).speed(0.1));
+            ui.add(egui::DragValue::new(&mut position.0.z).speed(0.1));
+        });
+
+        let mut yaw = look.yaw.to_degrees();
+        let mut pitch = look.pitch.to_degrees();
+        ui.horizontal(|ui| {
+            ui.label("Yaw");
+            ui.add(egui::DragValue::new(&mut yaw).speed(1.0).suffix("°"));
+        });
+        ui.add(egui::Slider::new(&mut pitch, -89.0..=89.0).text("Pitch"));
+        look.yaw = yaw.to_radians();
+        look.pitch = pitch.to_radians();
+    });
+}
+
+fn performance_window(
+    context: &egui::Context,
+    fps: f32,
+    draw_calls: u64,
+    frame_times: &VecDeque<f32>,
+) {
+    egui::Window::new("Performance").show(context, |ui| {
+        ui.label(format!("{fps:.0} fps"));
+        ui.label(format!("{draw_calls} draw calls"));
+
+        let (rect, _) = ui.allocate_exact_size(GRAPH_SIZE, egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(128));
+        let y_for = |ms: f32| rect.bottom() - (ms / GRAPH_MAX_MS).min(1.0) * rect.height();
+
+        // 60 fps budget line
+        let budget = y_for(1000.0 / 60.0);
+        painter.hline(
+            rect.x_range(),
+            budget,
+            egui::Stroke::new(1.0, egui::Color32::DARK_GREEN),
+        );
+        let step = rect.width() / GRAPH_FRAMES as f32;
+        let points = frame_times
+            .iter()
+            .enumerate()
+            .map(|(index, ms)| egui::pos2(rect.left() + index as f32 * step, y_for(*ms)))
+            .collect();
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new(1.0, egui::Color32::WHITE),
+        ));
+    });
+}
+
+fn convert_modifiers(modifiers: Modifiers) -> egui::Modifiers {
+    let ctrl = modifiers.contains(Modifiers::Control);
+    egui::Modifiers {
+        alt: modifiers.contains(Modifiers::Alt),
+        ctrl,
+        shift: modifiers.contains(Modifiers::Shift),
+        mac_cmd: false,
+        command: ctrl,
+    }
+}
+
+fn convert_key(key: Key) -> Option<egui::Key> {
+    Some(match key {
+        Key::Backspace => egui::Key::Backspace,
+        Key::Delete => egui::Key::Delete,
+        Key::Enter | Key::KpEnter => egui::Key::Enter,
+        Key::Tab => egui::Key::Tab,
+        Key::Escape => egui::Key::Escape,
+        Key::Left => egui::Key::ArrowLeft,
+        Key::Right => egui::Key::ArrowRight,
+        Key::Up => egui::Key::ArrowUp,
+        Key::Down => egui::Key::ArrowDown,
+        Key::Home => egui::Key::Home,
+        Key::End => egui::Key::End,
+        Key::A => egui::Key::A,
+        Key::C => egui::Key::C,
+        Key::V => egui::Key::V,
+        Key::X => egui::Key::X,
+        Key::Z => egui::Key::Z,
+        _ => return None,
+    })
+}
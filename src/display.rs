@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FullscreenMode {
+    /// Takes over the monitor's video mode, fastest but slow to alt-tab
+    Exclusive,
+    /// Undecorated window covering the whole monitor
+    #[default]
+    Borderless,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct WindowedRect {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+/// Switches the window between windowed and fullscreen, remembering where the window was so
+/// it comes back to the same spot
+pub struct Display {
+    /// Only set while fullscreen
+    windowed: Option<WindowedRect>,
+}
+
+impl Display {
+    pub fn new() -> Self {
+        Display { windowed: None }
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.windowed.is_some()
+    }
+
+    /// `monitor` indexes the connected monitors, falling back to the primary one if it's
+    /// out of range. Returns false if there was no monitor to go fullscreen on.
+    pub fn enter_fullscreen(
+        &mut self,
+        glfw: &mut glfw::Glfw,
+        window: &mut glfw::Window,
+        mode: FullscreenMode,
+        monitor: usize,
+    ) -> bool {
+        let (x, y) = window.get_pos();
+        let (width, height) = window.get_size();
+        let windowed = self.windowed.unwrap_or(WindowedRect {
+            x,
+            y,
+            width: width.max(1) as u32,
+            height: height.max(1) as u32,
+        });
+
+        let entered = glfw.with_connected_monitors(|_, monitors| {
+            let Some(monitor) = monitors.get(monitor).or_else(|| monitors.first()) else {
+                return false;
+            };
+            let monitor: &glfw::Monitor = monitor;
+            let Some(video_mode) = monitor.get_video_mode() else {
+                return false;
+            };
+
+            match mode {
+                FullscreenMode::Exclusive => {
+                    window.set_decorated(true);
+                    window.set_monitor(
+                        glfw::WindowMode::FullScreen(monitor),
+                        0,
+                        0,
+                        video_mode.width,
+                        video_mode.height,
+                        Some(video_mode.refresh_rate),
+                    );
+                }
+                FullscreenMode::Borderless => {
+                    let (monitor_x, monitor_y) = monitor.get_pos();
+                    window.set_monitor(
+                        glfw::WindowMode::Windowed,
+                        monitor_x,
+                        monitor_y,
+                        video_mode.width,
+                        video_mode.height,
+                        None,
+                    );
+                    window.set_decorated(false);
+                }
+            }
+            true
+        });
+
+        if entered {
+            self.windowed = Some(windowed);
+        }
+        entered
+    }
+
+    pub fn leave_fullscreen(&mut self, window: &mut glfw::Window) {
+        let Some(rect) = self.windowed.take() else {
+            return;
+        };
+        window.set_decorated(true);
+        window.set_monitor(
+            glfw::WindowMode::Windowed,
+            rect.x,
+            rect.y,
+            rect.width,
+            rect.height,
+            None,
+        );
+    }
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,47 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Below this the OS scheduler is too coarse to trust, the rest is spun out
+const SPIN_THRESHOLD: Duration = Duration::from_millis(1);
+
+/// Keeps frames from starting more often than a target rate, sleeping instead of burning a
+/// core on frames nobody will see
+pub struct FrameLimiter {
+    next_frame: Instant,
+}
+
+impl FrameLimiter {
+    pub fn new() -> Self {
+        FrameLimiter {
+            next_frame: Instant::now(),
+        }
+    }
+
+    /// Blocks until the next frame is due at `fps`, None means don't wait at all
+    pub fn wait(&mut self, fps: Option<u32>) {
+        let now = Instant::now();
+        let Some(fps) = fps.filter(|fps| *fps > 0) else {
+            self.next_frame = now;
+            return;
+        };
+
+        let period = Duration::from_secs_f64(1.0 / fps as f64);
+        // Deadlines advance by whole periods so the average rate stays exact, unless we fell
+        // behind in which case there is nothing to catch up on
+        self.next_frame = (self.next_frame + period).max(now);
+
+        let remaining = self.next_frame.saturating_duration_since(now);
+        if remaining > SPIN_THRESHOLD {
+            thread::sleep(remaining - SPIN_THRESHOLD);
+        }
+        while Instant::now() < self.next_frame {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+impl Default for FrameLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
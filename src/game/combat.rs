@@ -0,0 +1,88 @@
+use cgmath::{InnerSpace, Vector3};
+use hecs::Entity;
+
+use super::components::{Knockback, Velocity};
+use super::item::{Tool, ToolKind, ToolTier};
+use super::scheduler::TickContext;
+
+/// Hit points a bare hand takes
+const HAND_DAMAGE: u32 = 1;
+/// Blocks per second a hit pushes away with, sideways and up
+const KNOCKBACK_SPEED: f32 = 8.0;
+const KNOCKBACK_LIFT: f32 = 5.0;
+/// How much of the push is left each tick after
+const KNOCKBACK_DECAY: f32 = 0.6;
+/// Pushes slower than this are over
+const KNOCKBACK_END: f32 = 0.1;
+
+/// Hit points a hit with `tool` takes, swords are made for it and the other tools less so.
+/// Better tiers hit harder.
+pub fn attack_damage(tool: Option<Tool>) -> u32 {
+    let Some(tool) = tool else {
+        return HAND_DAMAGE;
+    };
+    let base = match tool.kind {
+        ToolKind::Sword => 4,
+        ToolKind::Axe => 3,
+        ToolKind::Pickaxe => 2,
+        ToolKind::Shovel | ToolKind::Hoe => 1,
+    };
+    let bonus = match tool.tier {
+        ToolTier::Wood => 0,
+        ToolTier::Stone => 1,
+        ToolTier::Iron => 2,
+        ToolTier::Diamond => 3,
+    };
+    base + bonus
+}
+
+/// How much a hit wears `tool` down, the opposite of [`super::mining::wear`]
+pub fn wear(tool: Tool) -> u32 {
+    match tool.kind {
+        ToolKind::Sword => 1,
+        ToolKind::Pickaxe | ToolKind::Axe | ToolKind::Shovel | ToolKind::Hoe => 2,
+    }
+}
+
+/// Push `entity` along `direction`, flattened, and up off the ground
+pub fn knock_back(entities: &mut hecs::World, entity: Entity, direction: Vector3<f32>) {
+    let sideways = Vector3::new(direction.x, 0.0, direction.z);
+    if sideways.magnitude2() <= f32::EPSILON {
+        return;
+    }
+    let impulse = sideways.normalize() * KNOCKBACK_SPEED + Vector3::unit_y() * KNOCKBACK_LIFT;
+    push(entities, entity, impulse);
+}
+
+/// Push `entity` by `impulse` in blocks per second, up straight away and sideways through a
+/// [`Knockback`] that dies down
+pub fn push(entities: &mut hecs::World, entity: Entity, impulse: Vector3<f32>) {
+    let Ok(velocity) = entities.query_one_mut::<&mut Velocity>(entity) else {
+        return;
+    };
+    velocity.0.y = velocity.0.y.max(impulse.y);
+    let sideways = Vector3::new(impulse.x, 0.0, impulse.z);
+    if sideways.magnitude2() > f32::EPSILON {
+        let _ = entities.insert_one(entity, Knockback(sideways));
+    }
+}
+
+/// Runs after everything that sets how entities move by themselves and before gravity, adding
+/// what's left of each push on top
+pub fn apply_knockback(context: &mut TickContext) {
+    let mut ended = Vec::new();
+    for (entity, (velocity, knockback)) in context
+        .entities
+        .query_mut::<(&mut Velocity, &mut Knockback)>()
+    {
+        velocity.0.x += knockback.0.x;
+        velocity.0.z += knockback.0.z;
+        knockback.0 *= KNOCKBACK_DECAY;
+        if knockback.0.magnitude() < KNOCKBACK_END {
+            ended.push(entity);
+        }
+    }
+    for entity in ended {
+        let _ = context.entities.remove_one::<Knockback>(entity);
+    }
+}
@@ -0,0 +1,323 @@
+use cgmath::{Deg, Point3, Rad, Vector3};
+use serde::{Deserialize, Serialize};
+
+use super::hunger;
+use super::inventory::ItemStack;
+use super::mob::MobId;
+use crate::world::block::Face;
+use crate::world::BlockId;
+
+/// Feet position in blocks
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Position(pub Point3<f32>);
+
+/// Position at the start of the current tick, rendering blends from here to [`Position`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PreviousPosition(pub Point3<f32>);
+
+/// Blocks per second
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Velocity(pub Vector3<f32>);
+
+/// Box an entity collides with, centred on its feet horizontally
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Collider {
+    pub half_width: f32,
+    pub height: f32,
+}
+
+/// Standing on something as of the last tick, kept up to date for anything with a [`Collider`]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct OnGround(pub bool);
+
+/// Source block of the fluid an entity's [`Collider`] was in as of the last tick
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct InFluid(pub Option<BlockId>);
+
+/// How the player is getting around, worked out from the movement keys every tick
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Movement {
+    pub sprinting: bool,
+    pub sneaking: bool,
+    pub flying: bool,
+    /// Jump was down last tick, so holding it isn't taken for another press
+    pub jump_held: bool,
+    /// Ticks left to press jump a second time and toggle flying
+    pub double_jump_window: u32,
+}
+
+/// Where an entity is facing
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Look {
+    /// Radians, 0 looks toward -Z and it increases clockwise seen from above
+    pub yaw: f32,
+    /// Radians, positive looks up
+    pub pitch: f32,
+}
+
+impl Look {
+    /// Angles in radians, pitch is clamped so the view never flips over
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        let limit = Rad::from(Deg(89.0)).0;
+        self.yaw = (self.yaw + yaw).rem_euclid(std::f32::consts::TAU);
+        self.pitch = (self.pitch + pitch).clamp(-limit, limit);
+    }
+
+    /// Unit vector in the facing direction
+    pub fn forward(&self) -> Vector3<f32> {
+        let horizontal = self.pitch.cos();
+        Vector3::new(
+            self.yaw.sin() * horizontal,
+            self.pitch.sin(),
+            -self.yaw.cos() * horizontal,
+        )
+    }
+
+    /// Facing direction flattened onto the horizontal plane
+    pub fn horizontal_forward(&self) -> Vector3<f32> {
+        Vector3::new(self.yaw.sin(), 0.0, -self.yaw.cos())
+    }
+
+    /// The horizontal side of a block it looks the most towards
+    pub fn facing(&self) -> Face {
+        let forward = self.horizontal_forward();
+        match (
+            forward.x.abs() > forward.z.abs(),
+            forward.x > 0.0,
+            forward.z > 0.0,
+        ) {
+            (true, true, _) => Face::East,
+            (true, false, _) => Face::West,
+            (false, _, true) => Face::South,
+            (false, _, false) => Face::North,
+        }
+    }
+
+    pub fn right(&self) -> Vector3<f32> {
+        Vector3::new(self.yaw.cos(), 0.0, self.yaw.sin())
+    }
+}
+
+/// Hit points, two to a heart. Whatever has it can be hurt, see [`super::health`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Health {
+    pub points: u32,
+    /// Ticks left that further hits are shrugged off, so standing in lava doesn't hurt every
+    /// single tick
+    pub invulnerable: u32,
+    /// Ticks left of the red flash after the last hit
+    pub hurt: u32,
+}
+
+impl Health {
+    pub fn new(points: u32) -> Self {
+        Health {
+            points,
+            invulnerable: 0,
+            hurt: 0,
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.points == 0
+    }
+}
+
+/// What's left of a push from a hit, added on top of however the entity moves by itself and
+/// dying down every tick, see [`super::combat`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Knockback(pub Vector3<f32>);
+
+/// Ticks since a mob died, it falls over before it's gone
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Dying(pub u32);
+
+/// Experience points the player has earned in total
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Experience(pub u32);
+
+/// Blocks fallen since last standing on something or swimming, landing turns it into damage
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct FallDistance(pub f32);
+
+/// Ticks of air left with the head inside a fluid, it fills back up outside of one
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Breath(pub u32);
+
+/// How fed an entity is, see [`super::hunger`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Hunger {
+    /// Two to a drumstick, from 0 starving up to [`super::hunger::MAX_FOOD`]
+    pub food: u32,
+    /// Used up before any food is, never more than the food
+    pub saturation: f32,
+    /// Effort made since saturation or food last went down, it does at
+    /// [`super::hunger::EXHAUSTION_PER_POINT`]
+    pub exhaustion: f32,
+    /// Ticks towards the next hit point healed or starved away
+    pub timer: u32,
+}
+
+impl Hunger {
+    /// How a new player starts out
+    pub fn full() -> Self {
+        Hunger {
+            food: hunger::MAX_FOOD,
+            saturation: hunger::START_SATURATION,
+            exhaustion: 0.0,
+            timer: 0,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.food >= hunger::MAX_FOOD
+    }
+}
+
+/// The rules a player plays by
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameMode {
+    /// Blocks take time to break, items run out and the player can get hurt and hungry
+    #[default]
+    Survival,
+    /// Blocks break at once, items never run out, the player can fly and nothing hurts
+    Creative,
+}
+
+impl GameMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+        }
+    }
+
+    /// Its name, the first letter of it or Minecraft's number for it
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "survival" | "s" | "0" => Some(GameMode::Survival),
+            "creative" | "c" | "1" => Some(GameMode::Creative),
+            _ => None,
+        }
+    }
+
+    /// Whether anything from damage to hunger can touch an entity playing by `mode`, anything
+    /// that doesn't have a mode can
+    pub fn vulnerable(mode: Option<&GameMode>) -> bool {
+        mode.is_none_or(|mode| *mode == GameMode::Survival)
+    }
+}
+
+/// Marks the entity controlled by this client
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Player;
+
+/// The bed a player last used, they come back next to it after dying for as long as it's
+/// there. See [`super::sleeping`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SpawnPoint(pub Point3<i32>);
+
+/// Lying in the bed at `bed`, see [`super::sleeping`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Sleeping {
+    pub bed: Point3<i32>,
+    /// Ticks since they lay down
+    pub ticks: u32,
+}
+
+/// Drawn with `entity_models/<name>.json`, entities without one aren't drawn at all
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Model(pub String);
+
+/// A stack lying in the world, waiting to be picked up
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DroppedItem {
+    pub stack: ItemStack,
+    /// Ticks since it was dropped, it despawns once this gets old enough
+    pub age: u32,
+    /// Ticks left before it can be picked up, so it's seen flying out first
+    pub pickup_delay: u32,
+}
+
+/// Experience points lying in the world, waiting to fly to a player, see
+/// [`super::experience`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExperienceOrb {
+    pub points: u32,
+    /// Ticks since it was dropped, it despawns once this gets old enough
+    pub age: u32,
+    /// Ticks left before it goes for a player
+    pub pickup_delay: u32,
+}
+
+/// A block that was lit and blows up once its fuse runs out, see [`super::explosion`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PrimedTnt {
+    /// Drawn as this block
+    pub block: BlockId,
+    /// Ticks left
+    pub fuse: u32,
+}
+
+/// A creature of the kind the [`super::mob::MobRegistry`] has under this id
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Mob(pub MobId);
+
+/// Aimless walking around, see [`super::wander`]
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Wander {
+    /// Blocks per second
+    pub speed: f32,
+    /// Walking the way it faces rather than standing around
+    pub walking: bool,
+    /// Ticks left of walking or standing around
+    pub timer: u32,
+}
+
+/// Going after the player once they come close enough, see [`super::hunt`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Hunt {
+    /// Blocks per second
+    pub speed: f32,
+    pub damage: u32,
+    /// Blocks away it notices the player from
+    pub range: f32,
+    /// Feet positions still to walk through to get to the player, the next one first
+    pub path: Vec<Point3<i32>>,
+    /// Ticks until the path is worked out again
+    pub repath: u32,
+    /// Ticks until it can hit again
+    pub cooldown: u32,
+}
+
+/// Catches fire in daylight, see [`super::hunt::burn_in_daylight`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BurnsInDaylight;
+
+/// How far along its walk an entity is, moved on by the ground it covers each tick so legs
+/// swing in step with how fast it goes
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct WalkCycle {
+    /// Blocks walked horizontally
+    pub distance: f32,
+    pub previous_distance: f32,
+    /// From 0 standing still to 1 at full walking speed, eased so stopping isn't abrupt
+    pub amount: f32,
+    pub previous_amount: f32,
+}
+
+impl WalkCycle {
+    /// Walked distance and amount blended between the last two ticks
+    pub fn at(&self, alpha: f32) -> (f32, f32) {
+        let blend = |previous: f32, current: f32| previous + (current - previous) * alpha;
+        (
+            blend(self.previous_distance, self.distance),
+            blend(self.previous_amount, self.amount),
+        )
+    }
+}
+
+/// Ground covered on foot since the last footstep sound, see [`super::systems::take_footsteps`]
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Footsteps(pub f32);
@@ -0,0 +1,229 @@
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::inventory::ItemStack;
+use super::item::{ItemId, ItemRegistry};
+use crate::assets::Assets;
+
+/// Recipe names, each one is `recipes/<name>.json`
+const INDEX_PATH: &str = "recipes/index.json";
+/// Columns and rows of a crafting table's grid, the inventory's own grid is smaller
+pub const MAX_GRID_SIZE: usize = 3;
+/// The grid the player can always craft in, without a crafting table
+pub const INVENTORY_GRID_SIZE: usize = 2;
+
+/// What a recipe needs in the grid
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Ingredients {
+    /// Laid out just like this anywhere in the grid, or mirrored left to right. Rows top to
+    /// bottom, None for cells that have to stay empty.
+    Shaped {
+        width: usize,
+        cells: Vec<Option<ItemId>>,
+    },
+    /// One of each in any cells, sorted so it compares against a sorted grid
+    Shapeless(Vec<ItemId>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipe {
+    ingredients: Ingredients,
+    pub result: ItemStack,
+}
+
+impl Recipe {
+    fn matches(&self, grid: &CraftingGrid) -> bool {
+        match &self.ingredients {
+            Ingredients::Shaped { width, cells } => {
+                let Some((trimmed_width, trimmed)) = grid.trimmed() else {
+                    return false;
+                };
+                if trimmed_width != *width || trimmed.len() != cells.len() {
+                    return false;
+                }
+                let mirrored = trimmed
+                    .chunks(*width)
+                    .flat_map(|row| row.iter().rev().copied());
+                trimmed == *cells || mirrored.eq(cells.iter().copied())
+            }
+            Ingredients::Shapeless(needed) => {
+                let mut present: Vec<ItemId> = grid
+                    .slots
+                    .iter()
+                    .flatten()
+                    .map(|stack| stack.item)
+                    .collect();
+                present.sort_unstable();
+                present == *needed
+            }
+        }
+    }
+}
+
+/// Contents of `recipes/<name>.json`, exactly one of `pattern` and `ingredients`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RecipeFile {
+    /// Rows top to bottom of up to 3 characters, each one a key or a space for an empty cell
+    pattern: Option<Vec<String>>,
+    /// Item names for the characters of the pattern
+    #[serde(default)]
+    key: HashMap<char, String>,
+    /// Item names for a shapeless recipe
+    ingredients: Option<Vec<String>>,
+    result: String,
+    #[serde(default = "one")]
+    count: u32,
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// Every crafting recipe, in the order of `recipes/index.json`. When two match the same grid
+/// the first one wins.
+#[derive(Debug, Default)]
+pub struct RecipeBook {
+    recipes: Vec<Recipe>,
+}
+
+impl RecipeBook {
+    pub fn load(assets: &Assets, items: &ItemRegistry) -> anyhow::Result<Self> {
+        let index = assets.read(INDEX_PATH)?;
+        let names: Vec<String> = serde_json::from_slice(&index)
+            .with_context(|| format!("Failed to parse {INDEX_PATH}"))?;
+        let recipes = names
+            .iter()
+            .map(|name| load_recipe(assets, name, items))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(RecipeBook { recipes })
+    }
+
+    /// What the grid makes as it's laid out now
+    pub fn find(&self, grid: &CraftingGrid) -> Option<&Recipe> {
+        if grid.slots.iter().all(Option::is_none) {
+            return None;
+        }
+        self.recipes.iter().find(|recipe| recipe.matches(grid))
+    }
+}
+
+/// Stacks laid out for crafting, rows top to bottom
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CraftingGrid {
+    /// Columns and rows
+    pub size: usize,
+    pub slots: Vec<Option<ItemStack>>,
+}
+
+impl CraftingGrid {
+    pub fn new(size: usize) -> Self {
+        CraftingGrid {
+            size,
+            slots: vec![None; size * size],
+        }
+    }
+
+    /// Use up one of every stack for a crafted result
+    pub fn consume(&mut self) {
+        for slot in &mut self.slots {
+            *slot = slot.and_then(|stack| stack.take(1).1);
+        }
+    }
+
+    /// Empty the grid, e.g. when its screen closes
+    pub fn take_all(&mut self) -> Vec<ItemStack> {
+        self.slots.iter_mut().filter_map(Option::take).collect()
+    }
+
+    /// See [`trim`], None for an empty grid
+    fn trimmed(&self) -> Option<(usize, Vec<Option<ItemId>>)> {
+        let cells: Vec<Option<ItemId>> = self
+            .slots
+            .iter()
+            .map(|slot| slot.map(|stack| stack.item))
+            .collect();
+        trim(self.size, &cells)
+    }
+}
+
+/// The smallest rectangle of `cells`, rows of `width`, holding every filled one: its width
+/// and its cells row by row. None if they're all empty.
+fn trim(width: usize, cells: &[Option<ItemId>]) -> Option<(usize, Vec<Option<ItemId>>)> {
+    let filled: Vec<usize> = (0..cells.len())
+        .filter(|index| cells[*index].is_some())
+        .collect();
+    let left = filled.iter().map(|index| index % width).min()?;
+    let right = filled.iter().map(|index| index % width).max()?;
+    let top = filled.iter().map(|index| index / width).min()?;
+    let bottom = filled.iter().map(|index| index / width).max()?;
+    let trimmed = (top..=bottom)
+        .flat_map(|row| (left..=right).map(move |column| cells[row * width + column]))
+        .collect();
+    Some((right - left + 1, trimmed))
+}
+
+fn load_recipe(assets: &Assets, name: &str, items: &ItemRegistry) -> anyhow::Result<Recipe> {
+    let path = format!("recipes/{name}.json");
+    let bytes = assets.read(&path)?;
+    let file: RecipeFile =
+        serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {path}"))?;
+    let item = |name: &str| {
+        items
+            .id(name)
+            .with_context(|| format!("{path}: there's no item named {name}"))
+    };
+    let result = item(&file.result)?;
+    if file.count == 0 || file.count > items.max_stack(result) {
+        bail!("{path}: the count has to be from 1 to what fits in a stack");
+    }
+    let ingredients = match (file.pattern, file.ingredients) {
+        (Some(pattern), None) => {
+            let width = pattern.first().map_or(0, |row| row.chars().count());
+            if pattern.is_empty()
+                || pattern.len() > MAX_GRID_SIZE
+                || width == 0
+                || width > MAX_GRID_SIZE
+                || pattern.iter().any(|row| row.chars().count() != width)
+            {
+                bail!("{path}: the pattern has to be 1 to 3 rows of the same 1 to 3 characters");
+            }
+            let cells = pattern
+                .iter()
+                .flat_map(|row| row.chars())
+                .map(|key| match key {
+                    ' ' => Ok(None),
+                    key => {
+                        let name = file
+                            .key
+                            .get(&key)
+                            .with_context(|| format!("{path}: '{key}' isn't in the key"))?;
+                        item(name).map(Some)
+                    }
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            // Spaces around the pattern don't pin it to a place in the grid
+            let Some((width, cells)) = trim(width, &cells) else {
+                bail!("{path}: the pattern is all spaces");
+            };
+            Ingredients::Shaped { width, cells }
+        }
+        (None, Some(names)) => {
+            if names.is_empty() || names.len() > MAX_GRID_SIZE * MAX_GRID_SIZE {
+                bail!("{path}: a shapeless recipe takes 1 to 9 ingredients");
+            }
+            let mut needed = names
+                .iter()
+                .map(|name| item(name))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            needed.sort_unstable();
+            Ingredients::Shapeless(needed)
+        }
+        _ => bail!("{path}: a recipe has either a pattern or ingredients"),
+    };
+    Ok(Recipe {
+        ingredients,
+        result: ItemStack::new(result, file.count),
+    })
+}
@@ -0,0 +1,111 @@
+use cgmath::{Point3, Vector3};
+use std::collections::HashSet;
+
+use super::drops;
+use super::inventory::ItemStack;
+use super::scheduler::TickContext;
+use crate::world::block::Pair;
+use crate::world::noise;
+use crate::world::{BlockId, BlockRegistry, World};
+
+/// Keeps the drops of broken halves apart from anything else hashed from the seed
+const DOORS_PURPOSE: i64 = 0x646f_6f72;
+
+/// Open the door, trapdoor or gate at `position` if it's closed or close it if it's open,
+/// along with the other half of a door
+pub fn toggle(world: &mut World, blocks: &BlockRegistry, position: Point3<i32>) {
+    let Some(openable) = world
+        .block(position)
+        .and_then(|id| blocks.get(id))
+        .and_then(|block| block.openable)
+    else {
+        return;
+    };
+    let other = other_half_at(world, blocks, position);
+    for half in std::iter::once(position).chain(other) {
+        if let Some(id) = world
+            .block(half)
+            .and_then(|id| blocks.opened(id, !openable.open))
+        {
+            world.set_block(half, id);
+        }
+    }
+}
+
+/// Which way the other half of a block two high goes and the variant of it that goes with
+/// `block`, turned and opened the same way. None if it isn't two high.
+pub fn other_half(blocks: &BlockRegistry, block: BlockId) -> Option<(Vector3<i32>, BlockId)> {
+    let half = blocks.get(block)?;
+    let pair = half.pair.as_ref()?;
+    let mut other = blocks.id(&pair.with)?;
+    if let Some(orientation) = half.orientation {
+        other = blocks.oriented(other, orientation.facing)?;
+    }
+    if let Some(openable) = half.openable {
+        other = blocks.opened(other, openable.open)?;
+    }
+    Some((offset(pair), other))
+}
+
+/// Runs on the blocks changed since the last tick: halves of doors whose other half is gone
+/// break as well, leaving their drops behind
+pub fn break_lone_halves(context: &mut TickContext, changed: &[Point3<i32>]) {
+    let mut seen = HashSet::new();
+    let around = changed.iter().flat_map(|position| {
+        [
+            *position,
+            position + Vector3::unit_y(),
+            position - Vector3::unit_y(),
+        ]
+    });
+    for (index, position) in around.enumerate() {
+        if !seen.insert(position) || !is_lone(context.world, context.blocks, position) {
+            continue;
+        }
+        let Some(id) = context.world.set_block(position, BlockId::AIR) else {
+            continue;
+        };
+        let seed = noise::hash(
+            context.info.seed,
+            &[context.tick as i64, index as i64, DOORS_PURPOSE],
+        );
+        for (salt, item) in context.items.drops(id).iter().enumerate() {
+            let seed = noise::hash(seed, &[salt as i64]);
+            drops::spawn_from_block(context.entities, position, ItemStack::new(*item, 1), seed);
+        }
+    }
+}
+
+/// Where the other half of the block two high at `position` is, None if it isn't one or the
+/// other half is missing
+fn other_half_at(
+    world: &World,
+    blocks: &BlockRegistry,
+    position: Point3<i32>,
+) -> Option<Point3<i32>> {
+    let pair = blocks.get(world.block(position)?)?.pair.as_ref()?;
+    let other = position + offset(pair);
+    (blocks.base(world.block(other)?) == blocks.id(&pair.with)?).then_some(other)
+}
+
+/// Whether the block at `position` is half of a block two high without its other half.
+/// Halves next to a chunk that isn't loaded aren't.
+fn is_lone(world: &World, blocks: &BlockRegistry, position: Point3<i32>) -> bool {
+    let Some(pair) = world
+        .block(position)
+        .and_then(|id| blocks.get(id))
+        .and_then(|block| block.pair.as_ref())
+    else {
+        return false;
+    };
+    world.block(position + offset(pair)).is_some()
+        && other_half_at(world, blocks, position).is_none()
+}
+
+fn offset(pair: &Pair) -> Vector3<i32> {
+    if pair.above {
+        Vector3::unit_y()
+    } else {
+        -Vector3::unit_y()
+    }
+}
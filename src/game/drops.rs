@@ -0,0 +1,188 @@
+use cgmath::{InnerSpace, Point3, Vector3};
+use hecs::Entity;
+
+use super::components::{
+    Collider, DroppedItem, OnGround, Player, Position, PreviousPosition, Velocity,
+};
+use super::inventory::{Inventory, ItemStack};
+use super::physics::Aabb;
+use super::scheduler::TickContext;
+use crate::world::noise;
+
+/// Five minutes lying around before a dropped item disappears
+const DESPAWN_TICKS: u32 = 6000;
+/// Ticks before something that was just dropped can be picked up
+const PICKUP_DELAY: u32 = 10;
+/// Dropped items are small cubes this wide
+const HALF_WIDTH: f32 = 0.125;
+/// How far past the player's box items get picked up from, sideways and up or down
+const PICKUP_REACH: [f32; 2] = [1.0, 0.5];
+/// Stacks of the same item closer than this become one
+const MERGE_DISTANCE: f32 = 0.5;
+/// Horizontal speed kept each tick on the ground and in the air
+const GROUND_FRICTION: f32 = 0.6;
+const AIR_DRAG: f32 = 0.98;
+/// Blocks per second a broken block's drops pop out with, sideways and up
+const POP_SPEED: [f32; 2] = [2.0, 4.0];
+
+/// Put `stack` in the world at `position`, moving at `velocity`
+pub fn spawn(
+    entities: &mut hecs::World,
+    position: Point3<f32>,
+    velocity: Vector3<f32>,
+    stack: ItemStack,
+) -> Entity {
+    entities.spawn((
+        Position(position),
+        PreviousPosition(position),
+        Velocity(velocity),
+        Collider {
+            half_width: HALF_WIDTH,
+            height: HALF_WIDTH * 2.0,
+        },
+        OnGround(false),
+        DroppedItem {
+            stack,
+            age: 0,
+            pickup_delay: PICKUP_DELAY,
+        },
+    ))
+}
+
+/// Drop `stack` from the middle of the block at `block`, popping out in a direction that
+/// `seed` picks
+pub fn spawn_from_block(
+    entities: &mut hecs::World,
+    block: Point3<i32>,
+    stack: ItemStack,
+    seed: u64,
+) -> Entity {
+    let values = [block.x as i64, block.y as i64, block.z as i64];
+    let random = |salt: i64| {
+        let values = [values[0], values[1], values[2], salt];
+        noise::hash_unit(seed, &values) as f32 * 2.0 - 1.0
+    };
+    let [sideways, up] = POP_SPEED;
+    let velocity = Vector3::new(random(0) * sideways, up, random(1) * sideways);
+    let position = block.map(|value| value as f32 + 0.5) - Vector3::unit_y() * HALF_WIDTH;
+    spawn(entities, position, velocity, stack)
+}
+
+/// Runs after movement: slows dropped items down, merges stacks lying together, hands them
+/// to a player touching them and removes the ones that have been around too long
+pub fn update_dropped_items(context: &mut TickContext) {
+    let mut expired = Vec::new();
+    for (entity, (item, velocity, on_ground)) in
+        context
+            .entities
+            .query_mut::<(&mut DroppedItem, &mut Velocity, &OnGround)>()
+    {
+        item.age += 1;
+        item.pickup_delay = item.pickup_delay.saturating_sub(1);
+        if item.age >= DESPAWN_TICKS {
+            expired.push(entity);
+        }
+        let keep = if on_ground.0 {
+            GROUND_FRICTION
+        } else {
+            AIR_DRAG
+        };
+        velocity.0.x *= keep;
+        velocity.0.z *= keep;
+    }
+    for entity in expired {
+        let _ = context.entities.despawn(entity);
+    }
+    merge_stacks(context);
+    pick_up(context);
+}
+
+/// Same-item stacks close together pile onto the oldest one, as far as it has room
+fn merge_stacks(context: &mut TickContext) {
+    let mut dropped: Vec<(Entity, Point3<f32>, DroppedItem)> = context
+        .entities
+        .query_mut::<(&Position, &DroppedItem)>()
+        .into_iter()
+        .map(|(entity, (position, item))| (entity, position.0, *item))
+        .collect();
+    dropped.sort_by_key(|(_, _, item)| std::cmp::Reverse(item.age));
+    let mut emptied = Vec::new();
+    for first in 0..dropped.len() {
+        for second in first + 1..dropped.len() {
+            let (_, position, item) = dropped[first];
+            let (_, other_position, other) = dropped[second];
+            let max_stack = context.items.max_stack(item.stack.item);
+            if item.stack.count == 0
+                || other.stack.count == 0
+                || item.stack.item != other.stack.item
+                || item.stack.count + other.stack.count > max_stack
+                || (position - other_position).magnitude() > MERGE_DISTANCE
+            {
+                continue;
+            }
+            dropped[first].2.stack.count += other.stack.count;
+            dropped[first].2.pickup_delay = item.pickup_delay.max(other.pickup_delay);
+            dropped[second].2.stack.count = 0;
+            emptied.push(dropped[second].0);
+        }
+    }
+    for (entity, _, merged) in dropped {
+        if let Ok(item) = context.entities.query_one_mut::<&mut DroppedItem>(entity) {
+            *item = merged;
+        }
+    }
+    for entity in emptied {
+        let _ = context.entities.despawn(entity);
+    }
+}
+
+/// Into the inventory of any player whose box comes close enough, whatever doesn't fit stays
+/// on the ground
+fn pick_up(context: &mut TickContext) {
+    let players: Vec<(Entity, Aabb)> = context
+        .entities
+        .query_mut::<(&Position, &Collider)>()
+        .with::<(&Player, &Inventory)>()
+        .into_iter()
+        .map(|(entity, (position, collider))| {
+            let [sideways, vertical] = PICKUP_REACH;
+            let reach = Vector3::new(sideways, vertical, sideways);
+            let aabb = Aabb::standing(position.0, collider.half_width, collider.height);
+            (entity, aabb.inflated(reach))
+        })
+        .collect();
+    let touching: Vec<(Entity, Entity)> = context
+        .entities
+        .query_mut::<(&Position, &Collider, &DroppedItem)>()
+        .into_iter()
+        .filter(|(_, (_, _, item))| item.pickup_delay == 0)
+        .filter_map(|(entity, (position, collider, _))| {
+            let aabb = Aabb::standing(position.0, collider.half_width, collider.height);
+            let (player, _) = players.iter().find(|(_, reach)| reach.intersects(&aabb))?;
+            Some((entity, *player))
+        })
+        .collect();
+    let items = context.items;
+    for (entity, player) in touching {
+        let Ok(stack) = context
+            .entities
+            .get::<&DroppedItem>(entity)
+            .map(|item| item.stack)
+        else {
+            continue;
+        };
+        let Ok(inventory) = context.entities.query_one_mut::<&mut Inventory>(player) else {
+            continue;
+        };
+        match inventory.add(stack, items) {
+            Some(left) => {
+                if let Ok(item) = context.entities.query_one_mut::<&mut DroppedItem>(entity) {
+                    item.stack = left;
+                }
+            }
+            None => {
+                let _ = context.entities.despawn(entity);
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use super::inventory::ItemStack;
+use super::item::{ItemRegistry, Tool};
+use crate::world::noise;
+
+/// Highest level a modifier goes up to
+pub const MAX_LEVEL: u8 = 3;
+/// Experience levels each level of a modifier costs, so the third one costs three times this
+const LEVELS_PER_UPGRADE: u32 = 5;
+
+/// Something the enchanting table can add to a tool
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Modifier {
+    /// Breaks blocks meant for it faster
+    Efficiency,
+    /// Only some uses wear it down
+    Unbreaking,
+}
+
+impl Modifier {
+    pub const ALL: [Modifier; 2] = [Modifier::Efficiency, Modifier::Unbreaking];
+
+    /// Translation key of its name
+    pub fn key(self) -> &'static str {
+        match self {
+            Modifier::Efficiency => "enchantment.efficiency",
+            Modifier::Unbreaking => "enchantment.unbreaking",
+        }
+    }
+}
+
+/// How far each [`Modifier`] has been taken on a tool, 0 for not at all
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Modifiers {
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub efficiency: u8,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub unbreaking: u8,
+}
+
+impl Modifiers {
+    pub fn level(self, modifier: Modifier) -> u8 {
+        match modifier {
+            Modifier::Efficiency => self.efficiency,
+            Modifier::Unbreaking => self.unbreaking,
+        }
+    }
+
+    fn level_mut(&mut self, modifier: Modifier) -> &mut u8 {
+        match modifier {
+            Modifier::Efficiency => &mut self.efficiency,
+            Modifier::Unbreaking => &mut self.unbreaking,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Modifiers::default()
+    }
+}
+
+fn is_zero(value: &u8) -> bool {
+    *value == 0
+}
+
+/// Experience levels it costs to take `stack`'s `modifier` up a level, None if it isn't a
+/// tool or the modifier is already at [`MAX_LEVEL`]
+pub fn upgrade_cost(stack: ItemStack, modifier: Modifier, items: &ItemRegistry) -> Option<u32> {
+    items.tool(stack.item)?;
+    let level = stack.modifiers.level(modifier);
+    (level < MAX_LEVEL).then_some((level as u32 + 1) * LEVELS_PER_UPGRADE)
+}
+
+/// Take `stack`'s `modifier` up a level, as long as [`upgrade_cost`] says it can be
+pub fn upgrade(stack: &mut ItemStack, modifier: Modifier, items: &ItemRegistry) {
+    if upgrade_cost(*stack, modifier, items).is_some() {
+        *stack.modifiers.level_mut(modifier) += 1;
+    }
+}
+
+/// `tool` as its modifiers make it, efficiency adds the square of its level plus one to the
+/// speed
+pub fn modified(tool: Tool, modifiers: Modifiers) -> Tool {
+    let speed = match modifiers.efficiency {
+        0 => tool.speed,
+        level => tool.speed + (level as f32).powi(2) + 1.0,
+    };
+    Tool { speed, ..tool }
+}
+
+/// How many of `uses` wear a tool with `modifiers` down. With unbreaking each one only does
+/// one time in its level plus one, `seed` picks which.
+pub fn wear(modifiers: Modifiers, uses: u32, seed: u64) -> u32 {
+    let chance = modifiers.unbreaking as u64 + 1;
+    (0..uses)
+        .filter(|index| noise::hash(seed, &[*index as i64]).is_multiple_of(chance))
+        .count() as u32
+}
@@ -0,0 +1,177 @@
+use cgmath::{InnerSpace, Point3, Vector3};
+use hecs::Entity;
+
+use super::components::{
+    Collider, Experience, ExperienceOrb, Health, Model, OnGround, Player, Position,
+    PreviousPosition, Velocity,
+};
+use super::physics::Aabb;
+use super::scheduler::TickContext;
+use crate::world::noise;
+
+/// Drawn with `entity_models/experience_orb.json`
+const ORB_MODEL: &str = "experience_orb";
+/// Orbs are small cubes this wide
+const ORB_HALF_WIDTH: f32 = 0.125;
+/// Points are split into orbs of the largest of these that fit, largest first
+const ORB_SIZES: [u32; 5] = [37, 17, 7, 3, 1];
+/// Five minutes lying around before an orb disappears
+const DESPAWN_TICKS: u32 = 6000;
+/// Ticks before a new orb starts heading for the player, so it's seen popping out first
+const PICKUP_DELAY: u32 = 10;
+/// Blocks away from the player orbs start flying towards them from, and how fast they go
+const ATTRACT_RANGE: f32 = 8.0;
+const ATTRACT_SPEED: f32 = 6.0;
+/// How far past the player's box orbs get picked up from
+const PICKUP_REACH: f32 = 0.5;
+/// Horizontal speed kept each tick on the ground and in the air
+const GROUND_FRICTION: f32 = 0.6;
+const AIR_DRAG: f32 = 0.98;
+/// Blocks per second orbs pop out with, sideways and up
+const POP_SPEED: [f32; 2] = [1.5, 3.0];
+
+/// The level `total` points add up to
+pub fn level(total: u32) -> u32 {
+    let mut level = 0;
+    while points_to_reach(level + 1) <= total {
+        level += 1;
+    }
+    level
+}
+
+/// How far `total` points are from their level to the next one, from 0 to 1
+pub fn progress(total: u32) -> f32 {
+    let level = level(total);
+    let start = points_to_reach(level);
+    (total - start) as f32 / (points_to_reach(level + 1) - start) as f32
+}
+
+/// `total` points with `levels` levels taken off, whatever was earned towards the next level
+/// stays. None if there aren't that many.
+pub fn spend_levels(total: u32, levels: u32) -> Option<u32> {
+    let level = level(total);
+    let left = level.checked_sub(levels)?;
+    Some(total - points_to_reach(level) + points_to_reach(left))
+}
+
+/// Points from nothing to `level`, each level takes more than the one before
+fn points_to_reach(level: u32) -> u32 {
+    (0..level)
+        .map(|level| match level {
+            0..16 => 2 * level + 7,
+            16..31 => 5 * level - 38,
+            _ => 9 * level - 158,
+        })
+        .sum()
+}
+
+/// Scatter `points` as orbs from `position`, popping out in directions that `seed` picks
+pub fn spawn_orbs(entities: &mut hecs::World, position: Point3<f32>, points: u32, seed: u64) {
+    let mut left = points;
+    let mut index = 0;
+    while left > 0 {
+        let size = ORB_SIZES
+            .into_iter()
+            .find(|size| *size <= left)
+            .unwrap_or(left);
+        left -= size;
+        let random = |salt: i64| noise::hash_unit(seed, &[index, salt]) as f32 * 2.0 - 1.0;
+        let [sideways, up] = POP_SPEED;
+        let velocity = Vector3::new(random(0) * sideways, up, random(1) * sideways);
+        spawn_orb(entities, position, velocity, size);
+        index += 1;
+    }
+}
+
+fn spawn_orb(
+    entities: &mut hecs::World,
+    position: Point3<f32>,
+    velocity: Vector3<f32>,
+    points: u32,
+) -> Entity {
+    entities.spawn((
+        Model(ORB_MODEL.to_owned()),
+        Position(position),
+        PreviousPosition(position),
+        Velocity(velocity),
+        Collider {
+            half_width: ORB_HALF_WIDTH,
+            height: ORB_HALF_WIDTH * 2.0,
+        },
+        OnGround(false),
+        ExperienceOrb {
+            points,
+            age: 0,
+            pickup_delay: PICKUP_DELAY,
+        },
+    ))
+}
+
+/// Runs before gravity: orbs close enough to a living player fly at them and go into their
+/// experience once they touch, the rest slow down and disappear after a while
+pub fn update_orbs(context: &mut TickContext) {
+    let players: Vec<(Entity, Aabb)> = context
+        .entities
+        .query_mut::<(&Position, &Collider, &Health)>()
+        .with::<(&Player, &Experience)>()
+        .into_iter()
+        .filter(|(_, (_, _, health))| !health.is_dead())
+        .map(|(entity, (position, collider, _))| {
+            let aabb = Aabb::standing(position.0, collider.half_width, collider.height);
+            (entity, aabb)
+        })
+        .collect();
+
+    let mut expired = Vec::new();
+    let mut collected = Vec::new();
+    for (entity, (position, collider, velocity, on_ground, orb)) in context.entities.query_mut::<(
+        &Position,
+        &Collider,
+        &mut Velocity,
+        &OnGround,
+        &mut ExperienceOrb,
+    )>() {
+        orb.age += 1;
+        orb.pickup_delay = orb.pickup_delay.saturating_sub(1);
+        if orb.age >= DESPAWN_TICKS {
+            expired.push(entity);
+            continue;
+        }
+        let middle = position.0 + Vector3::unit_y() * collider.height / 2.0;
+        let closest = players
+            .iter()
+            .filter(|_| orb.pickup_delay == 0)
+            .map(|(player, aabb)| (*player, aabb, (aabb.middle() - middle).magnitude()))
+            .filter(|(_, _, distance)| *distance <= ATTRACT_RANGE)
+            .min_by(|a, b| a.2.total_cmp(&b.2));
+        let Some((player, aabb, _)) = closest else {
+            let keep = if on_ground.0 {
+                GROUND_FRICTION
+            } else {
+                AIR_DRAG
+            };
+            velocity.0.x *= keep;
+            velocity.0.z *= keep;
+            continue;
+        };
+        let own = Aabb::standing(position.0, collider.half_width, collider.height);
+        let reach = Vector3::new(PICKUP_REACH, PICKUP_REACH, PICKUP_REACH);
+        if aabb.inflated(reach).intersects(&own) {
+            collected.push((entity, player, orb.points));
+            continue;
+        }
+        let toward = aabb.middle() - middle;
+        if toward.magnitude2() > f32::EPSILON {
+            velocity.0 = toward.normalize() * ATTRACT_SPEED;
+        }
+    }
+    for entity in expired {
+        let _ = context.entities.despawn(entity);
+    }
+    for (entity, player, points) in collected {
+        if let Ok(experience) = context.entities.query_one_mut::<&mut Experience>(player) {
+            experience.0 = experience.0.saturating_add(points);
+        }
+        let _ = context.entities.despawn(entity);
+    }
+}
@@ -0,0 +1,281 @@
+use cgmath::{ElementWise, InnerSpace, Point3, Vector3};
+use hecs::Entity;
+use std::collections::HashSet;
+
+use super::components::{
+    Collider, GameMode, Health, OnGround, Position, PreviousPosition, PrimedTnt, Velocity,
+};
+use super::inventory::ItemStack;
+use super::physics::Aabb;
+use super::scheduler::TickContext;
+use super::{combat, drops, health, GameEvent};
+use crate::world::block::Interaction;
+use crate::world::noise;
+use crate::world::{BlockId, BlockRegistry, World};
+
+/// Four seconds from lighting TNT to it going off
+pub const FUSE_TICKS: u32 = 80;
+/// Shortest and longest fuse of TNT lit by another explosion, so a pile goes off in a ripple
+/// rather than all at once
+const CHAIN_FUSE_TICKS: [u32; 2] = [10, 30];
+/// How strongly TNT goes off, about how many blocks of air its rays get through
+const TNT_POWER: f32 = 4.0;
+/// Primed TNT is a block just small enough to fall through a one block gap
+const TNT_HALF_WIDTH: f32 = 0.49;
+/// Blocks per second primed TNT hops up with, and drifts sideways at most
+const PRIME_HOP: f32 = 4.0;
+const PRIME_DRIFT: f32 = 0.4;
+/// Horizontal speed kept each tick on the ground and in the air
+const GROUND_FRICTION: f32 = 0.6;
+const AIR_DRAG: f32 = 0.98;
+/// Rays go out from the middle through every point of a grid this many across on each side
+/// of a cube around it
+const RAYS_PER_SIDE: i32 = 16;
+/// Blocks a ray moves each step, it loses [`RAY_FALLOFF`] of its strength every step on top
+/// of what the blocks it goes through take
+const RAY_STEP: f32 = 0.3;
+const RAY_FALLOFF: f32 = 0.225;
+/// How much stronger or weaker than the power each ray starts out, as a fraction of it
+const RAY_VARIATION: f32 = 0.3;
+/// Entities within this many times the power get hurt and pushed
+const ENTITY_RANGE: f32 = 2.0;
+/// Damage at point blank with nothing in the way, times the range in blocks
+const DAMAGE_SCALE: f32 = 3.5;
+/// Blocks per second a point blank blast pushes entities with
+const PUSH_SPEED: f32 = 20.0;
+/// Points across each side of an entity's box checked for a clear line to the blast
+const EXPOSURE_SAMPLES: usize = 3;
+/// Blocks between the points checked along each of those lines
+const LINE_STEP: f32 = 0.25;
+
+/// Put a primed `block` where the block at `position` was, hopping up and a little to the
+/// side that `seed` picks. It goes off after `fuse` ticks.
+pub fn spawn_primed(
+    entities: &mut hecs::World,
+    position: Point3<i32>,
+    block: BlockId,
+    fuse: u32,
+    seed: u64,
+) -> Entity {
+    let random = |salt: i64| noise::hash_unit(seed, &[salt]) as f32 * 2.0 - 1.0;
+    let feet = Point3::new(
+        position.x as f32 + 0.5,
+        position.y as f32,
+        position.z as f32 + 0.5,
+    );
+    let velocity = Vector3::new(random(0) * PRIME_DRIFT, PRIME_HOP, random(1) * PRIME_DRIFT);
+    entities.spawn((
+        Position(feet),
+        PreviousPosition(feet),
+        Velocity(velocity),
+        Collider {
+            half_width: TNT_HALF_WIDTH,
+            height: TNT_HALF_WIDTH * 2.0,
+        },
+        OnGround(false),
+        PrimedTnt { block, fuse },
+    ))
+}
+
+/// Runs after movement: primed TNT slows down like anything else lying around and blows up
+/// from its middle once its fuse runs out
+pub fn update_primed_tnt(context: &mut TickContext) {
+    let mut exploding = Vec::new();
+    for (entity, (position, collider, velocity, on_ground, primed)) in
+        context.entities.query_mut::<(
+            &Position,
+            &Collider,
+            &mut Velocity,
+            &OnGround,
+            &mut PrimedTnt,
+        )>()
+    {
+        primed.fuse = primed.fuse.saturating_sub(1);
+        if primed.fuse == 0 {
+            let middle = position.0 + Vector3::unit_y() * collider.height / 2.0;
+            exploding.push((entity, middle));
+            continue;
+        }
+        let keep = if on_ground.0 {
+            GROUND_FRICTION
+        } else {
+            AIR_DRAG
+        };
+        velocity.0.x *= keep;
+        velocity.0.z *= keep;
+    }
+    for (entity, middle) in exploding {
+        let _ = context.entities.despawn(entity);
+        let seed = noise::hash(
+            context.info.seed,
+            &[context.tick as i64, entity.id() as i64],
+        );
+        explode(context, middle, TNT_POWER, seed);
+    }
+}
+
+/// Blow up at `center`. Entities close by with a clear line to it get hurt and pushed away,
+/// then rays go out in every direction and break the blocks they get through, weakening with
+/// distance and with how hard the blocks are. Unbreakable blocks and fluids stop them. Only
+/// one in `power` broken blocks drops anything, though whatever was stored in them always
+/// does, and TNT that gets hit is lit with a short fuse. `seed` varies the rays and drops.
+pub fn explode(context: &mut TickContext, center: Point3<f32>, power: f32, seed: u64) {
+    hurt_entities(context, center, power);
+    let destroyed = destroyed_blocks(context.world, context.blocks, center, power, seed);
+    for (index, position) in destroyed.into_iter().enumerate() {
+        let Some(id) = context.world.block(position) else {
+            continue;
+        };
+        let Some(block) = context.blocks.get(id) else {
+            continue;
+        };
+        let roll = noise::hash(seed, &[index as i64, -1]);
+        if block.interaction == Some(Interaction::Ignite) {
+            context.world.set_block(position, BlockId::AIR);
+            let [least, most] = CHAIN_FUSE_TICKS;
+            let fuse = least + (roll % (most - least + 1) as u64) as u32;
+            spawn_primed(context.entities, position, id, fuse, roll);
+            continue;
+        }
+        let contents: Vec<ItemStack> = context
+            .world
+            .take_block_entity(position)
+            .map(|entity| entity.slots().iter().flatten().copied().collect())
+            .unwrap_or_default();
+        context.world.set_block(position, BlockId::AIR);
+        let dropped = noise::hash_unit(seed, &[index as i64, -2]) < 1.0 / power as f64;
+        let drops = context
+            .items
+            .drops(id)
+            .iter()
+            .filter(|_| dropped)
+            .map(|item| ItemStack::new(*item, 1));
+        for (salt, stack) in drops.chain(contents).enumerate() {
+            let seed = noise::hash(roll, &[salt as i64]);
+            drops::spawn_from_block(context.entities, position, stack, seed);
+        }
+    }
+    context.events.push(GameEvent::Explosion { center, power });
+}
+
+/// Every block the rays from `center` get through, in the order they're first reached
+fn destroyed_blocks(
+    world: &World,
+    blocks: &BlockRegistry,
+    center: Point3<f32>,
+    power: f32,
+    seed: u64,
+) -> Vec<Point3<i32>> {
+    let mut seen = HashSet::new();
+    let mut destroyed = Vec::new();
+    for (ray, direction) in ray_directions().enumerate() {
+        let variation = noise::hash_unit(seed, &[ray as i64]) as f32 * 2.0 - 1.0;
+        let mut strength = power * (1.0 + variation * RAY_VARIATION);
+        let mut point = center;
+        while strength > 0.0 {
+            let position = point.map(|value| value.floor() as i32);
+            let Some(id) = world.block(position) else {
+                break;
+            };
+            if id != BlockId::AIR {
+                let Some(block) = blocks.get(id) else {
+                    break;
+                };
+                if block.hardness < 0.0 || block.fluid.is_some() {
+                    break;
+                }
+                strength -= (block.hardness + RAY_STEP) * RAY_STEP;
+                if strength > 0.0 && seen.insert(position) {
+                    destroyed.push(position);
+                }
+            }
+            point += direction * RAY_STEP;
+            strength -= RAY_FALLOFF;
+        }
+    }
+    destroyed
+}
+
+/// Unit vectors from the middle of a cube through each point on its surface
+fn ray_directions() -> impl Iterator<Item = Vector3<f32>> {
+    let last = RAYS_PER_SIDE - 1;
+    let grid = (0..RAYS_PER_SIDE).flat_map(|x| {
+        (0..RAYS_PER_SIDE).flat_map(move |y| (0..RAYS_PER_SIDE).map(move |z| [x, y, z]))
+    });
+    grid.filter(move |point| point.iter().any(|value| *value == 0 || *value == last))
+        .map(move |[x, y, z]| {
+            Vector3::new(x, y, z)
+                .map(|value| value as f32 / last as f32 * 2.0 - 1.0)
+                .normalize()
+        })
+}
+
+/// Hurts and pushes everything within range of `center`, more the closer and the less hidden
+/// behind solid blocks it is. Players that can't get hurt are still pushed.
+fn hurt_entities(context: &mut TickContext, center: Point3<f32>, power: f32) {
+    let range = power * ENTITY_RANGE;
+    let (world, blocks) = (&*context.world, context.blocks);
+    let mut pushed = Vec::new();
+    for (entity, (position, collider, health, mode)) in
+        context
+            .entities
+            .query_mut::<(&Position, &Collider, Option<&mut Health>, Option<&GameMode>)>()
+    {
+        let aabb = Aabb::standing(position.0, collider.half_width, collider.height);
+        let offset = aabb.middle() - center;
+        let distance = offset.magnitude();
+        if distance > range {
+            continue;
+        }
+        let impact = (1.0 - distance / range) * exposure(world, blocks, center, &aabb);
+        if impact <= 0.0 {
+            continue;
+        }
+        if let Some(health) = health.filter(|_| GameMode::vulnerable(mode)) {
+            let damage = (impact * impact + impact) / 2.0 * DAMAGE_SCALE * range + 1.0;
+            health::hurt(health, damage as u32);
+        }
+        let direction = if distance > f32::EPSILON {
+            offset / distance
+        } else {
+            Vector3::unit_y()
+        };
+        pushed.push((entity, direction * impact * PUSH_SPEED));
+    }
+    for (entity, impulse) in pushed {
+        combat::push(context.entities, entity, impulse);
+    }
+}
+
+/// How much of `aabb` `center` can see, from 0 behind a wall to 1 out in the open, going by
+/// a grid of points over it
+fn exposure(world: &World, blocks: &BlockRegistry, center: Point3<f32>, aabb: &Aabb) -> f32 {
+    let last = (EXPOSURE_SAMPLES - 1) as f32;
+    let size = aabb.max - aabb.min;
+    let mut clear = 0;
+    for x in 0..EXPOSURE_SAMPLES {
+        for y in 0..EXPOSURE_SAMPLES {
+            for z in 0..EXPOSURE_SAMPLES {
+                let fraction = Vector3::new(x, y, z).map(|value| value as f32 / last);
+                let point = aabb.min + size.mul_element_wise(fraction);
+                if clear_line(world, blocks, center, point) {
+                    clear += 1;
+                }
+            }
+        }
+    }
+    clear as f32 / EXPOSURE_SAMPLES.pow(3) as f32
+}
+
+/// Whether there's no solid block between `from` and `to`, unloaded chunks don't count
+fn clear_line(world: &World, blocks: &BlockRegistry, from: Point3<f32>, to: Point3<f32>) -> bool {
+    let offset = to - from;
+    let steps = (offset.magnitude() / LINE_STEP).ceil().max(1.0) as u32;
+    (0..steps).all(|step| {
+        let point = from + offset * (step as f32 / steps as f32);
+        world
+            .block(point.map(|value| value.floor() as i32))
+            .and_then(|id| blocks.get(id))
+            .is_none_or(|block| !block.solid)
+    })
+}
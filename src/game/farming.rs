@@ -0,0 +1,205 @@
+use cgmath::{Point3, Vector3};
+
+use super::drops;
+use super::inventory::ItemStack;
+use super::scheduler::TickContext;
+use crate::world::block::{Crop, Soil, Tilled};
+use crate::world::chunk::CHUNK_SIZE;
+use crate::world::noise;
+use crate::world::{BlockId, BlockRegistry, World};
+
+/// Blocks picked at random in every loaded chunk each tick to grow or dry out
+const RANDOM_TICKS_PER_CHUNK: u32 = 3;
+/// Chance a crop picked at random grows a stage, on moist and on dry farmland
+const MOIST_GROWTH_CHANCE: f64 = 1.0 / 3.0;
+const DRY_GROWTH_CHANCE: f64 = 1.0 / 8.0;
+/// Farmland stays moist with water this many blocks away sideways, level with it or a block
+/// higher
+const WATER_REACH: i32 = 4;
+/// What farmland is watered by, and what it turns back into once it dries out with nothing
+/// growing on it
+const WATER: &str = "water";
+const BARE_SOIL: &str = "dirt";
+/// Keeps the random ticks' numbers apart from anything else hashed from the seed
+const FARMING_PURPOSE: i64 = 0x6661_726d;
+
+/// Runs every tick: picks a few blocks in every loaded chunk at random, crops among them
+/// might grow a stage and farmland gets wet or dries out depending on the water nearby
+pub fn random_ticks(context: &mut TickContext) {
+    let (seed, tick) = (context.info.seed, context.tick);
+    let mut chunks: Vec<_> = context.world.chunk_positions().collect();
+    chunks.sort();
+    for chunk in chunks {
+        if context
+            .world
+            .chunk(chunk)
+            .is_none_or(|chunk| chunk.is_empty())
+        {
+            continue;
+        }
+        for attempt in 0..RANDOM_TICKS_PER_CHUNK {
+            let values = [
+                tick as i64,
+                chunk.x as i64,
+                chunk.y as i64,
+                chunk.z as i64,
+                attempt as i64,
+                FARMING_PURPOSE,
+            ];
+            let random = noise::hash(seed, &values);
+            let size = CHUNK_SIZE as u64;
+            let offset = Vector3::new(random, random / size, random / (size * size))
+                .map(|value| value % size);
+            let position = chunk.origin() + offset.map(|value| value as i32);
+            random_tick(context.world, context.blocks, position, random);
+        }
+    }
+}
+
+fn random_tick(world: &mut World, blocks: &BlockRegistry, position: Point3<i32>, seed: u64) {
+    let Some(id) = world.block(position) else {
+        return;
+    };
+    let Some(block) = blocks.get(id) else {
+        return;
+    };
+    if let Some(crop) = block.crop {
+        grow(world, blocks, position, crop, seed);
+    } else if let Some(soil) = block.soil {
+        water_soil(world, blocks, position, soil);
+    }
+}
+
+/// Maybe grow the crop at `position` a stage, if it's lit well enough and still has farmland
+/// under it. Moist farmland makes it likelier.
+fn grow(world: &mut World, blocks: &BlockRegistry, position: Point3<i32>, crop: Crop, seed: u64) {
+    if crop.is_ripe() {
+        return;
+    }
+    let Some(soil) = soil_at(world, blocks, position - Vector3::unit_y()) else {
+        return;
+    };
+    let lit = world
+        .light(position)
+        .is_some_and(|light| light.sky.max(light.block) >= crop.light);
+    let chance = if soil.moist {
+        MOIST_GROWTH_CHANCE
+    } else {
+        DRY_GROWTH_CHANCE
+    };
+    if !lit || noise::hash_unit(seed, &[FARMING_PURPOSE]) >= chance {
+        return;
+    }
+    if let Some(grown) = blocks.grown(crop.base, crop.stage + 1) {
+        world.set_block(position, grown);
+    }
+}
+
+/// Make the farmland at `position` moist if there's water close enough or let it dry out if
+/// not. Dry farmland without a crop on it turns back into dirt.
+fn water_soil(world: &mut World, blocks: &BlockRegistry, position: Point3<i32>, soil: Soil) {
+    let watered = (-WATER_REACH..=WATER_REACH).any(|x| {
+        (0..=1).any(|y| {
+            (-WATER_REACH..=WATER_REACH)
+                .any(|z| is_water(world, blocks, position + Vector3::new(x, y, z)))
+        })
+    });
+    if watered != soil.moist {
+        if let Some(variant) = blocks.moistened(soil.base, watered) {
+            world.set_block(position, variant);
+        }
+        return;
+    }
+    let planted = world
+        .block(position + Vector3::unit_y())
+        .and_then(|above| blocks.get(above))
+        .is_some_and(|above| above.crop.is_some());
+    if !watered && !planted {
+        if let Some(dirt) = blocks.id(BARE_SOIL) {
+            world.set_block(position, dirt);
+        }
+    }
+}
+
+/// Runs on the blocks changed since the last tick: crops that lost the farmland under them
+/// break, leaving their drops behind
+pub fn uproot_crops(context: &mut TickContext, changed: &[Point3<i32>]) {
+    for (index, below) in changed.iter().enumerate() {
+        let position = below + Vector3::unit_y();
+        let Some(id) = context.world.block(position) else {
+            continue;
+        };
+        let planted = context
+            .blocks
+            .get(id)
+            .is_some_and(|block| block.crop.is_some());
+        if !planted || soil_at(context.world, context.blocks, *below).is_some() {
+            continue;
+        }
+        context.world.set_block(position, BlockId::AIR);
+        let seed = noise::hash(
+            context.info.seed,
+            &[context.tick as i64, index as i64, FARMING_PURPOSE],
+        );
+        for (salt, item) in context.items.drops(id).iter().enumerate() {
+            let seed = noise::hash(seed, &[salt as i64]);
+            drops::spawn_from_block(context.entities, position, ItemStack::new(*item, 1), seed);
+        }
+    }
+}
+
+/// Turn the block at `position` into what tilling it with a hoe makes of it, as long as
+/// nothing is on top of it. Returns its `tilled` section, for what tilling it turns up.
+pub fn till(world: &mut World, blocks: &BlockRegistry, position: Point3<i32>) -> Option<Tilled> {
+    let tilled = blocks.get(world.block(position)?)?.tilled.clone()?;
+    if world.block(position + Vector3::unit_y()) != Some(BlockId::AIR) {
+        return None;
+    }
+    world.set_block(position, blocks.id(&tilled.into)?);
+    Some(tilled)
+}
+
+/// Grow the crop at `position` by between the first and second of `stages`, which `seed`
+/// picks. Returns whether it grew, ripe crops don't.
+pub fn fertilize(
+    world: &mut World,
+    blocks: &BlockRegistry,
+    position: Point3<i32>,
+    stages: [u8; 2],
+    seed: u64,
+) -> bool {
+    let Some(crop) = world
+        .block(position)
+        .and_then(|id| blocks.get(id))
+        .and_then(|block| block.crop)
+    else {
+        return false;
+    };
+    if crop.is_ripe() {
+        return false;
+    }
+    let [least, most] = stages;
+    let grown = least + (noise::hash(seed, &[FARMING_PURPOSE]) % u64::from(most - least + 1)) as u8;
+    let stage = crop.stage.saturating_add(grown).min(crop.stages - 1);
+    match blocks.grown(crop.base, stage) {
+        Some(block) => world.set_block(position, block).is_some(),
+        None => false,
+    }
+}
+
+/// Whether a crop can be planted on the block at `position`
+pub fn is_farmland(world: &World, blocks: &BlockRegistry, position: Point3<i32>) -> bool {
+    soil_at(world, blocks, position).is_some()
+}
+
+fn soil_at(world: &World, blocks: &BlockRegistry, position: Point3<i32>) -> Option<Soil> {
+    blocks.get(world.block(position)?)?.soil
+}
+
+fn is_water(world: &World, blocks: &BlockRegistry, position: Point3<i32>) -> bool {
+    let fluid = world
+        .block(position)
+        .and_then(|id| blocks.get(id))
+        .and_then(|block| block.fluid);
+    fluid.is_some_and(|fluid| Some(fluid.source) == blocks.id(WATER))
+}
@@ -0,0 +1,128 @@
+use cgmath::Vector3;
+
+use super::components::{
+    Breath, FallDistance, GameMode, Health, InFluid, Movement, OnGround, Position, PreviousPosition,
+};
+use super::scheduler::TickContext;
+use super::{EYE_HEIGHT, SNEAK_EYE_HEIGHT};
+
+/// Ten hearts
+pub const MAX_HEALTH: u32 = 20;
+/// Fifteen seconds under water before drowning starts
+pub const MAX_BREATH: u32 = 300;
+/// Ticks after a hit that further ones don't land
+const INVULNERABLE_TICKS: u32 = 10;
+/// Ticks the view flashes red for after a hit
+pub const HURT_TICKS: u32 = 10;
+/// Blocks anything can fall without getting hurt, every block past it is a hit point
+const SAFE_FALL: f32 = 3.0;
+/// Hit points lost every [`DROWN_INTERVAL`] ticks once out of air
+const DROWN_DAMAGE: u32 = 2;
+const DROWN_INTERVAL: u64 = 20;
+/// Ticks of air got back for every tick spent breathing
+const BREATH_RECOVERY: u32 = 5;
+
+/// Take `amount` hit points off unless the last hit was too recent or it's already dead.
+/// Returns whether the hit landed.
+pub fn hurt(health: &mut Health, amount: u32) -> bool {
+    if amount == 0 || health.invulnerable > 0 || health.is_dead() {
+        return false;
+    }
+    health.points = health.points.saturating_sub(amount);
+    health.invulnerable = INVULNERABLE_TICKS;
+    health.hurt = HURT_TICKS;
+    true
+}
+
+/// Runs before anything can hurt, so a hit keeps out the next one for exactly
+/// [`INVULNERABLE_TICKS`]
+pub fn recover(context: &mut TickContext) {
+    for (_, health) in context.entities.query_mut::<&mut Health>() {
+        health.invulnerable = health.invulnerable.saturating_sub(1);
+        health.hurt = health.hurt.saturating_sub(1);
+    }
+}
+
+/// Runs after movement: adds up how far each entity dropped this tick and turns it into
+/// damage when it lands. Fluids break a fall and flying never starts one, and creative
+/// players don't fall at all as far as damage goes.
+pub fn take_fall_damage(context: &mut TickContext) {
+    for (_, (position, previous, on_ground, fall, health, movement, in_fluid, mode)) in
+        context.entities.query_mut::<(
+            &Position,
+            &PreviousPosition,
+            &OnGround,
+            &mut FallDistance,
+            &mut Health,
+            Option<&Movement>,
+            Option<&InFluid>,
+            Option<&GameMode>,
+        )>()
+    {
+        let flying = movement.is_some_and(|movement| movement.flying);
+        let in_fluid = in_fluid.is_some_and(|in_fluid| in_fluid.0.is_some());
+        if flying || in_fluid || !GameMode::vulnerable(mode) {
+            fall.0 = 0.0;
+            continue;
+        }
+        fall.0 += (previous.0.y - position.0.y).max(0.0);
+        if on_ground.0 {
+            let damage = (fall.0 - SAFE_FALL).ceil().max(0.0) as u32;
+            hurt(health, damage);
+            fall.0 = 0.0;
+        }
+    }
+}
+
+/// Fluids with damage, like lava, hurt whatever is in them
+pub fn take_fluid_damage(context: &mut TickContext) {
+    let blocks = context.blocks;
+    for (_, (in_fluid, health, mode)) in context
+        .entities
+        .query_mut::<(&InFluid, &mut Health, Option<&GameMode>)>()
+    {
+        if !GameMode::vulnerable(mode) {
+            continue;
+        }
+        let damage = in_fluid
+            .0
+            .and_then(|source| blocks.get(source)?.fluid)
+            .map_or(0, |fluid| fluid.properties.damage);
+        hurt(health, damage);
+    }
+}
+
+/// Air runs out with the head inside a fluid and comes back quickly out of it. Without any
+/// left, drowning hurts once a second. Creative players never run out.
+pub fn breathe(context: &mut TickContext) {
+    let (world, blocks, tick) = (&*context.world, context.blocks, context.tick);
+    for (_, (position, breath, health, movement, mode)) in context.entities.query_mut::<(
+        &Position,
+        &mut Breath,
+        &mut Health,
+        Option<&Movement>,
+        Option<&GameMode>,
+    )>() {
+        if !GameMode::vulnerable(mode) {
+            breath.0 = MAX_BREATH;
+            continue;
+        }
+        let eye_height = if movement.is_some_and(|movement| movement.sneaking) {
+            SNEAK_EYE_HEIGHT
+        } else {
+            EYE_HEIGHT
+        };
+        let eye = (position.0 + Vector3::unit_y() * eye_height).map(|value| value.floor() as i32);
+        let submerged = world
+            .block(eye)
+            .and_then(|id| blocks.get(id))
+            .is_some_and(|block| block.fluid.is_some());
+        if !submerged {
+            breath.0 = (breath.0 + BREATH_RECOVERY).min(MAX_BREATH);
+        } else if breath.0 > 0 {
+            breath.0 -= 1;
+        } else if tick.is_multiple_of(DROWN_INTERVAL) {
+            hurt(health, DROWN_DAMAGE);
+        }
+    }
+}
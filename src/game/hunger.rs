@@ -0,0 +1,97 @@
+use cgmath::{InnerSpace, Vector2};
+
+use super::components::{GameMode, Health, Hunger, Movement, OnGround, Position, PreviousPosition};
+use super::health::{self, MAX_HEALTH};
+use super::item::Food;
+use super::scheduler::TickContext;
+
+/// Ten drumsticks
+pub const MAX_FOOD: u32 = 20;
+/// What a new player starts out with on top of full food
+pub const START_SATURATION: f32 = 5.0;
+/// Effort that takes one point of saturation, or of food once there's none left
+pub const EXHAUSTION_PER_POINT: f32 = 4.0;
+/// Sprinting takes more food than this
+pub const SPRINT_FOOD: u32 = 6;
+/// Effort for every block sprinted
+const SPRINT_EXHAUSTION: f32 = 0.1;
+/// Effort for a jump, and for one while sprinting
+pub const JUMP_EXHAUSTION: f32 = 0.05;
+pub const SPRINT_JUMP_EXHAUSTION: f32 = 0.2;
+/// Food it takes to heal, and the effort each healed hit point costs
+const REGENERATION_FOOD: u32 = 18;
+const REGENERATION_EXHAUSTION: f32 = 6.0;
+/// Ticks between healed or starved hit points
+const HEALTH_INTERVAL: u32 = 80;
+/// Ticks of holding food to the mouth before it's eaten
+pub const EAT_TICKS: u32 = 32;
+
+/// Fill `hunger` up with one of `food`
+pub fn eat(hunger: &mut Hunger, food: Food) {
+    hunger.food = (hunger.food + food.hunger).min(MAX_FOOD);
+    hunger.saturation = (hunger.saturation + food.saturation).min(hunger.food as f32);
+}
+
+/// Runs after movement, sprinting wears the player out by the ground it actually covered.
+/// Nothing wears out a creative player.
+pub fn exhaust(context: &mut TickContext) {
+    for (_, (position, previous, movement, on_ground, hunger, mode)) in
+        context.entities.query_mut::<(
+            &Position,
+            &PreviousPosition,
+            &Movement,
+            &OnGround,
+            &mut Hunger,
+            Option<&GameMode>,
+        )>()
+    {
+        if !GameMode::vulnerable(mode) {
+            hunger.exhaustion = 0.0;
+            continue;
+        }
+        if movement.sprinting && on_ground.0 {
+            let moved = position.0 - previous.0;
+            hunger.exhaustion += Vector2::new(moved.x, moved.z).magnitude() * SPRINT_EXHAUSTION;
+        }
+    }
+}
+
+/// Turns exhaustion into lost saturation and food, then heals while well fed or starves
+/// while there's no food left. Starving stops at half a heart and creative players neither
+/// heal nor starve.
+pub fn metabolize(context: &mut TickContext) {
+    for (_, (hunger, health, mode)) in context
+        .entities
+        .query_mut::<(&mut Hunger, &mut Health, Option<&GameMode>)>()
+    {
+        if health.is_dead() || !GameMode::vulnerable(mode) {
+            continue;
+        }
+        while hunger.exhaustion >= EXHAUSTION_PER_POINT {
+            hunger.exhaustion -= EXHAUSTION_PER_POINT;
+            if hunger.saturation > 0.0 {
+                hunger.saturation = (hunger.saturation - 1.0).max(0.0);
+            } else {
+                hunger.food = hunger.food.saturating_sub(1);
+            }
+        }
+
+        let healing = hunger.food >= REGENERATION_FOOD && health.points < MAX_HEALTH;
+        let starving = hunger.food == 0 && health.points > 1;
+        if !healing && !starving {
+            hunger.timer = 0;
+            continue;
+        }
+        hunger.timer += 1;
+        if hunger.timer < HEALTH_INTERVAL {
+            continue;
+        }
+        hunger.timer = 0;
+        if healing {
+            health.points += 1;
+            hunger.exhaustion += REGENERATION_EXHAUSTION;
+        } else {
+            health::hurt(health, 1);
+        }
+    }
+}
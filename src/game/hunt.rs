@@ -0,0 +1,153 @@
+use cgmath::{InnerSpace, Point3, Vector3};
+
+use super::components::{
+    BurnsInDaylight, Collider, Dying, GameMode, Health, Hunt, InFluid, Look, OnGround, Player,
+    Position, Velocity,
+};
+use super::pathfinding;
+use super::physics::Aabb;
+use super::scheduler::TickContext;
+use super::systems::JUMP_VELOCITY;
+use super::{combat, health};
+use crate::world::light::MAX_LIGHT;
+
+/// Ticks between working out the way to the player again, it keeps to the old one meanwhile
+const REPATH_TICKS: u32 = 10;
+/// Ticks between hits
+const ATTACK_COOLDOWN: u32 = 20;
+/// How far past its own box a mob reaches to hit, sideways
+const ATTACK_REACH: f32 = 0.3;
+/// How close to the middle of a block on its path a mob has to get before heading for the
+/// next one, sideways
+const WAYPOINT_REACHED: f32 = 0.3;
+/// What burning in daylight does, once a second
+const BURN_DAMAGE: u32 = 1;
+const BURN_INTERVAL: u64 = 20;
+
+/// Runs after wandering and takes over from it for mobs that notice the player: they follow
+/// a path to the player worked out every [`REPATH_TICKS`], and hit them once close enough.
+/// Creative and dead players go unnoticed.
+pub fn hunt(context: &mut TickContext) {
+    let (world, blocks) = (&*context.world, context.blocks);
+    let target = {
+        let mut query = context
+            .entities
+            .query::<(&Position, &Collider, &Health, Option<&GameMode>)>()
+            .with::<&Player>();
+        query
+            .iter()
+            .find(|(_, (_, _, health, mode))| !health.is_dead() && GameMode::vulnerable(*mode))
+            .map(|(entity, (position, collider, _, _))| {
+                let aabb = Aabb::standing(position.0, collider.half_width, collider.height);
+                (entity, position.0, aabb)
+            })
+    };
+    let Some((player, target, target_box)) = target else {
+        return;
+    };
+
+    let mut hits = Vec::new();
+    for (entity, (position, look, velocity, on_ground, collider, hunt)) in context
+        .entities
+        .query_mut::<(
+            &Position,
+            &mut Look,
+            &mut Velocity,
+            &OnGround,
+            &Collider,
+            &mut Hunt,
+        )>()
+        .without::<&Dying>()
+    {
+        hunt.cooldown = hunt.cooldown.saturating_sub(1);
+        hunt.repath = hunt.repath.saturating_sub(1);
+        if (target - position.0).magnitude() > hunt.range {
+            hunt.path.clear();
+            continue;
+        }
+
+        let reach = Aabb::standing(position.0, collider.half_width, collider.height)
+            .inflated(Vector3::new(ATTACK_REACH, 0.0, ATTACK_REACH));
+        if hunt.cooldown == 0 && reach.intersects(&target_box) {
+            hits.push((hunt.damage, target - position.0));
+            hunt.cooldown = ATTACK_COOLDOWN;
+        }
+
+        let feet = position.0.map(|value| value.floor() as i32);
+        if hunt.repath == 0 {
+            let goal = target.map(|value| value.floor() as i32);
+            let height = collider.height.ceil() as i32;
+            hunt.path =
+                pathfinding::find_path(world, blocks, feet, goal, height).unwrap_or_default();
+            // Spread out over ticks so a crowd doesn't search all at once
+            hunt.repath = REPATH_TICKS + entity.id() % REPATH_TICKS;
+        }
+        while hunt
+            .path
+            .first()
+            .is_some_and(|next| sideways(middle(*next) - position.0) < WAYPOINT_REACHED)
+        {
+            hunt.path.remove(0);
+        }
+
+        // With nowhere left to go on the path it's close enough to head straight at them
+        let next = hunt.path.first().copied();
+        let aim = next.map_or(target, middle);
+        let direction = Vector3::new(aim.x - position.0.x, 0.0, aim.z - position.0.z);
+        if direction.magnitude2() > f32::EPSILON {
+            let direction = direction.normalize();
+            look.yaw = direction.x.atan2(-direction.z);
+            velocity.0.x = direction.x * hunt.speed;
+            velocity.0.z = direction.z * hunt.speed;
+        }
+        if on_ground.0 && next.is_some_and(|next| next.y > feet.y) {
+            velocity.0.y = JUMP_VELOCITY;
+        }
+    }
+
+    for (damage, direction) in hits {
+        let landed = context
+            .entities
+            .query_one_mut::<&mut Health>(player)
+            .is_ok_and(|health| health::hurt(health, damage));
+        if landed {
+            combat::knock_back(context.entities, player, direction);
+        }
+    }
+}
+
+/// Mobs that burn in daylight take damage every second they're out under the open sky in it,
+/// unless rain or a fluid puts them out
+pub fn burn_in_daylight(context: &mut TickContext) {
+    let info = &*context.info;
+    if !super::is_daytime(info.time)
+        || info.weather.raining
+        || !context.tick.is_multiple_of(BURN_INTERVAL)
+    {
+        return;
+    }
+    let world = &*context.world;
+    for (_, (position, collider, health, in_fluid)) in context
+        .entities
+        .query_mut::<(&Position, &Collider, &mut Health, Option<&InFluid>)>()
+        .with::<&BurnsInDaylight>()
+    {
+        let head = (position.0 + Vector3::unit_y() * collider.height * 0.9)
+            .map(|value| value.floor() as i32);
+        let open_sky = world
+            .light(head)
+            .is_some_and(|light| light.sky == MAX_LIGHT);
+        if open_sky && in_fluid.is_none_or(|in_fluid| in_fluid.0.is_none()) {
+            health::hurt(health, BURN_DAMAGE);
+        }
+    }
+}
+
+/// Where to stand on top of the block below `feet`
+fn middle(feet: Point3<i32>) -> Point3<f32> {
+    Point3::new(feet.x as f32 + 0.5, feet.y as f32, feet.z as f32 + 0.5)
+}
+
+fn sideways(offset: Vector3<f32>) -> f32 {
+    offset.x.hypot(offset.z)
+}
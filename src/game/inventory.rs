@@ -0,0 +1,280 @@
+use super::enchanting::{self, Modifiers};
+use super::item::{ItemId, ItemRegistry};
+use crate::save::SavedStack;
+
+/// Slots along the bottom of the screen, the first ones in [`Inventory::slots`]
+pub const HOTBAR_SLOTS: usize = 9;
+/// The hotbar and three rows above it
+pub const SLOTS: usize = 36;
+
+/// Some number of one kind of item, never 0 and never more than the item's max stack
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: ItemId,
+    pub count: u32,
+    /// Uses a tool has had, it breaks at its durability. Always 0 for anything else, tools
+    /// don't stack so it never needs to be shared.
+    pub damage: u32,
+    /// What the enchanting table added, only ever on tools like the damage
+    pub modifiers: Modifiers,
+}
+
+impl ItemStack {
+    pub fn new(item: ItemId, count: u32) -> Self {
+        ItemStack {
+            item,
+            count,
+            damage: 0,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    /// The same item worn the same, just `count` of it
+    pub fn with_count(self, count: u32) -> Self {
+        ItemStack { count, ..self }
+    }
+
+    /// Split off `count` of it, None for the part left behind if that's all of it
+    pub fn take(self, count: u32) -> (ItemStack, Option<ItemStack>) {
+        let count = count.min(self.count);
+        let left = (count < self.count).then(|| self.with_count(self.count - count));
+        (self.with_count(count), left)
+    }
+}
+
+/// What the player carries, the hotbar first. The item in the selected hotbar slot is what
+/// gets used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    pub slots: [Option<ItemStack>; SLOTS],
+    /// Index into the hotbar
+    pub selected: usize,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Inventory {
+            slots: [None; SLOTS],
+            selected: 0,
+        }
+    }
+}
+
+impl Inventory {
+    /// Stacks of items that no longer exist are dropped
+    pub fn load(saved: &[SavedStack], selected: usize, items: &ItemRegistry) -> Self {
+        let mut inventory = Inventory::default();
+        for stack in saved {
+            let Some(item) = items.id(&stack.item) else {
+                log::warn!("Dropping {} of unknown item {}", stack.count, stack.item);
+                continue;
+            };
+            if let Some(slot) = inventory.slots.get_mut(stack.slot) {
+                let count = stack.count.min(items.max_stack(item));
+                *slot = (count > 0).then_some(ItemStack {
+                    item,
+                    count,
+                    damage: stack.damage,
+                    modifiers: stack.modifiers,
+                });
+            }
+        }
+        inventory.select(selected);
+        inventory
+    }
+
+    /// The filled slots, items by name so saves survive items being added or reordered
+    pub fn save(&self, items: &ItemRegistry) -> Vec<SavedStack> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, stack)| {
+                let stack = (*stack)?;
+                Some(SavedStack {
+                    slot,
+                    item: items.get(stack.item)?.name.clone(),
+                    count: stack.count,
+                    damage: stack.damage,
+                    modifiers: stack.modifiers,
+                })
+            })
+            .collect()
+    }
+
+    pub fn selected_stack(&self) -> Option<ItemStack> {
+        self.slots[self.selected]
+    }
+
+    /// Out of range slots pick the last one
+    pub fn select(&mut self, slot: usize) {
+        self.selected = slot.min(HOTBAR_SLOTS - 1);
+    }
+
+    /// Move the selection by `notches` slots, wrapping around at either end
+    pub fn scroll(&mut self, notches: i32) {
+        self.selected = (self.selected as i32 + notches).rem_euclid(HOTBAR_SLOTS as i32) as usize;
+    }
+
+    /// Top up stacks of the same item first, then fill empty slots with the hotbar first.
+    /// Returns what didn't fit.
+    pub fn add(&mut self, stack: ItemStack, items: &ItemRegistry) -> Option<ItemStack> {
+        let max_stack = items.max_stack(stack.item);
+        let mut left = stack.count;
+        for slot in self.slots.iter_mut().flatten() {
+            if slot.item == stack.item {
+                let moved = left.min(max_stack.saturating_sub(slot.count));
+                slot.count += moved;
+                left -= moved;
+            }
+        }
+        for slot in &mut self.slots {
+            if left == 0 {
+                break;
+            }
+            if slot.is_none() {
+                let moved = left.min(max_stack);
+                *slot = Some(stack.with_count(moved));
+                left -= moved;
+            }
+        }
+        (left > 0).then(|| stack.with_count(left))
+    }
+
+    /// Use up one of the selected stack, returns its item
+    pub fn take_selected(&mut self) -> Option<ItemId> {
+        let slot = &mut self.slots[self.selected];
+        let stack = (*slot)?;
+        *slot = stack.take(1).1;
+        Some(stack.item)
+    }
+
+    /// Wear the selected tool down by `uses`, it's gone once it runs out. Anything that isn't
+    /// a tool doesn't wear, and unbreaking lets some uses go by, picked by `seed`.
+    pub fn wear_selected(&mut self, uses: u32, seed: u64, items: &ItemRegistry) {
+        let slot = &mut self.slots[self.selected];
+        let Some(stack) = slot else {
+            return;
+        };
+        let Some(tool) = items.tool(stack.item) else {
+            return;
+        };
+        stack.damage += enchanting::wear(stack.modifiers, uses, seed);
+        if stack.damage >= tool.durability {
+            *slot = None;
+        }
+    }
+
+    /// Select the hotbar slot already holding `item`, or swap the first stack of it from the
+    /// rest of the inventory into the selected slot. Returns whether there was any.
+    pub fn pick(&mut self, item: ItemId) -> bool {
+        let Some(slot) = self
+            .slots
+            .iter()
+            .position(|stack| stack.is_some_and(|stack| stack.item == item))
+        else {
+            return false;
+        };
+        if slot < HOTBAR_SLOTS {
+            self.selected = slot;
+        } else {
+            self.slots.swap(slot, self.selected);
+        }
+        true
+    }
+
+    /// [`Inventory::pick`] `item` if it's there, otherwise fill the selected slot with a full
+    /// stack of it, or the first empty one if the selected slot is taken
+    pub fn pick_or_fill(&mut self, item: ItemId, items: &ItemRegistry) {
+        if self.pick(item) {
+            return;
+        }
+        let hotbar = &self.slots[..HOTBAR_SLOTS];
+        if self.slots[self.selected].is_some() {
+            if let Some(empty) = hotbar.iter().position(Option::is_none) {
+                self.selected = empty;
+            }
+        }
+        self.slots[self.selected] = Some(ItemStack::new(item, items.max_stack(item)));
+    }
+}
+
+/// A left click on `target` with `cursor` held: pick up the whole stack, put down the whole
+/// held stack, top up a stack of the same item or swap two different ones or full ones
+pub fn click(target: &mut Option<ItemStack>, cursor: &mut Option<ItemStack>, items: &ItemRegistry) {
+    match (*target, *cursor) {
+        (Some(stack), Some(held))
+            if stack.item == held.item && stack.count < items.max_stack(stack.item) =>
+        {
+            let max_stack = items.max_stack(stack.item);
+            let moved = held.count.min(max_stack - stack.count);
+            *target = Some(stack.with_count(stack.count + moved));
+            *cursor = held.take(moved).1;
+        }
+        _ => std::mem::swap(target, cursor),
+    }
+}
+
+/// A right click on `target`: pick up half the stack with nothing held, rounded up, or put
+/// down one of the held stack
+pub fn right_click(
+    target: &mut Option<ItemStack>,
+    cursor: &mut Option<ItemStack>,
+    items: &ItemRegistry,
+) {
+    match (*target, *cursor) {
+        (Some(stack), None) => {
+            let (half, left) = stack.take(stack.count.div_ceil(2));
+            *cursor = Some(half);
+            *target = left;
+        }
+        (None, Some(held)) => {
+            let (one, left) = held.take(1);
+            *target = Some(one);
+            *cursor = left;
+        }
+        (Some(stack), Some(held))
+            if stack.item == held.item && stack.count < items.max_stack(stack.item) =>
+        {
+            *target = Some(stack.with_count(stack.count + 1));
+            *cursor = held.take(1).1;
+        }
+        _ => {}
+    }
+}
+
+/// Whether the held stack can be spread over `target` by dragging across it
+pub fn accepts(target: Option<ItemStack>, held: ItemStack, items: &ItemRegistry) -> bool {
+    target.is_none_or(|stack| stack.item == held.item && stack.count < items.max_stack(held.item))
+}
+
+/// Split the held stack evenly over `targets` after dragging it across them, what's left
+/// over stays held
+pub fn spread(
+    targets: Vec<&mut Option<ItemStack>>,
+    cursor: &mut Option<ItemStack>,
+    items: &ItemRegistry,
+) {
+    let Some(held) = *cursor else {
+        return;
+    };
+    let targets: Vec<_> = targets
+        .into_iter()
+        .filter(|target| accepts(**target, held, items))
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+    // One each to the first few when there aren't enough to go around
+    let share = (held.count / targets.len() as u32).max(1);
+    let max_stack = items.max_stack(held.item);
+    let mut left = held.count;
+    for target in targets {
+        let count = target.map_or(0, |stack| stack.count);
+        let moved = share.min(max_stack.saturating_sub(count)).min(left);
+        if moved > 0 {
+            *target = Some(held.with_count(count + moved));
+            left -= moved;
+        }
+    }
+    *cursor = (left > 0).then(|| held.with_count(left));
+}
@@ -0,0 +1,335 @@
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::inventory::ItemStack;
+use crate::assets::Assets;
+use crate::world::{BlockId, BlockRegistry};
+
+/// Item names in id order, after the items of every block
+const INDEX_PATH: &str = "items/index.json";
+/// How many of an item fit in one inventory slot unless its file says otherwise
+const DEFAULT_MAX_STACK: u32 = 64;
+
+/// Numeric item type, what each id means comes from the [`ItemRegistry`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ItemId(pub u16);
+
+/// What a tool is best at, the `kind` of its file's `tool` section
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolKind {
+    Pickaxe,
+    Axe,
+    Shovel,
+    Sword,
+    /// Tills dirt and grass into farmland, see [`super::farming::till`]
+    Hoe,
+}
+
+/// What a tool is made of, later tiers are better at everything
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolTier {
+    Wood,
+    Stone,
+    Iron,
+    Diamond,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tool {
+    pub kind: ToolKind,
+    pub tier: ToolTier,
+    /// Uses before it breaks
+    pub durability: u32,
+    /// How many times faster than by hand it breaks blocks meant for its kind
+    pub speed: f32,
+}
+
+/// What eating one does, see [`super::hunger::eat`]
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Food {
+    /// Food points it fills up, two to a drumstick
+    pub hunger: u32,
+    /// How long it keeps the hunger away, added to the saturation
+    pub saturation: f32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ItemKind {
+    /// Places this block, always a base block
+    Block(BlockId),
+    Tool(Tool),
+    Food(Food),
+    /// Grows a crop it's used on by at least the first and at most the second many stages
+    Fertilizer([u8; 2]),
+    /// Only good for crafting, like sticks and ingots
+    Material,
+}
+
+/// Everything the game knows about one kind of item
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub name: String,
+    pub kind: ItemKind,
+    pub max_stack: u32,
+    /// Name of a texture in `textures/item/`, empty for a block's own item since it shows
+    /// the block itself
+    pub texture: String,
+}
+
+impl Item {
+    pub fn block(&self) -> Option<BlockId> {
+        match self.kind {
+            ItemKind::Block(block) => Some(block),
+            _ => None,
+        }
+    }
+
+    /// The block it's drawn as, None for items with a texture of their own
+    pub fn shown_block(&self) -> Option<BlockId> {
+        self.block().filter(|_| self.texture.is_empty())
+    }
+
+    pub fn tool(&self) -> Option<Tool> {
+        match self.kind {
+            ItemKind::Tool(tool) => Some(tool),
+            _ => None,
+        }
+    }
+
+    pub fn food(&self) -> Option<Food> {
+        match self.kind {
+            ItemKind::Food(food) => Some(food),
+            _ => None,
+        }
+    }
+
+    pub fn fertilizer(&self) -> Option<[u8; 2]> {
+        match self.kind {
+            ItemKind::Fertilizer(stages) => Some(stages),
+            _ => None,
+        }
+    }
+}
+
+/// Contents of `items/<name>.json`
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ItemFile {
+    /// The item's own name if left out
+    texture: Option<String>,
+    /// 1 for tools and [`DEFAULT_MAX_STACK`] for anything else if left out
+    max_stack: Option<u32>,
+    tool: Option<Tool>,
+    food: Option<Food>,
+    /// Name of the block it places, for items that place a block without being named after
+    /// it like seeds
+    places: Option<String>,
+    fertilizer: Option<[u8; 2]>,
+}
+
+/// Maps item ids to their definitions. Every block that can be held gets an item of the same
+/// name first, in block id order, followed by the items listed in `items/index.json`. Crops
+/// are the exception, they're placed by items of their own like seeds. Saves store item
+/// names, so ids are free to change.
+#[derive(Debug)]
+pub struct ItemRegistry {
+    items: Vec<Item>,
+    ids: HashMap<String, ItemId>,
+    /// Indexed by block id, for every variant of a block
+    block_items: Vec<Option<ItemId>>,
+    /// What each block leaves behind when broken, indexed by block id
+    drops: Vec<Vec<ItemId>>,
+}
+
+impl ItemRegistry {
+    pub fn load(assets: &Assets, blocks: &BlockRegistry) -> anyhow::Result<Self> {
+        let mut registry = ItemRegistry {
+            items: Vec::new(),
+            ids: HashMap::new(),
+            block_items: Vec::new(),
+            drops: Vec::new(),
+        };
+        for (id, block) in blocks.iter() {
+            let held = block.fluid.is_none() && block.crop.is_none();
+            if id == BlockId::AIR || !held || blocks.base(id) != id {
+                continue;
+            }
+            registry.insert(Item {
+                name: block.name.clone(),
+                kind: ItemKind::Block(id),
+                max_stack: DEFAULT_MAX_STACK,
+                texture: String::new(),
+            })?;
+        }
+
+        let index = assets.read(INDEX_PATH)?;
+        let names: Vec<String> = serde_json::from_slice(&index)
+            .with_context(|| format!("Failed to parse {INDEX_PATH}"))?;
+        for name in names {
+            let item = load_item(assets, blocks, &name)?;
+            registry.insert(item)?;
+        }
+
+        // The first item placing each block, its own comes before any others
+        let mut placers = HashMap::new();
+        for (id, item) in registry.iter() {
+            if let Some(block) = item.block() {
+                placers.entry(block).or_insert(id);
+            }
+        }
+        for (id, block) in blocks.iter() {
+            let item = placers.get(&blocks.base(id)).copied();
+            registry
+                .block_items
+                .push(item.filter(|_| block.fluid.is_none()));
+            let mut tilled = block.tilled.iter().flat_map(|tilled| &tilled.drops);
+            if let Some(name) = tilled.find(|name| registry.id(name).is_none()) {
+                bail!(
+                    "Tilling {} turns up {name}, which isn't an item",
+                    block.name
+                );
+            }
+            let drops = match &block.drops {
+                Some(names) => names
+                    .iter()
+                    .map(|name| {
+                        registry.id(name).with_context(|| {
+                            format!("Block {} drops {name}, which isn't an item", block.name)
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+                None => registry.block_items[id.0 as usize].into_iter().collect(),
+            };
+            registry.drops.push(drops);
+        }
+        Ok(registry)
+    }
+
+    /// None for ids that no item uses, e.g. from a newer save
+    pub fn get(&self, id: ItemId) -> Option<&Item> {
+        self.items.get(id.0 as usize)
+    }
+
+    pub fn id(&self, name: &str) -> Option<ItemId> {
+        self.ids.get(name).copied()
+    }
+
+    /// The item that places `block`, whichever way it's turned. None for air and fluids.
+    pub fn of_block(&self, block: BlockId) -> Option<ItemId> {
+        self.block_items.get(block.0 as usize).copied().flatten()
+    }
+
+    /// What `item` places, if anything
+    pub fn block(&self, item: ItemId) -> Option<BlockId> {
+        self.get(item)?.block()
+    }
+
+    /// What `item` is as a tool, if it's one
+    pub fn tool(&self, item: ItemId) -> Option<Tool> {
+        self.get(item)?.tool()
+    }
+
+    /// What eating `item` does, if it can be eaten
+    pub fn food(&self, item: ItemId) -> Option<Food> {
+        self.get(item)?.food()
+    }
+
+    /// How many stages `item` grows a crop by, if it does
+    pub fn fertilizer(&self, item: ItemId) -> Option<[u8; 2]> {
+        self.get(item)?.fertilizer()
+    }
+
+    /// How worn out a tool stack is, from 0 new to 1 broken. None for anything that doesn't
+    /// wear.
+    pub fn wear(&self, stack: ItemStack) -> Option<f32> {
+        let tool = self.tool(stack.item)?;
+        Some((stack.damage as f32 / tool.durability as f32).min(1.0))
+    }
+
+    /// 1 for ids that no item uses, so they never pile up
+    pub fn max_stack(&self, item: ItemId) -> u32 {
+        self.get(item).map_or(1, |item| item.max_stack)
+    }
+
+    /// What's left behind when `block` is broken, one of each
+    pub fn drops(&self, block: BlockId) -> &[ItemId] {
+        self.drops.get(block.0 as usize).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every item in id order
+    pub fn iter(&self) -> impl Iterator<Item = (ItemId, &Item)> {
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(id, item)| (ItemId(id as u16), item))
+    }
+
+    fn insert(&mut self, item: Item) -> anyhow::Result<()> {
+        let Ok(id) = u16::try_from(self.items.len()) else {
+            bail!("Too many items");
+        };
+        if self.ids.insert(item.name.clone(), ItemId(id)).is_some() {
+            bail!("There are two items named {}", item.name);
+        }
+        self.items.push(item);
+        Ok(())
+    }
+}
+
+fn load_item(assets: &Assets, blocks: &BlockRegistry, name: &str) -> anyhow::Result<Item> {
+    let path = format!("items/{name}.json");
+    let bytes = assets.read(&path)?;
+    let file: ItemFile =
+        serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {path}"))?;
+    let max_stack = match (file.max_stack, file.tool) {
+        (Some(0), _) => bail!("{path}: the max stack has to be at least 1"),
+        (Some(2..), Some(_)) => bail!("{path}: tools don't stack"),
+        (Some(max_stack), _) => max_stack,
+        (None, Some(_)) => 1,
+        (None, None) => DEFAULT_MAX_STACK,
+    };
+    if file.tool.is_some_and(|tool| tool.durability == 0) {
+        bail!("{path}: a tool's durability has to be at least 1");
+    }
+    if file.tool.is_some_and(|tool| tool.speed <= 0.0) {
+        bail!("{path}: a tool's speed has to be positive");
+    }
+    if file
+        .food
+        .is_some_and(|food| food.hunger == 0 || food.saturation < 0.0)
+    {
+        bail!("{path}: food has to fill up at least one point and can't take saturation away");
+    }
+    if file
+        .fertilizer
+        .is_some_and(|[least, most]| least == 0 || least > most)
+    {
+        bail!("{path}: fertilizer grows crops by at least one stage, from fewest to most");
+    }
+    let places = match &file.places {
+        Some(block) => match blocks.id(block) {
+            Some(id) if blocks.base(id) == id => Some(id),
+            _ => bail!("{path}: it places {block}, which isn't the first variant of a block"),
+        },
+        None => None,
+    };
+    let kind = match (file.tool, file.food, places, file.fertilizer) {
+        (Some(tool), None, None, None) => ItemKind::Tool(tool),
+        (None, Some(food), None, None) => ItemKind::Food(food),
+        (None, None, Some(block), None) => ItemKind::Block(block),
+        (None, None, None, Some(stages)) => ItemKind::Fertilizer(stages),
+        (None, None, None, None) => ItemKind::Material,
+        _ => bail!("{path}: an item is only one of a tool, food, a block or fertilizer"),
+    };
+    Ok(Item {
+        name: name.to_owned(),
+        kind,
+        max_stack,
+        texture: file.texture.unwrap_or_else(|| name.to_owned()),
+    })
+}
@@ -0,0 +1,67 @@
+use cgmath::Point3;
+
+use super::item::{Tool, ToolKind};
+use super::TICKS_PER_SECOND;
+use crate::world::block::Block;
+
+/// Ticks after a block breaks before the next one starts, so holding the button down doesn't
+/// clear out soft blocks faster than anyone could aim
+pub const COOLDOWN_TICKS: u32 = 5;
+/// How much slower than its hardness a block breaks with the right tool, or by hand when it
+/// doesn't need one
+const HARVEST_FACTOR: f32 = 1.5;
+/// The same for a block that needs a better tool than what's held, which drops nothing anyway
+const NO_HARVEST_FACTOR: f32 = 5.0;
+
+/// A block the player is breaking
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Mining {
+    pub block: Point3<i32>,
+    /// From 0 to 1, it breaks at 1
+    pub progress: f32,
+}
+
+impl Mining {
+    /// Which of `stages` crack textures to draw over the block
+    pub fn stage(&self, stages: u32) -> u32 {
+        ((self.progress * stages as f32) as u32).min(stages - 1)
+    }
+}
+
+/// Whether breaking `block` with `tool` drops anything: blocks without a tier always do,
+/// the rest need a tool of their kind of at least that tier
+pub fn can_harvest(block: &Block, tool: Option<Tool>) -> bool {
+    let Some(tier) = block.tier else {
+        return true;
+    };
+    tool.is_some_and(|tool| Some(tool.kind) == block.tool && tool.tier >= tier)
+}
+
+/// How much of `block` one tick of breaking it with `tool` gets done, None if it can't be
+/// broken at all
+pub fn progress_per_tick(block: &Block, tool: Option<Tool>) -> Option<f32> {
+    if block.hardness < 0.0 {
+        return None;
+    }
+    if block.hardness == 0.0 {
+        return Some(1.0);
+    }
+    let factor = if can_harvest(block, tool) {
+        HARVEST_FACTOR
+    } else {
+        NO_HARVEST_FACTOR
+    };
+    let speed = tool
+        .filter(|tool| Some(tool.kind) == block.tool)
+        .map_or(1.0, |tool| tool.speed);
+    let seconds = block.hardness * factor / speed;
+    Some(1.0 / (seconds * TICKS_PER_SECOND as f32))
+}
+
+/// How much breaking a block wears `tool` down. Swords aren't made for it.
+pub fn wear(tool: Tool) -> u32 {
+    match tool.kind {
+        ToolKind::Sword => 2,
+        ToolKind::Pickaxe | ToolKind::Axe | ToolKind::Shovel | ToolKind::Hoe => 1,
+    }
+}
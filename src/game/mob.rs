@@ -0,0 +1,234 @@
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use super::item::{ItemId, ItemRegistry};
+use crate::assets::Assets;
+use crate::world::{BlockId, BlockRegistry};
+
+/// Mob names in id order
+const INDEX_PATH: &str = "mobs/index.json";
+
+/// Numeric mob type, what each id means comes from the [`MobRegistry`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MobId(pub u16);
+
+/// Some number of an item a mob leaves behind when it dies
+#[derive(Debug, Clone)]
+pub struct MobDrop {
+    pub item: ItemId,
+    pub count: RangeInclusive<u32>,
+}
+
+/// How a hostile mob goes after the player
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Attack {
+    /// Hit points each hit takes
+    pub damage: u32,
+    /// Blocks away it notices the player from
+    pub range: f32,
+}
+
+/// One kind of mob, from `mobs/<name>.json`
+#[derive(Debug, Clone)]
+pub struct MobType {
+    pub name: String,
+    /// Drawn with `entity_models/<model>.json`
+    pub model: String,
+    pub health: u32,
+    /// Its [`super::components::Collider`]
+    pub half_width: f32,
+    pub height: f32,
+    /// Blocks per second it walks at
+    pub speed: f32,
+    /// Blocks it spawns on top of, never spawned naturally if there are none
+    pub spawn_on: Vec<BlockId>,
+    /// How many spawn together
+    pub group: RangeInclusive<u32>,
+    /// Only spawns in the dark rather than only in the light
+    pub dark: bool,
+    /// None for passive mobs, which never go after anyone
+    pub attack: Option<Attack>,
+    /// Catches fire in daylight
+    pub burns: bool,
+    pub drops: Vec<MobDrop>,
+    /// Experience points for the player who kills it
+    pub experience: RangeInclusive<u32>,
+}
+
+impl MobType {
+    pub fn is_hostile(&self) -> bool {
+        self.attack.is_some()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DropFile {
+    item: String,
+    #[serde(default = "one")]
+    min: u32,
+    #[serde(default = "one")]
+    max: u32,
+}
+
+/// Contents of `mobs/<name>.json`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MobFile {
+    /// The mob's own name if left out
+    #[serde(default)]
+    model: Option<String>,
+    health: u32,
+    /// Width and height in blocks
+    size: [f32; 2],
+    speed: f32,
+    #[serde(default)]
+    spawn_on: Vec<String>,
+    #[serde(default = "single")]
+    group: [u32; 2],
+    #[serde(default)]
+    dark: bool,
+    #[serde(default)]
+    attack: Option<Attack>,
+    #[serde(default)]
+    burns: bool,
+    #[serde(default)]
+    drops: Vec<DropFile>,
+    /// Least and most experience, none if left out
+    #[serde(default)]
+    experience: [u32; 2],
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn single() -> [u32; 2] {
+    [1, 1]
+}
+
+/// Every kind of mob, from the ones listed in `mobs/index.json`
+#[derive(Debug)]
+pub struct MobRegistry {
+    mobs: Vec<MobType>,
+    ids: HashMap<String, MobId>,
+}
+
+impl MobRegistry {
+    pub fn load(
+        assets: &Assets,
+        blocks: &BlockRegistry,
+        items: &ItemRegistry,
+    ) -> anyhow::Result<Self> {
+        let index = assets.read(INDEX_PATH)?;
+        let names: Vec<String> = serde_json::from_slice(&index)
+            .with_context(|| format!("Failed to parse {INDEX_PATH}"))?;
+        let mut registry = MobRegistry {
+            mobs: Vec::new(),
+            ids: HashMap::new(),
+        };
+        for name in names {
+            let mob = load_mob(assets, &name, blocks, items)?;
+            let Ok(id) = u16::try_from(registry.mobs.len()) else {
+                bail!("Too many mobs");
+            };
+            if registry.ids.insert(name.clone(), MobId(id)).is_some() {
+                bail!("There are two mobs named {name}");
+            }
+            registry.mobs.push(mob);
+        }
+        Ok(registry)
+    }
+
+    pub fn get(&self, id: MobId) -> Option<&MobType> {
+        self.mobs.get(id.0 as usize)
+    }
+
+    pub fn id(&self, name: &str) -> Option<MobId> {
+        self.ids.get(name).copied()
+    }
+
+    /// Every mob in id order
+    pub fn iter(&self) -> impl Iterator<Item = (MobId, &MobType)> {
+        self.mobs
+            .iter()
+            .enumerate()
+            .map(|(id, mob)| (MobId(id as u16), mob))
+    }
+}
+
+fn load_mob(
+    assets: &Assets,
+    name: &str,
+    blocks: &BlockRegistry,
+    items: &ItemRegistry,
+) -> anyhow::Result<MobType> {
+    let path = format!("mobs/{name}.json");
+    let bytes = assets.read(&path)?;
+    let file: MobFile =
+        serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {path}"))?;
+    if file.health == 0 {
+        bail!("{path}: the health has to be at least 1");
+    }
+    if file.size.iter().any(|size| *size <= 0.0) || file.speed <= 0.0 {
+        bail!("{path}: the size and speed have to be positive");
+    }
+    if file
+        .attack
+        .is_some_and(|attack| attack.damage == 0 || attack.range <= 0.0)
+    {
+        bail!("{path}: an attack has to do some damage from some range");
+    }
+    let [min_group, max_group] = file.group;
+    if min_group == 0 || min_group > max_group {
+        bail!("{path}: the group size has to be at least 1 and go from small to large");
+    }
+    let spawn_on = file
+        .spawn_on
+        .iter()
+        .map(|block| {
+            blocks
+                .id(block)
+                .with_context(|| format!("{path}: spawns on {block}, which isn't a block"))
+        })
+        .collect::<anyhow::Result<_>>()?;
+    let drops = file
+        .drops
+        .into_iter()
+        .map(|drop| {
+            let item = items
+                .id(&drop.item)
+                .with_context(|| format!("{path}: drops {}, which isn't an item", drop.item))?;
+            if drop.min > drop.max {
+                bail!("{path}: drops more {} at least than at most", drop.item);
+            }
+            Ok(MobDrop {
+                item,
+                count: drop.min..=drop.max,
+            })
+        })
+        .collect::<anyhow::Result<_>>()?;
+    let [min_experience, max_experience] = file.experience;
+    if min_experience > max_experience {
+        bail!("{path}: the experience has to go from small to large");
+    }
+    let [width, height] = file.size;
+    Ok(MobType {
+        name: name.to_owned(),
+        model: file.model.unwrap_or_else(|| name.to_owned()),
+        health: file.health,
+        half_width: width / 2.0,
+        height,
+        speed: file.speed,
+        spawn_on,
+        group: min_group..=max_group,
+        dark: file.dark,
+        attack: file.attack,
+        burns: file.burns,
+        drops,
+        experience: min_experience..=max_experience,
+    })
+}
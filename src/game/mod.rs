@@ -0,0 +1,1228 @@
+pub mod combat;
+pub mod components;
+pub mod crafting;
+pub mod doors;
+pub mod drops;
+pub mod enchanting;
+pub mod experience;
+pub mod explosion;
+pub mod farming;
+pub mod health;
+pub mod hunger;
+pub mod hunt;
+pub mod inventory;
+pub mod item;
+pub mod mining;
+pub mod mob;
+mod pathfinding;
+pub mod physics;
+pub mod scheduler;
+pub mod sleeping;
+pub mod smelting;
+pub mod spawning;
+mod systems;
+mod wander;
+pub mod weather;
+
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Vector3, Zero};
+use hecs::Entity;
+use std::sync::Arc;
+
+use crate::save::region::RegionStorage;
+use crate::save::saver::{ChunkStore, Saver};
+use crate::save::{self, PlayerData, WorldInfo};
+use crate::world::biome::Precipitation;
+use crate::world::block::{Block, Face, Interaction};
+use crate::world::block_entity::BlockEntity;
+use crate::world::chunk::ChunkPos;
+use crate::world::circuit::{self, CircuitSimulation};
+use crate::world::fluid::FluidSimulation;
+use crate::world::generator::{Features, TerrainGenerator, SEA_LEVEL};
+use crate::world::light::MAX_LIGHT;
+use crate::world::noise;
+use crate::world::pipeline::GenerationPipeline;
+use crate::world::raycast::{self, RayHit};
+use crate::world::streaming::ChunkStreamer;
+use crate::world::{BlockId, BlockRegistry, World};
+use components::{
+    Breath, Collider, Dying, Experience, FallDistance, Footsteps, GameMode, Health, Hunger,
+    InFluid, Knockback, Look, Movement, OnGround, Player, Position, PreviousPosition, Sleeping,
+    SpawnPoint, Velocity,
+};
+use inventory::{Inventory, ItemStack};
+use item::{ItemId, ItemRegistry, ToolKind};
+use mining::Mining;
+use mob::MobRegistry;
+use scheduler::{Scheduler, TickState};
+use sleeping::CantSleep;
+use smelting::SmeltingBook;
+
+pub const TICKS_PER_SECOND: u32 = 20;
+pub const TICK_SECONDS: f32 = 1.0 / TICKS_PER_SECOND as f32;
+/// Ticks in one in-game day
+pub const DAY_LENGTH: u64 = 24000;
+/// The first half of every day, from sunrise to sunset
+pub fn is_daytime(time: u64) -> bool {
+    time % DAY_LENGTH < DAY_LENGTH / 2
+}
+
+/// Height of the player's eyes above their feet
+const EYE_HEIGHT: f32 = 1.62;
+const SNEAK_EYE_HEIGHT: f32 = 1.27;
+/// Lying on top of a bed
+const SLEEP_EYE_HEIGHT: f32 = 0.2;
+/// The player's collision box
+const PLAYER_HALF_WIDTH: f32 = 0.3;
+const PLAYER_HEIGHT: f32 = 1.8;
+/// How far away in blocks the player can reach
+pub const REACH: f32 = 5.0;
+/// Blocks per second a stack the player drops is thrown forward with
+const THROW_SPEED: f32 = 6.0;
+/// A minute between autosaves, they run in the background so they can be frequent
+const AUTOSAVE_INTERVAL: u64 = 60 * TICKS_PER_SECOND as u64;
+/// Chunks generated in each direction from the spawn chunk before the first tick
+const SPAWN_RADIUS: i32 = 2;
+/// Vertical limit on the chunks kept loaded, the render distance sets the rest
+const MAX_STREAM_HEIGHT: i32 = 4;
+/// Chunks stay loaded this much past the render distance, so walking back and forth over a
+/// border doesn't reload them every time
+const UNLOAD_MARGIN: i32 = 1;
+/// How much the view direction weighs in the loading order, from 0 for distance only up to
+/// 1 where chunks right ahead come first no matter how far
+const VIEW_PRIORITY: f32 = 0.5;
+
+/// Movement the player asked for this tick, each axis from -1 to 1 relative to where they look
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct PlayerInput {
+    pub forward: f32,
+    pub strafe: f32,
+    pub jump: bool,
+    pub sneak: bool,
+    pub sprint: bool,
+    /// Holding down the button that breaks blocks
+    pub mining: bool,
+    /// Holding down the button that uses the held item, e.g. to eat it
+    pub using: bool,
+}
+
+/// Something that happened during a tick that the player should see or hear, handed out by
+/// [`Game::take_events`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GameEvent {
+    /// Something blew up with this power, see [`explosion::explode`]
+    Explosion { center: Point3<f32>, power: f32 },
+    /// The player broke this block
+    BlockBroken {
+        position: Point3<i32>,
+        block: BlockId,
+    },
+    /// The player placed this block
+    BlockPlaced {
+        position: Point3<i32>,
+        block: BlockId,
+    },
+    /// Someone took a step on this block, see [`systems::take_footsteps`]
+    Footstep {
+        position: Point3<f32>,
+        block: BlockId,
+    },
+}
+
+/// The world that's being played: its metadata, blocks and every entity in it. Gameplay
+/// happens in fixed ticks, rendering interpolates between the last two.
+pub struct Game {
+    pub info: WorldInfo,
+    pub world: World,
+    pub blocks: Arc<BlockRegistry>,
+    pub items: Arc<ItemRegistry>,
+    pub mobs: Arc<MobRegistry>,
+    pub generator: Arc<TerrainGenerator>,
+    pub streamer: ChunkStreamer,
+    saver: Saver,
+    /// Chunks kept loaded in each horizontal direction from the player
+    render_distance: i32,
+    pub entities: hecs::World,
+    pub player: Entity,
+    pub scheduler: Scheduler,
+    /// Ticks run since the world was opened
+    tick: u64,
+    /// The block being broken, None while the button isn't held or nothing's in reach
+    mining: Option<Mining>,
+    /// Ticks left before breaking can start again, see [`mining::COOLDOWN_TICKS`]
+    mining_cooldown: u32,
+    /// The held food being eaten and for how many ticks so far
+    eating: Option<(ItemId, u32)>,
+    /// Gathered since the app last took them
+    events: Vec<GameEvent>,
+}
+
+/// The block each entity that takes up space has its feet in, for pressure plates
+fn occupied_blocks(entities: &hecs::World) -> Vec<Point3<i32>> {
+    entities
+        .query::<(&Position, &Collider)>()
+        .iter()
+        .map(|(_, (position, _))| position.0.map(|value| value.floor() as i32))
+        .collect()
+}
+
+/// Where new players start and dead ones come back, on top of the terrain in the middle of
+/// the world
+fn spawn_point(generator: &TerrainGenerator) -> Point3<f32> {
+    let surface = generator.surface_height(0, 0).max(SEA_LEVEL);
+    Point3::new(0.5, surface as f32 + 1.0, 0.5)
+}
+
+impl Game {
+    pub fn new(
+        info: WorldInfo,
+        blocks: Arc<BlockRegistry>,
+        items: Arc<ItemRegistry>,
+        smelting: Arc<SmeltingBook>,
+        mobs: Arc<MobRegistry>,
+        features: Arc<Features>,
+        render_distance: u32,
+    ) -> Self {
+        let generator = Arc::new(TerrainGenerator::new(
+            info.seed,
+            info.generator,
+            &blocks,
+            features,
+        ));
+        let saved_player = save::load_player(&info.directory).unwrap_or_else(|err| {
+            log::warn!("Starting at spawn: {err:#}");
+            None
+        });
+        // Dying doesn't change it
+        let game_mode = saved_player
+            .as_ref()
+            .map_or(GameMode::default(), |player| player.game_mode);
+        let experience = saved_player
+            .as_ref()
+            .filter(|player| player.health > 0)
+            .map_or(0, |player| player.experience);
+        // Whether the bed is still there is only checked when they die again
+        let bed = saved_player
+            .as_ref()
+            .and_then(|player| player.spawn)
+            .map(Point3::from);
+        let (feet, look, flying, inventory, health, hunger) = match saved_player {
+            // Quitting on the death screen respawns them, with whatever they dropped still
+            // lying where they died
+            Some(player) if player.health > 0 => (
+                Point3::from(player.position),
+                Look {
+                    yaw: player.yaw,
+                    pitch: player.pitch,
+                },
+                player.flying && game_mode == GameMode::Creative,
+                Inventory::load(&player.inventory, player.selected_slot, &items),
+                player.health.min(health::MAX_HEALTH),
+                Hunger {
+                    food: player.food.min(hunger::MAX_FOOD),
+                    saturation: player.saturation.clamp(0.0, player.food as f32),
+                    exhaustion: 0.0,
+                    timer: 0,
+                },
+            ),
+            _ => (
+                bed.map_or_else(|| spawn_point(&generator), sleeping::feet_on),
+                Look::default(),
+                false,
+                Inventory::default(),
+                health::MAX_HEALTH,
+                Hunger::full(),
+            ),
+        };
+        let mut storage =
+            RegionStorage::new(&info.directory, Arc::clone(&blocks), Arc::clone(&items));
+        let mut world = World::new();
+        let mut pipeline = GenerationPipeline::new();
+        let (spawn_chunk, _) = ChunkPos::of_block(feet.map(|value| value.floor() as i32));
+        for x in -SPAWN_RADIUS..=SPAWN_RADIUS {
+            for y in -SPAWN_RADIUS..=SPAWN_RADIUS {
+                for z in -SPAWN_RADIUS..=SPAWN_RADIUS {
+                    let position =
+                        ChunkPos::new(spawn_chunk.x + x, spawn_chunk.y + y, spawn_chunk.z + z);
+                    match storage.load_chunk(position) {
+                        Ok(Some(chunk)) => {
+                            world.insert_chunk(position, chunk);
+                            world.mark_saved(position);
+                        }
+                        result => {
+                            if let Err(err) = result {
+                                log::warn!("Generating chunk {position:?} again: {err:#}");
+                            }
+                            world.insert_chunk(position, pipeline.generate(&generator, position));
+                        }
+                    }
+                }
+            }
+        }
+
+        world.update_light(&blocks);
+
+        let mut entities = hecs::World::new();
+        let mut player = hecs::EntityBuilder::new();
+        player.add_bundle((
+            Player,
+            Position(feet),
+            PreviousPosition(feet),
+            Velocity(cgmath::Vector3::new(0.0, 0.0, 0.0)),
+            Collider {
+                half_width: PLAYER_HALF_WIDTH,
+                height: PLAYER_HEIGHT,
+            },
+            OnGround(false),
+            InFluid(None),
+            Movement {
+                flying,
+                ..Movement::default()
+            },
+            look,
+            inventory,
+            Health::new(health),
+            FallDistance::default(),
+            Breath(health::MAX_BREATH),
+            hunger,
+            game_mode,
+        ));
+        player.add(Experience(experience));
+        player.add(Footsteps::default());
+        if let Some(bed) = bed {
+            player.add(SpawnPoint(bed));
+        }
+        let player = entities.spawn(player.build());
+
+        let store = Arc::new(ChunkStore::new(storage));
+        let mut scheduler = Scheduler::new();
+        scheduler.add_system("previous positions", systems::store_previous_positions);
+        scheduler.add_system("player control", systems::player_control);
+        scheduler.add_system("wandering", wander::wander);
+        scheduler.add_system("hunting", hunt::hunt);
+        scheduler.add_system("knockback", combat::apply_knockback);
+        scheduler.add_system("experience orbs", experience::update_orbs);
+        scheduler.add_system("gravity", systems::apply_gravity);
+        scheduler.add_system("velocity", systems::apply_velocity);
+        scheduler.add_system("walk cycles", systems::advance_walk_cycles);
+        scheduler.add_system("footsteps", systems::take_footsteps);
+        scheduler.add_system("recovery", health::recover);
+        scheduler.add_system("fall damage", health::take_fall_damage);
+        scheduler.add_system("fluid damage", health::take_fluid_damage);
+        scheduler.add_system("breathing", health::breathe);
+        scheduler.add_system("daylight burning", hunt::burn_in_daylight);
+        scheduler.add_system("exhaustion", hunger::exhaust);
+        scheduler.add_system("metabolism", hunger::metabolize);
+        scheduler.add_system("dropped items", drops::update_dropped_items);
+        scheduler.add_system("primed tnt", explosion::update_primed_tnt);
+        scheduler.add_system("time", systems::advance_time);
+        scheduler.add_system("sleeping", sleeping::update_sleepers);
+        scheduler.add_system("weather", systems::update_weather);
+        // Fluids go first so circuits see where they flowed to within the same tick
+        let mut fluids = FluidSimulation::new();
+        let mut circuits = CircuitSimulation::new();
+        scheduler.tasks.every(1, move |context| {
+            let changed = context.world.take_block_updates();
+            fluids.tick(context.world, context.blocks, &changed, context.tick);
+            let occupied = occupied_blocks(context.entities);
+            circuits.tick(context.world, context.blocks, &changed, &occupied);
+            farming::uproot_crops(context, &changed);
+            doors::break_lone_halves(context, &changed);
+        });
+        scheduler.tasks.every(1, farming::random_ticks);
+        scheduler.tasks.every(1, move |context| {
+            let items = context.items;
+            context.world.update_block_entities(|entity| match entity {
+                BlockEntity::Furnace(furnace) => smelting::tick(furnace, &smelting, items),
+                BlockEntity::Chest(_) | BlockEntity::Sign(_) => false,
+            });
+        });
+        let despawn_mobs = Arc::clone(&mobs);
+        scheduler.tasks.every(1, move |context| {
+            spawning::remove_mobs(context, &despawn_mobs);
+        });
+        let spawn_mobs = Arc::clone(&mobs);
+        scheduler
+            .tasks
+            .every(spawning::SPAWN_INTERVAL, move |context| {
+                spawning::spawn_mobs(context, &spawn_mobs);
+            });
+        let surface_generator = Arc::clone(&generator);
+        scheduler
+            .tasks
+            .every(weather::SURFACE_INTERVAL, move |context| {
+                weather::change_surfaces(context, &surface_generator);
+            });
+
+        Game {
+            info,
+            world,
+            blocks,
+            items,
+            mobs,
+            streamer: ChunkStreamer::new(Arc::clone(&generator), pipeline, Arc::clone(&store)),
+            saver: Saver::new(store),
+            generator,
+            render_distance: render_distance as i32,
+            entities,
+            player,
+            scheduler,
+            tick: 0,
+            mining: None,
+            mining_cooldown: 0,
+            eating: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Advance the world by one tick
+    pub fn tick(&mut self, input: &PlayerInput) {
+        self.tick += 1;
+        let state = TickState {
+            entities: &mut self.entities,
+            world: &mut self.world,
+            blocks: &self.blocks,
+            items: &self.items,
+            info: &mut self.info,
+            events: &mut self.events,
+        };
+        self.scheduler.run_tick(self.tick, state, input);
+        if self.is_dead() {
+            self.drop_inventory();
+        } else {
+            let awake = !self.is_sleeping();
+            self.mine(input.mining && awake);
+            self.eat(input.using && awake);
+        }
+        self.stream_chunks();
+        self.world.update_light(&self.blocks);
+        if self.tick.is_multiple_of(AUTOSAVE_INTERVAL) {
+            self.autosave();
+        }
+    }
+
+    /// Hand the world info, the player and every chunk that changed since it was last saved
+    /// to the saver thread, returns without waiting for the disk
+    pub fn autosave(&mut self) {
+        let unsaved: Vec<_> = self.world.unsaved_chunks().collect();
+        let mut chunks = Vec::with_capacity(unsaved.len());
+        for position in unsaved {
+            if let Some(chunk) = self.world.chunk(position) {
+                chunks.push((position, chunk.clone()));
+            }
+            self.world.mark_saved(position);
+        }
+        self.saver
+            .save_all(self.info.clone(), self.player_data(), chunks);
+    }
+
+    /// Like [`Game::autosave`] but waits until everything is written, e.g. before closing
+    pub fn save(&mut self) -> anyhow::Result<()> {
+        self.autosave();
+        self.saver.flush()
+    }
+
+    /// Takes effect from the next tick, chunks out of the new range are unloaded then
+    pub fn set_render_distance(&mut self, chunks: u32) {
+        self.render_distance = chunks as i32;
+    }
+
+    /// Keep the chunks around the player loaded: drop what fell out of range, take in what
+    /// the streamer finished and ask for what's still missing
+    fn stream_chunks(&mut self) {
+        let (center, _) = ChunkPos::of_block(self.player_feet().map(|value| value.floor() as i32));
+        let radius = self.render_distance;
+        let height = radius.min(MAX_STREAM_HEIGHT);
+        let in_range = |position: ChunkPos, margin: i32| {
+            (position.x - center.x).abs() <= radius + margin
+                && (position.y - center.y).abs() <= height + margin
+                && (position.z - center.z).abs() <= radius + margin
+        };
+
+        let far: Vec<_> = self
+            .world
+            .chunk_positions()
+            .filter(|position| !in_range(*position, UNLOAD_MARGIN))
+            .collect();
+        let mut unloaded = Vec::new();
+        for position in far {
+            let unsaved = self.world.is_unsaved(position);
+            if let Some(chunk) = self.world.remove_chunk(position) {
+                if unsaved {
+                    unloaded.push((position, chunk));
+                }
+            }
+        }
+        self.saver.save_chunks(unloaded);
+        for streamed in self.streamer.receive() {
+            let position = streamed.position;
+            if in_range(position, UNLOAD_MARGIN) && self.world.chunk(position).is_none() {
+                self.world.insert_chunk(position, streamed.chunk);
+                if streamed.loaded {
+                    self.world.mark_saved(position);
+                }
+            }
+        }
+
+        let forward = self
+            .entities
+            .get::<&Look>(self.player)
+            .map_or(Vector3::zero(), |look| look.forward());
+        let mut missing = Vec::new();
+        for x in -radius..=radius {
+            for y in -height..=height {
+                for z in -radius..=radius {
+                    let position = ChunkPos::new(center.x + x, center.y + y, center.z + z);
+                    if self.world.chunk(position).is_some() {
+                        continue;
+                    }
+                    let offset = Vector3::new(x as f32, y as f32, z as f32);
+                    let distance = offset.magnitude();
+                    let facing = if distance > 0.0 {
+                        offset.dot(forward) / distance
+                    } else {
+                        1.0
+                    };
+                    // Straight ahead counts as closer than it is and behind as further
+                    missing.push((distance * (1.0 - VIEW_PRIORITY * facing), position));
+                }
+            }
+        }
+        missing.sort_unstable_by(|(a, _), (b, _)| a.total_cmp(b));
+        let positions: Vec<_> = missing.into_iter().map(|(_, position)| position).collect();
+        // The pipeline needs two more chunks of neighbours around anything it generates
+        let keep_radius = radius.max(height) + UNLOAD_MARGIN + 2;
+        self.streamer.request(&positions, center, keep_radius);
+    }
+
+    /// Mouse look applies right away instead of waiting for the next tick
+    pub fn rotate_player(&mut self, yaw: f32, pitch: f32) {
+        if let Ok(look) = self.entities.query_one_mut::<&mut Look>(self.player) {
+            look.rotate(yaw, pitch);
+        }
+    }
+
+    /// Move the player's feet without any interpolation from where they were. It doesn't
+    /// count as a fall.
+    pub fn teleport_player(&mut self, feet: Point3<f32>) {
+        if let Ok((position, previous, fall)) =
+            self.entities
+                .query_one_mut::<(&mut Position, &mut PreviousPosition, &mut FallDistance)>(
+                    self.player,
+                )
+        {
+            position.0 = feet;
+            previous.0 = feet;
+            fall.0 = 0.0;
+        }
+    }
+
+    pub fn player_health(&self) -> Option<Health> {
+        self.entities
+            .get::<&Health>(self.player)
+            .ok()
+            .map(|health| *health)
+    }
+
+    pub fn player_breath(&self) -> Option<Breath> {
+        self.entities
+            .get::<&Breath>(self.player)
+            .ok()
+            .map(|breath| *breath)
+    }
+
+    pub fn player_hunger(&self) -> Option<Hunger> {
+        self.entities
+            .get::<&Hunger>(self.player)
+            .ok()
+            .map(|hunger| *hunger)
+    }
+
+    pub fn game_mode(&self) -> GameMode {
+        self.entities
+            .get::<&GameMode>(self.player)
+            .map_or(GameMode::default(), |mode| *mode)
+    }
+
+    /// Switch the rules the player plays by. Leaving creative brings them down from flying,
+    /// whatever damage and hunger they had before is where survival picks up again.
+    pub fn set_game_mode(&mut self, game_mode: GameMode) {
+        if let Ok((mode, movement)) = self
+            .entities
+            .query_one_mut::<(&mut GameMode, &mut Movement)>(self.player)
+        {
+            *mode = game_mode;
+            movement.flying &= game_mode == GameMode::Creative;
+        }
+        self.mining = None;
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.player_health().is_some_and(|health| health.is_dead())
+    }
+
+    /// Bring the player back on their bed, or at the world spawn if they haven't slept in one
+    /// or it's gone since, fully healed and fed and standing still. Their experience is lost.
+    /// Returns false if they had a bed but it was gone.
+    pub fn respawn(&mut self) -> bool {
+        let bed = self
+            .entities
+            .get::<&SpawnPoint>(self.player)
+            .ok()
+            .map(|spawn| spawn.0);
+        // A bed in a chunk that isn't loaded yet gets the benefit of the doubt
+        let kept = bed.filter(|bed| {
+            self.world.block(*bed).is_none() || sleeping::is_bed(&self.world, &self.blocks, *bed)
+        });
+        let feet = match kept {
+            Some(bed) => sleeping::feet_on(bed),
+            None => {
+                let _ = self.entities.remove_one::<SpawnPoint>(self.player);
+                spawn_point(&self.generator)
+            }
+        };
+        self.teleport_player(feet);
+        if let Ok((health, breath, hunger, velocity, experience)) =
+            self.entities.query_one_mut::<(
+                &mut Health,
+                &mut Breath,
+                &mut Hunger,
+                &mut Velocity,
+                &mut Experience,
+            )>(self.player)
+        {
+            *health = Health::new(health::MAX_HEALTH);
+            breath.0 = health::MAX_BREATH;
+            *hunger = Hunger::full();
+            velocity.0 = Vector3::zero();
+            experience.0 = 0;
+        }
+        let _ = self.entities.remove_one::<Knockback>(self.player);
+        let _ = self.entities.remove_one::<Sleeping>(self.player);
+        self.mining = None;
+        self.eating = None;
+        bed.is_none() || kept.is_some()
+    }
+
+    /// Make the bed at `position` where the player comes back after dying and get into it, as
+    /// long as it's night and no monsters are close. The spawn point moves even when they
+    /// can't sleep.
+    pub fn sleep(&mut self, position: Point3<i32>) -> Result<(), CantSleep> {
+        let _ = self.entities.insert_one(self.player, SpawnPoint(position));
+        if is_daytime(self.info.time) {
+            return Err(CantSleep::Day);
+        }
+        if sleeping::monsters_nearby(&self.entities, sleeping::feet_on(position)) {
+            return Err(CantSleep::Monsters);
+        }
+        self.teleport_player(sleeping::feet_on(position));
+        if let Ok(velocity) = self.entities.query_one_mut::<&mut Velocity>(self.player) {
+            velocity.0 = Vector3::zero();
+        }
+        let _ = self.entities.insert_one(
+            self.player,
+            Sleeping {
+                bed: position,
+                ticks: 0,
+            },
+        );
+        self.mining = None;
+        self.eating = None;
+        Ok(())
+    }
+
+    pub fn is_sleeping(&self) -> bool {
+        self.entities.get::<&Sleeping>(self.player).is_ok()
+    }
+
+    /// How far the player is into falling asleep, from 0 to 1 once they've slept long enough
+    /// for the night to be skipped. None while they're awake.
+    pub fn sleep_progress(&self) -> Option<f32> {
+        let sleeping = self.entities.get::<&Sleeping>(self.player).ok()?;
+        Some((sleeping.ticks as f32 / sleeping::SLEEP_TICKS as f32).min(1.0))
+    }
+
+    pub fn is_eating(&self) -> bool {
+        self.eating.is_some()
+    }
+
+    /// Keep eating the held food while `held` and the player is hungry, one of it is used up
+    /// every [`hunger::EAT_TICKS`]
+    fn eat(&mut self, held: bool) {
+        let food = self
+            .held_item()
+            .filter(|_| held)
+            .and_then(|item| Some((item, self.items.food(item)?)));
+        let hungry = self.player_hunger().is_some_and(|hunger| !hunger.is_full());
+        let Some((item, food)) = food.filter(|_| hungry) else {
+            self.eating = None;
+            return;
+        };
+        let ticks = match self.eating {
+            Some((eating, ticks)) if eating == item => ticks + 1,
+            _ => 1,
+        };
+        if ticks < hunger::EAT_TICKS {
+            self.eating = Some((item, ticks));
+            return;
+        }
+        self.eating = None;
+        let player = self.player;
+        if let Ok((inventory, hunger)) = self
+            .entities
+            .query_one_mut::<(&mut Inventory, &mut Hunger)>(player)
+        {
+            inventory.take_selected();
+            hunger::eat(hunger, food);
+        }
+    }
+
+    /// Scatter everything the player carries around where they are
+    fn drop_inventory(&mut self) {
+        let stacks: Vec<ItemStack> = match self.inventory_mut() {
+            Some(inventory) => inventory
+                .slots
+                .iter_mut()
+                .filter_map(Option::take)
+                .collect(),
+            None => return,
+        };
+        let block = self.player_feet().map(|value| value.floor() as i32);
+        for (index, stack) in stacks.into_iter().enumerate() {
+            let seed = noise::hash(self.info.seed, &[self.tick as i64, index as i64]);
+            drops::spawn_from_block(&mut self.entities, block, stack, seed);
+        }
+    }
+
+    pub fn player_feet(&self) -> Point3<f32> {
+        self.entities
+            .get::<&Position>(self.player)
+            .map_or(Point3::origin(), |position| position.0)
+    }
+
+    /// What's falling at `position` and how heavily, from 0 to 1. Less reaches places the sky
+    /// light doesn't fully get to, none at all where it doesn't get to at all.
+    pub fn precipitation(&self, position: Point3<f32>) -> Option<(Precipitation, f32)> {
+        let strength = self.info.weather.strength;
+        let block = position.map(|value| value.floor() as i32);
+        let kind = self.generator.biome(block.x, block.z).precipitation();
+        if strength <= 0.0 || kind == Precipitation::None {
+            return None;
+        }
+        let sky = self.world.light(block).map_or(MAX_LIGHT, |light| light.sky);
+        Some((kind, strength * sky as f32 / MAX_LIGHT as f32))
+    }
+
+    fn player_data(&self) -> PlayerData {
+        let feet = self.player_feet();
+        let look = self
+            .entities
+            .get::<&Look>(self.player)
+            .map_or(Look::default(), |look| *look);
+        let (inventory, selected_slot) = self
+            .entities
+            .get::<&Inventory>(self.player)
+            .map_or((Vec::new(), 0), |inventory| {
+                (inventory.save(&self.items), inventory.selected)
+            });
+        let hunger = self.player_hunger().unwrap_or_else(Hunger::full);
+        PlayerData {
+            position: [feet.x, feet.y, feet.z],
+            yaw: look.yaw,
+            pitch: look.pitch,
+            flying: self.player_movement().flying,
+            inventory,
+            selected_slot,
+            health: self
+                .player_health()
+                .map_or(health::MAX_HEALTH, |health| health.points),
+            food: hunger.food,
+            saturation: hunger.saturation,
+            game_mode: self.game_mode(),
+            experience: self.experience(),
+            spawn: self
+                .entities
+                .get::<&SpawnPoint>(self.player)
+                .ok()
+                .map(|spawn| spawn.0.into()),
+        }
+    }
+
+    /// The item in the selected hotbar slot
+    pub fn held_item(&self) -> Option<ItemId> {
+        self.entities
+            .get::<&Inventory>(self.player)
+            .ok()
+            .and_then(|inventory| inventory.selected_stack())
+            .map(|stack| stack.item)
+    }
+
+    /// What the player places, None when the held item isn't a block
+    pub fn held_block(&self) -> Option<BlockId> {
+        self.items.block(self.held_item()?)
+    }
+
+    pub fn inventory(&self) -> Option<hecs::Ref<'_, Inventory>> {
+        self.entities.get::<&Inventory>(self.player).ok()
+    }
+
+    pub fn inventory_mut(&mut self) -> Option<&mut Inventory> {
+        self.entities
+            .query_one_mut::<&mut Inventory>(self.player)
+            .ok()
+    }
+
+    pub fn player_movement(&self) -> Movement {
+        self.entities
+            .get::<&Movement>(self.player)
+            .map_or(Movement::default(), |movement| *movement)
+    }
+
+    /// The block the player is looking at, if it's within reach
+    pub fn target(&self) -> Option<RayHit> {
+        let (eye, look) = self.player_eye(1.0);
+        raycast::raycast(&self.world, &self.blocks, eye, look.forward(), REACH)
+    }
+
+    /// The living entity the player is looking at and how far away it is, if it's within reach
+    /// and not behind the block they're looking at
+    fn target_entity(&self) -> Option<(Entity, f32)> {
+        let (eye, look) = self.player_eye(1.0);
+        let reach = self.target().map_or(REACH, |hit| hit.distance);
+        let mut query = self
+            .entities
+            .query::<(&Position, &Collider, &Health)>()
+            .without::<&Player>()
+            .without::<&Dying>();
+        query
+            .iter()
+            .filter(|(_, (_, _, health))| !health.is_dead())
+            .filter_map(|(entity, (position, collider, _))| {
+                let aabb =
+                    physics::Aabb::standing(position.0, collider.half_width, collider.height);
+                let distance = aabb.ray_distance(eye, look.forward())?;
+                (distance <= reach).then_some((entity, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Hit the entity the player is looking at with the held item and knock it back. Tools
+    /// wear down in survival.
+    pub fn attack(&mut self) {
+        let Some((entity, _)) = self.target_entity() else {
+            return;
+        };
+        let (_, look) = self.player_eye(1.0);
+        let tool = self.held_tool();
+        let Ok(health) = self.entities.query_one_mut::<&mut Health>(entity) else {
+            return;
+        };
+        if !health::hurt(health, combat::attack_damage(tool)) {
+            return;
+        }
+        combat::knock_back(&mut self.entities, entity, look.horizontal_forward());
+        if let Some(tool) = tool.filter(|_| self.game_mode() == GameMode::Survival) {
+            self.wear_held_tool(combat::wear(tool));
+        }
+    }
+
+    /// The block being broken and how far along it is
+    pub fn mining(&self) -> Option<Mining> {
+        self.mining
+    }
+
+    /// Keep breaking the block the player is looking at while `held`, starting over when they
+    /// look at another one. How long it takes comes from its hardness and the held tool, in
+    /// creative anything breakable goes at once. Nothing breaks while they're looking at an
+    /// entity, that's attacking instead.
+    fn mine(&mut self, held: bool) {
+        self.mining_cooldown = self.mining_cooldown.saturating_sub(1);
+        let target = self
+            .target()
+            .filter(|_| held && self.mining_cooldown == 0 && self.target_entity().is_none());
+        let Some(target) = target else {
+            self.mining = None;
+            return;
+        };
+        let tool = self.held_tool();
+        let step = self
+            .world
+            .block(target.block)
+            .and_then(|id| self.blocks.get(id))
+            .and_then(|block| mining::progress_per_tick(block, tool))
+            .map(|step| match self.game_mode() {
+                GameMode::Survival => step,
+                GameMode::Creative => 1.0,
+            });
+        let Some(step) = step else {
+            self.mining = None;
+            return;
+        };
+        let mining = match self.mining {
+            Some(mining) if mining.block == target.block => mining,
+            _ => Mining {
+                block: target.block,
+                progress: 0.0,
+            },
+        };
+        let progress = mining.progress + step;
+        if progress >= 1.0 {
+            self.mining = None;
+            self.mining_cooldown = mining::COOLDOWN_TICKS;
+            self.break_block();
+        } else {
+            self.mining = Some(Mining { progress, ..mining });
+        }
+    }
+
+    /// Remove the block the player is looking at unless it can't be broken, leaving whatever
+    /// was stored in it behind along with its drops if the held tool can harvest it. The
+    /// tool wears down unless the block broke instantly. Creative players get no drops and
+    /// their tools never wear.
+    pub fn break_block(&mut self) {
+        let Some(target) = self.target() else {
+            return;
+        };
+        let Some(id) = self.world.block(target.block) else {
+            return;
+        };
+        let Some(block) = self.blocks.get(id).filter(|block| block.hardness >= 0.0) else {
+            return;
+        };
+        let survival = self.game_mode() == GameMode::Survival;
+        let tool = self.held_tool().filter(|_| survival);
+        let harvested = survival && mining::can_harvest(block, tool);
+        let hardness = block.hardness;
+        let [least, most] = block.experience;
+        let contents: Vec<ItemStack> = self
+            .world
+            .take_block_entity(target.block)
+            .map(|entity| entity.slots().iter().flatten().copied().collect())
+            .unwrap_or_default();
+        self.world.set_block(target.block, BlockId::AIR);
+        self.events.push(GameEvent::BlockBroken {
+            position: target.block,
+            block: id,
+        });
+        let drops = self
+            .items
+            .drops(id)
+            .iter()
+            .filter(|_| harvested)
+            .map(|item| ItemStack::new(*item, 1));
+        for (index, stack) in drops.chain(contents).enumerate() {
+            let seed = noise::hash(self.info.seed, &[self.tick as i64, index as i64]);
+            drops::spawn_from_block(&mut self.entities, target.block, stack, seed);
+        }
+        if harvested {
+            let seed = noise::hash(self.info.seed, &[self.tick as i64, -1]);
+            let points = least + (seed % (most - least + 1) as u64) as u32;
+            let middle = target.block.map(|value| value as f32 + 0.5);
+            experience::spawn_orbs(&mut self.entities, middle, points, seed);
+        }
+        if let Some(tool) = tool.filter(|_| hardness > 0.0) {
+            self.wear_held_tool(mining::wear(tool));
+        }
+    }
+
+    /// The held item as a tool, with whatever its modifiers add
+    fn held_tool(&self) -> Option<item::Tool> {
+        let inventory = self.entities.get::<&Inventory>(self.player).ok()?;
+        let stack = inventory.selected_stack()?;
+        let tool = self.items.tool(stack.item)?;
+        Some(enchanting::modified(tool, stack.modifiers))
+    }
+
+    fn wear_held_tool(&mut self, uses: u32) {
+        let seed = noise::hash(self.info.seed, &[self.tick as i64]);
+        if let Ok(inventory) = self.entities.query_one_mut::<&mut Inventory>(self.player) {
+            inventory.wear_selected(uses, seed, &self.items);
+        }
+    }
+
+    /// Experience points the player has earned in total
+    pub fn experience(&self) -> u32 {
+        self.entities
+            .get::<&Experience>(self.player)
+            .map_or(0, |experience| experience.0)
+    }
+
+    /// Take `levels` experience levels off the player, returns whether they had them.
+    /// Creative players have as many as they like.
+    pub fn spend_levels(&mut self, levels: u32) -> bool {
+        if self.game_mode() == GameMode::Creative {
+            return true;
+        }
+        let Ok(experience) = self.entities.query_one_mut::<&mut Experience>(self.player) else {
+            return false;
+        };
+        match experience::spend_levels(experience.0, levels) {
+            Some(left) => {
+                experience.0 = left;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// What using the block the player is looking at does and where that block is, None if
+    /// it's nothing special or they're sneaking to place against it instead
+    pub fn interaction(&self) -> Option<(Interaction, Point3<i32>)> {
+        if self.player_movement().sneaking {
+            return None;
+        }
+        let target = self.target()?;
+        let id = self.world.block(target.block)?;
+        let interaction = self.blocks.get(id)?.interaction?;
+        Some((interaction, target.block))
+    }
+
+    /// Light the block at `position` if it's something that blows up, it turns into a primed
+    /// entity that goes off after [`explosion::FUSE_TICKS`]
+    pub fn ignite(&mut self, position: Point3<i32>) {
+        let Some(id) = self.world.block(position) else {
+            return;
+        };
+        let lit = self
+            .blocks
+            .get(id)
+            .is_some_and(|block| block.interaction == Some(Interaction::Ignite));
+        if !lit {
+            return;
+        }
+        self.world.set_block(position, BlockId::AIR);
+        let seed = noise::hash(self.info.seed, &[self.tick as i64]);
+        explosion::spawn_primed(
+            &mut self.entities,
+            position,
+            id,
+            explosion::FUSE_TICKS,
+            seed,
+        );
+    }
+
+    /// Flip the lever or press the button at `position`, a pressed button pops back out on its
+    /// own after [`circuit::BUTTON_TICKS`]
+    pub fn switch(&mut self, position: Point3<i32>) {
+        let Some(delay) = circuit::switch(&mut self.world, &self.blocks, position) else {
+            return;
+        };
+        self.scheduler.tasks.after(delay, move |context| {
+            circuit::release(context.world, context.blocks, position);
+        });
+    }
+
+    /// Open or close the door, trapdoor or gate at `position`
+    pub fn toggle(&mut self, position: Point3<i32>) {
+        doors::toggle(&mut self.world, &self.blocks, position);
+    }
+
+    /// Everything that happened since this was last called, oldest first
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// The player's inventory along with the block entity at `position`, for moving stacks
+    /// between them. One is made if the block should have one but doesn't, e.g. after an
+    /// import. None if there's no such block there anymore.
+    pub fn container_mut(
+        &mut self,
+        position: Point3<i32>,
+    ) -> Option<(&mut Inventory, &mut BlockEntity)> {
+        if self.world.block_entity(position).is_none() {
+            let id = self.world.block(position)?;
+            let entity = BlockEntity::new(self.blocks.get(id)?.interaction?)?;
+            self.world.insert_block_entity(position, entity);
+        }
+        let inventory = self
+            .entities
+            .query_one_mut::<&mut Inventory>(self.player)
+            .ok()?;
+        Some((inventory, self.world.block_entity_mut(position)?))
+    }
+
+    /// The text on the sign at `position`, made empty if the sign should have some but
+    /// doesn't. None if there's no sign there anymore.
+    pub fn sign_text_mut(&mut self, position: Point3<i32>) -> Option<&mut [String]> {
+        if self.world.block_entity(position).is_none() {
+            let id = self.world.block(position)?;
+            let interaction = self.blocks.get(id)?.interaction;
+            if interaction != Some(Interaction::Sign) {
+                return None;
+            }
+            self.world
+                .insert_block_entity(position, BlockEntity::new(Interaction::Sign)?);
+        }
+        let text = self.world.block_entity_mut(position)?.text_mut();
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// Throw `stack` out in front of the player, e.g. what didn't fit back in their inventory
+    pub fn drop_stack(&mut self, stack: ItemStack) {
+        let (eye, look) = self.player_eye(1.0);
+        let position = eye - Vector3::unit_y() * 0.3;
+        let velocity = look.forward() * THROW_SPEED;
+        drops::spawn(&mut self.entities, position, velocity, stack);
+    }
+
+    /// Use the held item on the block the player is looking at instead of placing anything:
+    /// a hoe tills it and fertilizer grows the crop it's on. Fertilizer is used up and hoes
+    /// wear outside creative. Returns whether either happened.
+    pub fn use_held_item(&mut self) -> bool {
+        let (Some(item), Some(target)) = (self.held_item(), self.target()) else {
+            return false;
+        };
+        let creative = self.game_mode() == GameMode::Creative;
+        let seed = noise::hash(self.info.seed, &[self.tick as i64]);
+        if let Some(stages) = self.items.fertilizer(item) {
+            if !farming::fertilize(&mut self.world, &self.blocks, target.block, stages, seed) {
+                return false;
+            }
+            if let Some(inventory) = self.inventory_mut().filter(|_| !creative) {
+                inventory.take_selected();
+            }
+            return true;
+        }
+
+        let hoe = self
+            .items
+            .tool(item)
+            .filter(|tool| tool.kind == ToolKind::Hoe);
+        let Some(hoe) = hoe else {
+            return false;
+        };
+        let Some(tilled) = farming::till(&mut self.world, &self.blocks, target.block) else {
+            return false;
+        };
+        if noise::hash_unit(seed, &[-1]) < tilled.chance {
+            let above = target.block + Vector3::unit_y();
+            for (index, name) in tilled.drops.iter().enumerate() {
+                let Some(item) = self.items.id(name) else {
+                    continue;
+                };
+                let seed = noise::hash(seed, &[index as i64]);
+                drops::spawn_from_block(&mut self.entities, above, ItemStack::new(item, 1), seed);
+            }
+        }
+        if !creative {
+            self.wear_held_tool(mining::wear(hoe));
+        }
+        true
+    }
+
+    /// Put the held block against the face the player is looking at, turned the way its
+    /// placement rule says, using up one of the stack outside creative. It only replaces air
+    /// and fluids, never goes inside the player and crops only go on farmland. Returns where
+    /// it went.
+    pub fn place_block(&mut self) -> Option<Point3<i32>> {
+        let (held, target) = (self.held_block()?, self.target()?);
+        let clicked = target.face?;
+        let position = target.adjacent();
+        let fits = |position: Point3<i32>, block: &Block| {
+            let replaceable = self.world.block(position).is_some_and(|id| {
+                id == BlockId::AIR
+                    || self
+                        .blocks
+                        .get(id)
+                        .is_some_and(|block| block.fluid.is_some())
+            });
+            replaceable
+                && !(block.solid && physics::overlaps_block(&self.player_aabb(), position, block))
+        };
+        let looking = self
+            .entities
+            .get::<&Look>(self.player)
+            .map_or(Face::North, |look| look.facing());
+        let placed = self.blocks.placed(held, clicked, looking)?;
+        let block = self.blocks.get(placed)?;
+        if !fits(position, block) {
+            return None;
+        }
+        let below = position - Vector3::unit_y();
+        if block.crop.is_some() && !farming::is_farmland(&self.world, &self.blocks, below) {
+            return None;
+        }
+        // The other half of a door goes in along with it, it needs the room as well
+        let other =
+            doors::other_half(&self.blocks, placed).map(|(offset, id)| (position + offset, id));
+        if let Some((other, id)) = other {
+            if !fits(other, self.blocks.get(id)?) {
+                return None;
+            }
+        }
+        self.world.set_block(position, placed);
+        if let Some((other, id)) = other {
+            self.world.set_block(other, id);
+        }
+        if let Some(entity) = block.interaction.and_then(BlockEntity::new) {
+            self.world.insert_block_entity(position, entity);
+        }
+        self.events.push(GameEvent::BlockPlaced {
+            position,
+            block: placed,
+        });
+        if self.game_mode() != GameMode::Creative {
+            if let Some(inventory) = self.inventory_mut() {
+                inventory.take_selected();
+            }
+        }
+        Some(position)
+    }
+
+    /// Hold the item of the block the player is looking at, whichever way it's turned, see
+    /// [`Inventory::pick`]. Creative players get a stack of it if they don't have one.
+    pub fn pick_block(&mut self) {
+        let picked = self
+            .target()
+            .and_then(|target| self.world.block(target.block))
+            .filter(|id| {
+                self.blocks
+                    .get(*id)
+                    .is_some_and(|block| block.fluid.is_none())
+            });
+        let Some(item) = picked.and_then(|id| self.items.of_block(id)) else {
+            return;
+        };
+        let creative = self.game_mode() == GameMode::Creative;
+        let player = self.player;
+        if let Ok(inventory) = self.entities.query_one_mut::<&mut Inventory>(player) {
+            if creative {
+                inventory.pick_or_fill(item, &self.items);
+            } else {
+                inventory.pick(item);
+            }
+        }
+    }
+
+    fn player_aabb(&self) -> physics::Aabb {
+        let mut query = self
+            .entities
+            .query_one::<(&Position, &Collider)>(self.player)
+            .ok();
+        match query.as_mut().and_then(|query| query.get()) {
+            Some((position, collider)) => {
+                physics::Aabb::standing(position.0, collider.half_width, collider.height)
+            }
+            None => physics::Aabb::standing(Point3::origin(), 0.0, 0.0),
+        }
+    }
+
+    /// Eye position and look direction, `alpha` is how far into the next tick the frame is
+    pub fn player_eye(&self, alpha: f32) -> (Point3<f32>, Look) {
+        let mut query = self
+            .entities
+            .query_one::<(&Position, &PreviousPosition, &Look)>(self.player)
+            .ok();
+        let Some((position, previous, look)) = query.as_mut().and_then(|query| query.get()) else {
+            return (Point3::origin(), Look::default());
+        };
+        let feet = previous.0 + (position.0 - previous.0) * alpha;
+        let eye_height = if self.is_sleeping() {
+            SLEEP_EYE_HEIGHT
+        } else if self.player_movement().sneaking {
+            SNEAK_EYE_HEIGHT
+        } else {
+            EYE_HEIGHT
+        };
+        (feet + cgmath::Vector3::unit_y() * eye_height, *look)
+    }
+}
@@ -0,0 +1,138 @@
+use cgmath::{Point3, Vector3};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::world::{BlockRegistry, World};
+
+/// Most blocks a search expands before it settles for getting as close as it can
+const MAX_NODES: usize = 1024;
+/// Deepest a path drops down in one step, like the fall that starts to hurt
+const MAX_DROP: i32 = 3;
+const SIDES: [[i32; 2]; 4] = [[1, 0], [-1, 0], [0, 1], [0, -1]];
+
+/// An A* search over the blocks for a way to walk from `start` to `goal`, both feet
+/// positions, for something `height` blocks tall. Paths walk from block to block, step up
+/// single blocks and drop down at most [`MAX_DROP`], they don't swim or jump gaps. When the
+/// goal can't be reached within [`MAX_NODES`] the path leads to the block found closest to
+/// it instead.
+///
+/// The feet positions to walk through in order, without `start`. None when there's no
+/// getting any closer.
+pub fn find_path(
+    world: &World,
+    blocks: &BlockRegistry,
+    start: Point3<i32>,
+    goal: Point3<i32>,
+    height: i32,
+) -> Option<Vec<Point3<i32>>> {
+    let grid = Grid {
+        world,
+        blocks,
+        height,
+    };
+    let mut open: BinaryHeap<Reverse<(u32, u32, [i32; 3])>> = BinaryHeap::new();
+    let mut costs = HashMap::from([(start, 0)]);
+    let mut came_from = HashMap::new();
+    let mut closest = (estimate(start, goal), start);
+    // Points aren't ordered, the queue holds plain arrays
+    open.push(Reverse((closest.0, 0, *start.as_ref())));
+    let mut expanded = 0;
+    while let Some(Reverse((_, cost, node))) = open.pop() {
+        let node = Point3::from(node);
+        // A cheaper way here was found after this one was queued
+        if costs.get(&node).is_some_and(|best| *best < cost) {
+            continue;
+        }
+        if node == goal || expanded == MAX_NODES {
+            break;
+        }
+        expanded += 1;
+        for next in grid.neighbours(node) {
+            let next_cost = cost + 1;
+            if costs.get(&next).is_some_and(|best| *best <= next_cost) {
+                continue;
+            }
+            costs.insert(next, next_cost);
+            came_from.insert(next, node);
+            let remaining = estimate(next, goal);
+            if remaining < closest.0 {
+                closest = (remaining, next);
+            }
+            open.push(Reverse((next_cost + remaining, next_cost, *next.as_ref())));
+        }
+    }
+
+    let mut path = Vec::new();
+    let mut node = closest.1;
+    while node != start {
+        path.push(node);
+        node = came_from[&node];
+    }
+    path.reverse();
+    (!path.is_empty()).then_some(path)
+}
+
+/// Never more steps than it really takes: every step goes one block sideways and at most one
+/// up
+fn estimate(from: Point3<i32>, to: Point3<i32>) -> u32 {
+    let sideways = from.x.abs_diff(to.x) + from.z.abs_diff(to.z);
+    let rise = (to.y - from.y).max(0) as u32;
+    sideways.max(rise)
+}
+
+/// The blocks as far as walking through them goes
+struct Grid<'a> {
+    world: &'a World,
+    blocks: &'a BlockRegistry,
+    height: i32,
+}
+
+impl Grid<'_> {
+    /// Unloaded blocks are walls, nothing is known about them
+    fn solid(&self, position: Point3<i32>) -> bool {
+        self.world
+            .block(position)
+            .is_none_or(|id| self.blocks.get(id).is_some_and(|block| block.solid))
+    }
+
+    fn harmful(&self, position: Point3<i32>) -> bool {
+        self.world
+            .block(position)
+            .and_then(|id| self.blocks.get(id)?.fluid)
+            .is_some_and(|fluid| fluid.properties.damage > 0)
+    }
+
+    /// Whether feet at `position` fit, with something to stand on
+    fn standable(&self, position: Point3<i32>) -> bool {
+        self.fits(position, 0) && self.solid(position - Vector3::unit_y())
+    }
+
+    /// Whether there's room from `position` up to `extra` blocks above the entity's head
+    fn fits(&self, position: Point3<i32>, extra: i32) -> bool {
+        (0..self.height + extra).all(|above| {
+            let block = position + Vector3::unit_y() * above;
+            !self.solid(block) && !self.harmful(block)
+        })
+    }
+
+    fn neighbours(&self, node: Point3<i32>) -> impl Iterator<Item = Point3<i32>> + '_ {
+        SIDES.into_iter().filter_map(move |[x, z]| {
+            let side = node + Vector3::new(x, 0, z);
+            if self.standable(side) {
+                return Some(side);
+            }
+            if self.solid(side) {
+                // Stepping up needs room to jump where it stands too
+                let up = side + Vector3::unit_y();
+                return (self.standable(up) && self.fits(node, 1)).then_some(up);
+            }
+            if !self.fits(side, 0) {
+                return None;
+            }
+            (1..=MAX_DROP)
+                .map(|depth| side - Vector3::unit_y() * depth)
+                .take_while(|below| !self.solid(*below))
+                .find(|below| self.standable(*below))
+        })
+    }
+}
@@ -0,0 +1,247 @@
+use cgmath::{Point3, Vector3};
+
+use crate::world::block::{Block, FULL_BLOCK};
+use crate::world::{BlockId, BlockRegistry, World};
+
+/// Keeps boxes that are flush against a block from counting as inside it, and floating
+/// point drift from pushing them in
+const EPSILON: f32 = 1e-4;
+/// How far down a sneaking entity looks for something to stand on
+const EDGE_DROP: f32 = 0.6;
+/// Steps motion is cut back by until there's ground under the box again
+const EDGE_STEP: f32 = 0.05;
+
+/// Axis-aligned box in world space
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    /// A box standing on `feet`, `half_width` out to each side
+    pub fn standing(feet: Point3<f32>, half_width: f32, height: f32) -> Self {
+        Aabb {
+            min: Point3::new(feet.x - half_width, feet.y, feet.z - half_width),
+            max: Point3::new(feet.x + half_width, feet.y + height, feet.z + half_width),
+        }
+    }
+
+    /// Grown by `amount` in every direction
+    pub fn inflated(self, amount: Vector3<f32>) -> Self {
+        Aabb {
+            min: self.min - amount,
+            max: self.max + amount,
+        }
+    }
+
+    pub fn middle(&self) -> Point3<f32> {
+        self.min + (self.max - self.min) / 2.0
+    }
+
+    /// Whether the boxes overlap by more than touching on every axis
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|axis| self.overlaps(other, axis))
+    }
+
+    /// How far along the ray from `origin` towards the unit vector `direction` it first meets
+    /// the box, 0 from inside it. None if it misses.
+    pub fn ray_distance(&self, origin: Point3<f32>, direction: Vector3<f32>) -> Option<f32> {
+        let (mut near, mut far) = (0.0f32, f32::INFINITY);
+        for axis in 0..3 {
+            if direction[axis] == 0.0 {
+                if origin[axis] < self.min[axis] || origin[axis] > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let to_min = (self.min[axis] - origin[axis]) / direction[axis];
+            let to_max = (self.max[axis] - origin[axis]) / direction[axis];
+            near = near.max(to_min.min(to_max));
+            far = far.min(to_min.max(to_max));
+        }
+        (near <= far).then_some(near)
+    }
+
+    fn translated(self, axis: usize, distance: f32) -> Self {
+        let mut moved = self;
+        moved.min[axis] += distance;
+        moved.max[axis] += distance;
+        moved
+    }
+
+    /// Whether the two boxes overlap on `axis` by more than touching
+    fn overlaps(&self, other: &Aabb, axis: usize) -> bool {
+        self.min[axis] < other.max[axis] - EPSILON && self.max[axis] > other.min[axis] + EPSILON
+    }
+
+    /// Blocks the box overlaps on `axis`, boxes ending right on a boundary don't reach into
+    /// the next block
+    fn block_range(&self, axis: usize) -> std::ops::RangeInclusive<i32> {
+        (self.min[axis] + EPSILON).floor() as i32..=(self.max[axis] - EPSILON).floor() as i32
+    }
+
+    /// Every block the box overlaps
+    fn blocks(&self) -> impl Iterator<Item = Point3<i32>> + '_ {
+        self.block_range(0).flat_map(move |x| {
+            self.block_range(1)
+                .flat_map(move |y| self.block_range(2).map(move |z| Point3::new(x, y, z)))
+        })
+    }
+}
+
+/// Which axes a move was cut short on
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Collisions {
+    pub x: bool,
+    /// Stopped while moving down, i.e. landed on something
+    pub ground: bool,
+    /// Stopped while moving up
+    pub ceiling: bool,
+    pub z: bool,
+}
+
+/// Move `aabb` by `motion` one axis at a time, Y first then X and Z, stopping at the first
+/// solid block on each. Returns how far it actually went.
+pub fn move_and_collide(
+    world: &World,
+    blocks: &BlockRegistry,
+    aabb: Aabb,
+    motion: Vector3<f32>,
+) -> (Vector3<f32>, Collisions) {
+    let mut aabb = aabb;
+    let mut moved = Vector3::new(0.0, 0.0, 0.0);
+    let mut collisions = Collisions::default();
+    for axis in [1, 0, 2] {
+        let wanted = motion[axis];
+        let allowed = sweep(world, blocks, &aabb, axis, wanted);
+        if allowed != wanted {
+            match axis {
+                0 => collisions.x = true,
+                1 if wanted < 0.0 => collisions.ground = true,
+                1 => collisions.ceiling = true,
+                _ => collisions.z = true,
+            }
+        }
+        aabb = aabb.translated(axis, allowed);
+        moved[axis] = allowed;
+    }
+    (moved, collisions)
+}
+
+/// Cut horizontal `motion` back until the box would still have ground under it, so sneaking
+/// can't walk off an edge. Only meant for boxes that are standing on something.
+pub fn hold_edge(
+    world: &World,
+    blocks: &BlockRegistry,
+    aabb: Aabb,
+    motion: Vector3<f32>,
+) -> Vector3<f32> {
+    let supported = |x: f32, z: f32| {
+        let below = aabb
+            .translated(0, x)
+            .translated(1, -EDGE_DROP)
+            .translated(2, z);
+        overlaps_solid(world, blocks, &below)
+    };
+    let step_back = |value: f32| {
+        if value.abs() <= EDGE_STEP {
+            0.0
+        } else {
+            value - EDGE_STEP.copysign(value)
+        }
+    };
+
+    let (mut x, mut z) = (motion.x, motion.z);
+    while x != 0.0 && !supported(x, 0.0) {
+        x = step_back(x);
+    }
+    while z != 0.0 && !supported(0.0, z) {
+        z = step_back(z);
+    }
+    // Each axis alone can be fine while the diagonal hangs over a corner
+    while x != 0.0 && z != 0.0 && !supported(x, z) {
+        x = step_back(x);
+        z = step_back(z);
+    }
+    Vector3::new(x, motion.y, z)
+}
+
+/// World space boxes of the block at `block` if it's solid. Unloaded chunks count as full
+/// blocks so nothing falls out of the world while they stream in.
+fn solid_boxes<'a>(
+    world: &World,
+    blocks: &'a BlockRegistry,
+    block: Point3<i32>,
+) -> impl Iterator<Item = Aabb> + 'a {
+    let shape: &[([f32; 3], [f32; 3])] = match world.block(block) {
+        None => &FULL_BLOCK,
+        Some(id) => blocks
+            .get(id)
+            .filter(|block| block.solid)
+            .map_or(&[][..], |block| block.boxes()),
+    };
+    let corner = block.map(|value| value as f32);
+    shape.iter().map(move |(min, max)| Aabb {
+        min: corner + Vector3::from(*min),
+        max: corner + Vector3::from(*max),
+    })
+}
+
+/// Whether `block` would reach into the box if it were at `position`, solid or not
+pub fn overlaps_block(aabb: &Aabb, position: Point3<i32>, block: &Block) -> bool {
+    let corner = position.map(|value| value as f32);
+    block.boxes().iter().any(|(min, max)| {
+        let shape = Aabb {
+            min: corner + Vector3::from(*min),
+            max: corner + Vector3::from(*max),
+        };
+        (0..3).all(|axis| aabb.overlaps(&shape, axis))
+    })
+}
+
+/// Whether any solid block reaches into the box
+fn overlaps_solid(world: &World, blocks: &BlockRegistry, aabb: &Aabb) -> bool {
+    aabb.blocks().any(|block| {
+        solid_boxes(world, blocks, block)
+            .any(|solid| (0..3).all(|axis| aabb.overlaps(&solid, axis)))
+    })
+}
+
+/// Source block of a fluid the box is in, the first one found if it's in several
+pub fn fluid_in(world: &World, blocks: &BlockRegistry, aabb: &Aabb) -> Option<BlockId> {
+    aabb.blocks().find_map(|block| {
+        let id = world.block(block)?;
+        blocks.get(id)?.fluid.map(|fluid| fluid.source)
+    })
+}
+
+/// How far the box can go along `axis` before it hits a solid block, at most `distance`.
+/// Boxes it already overlaps don't stop it, so it can always get out of them.
+fn sweep(world: &World, blocks: &BlockRegistry, aabb: &Aabb, axis: usize, distance: f32) -> f32 {
+    if distance == 0.0 {
+        return 0.0;
+    }
+    let across = [(axis + 1) % 3, (axis + 2) % 3];
+    let mut path = *aabb;
+    if distance > 0.0 {
+        path.max[axis] += distance;
+    } else {
+        path.min[axis] += distance;
+    }
+
+    let mut allowed = distance;
+    for block in path.blocks() {
+        for solid in solid_boxes(world, blocks, block) {
+            if !across.iter().all(|other| aabb.overlaps(&solid, *other)) {
+                continue;
+            }
+            if distance > 0.0 && solid.min[axis] >= aabb.max[axis] - EPSILON {
+                allowed = allowed.min((solid.min[axis] - aabb.max[axis]).max(0.0));
+            } else if distance < 0.0 && solid.max[axis] <= aabb.min[axis] + EPSILON {
+                allowed = allowed.max((solid.max[axis] - aabb.min[axis]).min(0.0));
+            }
+        }
+    }
+    allowed
+}
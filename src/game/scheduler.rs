@@ -0,0 +1,180 @@
+use hecs::World;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use super::item::ItemRegistry;
+use super::{GameEvent, PlayerInput};
+use crate::profiler;
+use crate::save::WorldInfo;
+use crate::world::{self, BlockRegistry};
+
+/// Everything a system or task gets to touch during a tick
+pub struct TickContext<'a> {
+    /// Ticks since the game was opened, unlike the world time it never jumps
+    pub tick: u64,
+    pub entities: &'a mut World,
+    /// The blocks, as opposed to the entities
+    pub world: &'a mut world::World,
+    pub blocks: &'a BlockRegistry,
+    pub items: &'a ItemRegistry,
+    pub info: &'a mut WorldInfo,
+    pub input: &'a PlayerInput,
+    /// For scheduling more work from inside a tick
+    pub tasks: &'a mut TaskQueue,
+    /// What happened for the rest of the app to show, see [`super::Game::take_events`]
+    pub events: &'a mut Vec<GameEvent>,
+}
+
+/// The game's side of a [`TickContext`], the scheduler adds its own task queue
+pub struct TickState<'a> {
+    pub entities: &'a mut World,
+    pub world: &'a mut world::World,
+    pub blocks: &'a BlockRegistry,
+    pub items: &'a ItemRegistry,
+    pub info: &'a mut WorldInfo,
+    pub events: &'a mut Vec<GameEvent>,
+}
+
+pub type System = fn(&mut TickContext);
+type Task = Box<dyn FnMut(&mut TickContext)>;
+
+struct Scheduled {
+    due: u64,
+    /// Breaks ties so tasks due on the same tick run in the order they were scheduled
+    sequence: u64,
+    /// Ticks until it runs again, None for one-shot tasks
+    interval: Option<u64>,
+    task: Task,
+}
+
+// Reversed so the max-heap pops the earliest task first
+impl Ord for Scheduled {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.due, other.sequence).cmp(&(self.due, self.sequence))
+    }
+}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        (self.due, self.sequence) == (other.due, other.sequence)
+    }
+}
+
+impl Eq for Scheduled {}
+
+/// Work that runs on a later tick, either once or over and over
+pub struct TaskQueue {
+    heap: BinaryHeap<Scheduled>,
+    /// The tick being run, delays count from here
+    now: u64,
+    next_sequence: u64,
+}
+
+impl TaskQueue {
+    fn new() -> Self {
+        TaskQueue {
+            heap: BinaryHeap::new(),
+            now: 0,
+            next_sequence: 0,
+        }
+    }
+
+    /// Run `task` once, `delay` ticks from now. A delay of 0 still waits for the next tick.
+    pub fn after(&mut self, delay: u64, task: impl FnOnce(&mut TickContext) + 'static) {
+        let mut task = Some(task);
+        self.push(delay, None, move |context: &mut TickContext| {
+            if let Some(task) = task.take() {
+                task(context);
+            }
+        });
+    }
+
+    /// Run `task` every `interval` ticks, the first time one interval from now
+    pub fn every(&mut self, interval: u64, task: impl FnMut(&mut TickContext) + 'static) {
+        let interval = interval.max(1);
+        self.push(interval, Some(interval), task);
+    }
+
+    fn push(
+        &mut self,
+        delay: u64,
+        interval: Option<u64>,
+        task: impl FnMut(&mut TickContext) + 'static,
+    ) {
+        self.heap.push(Scheduled {
+            due: self.now + delay.max(1),
+            sequence: self.next_sequence,
+            interval,
+            task: Box::new(task),
+        });
+        self.next_sequence += 1;
+    }
+
+    fn pop_due(&mut self, tick: u64) -> Option<Scheduled> {
+        if self.heap.peek()?.due > tick {
+            return None;
+        }
+        self.heap.pop()
+    }
+}
+
+/// Runs the per-tick systems in order, then whatever tasks are due
+pub struct Scheduler {
+    systems: Vec<(&'static str, System)>,
+    pub tasks: TaskQueue,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Scheduler {
+            systems: Vec::new(),
+            tasks: TaskQueue::new(),
+        }
+    }
+
+    /// Systems run every tick in the order they were added, `name` shows up in the profiler
+    pub fn add_system(&mut self, name: &'static str, system: System) {
+        self.systems.push((name, system));
+    }
+
+    pub fn run_tick(&mut self, tick: u64, state: TickState, input: &PlayerInput) {
+        self.tasks.now = tick;
+        let mut context = TickContext {
+            tick,
+            entities: state.entities,
+            world: state.world,
+            blocks: state.blocks,
+            items: state.items,
+            info: state.info,
+            input,
+            tasks: &mut self.tasks,
+            events: state.events,
+        };
+        for &(name, system) in &self.systems {
+            let _scope = profiler::scope(name);
+            system(&mut context);
+        }
+
+        let _scope = profiler::scope("tasks");
+        // Tasks scheduled while this runs are due next tick at the earliest, so this ends
+        while let Some(mut scheduled) = context.tasks.pop_due(tick) {
+            (scheduled.task)(&mut context);
+            if let Some(interval) = scheduled.interval {
+                scheduled.due = tick + interval;
+                context.tasks.heap.push(scheduled);
+            }
+        }
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,86 @@
+use cgmath::{InnerSpace, Point3, Vector2};
+
+use super::components::{Dying, Hunt, Player, Position, Sleeping};
+use super::scheduler::TickContext;
+use super::{is_daytime, DAY_LENGTH};
+use crate::world::block::Interaction;
+use crate::world::{BlockRegistry, World};
+
+/// Ticks every player has to have been lying in bed before the night is skipped
+pub const SLEEP_TICKS: u32 = 100;
+/// Monsters this many blocks away sideways, and this many up or down, keep the player awake
+const MONSTER_RANGE: f32 = 8.0;
+const MONSTER_HEIGHT: f32 = 5.0;
+/// Blocks a sleeper can get pushed away from their bed before they wake up
+const MAX_DRIFT: f32 = 0.5;
+
+/// Why the player couldn't get into bed
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CantSleep {
+    /// Beds are only for the night
+    Day,
+    /// A hostile mob is too close
+    Monsters,
+}
+
+/// Where a player lying in the bed at `bed`, or coming back to it, has their feet
+pub fn feet_on(bed: Point3<i32>) -> Point3<f32> {
+    Point3::new(bed.x as f32 + 0.5, bed.y as f32 + 1.0, bed.z as f32 + 0.5)
+}
+
+pub fn is_bed(world: &World, blocks: &BlockRegistry, position: Point3<i32>) -> bool {
+    world
+        .block(position)
+        .and_then(|id| blocks.get(id))
+        .is_some_and(|block| block.interaction == Some(Interaction::Sleep))
+}
+
+/// Whether a hostile mob that's still alive is close enough to `feet` to keep a player there
+/// from sleeping
+pub fn monsters_nearby(entities: &hecs::World, feet: Point3<f32>) -> bool {
+    entities
+        .query::<&Position>()
+        .with::<&Hunt>()
+        .without::<&Dying>()
+        .iter()
+        .any(|(_, position)| {
+            let offset = position.0 - feet;
+            offset.y.abs() <= MONSTER_HEIGHT
+                && Vector2::new(offset.x, offset.z).magnitude() <= MONSTER_RANGE
+        })
+}
+
+/// Runs after the clock: sleepers wake up when they jump or sneak, once it's day, or when
+/// their bed is gone or they got pushed off it. Once every player has been asleep for
+/// [`SLEEP_TICKS`] the clock skips to the next sunrise, which wakes them all on the next tick.
+pub fn update_sleepers(context: &mut TickContext) {
+    let input = context.input;
+    let daytime = is_daytime(context.info.time);
+    let (world, blocks) = (&*context.world, context.blocks);
+    let mut woken = Vec::new();
+    for (entity, (position, sleeping)) in context.entities.query_mut::<(&Position, &mut Sleeping)>()
+    {
+        sleeping.ticks = sleeping.ticks.saturating_add(1);
+        let in_bed = is_bed(world, blocks, sleeping.bed)
+            && (position.0 - feet_on(sleeping.bed)).magnitude() <= MAX_DRIFT;
+        if input.jump || input.sneak || daytime || !in_bed {
+            woken.push(entity);
+        }
+    }
+    for entity in woken {
+        let _ = context.entities.remove_one::<Sleeping>(entity);
+    }
+    if daytime {
+        return;
+    }
+    let rested: Vec<bool> = context
+        .entities
+        .query_mut::<Option<&Sleeping>>()
+        .with::<&Player>()
+        .into_iter()
+        .map(|(_, sleeping)| sleeping.is_some_and(|sleeping| sleeping.ticks >= SLEEP_TICKS))
+        .collect();
+    if !rested.is_empty() && rested.iter().all(|rested| *rested) {
+        context.info.time = (context.info.time / DAY_LENGTH + 1) * DAY_LENGTH;
+    }
+}
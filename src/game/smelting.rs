@@ -0,0 +1,108 @@
+use anyhow::{bail, Context};
+use std::collections::HashMap;
+
+use super::inventory::ItemStack;
+use super::item::{ItemId, ItemRegistry};
+use crate::assets::Assets;
+use crate::world::block_entity::{
+    Furnace, FURNACE_FUEL, FURNACE_INPUT, FURNACE_OUTPUT, SMELT_TICKS,
+};
+
+/// Item names mapped to the item they smelt into
+const RECIPES_PATH: &str = "smelting/recipes.json";
+/// Item names mapped to the ticks one of them burns for
+const FUELS_PATH: &str = "smelting/fuels.json";
+/// Progress lost every tick the fire is out, so a furnace that runs out of fuel halfway
+/// slowly forgets the item it was on
+const COOL_DOWN: u32 = 2;
+
+/// What furnaces can smelt and what they can burn
+#[derive(Debug, Default)]
+pub struct SmeltingBook {
+    results: HashMap<ItemId, ItemId>,
+    fuels: HashMap<ItemId, u32>,
+}
+
+impl SmeltingBook {
+    pub fn load(assets: &Assets, items: &ItemRegistry) -> anyhow::Result<Self> {
+        let item = |path: &str, name: &str| {
+            items
+                .id(name)
+                .with_context(|| format!("{path}: there's no item named {name}"))
+        };
+        let recipes: HashMap<String, String> = read(assets, RECIPES_PATH)?;
+        let results = recipes
+            .iter()
+            .map(|(input, result)| Ok((item(RECIPES_PATH, input)?, item(RECIPES_PATH, result)?)))
+            .collect::<anyhow::Result<_>>()?;
+        let fuels: HashMap<String, u32> = read(assets, FUELS_PATH)?;
+        if fuels.values().any(|ticks| *ticks == 0) {
+            bail!("{FUELS_PATH}: a fuel has to burn for at least one tick");
+        }
+        let fuels = fuels
+            .iter()
+            .map(|(name, ticks)| Ok((item(FUELS_PATH, name)?, *ticks)))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(SmeltingBook { results, fuels })
+    }
+
+    /// What one of `item` smelts into, None if it can't be smelted
+    pub fn result(&self, item: ItemId) -> Option<ItemId> {
+        self.results.get(&item).copied()
+    }
+
+    /// Ticks one of `item` burns for, None if it isn't a fuel
+    pub fn burn_ticks(&self, item: ItemId) -> Option<u32> {
+        self.fuels.get(&item).copied()
+    }
+}
+
+fn read<T: serde::de::DeserializeOwned>(assets: &Assets, path: &str) -> anyhow::Result<T> {
+    let bytes = assets.read(path)?;
+    serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {path}"))
+}
+
+/// Run `furnace` for one tick: burn down the fuel, light the next one when there's something
+/// to smelt and move the input along, one item every [`SMELT_TICKS`]. Returns whether
+/// anything about it changed.
+pub fn tick(furnace: &mut Furnace, book: &SmeltingBook, items: &ItemRegistry) -> bool {
+    let before = furnace.clone();
+    furnace.burn_left = furnace.burn_left.saturating_sub(1);
+
+    let output = furnace.slots[FURNACE_OUTPUT];
+    let result = furnace.slots[FURNACE_INPUT]
+        .and_then(|input| book.result(input.item))
+        .filter(|result| {
+            output.is_none_or(|output| {
+                output.item == *result && output.count < items.max_stack(*result)
+            })
+        });
+    let Some(result) = result else {
+        furnace.progress = 0;
+        return *furnace != before;
+    };
+
+    if !furnace.is_burning() {
+        let fuel =
+            furnace.slots[FURNACE_FUEL].and_then(|fuel| Some((fuel, book.burn_ticks(fuel.item)?)));
+        if let Some((fuel, ticks)) = fuel {
+            furnace.slots[FURNACE_FUEL] = fuel.take(1).1;
+            furnace.burn_left = ticks;
+            furnace.burn_total = ticks;
+        }
+    }
+    if !furnace.is_burning() {
+        furnace.progress = furnace.progress.saturating_sub(COOL_DOWN);
+        return *furnace != before;
+    }
+
+    furnace.progress += 1;
+    if furnace.progress >= SMELT_TICKS {
+        furnace.progress = 0;
+        furnace.slots[FURNACE_INPUT] =
+            furnace.slots[FURNACE_INPUT].and_then(|input| input.take(1).1);
+        let count = output.map_or(0, |output| output.count);
+        furnace.slots[FURNACE_OUTPUT] = Some(ItemStack::new(result, count + 1));
+    }
+    *furnace != before
+}
@@ -0,0 +1,298 @@
+use cgmath::{InnerSpace, Point3, Vector3, Zero};
+use hecs::{Entity, EntityBuilder};
+use std::f32::consts::TAU;
+
+use super::components::{
+    Breath, BurnsInDaylight, Collider, Dying, FallDistance, Health, Hunt, InFluid, Look, Mob,
+    Model, OnGround, Player, Position, PreviousPosition, Velocity, WalkCycle, Wander,
+};
+use super::health::MAX_BREATH;
+use super::inventory::ItemStack;
+use super::mob::{MobId, MobRegistry, MobType};
+use super::scheduler::TickContext;
+use super::{drops, experience};
+use crate::world::chunk::CHUNK_SIZE;
+use crate::world::light::Light;
+use crate::world::noise;
+use crate::world::{BlockId, BlockRegistry, World};
+
+/// Ticks between attempts at spawning a group of mobs
+pub const SPAWN_INTERVAL: u64 = 20;
+/// No more passive or hostile mobs spawn while there are this many of them around
+const PASSIVE_CAP: usize = 12;
+const HOSTILE_CAP: usize = 20;
+/// Nor while there are this many of them within [`AREA_RADIUS`] of where they'd spawn, so
+/// they spread out over the loaded world
+const AREA_CAP: usize = 6;
+const AREA_RADIUS: f32 = 32.0;
+/// Mobs that spawn in the dark only do where the light is at most this, the rest only where
+/// it's brighter
+const MAX_DARK: u8 = 7;
+/// How much less the sky lights up at night
+const NIGHT_SKY_DIMMING: u8 = 11;
+/// Mobs never spawn closer to the player than this, so they don't pop up in plain sight
+const MIN_SPAWN_DISTANCE: f32 = 24.0;
+/// Mobs further from the player than this disappear, as do ones outside the loaded world
+const DESPAWN_DISTANCE: f32 = 96.0;
+/// Ticks a mob spends falling over after it dies before it's gone
+pub const DEATH_TICKS: u32 = 20;
+/// Blocks from where a group is spawned that the rest of it can end up, sideways and up or
+/// down
+const GROUP_SPREAD: i32 = 2;
+
+/// Put a mob of the kind `mob` at `feet`, facing `yaw`
+pub fn spawn(
+    entities: &mut hecs::World,
+    id: MobId,
+    mob: &MobType,
+    feet: Point3<f32>,
+    yaw: f32,
+) -> Entity {
+    let mut builder = EntityBuilder::new();
+    builder.add_bundle((
+        Mob(id),
+        Model(mob.model.clone()),
+        Position(feet),
+        PreviousPosition(feet),
+        Velocity(Vector3::zero()),
+        Collider {
+            half_width: mob.half_width,
+            height: mob.height,
+        },
+        OnGround(false),
+        InFluid(None),
+        Look { yaw, pitch: 0.0 },
+        WalkCycle::default(),
+        Health::new(mob.health),
+        FallDistance::default(),
+        Breath(MAX_BREATH),
+        Wander {
+            speed: mob.speed,
+            ..Wander::default()
+        },
+    ));
+    if let Some(attack) = mob.attack {
+        builder.add(Hunt {
+            speed: mob.speed,
+            damage: attack.damage,
+            range: attack.range,
+            ..Hunt::default()
+        });
+    }
+    if mob.burns {
+        builder.add(BurnsInDaylight);
+    }
+    entities.spawn(builder.build())
+}
+
+/// Picks a random column of a random loaded chunk and spawns a group of mobs on top of the
+/// highest block there with room above, if some kind of mob spawns on it in that light.
+/// Nothing spawns too close to the player or where there are too many mobs of the same
+/// kind, hostile or passive, around already.
+pub fn spawn_mobs(context: &mut TickContext, mobs: &MobRegistry) {
+    let Some(player) = player_feet(context.entities) else {
+        return;
+    };
+    let (world, blocks) = (&*context.world, context.blocks);
+    let (seed, tick, time) = (context.info.seed, context.tick as i64, context.info.time);
+    let random = |salt: i64| noise::hash(seed, &[tick, salt]);
+
+    let chunks = world.chunk_positions().count();
+    let Some(chunk) = world
+        .chunk_positions()
+        .nth((random(0) % chunks.max(1) as u64) as usize)
+    else {
+        return;
+    };
+    let origin = chunk.origin();
+    let (x, z) = (
+        origin.x + (random(1) % CHUNK_SIZE as u64) as i32,
+        origin.z + (random(2) % CHUNK_SIZE as u64) as i32,
+    );
+    let top = origin.y + CHUNK_SIZE - 1;
+    let Some((ground, block)) = spawn_spot(world, blocks, x, z, top, origin.y) else {
+        return;
+    };
+    let distance = (feet_on(ground) - player).magnitude();
+    if !(MIN_SPAWN_DISTANCE..DESPAWN_DISTANCE).contains(&distance) {
+        return;
+    }
+    let light = world
+        .light(ground + Vector3::unit_y())
+        .unwrap_or(Light::OPEN);
+    let dark = light_level(light, time) <= MAX_DARK;
+    let candidates: Vec<_> = mobs
+        .iter()
+        .filter(|(_, mob)| mob.spawn_on.contains(&block) && mob.dark == dark)
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+    let (id, mob) = candidates[(random(3) % candidates.len() as u64) as usize];
+    let (total, nearby) = count_mobs(context.entities, mobs, mob.is_hostile(), feet_on(ground));
+    let cap = if mob.is_hostile() {
+        HOSTILE_CAP
+    } else {
+        PASSIVE_CAP
+    };
+    let room = AREA_CAP
+        .saturating_sub(nearby)
+        .min(cap.saturating_sub(total));
+    let group = mob.group.end() - mob.group.start() + 1;
+    let count = (mob.group.start() + (random(4) % group as u64) as u32).min(room as u32);
+
+    let mut spawned = Vec::new();
+    for index in 0..count as i64 {
+        let offset = |salt: i64| {
+            (random(5 + index * 3 + salt) % (GROUP_SPREAD * 2 + 1) as u64) as i32 - GROUP_SPREAD
+        };
+        let (x, z) = (ground.x + offset(0), ground.z + offset(1));
+        let spot = spawn_spot(
+            world,
+            blocks,
+            x,
+            z,
+            ground.y + GROUP_SPREAD,
+            ground.y - GROUP_SPREAD,
+        );
+        let Some((ground, block)) = spot else {
+            continue;
+        };
+        if mob.spawn_on.contains(&block) && has_room(world, ground, mob.height) {
+            let yaw = noise::hash_unit(seed, &[tick, index, 2]) as f32 * TAU;
+            spawned.push((feet_on(ground), yaw));
+        }
+    }
+    for (feet, yaw) in spawned {
+        spawn(context.entities, id, mob, feet, yaw);
+    }
+}
+
+/// Mobs that died stop moving and fall over for [`DEATH_TICKS`], then they're removed and
+/// leave their drops and experience behind. Ones that wandered too far from the player or out of the loaded
+/// world are removed straight away.
+pub fn remove_mobs(context: &mut TickContext, mobs: &MobRegistry) {
+    let player = player_feet(context.entities);
+    let mut died = Vec::new();
+    let mut dead = Vec::new();
+    let mut gone = Vec::new();
+    for (entity, (mob, position, velocity, health, dying)) in
+        context
+            .entities
+            .query_mut::<(&Mob, &Position, &mut Velocity, &Health, Option<&mut Dying>)>()
+    {
+        let block = position.0.map(|value| value.floor() as i32);
+        let far = player.is_none_or(|player| (position.0 - player).magnitude() > DESPAWN_DISTANCE);
+        if let Some(dying) = dying {
+            dying.0 += 1;
+            if dying.0 >= DEATH_TICKS {
+                dead.push((entity, mob.0, block, position.0));
+            }
+        } else if health.is_dead() {
+            velocity.0.x = 0.0;
+            velocity.0.z = 0.0;
+            died.push(entity);
+        } else if far || context.world.block(block).is_none() {
+            gone.push(entity);
+        }
+    }
+    for entity in died {
+        let _ = context.entities.insert_one(entity, Dying::default());
+    }
+    for entity in gone {
+        let _ = context.entities.despawn(entity);
+    }
+
+    let (seed, tick) = (context.info.seed, context.tick as i64);
+    for (entity, id, block, feet) in dead {
+        let _ = context.entities.despawn(entity);
+        let Some(mob) = mobs.get(id) else {
+            continue;
+        };
+        for (index, drop) in mob.drops.iter().enumerate() {
+            let roll = noise::hash(seed, &[entity.id() as i64, tick, index as i64]);
+            let range = drop.count.end() - drop.count.start() + 1;
+            let count = drop.count.start() + (roll % range as u64) as u32;
+            if count > 0 {
+                let stack = ItemStack::new(drop.item, count);
+                drops::spawn_from_block(context.entities, block, stack, roll);
+            }
+        }
+        let roll = noise::hash(seed, &[entity.id() as i64, tick, -1]);
+        let range = mob.experience.end() - mob.experience.start() + 1;
+        let points = mob.experience.start() + (roll % range as u64) as u32;
+        experience::spawn_orbs(context.entities, feet, points, roll);
+    }
+}
+
+/// Mobs that are `hostile` or not, all of them and the ones within [`AREA_RADIUS`] of
+/// `position`
+fn count_mobs(
+    entities: &hecs::World,
+    mobs: &MobRegistry,
+    hostile: bool,
+    position: Point3<f32>,
+) -> (usize, usize) {
+    let mut query = entities.query::<(&Mob, &Position)>();
+    let same: Vec<_> = query
+        .iter()
+        .filter(|(_, (mob, _))| {
+            mobs.get(mob.0)
+                .is_some_and(|mob| mob.is_hostile() == hostile)
+        })
+        .map(|(_, (_, feet))| feet.0)
+        .collect();
+    let nearby = same
+        .iter()
+        .filter(|feet| (**feet - position).magnitude() <= AREA_RADIUS)
+        .count();
+    (same.len(), nearby)
+}
+
+/// How bright `light` is at `time`, the sky counts for less at night
+fn light_level(light: Light, time: u64) -> u8 {
+    let sky = if super::is_daytime(time) {
+        light.sky
+    } else {
+        light.sky.saturating_sub(NIGHT_SKY_DIMMING)
+    };
+    sky.max(light.block)
+}
+
+fn player_feet(entities: &hecs::World) -> Option<Point3<f32>> {
+    let mut query = entities.query::<&Position>().with::<&Player>();
+    query.iter().next().map(|(_, position)| position.0)
+}
+
+/// The highest block from `top` down to `bottom` in the column at `x` `z` that's solid with
+/// two blocks of air on top
+fn spawn_spot(
+    world: &World,
+    blocks: &BlockRegistry,
+    x: i32,
+    z: i32,
+    top: i32,
+    bottom: i32,
+) -> Option<(Point3<i32>, BlockId)> {
+    (bottom..=top).rev().find_map(|y| {
+        let ground = Point3::new(x, y, z);
+        let id = world.block(ground)?;
+        let solid = blocks.get(id)?.solid;
+        (solid && has_room(world, ground, 2.0)).then_some((ground, id))
+    })
+}
+
+/// Whether there's nothing but air for `height` blocks on top of `ground`
+fn has_room(world: &World, ground: Point3<i32>, height: f32) -> bool {
+    (1..=height.ceil() as i32)
+        .all(|above| world.block(ground + Vector3::unit_y() * above) == Some(BlockId::AIR))
+}
+
+/// Standing in the middle of the top of `ground`
+fn feet_on(ground: Point3<i32>) -> Point3<f32> {
+    Point3::new(
+        ground.x as f32 + 0.5,
+        ground.y as f32 + 1.0,
+        ground.z as f32 + 0.5,
+    )
+}
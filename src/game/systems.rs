@@ -0,0 +1,275 @@
+use cgmath::{InnerSpace, Point3, Vector2};
+
+use super::components::{
+    Collider, Footsteps, GameMode, Hunger, InFluid, Look, Movement, OnGround, Player, Position,
+    PreviousPosition, Sleeping, Velocity, WalkCycle,
+};
+use super::hunger;
+use super::physics::{self, Aabb};
+use super::scheduler::TickContext;
+use super::{GameEvent, TICK_SECONDS};
+use crate::world::block::FluidProperties;
+use crate::world::{BlockId, BlockRegistry};
+
+/// Blocks per second on foot
+const WALK_SPEED: f32 = 4.3;
+const SPRINT_MULTIPLIER: f32 = 1.3;
+const SNEAK_MULTIPLIER: f32 = 0.3;
+/// Blocks per second while flying, sprinting doubles it
+const FLY_SPEED: f32 = 10.9;
+const FLY_SPRINT_MULTIPLIER: f32 = 2.0;
+/// Blocks per second up or down while flying
+const FLY_VERTICAL_SPEED: f32 = 7.5;
+/// Ticks the second press of a double jump has to come within
+const DOUBLE_JUMP_TICKS: u32 = 7;
+/// Blocks per second squared
+const GRAVITY: f32 = 32.0;
+/// Upward speed a jump starts with, just enough to get on top of a block
+pub const JUMP_VELOCITY: f32 = 9.0;
+/// Fastest fall in blocks per second
+const TERMINAL_VELOCITY: f32 = 78.4;
+/// Upward speed while holding jump in a fluid
+pub const SWIM_SPEED: f32 = 3.0;
+/// Gravity in a fluid, along with how much vertical speed it keeps each tick
+const FLUID_GRAVITY: f32 = 4.0;
+const FLUID_DRAG: f32 = 0.8;
+/// Fastest sinking in a fluid, in blocks per second
+const SINK_SPEED: f32 = 2.0;
+/// How much of the way to the new walk amount an entity gets each tick
+const WALK_EASING: f32 = 0.4;
+/// Blocks walked between footstep sounds
+const STEP_LENGTH: f32 = 1.6;
+/// How far under the feet to look for the block being walked on, so slabs and farmland
+/// count as well as full blocks
+const STEP_DEPTH: f32 = 0.2;
+
+/// Runs first so interpolation starts from where entities were before this tick
+pub fn store_previous_positions(context: &mut TickContext) {
+    for (_, (position, previous)) in context
+        .entities
+        .query_mut::<(&Position, &mut PreviousPosition)>()
+    {
+        previous.0 = position.0;
+    }
+}
+
+/// Walking sets the horizontal velocity outright, the vertical one is left to gravity and
+/// jumping unless the player is flying. Jumping twice in quick succession toggles flying in
+/// creative and holding it in a fluid swims up. Jumps make the player hungrier and a starving
+/// one can't sprint.
+pub fn player_control(context: &mut TickContext) {
+    let input = context.input;
+    let blocks = context.blocks;
+    for (_, (look, velocity, on_ground, movement, in_fluid, hunger, mode)) in context
+        .entities
+        .query_mut::<(
+            &Look,
+            &mut Velocity,
+            &OnGround,
+            &mut Movement,
+            Option<&InFluid>,
+            Option<&mut Hunger>,
+            Option<&GameMode>,
+        )>()
+        .with::<&Player>()
+        .without::<&Sleeping>()
+    {
+        let jump_pressed = input.jump && !movement.jump_held;
+        movement.jump_held = input.jump;
+        movement.double_jump_window = movement.double_jump_window.saturating_sub(1);
+        let creative = mode == Some(&GameMode::Creative);
+        if jump_pressed && creative {
+            if movement.double_jump_window > 0 {
+                movement.flying = !movement.flying;
+                movement.double_jump_window = 0;
+            } else {
+                movement.double_jump_window = DOUBLE_JUMP_TICKS;
+            }
+        }
+        // Sneak moves down while flying, so it never slows flight down
+        movement.sneaking = input.sneak && !movement.flying;
+        let fed = hunger
+            .as_deref()
+            .is_none_or(|hunger| hunger.food > hunger::SPRINT_FOOD);
+        movement.sprinting = input.sprint && input.forward > 0.0 && !movement.sneaking && fed;
+
+        let mut direction = look.horizontal_forward() * input.forward + look.right() * input.strafe;
+        // Diagonals aren't any faster
+        if direction.magnitude2() > 1.0 {
+            direction = direction.normalize();
+        }
+        let fluid = swimming_in(blocks, in_fluid, Some(&*movement));
+        let mut speed = match (movement.flying, movement.sprinting, movement.sneaking) {
+            (true, true, _) => FLY_SPEED * FLY_SPRINT_MULTIPLIER,
+            (true, false, _) => FLY_SPEED,
+            (false, true, _) => WALK_SPEED * SPRINT_MULTIPLIER,
+            (false, false, true) => WALK_SPEED * SNEAK_MULTIPLIER,
+            (false, false, false) => WALK_SPEED,
+        };
+        if let Some(fluid) = fluid {
+            speed *= fluid.speed;
+        }
+        velocity.0.x = direction.x * speed;
+        velocity.0.z = direction.z * speed;
+        if movement.flying {
+            let vertical = input.jump as i32 as f32 - input.sneak as i32 as f32;
+            velocity.0.y = vertical * FLY_VERTICAL_SPEED;
+        } else if input.jump && on_ground.0 {
+            velocity.0.y = JUMP_VELOCITY;
+            if let Some(hunger) = hunger.filter(|_| GameMode::vulnerable(mode)) {
+                hunger.exhaustion += if movement.sprinting {
+                    hunger::SPRINT_JUMP_EXHAUSTION
+                } else {
+                    hunger::JUMP_EXHAUSTION
+                };
+            }
+        } else if input.jump && fluid.is_some() {
+            velocity.0.y = velocity.0.y.max(SWIM_SPEED);
+        }
+    }
+}
+
+/// Only pulls on entities that collide, anything else would fall forever, and not on
+/// anything flying. Fluids hold entities up so they sink slowly.
+pub fn apply_gravity(context: &mut TickContext) {
+    let blocks = context.blocks;
+    for (_, (velocity, movement, in_fluid)) in context
+        .entities
+        .query_mut::<(&mut Velocity, Option<&Movement>, Option<&InFluid>)>()
+        .with::<&Collider>()
+    {
+        if movement.is_some_and(|movement| movement.flying) {
+            continue;
+        }
+        if swimming_in(blocks, in_fluid, movement).is_some() {
+            velocity.0.y =
+                (velocity.0.y * FLUID_DRAG - FLUID_GRAVITY * TICK_SECONDS).max(-SINK_SPEED);
+            continue;
+        }
+        velocity.0.y = (velocity.0.y - GRAVITY * TICK_SECONDS).max(-TERMINAL_VELOCITY);
+    }
+}
+
+/// Entities with a [`Collider`] stop at solid blocks and lose their velocity on the axes they
+/// ran into, the rest go straight through. Sneaking keeps them from walking off edges and
+/// landing ends flight.
+pub fn apply_velocity(context: &mut TickContext) {
+    let world = &*context.world;
+    let blocks = context.blocks;
+    for (_, (position, velocity, body, movement, in_fluid)) in context.entities.query_mut::<(
+        &mut Position,
+        &mut Velocity,
+        Option<(&Collider, &mut OnGround)>,
+        Option<&mut Movement>,
+        Option<&mut InFluid>,
+    )>() {
+        let mut motion = velocity.0 * TICK_SECONDS;
+        let Some((collider, on_ground)) = body else {
+            position.0 += motion;
+            continue;
+        };
+        let aabb = Aabb::standing(position.0, collider.half_width, collider.height);
+        let sneaking = movement.as_ref().is_some_and(|movement| movement.sneaking);
+        if sneaking && on_ground.0 {
+            motion = physics::hold_edge(world, blocks, aabb, motion);
+        }
+        let (moved, collisions) = physics::move_and_collide(world, blocks, aabb, motion);
+        position.0 += moved;
+        on_ground.0 = collisions.ground;
+        if let Some(in_fluid) = in_fluid {
+            let moved_aabb = Aabb::standing(position.0, collider.half_width, collider.height);
+            in_fluid.0 = physics::fluid_in(world, blocks, &moved_aabb);
+        }
+        if let Some(movement) = movement {
+            movement.flying &= !collisions.ground;
+        }
+        if collisions.x {
+            velocity.0.x = 0.0;
+        }
+        if collisions.ground || collisions.ceiling {
+            velocity.0.y = 0.0;
+        }
+        if collisions.z {
+            velocity.0.z = 0.0;
+        }
+    }
+}
+
+/// Runs after movement, walking is judged by how far the entity actually got
+pub fn advance_walk_cycles(context: &mut TickContext) {
+    for (_, (position, previous, walk)) in
+        context
+            .entities
+            .query_mut::<(&Position, &PreviousPosition, &mut WalkCycle)>()
+    {
+        let moved = position.0 - previous.0;
+        let distance = Vector2::new(moved.x, moved.z).magnitude();
+        let target = (distance / TICK_SECONDS / WALK_SPEED).min(1.0);
+        walk.previous_distance = walk.distance;
+        walk.previous_amount = walk.amount;
+        walk.distance += distance;
+        walk.amount += (target - walk.amount) * WALK_EASING;
+    }
+}
+
+/// Runs after movement, every [`STEP_LENGTH`] blocks walked on the ground makes a footstep
+/// sound for the block underfoot. Swimming and flying are silent.
+pub fn take_footsteps(context: &mut TickContext) {
+    for (_, (position, previous, on_ground, in_fluid, movement, steps)) in
+        context.entities.query_mut::<(
+            &Position,
+            &PreviousPosition,
+            &OnGround,
+            &InFluid,
+            Option<&Movement>,
+            &mut Footsteps,
+        )>()
+    {
+        let flying = movement.is_some_and(|movement| movement.flying);
+        if !on_ground.0 || in_fluid.0.is_some() || flying {
+            continue;
+        }
+        let moved = position.0 - previous.0;
+        steps.0 += Vector2::new(moved.x, moved.z).magnitude();
+        if steps.0 < STEP_LENGTH {
+            continue;
+        }
+        steps.0 -= STEP_LENGTH;
+        let below = (position.0.y - STEP_DEPTH).floor() as i32;
+        let ground = Point3::new(
+            position.0.x.floor() as i32,
+            below,
+            position.0.z.floor() as i32,
+        );
+        // Standing on the edge of a block with air right underneath
+        let Some(block) = context.world.block(ground).filter(|id| *id != BlockId::AIR) else {
+            continue;
+        };
+        context.events.push(GameEvent::Footstep {
+            position: position.0,
+            block,
+        });
+    }
+}
+
+/// The fluid an entity is swimming in, flying over it doesn't count
+fn swimming_in(
+    blocks: &BlockRegistry,
+    in_fluid: Option<&InFluid>,
+    movement: Option<&Movement>,
+) -> Option<FluidProperties> {
+    if movement.is_some_and(|movement| movement.flying) {
+        return None;
+    }
+    let source = in_fluid?.0?;
+    blocks.get(source)?.fluid.map(|fluid| fluid.properties)
+}
+
+pub fn advance_time(context: &mut TickContext) {
+    context.info.time += 1;
+}
+
+pub fn update_weather(context: &mut TickContext) {
+    let (seed, time) = (context.info.seed, context.info.time);
+    context.info.weather.tick(seed, time);
+}
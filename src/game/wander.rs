@@ -0,0 +1,128 @@
+use cgmath::{Point3, Vector3};
+use std::f32::consts::{PI, TAU};
+
+use super::components::{Collider, Dying, InFluid, Look, OnGround, Position, Velocity, Wander};
+use super::scheduler::TickContext;
+use super::systems::{JUMP_VELOCITY, SWIM_SPEED};
+use crate::world::noise;
+use crate::world::{BlockRegistry, World};
+
+/// Shortest and longest a mob keeps walking one way, and standing around, in ticks
+const WALK_TICKS: [u32; 2] = [20, 80];
+const IDLE_TICKS: [u32; 2] = [40, 200];
+/// Blocks past the front of its collider a mob looks for walls and drops
+const LOOK_AHEAD: f32 = 0.4;
+/// Deepest drop a mob walks off, it stays clear of ones that would hurt
+const MAX_DROP: i32 = 3;
+
+/// What's in the way of walking on
+enum Path {
+    Clear,
+    /// A wall a block high with room on top, it can be jumped
+    Step,
+    /// A wall, a drop, a fluid that hurts or the end of the loaded world
+    Blocked,
+}
+
+/// Runs before gravity: mobs walk a random way for a while, then stand around for a while.
+/// They jump up single blocks, turn away from anything else in the way and keep their heads
+/// above any fluid they end up in.
+pub fn wander(context: &mut TickContext) {
+    let (world, blocks) = (&*context.world, context.blocks);
+    let (seed, tick) = (context.info.seed, context.tick as i64);
+    for (entity, (position, look, velocity, on_ground, collider, in_fluid, wander)) in context
+        .entities
+        .query_mut::<(
+            &Position,
+            &mut Look,
+            &mut Velocity,
+            &OnGround,
+            &Collider,
+            Option<&InFluid>,
+            &mut Wander,
+        )>()
+        .without::<&Dying>()
+    {
+        let random = |salt: i64| noise::hash_unit(seed, &[entity.id() as i64, tick, salt]) as f32;
+        if wander.timer == 0 {
+            wander.walking = !wander.walking;
+            let [min, max] = if wander.walking {
+                WALK_TICKS
+            } else {
+                IDLE_TICKS
+            };
+            wander.timer = min + (random(0) * (max - min) as f32) as u32;
+            if wander.walking {
+                look.yaw = random(1) * TAU;
+            }
+        } else {
+            wander.timer -= 1;
+        }
+        if in_fluid.is_some_and(|in_fluid| in_fluid.0.is_some()) {
+            velocity.0.y = velocity.0.y.max(SWIM_SPEED);
+        }
+        if !wander.walking {
+            velocity.0.x = 0.0;
+            velocity.0.z = 0.0;
+            continue;
+        }
+
+        match path_ahead(world, blocks, position.0, look, collider) {
+            Path::Clear => {}
+            Path::Step if on_ground.0 => velocity.0.y = JUMP_VELOCITY,
+            Path::Step => {}
+            // Anywhere from a quarter turn to the left to one to the right of turning back
+            Path::Blocked => look.yaw = (look.yaw + PI * (0.5 + random(2))).rem_euclid(TAU),
+        }
+        let forward = look.horizontal_forward() * wander.speed;
+        velocity.0.x = forward.x;
+        velocity.0.z = forward.z;
+    }
+}
+
+/// Looks at the column of blocks just in front of an entity walking the way it faces
+fn path_ahead(
+    world: &World,
+    blocks: &BlockRegistry,
+    feet: Point3<f32>,
+    look: &Look,
+    collider: &Collider,
+) -> Path {
+    let ahead = feet + look.horizontal_forward() * (collider.half_width + LOOK_AHEAD);
+    let column = ahead.map(|value| value.floor() as i32);
+    let solid = |height: i32| {
+        world
+            .block(column + Vector3::unit_y() * height)
+            .is_none_or(|id| blocks.get(id).is_some_and(|block| block.solid))
+    };
+    let headroom = collider.height.ceil() as i32;
+    if solid(0) {
+        return if (1..=headroom).any(solid) {
+            Path::Blocked
+        } else {
+            Path::Step
+        };
+    }
+    if (1..headroom).any(solid) {
+        return Path::Blocked;
+    }
+    for depth in 0..=MAX_DROP {
+        let below = world
+            .block(column - Vector3::unit_y() * depth)
+            .and_then(|id| blocks.get(id));
+        let Some(below) = below else {
+            return Path::Blocked;
+        };
+        if let Some(fluid) = below.fluid {
+            return if fluid.properties.damage > 0 {
+                Path::Blocked
+            } else {
+                Path::Clear
+            };
+        }
+        if below.solid {
+            return Path::Clear;
+        }
+    }
+    Path::Blocked
+}
@@ -0,0 +1,149 @@
+use cgmath::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+
+use super::components::{Player, Position};
+use super::scheduler::TickContext;
+use crate::world::biome::Precipitation;
+use crate::world::generator::TerrainGenerator;
+use crate::world::{noise, BlockId};
+
+/// Shortest and longest stretch of each kind of weather, in ticks
+const CLEAR_TICKS: (u64, u64) = (12000, 168000);
+const RAIN_TICKS: (u64, u64) = (12000, 24000);
+/// Ticks for rain to fade fully in or out
+const FADE_TICKS: f32 = 200.0;
+/// Ticks between rounds of snow and puddle changes
+pub const SURFACE_INTERVAL: u64 = 10;
+/// Columns around the player tried in each round
+const SURFACE_TRIES: u32 = 8;
+/// Furthest column from the player that gets snow or puddles, in blocks
+const SURFACE_RADIUS: i32 = 48;
+/// How far above and below the player the surface is looked for
+const SURFACE_SEARCH: i32 = 48;
+/// Keeps the weather's random numbers apart from anything else hashed from the seed
+const WEATHER_PURPOSE: i64 = 0x7765_6174;
+
+/// Rain or clear skies for the whole world, what actually falls depends on the biome. Saved
+/// with the world info.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Weather {
+    pub raining: bool,
+    /// Ticks until it starts or stops raining
+    pub remaining: u64,
+    /// How heavy the rain is while it fades in and out, from 0 to 1
+    pub strength: f32,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Weather {
+            raining: false,
+            remaining: CLEAR_TICKS.0,
+            strength: 0.0,
+        }
+    }
+}
+
+impl Weather {
+    /// Count down to the next change and fade toward the current weather. `time` only picks
+    /// how long the next stretch lasts.
+    pub fn tick(&mut self, seed: u64, time: u64) {
+        if self.remaining == 0 {
+            let (shortest, longest) = if self.raining {
+                CLEAR_TICKS
+            } else {
+                RAIN_TICKS
+            };
+            let random = noise::hash(seed, &[time as i64, WEATHER_PURPOSE]);
+            self.raining = !self.raining;
+            self.remaining = shortest + random % (longest - shortest + 1);
+        } else {
+            self.remaining -= 1;
+        }
+        let target = if self.raining { 1.0 } else { 0.0 };
+        let step = 1.0 / FADE_TICKS;
+        self.strength = if self.strength < target {
+            (self.strength + step).min(target)
+        } else {
+            (self.strength - step).max(target)
+        };
+    }
+
+    /// Switch to rain or clear skies for `duration` ticks, fading like a natural change
+    pub fn set(&mut self, raining: bool, duration: u64) {
+        self.raining = raining;
+        self.remaining = duration;
+    }
+}
+
+/// Lay snow on the ground and fill puddles on grass around the player while it rains, and
+/// dry the puddles up again once it stops. Only open sky gets either.
+pub fn change_surfaces(context: &mut TickContext, generator: &TerrainGenerator) {
+    let weather = context.info.weather;
+    let Some((_, feet)) = context
+        .entities
+        .query_mut::<&Position>()
+        .with::<&Player>()
+        .into_iter()
+        .next()
+    else {
+        return;
+    };
+    let blocks = context.blocks;
+    let (Some(grass), Some(snow), Some(puddle)) = (
+        blocks.id("grass_block"),
+        blocks.id("snow"),
+        blocks.id("puddle"),
+    ) else {
+        return;
+    };
+
+    let (seed, tick) = (context.info.seed, context.tick);
+    let random = |attempt: u32, axis: i64| {
+        let values = [tick as i64, attempt as i64, axis, WEATHER_PURPOSE];
+        (noise::hash(seed, &values) % (2 * SURFACE_RADIUS as u64 + 1)) as i32 - SURFACE_RADIUS
+    };
+    let feet = feet.0.map(|value| value.floor() as i32);
+    for attempt in 0..SURFACE_TRIES {
+        let (x, z) = (feet.x + random(attempt, 0), feet.z + random(attempt, 1));
+        let Some(surface) = exposed_surface(context, Point3::new(x, feet.y, z)) else {
+            continue;
+        };
+        let Some(block) = context.world.block(surface) else {
+            continue;
+        };
+        if !weather.raining {
+            if block == puddle {
+                context.world.set_block(surface, grass);
+            }
+            continue;
+        }
+        if weather.strength < 1.0 {
+            continue;
+        }
+        match generator.biome(x, z).precipitation() {
+            Precipitation::Rain if block == grass => {
+                context.world.set_block(surface, puddle);
+            }
+            Precipitation::Snow => {
+                let solid = blocks.get(block).is_some_and(|block| block.solid);
+                if solid && block != snow && block != puddle {
+                    context.world.set_block(surface + Vector3::unit_y(), snow);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The highest block in the column near `around` with nothing but air above it, as far as
+/// the loaded chunks tell
+fn exposed_surface(context: &TickContext, around: Point3<i32>) -> Option<Point3<i32>> {
+    let top = around.y + SURFACE_SEARCH;
+    (around.y - SURFACE_SEARCH..=top)
+        .rev()
+        .map(|y| Point3::new(around.x, y, around.z))
+        .find(|position| context.world.block(*position) != Some(BlockId::AIR))
+        .filter(|position| position.y < top && context.world.block(*position).is_some())
+}
@@ -0,0 +1,81 @@
+use anyhow::Context;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::sync::{PoisonError, RwLock};
+
+use crate::assets::Assets;
+
+pub const DEFAULT_LANGUAGE: &str = "en_us";
+/// Maps language codes to their names, each code has a `lang/<code>.json` next to it
+const LANGUAGES_PATH: &str = "lang/languages.json";
+
+/// Shared by every thread, background workers post translated messages too
+static TRANSLATIONS: RwLock<Option<Translations>> = RwLock::new(None);
+
+struct Translations {
+    strings: HashMap<String, String>,
+    /// The default language, for keys the selected one hasn't translated yet
+    fallback: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Language {
+    pub code: String,
+    /// In the language itself, so it can be found without reading the current one
+    pub name: String,
+}
+
+/// Every language the resource packs provide, sorted by code
+pub fn languages(assets: &Assets) -> anyhow::Result<Vec<Language>> {
+    let bytes = assets.read(LANGUAGES_PATH)?;
+    let names: BTreeMap<String, String> = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Failed to parse {LANGUAGES_PATH}"))?;
+    Ok(names
+        .into_iter()
+        .map(|(code, name)| Language { code, name })
+        .collect())
+}
+
+/// Switch all text over to `language`, takes effect from the next lookup on. On failure the
+/// previous language stays.
+pub fn set_language(assets: &Assets, language: &str) -> anyhow::Result<()> {
+    let fallback = read_strings(assets, DEFAULT_LANGUAGE)?;
+    let strings = if language == DEFAULT_LANGUAGE {
+        HashMap::new()
+    } else {
+        read_strings(assets, language)?
+    };
+    *TRANSLATIONS.write().unwrap_or_else(PoisonError::into_inner) =
+        Some(Translations { strings, fallback });
+    Ok(())
+}
+
+/// Text for `key` in the current language, the key itself if no language has it
+pub fn tr(key: &str) -> String {
+    let translations = TRANSLATIONS.read().unwrap_or_else(PoisonError::into_inner);
+    translations
+        .as_ref()
+        .and_then(|translations| {
+            translations
+                .strings
+                .get(key)
+                .or_else(|| translations.fallback.get(key))
+        })
+        .cloned()
+        .unwrap_or_else(|| key.to_owned())
+}
+
+/// [`tr`] with every `{name}` in the text replaced by its argument
+pub fn tr_args(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = tr(key);
+    for (name, value) in args {
+        text = text.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    text
+}
+
+fn read_strings(assets: &Assets, language: &str) -> anyhow::Result<HashMap<String, String>> {
+    let path = format!("lang/{language}.json");
+    let bytes = assets.read(&path)?;
+    serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {path}"))
+}
@@ -0,0 +1,272 @@
+use glfw::{Action, Key, MouseButton};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::i18n;
+
+/// Mouse speed in counts per second at which acceleration 1 doubles the turn
+const ACCELERATION_SPEED: f32 = 2000.0;
+const MAX_ACCELERATION_GAIN: f32 = 4.0;
+/// Select the hotbar slots in order, these aren't rebindable
+pub const HOTBAR_KEYS: [Key; 9] = [
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
+
+/// Everything the player can trigger from the keyboard or mouse, bound through [`KeyBindings`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Sneak,
+    Sprint,
+    BreakBlock,
+    PlaceBlock,
+    PickBlock,
+    OpenInventory,
+    OpenConsole,
+    Screenshot,
+    ToggleDebug,
+    ToggleDebugUi,
+    ToggleProfiler,
+    ToggleWireframe,
+    ToggleChunkBorders,
+    CycleDebugShading,
+    Fullscreen,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 20] = [
+        InputAction::MoveForward,
+        InputAction::MoveBack,
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::Jump,
+        InputAction::Sneak,
+        InputAction::Sprint,
+        InputAction::BreakBlock,
+        InputAction::PlaceBlock,
+        InputAction::PickBlock,
+        InputAction::OpenInventory,
+        InputAction::OpenConsole,
+        InputAction::Screenshot,
+        InputAction::ToggleDebug,
+        InputAction::ToggleDebugUi,
+        InputAction::ToggleProfiler,
+        InputAction::ToggleWireframe,
+        InputAction::ToggleChunkBorders,
+        InputAction::CycleDebugShading,
+        InputAction::Fullscreen,
+    ];
+
+    /// Name in the current language
+    pub fn label(self) -> String {
+        i18n::tr(match self {
+            Self::MoveForward => "key.forward",
+            Self::MoveBack => "key.back",
+            Self::MoveLeft => "key.left",
+            Self::MoveRight => "key.right",
+            Self::Jump => "key.jump",
+            Self::Sneak => "key.sneak",
+            Self::Sprint => "key.sprint",
+            Self::BreakBlock => "key.break",
+            Self::PlaceBlock => "key.place",
+            Self::PickBlock => "key.pick",
+            Self::OpenInventory => "key.inventory",
+            Self::OpenConsole => "key.console",
+            Self::Screenshot => "key.screenshot",
+            Self::ToggleDebug => "key.debug",
+            Self::ToggleDebugUi => "key.debug_ui",
+            Self::ToggleProfiler => "key.profiler",
+            Self::ToggleWireframe => "key.wireframe",
+            Self::ToggleChunkBorders => "key.chunk_borders",
+            Self::CycleDebugShading => "key.debug_shading",
+            Self::Fullscreen => "key.fullscreen",
+        })
+    }
+
+    pub fn default_binding(self) -> Binding {
+        match self {
+            Self::MoveForward => Binding::Key(Key::W),
+            Self::MoveBack => Binding::Key(Key::S),
+            Self::MoveLeft => Binding::Key(Key::A),
+            Self::MoveRight => Binding::Key(Key::D),
+            Self::Jump => Binding::Key(Key::Space),
+            Self::Sneak => Binding::Key(Key::LeftShift),
+            Self::Sprint => Binding::Key(Key::LeftControl),
+            Self::BreakBlock => Binding::Mouse(MouseButton::Button1),
+            Self::PlaceBlock => Binding::Mouse(MouseButton::Button2),
+            Self::PickBlock => Binding::Mouse(MouseButton::Button3),
+            Self::OpenInventory => Binding::Key(Key::E),
+            Self::OpenConsole => Binding::Key(Key::GraveAccent),
+            Self::Screenshot => Binding::Key(Key::F2),
+            Self::ToggleDebug => Binding::Key(Key::F3),
+            Self::ToggleDebugUi => Binding::Key(Key::F4),
+            Self::ToggleProfiler => Binding::Key(Key::F6),
+            Self::ToggleWireframe => Binding::Key(Key::F7),
+            Self::ToggleChunkBorders => Binding::Key(Key::F8),
+            Self::CycleDebugShading => Binding::Key(Key::F9),
+            Self::Fullscreen => Binding::Key(Key::F11),
+        }
+    }
+}
+
+/// A physical key or mouse button, stored by name in the config file
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+}
+
+#[rustfmt::skip]
+const KEY_NAMES: &[(Key, &str)] = &[
+    (Key::A, "A"), (Key::B, "B"), (Key::C, "C"), (Key::D, "D"), (Key::E, "E"), (Key::F, "F"),
+    (Key::G, "G"), (Key::H, "H"), (Key::I, "I"), (Key::J, "J"), (Key::K, "K"), (Key::L, "L"),
+    (Key::M, "M"), (Key::N, "N"), (Key::O, "O"), (Key::P, "P"), (Key::Q, "Q"), (Key::R, "R"),
+    (Key::S, "S"), (Key::T, "T"), (Key::U, "U"), (Key::V, "V"), (Key::W, "W"), (Key::X, "X"),
+    (Key::Y, "Y"), (Key::Z, "Z"),
+    (Key::Num0, "0"), (Key::Num1, "1"), (Key::Num2, "2"), (Key::Num3, "3"), (Key::Num4, "4"),
+    (Key::Num5, "5"), (Key::Num6, "6"), (Key::Num7, "7"), (Key::Num8, "8"), (Key::Num9, "9"),
+    (Key::F1, "F1"), (Key::F2, "F2"), (Key::F3, "F3"), (Key::F4, "F4"), (Key::F5, "F5"),
+    (Key::F6, "F6"), (Key::F7, "F7"), (Key::F8, "F8"), (Key::F9, "F9"), (Key::F10, "F10"),
+    (Key::F11, "F11"), (Key::F12, "F12"),
+    (Key::Space, "Space"), (Key::Tab, "Tab"), (Key::Enter, "Enter"),
+    (Key::Backspace, "Backspace"), (Key::GraveAccent, "`"), (Key::Minus, "-"),
+    (Key::Equal, "="), (Key::LeftBracket, "["), (Key::RightBracket, "]"),
+    (Key::Backslash, "\\"), (Key::Semicolon, ";"), (Key::Apostrophe, "'"), (Key::Comma, ","),
+    (Key::Period, "."), (Key::Slash, "/"),
+    (Key::LeftShift, "Left Shift"), (Key::RightShift, "Right Shift"),
+    (Key::LeftControl, "Left Control"), (Key::RightControl, "Right Control"),
+    (Key::LeftAlt, "Left Alt"), (Key::RightAlt, "Right Alt"), (Key::CapsLock, "Caps Lock"),
+    (Key::Up, "Up"), (Key::Down, "Down"), (Key::Left, "Left"), (Key::Right, "Right"),
+    (Key::Insert, "Insert"), (Key::Delete, "Delete"), (Key::Home, "Home"), (Key::End, "End"),
+    (Key::PageUp, "Page Up"), (Key::PageDown, "Page Down"),
+    (Key::Kp0, "Keypad 0"), (Key::Kp1, "Keypad 1"), (Key::Kp2, "Keypad 2"),
+    (Key::Kp3, "Keypad 3"), (Key::Kp4, "Keypad 4"), (Key::Kp5, "Keypad 5"),
+    (Key::Kp6, "Keypad 6"), (Key::Kp7, "Keypad 7"), (Key::Kp8, "Keypad 8"),
+    (Key::Kp9, "Keypad 9"),
+];
+
+const MOUSE_NAMES: &[(MouseButton, &str)] = &[
+    (MouseButton::Button1, "Left Mouse"),
+    (MouseButton::Button2, "Right Mouse"),
+    (MouseButton::Button3, "Middle Mouse"),
+    (MouseButton::Button4, "Mouse 4"),
+    (MouseButton::Button5, "Mouse 5"),
+    (MouseButton::Button6, "Mouse 6"),
+    (MouseButton::Button7, "Mouse 7"),
+    (MouseButton::Button8, "Mouse 8"),
+];
+
+impl Binding {
+    /// Keys without a name can't be saved, so they can't be bound either
+    pub fn is_nameable(self) -> bool {
+        self.name().is_some()
+    }
+
+    fn name(self) -> Option<&'static str> {
+        match self {
+            Binding::Key(key) => KEY_NAMES
+                .iter()
+                .find(|(candidate, _)| *candidate == key)
+                .map(|(_, name)| *name),
+            Binding::Mouse(button) => MOUSE_NAMES
+                .iter()
+                .find(|(candidate, _)| *candidate == button)
+                .map(|(_, name)| *name),
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name().unwrap_or("?"))
+    }
+}
+
+impl From<Binding> for String {
+    fn from(binding: Binding) -> Self {
+        binding.to_string()
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        KEY_NAMES
+            .iter()
+            .find(|(_, candidate)| *candidate == name)
+            .map(|(key, _)| Binding::Key(*key))
+            .or_else(|| {
+                MOUSE_NAMES
+                    .iter()
+                    .find(|(_, candidate)| *candidate == name)
+                    .map(|(button, _)| Binding::Mouse(*button))
+            })
+            .ok_or_else(|| format!("unknown key {name:?}"))
+    }
+}
+
+/// Action to binding map, actions missing from the config file keep their default
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeyBindings(BTreeMap<InputAction, Binding>);
+
+impl KeyBindings {
+    pub fn get(&self, action: InputAction) -> Binding {
+        self.0
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_binding())
+    }
+
+    pub fn set(&mut self, action: InputAction, binding: Binding) {
+        if binding == action.default_binding() {
+            self.0.remove(&action);
+        } else {
+            self.0.insert(action, binding);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.0.clear();
+    }
+
+    /// Several actions may share a key, they all fire
+    pub fn actions_for(&self, binding: Binding) -> impl Iterator<Item = InputAction> + '_ {
+        InputAction::ALL
+            .into_iter()
+            .filter(move |action| self.get(*action) == binding)
+    }
+
+    /// Whether the binding for `action` is held right now
+    pub fn is_down(&self, window: &glfw::Window, action: InputAction) -> bool {
+        match self.get(action) {
+            Binding::Key(key) => window.get_key(key) != Action::Release,
+            Binding::Mouse(button) => window.get_mouse_button(button) != Action::Release,
+        }
+    }
+}
+
+/// How much further `distance` counts of mouse motion over `seconds` should turn than linear
+/// mouse look. Slow motion stays close to 1 so lining up with a block face is repeatable.
+pub fn acceleration_gain(distance: f32, seconds: f32, acceleration: f32) -> f32 {
+    if acceleration <= 0.0 || seconds <= 0.0 {
+        return 1.0;
+    }
+    let speed = distance / seconds;
+    (1.0 + acceleration * speed / ACCELERATION_SPEED).min(MAX_ACCELERATION_GAIN)
+}
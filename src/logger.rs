@@ -0,0 +1,61 @@
+use log::{LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::sync::{Mutex, TryLockError};
+
+/// Lines kept in memory for crash reports
+const RECENT_LINES: usize = 200;
+
+static LOGGER: Logger = Logger {
+    recent: Mutex::new(VecDeque::new()),
+};
+
+/// Writes to stderr and remembers the last few lines
+struct Logger {
+    recent: Mutex<VecDeque<String>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "[{} {} {}] {}",
+            chrono::Local::now().format("%H:%M:%S"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        eprintln!("{line}");
+
+        let mut recent = self.recent.lock().unwrap_or_else(|err| err.into_inner());
+        if recent.len() == RECENT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+
+    fn flush(&self) {}
+}
+
+/// Route the `log` macros here, only the first call does anything
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
+
+/// Oldest first. None if the thread asking is in the middle of logging, which happens when
+/// formatting a log line panics.
+pub fn recent_lines() -> Option<Vec<String>> {
+    let recent = match LOGGER.recent.try_lock() {
+        Ok(recent) => recent,
+        Err(TryLockError::Poisoned(err)) => err.into_inner(),
+        Err(TryLockError::WouldBlock) => return None,
+    };
+    Some(recent.iter().cloned().collect())
+}
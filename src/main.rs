@@ -1,5 +1,44 @@
-use gl_lib;
+mod app;
+mod assets;
+mod audio;
+mod camera;
+mod console;
+mod crash;
+mod debug_overlay;
+#[cfg(feature = "debug-ui")]
+mod debug_ui;
+mod display;
+mod frame_limiter;
+mod game;
+mod i18n;
+mod input;
+mod logger;
+mod messages;
+mod profiler;
+mod render;
+mod save;
+mod screenshot;
+mod settings;
+mod state;
+mod ui;
+mod world;
 
-fn main() {
-    println!("Hello, world!");
+use anyhow::Context;
+use std::panic::{self, AssertUnwindSafe};
+
+use app::App;
+
+fn main() -> anyhow::Result<()> {
+    logger::init();
+    crash::install();
+    let mut app = App::new().context("Failed to start RustCraft")?;
+    // The crash report is already written by the hook, all that's left is saving what can
+    // still be saved
+    match panic::catch_unwind(AssertUnwindSafe(|| app.run())) {
+        Ok(result) => result,
+        Err(payload) => {
+            app.emergency_save();
+            panic::resume_unwind(payload)
+        }
+    }
 }
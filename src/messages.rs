@@ -0,0 +1,69 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+use crate::render::text::TextRenderer;
+
+const LIFETIME: Duration = Duration::from_secs(5);
+const FADE: Duration = Duration::from_secs(1);
+const MAX_MESSAGES: usize = 10;
+const MARGIN: f32 = 4.0;
+
+/// Short-lived lines of text in the bottom left corner, background threads can post through
+/// a [`Sender`] from [`Messages::sender`]
+pub struct Messages {
+    entries: VecDeque<(String, Instant)>,
+    sender: Sender<String>,
+    receiver: Receiver<String>,
+}
+
+impl Messages {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Messages {
+            entries: VecDeque::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn push(&mut self, message: impl Into<String>) {
+        self.entries.push_back((message.into(), Instant::now()));
+        while self.entries.len() > MAX_MESSAGES {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn sender(&self) -> Sender<String> {
+        self.sender.clone()
+    }
+
+    pub fn draw(&mut self, text: &mut TextRenderer, screen_size: [f32; 2]) {
+        while let Ok(message) = self.receiver.try_recv() {
+            self.push(message);
+        }
+        self.entries
+            .retain(|(_, posted)| posted.elapsed() < LIFETIME);
+
+        let line_height = text.line_height();
+        let bottom = screen_size[1] - MARGIN;
+        for (index, (message, posted)) in self.entries.iter().rev().enumerate() {
+            let remaining = LIFETIME.saturating_sub(posted.elapsed());
+            let alpha = (remaining.as_secs_f32() / FADE.as_secs_f32()).min(1.0);
+            let y = bottom - (index + 1) as f32 * line_height;
+            let [width, _] = text.measure(message);
+            text.queue_rect(
+                [MARGIN - 2.0, y],
+                [width + 4.0, line_height],
+                [0.0, 0.0, 0.0, 0.5 * alpha],
+            );
+            text.queue(message, [MARGIN, y], [1.0, 1.0, 1.0, alpha]);
+        }
+    }
+}
+
+impl Default for Messages {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,293 @@
+use anyhow::{bail, Context};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::render::text::{Color, TextRenderer, WHITE};
+
+pub const PROFILE_DIR: &str = "profiles";
+/// Frames kept for the graph and the averages
+const HISTORY: usize = 240;
+/// Frame time at the top of the graph, in milliseconds
+const GRAPH_MAX_MS: f32 = 33.3;
+const GRAPH_HEIGHT: f32 = 100.0;
+const BAR_WIDTH: f32 = 2.0;
+const MARGIN: f32 = 4.0;
+const BACKGROUND: Color = [0.0, 0.0, 0.0, 0.6];
+const BUDGET_COLOR: Color = [0.2, 0.8, 0.2, 0.8];
+/// Frame time not covered by any scope
+const UNTRACKED_COLOR: Color = [0.5, 0.5, 0.5, 0.8];
+/// Recording stops by itself after this many frames so a forgotten trace can't eat all memory
+const MAX_TRACE_FRAMES: usize = 3600;
+
+thread_local! {
+    static PROFILER: RefCell<Profiler> = RefCell::new(Profiler::new());
+}
+
+/// One timed scope within a frame, times are relative to the frame's start
+#[derive(Debug, Clone)]
+struct Span {
+    name: &'static str,
+    depth: usize,
+    start: Duration,
+    duration: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct Frame {
+    start: Instant,
+    duration: Duration,
+    spans: Vec<Span>,
+}
+
+/// Event in Chrome's trace format, the "X" phase is a complete span with a duration
+#[derive(Serialize)]
+struct TraceEvent {
+    name: &'static str,
+    ph: &'static str,
+    /// Microseconds
+    ts: f64,
+    dur: f64,
+    pid: u32,
+    tid: u32,
+}
+
+#[derive(Serialize)]
+struct Trace {
+    #[serde(rename = "traceEvents")]
+    trace_events: Vec<TraceEvent>,
+}
+
+/// Hierarchical CPU timer for the main thread. Frames are split into named scopes that may
+/// nest, the last few seconds are kept for the on-screen graph and a longer run can be
+/// recorded for chrome://tracing.
+struct Profiler {
+    visible: bool,
+    current: Frame,
+    /// Indices into `current.spans` of the scopes still running, innermost last
+    open: Vec<usize>,
+    history: VecDeque<Frame>,
+    recording: Option<(Instant, Vec<Frame>)>,
+}
+
+/// Ends its scope when dropped
+#[must_use = "the scope ends as soon as this is dropped"]
+pub struct Scope {
+    index: usize,
+}
+
+impl Profiler {
+    fn new() -> Self {
+        Profiler {
+            visible: false,
+            current: Frame {
+                start: Instant::now(),
+                duration: Duration::ZERO,
+                spans: Vec::new(),
+            },
+            open: Vec::new(),
+            history: VecDeque::with_capacity(HISTORY),
+            recording: None,
+        }
+    }
+
+    fn begin_frame(&mut self) {
+        let now = Instant::now();
+        // Anything still open is cut off where the frame ends
+        for index in self.open.drain(..) {
+            let span = &mut self.current.spans[index];
+            span.duration = now - self.current.start - span.start;
+        }
+        let mut frame = std::mem::replace(
+            &mut self.current,
+            Frame {
+                start: now,
+                duration: Duration::ZERO,
+                spans: Vec::new(),
+            },
+        );
+        frame.duration = now - frame.start;
+
+        if let Some((_, frames)) = &mut self.recording {
+            if frames.len() < MAX_TRACE_FRAMES {
+                frames.push(frame.clone());
+            }
+        }
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(frame);
+    }
+
+    fn begin(&mut self, name: &'static str) -> usize {
+        let index = self.current.spans.len();
+        self.current.spans.push(Span {
+            name,
+            depth: self.open.len(),
+            start: self.current.start.elapsed(),
+            duration: Duration::ZERO,
+        });
+        self.open.push(index);
+        index
+    }
+
+    fn end(&mut self, index: usize) {
+        // A scope from before the last begin_frame has nothing left to end
+        let Some(position) = self.open.iter().rposition(|open| *open == index) else {
+            return;
+        };
+        let now = self.current.start.elapsed();
+        for index in self.open.drain(position..) {
+            let span = &mut self.current.spans[index];
+            span.duration = now - span.start;
+        }
+    }
+
+    /// Average milliseconds per frame of every scope, in the order they first ran
+    fn averages(&self) -> Vec<(&'static str, usize, f32)> {
+        let mut totals: Vec<(&'static str, usize, Duration)> = Vec::new();
+        for span in self.history.iter().flat_map(|frame| &frame.spans) {
+            match totals
+                .iter_mut()
+                .find(|(name, depth, _)| *name == span.name && *depth == span.depth)
+            {
+                Some((_, _, total)) => *total += span.duration,
+                None => totals.push((span.name, span.depth, span.duration)),
+            }
+        }
+        let frames = self.history.len().max(1) as f32;
+        totals
+            .into_iter()
+            .map(|(name, depth, total)| (name, depth, total.as_secs_f32() * 1000.0 / frames))
+            .collect()
+    }
+
+    fn draw(&self, text: &mut TextRenderer, screen_size: [f32; 2]) {
+        let graph_width = HISTORY as f32 * BAR_WIDTH;
+        let left = screen_size[0] - graph_width - MARGIN;
+        let bottom = screen_size[1] - MARGIN;
+        let top = bottom - GRAPH_HEIGHT;
+        text.queue_rect([left, top], [graph_width, GRAPH_HEIGHT], BACKGROUND);
+
+        let pixels_per_ms = GRAPH_HEIGHT / GRAPH_MAX_MS;
+        for (column, frame) in self.history.iter().enumerate() {
+            let x = left + column as f32 * BAR_WIDTH;
+            // The whole frame goes behind its scopes, what sticks out wasn't measured
+            let total = (frame.duration.as_secs_f32() * 1000.0 * pixels_per_ms).min(GRAPH_HEIGHT);
+            text.queue_rect([x, bottom - total], [BAR_WIDTH, total], UNTRACKED_COLOR);
+            let mut y = bottom;
+            for span in frame.spans.iter().filter(|span| span.depth == 0) {
+                let height = span.duration.as_secs_f32() * 1000.0 * pixels_per_ms;
+                let height = height.min(y - top);
+                y -= height;
+                text.queue_rect([x, y], [BAR_WIDTH, height], color_for(span.name));
+            }
+        }
+        let budget = bottom - 1000.0 / 60.0 * pixels_per_ms;
+        text.queue_rect([left, budget], [graph_width, 1.0], BUDGET_COLOR);
+
+        let averages = self.averages();
+        let line_height = text.line_height();
+        let mut y = top - MARGIN - averages.len() as f32 * line_height;
+        for (name, depth, ms) in averages {
+            let x = left + depth as f32 * 2.0 * MARGIN;
+            if depth == 0 {
+                text.queue_rect([x, y + 2.0], [line_height - 4.0; 2], color_for(name));
+            }
+            let label = format!("{name}: {ms:.2} ms");
+            text.queue(&label, [x + line_height, y], WHITE);
+            y += line_height;
+        }
+    }
+}
+
+/// Start a new frame, scopes still open are closed at this point
+pub fn begin_frame() {
+    PROFILER.with_borrow_mut(Profiler::begin_frame);
+}
+
+/// Time everything until the returned guard is dropped, scopes opened meanwhile nest under it
+pub fn scope(name: &'static str) -> Scope {
+    Scope {
+        index: PROFILER.with_borrow_mut(|profiler| profiler.begin(name)),
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        PROFILER.with_borrow_mut(|profiler| profiler.end(self.index));
+    }
+}
+
+pub fn toggle() {
+    PROFILER.with_borrow_mut(|profiler| profiler.visible = !profiler.visible);
+}
+
+/// Queue the graph and per scope averages into `text` if the profiler is shown
+pub fn draw(text: &mut TextRenderer, screen_size: [f32; 2]) {
+    PROFILER.with_borrow(|profiler| {
+        if profiler.visible {
+            profiler.draw(text, screen_size);
+        }
+    });
+}
+
+pub fn start_recording() -> anyhow::Result<()> {
+    PROFILER.with_borrow_mut(|profiler| {
+        if profiler.recording.is_some() {
+            bail!("Already recording");
+        }
+        profiler.recording = Some((Instant::now(), Vec::new()));
+        Ok(())
+    })
+}
+
+/// Write everything since [`start_recording`] as a Chrome trace, returns the file written
+pub fn stop_recording() -> anyhow::Result<PathBuf> {
+    let (start, frames) = PROFILER
+        .with_borrow_mut(|profiler| profiler.recording.take())
+        .context("Not recording")?;
+
+    let micros = |duration: Duration| duration.as_secs_f64() * 1_000_000.0;
+    let mut trace_events = Vec::new();
+    for frame in &frames {
+        let frame_start = frame.start.saturating_duration_since(start);
+        trace_events.push(TraceEvent {
+            name: "frame",
+            ph: "X",
+            ts: micros(frame_start),
+            dur: micros(frame.duration),
+            pid: 1,
+            tid: 1,
+        });
+        trace_events.extend(frame.spans.iter().map(|span| TraceEvent {
+            name: span.name,
+            ph: "X",
+            ts: micros(frame_start + span.start),
+            dur: micros(span.duration),
+            pid: 1,
+            tid: 1,
+        }));
+    }
+
+    let directory = Path::new(PROFILE_DIR);
+    fs::create_dir_all(directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+    let timestamp = chrono::Local::now().format("%Y-%m-%d_%H.%M.%S");
+    let path = directory.join(format!("trace_{timestamp}.json"));
+    let json = serde_json::to_string(&Trace { trace_events })?;
+    fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Stable color per scope name so a scope keeps its color from frame to frame
+fn color_for(name: &str) -> Color {
+    let hash = name.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    });
+    let channel = |shift: u32| 0.35 + ((hash >> shift) & 0xff) as f32 / 255.0 * 0.65;
+    [channel(0), channel(8), channel(16), 0.9]
+}
@@ -0,0 +1,266 @@
+use anyhow::{bail, Context};
+use gl_lib::Texture2DArray;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::world::block::{BlockRegistry, Face};
+
+/// Edge length every block texture must have
+const TEXTURE_SIZE: u32 = 16;
+const TEXTURE_DIR: &str = "textures/block";
+/// Layer 0, shown for textures that are missing or fail to load
+const MISSING_LAYER: u32 = 0;
+
+/// Texture layers of one block, see [`BlockTextures::layers`]
+#[derive(Debug, Clone, Default)]
+pub struct BlockLayers {
+    /// In [`Face::ALL`] order
+    pub faces: [u32; 6],
+    /// One for each quad of the block's model
+    pub model: Vec<u32>,
+}
+
+/// Every texture the registered blocks use, one layer each in a single texture array.
+/// Animated textures are a strip of frames stacked top to bottom, their layer is overwritten
+/// with the current frame as time goes on.
+pub struct BlockTextures {
+    texture: Texture2DArray,
+    /// Indexed by block id
+    layers: Arc<[BlockLayers]>,
+    animations: Vec<Animation>,
+}
+
+/// The frames of an animated texture and which one its layer shows
+struct Animation {
+    layer: u32,
+    frames: Vec<Vec<u8>>,
+    /// Frame index and how many ticks it stays, in playing order
+    sequence: Vec<(usize, u64)>,
+    /// Ticks before the sequence repeats
+    period: u64,
+    shown: usize,
+}
+
+/// A texture as read from its file, see [`load_texture`]
+struct Frames {
+    /// Pixels of every frame top to bottom, textures that aren't animated have a single one
+    pixels: Vec<Vec<u8>>,
+    /// Frame index and how many ticks it stays, in playing order
+    sequence: Vec<(usize, u64)>,
+}
+
+/// `<name>.png.mcmeta` next to an animated texture, the same format resource packs for the
+/// original game use. Without one every frame shows for a tick in order.
+#[derive(Debug, Deserialize)]
+struct AnimationFile {
+    animation: AnimationSection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct AnimationSection {
+    /// Ticks each frame stays unless it says otherwise
+    frametime: u64,
+    /// Frame indices in playing order, all of them top to bottom if left out
+    frames: Option<Vec<FrameEntry>>,
+}
+
+impl Default for AnimationSection {
+    fn default() -> Self {
+        AnimationSection {
+            frametime: 1,
+            frames: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FrameEntry {
+    Index(usize),
+    Timed { index: usize, time: u64 },
+}
+
+impl BlockTextures {
+    pub fn load(assets: &Assets, blocks: &BlockRegistry) -> anyhow::Result<Self> {
+        let mut layers = vec![missing_texture()];
+        let mut layer_of: HashMap<String, u32> = HashMap::new();
+        let mut animations = Vec::new();
+        let mut layer = |name: &str| {
+            if name.is_empty() {
+                return MISSING_LAYER;
+            }
+            if let Some(layer) = layer_of.get(name) {
+                return *layer;
+            }
+            let layer = match load_texture(assets, name) {
+                Ok(Frames {
+                    mut pixels,
+                    sequence,
+                }) => {
+                    let layer = layers.len() as u32;
+                    if pixels.len() == 1 {
+                        layers.extend(pixels.pop());
+                    } else {
+                        layers.push(pixels[sequence[0].0].clone());
+                        let period = sequence.iter().map(|(_, time)| time).sum();
+                        animations.push(Animation {
+                            layer,
+                            shown: sequence[0].0,
+                            frames: pixels,
+                            sequence,
+                            period,
+                        });
+                    }
+                    layer
+                }
+                Err(err) => {
+                    log::warn!("{err:#}");
+                    MISSING_LAYER
+                }
+            };
+            layer_of.insert(name.to_owned(), layer);
+            layer
+        };
+        let block_layers: Vec<BlockLayers> = blocks
+            .iter()
+            .map(|(_, block)| BlockLayers {
+                faces: Face::ALL.map(|face| layer(block.texture(face))),
+                model: block.model.as_ref().map_or(Vec::new(), |model| {
+                    model
+                        .quads
+                        .iter()
+                        .map(|quad| layer(&quad.texture))
+                        .collect()
+                }),
+            })
+            .collect();
+
+        let slices: Vec<&[u8]> = layers.iter().map(Vec::as_slice).collect();
+        let texture = Texture2DArray::from_rgba8_layers(TEXTURE_SIZE, TEXTURE_SIZE, &slices)
+            .context("Failed to upload block textures")?;
+        Ok(BlockTextures {
+            texture,
+            layers: block_layers.into(),
+            animations,
+        })
+    }
+
+    /// Show the frame of every animated texture that's current at `tick`, only frames that
+    /// changed get uploaded
+    pub fn animate(&mut self, tick: u64) {
+        let mut changed = false;
+        for animation in &mut self.animations {
+            let mut remaining = tick % animation.period;
+            let frame = animation
+                .sequence
+                .iter()
+                .find(|(_, time)| {
+                    let current = remaining < *time;
+                    remaining = remaining.saturating_sub(*time);
+                    current
+                })
+                .map_or(animation.shown, |(frame, _)| *frame);
+            if frame == animation.shown {
+                continue;
+            }
+            animation.shown = frame;
+            match self
+                .texture
+                .update_layer(animation.layer, &animation.frames[frame])
+            {
+                Ok(()) => changed = true,
+                Err(err) => log::warn!("Failed to animate a block texture: {err}"),
+            }
+        }
+        if changed {
+            self.texture.generate_mipmaps();
+        }
+    }
+
+    /// Texture layers per block id, for the mesher
+    pub fn layers(&self) -> Arc<[BlockLayers]> {
+        Arc::clone(&self.layers)
+    }
+
+    pub fn bind(&self, unit: u32) {
+        self.texture.bind(unit);
+    }
+}
+
+/// Every frame of the texture and the order to play them in. Textures that aren't animated
+/// have a single frame.
+fn load_texture(assets: &Assets, name: &str) -> anyhow::Result<Frames> {
+    let path = format!("{TEXTURE_DIR}/{name}.png");
+    let bytes = assets.read(&path)?;
+    let image =
+        image::load_from_memory(&bytes).with_context(|| format!("Failed to decode {path}"))?;
+    let (width, height) = (image.width(), image.height());
+    if width != TEXTURE_SIZE || height == 0 || height % TEXTURE_SIZE != 0 {
+        bail!("{path} is not {TEXTURE_SIZE}x{TEXTURE_SIZE} or a strip of frames that size");
+    }
+    // Split before flipping, flipping the whole strip would reverse the frame order too
+    let count = (height / TEXTURE_SIZE) as usize;
+    let pixels: Vec<Vec<u8>> = (0..count as u32)
+        .map(|frame| {
+            image
+                .crop_imm(0, frame * TEXTURE_SIZE, TEXTURE_SIZE, TEXTURE_SIZE)
+                .flipv()
+                .into_rgba8()
+                .into_raw()
+        })
+        .collect();
+    if count == 1 {
+        return Ok(Frames {
+            pixels,
+            sequence: vec![(0, 1)],
+        });
+    }
+
+    let meta_path = format!("{path}.mcmeta");
+    let section = match assets.read(&meta_path) {
+        Ok(bytes) => {
+            serde_json::from_slice::<AnimationFile>(&bytes)
+                .with_context(|| format!("Failed to parse {meta_path}"))?
+                .animation
+        }
+        Err(_) => AnimationSection::default(),
+    };
+    let sequence: Vec<(usize, u64)> = match section.frames {
+        Some(entries) => entries
+            .into_iter()
+            .map(|entry| match entry {
+                FrameEntry::Index(index) => (index, section.frametime),
+                FrameEntry::Timed { index, time } => (index, time),
+            })
+            .collect(),
+        None => (0..count).map(|index| (index, section.frametime)).collect(),
+    };
+    if sequence.is_empty() {
+        bail!("{meta_path}: the animation needs at least one frame");
+    }
+    if let Some((index, _)) = sequence.iter().find(|(index, _)| *index >= count) {
+        bail!("{meta_path}: frame {index} is past the {count} frames in {path}");
+    }
+    if sequence.iter().any(|(_, time)| *time == 0) {
+        bail!("{meta_path}: every frame has to stay at least one tick");
+    }
+    Ok(Frames { pixels, sequence })
+}
+
+/// Magenta and black checkers, hard to mistake for a real texture
+pub fn missing_texture() -> Vec<u8> {
+    let half = TEXTURE_SIZE / 2;
+    (0..TEXTURE_SIZE * TEXTURE_SIZE)
+        .flat_map(|index| {
+            let (x, y) = (index % TEXTURE_SIZE, index / TEXTURE_SIZE);
+            if (x / half + y / half).is_multiple_of(2) {
+                [255, 0, 255, 255]
+            } else {
+                [0, 0, 0, 255]
+            }
+        })
+        .collect()
+}
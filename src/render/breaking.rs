@@ -0,0 +1,153 @@
+use anyhow::{bail, Context};
+use gl_lib::{Attribute, BlendMode, CullMode, Mesh, RenderState, ShaderProgram, Texture2DArray};
+use std::mem;
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::game::mining::Mining;
+use crate::world::block::FULL_BLOCK;
+use crate::world::noise;
+use crate::world::raycast::RayHit;
+
+/// Crack textures from barely scratched to about to break, `destroy_stage_<n>.png`
+const STAGES: u32 = 10;
+const TEXTURE_DIR: &str = "textures/block";
+const TEXTURE_SIZE: u32 = 16;
+/// How far the cracks sit outside the block on every side, less than the outline
+const INFLATE: f32 = 0.001;
+const DEPTH_BIAS: f32 = 0.0001;
+/// Shade of the cracks made up for missing textures, darkening what's behind by half
+const FALLBACK_SHADE: u8 = 64;
+
+const VERTEX_ATTRIBUTES: [Attribute; 2] = [
+    Attribute::float(3, mem::offset_of!(CrackVertex, position)),
+    Attribute::float(2, mem::offset_of!(CrackVertex, uv)),
+];
+
+/// Blended onto the block's faces, so the far ones stay hidden behind the near ones
+const CRACK_STATE: RenderState = RenderState {
+    depth_test: true,
+    depth_write: false,
+    blend: Some(BlendMode::Multiply),
+    cull: CullMode::Back,
+};
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct CrackVertex {
+    position: [f32; 3],
+    uv: [f32; 2],
+}
+
+/// Cracks drawn over the block the player is breaking, spreading as they get further
+pub struct BreakingOverlay {
+    program: Handle<ShaderProgram>,
+    stages: Texture2DArray,
+    mesh: Mesh,
+    /// What the mesh was built around, like [`super::outline::SelectionOutline`]
+    bounds: ([f32; 3], [f32; 3]),
+}
+
+impl BreakingOverlay {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        let program = assets.load("shader/breaking")?;
+        let pixels: Vec<Vec<u8>> = (0..STAGES)
+            .map(|stage| {
+                load_stage(assets, stage).unwrap_or_else(|err| {
+                    log::warn!("{err:#}");
+                    fallback_stage(stage)
+                })
+            })
+            .collect();
+        let slices: Vec<&[u8]> = pixels.iter().map(Vec::as_slice).collect();
+        let stages = Texture2DArray::from_rgba8_layers(TEXTURE_SIZE, TEXTURE_SIZE, &slices)
+            .context("Failed to upload breaking textures")?;
+        let bounds = FULL_BLOCK[0];
+        Ok(BreakingOverlay {
+            program,
+            stages,
+            mesh: box_mesh(bounds),
+            bounds,
+        })
+    }
+
+    /// Only draws anything while `mining` is the block `target` points at
+    pub fn draw(&mut self, assets: &Assets, camera: &Camera, target: &RayHit, mining: Mining) {
+        if mining.block != target.block {
+            return;
+        }
+        let Some(program) = assets.get(self.program) else {
+            return;
+        };
+        if target.bounds != self.bounds {
+            self.bounds = target.bounds;
+            self.mesh = box_mesh(target.bounds);
+        }
+        CRACK_STATE.apply();
+        let view_projection: [[f32; 4]; 4] = camera.view_projection().into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        let block = target.block.map(|value| value as f32);
+        program.set_vec3(c"u_block", block.into());
+        program.set_float(c"u_depth_bias", DEPTH_BIAS);
+        program.set_float(c"u_stage", mining.stage(STAGES) as f32);
+        program.set_int(c"u_stages", 0);
+        self.stages.bind(0);
+        program.bind();
+        self.mesh.draw();
+    }
+}
+
+/// The six faces of a slightly inflated box, wound counterclockwise from outside. Texture
+/// coordinates follow the block grid so a slab shows the part of the cracks it covers.
+fn box_mesh((min, max): ([f32; 3], [f32; 3])) -> Mesh {
+    let inflated_min = min.map(|value| value - INFLATE);
+    let inflated_max = max.map(|value| value + INFLATE);
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for axis in 0..3 {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        for (side, positive) in [(inflated_min[axis], false), (inflated_max[axis], true)] {
+            let start = vertices.len() as u32;
+            for (a, b) in [(0, 0), (1, 0), (1, 1), (0, 1)] {
+                let mut position = [0.0; 3];
+                position[axis] = side;
+                position[u] = [inflated_min[u], inflated_max[u]][a];
+                position[v] = [inflated_min[v], inflated_max[v]][b];
+                let uv = [[min[u], max[u]][a], [min[v], max[v]][b]];
+                vertices.push(CrackVertex { position, uv });
+            }
+            let order = if positive {
+                [0, 1, 2, 0, 2, 3]
+            } else {
+                [0, 2, 1, 0, 3, 2]
+            };
+            indices.extend(order.map(|offset| start + offset));
+        }
+    }
+    Mesh::new(&vertices, &indices, &VERTEX_ATTRIBUTES)
+}
+
+fn load_stage(assets: &Assets, stage: u32) -> anyhow::Result<Vec<u8>> {
+    let path = format!("{TEXTURE_DIR}/destroy_stage_{stage}.png");
+    let bytes = assets.read(&path)?;
+    let image =
+        image::load_from_memory(&bytes).with_context(|| format!("Failed to decode {path}"))?;
+    if image.width() != TEXTURE_SIZE || image.height() != TEXTURE_SIZE {
+        bail!("{path} is not {TEXTURE_SIZE}x{TEXTURE_SIZE}");
+    }
+    Ok(image.flipv().into_rgba8().into_raw())
+}
+
+/// Dark specks covering more of the block at every stage, each stage keeping the ones before
+fn fallback_stage(stage: u32) -> Vec<u8> {
+    let covered = (stage + 1) as f64 / STAGES as f64 * 0.6;
+    let mut pixels = Vec::with_capacity((TEXTURE_SIZE * TEXTURE_SIZE * 4) as usize);
+    for y in 0..TEXTURE_SIZE {
+        for x in 0..TEXTURE_SIZE {
+            let cracked = noise::hash_unit(0, &[x as i64, y as i64]) < covered;
+            let alpha = if cracked { 255 } else { 0 };
+            pixels.extend([FALLBACK_SHADE, FALLBACK_SHADE, FALLBACK_SHADE, alpha]);
+        }
+    }
+    pixels
+}
@@ -0,0 +1,232 @@
+use gl_lib::{Buffer, BufferTarget, BufferUsage, VertexArray};
+use std::collections::BTreeMap;
+use std::mem;
+
+use crate::render::mesher::{BlockVertex, ChunkMesh, BLOCK_VERTEX_ATTRIBUTES};
+use crate::world::chunk::ChunkPos;
+
+/// Vertices in each pool buffer, 16 MiB of them. The densest possible chunk needs about 50k.
+const POOL_VERTICES: u32 = 1 << 20;
+/// Every quad is 4 vertices and 6 indices
+const POOL_INDICES: u32 = POOL_VERTICES / 4 * 6;
+
+/// Where one chunk's mesh lives in the pools
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MeshSlot {
+    pool: usize,
+    first_vertex: u32,
+    vertex_count: u32,
+    first_index: u32,
+    index_count: u32,
+}
+
+/// Chunk meshes packed into a few large buffers instead of one buffer pair each, so drawing
+/// them all binds a vertex array per pool rather than per chunk. Pools are added as the
+/// existing ones fill up.
+pub struct ChunkPool {
+    pools: Vec<Pool>,
+}
+
+impl ChunkPool {
+    pub fn new() -> Self {
+        ChunkPool { pools: Vec::new() }
+    }
+
+    /// Upload a mesh into the first pool with room for it, None for an empty mesh
+    pub fn insert(&mut self, mesh: &ChunkMesh) -> Option<MeshSlot> {
+        if mesh.is_empty() {
+            return None;
+        }
+        let vertex_count = mesh.vertices.len() as u32;
+        let index_count = mesh.indices.len() as u32;
+        if vertex_count > POOL_VERTICES || index_count > POOL_INDICES {
+            log::warn!("Chunk mesh of {vertex_count} vertices doesn't fit a pool");
+            return None;
+        }
+        let existing = self
+            .pools
+            .iter_mut()
+            .enumerate()
+            .find_map(|(index, pool)| Some((index, pool.reserve(vertex_count, index_count)?)));
+        let (pool, (first_vertex, first_index)) = match existing {
+            Some(found) => found,
+            None => {
+                let mut pool = Pool::new();
+                let reserved = pool.reserve(vertex_count, index_count)?;
+                self.pools.push(pool);
+                (self.pools.len() - 1, reserved)
+            }
+        };
+        let buffers = &self.pools[pool];
+        // Binding the element buffer changes whatever vertex array is bound
+        buffers.vertex_array.bind();
+        buffers.vertices.set_sub_data(
+            first_vertex as usize * mem::size_of::<BlockVertex>(),
+            &mesh.vertices,
+        );
+        buffers
+            .indices
+            .set_sub_data(first_index as usize * mem::size_of::<u32>(), &mesh.indices);
+        Some(MeshSlot {
+            pool,
+            first_vertex,
+            vertex_count,
+            first_index,
+            index_count,
+        })
+    }
+
+    /// Give the slot's space back, the data stays until something else is put there
+    pub fn remove(&mut self, slot: MeshSlot) {
+        if let Some(pool) = self.pools.get_mut(slot.pool) {
+            pool.vertex_space.free(slot.first_vertex, slot.vertex_count);
+            pool.index_space.free(slot.first_index, slot.index_count);
+        }
+    }
+
+    /// Drop every pool, for when the world is closed
+    pub fn clear(&mut self) {
+        self.pools.clear();
+    }
+
+    /// Draws every chunk's slot with `before_each` called first, e.g. to set the chunk's
+    /// uniforms. Slots are grouped by pool so each vertex array is bound once.
+    pub fn draw<'a>(
+        &self,
+        slots: impl IntoIterator<Item = (ChunkPos, &'a MeshSlot)>,
+        mut before_each: impl FnMut(ChunkPos),
+    ) {
+        let mut by_pool: Vec<Vec<(ChunkPos, &MeshSlot)>> = vec![Vec::new(); self.pools.len()];
+        for (position, slot) in slots {
+            if let Some(group) = by_pool.get_mut(slot.pool) {
+                group.push((position, slot));
+            }
+        }
+        for (pool, group) in self.pools.iter().zip(by_pool) {
+            if group.is_empty() {
+                continue;
+            }
+            pool.vertex_array.bind();
+            for (position, slot) in group {
+                before_each(position);
+                slot.draw();
+            }
+        }
+    }
+
+    /// Like [`ChunkPool::draw`] but keeping the order of `slots`, for blending where it
+    /// matters. The vertex array is only rebound when the pool changes.
+    pub fn draw_in_order<'a>(
+        &self,
+        slots: impl IntoIterator<Item = (ChunkPos, &'a MeshSlot)>,
+        mut before_each: impl FnMut(ChunkPos),
+    ) {
+        let mut bound = None;
+        for (position, slot) in slots {
+            let Some(pool) = self.pools.get(slot.pool) else {
+                continue;
+            };
+            if bound != Some(slot.pool) {
+                pool.vertex_array.bind();
+                bound = Some(slot.pool);
+            }
+            before_each(position);
+            slot.draw();
+        }
+    }
+}
+
+impl MeshSlot {
+    /// The pool's vertex array has to be bound
+    fn draw(&self) {
+        gl_lib::draw_indexed_triangles_range(
+            self.index_count as usize,
+            self.first_index as usize,
+            self.first_vertex as usize,
+        );
+    }
+}
+
+struct Pool {
+    vertex_array: VertexArray,
+    vertices: Buffer,
+    indices: Buffer,
+    vertex_space: FreeList,
+    index_space: FreeList,
+}
+
+impl Pool {
+    fn new() -> Self {
+        let vertex_array = VertexArray::new();
+        vertex_array.bind();
+        let vertices = Buffer::new(BufferTarget::Array);
+        vertices.allocate(
+            POOL_VERTICES as usize * mem::size_of::<BlockVertex>(),
+            BufferUsage::Dynamic,
+        );
+        // The element buffer binding is part of the vertex array's state
+        let indices = Buffer::new(BufferTarget::ElementArray);
+        indices.allocate(
+            POOL_INDICES as usize * mem::size_of::<u32>(),
+            BufferUsage::Dynamic,
+        );
+        vertex_array.set_attributes(&BLOCK_VERTEX_ATTRIBUTES, mem::size_of::<BlockVertex>());
+        Pool {
+            vertex_array,
+            vertices,
+            indices,
+            vertex_space: FreeList::new(POOL_VERTICES),
+            index_space: FreeList::new(POOL_INDICES),
+        }
+    }
+
+    /// Room for both the vertices and the indices, or neither
+    fn reserve(&mut self, vertex_count: u32, index_count: u32) -> Option<(u32, u32)> {
+        let first_vertex = self.vertex_space.allocate(vertex_count)?;
+        let Some(first_index) = self.index_space.allocate(index_count) else {
+            self.vertex_space.free(first_vertex, vertex_count);
+            return None;
+        };
+        Some((first_vertex, first_index))
+    }
+}
+
+/// First fit allocation of ranges out of `0..size`, freed ranges merge with free neighbours
+struct FreeList {
+    /// Start of each free range to its length
+    free: BTreeMap<u32, u32>,
+}
+
+impl FreeList {
+    fn new(size: u32) -> Self {
+        FreeList {
+            free: BTreeMap::from([(0, size)]),
+        }
+    }
+
+    fn allocate(&mut self, length: u32) -> Option<u32> {
+        let (&start, &available) = self
+            .free
+            .iter()
+            .find(|(_, available)| **available >= length)?;
+        self.free.remove(&start);
+        if available > length {
+            self.free.insert(start + length, available - length);
+        }
+        Some(start)
+    }
+
+    fn free(&mut self, mut start: u32, mut length: u32) {
+        if let Some((&before, &before_length)) = self.free.range(..start).next_back() {
+            if before + before_length == start {
+                self.free.remove(&before);
+                start = before;
+                length += before_length;
+            }
+        }
+        if let Some(after_length) = self.free.remove(&(start + length)) {
+            length += after_length;
+        }
+        self.free.insert(start, length);
+    }
+}
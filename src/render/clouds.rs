@@ -0,0 +1,88 @@
+use gl_lib::{Attribute, BlendMode, CullMode, Mesh, RenderState, ShaderProgram};
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::render::sky::DayCycle;
+use crate::world::chunk::CHUNK_SIZE;
+
+/// Blocks the clouds drift along +X each tick
+const WIND_SPEED: f64 = 0.05;
+/// Width in blocks of one cloud cell, clouds are made of whole cells
+const CELL_SIZE: f32 = 12.0;
+/// Cells before the pattern repeats, has to match the shader
+const PATTERN_CELLS: f64 = 256.0;
+/// Clouds reach this many times further than the terrain, they're high enough to stay in
+/// view past it
+const RANGE_SCALE: f32 = 2.0;
+/// Keeps the far edge of the layer inside the camera's far plane
+const MAX_RANGE: f32 = 768.0;
+
+/// Drawn after the terrain so it hides clouds behind it, without writing depth since the
+/// clouds are see-through. Seen from both above and below.
+const CLOUD_STATE: RenderState = RenderState {
+    depth_test: true,
+    depth_write: false,
+    blend: Some(BlendMode::Alpha),
+    cull: CullMode::None,
+};
+
+/// A flat layer of blocky clouds drifting with the world time
+pub struct CloudRenderer {
+    program: Handle<ShaderProgram>,
+    quad: Mesh,
+    height: f32,
+    /// From 0 for a clear sky to 1 for overcast, nothing is drawn at 0
+    density: f32,
+}
+
+impl CloudRenderer {
+    pub fn new(assets: &mut Assets, height: f32, density: f32) -> anyhow::Result<Self> {
+        let program = assets.load("shader/clouds")?;
+        let corners: [[f32; 2]; 4] = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+        let quad = Mesh::new(&corners, &[0, 1, 2, 2, 3, 0], &[Attribute::float(2, 0)]);
+        Ok(CloudRenderer {
+            program,
+            quad,
+            height,
+            density,
+        })
+    }
+
+    pub fn set_layer(&mut self, height: f32, density: f32) {
+        self.height = height;
+        self.density = density;
+    }
+
+    /// `time` in ticks, fractional like for [`DayCycle::at`]
+    pub fn draw(
+        &self,
+        assets: &Assets,
+        camera: &Camera,
+        cycle: &DayCycle,
+        time: f64,
+        render_distance: u32,
+    ) {
+        if self.density <= 0.0 {
+            return;
+        }
+        let Some(program) = assets.get(self.program) else {
+            return;
+        };
+        CLOUD_STATE.apply();
+        let view_projection: [[f32; 4]; 4] = camera.view_projection().into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        program.set_vec3(c"u_camera_position", camera.position.into());
+        let range = ((render_distance as i32 * CHUNK_SIZE) as f32 * RANGE_SCALE).min(MAX_RANGE);
+        program.set_float(c"u_range", range);
+        program.set_float(c"u_height", self.height);
+        // Wrapped to the pattern so the offset keeps its precision on old worlds
+        let wind = (time * WIND_SPEED).rem_euclid(PATTERN_CELLS * CELL_SIZE as f64);
+        program.set_float(c"u_wind", wind as f32);
+        program.set_float(c"u_cell_size", CELL_SIZE);
+        program.set_float(c"u_density", self.density.min(1.0));
+        program.set_float(c"u_daylight", cycle.daylight);
+        program.set_vec3(c"u_fog_color", cycle.horizon_color);
+        program.bind();
+        self.quad.draw();
+    }
+}
@@ -0,0 +1,133 @@
+use gl_lib::{Attribute, BlendMode, CullMode, Mesh, RenderState, ShaderProgram};
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::world::chunk::{ChunkPos, CHUNK_SIZE};
+
+/// Chunks around the camera's that get a border, in every direction
+const BORDER_RADIUS: i32 = 1;
+const CAMERA_CHUNK_COLOR: [f32; 4] = [1.0, 0.85, 0.1, 1.0];
+const NEIGHBOUR_COLOR: [f32; 4] = [0.2, 0.5, 1.0, 0.7];
+
+/// Lines are hidden behind terrain like everything else, but never hide each other
+const BORDER_STATE: RenderState = RenderState {
+    depth_test: true,
+    depth_write: false,
+    blend: Some(BlendMode::Alpha),
+    cull: CullMode::None,
+};
+
+/// What the terrain shows instead of its finished color, to see one part of the lighting
+/// on its own
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum DebugShading {
+    #[default]
+    Normal,
+    /// Textures and tints without any light
+    Unlit,
+    /// Light on plain white blocks
+    Lighting,
+    /// Face directions as colors
+    Normals,
+}
+
+impl DebugShading {
+    pub const ALL: [DebugShading; 4] = [
+        DebugShading::Normal,
+        DebugShading::Unlit,
+        DebugShading::Lighting,
+        DebugShading::Normals,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DebugShading::Normal => "normal",
+            DebugShading::Unlit => "unlit",
+            DebugShading::Lighting => "lighting",
+            DebugShading::Normals => "normals",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|shading| shading.name() == name)
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|shading| *shading == self);
+        Self::ALL[index.map_or(0, |index| (index + 1) % Self::ALL.len())]
+    }
+
+    /// Matches `u_debug_shading` in lighting.glsl
+    pub fn apply(self, program: &ShaderProgram) {
+        program.set_int(c"u_debug_shading", self as i32);
+    }
+}
+
+/// Debug drawing toggled from the console or with F-keys, none of it is saved
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DebugView {
+    /// Terrain drawn as the edges of its triangles
+    pub wireframe: bool,
+    /// Boxes around the camera's chunk and the ones next to it
+    pub chunk_borders: bool,
+    pub shading: DebugShading,
+}
+
+/// The edges of a chunk, drawn around the camera's and its neighbours with
+/// [`DebugView::chunk_borders`]
+pub struct ChunkBorders {
+    program: Handle<ShaderProgram>,
+    mesh: Mesh,
+}
+
+impl ChunkBorders {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        // Same flat color with an offset as the selection outline
+        let program = assets.load("shader/outline")?;
+        let size = CHUNK_SIZE as f32;
+        let corners: Vec<[f32; 3]> = (0..8)
+            .map(|corner| {
+                [corner & 1, corner >> 1 & 1, corner >> 2 & 1].map(|bit| bit as f32 * size)
+            })
+            .collect();
+        // Corners one bit apart are joined by an edge
+        let indices: Vec<u32> = (0..8u32)
+            .flat_map(|corner| {
+                [1, 2, 4]
+                    .into_iter()
+                    .filter(move |bit| corner & bit == 0)
+                    .flat_map(move |bit| [corner, corner | bit])
+            })
+            .collect();
+        let mesh = Mesh::new(&corners, &indices, &[Attribute::float(3, 0)]);
+        Ok(ChunkBorders { program, mesh })
+    }
+
+    pub fn draw(&self, assets: &Assets, camera: &Camera) {
+        let Some(program) = assets.get(self.program) else {
+            return;
+        };
+        BORDER_STATE.apply();
+        let view_projection: [[f32; 4]; 4] = camera.view_projection().into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        program.set_float(c"u_depth_bias", 0.0);
+        program.bind();
+        let (center, _) = ChunkPos::of_block(camera.position.map(|value| value.floor() as i32));
+        for x in -BORDER_RADIUS..=BORDER_RADIUS {
+            for y in -BORDER_RADIUS..=BORDER_RADIUS {
+                for z in -BORDER_RADIUS..=BORDER_RADIUS {
+                    let position = ChunkPos::new(center.x + x, center.y + y, center.z + z);
+                    let origin = position.origin().map(|value| value as f32);
+                    program.set_vec3(c"u_block", origin.into());
+                    let color = if (x, y, z) == (0, 0, 0) {
+                        CAMERA_CHUNK_COLOR
+                    } else {
+                        NEIGHBOUR_COLOR
+                    };
+                    program.set_vec4(c"u_color", color);
+                    self.mesh.draw_lines();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use anyhow::Context;
+use cgmath::SquareMatrix;
+use gl_lib::{
+    set_viewport, Attribute, CullMode, FramebufferBinding, Mesh, RenderState, ShaderProgram,
+    TextureFormat, TextureFramebuffer,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::render::debug_view::DebugShading;
+use crate::render::fog::Fog;
+use crate::render::lights;
+use crate::render::shadows::ShadowMap;
+use crate::render::sky::DayCycle;
+
+/// Albedo, normal and light levels, in the order chunk.frag writes them
+const GBUFFER_FORMATS: [TextureFormat; 3] = [TextureFormat::Rgba8; 3];
+/// Past the G-buffer's own textures, samplers of different types can't share a unit
+const SHADOW_UNIT: u32 = 4;
+const OCCLUSION_UNIT: u32 = 5;
+/// Blocks around each pixel that can occlude it
+const SSAO_RADIUS: f32 = 1.0;
+
+/// Replaces every lit pixel, the sky behind is only kept where nothing was drawn
+const RESOLVE_STATE: RenderState = RenderState {
+    depth_test: false,
+    depth_write: false,
+    blend: None,
+    cull: CullMode::None,
+};
+
+/// How many points around each pixel screen-space ambient occlusion tests. It darkens
+/// creases the baked per-vertex occlusion misses, like where block models meet, and needs
+/// the G-buffer so it's only there with deferred rendering.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SsaoQuality {
+    Off,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl SsaoQuality {
+    pub const ALL: [SsaoQuality; 4] = [
+        SsaoQuality::Off,
+        SsaoQuality::Low,
+        SsaoQuality::Medium,
+        SsaoQuality::High,
+    ];
+
+    pub fn samples(self) -> u32 {
+        match self {
+            SsaoQuality::Off => 0,
+            SsaoQuality::Low => 8,
+            SsaoQuality::Medium => 16,
+            SsaoQuality::High => 32,
+        }
+    }
+
+    /// Low quality works at half resolution, it's blurred by the resolve anyway
+    fn scale(self) -> u32 {
+        match self {
+            SsaoQuality::Low => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// The deferred alternative to lighting chunks as they're drawn: the opaque pass only writes
+/// what each pixel is made of into a G-buffer, and one fullscreen pass lights them all
+/// afterwards. Lighting then costs the same however much overdraw there was, which leaves
+/// room for many lights. Translucent faces are still drawn forward on top.
+pub struct DeferredRenderer {
+    program: Handle<ShaderProgram>,
+    gbuffer: TextureFramebuffer,
+    ssao_program: Handle<ShaderProgram>,
+    ssao: SsaoQuality,
+    /// How occluded each pixel is, from 0 for fully to 1 for open. None while SSAO is off.
+    occlusion: Option<TextureFramebuffer>,
+    debug_shading: DebugShading,
+    /// One triangle big enough to cover the screen
+    screen: Mesh,
+}
+
+impl DeferredRenderer {
+    pub fn new(
+        assets: &mut Assets,
+        width: u32,
+        height: u32,
+        ssao: SsaoQuality,
+    ) -> anyhow::Result<Self> {
+        let program = assets.load("shader/deferred")?;
+        let ssao_program = assets.load("shader/ssao")?;
+        let gbuffer = TextureFramebuffer::new(width, height, &GBUFFER_FORMATS, true)
+            .context("Failed to create the G-buffer")?;
+        let occlusion = create_occlusion(width, height, ssao)?;
+        let screen_corners: [[f32; 2]; 3] = [[-1.0, -1.0], [3.0, -1.0], [-1.0, 3.0]];
+        let screen = Mesh::new(&screen_corners, &[0, 1, 2], &[Attribute::float(2, 0)]);
+        Ok(DeferredRenderer {
+            program,
+            gbuffer,
+            ssao_program,
+            ssao,
+            occlusion,
+            debug_shading: DebugShading::default(),
+            screen,
+        })
+    }
+
+    /// Recreate the G-buffer for a new window size
+    pub fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        if (width, height) != (self.gbuffer.width(), self.gbuffer.height()) {
+            self.gbuffer = TextureFramebuffer::new(width, height, &GBUFFER_FORMATS, true)
+                .context("Failed to create the G-buffer")?;
+            self.occlusion = create_occlusion(width, height, self.ssao)?;
+        }
+        Ok(())
+    }
+
+    /// Left off if the new target can't be created
+    pub fn set_ssao(&mut self, ssao: SsaoQuality) -> anyhow::Result<()> {
+        if ssao != self.ssao {
+            self.ssao = SsaoQuality::Off;
+            self.occlusion = None;
+            let (width, height) = (self.gbuffer.width(), self.gbuffer.height());
+            self.occlusion = create_occlusion(width, height, ssao)?;
+            self.ssao = ssao;
+        }
+        Ok(())
+    }
+
+    pub fn set_debug_shading(&mut self, debug_shading: DebugShading) {
+        self.debug_shading = debug_shading;
+    }
+
+    /// Bind the G-buffer and clear it, for the opaque chunk pass. Returns where the lit
+    /// result should go, what was bound until now.
+    pub fn begin(&self) -> FramebufferBinding {
+        let output = FramebufferBinding::current();
+        self.gbuffer.bind();
+        unsafe {
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+        output
+    }
+
+    /// Light the G-buffer into `output`, then copy its depth over so whatever is drawn
+    /// afterwards is still hidden behind the terrain
+    pub fn resolve(
+        &self,
+        output: FramebufferBinding,
+        assets: &Assets,
+        camera: &Camera,
+        cycle: &DayCycle,
+        shadows: Option<&ShadowMap>,
+        fog: &Fog,
+    ) {
+        let view_projection = camera.view_projection();
+        let Some(inverse) = view_projection.invert() else {
+            output.bind();
+            self.gbuffer.blit_depth_to(output);
+            return;
+        };
+        let inverse: [[f32; 4]; 4] = inverse.into();
+        RESOLVE_STATE.apply();
+        let ssao = self.render_ssao(assets, camera, view_projection.into(), &inverse);
+        output.bind();
+        let Some(program) = assets.get(self.program) else {
+            self.gbuffer.blit_depth_to(output);
+            return;
+        };
+        program.set_mat4(c"u_inverse_view_projection", &inverse);
+        program.set_vec3(c"u_camera_position", camera.position.into());
+        fog.apply(program);
+        self.debug_shading.apply(program);
+        lights::use_lights(program);
+        program.set_float(c"u_daylight", cycle.daylight);
+        for (unit, name) in [c"u_albedo", c"u_normal", c"u_light"]
+            .into_iter()
+            .enumerate()
+        {
+            program.set_int(name, unit as i32);
+            self.gbuffer.color(unit).bind(unit as u32);
+        }
+        program.set_int(c"u_depth", GBUFFER_FORMATS.len() as i32);
+        if let Some(depth) = self.gbuffer.depth() {
+            depth.bind(GBUFFER_FORMATS.len() as u32);
+        }
+        match shadows {
+            Some(shadows) => shadows.bind(program, SHADOW_UNIT),
+            None => {
+                program.set_int(c"u_shadow_map", SHADOW_UNIT as i32);
+                program.set_float(c"u_shadow_strength", 0.0);
+            }
+        }
+        program.set_int(c"u_occlusion", OCCLUSION_UNIT as i32);
+        program.set_int(c"u_ssao", ssao as i32);
+        if let (true, Some(occlusion)) = (ssao, &self.occlusion) {
+            occlusion.color(0).bind(OCCLUSION_UNIT);
+        }
+        program.bind();
+        self.screen.draw();
+        self.gbuffer.blit_depth_to(output);
+    }
+
+    /// Fill the occlusion target from the G-buffer's depth and normals. Returns whether
+    /// there's anything in it for the resolve to use.
+    fn render_ssao(
+        &self,
+        assets: &Assets,
+        camera: &Camera,
+        view_projection: [[f32; 4]; 4],
+        inverse: &[[f32; 4]; 4],
+    ) -> bool {
+        let (Some(occlusion), Some(program)) = (&self.occlusion, assets.get(self.ssao_program))
+        else {
+            return false;
+        };
+        occlusion.bind();
+        set_viewport(occlusion.width(), occlusion.height());
+        program.set_mat4(c"u_view_projection", &view_projection);
+        program.set_mat4(c"u_inverse_view_projection", inverse);
+        program.set_vec3(c"u_camera_position", camera.position.into());
+        program.set_int(c"u_samples", self.ssao.samples() as i32);
+        program.set_float(c"u_radius", SSAO_RADIUS);
+        program.set_int(c"u_normal", 0);
+        program.set_int(c"u_depth", 1);
+        self.gbuffer.color(1).bind(0);
+        if let Some(depth) = self.gbuffer.depth() {
+            depth.bind(1);
+        }
+        program.bind();
+        self.screen.draw();
+        set_viewport(self.gbuffer.width(), self.gbuffer.height());
+        true
+    }
+}
+
+/// Single channel target for the SSAO pass, at a fraction of the window's size for the
+/// cheaper presets
+fn create_occlusion(
+    width: u32,
+    height: u32,
+    ssao: SsaoQuality,
+) -> anyhow::Result<Option<TextureFramebuffer>> {
+    if ssao == SsaoQuality::Off {
+        return Ok(None);
+    }
+    let scale = ssao.scale();
+    let (width, height) = ((width / scale).max(1), (height / scale).max(1));
+    TextureFramebuffer::new(width, height, &[TextureFormat::R8], false)
+        .map(Some)
+        .context("Failed to create the ambient occlusion target")
+}
@@ -0,0 +1,175 @@
+use cgmath::{EuclideanSpace, Matrix4, Rad, Vector3};
+use gl_lib::{Mesh, RenderState, ShaderProgram};
+use std::collections::HashMap;
+
+use crate::assets::{Assets, Handle};
+use crate::game::components::{DroppedItem, Position, PreviousPosition, PrimedTnt};
+use crate::game::item::{ItemId, ItemRegistry};
+use crate::render::block_textures::BlockTextures;
+use crate::render::entities::{self, EntityScene};
+use crate::render::icons::{self, ItemTexture};
+use crate::render::item_textures::ItemTextures;
+use crate::world::{BlockId, BlockRegistry};
+
+/// Blocks across a dropped block is drawn
+const BLOCK_SCALE: f32 = 0.25;
+/// Blocks across the card of any other item
+const CARD_SCALE: f32 = 0.4;
+/// Radians per second dropped items turn
+const SPIN_SPEED: f32 = 1.0;
+/// Blocks up and down they bob, and how many times a second
+const BOB_HEIGHT: f32 = 0.08;
+const BOB_RATE: f32 = 0.5;
+/// Primed TNT flashes white every this many ticks of its fuse, this strongly
+const FLASH_TICKS: u32 = 5;
+const FLASH_STRENGTH: f32 = 0.6;
+/// Over its last ticks it swells up by this much of its size
+const SWELL_TICKS: u32 = 10;
+const SWELL: f32 = 0.3;
+
+/// Draws every [`DroppedItem`] spinning slowly and bobbing up and down, each one a little out
+/// of step with the others, and [`PrimedTnt`] as the full block it was, flashing as its fuse
+/// burns. Meshes are built the first time an item shows up.
+pub struct DroppedItemRenderer {
+    program: Handle<ShaderProgram>,
+    /// None for ids that no item uses, so they're only looked up once
+    meshes: HashMap<ItemId, Option<(Mesh, ItemTexture)>>,
+    /// Item of each block that was primed, whose mesh it's drawn with
+    primed_items: HashMap<BlockId, Option<ItemId>>,
+}
+
+/// The texture arrays item meshes take their layers from
+pub struct ItemTextureSet<'a> {
+    pub blocks: &'a BlockTextures,
+    pub items: &'a ItemTextures,
+}
+
+impl DroppedItemRenderer {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        let program = assets.load("shader/dropped_item")?;
+        Ok(DroppedItemRenderer {
+            program,
+            meshes: HashMap::new(),
+            primed_items: HashMap::new(),
+        })
+    }
+
+    /// Build meshes for items that were dropped or blocks that were primed for the first time
+    pub fn prepare(
+        &mut self,
+        entities: &hecs::World,
+        items: &ItemRegistry,
+        blocks: &BlockRegistry,
+        textures: &ItemTextureSet,
+    ) {
+        let layers = textures.blocks.layers();
+        let dropped = entities
+            .query::<&DroppedItem>()
+            .iter()
+            .map(|(_, dropped)| dropped.stack.item)
+            .collect::<Vec<_>>();
+        let mut primed = Vec::new();
+        for (_, tnt) in entities.query::<&PrimedTnt>().iter() {
+            let item = *self
+                .primed_items
+                .entry(tnt.block)
+                .or_insert_with(|| items.of_block(tnt.block));
+            primed.extend(item);
+        }
+        for item in dropped.into_iter().chain(primed) {
+            self.meshes
+                .entry(item)
+                .or_insert_with(|| icons::item_mesh(item, items, blocks, &layers, textures.items));
+        }
+    }
+
+    pub fn draw(&self, assets: &Assets, scene: &EntityScene, textures: &ItemTextureSet) {
+        let Some(program) = assets.get(self.program) else {
+            return;
+        };
+        RenderState::OPAQUE.apply();
+        let view_projection: [[f32; 4]; 4] = scene.camera.view_projection().into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        program.set_vec3(c"u_camera_position", scene.camera.position.into());
+        program.set_float(c"u_daylight", scene.cycle.daylight);
+        program.set_int(c"u_textures", 0);
+        // Never sampled, but off unit 0 all the same since it's a different sampler type
+        program.set_int(c"u_shadow_map", 1);
+        program.set_float(c"u_shadow_strength", 0.0);
+        program.set_float(c"u_flash", 0.0);
+        scene.fog.apply(program);
+        program.bind();
+
+        let mut bound = None;
+        let mut query = scene
+            .entities
+            .query::<(&DroppedItem, &Position, Option<&PreviousPosition>)>();
+        for (entity, (dropped, position, previous)) in query.iter() {
+            let Some(Some((mesh, texture))) = self.meshes.get(&dropped.stack.item) else {
+                continue;
+            };
+            if bound != Some(*texture) {
+                match texture {
+                    ItemTexture::Block => textures.blocks.bind(0),
+                    ItemTexture::Item => textures.items.bind(0),
+                }
+                bound = Some(*texture);
+            }
+            let feet = previous.map_or(position.0, |previous| {
+                previous.0 + (position.0 - previous.0) * scene.alpha
+            });
+            // Spread out by entity so stacks lying together don't move in lockstep
+            let phase = (entity.id() % 64) as f32 * 0.7;
+            let time = scene.time + phase;
+            let scale = match texture {
+                ItemTexture::Block => BLOCK_SCALE,
+                ItemTexture::Item => CARD_SCALE,
+            };
+            let bob = (time * BOB_RATE * std::f32::consts::TAU).sin() * 0.5 + 0.5;
+            let center = feet.to_vec() + Vector3::unit_y() * (scale / 2.0 + bob * BOB_HEIGHT);
+            let model: [[f32; 4]; 4] = (Matrix4::from_translation(center)
+                * Matrix4::from_angle_y(Rad(time * SPIN_SPEED))
+                * Matrix4::from_scale(scale)
+                * Matrix4::from_translation(Vector3::new(-0.5, -0.5, -0.5)))
+            .into();
+            program.set_mat4(c"u_model", &model);
+            program.set_vec2(c"u_light", entities::light_at(scene.world, feet));
+            mesh.draw();
+        }
+        self.draw_primed(program, scene, textures);
+    }
+
+    /// Primed TNT, block textures only since it's always a block
+    fn draw_primed(&self, program: &ShaderProgram, scene: &EntityScene, textures: &ItemTextureSet) {
+        textures.blocks.bind(0);
+        let mut query = scene
+            .entities
+            .query::<(&PrimedTnt, &Position, Option<&PreviousPosition>)>();
+        for (_, (primed, position, previous)) in query.iter() {
+            let item = self.primed_items.get(&primed.block).copied().flatten();
+            let Some(Some((mesh, _))) = item.and_then(|item| self.meshes.get(&item)) else {
+                continue;
+            };
+            let feet = previous.map_or(position.0, |previous| {
+                previous.0 + (position.0 - previous.0) * scene.alpha
+            });
+            let swell = 1.0 - primed.fuse.min(SWELL_TICKS) as f32 / SWELL_TICKS as f32;
+            let scale = 1.0 + swell.powi(4) * SWELL;
+            let flash = if (primed.fuse / FLASH_TICKS).is_multiple_of(2) {
+                FLASH_STRENGTH
+            } else {
+                0.0
+            };
+            let center = feet.to_vec() + Vector3::unit_y() * 0.5;
+            let model: [[f32; 4]; 4] = (Matrix4::from_translation(center)
+                * Matrix4::from_scale(scale)
+                * Matrix4::from_translation(Vector3::new(-0.5, -0.5, -0.5)))
+            .into();
+            program.set_mat4(c"u_model", &model);
+            program.set_vec2(c"u_light", entities::light_at(scene.world, feet));
+            program.set_float(c"u_flash", flash);
+            mesh.draw();
+        }
+        program.set_float(c"u_flash", 0.0);
+    }
+}
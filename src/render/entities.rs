@@ -0,0 +1,239 @@
+use cgmath::{Deg, EuclideanSpace, Matrix4, Point3, Rad, Vector3};
+use gl_lib::{Attribute, Mesh, RenderState, ShaderProgram, Texture2D};
+use std::collections::HashMap;
+use std::mem;
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::game::components::{
+    Collider, Dying, Health, Look, Model, Position, PreviousPosition, WalkCycle,
+};
+use crate::game::spawning::DEATH_TICKS;
+use crate::render::debug_view::DebugView;
+use crate::render::entity_model::{AnimationState, EntityModel, EntityVertex};
+use crate::render::fog::Fog;
+use crate::render::frustum::Frustum;
+use crate::render::lights;
+use crate::render::shadows::ShadowMap;
+use crate::render::sky::DayCycle;
+use crate::world::light::{Light, MAX_LIGHT};
+use crate::world::World;
+
+const TEXTURE_DIR: &str = "textures/entity";
+/// Pixels along each side of the checkerboard shown for missing textures
+const MISSING_SIZE: u32 = 16;
+/// How far over a dying mob has fallen by the time it's gone
+const DEATH_TILT: Deg<f32> = Deg(90.0);
+
+const VERTEX_ATTRIBUTES: [Attribute; 3] = [
+    Attribute::float(3, mem::offset_of!(EntityVertex, position)),
+    Attribute::float(3, mem::offset_of!(EntityVertex, normal)),
+    Attribute::float(2, mem::offset_of!(EntityVertex, uv)),
+];
+
+/// What the entities are drawn into this frame
+pub struct EntityScene<'a> {
+    pub camera: &'a Camera,
+    pub cycle: &'a DayCycle,
+    pub shadows: Option<&'a ShadowMap>,
+    pub fog: &'a Fog,
+    /// For the light level each entity stands in
+    pub world: &'a World,
+    pub entities: &'a hecs::World,
+    /// How far into the next tick the frame is
+    pub alpha: f32,
+    /// Seconds, for idle animations
+    pub time: f32,
+}
+
+/// A model with one mesh for each of its parts, ready to draw
+struct LoadedModel {
+    model: EntityModel,
+    meshes: Vec<Mesh>,
+    /// None when the texture failed to load, the checkerboard shows instead
+    texture: Option<Handle<Texture2D>>,
+}
+
+/// Draws every entity with a [`Model`], lit by the light level where it stands like the
+/// blocks around it. Models are loaded the first time an entity uses them.
+pub struct EntityRenderer {
+    program: Handle<ShaderProgram>,
+    /// Keyed by model name, None for models that failed to load so they're only reported once
+    models: HashMap<String, Option<LoadedModel>>,
+    missing_texture: Texture2D,
+    debug_view: DebugView,
+}
+
+impl EntityRenderer {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        let program = assets.load("shader/entity")?;
+        let missing_texture = Texture2D::from_rgba8(MISSING_SIZE, MISSING_SIZE, &missing())?;
+        Ok(EntityRenderer {
+            program,
+            models: HashMap::new(),
+            missing_texture,
+            debug_view: DebugView::default(),
+        })
+    }
+
+    pub fn set_debug_view(&mut self, debug_view: DebugView) {
+        self.debug_view = debug_view;
+    }
+
+    /// Load the models of entities that showed up with one that isn't loaded yet
+    pub fn prepare(&mut self, assets: &mut Assets, entities: &hecs::World) {
+        for (_, model) in entities.query::<&Model>().iter() {
+            if self.models.contains_key(&model.0) {
+                continue;
+            }
+            let loaded = match EntityModel::load(assets, &model.0) {
+                Ok(loaded) => Some(upload(assets, loaded)),
+                Err(err) => {
+                    log::warn!("Entities using model {} won't be drawn: {err:#}", model.0);
+                    None
+                }
+            };
+            self.models.insert(model.0.clone(), loaded);
+        }
+    }
+
+    /// Forget every loaded model and free its texture, e.g. when leaving a world. They load
+    /// again once entities using them show up.
+    pub fn clear(&mut self, assets: &mut Assets) {
+        for loaded in self.models.drain().filter_map(|(_, loaded)| loaded) {
+            // Models sharing a texture share its handle, only the first unload frees it
+            if let Some(texture) = loaded.texture {
+                assets.unload(texture);
+            }
+        }
+    }
+
+    /// Entities whose model isn't loaded are skipped, see [`EntityRenderer::prepare`]
+    pub fn draw(&self, assets: &Assets, scene: &EntityScene) {
+        let Some(program) = assets.get(self.program) else {
+            return;
+        };
+        RenderState::OPAQUE.apply();
+        let camera = scene.camera;
+        let view_projection = camera.view_projection();
+        let frustum = Frustum::from_view_projection(view_projection);
+        let view_projection: [[f32; 4]; 4] = view_projection.into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        program.set_vec3(c"u_camera_position", camera.position.into());
+        program.set_float(c"u_daylight", scene.cycle.daylight);
+        program.set_int(c"u_texture", 0);
+        scene.fog.apply(program);
+        self.debug_view.shading.apply(program);
+        lights::use_lights(program);
+        match scene.shadows {
+            Some(shadows) => shadows.bind(program, 1),
+            None => {
+                // Off unit 0 all the same, samplers of different types can't share a unit
+                program.set_int(c"u_shadow_map", 1);
+                program.set_float(c"u_shadow_strength", 0.0);
+            }
+        }
+        program.bind();
+        gl_lib::set_wireframe(self.debug_view.wireframe);
+
+        let mut query = scene.entities.query::<(
+            &Model,
+            &Position,
+            Option<&PreviousPosition>,
+            Option<&Look>,
+            Option<&WalkCycle>,
+            Option<&Collider>,
+            Option<&Health>,
+            Option<&Dying>,
+        )>();
+        for (_, (model, position, previous, look, walk, collider, health, dying)) in query.iter() {
+            let Some(Some(loaded)) = self.models.get(&model.0) else {
+                continue;
+            };
+            let feet = previous.map_or(position.0, |previous| {
+                previous.0 + (position.0 - previous.0) * scene.alpha
+            });
+            if let Some(collider) = collider {
+                let (width, height) = (collider.half_width, collider.height);
+                let min = feet + Vector3::new(-width, 0.0, -width);
+                let max = feet + Vector3::new(width, height, width);
+                if !frustum.intersects_box(min, max) {
+                    continue;
+                }
+            }
+            let look = look.copied().unwrap_or_default();
+            let (walk_distance, walk_amount) = walk.map_or((0.0, 0.0), |walk| walk.at(scene.alpha));
+            let state = AnimationState {
+                walk_distance,
+                walk_amount,
+                time: scene.time,
+                head_pitch: look.pitch,
+            };
+            // Yaw turns clockwise seen from above, rotations about Y go the other way
+            let placement =
+                Matrix4::from_translation(feet.to_vec()) * Matrix4::from_angle_y(Rad(-look.yaw));
+            // Dying ones tip over onto their side
+            let placement = match dying {
+                Some(dying) => {
+                    let progress = ((dying.0 as f32 + scene.alpha) / DEATH_TICKS as f32).min(1.0);
+                    placement * Matrix4::from_angle_z(DEATH_TILT * progress)
+                }
+                None => placement,
+            };
+            let hurt = dying.is_some() || health.is_some_and(|health| health.hurt > 0);
+            program.set_float(c"u_hurt", if hurt { 1.0 } else { 0.0 });
+            program.set_vec2(c"u_light", light_at(scene.world, feet));
+            match loaded.texture.and_then(|texture| assets.get(texture)) {
+                Some(texture) => texture.bind(0),
+                None => self.missing_texture.bind(0),
+            }
+            for (mesh, transform) in loaded.meshes.iter().zip(loaded.model.pose(&state)) {
+                let model_matrix: [[f32; 4]; 4] = (placement * transform).into();
+                program.set_mat4(c"u_model", &model_matrix);
+                mesh.draw();
+            }
+        }
+        gl_lib::set_wireframe(false);
+    }
+}
+
+fn upload(assets: &mut Assets, model: EntityModel) -> LoadedModel {
+    let meshes = model
+        .parts
+        .iter()
+        .map(|part| Mesh::new(&part.vertices, &part.indices, &VERTEX_ATTRIBUTES))
+        .collect();
+    let path = format!("{TEXTURE_DIR}/{}.png", model.texture);
+    let texture = assets
+        .load(&path)
+        .inspect_err(|err| log::warn!("Failed to load entity texture {path}: {err:#}"))
+        .ok();
+    LoadedModel {
+        model,
+        meshes,
+        texture,
+    }
+}
+
+/// Sky and block light from 0 to 1 in the block the feet are in, open sky where it isn't
+/// loaded
+pub fn light_at(world: &World, feet: Point3<f32>) -> [f32; 2] {
+    let light = world
+        .light(feet.map(|value| value.floor() as i32))
+        .unwrap_or(Light::OPEN);
+    [light.sky, light.block].map(|level| level as f32 / MAX_LIGHT as f32)
+}
+
+fn missing() -> Vec<u8> {
+    let half = MISSING_SIZE / 2;
+    (0..MISSING_SIZE * MISSING_SIZE)
+        .flat_map(|index| {
+            let (x, y) = (index % MISSING_SIZE, index / MISSING_SIZE);
+            if (x / half + y / half).is_multiple_of(2) {
+                [255, 0, 255, 255]
+            } else {
+                [0, 0, 0, 255]
+            }
+        })
+        .collect()
+}
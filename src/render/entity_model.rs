@@ -0,0 +1,279 @@
+use anyhow::{bail, Context};
+use cgmath::{Deg, Matrix4, Rad, SquareMatrix, Vector3};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+use crate::assets::Assets;
+use crate::world::block::Face;
+use crate::world::model::{CORNER_UVS, FACE_CORNERS};
+
+const MODEL_DIR: &str = "entity_models";
+/// Blocks walked for one full swing of the legs forward and back
+const STRIDE_LENGTH: f32 = 1.6;
+
+/// One corner of a model part's faces, positions are in blocks from the entity's feet
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(C)]
+pub struct EntityVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+/// Axis a part turns about in an animation
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    fn rotation(self, angle: Rad<f32>) -> Matrix4<f32> {
+        match self {
+            Axis::X => Matrix4::from_angle_x(angle),
+            Axis::Y => Matrix4::from_angle_y(angle),
+            Axis::Z => Matrix4::from_angle_z(angle),
+        }
+    }
+}
+
+/// Swings a part back and forth while the entity walks, like legs and arms
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WalkSwing {
+    pub axis: Axis,
+    /// Degrees each way at full walking speed
+    pub swing: f32,
+    /// Degrees into the stride the part starts at, 180 for the opposite leg
+    #[serde(default)]
+    pub phase: f32,
+}
+
+/// A slow sway that runs all the time, like breathing or a wagging tail
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IdleSwing {
+    pub axis: Axis,
+    /// Degrees each way
+    pub swing: f32,
+    /// Seconds for one sway there and back
+    pub period: f32,
+    #[serde(default)]
+    pub phase: f32,
+}
+
+/// A group of cuboids that moves as one, turning about its pivot
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelPart {
+    pub name: String,
+    /// Index of the part it's attached to and moves along with, always an earlier one
+    pub parent: Option<usize>,
+    /// In blocks from the entity's feet
+    pub pivot: Vector3<f32>,
+    /// Degrees about X, Y then Z it's turned by before any animation
+    pub rotation: [f32; 3],
+    /// Tilts up and down with where the entity looks
+    pub head: bool,
+    pub walk: Option<WalkSwing>,
+    pub idle: Option<IdleSwing>,
+    pub vertices: Vec<EntityVertex>,
+    pub indices: Vec<u32>,
+}
+
+/// What an entity is doing this frame, as far as its animations care
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct AnimationState {
+    /// Blocks walked so far, moves the walk swings along
+    pub walk_distance: f32,
+    /// From 0 standing still to 1 at full walking speed, scales the walk swings
+    pub walk_amount: f32,
+    /// Seconds, moves the idle swings along
+    pub time: f32,
+    /// Radians, positive looks up
+    pub head_pitch: f32,
+}
+
+/// The shape of an entity, loaded from `entity_models/<name>.json`. Like the original game's
+/// models it's a tree of parts made of cuboids, each turning about its own pivot, and the
+/// texture is unwrapped onto the cuboids in the usual box layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityModel {
+    /// Name of a texture in `textures/entity/`
+    pub texture: String,
+    /// Parents always come before their children
+    pub parts: Vec<ModelPart>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ModelFile {
+    texture: String,
+    /// Pixels across and down the texture, the cuboids' `uv` are counted in these
+    texture_size: [u32; 2],
+    parts: Vec<PartFile>,
+}
+
+/// Positions are in sixteenths of a block from the entity's feet, the model faces -Z
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PartFile {
+    name: String,
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(default)]
+    pivot: [f32; 3],
+    #[serde(default)]
+    rotation: [f32; 3],
+    #[serde(default)]
+    head: bool,
+    #[serde(default)]
+    walk: Option<WalkSwing>,
+    #[serde(default)]
+    idle: Option<IdleSwing>,
+    cubes: Vec<CubeFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CubeFile {
+    /// Smallest corner
+    origin: [f32; 3],
+    size: [f32; 3],
+    /// Top left of the cuboid's box layout in texture pixels, see [`face_rect`]
+    uv: [f32; 2],
+}
+
+impl EntityModel {
+    pub fn load(assets: &Assets, name: &str) -> anyhow::Result<Self> {
+        let path = format!("{MODEL_DIR}/{name}.json");
+        let bytes = assets.read(&path)?;
+        let file: ModelFile =
+            serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {path}"))?;
+        if file.parts.is_empty() {
+            bail!("{path}: a model needs at least one part");
+        }
+        if file.texture_size.contains(&0) {
+            bail!("{path}: the texture size can't be 0");
+        }
+        let texture_size = file.texture_size.map(|size| size as f32);
+
+        let mut indices_by_name = HashMap::new();
+        let mut parts = Vec::with_capacity(file.parts.len());
+        for part in file.parts {
+            let parent = match &part.parent {
+                Some(parent) => Some(*indices_by_name.get(parent).with_context(|| {
+                    format!(
+                        "{path}: {} is attached to {parent}, which has to come first",
+                        part.name
+                    )
+                })?),
+                None => None,
+            };
+            if part.idle.is_some_and(|idle| idle.period <= 0.0) {
+                bail!(
+                    "{path}: the idle period of {} has to be positive",
+                    part.name
+                );
+            }
+            if indices_by_name
+                .insert(part.name.clone(), parts.len())
+                .is_some()
+            {
+                bail!("{path}: there are two parts named {}", part.name);
+            }
+
+            let mut vertices = Vec::new();
+            let mut indices = Vec::new();
+            for cube in &part.cubes {
+                if cube.size.iter().any(|size| *size < 0.0) {
+                    bail!("{path}: the cuboids of {} have a negative size", part.name);
+                }
+                for face in Face::ALL {
+                    let start = vertices.len() as u32;
+                    let [u, v, width, height] = face_rect(face, cube.uv, cube.size);
+                    let normal = face.normal().map(|value| value as f32);
+                    for (corner, [corner_u, corner_v]) in
+                        FACE_CORNERS[face as usize].into_iter().zip(CORNER_UVS)
+                    {
+                        let position = [0, 1, 2].map(|axis| {
+                            (cube.origin[axis] + corner[axis] * cube.size[axis]) / 16.0
+                        });
+                        // Box layouts count rows from the top, textures here from the bottom
+                        let pixel = [u + corner_u * width, v + (1.0 - corner_v) * height];
+                        vertices.push(EntityVertex {
+                            position,
+                            normal,
+                            uv: [pixel[0] / texture_size[0], 1.0 - pixel[1] / texture_size[1]],
+                        });
+                    }
+                    indices.extend([0, 1, 2, 0, 2, 3].map(|offset| start + offset));
+                }
+            }
+            parts.push(ModelPart {
+                name: part.name,
+                parent,
+                pivot: Vector3::from(part.pivot.map(|value| value / 16.0)),
+                rotation: part.rotation,
+                head: part.head,
+                walk: part.walk,
+                idle: part.idle,
+                vertices,
+                indices,
+            });
+        }
+        Ok(EntityModel {
+            texture: file.texture,
+            parts,
+        })
+    }
+
+    /// Transform of each part from the model's space, in the order of [`EntityModel::parts`]
+    pub fn pose(&self, state: &AnimationState) -> Vec<Matrix4<f32>> {
+        let mut transforms: Vec<Matrix4<f32>> = Vec::with_capacity(self.parts.len());
+        for part in &self.parts {
+            let [x, y, z] = part.rotation;
+            let mut rotation = Matrix4::from_angle_z(Deg(z))
+                * Matrix4::from_angle_y(Deg(y))
+                * Matrix4::from_angle_x(Deg(x));
+            if part.head {
+                rotation = rotation * Matrix4::from_angle_x(Rad(state.head_pitch));
+            }
+            if let Some(walk) = part.walk {
+                let stride =
+                    state.walk_distance / STRIDE_LENGTH * TAU + Rad::from(Deg(walk.phase)).0;
+                let angle = Deg(walk.swing * state.walk_amount * stride.sin());
+                rotation = rotation * walk.axis.rotation(angle.into());
+            }
+            if let Some(idle) = part.idle {
+                let sway = state.time / idle.period * TAU + Rad::from(Deg(idle.phase)).0;
+                rotation = rotation * idle.axis.rotation(Deg(idle.swing * sway.sin()).into());
+            }
+            let local = Matrix4::from_translation(part.pivot)
+                * rotation
+                * Matrix4::from_translation(-part.pivot);
+            let parent = part
+                .parent
+                .map_or(Matrix4::identity(), |parent| transforms[parent]);
+            transforms.push(parent * local);
+        }
+        transforms
+    }
+}
+
+/// Where `face` of a cuboid sized `size` sits in a box layout starting at `uv`, as the left,
+/// top, width and height in pixels. The top and bottom go in the first row, the sides under
+/// them running east, north (the front), west and south, the way the original game lays out
+/// its entity textures.
+fn face_rect(face: Face, [u, v]: [f32; 2], [width, height, depth]: [f32; 3]) -> [f32; 4] {
+    match face {
+        Face::Up => [u + depth, v, width, depth],
+        Face::Down => [u + depth + width, v, width, depth],
+        Face::East => [u, v + depth, depth, height],
+        Face::North => [u + depth, v + depth, width, height],
+        Face::West => [u + depth + width, v + depth, depth, height],
+        Face::South => [u + depth * 2.0 + width, v + depth, width, height],
+    }
+}
@@ -0,0 +1,47 @@
+use gl_lib::ShaderProgram;
+
+use crate::render::sky::DayCycle;
+use crate::world::block::FluidProperties;
+use crate::world::chunk::CHUNK_SIZE;
+
+/// Where the fog starts, as a fraction of the render distance
+const FOG_START: f32 = 0.75;
+
+/// What the world fades into with distance from the camera
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Fog {
+    /// Start and end of the fade in blocks
+    pub range: [f32; 2],
+    pub color: [f32; 3],
+    /// Measured in every direction instead of only horizontally
+    pub spherical: bool,
+}
+
+impl Fog {
+    /// Fades into the horizon toward `render_distance` chunks, so the edge of the loaded
+    /// world doesn't show. Only horizontal distance counts, chunks load in columns around the
+    /// player so the edge is a ring.
+    pub fn sky(cycle: &DayCycle, render_distance: u32) -> Self {
+        let end = (render_distance as i32 * CHUNK_SIZE) as f32;
+        Fog {
+            range: [end * FOG_START, end],
+            color: cycle.horizon_color,
+            spherical: false,
+        }
+    }
+
+    /// Thick fog in the fluid's color, for when the camera is inside it
+    pub fn fluid(properties: &FluidProperties) -> Self {
+        Fog {
+            range: [0.0, properties.fog_distance],
+            color: properties.fog_color,
+            spherical: true,
+        }
+    }
+
+    pub fn apply(&self, program: &ShaderProgram) {
+        program.set_vec2(c"u_fog_range", self.range);
+        program.set_vec3(c"u_fog_color", self.color);
+        program.set_int(c"u_fog_spherical", self.spherical as i32);
+    }
+}
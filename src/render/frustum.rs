@@ -0,0 +1,40 @@
+use cgmath::{Matrix, Matrix4, Point3, Vector4};
+
+/// The six planes bounding what a camera sees, pointing inward. Taken straight from the
+/// view-projection matrix so it always matches what gets drawn.
+#[derive(Debug, Copy, Clone)]
+pub struct Frustum {
+    /// Plane equations `ax + by + cz + d`, positive on the inside
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /// Gribb and Hartmann: each plane is the last row of the matrix plus or minus one of the
+    /// others
+    pub fn from_view_projection(matrix: Matrix4<f32>) -> Self {
+        let rows = [0, 1, 2, 3].map(|index| matrix.row(index));
+        Frustum {
+            planes: [
+                rows[3] + rows[0],
+                rows[3] - rows[0],
+                rows[3] + rows[1],
+                rows[3] - rows[1],
+                rows[3] + rows[2],
+                rows[3] - rows[2],
+            ],
+        }
+    }
+
+    /// False only when the box is certainly out of view, boxes near a corner of the frustum
+    /// can pass without being seen
+    pub fn intersects_box(&self, min: Point3<f32>, max: Point3<f32>) -> bool {
+        self.planes.iter().all(|plane| {
+            // The corner furthest along the plane's normal, if even that is outside so is
+            // the whole box
+            let x = if plane.x >= 0.0 { max.x } else { min.x };
+            let y = if plane.y >= 0.0 { max.y } else { min.y };
+            let z = if plane.z >= 0.0 { max.z } else { min.z };
+            plane.x * x + plane.y * y + plane.z * z + plane.w >= 0.0
+        })
+    }
+}
@@ -0,0 +1,141 @@
+use cgmath::{perspective, Deg, Matrix4, Vector3};
+use gl_lib::{Mesh, RenderState, ShaderProgram};
+use std::f32::consts::PI;
+
+use crate::assets::{Assets, Handle};
+use crate::game::item::{ItemId, ItemRegistry};
+use crate::render::block_textures::BlockTextures;
+use crate::render::icons::{self, ItemTexture};
+use crate::render::item_textures::ItemTextures;
+use crate::world::light::{Light, MAX_LIGHT};
+use crate::world::BlockRegistry;
+
+/// Seconds for one swing down and back up
+const SWING_SECONDS: f32 = 0.3;
+/// Seconds for a newly selected item to come up into view
+const EQUIP_SECONDS: f32 = 0.2;
+/// Fixed so the block stays the same size whatever the field of view is set to
+const FOV: Deg<f32> = Deg(70.0);
+/// Where the item's centre rests in view space, to the right and below the crosshair
+const REST_POSITION: [f32; 3] = [0.56, -0.52, -0.72];
+/// Blocks across the held item is drawn
+const SCALE: f32 = 0.4;
+/// How far below the view an item starts when it's switched to
+const EQUIP_DROP: f32 = 0.6;
+
+/// The selected item drawn in the lower right corner of the view, swinging when it's used
+/// and coming up into view when it changes. It's drawn last with the depth buffer cleared, so
+/// it's never hidden inside a wall the player stands against.
+pub struct HeldItemRenderer {
+    program: Handle<ShaderProgram>,
+    /// The item the mesh was built for, None with nothing selected
+    mesh: Option<(ItemId, Mesh, ItemTexture)>,
+    /// Seconds into the current swing, None while not swinging
+    swing: Option<f32>,
+    /// From 0 just switched to 1 fully raised
+    equip: f32,
+}
+
+impl HeldItemRenderer {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        let program = assets.load("shader/held_item")?;
+        Ok(HeldItemRenderer {
+            program,
+            mesh: None,
+            swing: None,
+            equip: 1.0,
+        })
+    }
+
+    /// Start a swing, over again if one is already going
+    pub fn swing(&mut self) {
+        self.swing = Some(0.0);
+    }
+
+    pub fn is_swinging(&self) -> bool {
+        self.swing.is_some()
+    }
+
+    /// Move the animations along by `delta` seconds and rebuild the mesh if the selected
+    /// item changed
+    pub fn update(
+        &mut self,
+        delta: f32,
+        held: Option<ItemId>,
+        items: &ItemRegistry,
+        blocks: &BlockRegistry,
+        textures: &BlockTextures,
+        item_textures: &ItemTextures,
+    ) {
+        let current = self.mesh.as_ref().map(|(id, _, _)| *id);
+        if held != current {
+            let layers = textures.layers();
+            self.mesh = held.and_then(|id| {
+                let (mesh, texture) = icons::item_mesh(id, items, blocks, &layers, item_textures)?;
+                Some((id, mesh, texture))
+            });
+            self.equip = 0.0;
+        }
+        self.equip = (self.equip + delta / EQUIP_SECONDS).min(1.0);
+        self.swing = self
+            .swing
+            .map(|seconds| seconds + delta)
+            .filter(|seconds| *seconds < SWING_SECONDS);
+    }
+
+    /// Clears the depth buffer, call it once everything else in the world is drawn. `light`
+    /// is where the camera is.
+    pub fn draw(
+        &self,
+        assets: &Assets,
+        textures: &BlockTextures,
+        item_textures: &ItemTextures,
+        aspect: f32,
+        light: Light,
+        daylight: f32,
+    ) {
+        let (Some((_, mesh, texture)), Some(program)) = (&self.mesh, assets.get(self.program))
+        else {
+            return;
+        };
+        unsafe {
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+        }
+        RenderState::OPAQUE.apply();
+        let view_projection: [[f32; 4]; 4] =
+            (perspective(FOV, aspect, 0.05, 10.0) * self.placement()).into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        let levels = [light.sky, light.block].map(|level| level as f32 / MAX_LIGHT as f32);
+        program.set_vec2(c"u_light", levels);
+        program.set_float(c"u_daylight", daylight);
+        program.set_int(c"u_blocks", 0);
+        // Never sampled, but off unit 0 all the same since it's a different sampler type
+        program.set_int(c"u_shadow_map", 1);
+        program.set_float(c"u_shadow_strength", 0.0);
+        program.bind();
+        match texture {
+            ItemTexture::Block => textures.bind(0),
+            ItemTexture::Item => item_textures.bind(0),
+        }
+        mesh.draw();
+    }
+
+    /// From the item's own units into view space, with the swing and equip animations
+    fn placement(&self) -> Matrix4<f32> {
+        let progress = self.swing.map_or(0.0, |seconds| seconds / SWING_SECONDS);
+        // Out and back once over the swing, dipping down twice as fast
+        let arc = (progress * PI).sin();
+        let dip = (progress * PI * 2.0).sin();
+        let [x, y, z] = REST_POSITION;
+        let position = Vector3::new(
+            x - 0.3 * arc,
+            y + 0.1 * dip - EQUIP_DROP * (1.0 - self.equip),
+            z - 0.2 * arc,
+        );
+        Matrix4::from_translation(position)
+            * Matrix4::from_angle_x(Deg(-60.0 * arc))
+            * Matrix4::from_angle_y(Deg(45.0))
+            * Matrix4::from_scale(SCALE)
+            * Matrix4::from_translation(Vector3::new(-0.5, -0.5, -0.5))
+    }
+}
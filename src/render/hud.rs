@@ -0,0 +1,592 @@
+use cgmath::ortho;
+use gl_lib::{
+    BlendMode, Buffer, BufferTarget, BufferUsage, RenderState, ShaderProgram, VertexArray,
+};
+use std::mem;
+use std::ops::Range;
+
+use crate::assets::{Assets, Handle};
+use crate::game::components::{Breath, Health, Hunger};
+use crate::game::experience;
+use crate::game::health::{MAX_BREATH, MAX_HEALTH};
+use crate::game::hunger::MAX_FOOD;
+use crate::game::inventory::{Inventory, ItemStack, HOTBAR_SLOTS};
+use crate::game::item::ItemRegistry;
+use crate::render::icons::{IconAtlas, IconRect};
+use crate::render::text::{Color, TextRenderer, WHITE};
+
+const CROSSHAIR_ARM: f32 = 9.0;
+const CROSSHAIR_THICKNESS: f32 = 2.0;
+/// One inventory slot, in GUI units
+pub const SLOT_SIZE: f32 = 40.0;
+const SLOT_ICON_SIZE: f32 = 32.0;
+/// Gap between the hotbar and the bottom of the screen
+const HOTBAR_MARGIN: f32 = 4.0;
+const SLOT_COLOR: Color = [0.0, 0.0, 0.0, 0.45];
+const SELECTED_COLOR: Color = [1.0, 1.0, 1.0, 0.9];
+const SELECTED_BORDER: f32 = 2.0;
+const COUNT_SHADOW_COLOR: Color = [0.25, 0.25, 0.25, 1.0];
+/// Height of the bar along the bottom of a worn tool's icon, under it a black strip one
+/// unit taller
+const WEAR_BAR_HEIGHT: f32 = 2.0;
+const WEAR_BAR_BACKGROUND: Color = [0.0, 0.0, 0.0, 1.0];
+/// GUI units per pixel of [`HEART`] and [`DRUMSTICK`]
+const PIXEL: f32 = 2.0;
+/// Gap between the hearts and the hotbar, and between the hearts and the air bubbles
+const VITALS_GAP: f32 = 4.0;
+const HEART_COLOR: Color = [0.85, 0.1, 0.1, 1.0];
+const EMPTY_HEART_COLOR: Color = [0.15, 0.05, 0.05, 0.8];
+/// Empty hearts light up for a moment after a hit
+const HURT_HEART_COLOR: Color = [0.9, 0.9, 0.9, 0.8];
+const BUBBLE_COLOR: Color = [0.45, 0.7, 1.0, 1.0];
+const FOOD_COLOR: Color = [0.7, 0.4, 0.15, 1.0];
+const EMPTY_FOOD_COLOR: Color = [0.12, 0.07, 0.03, 0.8];
+/// The experience bar right above the hotbar, the vitals move up to make room for it
+const LEVEL_BAR_HEIGHT: f32 = 6.0;
+const LEVEL_BAR_COLOR: Color = [0.5, 0.9, 0.2, 1.0];
+const EMPTY_LEVEL_BAR_COLOR: Color = [0.0, 0.0, 0.0, 0.6];
+/// The level in the middle of the bar
+const LEVEL_COLOR: Color = [0.6, 1.0, 0.3, 1.0];
+const LEVEL_SHADOW_COLOR: Color = [0.0, 0.0, 0.0, 1.0];
+/// Between the top of the bar and the top of the level's text
+const LEVEL_TEXT_OFFSET: f32 = 7.0;
+/// What the screen fades to while falling asleep
+const SLEEP_COLOR: [f32; 3] = [0.0, 0.0, 0.03];
+const MAX_SLEEP_ALPHA: f32 = 0.9;
+/// One heart, rows top to bottom. A half heart is the columns left of the middle one.
+const HEART: [&str; 7] = [
+    ".##...##.",
+    "####.####",
+    "#########",
+    ".#######.",
+    "..#####..",
+    "...###...",
+    "....#....",
+];
+/// One drumstick, the same size as [`HEART`]
+const DRUMSTICK: [&str; 7] = [
+    "....####.",
+    "...######",
+    "...######",
+    "....#####",
+    "...###...",
+    ".##......",
+    "##.......",
+];
+
+/// What HUD elements get to look at while drawing, grows as the game state does
+pub struct HudContext<'a> {
+    /// GUI units, the HUD projection stretches them over the framebuffer with the origin
+    /// top left
+    pub screen_size: [f32; 2],
+    pub icons: &'a IconAtlas,
+    pub items: &'a ItemRegistry,
+    /// The player's, None without a world
+    pub inventory: Option<&'a Inventory>,
+    pub health: Option<Health>,
+    pub breath: Option<Breath>,
+    pub hunger: Option<Hunger>,
+    /// Experience points in total, None hides the level bar
+    pub experience: Option<u32>,
+    /// How far into falling asleep the player is, from 0 while awake to 1
+    pub sleep: f32,
+}
+
+/// One piece of the HUD (crosshair, hotbar, health...), elements are drawn in the order they
+/// were added so later ones end up on top
+pub trait HudElement {
+    fn draw(&self, batch: &mut HudBatch, context: &HudContext);
+}
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+struct HudVertex {
+    position: [f32; 2],
+    color: Color,
+    /// Into the icon atlas, unused for plain rectangles
+    uv: [f32; 2],
+    /// 1 for icons
+    textured: f32,
+}
+
+struct Segment {
+    blend: BlendMode,
+    start: usize,
+}
+
+/// Text queued along with the quads
+struct Label {
+    text: String,
+    /// Top of the text
+    position: [f32; 2],
+    color: Color,
+    /// How much of the text's width is left of `position`, 0 for its left end and 1 for its
+    /// right end
+    anchor: f32,
+}
+
+/// Collects flat quads for the whole HUD, split into segments wherever the blend mode
+/// changes. Labels go to the text renderer, which draws them on top of everything.
+pub struct HudBatch {
+    vertices: Vec<HudVertex>,
+    segments: Vec<Segment>,
+    labels: Vec<Label>,
+}
+
+impl HudBatch {
+    fn new() -> Self {
+        HudBatch {
+            vertices: Vec::new(),
+            segments: Vec::new(),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Applies to everything queued afterwards, until changed again
+    pub fn set_blend(&mut self, blend: BlendMode) {
+        if self.segments.last().map(|segment| segment.blend) != Some(blend) {
+            self.segments.push(Segment {
+                blend,
+                start: self.vertices.len(),
+            });
+        }
+    }
+
+    pub fn rect(&mut self, position: [f32; 2], size: [f32; 2], color: Color) {
+        self.quad(position, size, color, None);
+    }
+
+    /// One icon from the [`IconAtlas`], its colors multiplied by `color`
+    pub fn icon(&mut self, position: [f32; 2], size: [f32; 2], icon: IconRect, color: Color) {
+        self.quad(position, size, color, Some(icon));
+    }
+
+    /// `top_right` is where the text ends
+    pub fn label_right(&mut self, text: String, top_right: [f32; 2], color: Color) {
+        self.labels.push(Label {
+            text,
+            position: top_right,
+            color,
+            anchor: 1.0,
+        });
+    }
+
+    /// `top_center` is the middle of the text's top edge
+    pub fn label_centered(&mut self, text: String, top_center: [f32; 2], color: Color) {
+        self.labels.push(Label {
+            text,
+            position: top_center,
+            color,
+            anchor: 0.5,
+        });
+    }
+
+    /// A stack's icon filling a slot at `position`, with its count in the corner unless it's
+    /// just one and a bar going from green to red under tools once they're worn. Unknown
+    /// items leave the slot empty.
+    pub fn stack(
+        &mut self,
+        position: [f32; 2],
+        stack: ItemStack,
+        icons: &IconAtlas,
+        items: &ItemRegistry,
+    ) {
+        let Some(icon) = icons.icon(stack.item) else {
+            return;
+        };
+        let inset = (SLOT_SIZE - SLOT_ICON_SIZE) / 2.0;
+        self.icon(
+            [position[0] + inset, position[1] + inset],
+            [SLOT_ICON_SIZE, SLOT_ICON_SIZE],
+            icon,
+            WHITE,
+        );
+        if stack.count > 1 {
+            let corner = [
+                position[0] + SLOT_SIZE - 2.0,
+                position[1] + SLOT_SIZE - 20.0,
+            ];
+            let count = stack.count.to_string();
+            self.label_right(
+                count.clone(),
+                [corner[0] + 1.0, corner[1] + 1.0],
+                COUNT_SHADOW_COLOR,
+            );
+            self.label_right(count, corner, WHITE);
+        }
+        if let Some(wear) = items.wear(stack).filter(|wear| *wear > 0.0) {
+            let left = position[0] + inset + 2.0;
+            let top = position[1] + inset + SLOT_ICON_SIZE - WEAR_BAR_HEIGHT * 2.0;
+            let width = SLOT_ICON_SIZE - 4.0;
+            self.rect(
+                [left, top],
+                [width, WEAR_BAR_HEIGHT + 1.0],
+                WEAR_BAR_BACKGROUND,
+            );
+            let left_over = 1.0 - wear;
+            self.rect(
+                [left, top],
+                [width * left_over, WEAR_BAR_HEIGHT],
+                [wear.min(0.5) * 2.0, left_over.min(0.5) * 2.0, 0.0, 1.0],
+            );
+        }
+    }
+
+    fn quad(&mut self, position: [f32; 2], size: [f32; 2], color: Color, icon: Option<IconRect>) {
+        if self.segments.is_empty() {
+            self.set_blend(BlendMode::Alpha);
+        }
+        let [x0, y0] = position;
+        let [x1, y1] = [x0 + size[0], y0 + size[1]];
+        // The atlas has its origin bottom left, the HUD top left
+        let [u0, v0, u1, v1] = icon.unwrap_or_default();
+        let textured = icon.is_some() as u32 as f32;
+        let vertex = |x, y, u, v| HudVertex {
+            position: [x, y],
+            color,
+            uv: [u, v],
+            textured,
+        };
+        self.vertices.extend_from_slice(&[
+            vertex(x0, y0, u0, v1),
+            vertex(x0, y1, u0, v0),
+            vertex(x1, y1, u1, v0),
+            vertex(x1, y1, u1, v0),
+            vertex(x1, y0, u1, v1),
+            vertex(x0, y0, u0, v1),
+        ]);
+    }
+
+    fn clear(&mut self) {
+        self.vertices.clear();
+        self.segments.clear();
+        self.labels.clear();
+    }
+}
+
+/// Screen space pass drawn after the world, independent of the 3D pipeline
+pub struct Hud {
+    elements: Vec<Box<dyn HudElement>>,
+    batch: HudBatch,
+    program: Handle<ShaderProgram>,
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer,
+}
+
+impl Hud {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        let program = assets.load("shader/hud")?;
+
+        let vertex_array = VertexArray::new();
+        vertex_array.bind();
+        let vertex_buffer = Buffer::new(BufferTarget::Array);
+        vertex_buffer.bind();
+        let stride = mem::size_of::<HudVertex>();
+        vertex_array.attrib_f32(0, 2, stride, mem::offset_of!(HudVertex, position));
+        vertex_array.attrib_f32(1, 4, stride, mem::offset_of!(HudVertex, color));
+        vertex_array.attrib_f32(2, 2, stride, mem::offset_of!(HudVertex, uv));
+        vertex_array.attrib_f32(3, 1, stride, mem::offset_of!(HudVertex, textured));
+
+        Ok(Hud {
+            elements: Vec::new(),
+            batch: HudBatch::new(),
+            program,
+            vertex_array,
+            vertex_buffer,
+        })
+    }
+
+    pub fn push(&mut self, element: impl HudElement + 'static) {
+        self.elements.push(Box::new(element));
+    }
+
+    pub fn draw(&mut self, assets: &Assets, text: &mut TextRenderer, context: &HudContext) {
+        for element in &self.elements {
+            element.draw(&mut self.batch, context);
+        }
+        self.flush(assets, text, context);
+    }
+
+    /// Draw quads queued by something other than the HUD's elements, like the slots of the
+    /// inventory screen
+    pub fn draw_with(
+        &mut self,
+        assets: &Assets,
+        text: &mut TextRenderer,
+        context: &HudContext,
+        queue: impl FnOnce(&mut HudBatch),
+    ) {
+        queue(&mut self.batch);
+        self.flush(assets, text, context);
+    }
+
+    fn flush(&mut self, assets: &Assets, text: &mut TextRenderer, context: &HudContext) {
+        for label in &self.batch.labels {
+            let [width, _] = text.measure(&label.text);
+            let [x, y] = label.position;
+            let left = (x - width * label.anchor).round();
+            text.queue(&label.text, [left, y], label.color);
+        }
+        let Some(program) = assets.get(self.program) else {
+            self.batch.clear();
+            return;
+        };
+        if self.batch.vertices.is_empty() {
+            self.batch.clear();
+            return;
+        }
+
+        let [width, height] = context.screen_size;
+        let projection: [[f32; 4]; 4] = ortho(0.0, width, height, 0.0, -1.0, 1.0).into();
+        program.set_mat4(c"u_projection", &projection);
+        program.set_int(c"u_icons", 0);
+        program.bind();
+        context.icons.bind(0);
+        self.vertex_array.bind();
+        self.vertex_buffer
+            .set_data(&self.batch.vertices, BufferUsage::Stream);
+
+        for (index, segment) in self.batch.segments.iter().enumerate() {
+            let end = self
+                .batch
+                .segments
+                .get(index + 1)
+                .map_or(self.batch.vertices.len(), |next| next.start);
+            if end == segment.start {
+                continue;
+            }
+            RenderState::OVERLAY.with_blend(Some(segment.blend)).apply();
+            gl_lib::draw_triangles(segment.start, end - segment.start);
+        }
+        self.batch.clear();
+    }
+}
+
+pub struct Crosshair;
+
+impl HudElement for Crosshair {
+    fn draw(&self, batch: &mut HudBatch, context: &HudContext) {
+        let center = [
+            (context.screen_size[0] / 2.0).floor(),
+            (context.screen_size[1] / 2.0).floor(),
+        ];
+        let half = CROSSHAIR_THICKNESS / 2.0;
+        batch.set_blend(BlendMode::Invert);
+        batch.rect(
+            [center[0] - CROSSHAIR_ARM, center[1] - half],
+            [CROSSHAIR_ARM * 2.0, CROSSHAIR_THICKNESS],
+            [1.0, 1.0, 1.0, 1.0],
+        );
+        // The vertical bar skips the middle, inverting it twice would cancel out
+        batch.rect(
+            [center[0] - half, center[1] - CROSSHAIR_ARM],
+            [CROSSHAIR_THICKNESS, CROSSHAIR_ARM - half],
+            [1.0, 1.0, 1.0, 1.0],
+        );
+        batch.rect(
+            [center[0] - half, center[1] + half],
+            [CROSSHAIR_THICKNESS, CROSSHAIR_ARM - half],
+            [1.0, 1.0, 1.0, 1.0],
+        );
+    }
+}
+
+/// The first row of the inventory along the bottom of the screen, with the selected slot
+/// outlined
+pub struct Hotbar;
+
+impl HudElement for Hotbar {
+    fn draw(&self, batch: &mut HudBatch, context: &HudContext) {
+        let Some(inventory) = context.inventory else {
+            return;
+        };
+        let [width, height] = context.screen_size;
+        let left = ((width - SLOT_SIZE * HOTBAR_SLOTS as f32) / 2.0).floor();
+        let top = height - SLOT_SIZE - HOTBAR_MARGIN;
+        batch.set_blend(BlendMode::Alpha);
+        batch.rect(
+            [left, top],
+            [SLOT_SIZE * HOTBAR_SLOTS as f32, SLOT_SIZE],
+            SLOT_COLOR,
+        );
+        let selected = [left + SLOT_SIZE * inventory.selected as f32, top];
+        outline(batch, selected, SLOT_SIZE, SELECTED_BORDER, SELECTED_COLOR);
+        for (index, stack) in inventory.slots[..HOTBAR_SLOTS].iter().enumerate() {
+            if let Some(stack) = stack {
+                batch.stack(
+                    [left + SLOT_SIZE * index as f32, top],
+                    *stack,
+                    context.icons,
+                    context.items,
+                );
+            }
+        }
+    }
+}
+
+/// A square border `thickness` wide just inside the square at `position`
+pub fn outline(batch: &mut HudBatch, position: [f32; 2], size: f32, thickness: f32, color: Color) {
+    let [x, y] = position;
+    batch.rect([x, y], [size, thickness], color);
+    batch.rect([x, y + size - thickness], [size, thickness], color);
+    batch.rect(
+        [x, y + thickness],
+        [thickness, size - thickness * 2.0],
+        color,
+    );
+    batch.rect(
+        [x + size - thickness, y + thickness],
+        [thickness, size - thickness * 2.0],
+        color,
+    );
+}
+
+/// Hearts above the left end of the hotbar, two hit points each, with a row of air bubbles
+/// above them while the player's breath is running out. Drumsticks for food go above the
+/// right end.
+pub struct Vitals;
+
+impl HudElement for Vitals {
+    fn draw(&self, batch: &mut HudBatch, context: &HudContext) {
+        let [width, height] = context.screen_size;
+        let hotbar_width = SLOT_SIZE * HOTBAR_SLOTS as f32;
+        let left = ((width - hotbar_width) / 2.0).floor();
+        let icon_width = HEART[0].len() as f32 * PIXEL;
+        let icon_height = HEART.len() as f32 * PIXEL;
+        let level_bar = match context.experience {
+            Some(_) => LEVEL_BAR_HEIGHT + VITALS_GAP,
+            None => 0.0,
+        };
+        let top = height - SLOT_SIZE - HOTBAR_MARGIN - VITALS_GAP - level_bar - icon_height;
+        let spacing = icon_width + PIXEL;
+        batch.set_blend(BlendMode::Alpha);
+
+        if let Some(health) = context.health {
+            let empty = if health.hurt > 0 {
+                HURT_HEART_COLOR
+            } else {
+                EMPTY_HEART_COLOR
+            };
+            for heart in 0..MAX_HEALTH.div_ceil(2) {
+                let position = [left + heart as f32 * spacing, top];
+                let width = HEART[0].len();
+                pixel_art(batch, &HEART, position, 0..width, empty);
+                match health.points.saturating_sub(heart * 2) {
+                    0 => {}
+                    1 => pixel_art(batch, &HEART, position, 0..width / 2, HEART_COLOR),
+                    _ => pixel_art(batch, &HEART, position, 0..width, HEART_COLOR),
+                }
+            }
+        }
+
+        if let Some(hunger) = context.hunger {
+            // Right to left, a half drumstick keeps its right half
+            let right = left + hotbar_width - icon_width;
+            for drumstick in 0..MAX_FOOD.div_ceil(2) {
+                let position = [right - drumstick as f32 * spacing, top];
+                let width = DRUMSTICK[0].len();
+                pixel_art(batch, &DRUMSTICK, position, 0..width, EMPTY_FOOD_COLOR);
+                match hunger.food.saturating_sub(drumstick * 2) {
+                    0 => {}
+                    1 => pixel_art(batch, &DRUMSTICK, position, width / 2..width, FOOD_COLOR),
+                    _ => pixel_art(batch, &DRUMSTICK, position, 0..width, FOOD_COLOR),
+                }
+            }
+        }
+
+        let Some(breath) = context.breath.filter(|breath| breath.0 < MAX_BREATH) else {
+            return;
+        };
+        // Ten bubbles like the hearts, each one gone as soon as it starts running out
+        let bubbles = (breath.0 * 10).div_ceil(MAX_BREATH);
+        let bubble = icon_height - PIXEL * 2.0;
+        let top = top - VITALS_GAP - bubble;
+        for index in 0..bubbles {
+            let x = left + index as f32 * spacing + PIXEL;
+            batch.rect([x, top], [bubble, bubble], BUBBLE_COLOR);
+        }
+    }
+}
+
+/// How far the player is to their next experience level as a bar as wide as the hotbar,
+/// right above it, with the level they're at in the middle once they have one
+pub struct LevelBar;
+
+impl HudElement for LevelBar {
+    fn draw(&self, batch: &mut HudBatch, context: &HudContext) {
+        let Some(total) = context.experience else {
+            return;
+        };
+        let [width, height] = context.screen_size;
+        let hotbar_width = SLOT_SIZE * HOTBAR_SLOTS as f32;
+        let left = ((width - hotbar_width) / 2.0).floor();
+        let top = height - SLOT_SIZE - HOTBAR_MARGIN - VITALS_GAP - LEVEL_BAR_HEIGHT;
+        batch.set_blend(BlendMode::Alpha);
+        batch.rect(
+            [left, top],
+            [hotbar_width, LEVEL_BAR_HEIGHT],
+            EMPTY_LEVEL_BAR_COLOR,
+        );
+        let filled = (hotbar_width * experience::progress(total)).round();
+        batch.rect([left, top], [filled, LEVEL_BAR_HEIGHT], LEVEL_BAR_COLOR);
+
+        let level = experience::level(total);
+        if level > 0 {
+            let center = width / 2.0;
+            let text_top = top - LEVEL_TEXT_OFFSET;
+            batch.label_centered(
+                level.to_string(),
+                [center + 1.0, text_top + 1.0],
+                LEVEL_SHADOW_COLOR,
+            );
+            batch.label_centered(level.to_string(), [center, text_top], LEVEL_COLOR);
+        }
+    }
+}
+
+/// Darkens the whole screen as the player falls asleep, added first so the rest of the HUD
+/// stays readable over it
+pub struct SleepFade;
+
+impl HudElement for SleepFade {
+    fn draw(&self, batch: &mut HudBatch, context: &HudContext) {
+        if context.sleep <= 0.0 {
+            return;
+        }
+        let [r, g, b] = SLEEP_COLOR;
+        batch.set_blend(BlendMode::Alpha);
+        batch.rect(
+            [0.0, 0.0],
+            context.screen_size,
+            [r, g, b, context.sleep * MAX_SLEEP_ALPHA],
+        );
+    }
+}
+
+/// The `columns` of `art` at `position`, a rect per run of pixels
+fn pixel_art(
+    batch: &mut HudBatch,
+    art: &[&str],
+    position: [f32; 2],
+    columns: Range<usize>,
+    color: Color,
+) {
+    for (row, pixels) in art.iter().enumerate() {
+        let pixels = pixels.as_bytes();
+        let y = position[1] + row as f32 * PIXEL;
+        let mut column = columns.start;
+        while column < columns.end {
+            if pixels[column] != b'#' {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < columns.end && pixels[column] == b'#' {
+                column += 1;
+            }
+            let x = position[0] + start as f32 * PIXEL;
+            let run = (column - start) as f32 * PIXEL;
+            batch.rect([x, y], [run, PIXEL], color);
+        }
+    }
+}
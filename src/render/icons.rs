@@ -0,0 +1,265 @@
+use anyhow::{anyhow, Context};
+use cgmath::{ortho, Matrix4, Point3, Vector3};
+use gl_lib::{
+    Attribute, FramebufferBinding, Mesh, RenderState, ShaderProgram, TextureFormat,
+    TextureFramebuffer,
+};
+use std::mem;
+
+use crate::assets::Assets;
+use crate::game::item::{ItemId, ItemRegistry};
+use crate::render::block_textures::{BlockLayers, BlockTextures};
+use crate::render::item_textures::ItemTextures;
+use crate::world::biome::Biome;
+use crate::world::block::{Block, Face, Tint};
+use crate::world::model::{CORNER_UVS, FACE_CORNERS};
+use crate::world::BlockRegistry;
+
+/// Pixels along each side of one icon in the atlas
+const ICON_SIZE: u32 = 32;
+/// Icons in each row of the atlas
+const COLUMNS: u32 = 16;
+/// Half the width of the view around a miniature, a cube seen corner on is about 1.63 blocks
+/// tall
+const VIEW_HALF_SIZE: f32 = 0.85;
+/// Seen from above the north-east corner, so stairs show their step
+const VIEW_DIRECTION: [f32; 3] = [1.0, 1.0, -1.0];
+
+const VERTEX_ATTRIBUTES: [Attribute; 4] = [
+    Attribute::float(3, mem::offset_of!(IconVertex, position)),
+    Attribute::float(3, mem::offset_of!(IconVertex, uv)),
+    Attribute::float(1, mem::offset_of!(IconVertex, shade)),
+    Attribute::float(3, mem::offset_of!(IconVertex, tint)),
+];
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+struct IconVertex {
+    /// In block units
+    position: [f32; 3],
+    /// Texture coordinates and the block texture layer
+    uv: [f32; 3],
+    shade: f32,
+    tint: [f32; 3],
+}
+
+/// Where one icon is in the atlas, as the bottom left and top right texture coordinates
+pub type IconRect = [f32; 4];
+
+/// Which texture array an item's vertices take their layers from
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ItemTexture {
+    Block,
+    Item,
+}
+
+/// A picture of every item for inventories, drawn once into one texture when the game
+/// starts: a small 3D view for most blocks and the texture laid flat for thin ones like
+/// torches and for items that aren't blocks.
+pub struct IconAtlas {
+    target: TextureFramebuffer,
+    /// Indexed by item id
+    icons: Vec<IconRect>,
+}
+
+impl IconAtlas {
+    /// Leaves the window's framebuffer bound, the caller sets its viewport again
+    pub fn bake(
+        assets: &mut Assets,
+        blocks: &BlockRegistry,
+        items: &ItemRegistry,
+        textures: &BlockTextures,
+        item_textures: &ItemTextures,
+    ) -> anyhow::Result<Self> {
+        let handle = assets.load::<ShaderProgram>("shader/icon")?;
+        let program = assets
+            .get(handle)
+            .ok_or_else(|| anyhow!("The icon shader isn't loaded"))?;
+        let layers = textures.layers();
+        let count = items.iter().count();
+        let rows = (count as u32).div_ceil(COLUMNS).max(1);
+        let (width, height) = (COLUMNS * ICON_SIZE, rows * ICON_SIZE);
+        let target = TextureFramebuffer::new(width, height, &[TextureFormat::Rgba8], true)
+            .context("Failed to create the icon atlas")?;
+
+        target.bind();
+        unsafe {
+            gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+        RenderState::OPAQUE.apply();
+        program.set_int(c"u_blocks", 0);
+        program.bind();
+        let [view_x, view_y, view_z] = VIEW_DIRECTION;
+        let center = Point3::new(0.5, 0.5, 0.5);
+        let view = Matrix4::look_at_rh(
+            center + Vector3::new(view_x, view_y, view_z),
+            center,
+            Vector3::unit_y(),
+        );
+        let size = VIEW_HALF_SIZE;
+        let miniature: [[f32; 4]; 4] = (ortho(-size, size, -size, size, -4.0, 4.0) * view).into();
+        let flat: [[f32; 4]; 4] = ortho(0.0, 1.0, 0.0, 1.0, -1.0, 1.0).into();
+
+        let mut icons = Vec::with_capacity(count);
+        for (index, (id, item)) in items.iter().enumerate() {
+            let (column, row) = (index as u32 % COLUMNS, index as u32 / COLUMNS);
+            gl_lib::set_viewport_rect(column * ICON_SIZE, row * ICON_SIZE, ICON_SIZE, ICON_SIZE);
+            let block = item
+                .shown_block()
+                .and_then(|block| Some((blocks.get(block)?, block)));
+            let (vertices, view_projection) = match block {
+                Some((block, block_id)) => {
+                    textures.bind(0);
+                    let block_layers = &layers[block_id.0 as usize];
+                    if block.flat_icon {
+                        (flat_quad(block_layers.faces[Face::North as usize]), &flat)
+                    } else {
+                        (miniature_faces(block, block_layers), &miniature)
+                    }
+                }
+                None => {
+                    item_textures.bind(0);
+                    (flat_quad(item_textures.layer(id)), &flat)
+                }
+            };
+            program.set_mat4(c"u_view_projection", view_projection);
+            quad_mesh(&vertices).draw();
+
+            // Pulled in by half a pixel so filtering doesn't reach the neighbouring icons
+            let inset = 0.5;
+            icons.push([
+                (column as f32 * ICON_SIZE as f32 + inset) / width as f32,
+                (row as f32 * ICON_SIZE as f32 + inset) / height as f32,
+                ((column + 1) as f32 * ICON_SIZE as f32 - inset) / width as f32,
+                ((row + 1) as f32 * ICON_SIZE as f32 - inset) / height as f32,
+            ]);
+        }
+        FramebufferBinding::DEFAULT.bind();
+        Ok(IconAtlas { target, icons })
+    }
+
+    /// None for ids that no item uses
+    pub fn icon(&self, item: ItemId) -> Option<IconRect> {
+        self.icons.get(item.0 as usize).copied()
+    }
+
+    pub fn bind(&self, unit: u32) {
+        self.target.color(0).bind(unit);
+    }
+}
+
+/// The block's shape in block units from its bottom corner, laid out for shaders reading the
+/// same attributes as icon.vert
+pub fn block_mesh(block: &Block, layers: &BlockLayers) -> Mesh {
+    quad_mesh(&miniature_faces(block, layers))
+}
+
+/// `item`'s shape in a block's space like [`block_mesh`]: the block itself for block items
+/// and a thin card standing in the middle for anything else. None for unknown ids.
+pub fn item_mesh(
+    item: ItemId,
+    items: &ItemRegistry,
+    blocks: &BlockRegistry,
+    layers: &[BlockLayers],
+    item_textures: &ItemTextures,
+) -> Option<(Mesh, ItemTexture)> {
+    match items.get(item)?.shown_block() {
+        Some(block) => {
+            let mesh = block_mesh(blocks.get(block)?, layers.get(block.0 as usize)?);
+            Some((mesh, ItemTexture::Block))
+        }
+        None => Some((card(item_textures.layer(item)), ItemTexture::Item)),
+    }
+}
+
+/// `vertices` are the corners of quads, four at a time
+fn quad_mesh(vertices: &[IconVertex]) -> Mesh {
+    let indices: Vec<u32> = (0..vertices.len() as u32 / 4)
+        .flat_map(|quad| [0, 1, 2, 0, 2, 3].map(|offset| quad * 4 + offset))
+        .collect();
+    Mesh::new(vertices, &indices, &VERTEX_ATTRIBUTES)
+}
+
+/// The block's faces or model quads as they are in the world, shaded by which way they face
+/// since there's no light to go by
+fn miniature_faces(block: &Block, layers: &BlockLayers) -> Vec<IconVertex> {
+    let [grass, foliage] = {
+        let plains = Biome::Plains.parameters();
+        [plains.grass_color, plains.foliage_color]
+    };
+    let tint = |face: Face| match block.tints[face as usize] {
+        Some(Tint::Grass) => grass,
+        Some(Tint::Foliage) => foliage,
+        None => [1.0; 3],
+    };
+    let shade = |face: Face| match face {
+        Face::Up => 1.0,
+        Face::North => 0.8,
+        Face::East => 0.6,
+        _ => 0.5,
+    };
+    let mut vertices = Vec::new();
+    match &block.model {
+        Some(model) => {
+            for (quad, layer) in model.quads.iter().zip(&layers.model) {
+                for (corner, [u, v]) in quad.corners.into_iter().zip(quad.uvs) {
+                    vertices.push(IconVertex {
+                        position: corner,
+                        uv: [u, v, *layer as f32],
+                        shade: shade(quad.face),
+                        tint: tint(quad.face),
+                    });
+                }
+            }
+        }
+        None => {
+            for face in Face::ALL {
+                let layer = layers.faces[face as usize] as f32;
+                for (corner, [u, v]) in FACE_CORNERS[face as usize].into_iter().zip(CORNER_UVS) {
+                    vertices.push(IconVertex {
+                        position: corner,
+                        uv: [u, v, layer],
+                        shade: shade(face),
+                        tint: tint(face),
+                    });
+                }
+            }
+        }
+    }
+    vertices
+}
+
+/// Texture `layer` standing upright halfway along Z, facing both ways so culling never hides
+/// it
+fn card(layer: u32) -> Mesh {
+    let front = flat_quad(layer);
+    let back = front.iter().rev().map(|vertex| IconVertex {
+        shade: 0.8,
+        ..*vertex
+    });
+    let vertices: Vec<IconVertex> = front
+        .iter()
+        .copied()
+        .chain(back)
+        .map(|vertex| IconVertex {
+            position: [vertex.position[0], vertex.position[1], 0.5],
+            ..vertex
+        })
+        .collect();
+    quad_mesh(&vertices)
+}
+
+/// Texture `layer` filling the whole icon
+fn flat_quad(layer: u32) -> Vec<IconVertex> {
+    let layer = layer as f32;
+    CORNER_UVS
+        .into_iter()
+        .map(|[u, v]| IconVertex {
+            position: [u, v, 0.0],
+            uv: [u, v, layer],
+            shade: 1.0,
+            tint: [1.0; 3],
+        })
+        .collect()
+}
@@ -0,0 +1,79 @@
+use anyhow::{bail, Context};
+use gl_lib::Texture2DArray;
+use std::collections::HashMap;
+
+use crate::assets::Assets;
+use crate::game::item::{ItemId, ItemRegistry};
+use crate::render::block_textures;
+
+/// Edge length every item texture must have, the same as block textures
+const TEXTURE_SIZE: u32 = 16;
+const TEXTURE_DIR: &str = "textures/item";
+/// Layer 0, shown for textures that are missing or fail to load
+const MISSING_LAYER: u32 = 0;
+
+/// The flat pictures of every item that isn't a block, one layer each in a texture array.
+/// Block items are drawn from the block textures instead.
+pub struct ItemTextures {
+    texture: Texture2DArray,
+    /// Indexed by item id, the missing layer for block items
+    layers: Vec<u32>,
+}
+
+impl ItemTextures {
+    pub fn load(assets: &Assets, items: &ItemRegistry) -> anyhow::Result<Self> {
+        let mut pixels = vec![block_textures::missing_texture()];
+        let mut layer_of: HashMap<&str, u32> = HashMap::new();
+        let mut layers = Vec::new();
+        for (_, item) in items.iter() {
+            if item.shown_block().is_some() {
+                layers.push(MISSING_LAYER);
+                continue;
+            }
+            if let Some(layer) = layer_of.get(item.texture.as_str()) {
+                layers.push(*layer);
+                continue;
+            }
+            let layer = match load_texture(assets, &item.texture) {
+                Ok(texture) => {
+                    pixels.push(texture);
+                    pixels.len() as u32 - 1
+                }
+                Err(err) => {
+                    log::warn!("{err:#}");
+                    MISSING_LAYER
+                }
+            };
+            layer_of.insert(&item.texture, layer);
+            layers.push(layer);
+        }
+
+        let slices: Vec<&[u8]> = pixels.iter().map(Vec::as_slice).collect();
+        let texture = Texture2DArray::from_rgba8_layers(TEXTURE_SIZE, TEXTURE_SIZE, &slices)
+            .context("Failed to upload item textures")?;
+        Ok(ItemTextures { texture, layers })
+    }
+
+    /// The missing texture's layer for block items and unknown ids
+    pub fn layer(&self, item: ItemId) -> u32 {
+        self.layers
+            .get(item.0 as usize)
+            .copied()
+            .unwrap_or(MISSING_LAYER)
+    }
+
+    pub fn bind(&self, unit: u32) {
+        self.texture.bind(unit);
+    }
+}
+
+fn load_texture(assets: &Assets, name: &str) -> anyhow::Result<Vec<u8>> {
+    let path = format!("{TEXTURE_DIR}/{name}.png");
+    let bytes = assets.read(&path)?;
+    let image =
+        image::load_from_memory(&bytes).with_context(|| format!("Failed to decode {path}"))?;
+    if image.width() != TEXTURE_SIZE || image.height() != TEXTURE_SIZE {
+        bail!("{path} is not {TEXTURE_SIZE}x{TEXTURE_SIZE}");
+    }
+    Ok(image.flipv().into_rgba8().into_raw())
+}
@@ -0,0 +1,78 @@
+use cgmath::{MetricSpace, Point3};
+use gl_lib::{Buffer, BufferTarget, BufferUsage, ShaderProgram};
+
+/// Most lights the shaders take at once, the closest ones to the camera win. Matches the
+/// array in lighting.glsl.
+pub const MAX_LIGHTS: usize = 32;
+/// Uniform buffer binding point of the `Lights` block
+const LIGHTS_BINDING: u32 = 0;
+
+/// Light that isn't part of the world's light levels, so it can move every frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PointLight {
+    pub position: Point3<f32>,
+    /// Linear, 1 for as bright as full block light
+    pub color: [f32; 3],
+    /// Blocks until it's faded out completely
+    pub radius: f32,
+}
+
+/// One light laid out like `PointLight` in lighting.glsl under std140
+#[derive(Debug, Copy, Clone, Default)]
+#[repr(C)]
+struct GpuLight {
+    position_radius: [f32; 4],
+    color: [f32; 4],
+}
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+struct LightBlock {
+    /// Only x is used, std140 pads the count out to a whole vec4 anyway
+    count: [i32; 4],
+    lights: [GpuLight; MAX_LIGHTS],
+}
+
+/// This frame's point lights in a uniform buffer, read by every program that calls
+/// [`use_lights`]
+pub struct LightBuffer {
+    buffer: Buffer,
+}
+
+impl LightBuffer {
+    pub fn new() -> Self {
+        let buffer = Buffer::new(BufferTarget::Uniform);
+        buffer.allocate(std::mem::size_of::<LightBlock>(), BufferUsage::Dynamic);
+        LightBuffer { buffer }
+    }
+
+    /// Replace the lights with the ones closest to `camera` out of `lights`, and make them
+    /// the ones [`use_lights`] reads
+    pub fn upload(&self, lights: &[PointLight], camera: Point3<f32>) {
+        let mut lights = lights.to_vec();
+        lights.sort_by(|a, b| {
+            let distance = |light: &PointLight| light.position.distance2(camera);
+            distance(a).total_cmp(&distance(b))
+        });
+        lights.truncate(MAX_LIGHTS);
+        let mut block = LightBlock {
+            count: [lights.len() as i32, 0, 0, 0],
+            lights: [GpuLight::default(); MAX_LIGHTS],
+        };
+        for (gpu, light) in block.lights.iter_mut().zip(&lights) {
+            let [x, y, z] = light.position.into();
+            let [red, green, blue] = light.color;
+            *gpu = GpuLight {
+                position_radius: [x, y, z, light.radius],
+                color: [red, green, blue, 0.0],
+            };
+        }
+        self.buffer.set_sub_data(0, &[block]);
+        self.buffer.bind_base(LIGHTS_BINDING);
+    }
+}
+
+/// Point the `Lights` block of `program` at the last uploaded [`LightBuffer`]
+pub fn use_lights(program: &ShaderProgram) {
+    program.bind_uniform_block(c"Lights", LIGHTS_BINDING);
+}
@@ -0,0 +1,186 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+
+use crate::render::block_textures::BlockLayers;
+use crate::render::mesher::{self, ChunkMeshes, ChunkSnapshot, Lod, MeshingMode};
+use crate::render::visibility::ChunkVisibility;
+use crate::world::chunk::ChunkPos;
+use crate::world::BlockRegistry;
+
+/// Worker threads at most, the render and game threads need a core too
+const MAX_WORKERS: usize = 4;
+
+pub struct MeshJob {
+    pub position: ChunkPos,
+    /// Handed back with the result so outdated meshes can be told apart, has to increase
+    /// with every job
+    pub version: u64,
+    /// Lower runs first, e.g. the squared distance to the camera
+    pub priority: u32,
+    pub snapshot: ChunkSnapshot,
+    pub mode: MeshingMode,
+    pub smooth_lighting: bool,
+    pub lod: Lod,
+}
+
+pub struct MeshResult {
+    pub position: ChunkPos,
+    pub version: u64,
+    pub meshes: ChunkMeshes,
+    pub visibility: ChunkVisibility,
+}
+
+/// Meshes chunks on background threads, most urgent job first. Results only hold CPU side
+/// buffers, uploading them is left to the render thread.
+pub struct MeshPool {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+    results: Receiver<MeshResult>,
+}
+
+struct Shared {
+    queue: Mutex<BinaryHeap<Queued>>,
+    available: Condvar,
+    shutdown: AtomicBool,
+    blocks: Arc<BlockRegistry>,
+    layers: Arc<[BlockLayers]>,
+}
+
+/// Ordered so the max-heap pops the lowest priority, oldest version first among equals
+struct Queued(MeshJob);
+
+impl MeshPool {
+    /// `layers` holds the texture layers of each block id, see [`mesher::mesh_chunk`]
+    pub fn new(blocks: Arc<BlockRegistry>, layers: Arc<[BlockLayers]>) -> Self {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(BinaryHeap::new()),
+            available: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+            blocks,
+            layers,
+        });
+        let worker_count = thread::available_parallelism()
+            .map_or(1, |count| count.get().saturating_sub(2))
+            .clamp(1, MAX_WORKERS);
+        let (sender, results) = mpsc::channel();
+        let workers = (0..worker_count)
+            .map(|index| {
+                let shared = Arc::clone(&shared);
+                let sender = sender.clone();
+                thread::Builder::new()
+                    .name(format!("mesher {index}"))
+                    .spawn(move || work(&shared, &sender))
+                    .expect("failed to spawn a mesher thread")
+            })
+            .collect();
+        MeshPool {
+            shared,
+            workers,
+            results,
+        }
+    }
+
+    pub fn submit(&self, job: MeshJob) {
+        self.shared.lock_queue().push(Queued(job));
+        self.shared.available.notify_one();
+    }
+
+    /// Drop every job that hasn't started yet
+    pub fn clear(&self) {
+        self.shared.lock_queue().clear();
+    }
+
+    /// Jobs no worker has taken yet
+    pub fn queued(&self) -> usize {
+        self.shared.lock_queue().len()
+    }
+
+    /// Results finished since the last call, never blocks
+    pub fn finished(&self) -> impl Iterator<Item = MeshResult> + '_ {
+        self.results.try_iter()
+    }
+}
+
+impl Drop for MeshPool {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, AtomicOrdering::Relaxed);
+        self.clear();
+        self.shared.available.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Shared {
+    fn lock_queue(&self) -> MutexGuard<'_, BinaryHeap<Queued>> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn work(shared: &Shared, results: &Sender<MeshResult>) {
+    loop {
+        let job = {
+            let mut queue = shared.lock_queue();
+            loop {
+                if shared.shutdown.load(AtomicOrdering::Relaxed) {
+                    return;
+                }
+                if let Some(Queued(job)) = queue.pop() {
+                    break job;
+                }
+                queue = shared
+                    .available
+                    .wait(queue)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        };
+
+        let meshes = mesher::mesh_chunk(
+            &job.snapshot,
+            &shared.blocks,
+            &shared.layers,
+            job.mode,
+            job.smooth_lighting,
+            job.lod,
+        );
+        let visibility = ChunkVisibility::compute(job.snapshot.chunk(), &shared.blocks);
+        let result = MeshResult {
+            position: job.position,
+            version: job.version,
+            meshes,
+            visibility,
+        };
+        if results.send(result).is_err() {
+            return;
+        }
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .0
+            .priority
+            .cmp(&self.0.priority)
+            .then_with(|| other.0.version.cmp(&self.0.version))
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
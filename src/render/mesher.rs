@@ -0,0 +1,745 @@
+use gl_lib::Attribute;
+use serde::{Deserialize, Serialize};
+use std::mem;
+
+use crate::render::block_textures::BlockLayers;
+use crate::world::block::{BlockRegistry, Face};
+use crate::world::chunk::{Chunk, ChunkPos, LocalPos, CHUNK_SIZE};
+use crate::world::generator::TerrainGenerator;
+use crate::world::light::{Light, MAX_LIGHT};
+use crate::world::model::{ModelQuad, CORNER_UVS, FACE_CORNERS};
+use crate::world::{BlockId, World};
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MeshingMode {
+    /// One quad per visible block face
+    Naive,
+    /// Neighbouring faces of the same block merged into larger quads, far fewer vertices for
+    /// a bit more meshing time
+    #[default]
+    Greedy,
+}
+
+/// How coarsely a chunk is meshed. Far chunks are built from bigger voxels, each standing
+/// in for a cube of blocks, so pushing the render distance out doesn't cost as many triangles.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Lod {
+    #[default]
+    Full,
+    /// Voxels of 2x2x2 blocks
+    Half,
+    /// Voxels of 4x4x4 blocks
+    Quarter,
+}
+
+impl Lod {
+    /// Full detail within `lod_distance` chunks of the camera, half within twice that and
+    /// quarter beyond. A `lod_distance` of 0 keeps everything at full detail.
+    pub fn for_distance(distance: u32, lod_distance: u32) -> Self {
+        if lod_distance == 0 || distance < lod_distance {
+            Lod::Full
+        } else if distance < lod_distance * 2 {
+            Lod::Half
+        } else {
+            Lod::Quarter
+        }
+    }
+
+    /// Blocks along each side of a voxel
+    pub fn scale(self) -> i32 {
+        match self {
+            Lod::Full => 1,
+            Lod::Half => 2,
+            Lod::Quarter => 4,
+        }
+    }
+}
+
+/// Four packed words per vertex, unpacked again in `chunk.vert`
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct BlockVertex {
+    /// Bits 0-14: whole blocks of x, y and z relative to the chunk's origin, 5 bits each since
+    /// they reach 16. Bits 15-24: whole blocks of texture u and v, so merged faces tile their
+    /// texture. Bits 25-27: the face in [`Face::ALL`] order. Bits 28-29: the ambient
+    /// occlusion level. Bit 30: set on water.
+    pub position_uv: u32,
+    /// Bits 0-15: texture array layer. Bits 16-31: sky then block light, 0 to 255 each.
+    pub layer_light: u32,
+    /// Sixteenths of a block added to x, y, z, u and v in that order, 4 bits each. Only block
+    /// models use them.
+    pub fraction: u32,
+    /// Red, green and blue the texture is multiplied with, 8 bits each. White for faces
+    /// without a biome tint. Bits 24-31: how much the face glows, 0 to 255.
+    pub tint: u32,
+}
+
+impl BlockVertex {
+    /// Positions and texture coordinates are rounded to sixteenths
+    fn pack(
+        position: [f32; 3],
+        uv: [f32; 2],
+        face: Face,
+        ao: u8,
+        layer: u32,
+        light: [f32; 2],
+        color: FaceColor,
+    ) -> Self {
+        let sixteenths = |value: f32| (value * 16.0).round().max(0.0) as u32;
+        let [x, y, z] = position.map(sixteenths);
+        let [u, v] = uv.map(sixteenths);
+        let [sky, block] = light.map(|level| (level / MAX_LIGHT as f32 * 255.0).round() as u32);
+        let [red, green, blue, emissive] =
+            [color.tint[0], color.tint[1], color.tint[2], color.emissive]
+                .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u32);
+        BlockVertex {
+            position_uv: x >> 4
+                | (y >> 4) << 5
+                | (z >> 4) << 10
+                | (u >> 4) << 15
+                | (v >> 4) << 20
+                | (face as u32) << 25
+                | u32::from(ao) << 28
+                | u32::from(color.water) << 30,
+            layer_light: layer.min(u16::MAX as u32) | sky << 16 | block << 24,
+            fraction: (x & 15) | (y & 15) << 4 | (z & 15) << 8 | (u & 15) << 12 | (v & 15) << 16,
+            tint: red | green << 8 | blue << 16 | emissive << 24,
+        }
+    }
+}
+
+pub const BLOCK_VERTEX_ATTRIBUTES: [Attribute; 4] = [
+    Attribute::unsigned(1, mem::offset_of!(BlockVertex, position_uv)),
+    Attribute::unsigned(1, mem::offset_of!(BlockVertex, layer_light)),
+    Attribute::unsigned(1, mem::offset_of!(BlockVertex, fraction)),
+    Attribute::unsigned(1, mem::offset_of!(BlockVertex, tint)),
+];
+/// Tint of faces that don't have one
+const WHITE: [f32; 3] = [1.0; 3];
+
+/// What a face's texture is multiplied with and how much it glows
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct FaceColor {
+    tint: [f32; 3],
+    /// From 0 to 1, see [`Block::emissive`](crate::world::block::Block::emissive)
+    emissive: f32,
+    /// Translucent fluid, which gets waves and reflections with fancy water
+    water: bool,
+}
+
+/// World axes the texture's U and V run along on each face, in [`Face::ALL`] order
+const TEXTURE_AXES: [(usize, usize); 6] = [(2, 1), (2, 1), (0, 2), (0, 2), (0, 1), (0, 1)];
+const QUAD_INDICES: [u32; 6] = [0, 1, 2, 2, 3, 0];
+/// Split along the other diagonal, see [`ChunkMesh::push_quad`]
+const FLIPPED_QUAD_INDICES: [u32; 6] = [1, 2, 3, 3, 0, 1];
+
+/// How each corner of a face is lit, in [`FACE_CORNERS`] order, see [`ChunkSnapshot::shading`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct FaceShading {
+    ao: [u8; 4],
+    /// Sky and block light, fractional where smooth lighting averaged it
+    light: [[f32; 2]; 4],
+}
+
+/// Triangles for one chunk, still on the CPU
+#[derive(Debug, Default)]
+pub struct ChunkMesh {
+    pub vertices: Vec<BlockVertex>,
+    pub indices: Vec<u32>,
+}
+
+impl ChunkMesh {
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// A face of `size` blocks starting at the block `start`, the size along the face's normal
+    /// has to be 1
+    fn push_quad(
+        &mut self,
+        start: [f32; 3],
+        size: [f32; 3],
+        face: Face,
+        layer: u32,
+        shading: FaceShading,
+        color: FaceColor,
+    ) {
+        let (u_axis, v_axis) = TEXTURE_AXES[face as usize];
+        let corners = FACE_CORNERS[face as usize]
+            .map(|corner| [0, 1, 2].map(|axis| start[axis] + corner[axis] * size[axis]));
+        let uvs = CORNER_UVS.map(|[u, v]| [u * size[u_axis], v * size[v_axis]]);
+        self.push_corners(corners, uvs, face, layer, shading, color);
+    }
+
+    /// A face of a block model, `origin` is the block's corner
+    fn push_model_quad(
+        &mut self,
+        origin: [f32; 3],
+        quad: &ModelQuad,
+        layer: u32,
+        light: Light,
+        color: FaceColor,
+    ) {
+        let corners = quad
+            .corners
+            .map(|corner| [0, 1, 2].map(|axis| origin[axis] + corner[axis]));
+        let light = [light.sky, light.block].map(f32::from);
+        let shading = FaceShading {
+            ao: [3; 4],
+            light: [light; 4],
+        };
+        self.push_corners(corners, quad.uvs, quad.face, layer, shading, color);
+    }
+
+    fn push_corners(
+        &mut self,
+        corners: [[f32; 3]; 4],
+        uvs: [[f32; 2]; 4],
+        face: Face,
+        layer: u32,
+        shading: FaceShading,
+        color: FaceColor,
+    ) {
+        let first = self.vertices.len() as u32;
+        for (index, (position, uv)) in corners.into_iter().zip(uvs).enumerate() {
+            self.vertices.push(BlockVertex::pack(
+                position,
+                uv,
+                face,
+                shading.ao[index],
+                layer,
+                shading.light[index],
+                color,
+            ));
+        }
+        let ao = shading.ao;
+        // Brightness is interpolated across each triangle, so the diagonal the quad is split
+        // along shows. Splitting between the brighter pair of corners keeps a single dark
+        // corner from smearing over the whole face.
+        let indices = if ao[0] + ao[2] < ao[1] + ao[3] {
+            FLIPPED_QUAD_INDICES
+        } else {
+            QUAD_INDICES
+        };
+        self.indices
+            .extend(indices.iter().map(|index| first + index));
+    }
+}
+
+/// A chunk's faces split by the pass that draws them
+#[derive(Debug, Default)]
+pub struct ChunkMeshes {
+    pub opaque: ChunkMesh,
+    /// Faces of translucent blocks, blended in after everything opaque
+    pub translucent: ChunkMesh,
+}
+
+impl ChunkMeshes {
+    fn for_block(&mut self, block: BlockId, blocks: &BlockRegistry) -> &mut ChunkMesh {
+        if blocks.get(block).is_some_and(|block| block.translucent) {
+            &mut self.translucent
+        } else {
+            &mut self.opaque
+        }
+    }
+}
+
+/// Every face of the chunk that can be seen from a neighbouring block, cubes merged
+/// according to `mode` and block models added face by face. `layers` holds the texture
+/// layers of each block id.
+pub fn mesh_chunk(
+    snapshot: &ChunkSnapshot,
+    blocks: &BlockRegistry,
+    layers: &[BlockLayers],
+    mode: MeshingMode,
+    smooth_lighting: bool,
+    lod: Lod,
+) -> ChunkMeshes {
+    let mut meshes = ChunkMeshes::default();
+    if snapshot.chunk.is_empty() {
+        return meshes;
+    }
+    let coarse;
+    let snapshot = if lod == Lod::Full {
+        snapshot
+    } else {
+        coarse = snapshot.downsample(lod.scale(), blocks);
+        &coarse
+    };
+
+    let layer = |block: BlockId, face: Face| {
+        layers
+            .get(block.0 as usize)
+            .map_or(0, |layers| layers.faces[face as usize])
+    };
+    match mode {
+        MeshingMode::Naive => mesh_naive(&mut meshes, snapshot, blocks, layer, smooth_lighting),
+        MeshingMode::Greedy => mesh_greedy(&mut meshes, snapshot, blocks, layer, smooth_lighting),
+    }
+    mesh_models(&mut meshes, snapshot, blocks, layers);
+    meshes
+}
+
+/// Faces of every block with a model, each lit by the block it's in or, for faces on the
+/// block's edge, the one it looks into. Faces against an opaque neighbour are left out.
+fn mesh_models(
+    meshes: &mut ChunkMeshes,
+    snapshot: &ChunkSnapshot,
+    blocks: &BlockRegistry,
+    layers: &[BlockLayers],
+) {
+    let size = snapshot.size();
+    for y in 0..size {
+        for z in 0..size {
+            for x in 0..size {
+                let block = snapshot.block([x, y, z]);
+                let Some(model) = blocks.get(block).and_then(|block| block.model.as_ref()) else {
+                    continue;
+                };
+                let model_layers = layers.get(block.0 as usize).map(|layers| &layers.model);
+                for (index, quad) in model.quads.iter().enumerate() {
+                    let light_from = match quad.cullface {
+                        Some(side) => {
+                            let normal = side.normal();
+                            let neighbor = [x + normal[0], y + normal[1], z + normal[2]];
+                            let covering = snapshot.block(neighbor);
+                            if blocks.get(covering).is_some_and(|block| !block.transparent) {
+                                continue;
+                            }
+                            neighbor
+                        }
+                        None => [x, y, z],
+                    };
+                    let layer = model_layers
+                        .and_then(|layers| layers.get(index))
+                        .copied()
+                        .unwrap_or(0);
+                    let origin = [x, y, z].map(|value| value as f32);
+                    let light = snapshot.light(light_from);
+                    let color = snapshot.color([x, y, z], block, quad.face, blocks);
+                    meshes
+                        .for_block(block, blocks)
+                        .push_model_quad(origin, quad, layer, light, color);
+                }
+            }
+        }
+    }
+}
+
+fn mesh_naive(
+    meshes: &mut ChunkMeshes,
+    snapshot: &ChunkSnapshot,
+    blocks: &BlockRegistry,
+    layer: impl Fn(BlockId, Face) -> u32,
+    smooth_lighting: bool,
+) {
+    let size = snapshot.size();
+    let scale = snapshot.scale as f32;
+    for y in 0..size {
+        for z in 0..size {
+            for x in 0..size {
+                let block = snapshot.block([x, y, z]);
+                if block == BlockId::AIR || !is_cube(block, blocks) {
+                    continue;
+                }
+                for face in Face::ALL {
+                    if snapshot.shows_face([x, y, z], block, face, blocks) {
+                        let start = [x, y, z].map(|value| value as f32 * scale);
+                        let shading = snapshot.shading([x, y, z], face, blocks, smooth_lighting);
+                        let color = snapshot.color([x, y, z], block, face, blocks);
+                        let mesh = meshes.for_block(block, blocks);
+                        let layer = layer(block, face);
+                        mesh.push_quad(start, [scale; 3], face, layer, shading, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// For each face direction and slice of the chunk, marks the visible faces and then covers
+/// them with as few rectangles as it can, growing each along U first and then along V. Only
+/// faces shaded and tinted the same at every corner merge.
+fn mesh_greedy(
+    meshes: &mut ChunkMeshes,
+    snapshot: &ChunkSnapshot,
+    blocks: &BlockRegistry,
+    layer: impl Fn(BlockId, Face) -> u32,
+    smooth_lighting: bool,
+) {
+    const SIZE: usize = CHUNK_SIZE as usize;
+    // Coarse snapshots only fill the corner of the mask their voxels cover
+    let size = snapshot.size() as usize;
+    let scale = snapshot.scale as f32;
+    for face in Face::ALL {
+        let (u_axis, v_axis) = TEXTURE_AXES[face as usize];
+        let normal_axis = 3 - u_axis - v_axis;
+        for depth in 0..snapshot.size() {
+            let position = |u: usize, v: usize| {
+                let mut position = [0; 3];
+                position[normal_axis] = depth;
+                position[u_axis] = u as i32;
+                position[v_axis] = v as i32;
+                position
+            };
+
+            let mut mask = [[None; SIZE]; SIZE];
+            for (v, row) in mask.iter_mut().enumerate().take(size) {
+                for (u, cell) in row.iter_mut().enumerate().take(size) {
+                    let position = position(u, v);
+                    let block = snapshot.block(position);
+                    if block != BlockId::AIR
+                        && is_cube(block, blocks)
+                        && snapshot.shows_face(position, block, face, blocks)
+                    {
+                        let shading = snapshot.shading(position, face, blocks, smooth_lighting);
+                        let color = snapshot.color(position, block, face, blocks);
+                        *cell = Some((block, shading, color));
+                    }
+                }
+            }
+
+            for v in 0..size {
+                let mut u = 0;
+                while u < size {
+                    let Some(cell) = mask[v][u] else {
+                        u += 1;
+                        continue;
+                    };
+                    let mut width = 1;
+                    while u + width < size && mask[v][u + width] == Some(cell) {
+                        width += 1;
+                    }
+                    let mut height = 1;
+                    while v + height < size
+                        && mask[v + height][u..u + width]
+                            .iter()
+                            .all(|other| *other == Some(cell))
+                    {
+                        height += 1;
+                    }
+                    for row in &mut mask[v..v + height] {
+                        row[u..u + width].fill(None);
+                    }
+
+                    let start = position(u, v).map(|value| value as f32 * scale);
+                    let mut extent = [scale; 3];
+                    extent[u_axis] = width as f32 * scale;
+                    extent[v_axis] = height as f32 * scale;
+                    let (block, shading, color) = cell;
+                    let mesh = meshes.for_block(block, blocks);
+                    mesh.push_quad(start, extent, face, layer(block, face), shading, color);
+                    u += width;
+                }
+            }
+        }
+    }
+}
+
+/// Blocks without a model, which the cube meshers handle
+fn is_cube(block: BlockId, blocks: &BlockRegistry) -> bool {
+    blocks.get(block).is_none_or(|block| block.model.is_none())
+}
+
+/// Copies of a chunk and the 26 around it, everything meshing it needs so it can happen away
+/// from the world. Positions are relative to the chunk's origin and in voxels, which are
+/// single blocks unless the snapshot was downsampled.
+pub struct ChunkSnapshot {
+    chunk: Chunk,
+    /// The 3x3x3 chunks around it in [`neighbor_index`] order, None where a neighbour isn't
+    /// loaded and for the chunk itself
+    neighbors: Vec<Option<Chunk>>,
+    /// Blocks along each side of a voxel, a downsampled chunk keeps its voxels in the corner
+    /// of the chunk at the origin
+    scale: i32,
+    /// Biome colors of each column of blocks by `z * CHUNK_SIZE + x`, see
+    /// [`TerrainGenerator::tint_colors`]. Empty when there's nothing to mesh.
+    tints: Vec<[[f32; 3]; 2]>,
+}
+
+impl ChunkSnapshot {
+    /// None if the chunk isn't loaded. The generator gives the biome colors.
+    pub fn capture(
+        world: &World,
+        generator: &TerrainGenerator,
+        position: ChunkPos,
+    ) -> Option<Self> {
+        let chunk = world.chunk(position)?.clone();
+        // Nothing to mesh, so nothing to look at next to it either
+        if chunk.is_empty() {
+            return Some(ChunkSnapshot {
+                chunk,
+                neighbors: Vec::new(),
+                scale: 1,
+                tints: Vec::new(),
+            });
+        }
+        // Same order as neighbor_index
+        let mut neighbors = Vec::with_capacity(27);
+        for y in -1..=1 {
+            for z in -1..=1 {
+                for x in -1..=1 {
+                    let neighbor = ChunkPos::new(position.x + x, position.y + y, position.z + z);
+                    let is_self = [x, y, z] == [0; 3];
+                    neighbors.push(world.chunk(neighbor).filter(|_| !is_self).cloned());
+                }
+            }
+        }
+        let origin = position.origin();
+        let mut tints = Vec::with_capacity((CHUNK_SIZE * CHUNK_SIZE) as usize);
+        for z in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                tints.push(generator.tint_colors(origin.x + x, origin.z + z));
+            }
+        }
+        Some(ChunkSnapshot {
+            chunk,
+            neighbors,
+            scale: 1,
+            tints,
+        })
+    }
+
+    pub fn chunk(&self) -> &Chunk {
+        &self.chunk
+    }
+
+    /// Voxels along each side of the chunk
+    fn size(&self) -> i32 {
+        CHUNK_SIZE / self.scale
+    }
+
+    /// The same chunks in voxels of `scale` blocks, see [`downsample_chunk`]
+    fn downsample(&self, scale: i32, blocks: &BlockRegistry) -> ChunkSnapshot {
+        let downsample = |chunk: &Chunk| downsample_chunk(chunk, scale, blocks);
+        ChunkSnapshot {
+            chunk: downsample(&self.chunk),
+            neighbors: self
+                .neighbors
+                .iter()
+                .map(|neighbor| neighbor.as_ref().map(downsample))
+                .collect(),
+            scale,
+            tints: self.tints.clone(),
+        }
+    }
+
+    /// Unloaded neighbours count as air so the edge of the world is closed
+    fn block(&self, position: [i32; 3]) -> BlockId {
+        self.locate(position)
+            .map_or(BlockId::AIR, |(chunk, local)| chunk.get(local))
+    }
+
+    /// Unloaded neighbours count as open sky
+    fn light(&self, position: [i32; 3]) -> Light {
+        self.locate(position)
+            .map_or(Light::OPEN, |(chunk, local)| chunk.light(local))
+    }
+
+    /// Tint, glow and surface of a face of `block` in the voxel at `position`
+    fn color(
+        &self,
+        position: [i32; 3],
+        block: BlockId,
+        face: Face,
+        blocks: &BlockRegistry,
+    ) -> FaceColor {
+        let definition = blocks.get(block);
+        FaceColor {
+            tint: self.tint(position, block, face, blocks),
+            emissive: definition.map_or(0.0, |block| block.emissive),
+            water: definition.is_some_and(|block| block.fluid.is_some() && block.translucent),
+        }
+    }
+
+    /// What a face of `block` in the voxel at `position` is multiplied with, the color of the
+    /// voxel's first column for coarse snapshots
+    fn tint(
+        &self,
+        position: [i32; 3],
+        block: BlockId,
+        face: Face,
+        blocks: &BlockRegistry,
+    ) -> [f32; 3] {
+        let Some(tint) = blocks
+            .get(block)
+            .and_then(|block| block.tints[face as usize])
+        else {
+            return WHITE;
+        };
+        let [x, z] =
+            [position[0], position[2]].map(|value| (value * self.scale).clamp(0, CHUNK_SIZE - 1));
+        self.tints
+            .get((z * CHUNK_SIZE + x) as usize)
+            .map_or(WHITE, |colors| colors[tint as usize])
+    }
+
+    /// Reaches at most one voxel into the neighbours
+    fn locate(&self, position: [i32; 3]) -> Option<(&Chunk, LocalPos)> {
+        let size = self.size();
+        let offset = position.map(|value| value.div_euclid(size));
+        let [x, y, z] = position.map(|value| value.rem_euclid(size) as u8);
+        let local = LocalPos::new(x, y, z);
+        if offset == [0; 3] {
+            return Some((&self.chunk, local));
+        }
+        let chunk = self.neighbors.get(neighbor_index(offset)?)?.as_ref()?;
+        Some((chunk, local))
+    }
+
+    /// Occlusion and light at each corner of a face, from the blocks around the one the face
+    /// looks into. Occlusion goes from 3 where nothing is in the way to 0 where both sides next
+    /// to the corner are blocked, no matter what's on the diagonal. Smooth lighting averages
+    /// the light of the open blocks touching the corner, otherwise the whole face takes the
+    /// light of the block it looks into.
+    fn shading(
+        &self,
+        position: [i32; 3],
+        face: Face,
+        blocks: &BlockRegistry,
+        smooth_lighting: bool,
+    ) -> FaceShading {
+        let normal = face.normal();
+        let (u_axis, v_axis) = TEXTURE_AXES[face as usize];
+        let outside = [0, 1, 2].map(|axis| position[axis] + normal[axis]);
+        let around = |u: i32, v: i32| {
+            let mut neighbor = outside;
+            neighbor[u_axis] += u;
+            neighbor[v_axis] += v;
+            neighbor
+        };
+        let is_open = |position| {
+            let block = self.block(position);
+            block == BlockId::AIR || blocks.get(block).is_none_or(|block| block.transparent)
+        };
+        let levels = |light: Light| [light.sky, light.block].map(f32::from);
+
+        let flat = levels(self.light(outside));
+        let mut shading = FaceShading {
+            ao: [3; 4],
+            light: [flat; 4],
+        };
+        for (index, corner) in FACE_CORNERS[face as usize].iter().enumerate() {
+            let u = if corner[u_axis] > 0.0 { 1 } else { -1 };
+            let v = if corner[v_axis] > 0.0 { 1 } else { -1 };
+            let (side_u, side_v, diagonal) = (around(u, 0), around(0, v), around(u, v));
+            let (open_u, open_v) = (is_open(side_u), is_open(side_v));
+            // Light can't get around to the diagonal with both sides blocked
+            let open_diagonal = (open_u || open_v) && is_open(diagonal);
+            shading.ao[index] = if !open_u && !open_v {
+                0
+            } else {
+                open_u as u8 + open_v as u8 + open_diagonal as u8
+            };
+
+            if smooth_lighting {
+                let mut sum = flat;
+                let mut count = 1.0;
+                for (neighbor, open) in [
+                    (side_u, open_u),
+                    (side_v, open_v),
+                    (diagonal, open_diagonal),
+                ] {
+                    if open {
+                        let [sky, block] = levels(self.light(neighbor));
+                        sum = [sum[0] + sky, sum[1] + block];
+                        count += 1.0;
+                    }
+                }
+                shading.light[index] = sum.map(|level| level / count);
+            }
+        }
+        shading
+    }
+
+    /// Faces between two of the same block are hidden even if it's transparent, so glass and
+    /// leaves don't show their insides. Levels of the same fluid count as the same block.
+    fn shows_face(
+        &self,
+        position: [i32; 3],
+        block: BlockId,
+        face: Face,
+        blocks: &BlockRegistry,
+    ) -> bool {
+        let normal = face.normal();
+        let neighbor = self.block([0, 1, 2].map(|axis| position[axis] + normal[axis]));
+        if neighbor == BlockId::AIR {
+            return true;
+        }
+        let source = |block| {
+            blocks
+                .get(block)
+                .and_then(|block| block.fluid)
+                .map_or(block, |fluid| fluid.source)
+        };
+        source(neighbor) != source(block)
+            && blocks
+                .get(neighbor)
+                .is_some_and(|neighbor| neighbor.transparent)
+    }
+}
+
+/// Each voxel takes the most common block in its cube of `scale` blocks, or air when most of
+/// the cube is air, and the brightest light in it. Blocks with models are too small to count
+/// and taken as air. Voxels are packed into the corner of the returned chunk at the origin.
+fn downsample_chunk(chunk: &Chunk, scale: i32, blocks: &BlockRegistry) -> Chunk {
+    let mut coarse = Chunk::new();
+    let size = CHUNK_SIZE / scale;
+    let volume = (scale * scale * scale) as usize;
+    let mut counts: Vec<(BlockId, usize)> = Vec::with_capacity(volume);
+    for y in 0..size {
+        for z in 0..size {
+            for x in 0..size {
+                counts.clear();
+                let mut light = Light { sky: 0, block: 0 };
+                for dy in 0..scale {
+                    for dz in 0..scale {
+                        for dx in 0..scale {
+                            let [bx, by, bz] = [x * scale + dx, y * scale + dy, z * scale + dz]
+                                .map(|value| value as u8);
+                            let local = LocalPos::new(bx, by, bz);
+                            let mut block = chunk.get(local);
+                            if !is_cube(block, blocks) {
+                                block = BlockId::AIR;
+                            }
+                            match counts.iter_mut().find(|(other, _)| *other == block) {
+                                Some((_, count)) => *count += 1,
+                                None => counts.push((block, 1)),
+                            }
+                            let other = chunk.light(local);
+                            light.sky = light.sky.max(other.sky);
+                            light.block = light.block.max(other.block);
+                        }
+                    }
+                }
+                let air = counts
+                    .iter()
+                    .find(|(block, _)| *block == BlockId::AIR)
+                    .map_or(0, |(_, count)| *count);
+                let block = if air * 2 > volume {
+                    BlockId::AIR
+                } else {
+                    counts
+                        .iter()
+                        .filter(|(block, _)| *block != BlockId::AIR)
+                        .max_by_key(|(_, count)| *count)
+                        .map_or(BlockId::AIR, |(block, _)| *block)
+                };
+                let local = LocalPos::new(x as u8, y as u8, z as u8);
+                coarse.set(local, block);
+                coarse.set_light(local, light);
+            }
+        }
+    }
+    coarse
+}
+
+/// Where the chunk `offset` chunks away sits in [`ChunkSnapshot`]'s neighbours, each axis of
+/// the offset from -1 to 1
+fn neighbor_index(offset: [i32; 3]) -> Option<usize> {
+    if offset.iter().any(|value| !(-1..=1).contains(value)) {
+        return None;
+    }
+    let [x, y, z] = offset.map(|value| (value + 1) as usize);
+    Some((y * 3 + z) * 3 + x)
+}
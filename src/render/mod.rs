@@ -0,0 +1,30 @@
+pub mod block_textures;
+pub mod breaking;
+pub mod chunk_pool;
+pub mod clouds;
+pub mod debug_view;
+pub mod deferred;
+pub mod dropped_items;
+pub mod entities;
+pub mod entity_model;
+pub mod fog;
+pub mod frustum;
+pub mod held_item;
+pub mod hud;
+pub mod icons;
+pub mod item_textures;
+pub mod lights;
+pub mod mesh_pool;
+pub mod mesher;
+pub mod outline;
+pub mod particles;
+pub mod post;
+pub mod precipitation;
+pub mod shadows;
+pub mod signs;
+pub mod sky;
+pub mod stats;
+pub mod text;
+pub mod visibility;
+pub mod water;
+pub mod world;
@@ -0,0 +1,103 @@
+use gl_lib::{Attribute, BlendMode, CullMode, Mesh, RenderState, ShaderProgram};
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::world::block::FULL_BLOCK;
+use crate::world::raycast::RayHit;
+
+/// Width of the outline in blocks
+const LINE_WIDTH: f32 = 1.0 / 64.0;
+/// How far the outline sits outside the block on every side
+const INFLATE: f32 = 0.002;
+/// Enough to win against the block's own faces at grazing angles without showing through
+/// anything in front of it
+const DEPTH_BIAS: f32 = 0.0002;
+const COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.4];
+
+/// The strips aren't wound consistently so nothing is culled, the block itself hides the
+/// edges on its far side
+const OUTLINE_STATE: RenderState = RenderState {
+    depth_test: true,
+    depth_write: false,
+    blend: Some(BlendMode::Alpha),
+    cull: CullMode::None,
+};
+
+/// The frame drawn around the block under the crosshair
+pub struct SelectionOutline {
+    program: Handle<ShaderProgram>,
+    mesh: Mesh,
+    /// What the mesh was built around, rebuilt when a block with another shape is targeted
+    bounds: ([f32; 3], [f32; 3]),
+}
+
+impl SelectionOutline {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        let program = assets.load("shader/outline")?;
+        let bounds = FULL_BLOCK[0];
+        Ok(SelectionOutline {
+            program,
+            mesh: frame_mesh(bounds),
+            bounds,
+        })
+    }
+
+    pub fn draw(&mut self, assets: &Assets, camera: &Camera, target: &RayHit) {
+        let Some(program) = assets.get(self.program) else {
+            return;
+        };
+        if target.bounds != self.bounds {
+            self.bounds = target.bounds;
+            self.mesh = frame_mesh(target.bounds);
+        }
+        OUTLINE_STATE.apply();
+        let view_projection: [[f32; 4]; 4] = camera.view_projection().into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        let block = target.block.map(|value| value as f32);
+        program.set_vec3(c"u_block", block.into());
+        program.set_float(c"u_depth_bias", DEPTH_BIAS);
+        program.set_vec4(c"u_color", COLOR);
+        program.bind();
+        self.mesh.draw();
+    }
+}
+
+fn frame_mesh((min, max): ([f32; 3], [f32; 3])) -> Mesh {
+    let (vertices, indices) = frame(min, max);
+    Mesh::new(&vertices, &indices, &[Attribute::float(3, 0)])
+}
+
+/// A border of thin strips on each face of a slightly inflated box, in block units. The
+/// strips along one direction stop short of the corners so blending doesn't darken the
+/// overlaps.
+fn frame(min: [f32; 3], max: [f32; 3]) -> (Vec<[f32; 3]>, Vec<u32>) {
+    let min = min.map(|value| value - INFLATE);
+    let max = max.map(|value| value + INFLATE);
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for axis in 0..3 {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let ([u0, u1], [v0, v1]) = ([min[u], max[u]], [min[v], max[v]]);
+        let strips = [
+            ([u0, u1], [v0, v0 + LINE_WIDTH]),
+            ([u0, u1], [v1 - LINE_WIDTH, v1]),
+            ([u0, u0 + LINE_WIDTH], [v0 + LINE_WIDTH, v1 - LINE_WIDTH]),
+            ([u1 - LINE_WIDTH, u1], [v0 + LINE_WIDTH, v1 - LINE_WIDTH]),
+        ];
+        for side in [min[axis], max[axis]] {
+            for ([a0, a1], [b0, b1]) in strips {
+                let start = vertices.len() as u32;
+                for (a, b) in [(a0, b0), (a1, b0), (a1, b1), (a0, b1)] {
+                    let mut corner = [0.0; 3];
+                    corner[axis] = side;
+                    corner[u] = a;
+                    corner[v] = b;
+                    vertices.push(corner);
+                }
+                indices.extend([0, 1, 2, 0, 2, 3].map(|offset| start + offset));
+            }
+        }
+    }
+    (vertices, indices)
+}
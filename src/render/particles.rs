@@ -0,0 +1,229 @@
+use cgmath::{InnerSpace, Point3, Vector3};
+use gl_lib::{
+    BlendMode, Buffer, BufferTarget, BufferUsage, CullMode, RenderState, ShaderProgram, VertexArray,
+};
+use std::mem;
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::world::noise;
+
+/// Oldest ones go first past this many
+const MAX_PARTICLES: usize = 4096;
+/// Smoke puffs and bright flashes per point of explosion power
+const SMOKE_PER_POWER: f32 = 24.0;
+const FLASHES_PER_POWER: f32 = 3.0;
+/// Blocks per second puffs fly out at, at most, per point of power
+const SMOKE_SPEED: f32 = 1.5;
+/// Fraction of their speed particles keep each second, and how fast smoke drifts up
+const DRAG: f32 = 0.05;
+const SMOKE_RISE: f32 = 0.8;
+/// Shortest and longest seconds each kind lasts
+const SMOKE_LIFETIME: [f32; 2] = [0.8, 2.0];
+const FLASH_LIFETIME: [f32; 2] = [0.15, 0.35];
+/// Half extents in blocks, smoke grows by [`SMOKE_GROWTH`] over its lifetime
+const SMOKE_SIZE: [f32; 2] = [0.25, 0.6];
+const SMOKE_GROWTH: f32 = 2.0;
+const FLASH_SIZE: [f32; 2] = [0.6, 1.2];
+/// Darkest and lightest grey of the smoke, and the color of the flashes
+const SMOKE_GREY: [f32; 2] = [0.25, 0.6];
+const FLASH_COLOR: [f32; 3] = [1.0, 0.85, 0.55];
+/// Particles never get darker than this at night
+const MIN_BRIGHTNESS: f32 = 0.2;
+
+/// Two triangles making up each quad, from -1 to 1 across it
+const CORNERS: [[f32; 2]; 6] = [
+    [-1.0, -1.0],
+    [1.0, -1.0],
+    [1.0, 1.0],
+    [1.0, 1.0],
+    [-1.0, 1.0],
+    [-1.0, -1.0],
+];
+
+/// See-through and sorted by nobody, like precipitation
+const PARTICLE_STATE: RenderState = RenderState {
+    depth_test: true,
+    depth_write: false,
+    blend: Some(BlendMode::Alpha),
+    cull: CullMode::None,
+};
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+struct ParticleVertex {
+    position: [f32; 3],
+    /// From -1 to 1 across the particle
+    corner: [f32; 2],
+    color: [f32; 4],
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Particle {
+    position: Point3<f32>,
+    /// Blocks per second
+    velocity: Vector3<f32>,
+    /// Blocks per second it floats up at on top of that
+    rise: f32,
+    /// Seconds since it appeared and how many it lasts
+    age: f32,
+    lifetime: f32,
+    /// Half extents when it appears and how much bigger it gets by the end
+    size: f32,
+    growth: f32,
+    color: [f32; 3],
+}
+
+/// Short-lived puffs and sparks for things that happen in the world, like explosions. They
+/// only live on this side, moved on the CPU every frame and streamed to the GPU as camera
+/// facing quads.
+pub struct ParticleRenderer {
+    program: Handle<ShaderProgram>,
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer,
+    particles: Vec<Particle>,
+    vertices: Vec<ParticleVertex>,
+    /// Particles spawned so far, each one's random values come from its number
+    spawned: u64,
+}
+
+impl ParticleRenderer {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        let program = assets.load("shader/particle")?;
+
+        let vertex_array = VertexArray::new();
+        vertex_array.bind();
+        let vertex_buffer = Buffer::new(BufferTarget::Array);
+        vertex_buffer.bind();
+        let stride = mem::size_of::<ParticleVertex>();
+        vertex_array.attrib_f32(0, 3, stride, mem::offset_of!(ParticleVertex, position));
+        vertex_array.attrib_f32(1, 2, stride, mem::offset_of!(ParticleVertex, corner));
+        vertex_array.attrib_f32(2, 4, stride, mem::offset_of!(ParticleVertex, color));
+
+        Ok(ParticleRenderer {
+            program,
+            vertex_array,
+            vertex_buffer,
+            particles: Vec::new(),
+            vertices: Vec::new(),
+            spawned: 0,
+        })
+    }
+
+    /// A burst of flashes and smoke from `center`, more and further out the more `power`
+    pub fn explosion(&mut self, center: Point3<f32>, power: f32) {
+        for _ in 0..(power * FLASHES_PER_POWER) as u32 {
+            let random = self.next_random();
+            let offset = random_direction(&random) * random(3) * power / 2.0;
+            self.push(Particle {
+                position: center + offset,
+                velocity: Vector3::new(0.0, 0.0, 0.0),
+                rise: 0.0,
+                age: 0.0,
+                lifetime: between(FLASH_LIFETIME, random(4)),
+                size: between(FLASH_SIZE, random(5)),
+                growth: 0.0,
+                color: FLASH_COLOR,
+            });
+        }
+        for _ in 0..(power * SMOKE_PER_POWER) as u32 {
+            let random = self.next_random();
+            let speed = random(3) * SMOKE_SPEED * power;
+            let grey = between(SMOKE_GREY, random(6));
+            self.push(Particle {
+                position: center,
+                velocity: random_direction(&random) * speed,
+                rise: SMOKE_RISE,
+                age: 0.0,
+                lifetime: between(SMOKE_LIFETIME, random(4)),
+                size: between(SMOKE_SIZE, random(5)),
+                growth: SMOKE_GROWTH,
+                color: [grey; 3],
+            });
+        }
+    }
+
+    /// Move every particle on by `seconds` and drop the ones that ran out
+    pub fn update(&mut self, seconds: f32) {
+        let keep = DRAG.powf(seconds);
+        for particle in &mut self.particles {
+            particle.age += seconds;
+            particle.position += (particle.velocity + Vector3::unit_y() * particle.rise) * seconds;
+            particle.velocity *= keep;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    /// Forget every particle, e.g. when leaving the world
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    /// `daylight` from 0 to 1 darkens them at night
+    pub fn draw(&mut self, assets: &Assets, camera: &Camera, daylight: f32) {
+        if self.particles.is_empty() {
+            return;
+        }
+        let Some(program) = assets.get(self.program) else {
+            return;
+        };
+        let forward = camera.forward();
+        let right = forward.cross(Vector3::unit_y()).normalize();
+        let up = right.cross(forward);
+        self.vertices.clear();
+        for particle in &self.particles {
+            let progress = particle.age / particle.lifetime;
+            let size = particle.size * (1.0 + particle.growth * progress);
+            let [r, g, b] = particle.color;
+            let color = [r, g, b, 1.0 - progress];
+            for corner in CORNERS {
+                let position = particle.position + (right * corner[0] + up * corner[1]) * size;
+                self.vertices.push(ParticleVertex {
+                    position: position.into(),
+                    corner,
+                    color,
+                });
+            }
+        }
+
+        PARTICLE_STATE.apply();
+        let view_projection: [[f32; 4]; 4] = camera.view_projection().into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        program.set_float(c"u_brightness", daylight.max(MIN_BRIGHTNESS));
+        program.bind();
+        self.vertex_array.bind();
+        self.vertex_buffer
+            .set_data(&self.vertices, BufferUsage::Stream);
+        gl_lib::draw_triangles(0, self.vertices.len());
+    }
+
+    fn push(&mut self, particle: Particle) {
+        if self.particles.len() >= MAX_PARTICLES {
+            self.particles.remove(0);
+        }
+        self.particles.push(particle);
+    }
+
+    /// Random values from 0 to 1 for the next particle, one for each salt
+    fn next_random(&mut self) -> impl Fn(i64) -> f32 {
+        let particle = self.spawned as i64;
+        self.spawned += 1;
+        move |salt| noise::hash_unit(0, &[particle, salt]) as f32
+    }
+}
+
+/// A unit vector from the first three of `random`'s values, spread evenly over the sphere
+fn random_direction(random: &impl Fn(i64) -> f32) -> Vector3<f32> {
+    let y = random(0) * 2.0 - 1.0;
+    let angle = random(1) * std::f32::consts::TAU;
+    let across = (1.0 - y * y).sqrt();
+    // Slightly up on average, so a blast on the ground doesn't hide half its smoke in it
+    let lift = random(2) * 0.3;
+    Vector3::new(across * angle.cos(), y + lift, across * angle.sin()).normalize()
+}
+
+/// From `range`'s first value to its second as `amount` goes from 0 to 1
+fn between(range: [f32; 2], amount: f32) -> f32 {
+    range[0] + (range[1] - range[0]) * amount
+}
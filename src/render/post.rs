@@ -0,0 +1,287 @@
+use anyhow::Context;
+use gl_lib::{
+    set_viewport, Attribute, CullMode, FramebufferBinding, Mesh, RenderState, ShaderProgram,
+    TextureFormat, TextureFramebuffer,
+};
+
+use crate::assets::{Assets, Handle};
+
+/// Multiplies the scene before tone mapping
+const EXPOSURE: f32 = 1.0;
+/// How much of the blurred bright parts is added back onto the scene
+const BLOOM_STRENGTH: f32 = 0.6;
+/// Horizontal and vertical blur pairs, each one spreads the glow further
+const BLOOM_BLURS: usize = 2;
+
+/// Every pass covers the whole screen and replaces what's there
+const POST_STATE: RenderState = RenderState {
+    depth_test: false,
+    depth_write: false,
+    blend: None,
+    cull: CullMode::None,
+};
+
+/// One fullscreen pass of the chain, each a fragment shader in `shader/post/` reading the
+/// previous pass's output as `u_scene`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PostEffect {
+    Underwater,
+    Nausea,
+    Bloom,
+    ToneMap,
+    Fxaa,
+    Hurt,
+    Vignette,
+    Gamma,
+}
+
+impl PostEffect {
+    /// In the order they run: distortions and bloom first while the scene is still HDR,
+    /// antialiasing once it's tone mapped so edges are judged by the brightness that's shown,
+    /// gamma last
+    pub const ALL: [PostEffect; 8] = [
+        PostEffect::Underwater,
+        PostEffect::Nausea,
+        PostEffect::Bloom,
+        PostEffect::ToneMap,
+        PostEffect::Fxaa,
+        PostEffect::Hurt,
+        PostEffect::Vignette,
+        PostEffect::Gamma,
+    ];
+
+    fn shader(self) -> &'static str {
+        match self {
+            PostEffect::Underwater => "shader/post/underwater",
+            PostEffect::Nausea => "shader/post/nausea",
+            PostEffect::Bloom => "shader/post/bloom",
+            PostEffect::ToneMap => "shader/post/tonemap",
+            PostEffect::Fxaa => "shader/post/fxaa",
+            PostEffect::Hurt => "shader/post/hurt",
+            PostEffect::Vignette => "shader/post/vignette",
+            PostEffect::Gamma => "shader/post/gamma",
+        }
+    }
+
+    /// Passes that wouldn't change anything are skipped. Tone mapping always runs, it's what
+    /// brings the scene down to the window's range.
+    fn active(self, params: &PostParams) -> bool {
+        match self {
+            PostEffect::Underwater => params.underwater > 0.0,
+            PostEffect::Nausea => params.nausea > 0.0,
+            PostEffect::Bloom => params.bloom,
+            PostEffect::ToneMap => true,
+            PostEffect::Fxaa => params.fxaa,
+            PostEffect::Hurt => params.hurt > 0.0,
+            PostEffect::Vignette => params.vignette > 0.0,
+            PostEffect::Gamma => params.gamma != 1.0,
+        }
+    }
+
+    fn set_uniforms(self, program: &ShaderProgram, params: &PostParams) {
+        match self {
+            PostEffect::Underwater => {
+                program.set_float(c"u_time", params.time);
+                program.set_float(c"u_strength", params.underwater);
+                program.set_vec3(c"u_tint", params.fluid_color);
+            }
+            PostEffect::Nausea => {
+                program.set_float(c"u_time", params.time);
+                program.set_float(c"u_strength", params.nausea);
+            }
+            PostEffect::Bloom => program.set_float(c"u_strength", BLOOM_STRENGTH),
+            PostEffect::ToneMap => program.set_float(c"u_exposure", EXPOSURE),
+            PostEffect::Fxaa => {}
+            PostEffect::Hurt => program.set_float(c"u_strength", params.hurt),
+            PostEffect::Vignette => program.set_float(c"u_strength", params.vignette),
+            PostEffect::Gamma => program.set_float(c"u_gamma", params.gamma),
+        }
+    }
+}
+
+/// What the effects depend on this frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PostParams {
+    /// Seconds, for animated effects
+    pub time: f32,
+    /// 1 leaves the picture as it is
+    pub gamma: f32,
+    /// How dark the corners get, from 0 to 1
+    pub vignette: f32,
+    /// From 0 for dry to 1 with the camera fully inside a fluid
+    pub underwater: f32,
+    /// What the view is tinted with inside a fluid
+    pub fluid_color: [f32; 3],
+    /// From 0 to 1
+    pub nausea: f32,
+    /// Red flash after the player gets hurt, from 1 right after the hit down to 0
+    pub hurt: f32,
+    /// Smooth jagged edges, for when MSAA is off or can't be used
+    pub fxaa: bool,
+    /// Let glowing blocks bleed light into their surroundings
+    pub bloom: bool,
+}
+
+/// The world is drawn into an HDR scene target instead of the window, then runs through
+/// [`PostEffect::ALL`] in order. Passes ping-pong between two targets and the last one draws
+/// into the window.
+pub struct PostProcessor {
+    passes: Vec<(PostEffect, Handle<ShaderProgram>)>,
+    scene: TextureFramebuffer,
+    swap: [TextureFramebuffer; 2],
+    bloom: BloomChain,
+    /// One triangle big enough to cover the screen
+    screen: Mesh,
+}
+
+impl PostProcessor {
+    pub fn new(assets: &mut Assets, width: u32, height: u32) -> anyhow::Result<Self> {
+        let passes = PostEffect::ALL
+            .into_iter()
+            .map(|effect| Ok((effect, assets.load(effect.shader())?)))
+            .collect::<anyhow::Result<_>>()?;
+        let (scene, swap) = create_targets(width, height)?;
+        let bloom = BloomChain {
+            extract: assets.load("shader/post/bloom_extract")?,
+            blur: assets.load("shader/post/blur")?,
+            targets: create_bloom_targets(width, height)?,
+        };
+        let screen_corners: [[f32; 2]; 3] = [[-1.0, -1.0], [3.0, -1.0], [-1.0, 3.0]];
+        let screen = Mesh::new(&screen_corners, &[0, 1, 2], &[Attribute::float(2, 0)]);
+        Ok(PostProcessor {
+            passes,
+            scene,
+            swap,
+            bloom,
+            screen,
+        })
+    }
+
+    /// Recreate the targets for a new window size
+    pub fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        if (width, height) != (self.scene.width(), self.scene.height()) {
+            (self.scene, self.swap) = create_targets(width, height)?;
+            self.bloom.targets = create_bloom_targets(width, height)?;
+        }
+        Ok(())
+    }
+
+    /// Where the world should end up, for resolving into it
+    pub fn scene(&self) -> FramebufferBinding {
+        self.scene.binding()
+    }
+
+    /// Bind the scene target and clear it, for drawing the world straight into it
+    pub fn begin(&self) {
+        self.scene.bind();
+        unsafe {
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+    }
+
+    /// Run the active passes over the scene into the window's framebuffer, which is left
+    /// bound
+    pub fn finish(&self, assets: &Assets, params: &PostParams) {
+        let passes: Vec<_> = self
+            .passes
+            .iter()
+            .filter(|(effect, _)| effect.active(params))
+            .filter_map(|(effect, program)| Some((*effect, assets.get(*program)?)))
+            .collect();
+        if passes.is_empty() {
+            self.scene.blit_color_to(FramebufferBinding::DEFAULT);
+            return;
+        }
+        POST_STATE.apply();
+        let mut source = &self.scene;
+        for (index, (effect, program)) in passes.iter().enumerate() {
+            if *effect == PostEffect::Bloom {
+                self.render_bloom(assets, source);
+                program.set_int(c"u_bloom", 1);
+                self.bloom.targets[0].color(0).bind(1);
+            }
+            let target = if index + 1 == passes.len() {
+                FramebufferBinding::DEFAULT.bind();
+                None
+            } else {
+                let target = &self.swap[index % 2];
+                target.bind();
+                Some(target)
+            };
+            program.set_int(c"u_scene", 0);
+            effect.set_uniforms(program, params);
+            program.bind();
+            source.color(0).bind(0);
+            self.screen.draw();
+            if let Some(target) = target {
+                source = target;
+            }
+        }
+    }
+
+    /// Pick out what's brighter than white in `source` and blur it at half resolution into
+    /// the first bloom target. Left black while the shaders aren't loaded.
+    fn render_bloom(&self, assets: &Assets, source: &TextureFramebuffer) {
+        let [first, second] = &self.bloom.targets;
+        set_viewport(first.width(), first.height());
+        first.bind();
+        match (assets.get(self.bloom.extract), assets.get(self.bloom.blur)) {
+            (Some(extract), Some(blur)) => {
+                extract.set_int(c"u_scene", 0);
+                extract.bind();
+                source.color(0).bind(0);
+                self.screen.draw();
+
+                blur.set_int(c"u_scene", 0);
+                blur.bind();
+                let texel = [1.0 / first.width() as f32, 1.0 / first.height() as f32];
+                for _ in 0..BLOOM_BLURS {
+                    for (from, to, direction) in [
+                        (first, second, [texel[0], 0.0]),
+                        (second, first, [0.0, texel[1]]),
+                    ] {
+                        to.bind();
+                        blur.set_vec2(c"u_direction", direction);
+                        from.color(0).bind(0);
+                        self.screen.draw();
+                    }
+                }
+            }
+            _ => unsafe {
+                gl::Clear(gl::COLOR_BUFFER_BIT);
+            },
+        }
+        set_viewport(self.scene.width(), self.scene.height());
+    }
+}
+
+/// Bright parts of the scene blurred for [`PostEffect::Bloom`], at half the window's size
+/// since they end up blurry anyway
+struct BloomChain {
+    /// Keeps only what's past full brightness
+    extract: Handle<ShaderProgram>,
+    blur: Handle<ShaderProgram>,
+    /// The blur ping-pongs between these and ends in the first
+    targets: [TextureFramebuffer; 2],
+}
+
+/// The scene with depth for the world to be drawn into, and two without for the passes
+fn create_targets(
+    width: u32,
+    height: u32,
+) -> anyhow::Result<(TextureFramebuffer, [TextureFramebuffer; 2])> {
+    let target = |depth| {
+        TextureFramebuffer::new(width, height, &[TextureFormat::Rgba16f], depth)
+            .context("Failed to create a post-processing target")
+    };
+    Ok((target(true)?, [target(false)?, target(false)?]))
+}
+
+fn create_bloom_targets(width: u32, height: u32) -> anyhow::Result<[TextureFramebuffer; 2]> {
+    let (width, height) = ((width / 2).max(1), (height / 2).max(1));
+    let target = || {
+        TextureFramebuffer::new(width, height, &[TextureFormat::Rgba16f], false)
+            .context("Failed to create a bloom target")
+    };
+    Ok([target()?, target()?])
+}
@@ -0,0 +1,132 @@
+use gl_lib::{Attribute, BlendMode, CullMode, Mesh, RenderState, ShaderProgram};
+use std::mem;
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::world::biome::Precipitation;
+use crate::world::noise;
+
+/// Drops or flakes in the box around the camera at full strength
+const PARTICLES: u32 = 4000;
+/// Size of the box the particles wrap around in, centered on the camera
+const BOX_SIZE: [f32; 3] = [32.0, 24.0, 32.0];
+/// Seconds before the animation wraps, keeps the time precise on long sessions
+const TIME_PERIOD: f64 = 3600.0;
+
+/// How one kind of precipitation looks and moves
+struct Style {
+    /// Blocks per second
+    fall_speed: f32,
+    /// How far flakes drift side to side, in blocks
+    sway: f32,
+    /// Half extents of each particle
+    size: [f32; 2],
+    color: [f32; 4],
+    /// Whether the particle is cut to a round flake instead of a streak
+    round: bool,
+}
+
+const RAIN: Style = Style {
+    fall_speed: 14.0,
+    sway: 0.0,
+    size: [0.015, 0.35],
+    color: [0.6, 0.7, 0.9, 0.5],
+    round: false,
+};
+
+const SNOW: Style = Style {
+    fall_speed: 1.5,
+    sway: 0.4,
+    size: [0.06, 0.06],
+    color: [1.0, 1.0, 1.0, 0.9],
+    round: true,
+};
+
+/// See-through and sorted by nobody, so they don't write depth
+const PRECIPITATION_STATE: RenderState = RenderState {
+    depth_test: true,
+    depth_write: false,
+    blend: Some(BlendMode::Alpha),
+    cull: CullMode::None,
+};
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+struct ParticleVertex {
+    /// From -1 to 1 across the particle
+    corner: [f32; 2],
+    /// Where the particle starts in the box and whether it shows at lower strengths, each
+    /// from 0 to 1 and the same for all four corners
+    seed: [f32; 4],
+}
+
+/// Rain and snow falling around the camera. The particles never leave the GPU, the shader
+/// moves them with time and wraps them around in a box that follows the camera.
+pub struct PrecipitationRenderer {
+    program: Handle<ShaderProgram>,
+    mesh: Mesh,
+}
+
+impl PrecipitationRenderer {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        let program = assets.load("shader/precipitation")?;
+        let corners = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+        let mut vertices = Vec::with_capacity(PARTICLES as usize * 4);
+        let mut indices = Vec::with_capacity(PARTICLES as usize * 6);
+        for particle in 0..PARTICLES {
+            let seed = std::array::from_fn(|component| {
+                noise::hash_unit(0, &[particle as i64, component as i64]) as f32
+            });
+            let start = vertices.len() as u32;
+            for corner in corners {
+                vertices.push(ParticleVertex { corner, seed });
+            }
+            indices.extend([0, 1, 2, 2, 3, 0].map(|offset| start + offset));
+        }
+        let mesh = Mesh::new(
+            &vertices,
+            &indices,
+            &[
+                Attribute::float(2, mem::offset_of!(ParticleVertex, corner)),
+                Attribute::float(4, mem::offset_of!(ParticleVertex, seed)),
+            ],
+        );
+        Ok(PrecipitationRenderer { program, mesh })
+    }
+
+    /// `strength` from 0 to 1 thins the particles out, `seconds` drives the animation
+    pub fn draw(
+        &self,
+        assets: &Assets,
+        camera: &Camera,
+        kind: Precipitation,
+        strength: f32,
+        seconds: f64,
+    ) {
+        let style = match kind {
+            Precipitation::None => return,
+            Precipitation::Rain => RAIN,
+            Precipitation::Snow => SNOW,
+        };
+        if strength <= 0.0 {
+            return;
+        }
+        let Some(program) = assets.get(self.program) else {
+            return;
+        };
+        PRECIPITATION_STATE.apply();
+        let view_projection: [[f32; 4]; 4] = camera.view_projection().into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        program.set_vec3(c"u_camera_position", camera.position.into());
+        program.set_vec3(c"u_box_size", BOX_SIZE);
+        program.set_float(c"u_time", seconds.rem_euclid(TIME_PERIOD) as f32);
+        program.set_float(c"u_fall_speed", style.fall_speed);
+        program.set_float(c"u_sway", style.sway);
+        program.set_vec2(c"u_size", style.size);
+        program.set_vec4(c"u_color", style.color);
+        program.set_int(c"u_round", style.round as i32);
+        program.set_float(c"u_strength", strength.min(1.0));
+        program.bind();
+        self.mesh.draw();
+    }
+}
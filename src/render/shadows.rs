@@ -0,0 +1,164 @@
+use cgmath::{
+    ortho, Deg, EuclideanSpace, InnerSpace, Matrix4, MetricSpace, Point3, Rad, SquareMatrix,
+    Transform, Vector3,
+};
+use gl_lib::{CullMode, DepthArrayTarget, RenderState, ShaderProgram};
+use std::ffi::CStr;
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::render::frustum::Frustum;
+use crate::render::sky::DayCycle;
+use crate::render::world::WorldRenderer;
+
+const CASCADES: usize = 3;
+/// Far end of each cascade in blocks from the camera, the near end is the previous one's.
+/// Nothing past the last one gets shadows.
+const CASCADE_ENDS: [f32; CASCADES] = [16.0, 48.0, 128.0];
+/// Pixels along each side of every cascade
+const MAP_SIZE: u32 = 2048;
+/// How far toward the light from a cascade's slice of the view blocks still cast shadows
+/// into it, mountains off screen included
+const CASTER_RANGE: f32 = 128.0;
+/// Below this height of the sun or moon the shadows fade out, they'd stretch across the
+/// whole map otherwise
+const FADE_HEIGHT: f32 = 0.15;
+const MATRIX_UNIFORMS: [&CStr; CASCADES] = [
+    c"u_shadow_matrices[0]",
+    c"u_shadow_matrices[1]",
+    c"u_shadow_matrices[2]",
+];
+
+/// Casters are culled from the light's side so the surfaces facing it don't shadow
+/// themselves
+const SHADOW_STATE: RenderState = RenderState {
+    depth_test: true,
+    depth_write: true,
+    blend: None,
+    cull: CullMode::Front,
+};
+
+/// Depth of the opaque chunk meshes seen from the sun, or the moon at night. The view is
+/// split into cascades by distance, each with its own map, so shadows up close stay sharp
+/// while the far ones still reach a fair way out.
+pub struct ShadowMap {
+    program: Handle<ShaderProgram>,
+    target: DepthArrayTarget,
+    matrices: [Matrix4<f32>; CASCADES],
+    /// Blocks covered by one pixel of each cascade
+    texels: [f32; CASCADES],
+    light_direction: Vector3<f32>,
+    /// 0 when the light is below the horizon and nothing was rendered
+    strength: f32,
+}
+
+impl ShadowMap {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        let program = assets.load("shader/shadow")?;
+        let target = DepthArrayTarget::new(MAP_SIZE, CASCADES as u32)?;
+        Ok(ShadowMap {
+            program,
+            target,
+            matrices: [Matrix4::identity(); CASCADES],
+            texels: [0.0; CASCADES],
+            light_direction: Vector3::unit_y(),
+            strength: 0.0,
+        })
+    }
+
+    /// Fit every cascade around its slice of the camera's view and draw the casters into it.
+    /// Leaves the shadow target bound, the caller goes back to its own.
+    pub fn render(
+        &mut self,
+        assets: &Assets,
+        camera: &Camera,
+        cycle: &DayCycle,
+        world: &WorldRenderer,
+    ) {
+        let sun = cycle.sun_direction;
+        self.light_direction = if sun.y >= 0.0 { sun } else { -sun };
+        self.strength = (self.light_direction.y / FADE_HEIGHT).clamp(0.0, 1.0);
+        let Some(program) = assets.get(self.program) else {
+            self.strength = 0.0;
+            return;
+        };
+        if self.strength == 0.0 {
+            return;
+        }
+        // The sun moves through the x-y plane, so z is never parallel to it
+        let light_view =
+            Matrix4::look_to_rh(Point3::origin(), -self.light_direction, Vector3::unit_z());
+        SHADOW_STATE.apply();
+        program.set_int(c"u_blocks", 0);
+        program.bind();
+        let mut near = 0.0;
+        for (cascade, far) in CASCADE_ENDS.into_iter().enumerate() {
+            let (matrix, texel) = fit_cascade(camera, light_view, near, far);
+            self.matrices[cascade] = matrix;
+            self.texels[cascade] = texel;
+            near = far;
+
+            self.target.bind_layer(cascade as u32);
+            let view_projection: [[f32; 4]; 4] = matrix.into();
+            program.set_mat4(c"u_view_projection", &view_projection);
+            let frustum = Frustum::from_view_projection(matrix);
+            world.draw_casters(program, &frustum);
+        }
+    }
+
+    /// Hand this frame's maps to the chunk shader, turned off when nothing was rendered
+    pub fn bind(&self, program: &ShaderProgram, unit: u32) {
+        program.set_int(c"u_shadow_map", unit as i32);
+        program.set_float(c"u_shadow_strength", self.strength);
+        program.set_vec3(c"u_light_direction", self.light_direction.into());
+        program.set_vec3(c"u_cascade_ends", CASCADE_ENDS);
+        program.set_vec3(c"u_shadow_texels", self.texels);
+        for (name, matrix) in MATRIX_UNIFORMS.into_iter().zip(self.matrices) {
+            let matrix: [[f32; 4]; 4] = matrix.into();
+            program.set_mat4(name, &matrix);
+        }
+        self.target.bind_texture(unit);
+    }
+}
+
+/// Orthographic light projection around the part of the view from `near` to `far` blocks
+/// out. It's sized to the slice's bounding sphere so turning the camera doesn't resize it,
+/// and moved in whole pixels so shadow edges don't crawl as the camera moves.
+fn fit_cascade(
+    camera: &Camera,
+    light_view: Matrix4<f32>,
+    near: f32,
+    far: f32,
+) -> (Matrix4<f32>, f32) {
+    let tan_y = (Rad::from(Deg(camera.fov.0 * camera.fov_scale)).0 / 2.0).tan();
+    let tan_x = tan_y * camera.aspect;
+    let forward = camera.forward();
+    let right = forward.cross(Vector3::unit_y()).normalize();
+    let up = right.cross(forward);
+    let center = camera.position + forward * ((near + far) / 2.0);
+    let mut radius: f32 = 0.0;
+    for distance in [near, far] {
+        for (x, y) in [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)] {
+            let corner = camera.position
+                + forward * distance
+                + right * (x * distance * tan_x)
+                + up * (y * distance * tan_y);
+            radius = radius.max(center.distance(corner));
+        }
+    }
+    let radius = radius.ceil();
+
+    let texel = radius * 2.0 / MAP_SIZE as f32;
+    let light_center = light_view.transform_point(center);
+    let x = (light_center.x / texel).floor() * texel;
+    let y = (light_center.y / texel).floor() * texel;
+    let projection = ortho(
+        x - radius,
+        x + radius,
+        y - radius,
+        y + radius,
+        -light_center.z - radius - CASTER_RANGE,
+        -light_center.z + radius,
+    );
+    (projection * light_view, texel)
+}
@@ -0,0 +1,182 @@
+use cgmath::{InnerSpace, Point3, Vector3};
+use gl_lib::{
+    BlendMode, Buffer, BufferTarget, BufferUsage, CullMode, RenderState, ShaderProgram, VertexArray,
+};
+use std::mem;
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::render::text::TextRenderer;
+use crate::world::block::Face;
+use crate::world::block_entity::{BlockEntity, SIGN_LINES};
+use crate::world::chunk::CHUNK_SIZE;
+use crate::world::{BlockRegistry, World};
+
+/// Text on signs further than this many blocks away isn't drawn, it fades out from
+/// [`FADE_START`] on so it doesn't pop
+const VIEW_DISTANCE: f32 = 16.0;
+const FADE_START: f32 = 10.0;
+/// Where the text goes on the board of `models/sign.json`, in blocks: the height of its
+/// middle, how far the front of the board is from the middle of the block and how big the
+/// space for the text is
+const BOARD_MIDDLE: f32 = 11.5 / 16.0;
+const BOARD_FRONT: f32 = 1.0 / 16.0;
+const TEXT_SIZE: [f32; 2] = [14.0 / 16.0, 7.0 / 16.0];
+/// Keeps the text from fighting with the board's face for depth
+const FRONT_BIAS: f32 = 0.005;
+const TEXT_COLOR: [f32; 3] = [0.1, 0.07, 0.03];
+
+/// On top of the opaque world, sorted by nobody
+const SIGN_STATE: RenderState = RenderState {
+    depth_test: true,
+    depth_write: false,
+    blend: Some(BlendMode::Alpha),
+    cull: CullMode::None,
+};
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+struct SignVertex {
+    position: [f32; 3],
+    /// Into the text renderer's glyph atlas
+    uv: [f32; 2],
+    color: [f32; 4],
+}
+
+/// Writes the text of signs close to the camera onto their boards, glyphs from the text
+/// renderer laid out in world space every frame
+pub struct SignRenderer {
+    program: Handle<ShaderProgram>,
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer,
+    vertices: Vec<SignVertex>,
+}
+
+impl SignRenderer {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        let program = assets.load("shader/sign")?;
+
+        let vertex_array = VertexArray::new();
+        vertex_array.bind();
+        let vertex_buffer = Buffer::new(BufferTarget::Array);
+        vertex_buffer.bind();
+        let stride = mem::size_of::<SignVertex>();
+        vertex_array.attrib_f32(0, 3, stride, mem::offset_of!(SignVertex, position));
+        vertex_array.attrib_f32(1, 2, stride, mem::offset_of!(SignVertex, uv));
+        vertex_array.attrib_f32(2, 4, stride, mem::offset_of!(SignVertex, color));
+
+        Ok(SignRenderer {
+            program,
+            vertex_array,
+            vertex_buffer,
+            vertices: Vec::new(),
+        })
+    }
+
+    pub fn draw(
+        &mut self,
+        assets: &Assets,
+        text: &mut TextRenderer,
+        camera: &Camera,
+        world: &World,
+        blocks: &BlockRegistry,
+    ) {
+        self.vertices.clear();
+        let around = camera.position.map(|value| value.floor() as i32);
+        let radius = (VIEW_DISTANCE / CHUNK_SIZE as f32).ceil() as i32;
+        for (position, entity) in world.block_entities_near(around, radius) {
+            let BlockEntity::Sign(sign) = entity else {
+                continue;
+            };
+            if sign.lines.iter().all(String::is_empty) {
+                continue;
+            }
+            let facing = world
+                .block(position)
+                .and_then(|id| blocks.get(id))
+                .and_then(|block| block.orientation)
+                .map_or(Face::South, |orientation| orientation.facing);
+            self.queue_sign(text, camera, position, facing, &sign.lines);
+        }
+        if self.vertices.is_empty() {
+            return;
+        }
+        let Some(program) = assets.get(self.program) else {
+            return;
+        };
+
+        SIGN_STATE.apply();
+        let view_projection: [[f32; 4]; 4] = camera.view_projection().into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        program.set_int(c"u_atlas", 0);
+        program.bind();
+        text.bind_atlas(0);
+        self.vertex_array.bind();
+        self.vertex_buffer
+            .set_data(&self.vertices, BufferUsage::Stream);
+        gl_lib::draw_triangles(0, self.vertices.len());
+    }
+
+    /// The lines of the sign at `position`, on the front of a board that faces away from
+    /// `facing`. Nothing if the camera is behind it or too far away.
+    fn queue_sign(
+        &mut self,
+        text: &mut TextRenderer,
+        camera: &Camera,
+        position: Point3<i32>,
+        facing: Face,
+        lines: &[String],
+    ) {
+        let middle = position.map(|value| value as f32) + Vector3::new(0.5, BOARD_MIDDLE, 0.5);
+        let [x, y, z] = facing.normal().map(|value| value as f32);
+        let facing = Vector3::new(x, y, z);
+        let front = middle - facing * (BOARD_FRONT + FRONT_BIAS);
+        let to_camera = camera.position - front;
+        let distance = to_camera.magnitude();
+        if distance > VIEW_DISTANCE || to_camera.dot(-facing) <= 0.0 {
+            return;
+        }
+        let fade = (distance - FADE_START) / (VIEW_DISTANCE - FADE_START);
+        let [r, g, b] = TEXT_COLOR;
+        let color = [r, g, b, 1.0 - fade.clamp(0.0, 1.0)];
+
+        // Left to right for someone reading it, looking the way the sign faces
+        let right = facing.cross(Vector3::unit_y());
+        let up = Vector3::unit_y();
+        let line_spacing = TEXT_SIZE[1] / SIGN_LINES as f32;
+        let text_top = front + up * TEXT_SIZE[1] / 2.0;
+        for (index, line) in lines.iter().enumerate() {
+            let (quads, width) = text.glyph_quads(line);
+            // GUI units to blocks, smaller for lines too wide for the board
+            let scale = (line_spacing / text.line_height()).min(TEXT_SIZE[0] / width.max(1.0));
+            let height = text.line_height() * scale;
+            let origin = text_top
+                - up * (index as f32 * line_spacing + (line_spacing - height) / 2.0)
+                - right * width * scale / 2.0;
+            for quad in quads {
+                let corner = |x: f32, y: f32| origin + right * x * scale - up * y * scale;
+                let [left, top] = quad.position;
+                let [right_edge, bottom] = [left + quad.size[0], top + quad.size[1]];
+                let [u0, v0] = quad.uv_min;
+                let [u1, v1] = quad.uv_max;
+                let vertex = |position: Point3<f32>, u, v| SignVertex {
+                    position: position.into(),
+                    uv: [u, v],
+                    color,
+                };
+                let top_left = vertex(corner(left, top), u0, v0);
+                let top_right = vertex(corner(right_edge, top), u1, v0);
+                let bottom_left = vertex(corner(left, bottom), u0, v1);
+                let bottom_right = vertex(corner(right_edge, bottom), u1, v1);
+                self.vertices.extend_from_slice(&[
+                    top_left,
+                    bottom_left,
+                    bottom_right,
+                    bottom_right,
+                    top_right,
+                    top_left,
+                ]);
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use cgmath::{InnerSpace, Matrix4, Point3, SquareMatrix, Vector3};
+use gl_lib::{Attribute, BlendMode, CullMode, Mesh, RenderState, ShaderProgram};
+use std::f32::consts::TAU;
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::game::DAY_LENGTH;
+
+/// Straight up and at the horizon at noon, the sky fades between them
+const DAY_ZENITH: [f32; 3] = [0.25, 0.5, 0.85];
+const DAY_HORIZON: [f32; 3] = [0.65, 0.8, 0.9];
+const NIGHT_ZENITH: [f32; 3] = [0.0, 0.0, 0.02];
+const NIGHT_HORIZON: [f32; 3] = [0.02, 0.03, 0.07];
+/// Mixed into the horizon while the sun is close to it, strongest on the sun's side
+const SUNSET_SKY: [f32; 3] = [0.9, 0.45, 0.2];
+/// Gray the sky fades to under heavy rain, scaled by how bright it was
+const OVERCAST_SKY: [f32; 3] = [0.5, 0.52, 0.55];
+/// Sky light lost under heavy rain
+const OVERCAST_DARKENING: f32 = 0.35;
+/// What's left of the sky light at midnight, so caves and the night aren't the same darkness
+const NIGHT_DAYLIGHT: f32 = 0.2;
+/// How far the sun and moon are drawn, well inside the far plane
+const ORBIT_DISTANCE: f32 = 100.0;
+/// Half the width of each billboard at `ORBIT_DISTANCE`
+const SUN_SIZE: f32 = 10.0;
+const MOON_SIZE: f32 = 7.0;
+const SUN_COLOR: [f32; 4] = [1.0, 0.95, 0.7, 1.0];
+const MOON_COLOR: [f32; 4] = [0.85, 0.87, 0.95, 1.0];
+
+/// Behind the world and always drawn first, so neither tests nor writes depth. The gradient
+/// covers the whole screen and the sun and moon blend over it.
+const SKY_STATE: RenderState = RenderState {
+    depth_test: false,
+    depth_write: false,
+    blend: Some(BlendMode::Alpha),
+    cull: CullMode::None,
+};
+
+/// Where the sun is and how bright the sky is at one moment of the day
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DayCycle {
+    /// Unit vector toward the sun, the moon is opposite
+    pub sun_direction: Vector3<f32>,
+    /// Multiplies the sky light, from a fifth at night to 1 during the day
+    pub daylight: f32,
+    pub zenith_color: [f32; 3],
+    /// Also what the fog fades into
+    pub horizon_color: [f32; 3],
+    /// How much sunrise or sunset tints the horizon, from 0 to 1
+    pub sunset: f32,
+    /// How visible the stars are, from 0 to 1
+    pub stars: f32,
+}
+
+impl DayCycle {
+    /// `time` in ticks, fractional so the sun moves smoothly between ticks. The day starts at
+    /// sunrise in the east, it's noon a quarter of the way in.
+    pub fn at(time: f64) -> Self {
+        let angle = (time.rem_euclid(DAY_LENGTH as f64) / DAY_LENGTH as f64) as f32 * TAU;
+        let sun_direction = Vector3::new(angle.cos(), angle.sin(), 0.0);
+        let height = sun_direction.y;
+        // Full daylight a little after sunrise, fully dark a little after sunset
+        let day = (height * 4.0 + 0.5).clamp(0.0, 1.0);
+        let daylight = NIGHT_DAYLIGHT + (1.0 - NIGHT_DAYLIGHT) * day;
+        let sunset = (1.0 - height.abs() * 4.0).clamp(0.0, 1.0);
+        let zenith_color = mix(NIGHT_ZENITH, DAY_ZENITH, day);
+        let horizon_color = mix(
+            mix(NIGHT_HORIZON, DAY_HORIZON, day),
+            SUNSET_SKY,
+            sunset * 0.4,
+        );
+        DayCycle {
+            sun_direction,
+            daylight,
+            zenith_color,
+            horizon_color,
+            sunset,
+            stars: (1.0 - day * 1.5).clamp(0.0, 1.0),
+        }
+    }
+
+    /// Cover the sky with clouds by `rain`, from 0 for clear to 1 for heavy rain: darker and
+    /// grayer, with no stars or sunset glow showing through
+    pub fn overcast(self, rain: f32) -> Self {
+        let gray = |color: [f32; 3]| {
+            let brightness = (color[0] + color[1] + color[2]) / 3.0;
+            mix(
+                color,
+                OVERCAST_SKY.map(|value| value * brightness * 1.5),
+                rain * 0.8,
+            )
+        };
+        DayCycle {
+            daylight: self.daylight * (1.0 - OVERCAST_DARKENING * rain),
+            zenith_color: gray(self.zenith_color),
+            horizon_color: gray(self.horizon_color),
+            sunset: self.sunset * (1.0 - rain),
+            stars: self.stars * (1.0 - rain),
+            ..self
+        }
+    }
+}
+
+/// The sky behind the world: a gradient with stars and the sunset glow drawn over the whole
+/// screen, then the sun and moon as billboards on opposite sides of a circle around the camera
+pub struct SkyRenderer {
+    sky_program: Handle<ShaderProgram>,
+    celestial_program: Handle<ShaderProgram>,
+    /// One triangle big enough to cover the screen
+    screen: Mesh,
+    quad: Mesh,
+}
+
+impl SkyRenderer {
+    pub fn new(assets: &mut Assets) -> anyhow::Result<Self> {
+        let sky_program = assets.load("shader/sky")?;
+        let celestial_program = assets.load("shader/celestial")?;
+        let screen_corners: [[f32; 2]; 3] = [[-1.0, -1.0], [3.0, -1.0], [-1.0, 3.0]];
+        let screen = Mesh::new(&screen_corners, &[0, 1, 2], &[Attribute::float(2, 0)]);
+        let corners: [[f32; 2]; 4] = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+        let quad = Mesh::new(&corners, &[0, 1, 2, 2, 3, 0], &[Attribute::float(2, 0)]);
+        Ok(SkyRenderer {
+            sky_program,
+            celestial_program,
+            screen,
+            quad,
+        })
+    }
+
+    pub fn draw(&self, assets: &Assets, camera: &Camera, cycle: &DayCycle) {
+        SKY_STATE.apply();
+        self.draw_gradient(assets, camera, cycle);
+        self.draw_celestial(assets, camera, cycle);
+    }
+
+    fn draw_gradient(&self, assets: &Assets, camera: &Camera, cycle: &DayCycle) {
+        let Some(program) = assets.get(self.sky_program) else {
+            return;
+        };
+        // Only the rotation matters, so the camera sits at the origin and the unprojected
+        // screen corners come out as view directions
+        let view = Matrix4::look_to_rh(
+            Point3::new(0.0, 0.0, 0.0),
+            camera.forward(),
+            Vector3::unit_y(),
+        );
+        let Some(inverse) = (camera.projection() * view).invert() else {
+            return;
+        };
+        let inverse: [[f32; 4]; 4] = inverse.into();
+        program.set_mat4(c"u_inverse_view_projection", &inverse);
+        program.set_vec3(c"u_zenith_color", cycle.zenith_color);
+        program.set_vec3(c"u_horizon_color", cycle.horizon_color);
+        program.set_vec3(c"u_sunset_color", SUNSET_SKY);
+        program.set_vec3(c"u_sun_direction", cycle.sun_direction.into());
+        program.set_float(c"u_sunset", cycle.sunset);
+        program.set_float(c"u_stars", cycle.stars);
+        program.bind();
+        self.screen.draw();
+    }
+
+    fn draw_celestial(&self, assets: &Assets, camera: &Camera, cycle: &DayCycle) {
+        let Some(program) = assets.get(self.celestial_program) else {
+            return;
+        };
+        let view_projection: [[f32; 4]; 4] = camera.view_projection().into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        program.set_vec3(c"u_camera_position", camera.position.into());
+        program.bind();
+        let bodies = [
+            (cycle.sun_direction, SUN_SIZE, SUN_COLOR),
+            (-cycle.sun_direction, MOON_SIZE, MOON_COLOR),
+        ];
+        for (direction, size, color) in bodies {
+            // The orbit is in the XY plane, so Z is always across the billboard
+            let up = Vector3::unit_z().cross(direction).normalize();
+            program.set_vec3(c"u_offset", (direction * ORBIT_DISTANCE).into());
+            program.set_vec3(c"u_right", (Vector3::unit_z() * size).into());
+            program.set_vec3(c"u_up", (up * size).into());
+            program.set_vec4(c"u_color", color);
+            self.quad.draw();
+        }
+    }
+}
+
+fn mix(from: [f32; 3], to: [f32; 3], amount: f32) -> [f32; 3] {
+    [0, 1, 2].map(|channel| from[channel] + (to[channel] - from[channel]) * amount)
+}
@@ -0,0 +1,25 @@
+use gl_lib::RenderStats;
+use std::mem;
+
+/// Counters for one frame, the GL side is counted by gl_lib and the rest by the renderer
+#[derive(Debug, Copy, Clone, Default)]
+pub struct FrameStats {
+    pub gl: RenderStats,
+    /// Chunk meshes rebuilt, bumped by the mesher
+    pub mesh_rebuilds: u32,
+    /// Chunks waiting on the mesh workers when the frame was drawn
+    pub mesh_queue: u32,
+    /// Of those, the ones no worker has picked up yet
+    pub mesh_unstarted: u32,
+    /// Chunk meshes drawn and skipped for being out of view
+    pub chunks_drawn: u32,
+    pub chunks_culled: u32,
+}
+
+impl FrameStats {
+    /// Close the frame, returning its numbers and starting over for the next one
+    pub fn finish(&mut self) -> FrameStats {
+        self.gl = gl_lib::take_render_stats();
+        mem::take(self)
+    }
+}
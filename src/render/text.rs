@@ -0,0 +1,341 @@
+use anyhow::{anyhow, Context};
+use fontdue::{Font, FontSettings};
+use gl_lib::{
+    Buffer, BufferTarget, BufferUsage, RenderState, ShaderProgram, Texture2D, TextureFormat,
+    VertexArray,
+};
+use std::collections::HashMap;
+use std::mem;
+
+use crate::assets::{Assets, Handle};
+
+pub type Color = [f32; 4];
+
+pub const WHITE: Color = [1.0, 1.0, 1.0, 1.0];
+
+const ATLAS_SIZE: u32 = 1024;
+/// Empty texels between glyphs so linear filtering doesn't bleed into the neighbours
+const GLYPH_PADDING: u32 = 1;
+/// Opaque block reserved in the atlas so rectangles can share the glyph batch
+const SOLID_SIZE: u32 = 4;
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+struct TextVertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+    color: Color,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Glyph {
+    uv_min: [f32; 2],
+    uv_max: [f32; 2],
+    size: [f32; 2],
+    /// From the pen position on the baseline to the top left corner of the bitmap
+    offset: [f32; 2],
+    advance: f32,
+}
+
+/// One character of a line laid out by [`TextRenderer::glyph_quads`]
+#[derive(Debug, Copy, Clone)]
+pub struct GlyphQuad {
+    /// Top left corner from the top left of the line, in GUI units
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+    pub uv_min: [f32; 2],
+    pub uv_max: [f32; 2],
+}
+
+/// Single channel coverage texture filled shelf by shelf as new characters show up
+struct GlyphAtlas {
+    texture: Texture2D,
+    cursor: [u32; 2],
+    row_height: u32,
+    glyphs: HashMap<char, Glyph>,
+    solid_uv: [f32; 2],
+    /// Where allocation starts over after a reset, just past the solid block
+    start: ([u32; 2], u32),
+}
+
+impl GlyphAtlas {
+    fn new() -> anyhow::Result<Self> {
+        let texture = Texture2D::empty(ATLAS_SIZE, ATLAS_SIZE, TextureFormat::R8)
+            .context("Failed to create glyph atlas")?;
+        let mut atlas = GlyphAtlas {
+            texture,
+            cursor: [GLYPH_PADDING, GLYPH_PADDING],
+            row_height: 0,
+            glyphs: HashMap::new(),
+            solid_uv: [0.0, 0.0],
+            start: ([0, 0], 0),
+        };
+
+        let [x, y] = atlas
+            .allocate(SOLID_SIZE, SOLID_SIZE)
+            .context("Glyph atlas is too small")?;
+        let solid = [u8::MAX; (SOLID_SIZE * SOLID_SIZE) as usize];
+        atlas
+            .texture
+            .update_region(x, y, SOLID_SIZE, SOLID_SIZE, &solid)?;
+        let center = SOLID_SIZE as f32 / 2.0;
+        atlas.solid_uv = [
+            (x as f32 + center) / ATLAS_SIZE as f32,
+            (y as f32 + center) / ATLAS_SIZE as f32,
+        ];
+        atlas.start = (atlas.cursor, atlas.row_height);
+        Ok(atlas)
+    }
+
+    /// Forget every glyph, their texels get overwritten as new ones come in
+    fn reset(&mut self) {
+        self.glyphs.clear();
+        (self.cursor, self.row_height) = self.start;
+    }
+
+    fn allocate(&mut self, width: u32, height: u32) -> Option<[u32; 2]> {
+        if self.cursor[0] + width + GLYPH_PADDING > ATLAS_SIZE {
+            self.cursor = [
+                GLYPH_PADDING,
+                self.cursor[1] + self.row_height + GLYPH_PADDING,
+            ];
+            self.row_height = 0;
+        }
+        if self.cursor[1] + height + GLYPH_PADDING > ATLAS_SIZE {
+            return None;
+        }
+        let position = self.cursor;
+        self.cursor[0] += width + GLYPH_PADDING;
+        self.row_height = self.row_height.max(height);
+        Some(position)
+    }
+}
+
+/// Batches screen-space text and flat rectangles into a single draw call, positions are in
+/// GUI units from the top left corner
+pub struct TextRenderer {
+    font: Font,
+    /// In GUI units, glyphs are rasterized at this times `scale`
+    pixel_size: f32,
+    /// Framebuffer pixels per GUI unit
+    scale: f32,
+    ascent: f32,
+    line_height: f32,
+    atlas: GlyphAtlas,
+    program: Handle<ShaderProgram>,
+    vertices: Vec<TextVertex>,
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer,
+}
+
+impl TextRenderer {
+    pub fn new(assets: &mut Assets, font_path: &str, pixel_size: f32) -> anyhow::Result<Self> {
+        let bytes = assets.read(font_path)?;
+        let font = Font::from_bytes(bytes, FontSettings::default())
+            .map_err(|err| anyhow!("Failed to parse font {font_path}: {err}"))?;
+        let line_metrics = font
+            .horizontal_line_metrics(pixel_size)
+            .with_context(|| format!("Font {font_path} has no horizontal metrics"))?;
+        let program = assets.load("shader/text")?;
+
+        let vertex_array = VertexArray::new();
+        vertex_array.bind();
+        let vertex_buffer = Buffer::new(BufferTarget::Array);
+        vertex_buffer.bind();
+        let stride = mem::size_of::<TextVertex>();
+        vertex_array.attrib_f32(0, 2, stride, mem::offset_of!(TextVertex, position));
+        vertex_array.attrib_f32(1, 2, stride, mem::offset_of!(TextVertex, uv));
+        vertex_array.attrib_f32(2, 4, stride, mem::offset_of!(TextVertex, color));
+
+        Ok(TextRenderer {
+            font,
+            pixel_size,
+            scale: 1.0,
+            ascent: line_metrics.ascent.round(),
+            line_height: line_metrics.new_line_size.round(),
+            atlas: GlyphAtlas::new()?,
+            program,
+            vertices: Vec::new(),
+            vertex_array,
+            vertex_buffer,
+        })
+    }
+
+    /// Glyphs are rasterized again at the new size so text stays sharp
+    pub fn set_scale(&mut self, scale: f32) {
+        if scale != self.scale {
+            self.scale = scale;
+            self.atlas.reset();
+        }
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    /// Width of the longest line and total height, without rasterizing anything
+    pub fn measure(&self, text: &str) -> [f32; 2] {
+        let mut width: f32 = 0.0;
+        let mut lines = 0;
+        for line in text.split('\n') {
+            let line_width: f32 = line
+                .chars()
+                .map(|character| self.font.metrics(character, self.pixel_size).advance_width)
+                .sum();
+            width = width.max(line_width);
+            lines += 1;
+        }
+        [width.ceil(), lines as f32 * self.line_height]
+    }
+
+    /// `position` is the top left corner of the first line
+    pub fn queue(&mut self, text: &str, position: [f32; 2], color: Color) {
+        let mut pen_x = position[0];
+        let mut baseline = position[1] + self.ascent;
+        for character in text.chars() {
+            if character == '\n' {
+                pen_x = position[0];
+                baseline += self.line_height;
+                continue;
+            }
+            let Some(glyph) = self.glyph(character) else {
+                continue;
+            };
+            if glyph.size[0] > 0.0 && glyph.size[1] > 0.0 {
+                // Snapped to whole framebuffer pixels, not GUI units
+                let corner = [
+                    ((pen_x + glyph.offset[0]) * self.scale).round() / self.scale,
+                    ((baseline + glyph.offset[1]) * self.scale).round() / self.scale,
+                ];
+                self.push_quad(corner, glyph.size, glyph.uv_min, glyph.uv_max, color);
+            }
+            pen_x += glyph.advance;
+        }
+    }
+
+    /// Lay out a single line for drawing somewhere other than the screen, like on a sign in
+    /// the world. Returns its glyphs along with its width, the uvs are into the atlas
+    /// [`TextRenderer::bind_atlas`] binds and only hold until the GUI scale changes.
+    pub fn glyph_quads(&mut self, line: &str) -> (Vec<GlyphQuad>, f32) {
+        let mut quads = Vec::new();
+        let mut pen_x = 0.0;
+        for character in line.chars() {
+            let Some(glyph) = self.glyph(character) else {
+                continue;
+            };
+            if glyph.size[0] > 0.0 && glyph.size[1] > 0.0 {
+                quads.push(GlyphQuad {
+                    position: [pen_x + glyph.offset[0], self.ascent + glyph.offset[1]],
+                    size: glyph.size,
+                    uv_min: glyph.uv_min,
+                    uv_max: glyph.uv_max,
+                });
+            }
+            pen_x += glyph.advance;
+        }
+        (quads, pen_x)
+    }
+
+    /// The glyph atlas, coverage in its red channel
+    pub fn bind_atlas(&self, unit: u32) {
+        self.atlas.texture.bind(unit);
+    }
+
+    pub fn queue_rect(&mut self, position: [f32; 2], size: [f32; 2], color: Color) {
+        let uv = self.atlas.solid_uv;
+        self.push_quad(position, size, uv, uv, color);
+    }
+
+    /// Draws everything queued since the last flush, `screen_size` in GUI units
+    pub fn flush(&mut self, assets: &Assets, screen_size: [f32; 2]) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        let Some(program) = assets.get(self.program) else {
+            self.vertices.clear();
+            return;
+        };
+
+        program.set_vec2(c"u_screen_size", screen_size);
+        program.set_int(c"u_atlas", 0);
+        program.bind();
+        self.atlas.texture.bind(0);
+        self.vertex_array.bind();
+        self.vertex_buffer
+            .set_data(&self.vertices, BufferUsage::Stream);
+
+        RenderState::OVERLAY.apply();
+        gl_lib::draw_triangles(0, self.vertices.len());
+        self.vertices.clear();
+    }
+
+    /// Rasterizes and caches on first use, None once the atlas is full
+    fn glyph(&mut self, character: char) -> Option<Glyph> {
+        if let Some(glyph) = self.atlas.glyphs.get(&character) {
+            return Some(*glyph);
+        }
+
+        let (metrics, bitmap) = self.font.rasterize(character, self.pixel_size * self.scale);
+        let (width, height) = (metrics.width as u32, metrics.height as u32);
+        let [x, y] = if width > 0 && height > 0 {
+            let position = self.atlas.allocate(width, height)?;
+            if let Err(err) =
+                self.atlas
+                    .texture
+                    .update_region(position[0], position[1], width, height, &bitmap)
+            {
+                log::warn!("Failed to upload glyph {character:?}: {err}");
+                return None;
+            }
+            position
+        } else {
+            [0, 0]
+        };
+
+        let atlas_size = ATLAS_SIZE as f32;
+        let glyph = Glyph {
+            uv_min: [x as f32 / atlas_size, y as f32 / atlas_size],
+            uv_max: [
+                (x + width) as f32 / atlas_size,
+                (y + height) as f32 / atlas_size,
+            ],
+            size: [width as f32 / self.scale, height as f32 / self.scale],
+            offset: [
+                metrics.xmin as f32 / self.scale,
+                -(height as f32 + metrics.ymin as f32) / self.scale,
+            ],
+            advance: metrics.advance_width / self.scale,
+        };
+        self.atlas.glyphs.insert(character, glyph);
+        Some(glyph)
+    }
+
+    fn push_quad(
+        &mut self,
+        position: [f32; 2],
+        size: [f32; 2],
+        uv_min: [f32; 2],
+        uv_max: [f32; 2],
+        color: Color,
+    ) {
+        let [x0, y0] = position;
+        let [x1, y1] = [x0 + size[0], y0 + size[1]];
+        let vertex = |x, y, u, v| TextVertex {
+            position: [x, y],
+            uv: [u, v],
+            color,
+        };
+        let top_left = vertex(x0, y0, uv_min[0], uv_min[1]);
+        let top_right = vertex(x1, y0, uv_max[0], uv_min[1]);
+        let bottom_left = vertex(x0, y1, uv_min[0], uv_max[1]);
+        let bottom_right = vertex(x1, y1, uv_max[0], uv_max[1]);
+        self.vertices.extend_from_slice(&[
+            top_left,
+            bottom_left,
+            bottom_right,
+            bottom_right,
+            top_right,
+            top_left,
+        ]);
+    }
+}
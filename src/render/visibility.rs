@@ -0,0 +1,137 @@
+use cgmath::{Point3, Vector3};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::render::frustum::Frustum;
+use crate::world::block::{BlockRegistry, Face};
+use crate::world::chunk::{Chunk, ChunkPos, LocalPos, CHUNK_SIZE};
+use crate::world::BlockId;
+
+/// Which faces of a chunk can see each other through it, for culling whatever is hidden
+/// behind solid rock. A bit for every ordered pair of faces in [`Face::ALL`] order.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ChunkVisibility(u64);
+
+impl ChunkVisibility {
+    /// Every face sees every other, e.g. for air or chunks that aren't meshed yet
+    pub const OPEN: ChunkVisibility = ChunkVisibility((1 << 36) - 1);
+
+    /// Flood fill each pocket of blocks light passes through, the faces a pocket touches
+    /// can all see each other
+    pub fn compute(chunk: &Chunk, blocks: &BlockRegistry) -> Self {
+        if chunk.is_empty() {
+            return ChunkVisibility::OPEN;
+        }
+        let size = CHUNK_SIZE as usize;
+        let index = |[x, y, z]: [i32; 3]| (y as usize * size + z as usize) * size + x as usize;
+        let is_open = |position: [i32; 3]| {
+            let [x, y, z] = position.map(|value| value as u8);
+            let block = chunk.get(LocalPos::new(x, y, z));
+            block == BlockId::AIR || blocks.get(block).is_some_and(|block| block.transparent)
+        };
+
+        let mut visibility = ChunkVisibility(0);
+        let mut visited = vec![false; size * size * size];
+        let mut queue = VecDeque::new();
+        for y in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    let start = [x, y, z];
+                    if visited[index(start)] || !is_open(start) {
+                        continue;
+                    }
+                    visited[index(start)] = true;
+                    queue.push_back(start);
+                    let mut touched = 0u8;
+                    while let Some(position) = queue.pop_front() {
+                        for face in Face::ALL {
+                            let normal = face.normal();
+                            let neighbor = [0, 1, 2].map(|axis| position[axis] + normal[axis]);
+                            if neighbor
+                                .iter()
+                                .any(|value| !(0..CHUNK_SIZE).contains(value))
+                            {
+                                touched |= 1 << face as u8;
+                                continue;
+                            }
+                            if !visited[index(neighbor)] && is_open(neighbor) {
+                                visited[index(neighbor)] = true;
+                                queue.push_back(neighbor);
+                            }
+                        }
+                    }
+                    visibility.connect_all(touched);
+                }
+            }
+        }
+        visibility
+    }
+
+    pub fn connects(self, from: Face, to: Face) -> bool {
+        self.0 & (1 << (from as u8 * 6 + to as u8)) != 0
+    }
+
+    /// Every face in the `faces` bitmask sees every other
+    fn connect_all(&mut self, faces: u8) {
+        for from in Face::ALL {
+            for to in Face::ALL {
+                if faces & (1 << from as u8) != 0 && faces & (1 << to as u8) != 0 {
+                    self.0 |= 1 << (from as u8 * 6 + to as u8);
+                }
+            }
+        }
+    }
+}
+
+/// Chunks that might be visible from `camera`, found by walking out from its chunk through
+/// faces that see each other. The walk never turns back toward the camera and stays inside
+/// the frustum and `radius` chunks. Chunks without an entry in `visibility` count as open.
+pub fn visible_chunks(
+    camera: Point3<f32>,
+    frustum: &Frustum,
+    visibility: &HashMap<ChunkPos, ChunkVisibility>,
+    radius: i32,
+) -> HashSet<ChunkPos> {
+    let (start, _) = ChunkPos::of_block(camera.map(|value| value.floor() as i32));
+    let mut visible = HashSet::from([start]);
+    // Each entry remembers the face it came in through and every direction taken so far
+    let mut queue = VecDeque::from([(start, None::<Face>, 0u8)]);
+    let size = CHUNK_SIZE as f32;
+    while let Some((position, entered, directions)) = queue.pop_front() {
+        let chunk = visibility
+            .get(&position)
+            .copied()
+            .unwrap_or(ChunkVisibility::OPEN);
+        for face in Face::ALL {
+            // Going back the way any step came can't reveal anything new
+            if directions & (1 << face.opposite() as u8) != 0 {
+                continue;
+            }
+            if entered.is_some_and(|entered| !chunk.connects(entered, face)) {
+                continue;
+            }
+            let [x, y, z] = face.normal();
+            let neighbor = ChunkPos::new(position.x + x, position.y + y, position.z + z);
+            let out_of_range = [
+                neighbor.x - start.x,
+                neighbor.y - start.y,
+                neighbor.z - start.z,
+            ]
+            .iter()
+            .any(|offset| offset.abs() > radius);
+            if out_of_range || visible.contains(&neighbor) {
+                continue;
+            }
+            let origin = neighbor.origin().map(|value| value as f32);
+            if !frustum.intersects_box(origin, origin + Vector3::new(size, size, size)) {
+                continue;
+            }
+            visible.insert(neighbor);
+            queue.push_back((
+                neighbor,
+                Some(face.opposite()),
+                directions | 1 << face as u8,
+            ));
+        }
+    }
+    visible
+}
@@ -0,0 +1,58 @@
+use anyhow::Context;
+use gl_lib::{FramebufferBinding, ShaderProgram, TextureFormat, TextureFramebuffer};
+
+/// What the fancy water surface needs besides the chunk meshes: a copy of everything opaque
+/// drawn so far, which the water reflects by marching through it in screen space. Reflected
+/// rays that leave the screen fall back to the sky's colors.
+pub struct WaterSurface {
+    /// Color and depth of the opaque pass, same size as the scene
+    reflection: TextureFramebuffer,
+    /// Seconds, for the waves
+    time: f32,
+}
+
+impl WaterSurface {
+    pub fn new(width: u32, height: u32) -> anyhow::Result<Self> {
+        Ok(WaterSurface {
+            reflection: create_reflection(width, height)?,
+            time: 0.0,
+        })
+    }
+
+    /// Recreate the copy for a new window size
+    pub fn resize(&mut self, width: u32, height: u32) -> anyhow::Result<()> {
+        let size = (self.reflection.width(), self.reflection.height());
+        if (width, height) != size {
+            self.reflection = create_reflection(width, height)?;
+        }
+        Ok(())
+    }
+
+    pub fn set_time(&mut self, seconds: f32) {
+        self.time = seconds;
+    }
+
+    /// Copy the framebuffer that's bound, once the opaque faces are in it
+    pub fn capture(&self) {
+        self.reflection.copy_from(FramebufferBinding::current());
+    }
+
+    /// Hand the copy and the waves' time to the chunk shader, color on `unit` and depth on
+    /// the one after
+    pub fn bind(&self, program: &ShaderProgram, unit: u32) {
+        program.set_int(c"u_fancy_water", 1);
+        program.set_float(c"u_time", self.time);
+        program.set_int(c"u_reflection_color", unit as i32);
+        program.set_int(c"u_reflection_depth", unit as i32 + 1);
+        self.reflection.color(0).bind(unit);
+        if let Some(depth) = self.reflection.depth() {
+            depth.bind(unit + 1);
+        }
+    }
+}
+
+/// HDR like the scene it copies, with depth for telling where a reflected ray hits
+fn create_reflection(width: u32, height: u32) -> anyhow::Result<TextureFramebuffer> {
+    TextureFramebuffer::new(width, height, &[TextureFormat::Rgba16f], true)
+        .context("Failed to create the water reflection target")
+}
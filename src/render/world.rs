@@ -0,0 +1,394 @@
+use anyhow::Context;
+use cgmath::{MetricSpace, Vector3};
+use gl_lib::{RenderState, ShaderProgram};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::assets::{Assets, Handle};
+use crate::camera::Camera;
+use crate::profiler;
+use crate::render::block_textures::BlockTextures;
+use crate::render::chunk_pool::{ChunkPool, MeshSlot};
+use crate::render::debug_view::DebugView;
+use crate::render::deferred::DeferredRenderer;
+use crate::render::fog::Fog;
+use crate::render::frustum::Frustum;
+use crate::render::lights;
+use crate::render::mesh_pool::{MeshJob, MeshPool};
+use crate::render::mesher::{ChunkSnapshot, Lod, MeshingMode};
+use crate::render::shadows::ShadowMap;
+use crate::render::sky::DayCycle;
+use crate::render::visibility::{self, ChunkVisibility};
+use crate::render::water::WaterSurface;
+use crate::world::chunk::{ChunkPos, CHUNK_SIZE};
+use crate::world::generator::TerrainGenerator;
+use crate::world::{BlockRegistry, World};
+
+/// Draws the blocks of a world, one mesh per chunk that has visible faces and a second for
+/// its translucent ones. Meshes are built on a [`MeshPool`] and uploaded into a
+/// [`ChunkPool`] once they're done.
+pub struct WorldRenderer {
+    program: Handle<ShaderProgram>,
+    textures: BlockTextures,
+    meshes: HashMap<ChunkPos, MeshSlot>,
+    translucent: HashMap<ChunkPos, MeshSlot>,
+    buffers: ChunkPool,
+    /// For every meshed chunk, including ones with nothing to draw
+    visibility: HashMap<ChunkPos, ChunkVisibility>,
+    meshing: MeshingMode,
+    smooth_lighting: bool,
+    /// Chunks this far from the camera or further are meshed coarser, see [`Lod::for_distance`]
+    lod_distance: u32,
+    /// Detail each chunk was last queued with
+    lods: HashMap<ChunkPos, Lod>,
+    /// Where the camera was when the detail levels were last checked
+    camera_chunk: Option<ChunkPos>,
+    pool: MeshPool,
+    /// Latest job version per chunk still being meshed, anything older is stale
+    pending: HashMap<ChunkPos, u64>,
+    next_version: u64,
+    debug_view: DebugView,
+}
+
+/// Optional parts of drawing the world, each None while its setting is off
+#[derive(Copy, Clone, Default)]
+pub struct WorldPasses<'a> {
+    /// Shades the sky light
+    pub shadows: Option<&'a ShadowMap>,
+    /// The opaque faces go through its G-buffer and are lit before the translucent ones are
+    /// drawn over them
+    pub deferred: Option<&'a DeferredRenderer>,
+    /// Water gets waves and reflections of what's around it
+    pub water: Option<&'a WaterSurface>,
+    /// Draws the entities once the opaque faces are lit, so the translucent ones blend over
+    /// them. It's free to change any state, the chunk program's is set up again after.
+    pub entities: Option<&'a dyn Fn()>,
+}
+
+impl WorldRenderer {
+    pub fn new(
+        assets: &mut Assets,
+        blocks: Arc<BlockRegistry>,
+        meshing: MeshingMode,
+        smooth_lighting: bool,
+        lod_distance: u32,
+    ) -> anyhow::Result<Self> {
+        let program = assets.load("shader/chunk")?;
+        let textures =
+            BlockTextures::load(assets, &blocks).context("Failed to load block textures")?;
+        let pool = MeshPool::new(blocks, textures.layers());
+        Ok(WorldRenderer {
+            program,
+            textures,
+            meshes: HashMap::new(),
+            translucent: HashMap::new(),
+            buffers: ChunkPool::new(),
+            visibility: HashMap::new(),
+            meshing,
+            smooth_lighting,
+            lod_distance,
+            lods: HashMap::new(),
+            camera_chunk: None,
+            pool,
+            pending: HashMap::new(),
+            next_version: 0,
+            debug_view: DebugView::default(),
+        })
+    }
+
+    /// Only chunks meshed from now on use the new mode, mark the world dirty to redo the rest
+    pub fn set_meshing(&mut self, meshing: MeshingMode) {
+        self.meshing = meshing;
+    }
+
+    /// Wireframe and shading take effect from the next frame, chunk borders are drawn by
+    /// [`ChunkBorders`](crate::render::debug_view::ChunkBorders)
+    pub fn set_debug_view(&mut self, debug_view: DebugView) {
+        self.debug_view = debug_view;
+    }
+
+    /// Same as [`WorldRenderer::set_meshing`], only chunks meshed from now on change
+    pub fn set_smooth_lighting(&mut self, smooth_lighting: bool) {
+        self.smooth_lighting = smooth_lighting;
+    }
+
+    /// Chunks that end up on the other side of a threshold get remeshed on the next update
+    pub fn set_lod_distance(&mut self, lod_distance: u32) {
+        if lod_distance != self.lod_distance {
+            self.lod_distance = lod_distance;
+            self.camera_chunk = None;
+        }
+    }
+
+    /// Move animated block textures on to their frame for `tick`
+    pub fn animate(&mut self, tick: u64) {
+        self.textures.animate(tick);
+    }
+
+    /// Queue the chunks that changed, closest to the camera first, and upload whatever the
+    /// pool finished since last time. Returns how many meshes were replaced.
+    pub fn update(
+        &mut self,
+        world: &mut World,
+        generator: &TerrainGenerator,
+        camera: &Camera,
+    ) -> u32 {
+        let meshing_scope = profiler::scope("meshing");
+        let (camera_chunk, _) =
+            ChunkPos::of_block(camera.position.map(|value| value.floor() as i32));
+        let mut changed: HashSet<ChunkPos> = world.take_dirty().into_iter().collect();
+        // Moving into another chunk shifts which chunks are far enough to be coarser
+        if self.camera_chunk != Some(camera_chunk) {
+            self.camera_chunk = Some(camera_chunk);
+            changed.extend(
+                self.lods
+                    .iter()
+                    .filter(|(position, lod)| self.lod(**position, camera_chunk) != **lod)
+                    .map(|(position, _)| *position),
+            );
+        }
+        for position in changed {
+            self.next_version += 1;
+            let Some(snapshot) = ChunkSnapshot::capture(world, generator, position) else {
+                // Unloaded, whatever is still being meshed for it can be thrown away
+                self.pending.remove(&position);
+                self.lods.remove(&position);
+                self.remove_meshes(position);
+                self.visibility.remove(&position);
+                continue;
+            };
+            let distance = [
+                position.x - camera_chunk.x,
+                position.y - camera_chunk.y,
+                position.z - camera_chunk.z,
+            ]
+            .map(|offset| offset.unsigned_abs().min(u16::MAX as u32));
+            let lod = self.lod(position, camera_chunk);
+            self.lods.insert(position, lod);
+            self.pending.insert(position, self.next_version);
+            self.pool.submit(MeshJob {
+                position,
+                version: self.next_version,
+                priority: distance.iter().map(|offset| offset * offset).sum(),
+                snapshot,
+                mode: self.meshing,
+                smooth_lighting: self.smooth_lighting,
+                lod,
+            });
+        }
+        drop(meshing_scope);
+
+        let _scope = profiler::scope("upload");
+        let mut uploaded = 0;
+        let finished: Vec<_> = self.pool.finished().collect();
+        for result in finished {
+            if self.pending.get(&result.position) != Some(&result.version) {
+                continue;
+            }
+            self.pending.remove(&result.position);
+            uploaded += 1;
+            self.visibility.insert(result.position, result.visibility);
+            self.remove_meshes(result.position);
+            if let Some(slot) = self.buffers.insert(&result.meshes.opaque) {
+                self.meshes.insert(result.position, slot);
+            }
+            if let Some(slot) = self.buffers.insert(&result.meshes.translucent) {
+                self.translucent.insert(result.position, slot);
+            }
+        }
+        uploaded
+    }
+
+    /// Chunks queued or being meshed
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Chunks still waiting for a mesh worker to pick them up
+    pub fn unstarted(&self) -> usize {
+        self.pool.queued()
+    }
+
+    /// Load the block textures again after the resource packs changed. The layers stay put
+    /// since they follow the texture names, so the meshes keep working.
+    pub fn reload_textures(
+        &mut self,
+        assets: &Assets,
+        blocks: &BlockRegistry,
+    ) -> anyhow::Result<()> {
+        self.textures =
+            BlockTextures::load(assets, blocks).context("Failed to load block textures")?;
+        Ok(())
+    }
+
+    /// Drop every mesh, for when the world is closed
+    pub fn clear(&mut self) {
+        self.meshes.clear();
+        self.translucent.clear();
+        self.buffers.clear();
+        self.visibility.clear();
+        self.lods.clear();
+        self.camera_chunk = None;
+        self.pending.clear();
+        self.pool.clear();
+    }
+
+    fn remove_meshes(&mut self, position: ChunkPos) {
+        let slots = [&mut self.meshes, &mut self.translucent].map(|map| map.remove(&position));
+        for slot in slots.into_iter().flatten() {
+            self.buffers.remove(slot);
+        }
+    }
+
+    fn lod(&self, position: ChunkPos, camera_chunk: ChunkPos) -> Lod {
+        let distance = [
+            position.x - camera_chunk.x,
+            position.y - camera_chunk.y,
+            position.z - camera_chunk.z,
+        ]
+        .map(i32::unsigned_abs)
+        .into_iter()
+        .max()
+        .unwrap_or(0);
+        Lod::for_distance(distance, self.lod_distance)
+    }
+
+    /// Chunks up to `render_distance` away fade into `fog`. Opaque faces go first, then
+    /// translucent ones blended over them from the furthest chunk in. Chunks out of view or
+    /// walled off from the camera are skipped, returns how many were drawn and how many
+    /// skipped. See [`WorldPasses`] for what the optional passes change.
+    pub fn draw(
+        &self,
+        assets: &Assets,
+        camera: &Camera,
+        cycle: &DayCycle,
+        passes: &WorldPasses,
+        fog: &Fog,
+        render_distance: u32,
+    ) -> (u32, u32) {
+        let WorldPasses {
+            shadows,
+            deferred,
+            water,
+            entities,
+        } = *passes;
+        let Some(program) = assets.get(self.program) else {
+            return (0, 0);
+        };
+        RenderState::OPAQUE.apply();
+        let frustum = Frustum::from_view_projection(camera.view_projection());
+        let view_projection: [[f32; 4]; 4] = camera.view_projection().into();
+        program.set_mat4(c"u_view_projection", &view_projection);
+        program.set_int(c"u_blocks", 0);
+        program.set_vec3(c"u_camera_position", camera.position.into());
+        fog.apply(program);
+        program.set_vec3(c"u_sky_color", cycle.zenith_color);
+        program.set_float(c"u_daylight", cycle.daylight);
+        program.set_int(c"u_translucent", 0);
+        program.set_int(c"u_deferred", deferred.is_some() as i32);
+        self.debug_view.shading.apply(program);
+        lights::use_lights(program);
+        // Bound again after the deferred resolve, which uses the same units
+        let bind_textures = || {
+            match shadows {
+                Some(shadows) => shadows.bind(program, 1),
+                None => {
+                    // Still off unit 0, samplers of different types can't share a unit
+                    program.set_int(c"u_shadow_map", 1);
+                    program.set_float(c"u_shadow_strength", 0.0);
+                }
+            }
+            self.textures.bind(0);
+        };
+        bind_textures();
+        program.bind();
+        let output = deferred.map(DeferredRenderer::begin);
+        let visible = visibility::visible_chunks(
+            camera.position,
+            &frustum,
+            &self.visibility,
+            render_distance as i32 + 1,
+        );
+        let slots: Vec<_> = self
+            .meshes
+            .iter()
+            .filter(|(position, _)| visible.contains(position))
+            .map(|(position, slot)| (*position, slot))
+            .collect();
+        let drawn = slots.len() as u32;
+        let set_origin = |position: ChunkPos| {
+            let origin = position.origin().map(|value| value as f32);
+            program.set_vec3(c"u_chunk_origin", origin.into());
+        };
+        // Only around the chunk draws, the deferred resolve has to fill the screen
+        gl_lib::set_wireframe(self.debug_view.wireframe);
+        self.buffers.draw(slots, set_origin);
+        gl_lib::set_wireframe(false);
+
+        if let (Some(deferred), Some(output)) = (deferred, output) {
+            deferred.resolve(output, assets, camera, cycle, shadows, fog);
+            program.set_int(c"u_deferred", 0);
+            bind_textures();
+            program.bind();
+        }
+        if let Some(draw_entities) = entities {
+            draw_entities();
+            RenderState::OPAQUE.apply();
+            bind_textures();
+            program.bind();
+        }
+        let half = CHUNK_SIZE as f32 / 2.0;
+        let distance = |position: ChunkPos| {
+            let center =
+                position.origin().map(|value| value as f32) + Vector3::new(half, half, half);
+            camera.position.distance2(center)
+        };
+        let mut translucent: Vec<_> = self
+            .translucent
+            .iter()
+            .filter(|(position, _)| visible.contains(position))
+            .map(|(position, slot)| (*position, slot))
+            .collect();
+        translucent.sort_by(|(a, _), (b, _)| distance(*b).total_cmp(&distance(*a)));
+        match water {
+            Some(water) => {
+                water.capture();
+                water.bind(program, 2);
+            }
+            None => {
+                program.set_int(c"u_fancy_water", 0);
+                program.set_int(c"u_reflection_color", 2);
+                program.set_int(c"u_reflection_depth", 3);
+            }
+        }
+        RenderState::TRANSLUCENT.apply();
+        program.set_int(c"u_translucent", 1);
+        gl_lib::set_wireframe(self.debug_view.wireframe);
+        self.buffers.draw_in_order(translucent, set_origin);
+        gl_lib::set_wireframe(false);
+        (drawn, self.meshes.len() as u32 - drawn)
+    }
+
+    /// Every block's textures, as the chunks use them
+    pub fn textures(&self) -> &BlockTextures {
+        &self.textures
+    }
+
+    /// Opaque meshes inside `frustum` with only their depth, for the shadow pass. `program`
+    /// is already bound, block textures go on unit 0 so cutouts can be discarded.
+    pub fn draw_casters(&self, program: &ShaderProgram, frustum: &Frustum) {
+        self.textures.bind(0);
+        let size = CHUNK_SIZE as f32;
+        let slots = self.meshes.iter().filter(|(position, _)| {
+            let min = position.origin().map(|value| value as f32);
+            frustum.intersects_box(min, min + Vector3::new(size, size, size))
+        });
+        self.buffers.draw(
+            slots.map(|(position, slot)| (*position, slot)),
+            |position| {
+                let origin = position.origin().map(|value| value as f32);
+                program.set_vec3(c"u_chunk_origin", origin.into());
+            },
+        );
+    }
+}
@@ -0,0 +1,426 @@
+use anyhow::{bail, Context};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::nbt::{self, Tag};
+use super::region::RegionStorage;
+use super::{PlayerData, WorldInfo};
+use crate::assets::Assets;
+use crate::game::components::GameMode;
+use crate::game::health::MAX_HEALTH;
+use crate::game::hunger::{MAX_FOOD, START_SATURATION};
+use crate::game::item::ItemRegistry;
+use crate::world::chunk::{Chunk, ChunkPos, LocalPos, CHUNK_SIZE, CHUNK_VOLUME};
+use crate::world::{BlockId, BlockRegistry};
+
+const MAPPING_PATH: &str = "import/anvil_blocks.json";
+/// Chunk columns along each side of an Anvil region
+const REGION_SIZE: usize = 32;
+const SECTOR_SIZE: usize = 4096;
+/// Data version of 1.16, block states stopped spanning two longs from there on
+const PADDED_STATES_VERSION: i64 = 2529;
+
+/// Minecraft block names to RustCraft blocks, loaded from `import/anvil_blocks.json`.
+/// Block state properties are ignored, stairs facing any way map the same.
+pub struct BlockMapping {
+    blocks: HashMap<String, BlockId>,
+    /// For everything the table doesn't list
+    fallback: BlockId,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MappingFile {
+    fallback: String,
+    blocks: HashMap<String, String>,
+}
+
+impl BlockMapping {
+    pub fn load(assets: &Assets, registry: &BlockRegistry) -> anyhow::Result<Self> {
+        let bytes = assets.read(MAPPING_PATH)?;
+        let file: MappingFile = serde_json::from_slice(&bytes)
+            .with_context(|| format!("Failed to parse {MAPPING_PATH}"))?;
+        let lookup = |name: &str| {
+            registry
+                .id(name)
+                .with_context(|| format!("Unknown block {name} in {MAPPING_PATH}"))
+        };
+        let blocks = file
+            .blocks
+            .iter()
+            .map(|(from, to)| Ok((from.clone(), lookup(to)?)))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(BlockMapping {
+            blocks,
+            fallback: lookup(&file.fallback)?,
+        })
+    }
+
+    /// Names without a mapping are collected in `unknown` so they can be reported once
+    fn get(&self, name: &str, unknown: &mut BTreeSet<String>) -> BlockId {
+        self.blocks.get(name).copied().unwrap_or_else(|| {
+            unknown.insert(name.to_owned());
+            self.fallback
+        })
+    }
+}
+
+/// Create a new world called `name` out of the Minecraft save in `source`, from 1.13 on.
+/// Chunks that can't be read are skipped, the world is deleted again if anything else fails.
+/// Only blocks come across, chests and furnaces start out empty.
+pub fn import_world(
+    source: &Path,
+    name: &str,
+    registry: Arc<BlockRegistry>,
+    items: Arc<ItemRegistry>,
+    mapping: &BlockMapping,
+) -> anyhow::Result<WorldInfo> {
+    let level = read_level(source)?;
+    let data = level.get("Data").context("level.dat has no Data")?;
+    // 1.16 moved the seed into the generator settings
+    let seed = data
+        .get("WorldGenSettings")
+        .and_then(|settings| settings.get("seed"))
+        .or_else(|| data.get("RandomSeed"))
+        .and_then(Tag::as_i64)
+        .unwrap_or(0);
+
+    let mut info = super::create_world(name, seed as u64)?;
+    info.time = data.get("Time").and_then(Tag::as_i64).unwrap_or(0).max(0) as u64;
+    let raining = data.get("raining").and_then(Tag::as_i64).unwrap_or(0) != 0;
+    // 0 lets Minecraft pick, the default schedule does the same here
+    let remaining = data.get("rainTime").and_then(Tag::as_i64).unwrap_or(0);
+    if remaining > 0 {
+        info.weather.set(raining, remaining as u64);
+        info.weather.strength = if raining { 1.0 } else { 0.0 };
+    }
+    let result =
+        import_into(source, data, &info, registry, items, mapping).and_then(|()| info.save());
+    if let Err(err) = result {
+        if let Err(delete_err) = super::delete_world(&info) {
+            log::warn!("Failed to clean up after the import: {delete_err:#}");
+        }
+        return Err(err);
+    }
+    Ok(info)
+}
+
+fn import_into(
+    source: &Path,
+    data: &Tag,
+    info: &WorldInfo,
+    registry: Arc<BlockRegistry>,
+    items: Arc<ItemRegistry>,
+    mapping: &BlockMapping,
+) -> anyhow::Result<()> {
+    let mut storage = RegionStorage::new(&info.directory, registry, items);
+    let mut unknown = BTreeSet::new();
+    let mut imported = 0;
+
+    let regions = source.join("region");
+    let entries =
+        fs::read_dir(&regions).with_context(|| format!("Failed to list {}", regions.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(region) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(parse_region_name)
+        else {
+            continue;
+        };
+        match import_region(&path, region, &mut storage, mapping, &mut unknown) {
+            Ok(count) => imported += count,
+            Err(err) => log::warn!("Skipping {}: {err:#}", path.display()),
+        }
+    }
+    log::info!("Imported {imported} chunks from {}", source.display());
+    if !unknown.is_empty() {
+        let names: Vec<_> = unknown.into_iter().collect();
+        log::warn!("No mapping for these blocks: {}", names.join(", "));
+    }
+
+    super::save_player(&info.directory, &player(data))
+}
+
+fn read_level(source: &Path) -> anyhow::Result<Tag> {
+    let path = source.join("level.dat");
+    let compressed =
+        fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut bytes = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut bytes)
+        .with_context(|| format!("Failed to decompress {}", path.display()))?;
+    nbt::read(&bytes).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Where the singleplayer player was, or the world spawn if it never had one
+fn player(data: &Tag) -> PlayerData {
+    let player = data.get("Player");
+    let position = player
+        .and_then(|player| player.get("Pos"))
+        .and_then(Tag::as_list)
+        .and_then(|values| match values {
+            [x, y, z] => Some([x.as_f64()?, y.as_f64()?, z.as_f64()?].map(|value| value as f32)),
+            _ => None,
+        });
+    let rotation = player
+        .and_then(|player| player.get("Rotation"))
+        .and_then(Tag::as_list)
+        .and_then(|values| match values {
+            [yaw, pitch] => Some([yaw.as_f64()?, pitch.as_f64()?].map(|value| value as f32)),
+            _ => None,
+        });
+
+    let spawn = ["SpawnX", "SpawnY", "SpawnZ"]
+        .map(|key| data.get(key).and_then(Tag::as_i64).unwrap_or(0) as f32);
+    let flying = player
+        .and_then(|player| player.get("abilities"))
+        .and_then(|abilities| abilities.get("flying"))
+        .and_then(Tag::as_i64)
+        .is_some_and(|flying| flying != 0);
+    // Minecraft's health and food count the same half hearts and drumsticks
+    let health = player
+        .and_then(|player| player.get("Health"))
+        .and_then(Tag::as_f64)
+        .map_or(MAX_HEALTH, |health| {
+            (health.max(0.0).ceil() as u32).min(MAX_HEALTH)
+        });
+    let food = player
+        .and_then(|player| player.get("foodLevel"))
+        .and_then(Tag::as_i64)
+        .map_or(MAX_FOOD, |food| food.clamp(0, MAX_FOOD as i64) as u32);
+    let saturation = player
+        .and_then(|player| player.get("foodSaturationLevel"))
+        .and_then(Tag::as_f64)
+        .map_or(START_SATURATION, |saturation| {
+            (saturation as f32).clamp(0.0, food as f32)
+        });
+    // Adventure plays like survival here and spectator like creative
+    let game_mode = match player
+        .and_then(|player| player.get("playerGameType"))
+        .and_then(Tag::as_i64)
+    {
+        Some(1 | 3) => GameMode::Creative,
+        _ => GameMode::Survival,
+    };
+    let experience = player
+        .and_then(|player| player.get("XpTotal"))
+        .and_then(Tag::as_i64)
+        .map_or(0, |total| total.max(0) as u32);
+    // Where the bed they last slept in is, Minecraft keeps it in three separate tags
+    let bed = ["SpawnX", "SpawnY", "SpawnZ"].map(|key| {
+        player
+            .and_then(|player| player.get(key))
+            .and_then(Tag::as_i64)
+            .map(|value| value as i32)
+    });
+    let [yaw, pitch] = rotation.unwrap_or_default();
+    PlayerData {
+        position: position.unwrap_or([spawn[0] + 0.5, spawn[1], spawn[2] + 0.5]),
+        // Minecraft's yaw starts out facing +Z and its pitch is positive looking down
+        yaw: (yaw + 180.0).to_radians().rem_euclid(std::f32::consts::TAU),
+        pitch: -pitch.to_radians(),
+        flying,
+        // Items don't map onto blocks, the player starts out empty handed
+        inventory: Vec::new(),
+        selected_slot: 0,
+        health,
+        food,
+        saturation,
+        game_mode,
+        experience,
+        spawn: match bed {
+            [Some(x), Some(y), Some(z)] => Some([x, y, z]),
+            _ => None,
+        },
+    }
+}
+
+/// Region coordinates from a name like `r.-1.2.mca`
+fn parse_region_name(name: &str) -> Option<[i32; 2]> {
+    let rest = name.strip_prefix("r.")?.strip_suffix(".mca")?;
+    let (x, z) = rest.split_once('.')?;
+    Some([x.parse().ok()?, z.parse().ok()?])
+}
+
+/// Returns how many chunks were written, a column with any broken data is skipped whole
+fn import_region(
+    path: &Path,
+    region: [i32; 2],
+    storage: &mut RegionStorage,
+    mapping: &BlockMapping,
+    unknown: &mut BTreeSet<String>,
+) -> anyhow::Result<usize> {
+    let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    // Minecraft leaves empty region files around
+    if data.len() < 2 * SECTOR_SIZE {
+        return Ok(0);
+    }
+
+    let mut count = 0;
+    for index in 0..REGION_SIZE * REGION_SIZE {
+        let location = &data[index * 4..index * 4 + 4];
+        let sector = u32::from_be_bytes([0, location[0], location[1], location[2]]) as usize;
+        if sector == 0 {
+            continue;
+        }
+        let column = [
+            region[0] * REGION_SIZE as i32 + (index % REGION_SIZE) as i32,
+            region[1] * REGION_SIZE as i32 + (index / REGION_SIZE) as i32,
+        ];
+        let result = read_column(&data, sector * SECTOR_SIZE)
+            .and_then(|tag| import_column(&tag, column, storage, mapping, unknown));
+        match result {
+            Ok(written) => count += written,
+            Err(err) => log::warn!("Skipping column {column:?}: {err:#}"),
+        }
+    }
+    Ok(count)
+}
+
+fn read_column(data: &[u8], offset: usize) -> anyhow::Result<Tag> {
+    let header = data
+        .get(offset..offset + 5)
+        .context("Chunk starts past the end of the file")?;
+    // The length counts the compression byte too
+    let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let body = data
+        .get(offset + 5..offset + 4 + length.max(1))
+        .context("Chunk runs past the end of the file")?;
+
+    let mut bytes = Vec::new();
+    match header[4] {
+        1 => {
+            GzDecoder::new(body).read_to_end(&mut bytes)?;
+        }
+        2 => {
+            ZlibDecoder::new(body).read_to_end(&mut bytes)?;
+        }
+        3 => bytes.extend_from_slice(body),
+        compression => bail!("Unsupported compression type {compression}"),
+    }
+    nbt::read(&bytes)
+}
+
+/// Every section with blocks in it becomes a chunk, Minecraft's sections are the same 16
+/// cubed and lay their blocks out in the same order
+fn import_column(
+    tag: &Tag,
+    [x, z]: [i32; 2],
+    storage: &mut RegionStorage,
+    mapping: &BlockMapping,
+    unknown: &mut BTreeSet<String>,
+) -> anyhow::Result<usize> {
+    let version = tag.get("DataVersion").and_then(Tag::as_i64).unwrap_or(0);
+    // 1.18 took everything out of the Level compound and renamed the sections
+    let level = tag.get("Level").unwrap_or(tag);
+    let sections = level
+        .get("sections")
+        .or_else(|| level.get("Sections"))
+        .and_then(Tag::as_list)
+        .unwrap_or_default();
+
+    let mut chunks = Vec::new();
+    for section in sections {
+        let Some(y) = section.get("Y").and_then(Tag::as_i64) else {
+            continue;
+        };
+        let (palette, states) = match section.get("block_states") {
+            Some(block_states) => (block_states.get("palette"), block_states.get("data")),
+            None => (section.get("Palette"), section.get("BlockStates")),
+        };
+        // Sections with only light in them, or numeric ids from before 1.13
+        let Some(palette) = palette.and_then(Tag::as_list) else {
+            continue;
+        };
+        let ids: Vec<_> = palette
+            .iter()
+            .map(|entry| {
+                entry
+                    .get("Name")
+                    .and_then(Tag::as_str)
+                    .map_or(BlockId::AIR, |name| mapping.get(name, unknown))
+            })
+            .collect();
+
+        let chunk = match (ids.as_slice(), states.and_then(Tag::as_long_array)) {
+            ([], _) => continue,
+            ([only], _) => Chunk::filled(*only),
+            (_, None) => bail!("Section {y} has a palette but no block states"),
+            (_, Some(states)) => {
+                let padded = version >= PADDED_STATES_VERSION;
+                let mut chunk = Chunk::new();
+                for (index, slot) in unpack(states, bits_for(ids.len()), padded)?
+                    .into_iter()
+                    .enumerate()
+                {
+                    let id = *ids
+                        .get(slot)
+                        .with_context(|| format!("Section {y} has a block outside its palette"))?;
+                    chunk.set(local_at(index), id);
+                }
+                chunk
+            }
+        };
+        if !chunk.is_empty() {
+            chunks.push((ChunkPos::new(x, y as i32, z), chunk));
+        }
+    }
+
+    // Nothing gets written until the whole column read fine
+    let count = chunks.len();
+    for (position, chunk) in chunks {
+        storage.save_chunk(position, &chunk)?;
+    }
+    Ok(count)
+}
+
+/// Block states take at least 4 bits each
+fn bits_for(palette_length: usize) -> u32 {
+    (usize::BITS - (palette_length - 1).leading_zeros()).max(4)
+}
+
+/// Palette indices packed into longs, either padded so none crosses into the next long or,
+/// before 1.16, packed tight across them
+fn unpack(states: &[i64], bits: u32, padded: bool) -> anyhow::Result<Vec<usize>> {
+    let mask = (1u64 << bits) - 1;
+    let word = |index: usize| {
+        states
+            .get(index)
+            .map(|&value| value as u64)
+            .context("Block states end early")
+    };
+    (0..CHUNK_VOLUME)
+        .map(|index| {
+            if padded {
+                let per_long = (64 / bits) as usize;
+                let shift = (index % per_long) as u32 * bits;
+                Ok(((word(index / per_long)? >> shift) & mask) as usize)
+            } else {
+                let bit = index * bits as usize;
+                let shift = (bit % 64) as u32;
+                let mut value = word(bit / 64)? >> shift;
+                if shift + bits > 64 {
+                    value |= word(bit / 64 + 1)? << (64 - shift);
+                }
+                Ok((value & mask) as usize)
+            }
+        })
+        .collect()
+}
+
+/// Inverse of the chunk's own Y, Z, X indexing
+fn local_at(index: usize) -> LocalPos {
+    let size = CHUNK_SIZE as usize;
+    LocalPos::new(
+        (index % size) as u8,
+        (index / (size * size)) as u8,
+        (index / size % size) as u8,
+    )
+}
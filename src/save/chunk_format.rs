@@ -0,0 +1,362 @@
+use anyhow::{bail, Context};
+use std::collections::HashMap;
+
+use crate::game::enchanting::Modifiers;
+use crate::game::inventory::ItemStack;
+use crate::game::item::ItemRegistry;
+use crate::world::block_entity::BlockEntity;
+use crate::world::chunk::{Chunk, LocalPos, CHUNK_SIZE, CHUNK_VOLUME};
+use crate::world::{BlockId, BlockRegistry};
+
+/// Starts every chunk written with a header, the first saves were bare block ids
+const MAGIC: [u8; 2] = *b"RC";
+/// Bumped whenever the layout changes or old palettes need a [`Migration`]
+pub const FORMAT_VERSION: u16 = 6;
+/// Chunks are small and get saved often, higher levels barely shrink them further
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Fixes up the palette of a chunk from an older version, e.g. after a block got renamed
+type Migration = fn(&mut [String]);
+
+/// Run in order on every chunk older than the version they're paired with
+const MIGRATIONS: &[(u16, Migration)] = &[];
+
+/// What every version decodes to before migrating: block names and each block's index into
+/// them, in the same Y, Z, X order chunks index by
+struct Decoded {
+    palette: Vec<String>,
+    blocks: Vec<u16>,
+    block_entities: Vec<DecodedBlockEntity>,
+}
+
+/// A block entity with its items still named
+struct DecodedBlockEntity {
+    local: LocalPos,
+    kind: String,
+    state: Vec<u32>,
+    /// Slot index, item name, count, damage and modifiers of every filled slot
+    slots: Vec<(u16, String, u16, u32, Modifiers)>,
+    text: Vec<String>,
+}
+
+/// Header, then compressed: the palette as length-prefixed names, runs of equal blocks as a
+/// length and a palette index, then the block entities. Every number is a little-endian u16
+/// unless it says otherwise.
+///
+/// A block entity is its local position as three bytes, its kind as a name, a count of state
+/// numbers and each as a u32, then a count of filled slots and each as its index, the item's
+/// name, how many of it there are, its damage as a u32 and the levels of its efficiency and
+/// unbreaking as a byte each, then a count of lines of text and each as a name.
+pub fn encode(
+    chunk: &Chunk,
+    registry: &BlockRegistry,
+    items: &ItemRegistry,
+) -> anyhow::Result<Vec<u8>> {
+    let mut palette = Vec::new();
+    let mut slots = HashMap::new();
+    let mut runs: Vec<(u16, u16)> = Vec::new();
+    for_each_local(|local| {
+        let id = chunk.get(local);
+        let slot = *slots.entry(id).or_insert_with(|| {
+            palette.push(id);
+            palette.len() as u16 - 1
+        });
+        match runs.last_mut() {
+            Some((length, last)) if *last == slot => *length += 1,
+            _ => runs.push((1, slot)),
+        }
+    });
+
+    let mut body = Vec::new();
+    body.extend_from_slice(&(palette.len() as u16).to_le_bytes());
+    for id in palette {
+        // Ids the registry doesn't know came from a save with more blocks, all that's left
+        // to keep is air
+        let name = registry.get(id).map_or("air", |block| block.name.as_str());
+        write_name(&mut body, name);
+    }
+    for (length, slot) in runs {
+        body.extend_from_slice(&length.to_le_bytes());
+        body.extend_from_slice(&slot.to_le_bytes());
+    }
+
+    let entities: Vec<_> = chunk.block_entities().collect();
+    body.extend_from_slice(&(entities.len() as u16).to_le_bytes());
+    for (local, entity) in entities {
+        body.extend_from_slice(&[local.x, local.y, local.z]);
+        write_name(&mut body, entity.kind());
+        let state = entity.state();
+        body.extend_from_slice(&(state.len() as u16).to_le_bytes());
+        for number in state {
+            body.extend_from_slice(&number.to_le_bytes());
+        }
+        // Stacks of items the registry doesn't know are already gone
+        let slots: Vec<_> = entity
+            .slots()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, stack)| {
+                let stack = (*stack)?;
+                Some((index, items.get(stack.item)?.name.as_str(), stack))
+            })
+            .collect();
+        body.extend_from_slice(&(slots.len() as u16).to_le_bytes());
+        for (index, name, stack) in slots {
+            body.extend_from_slice(&(index as u16).to_le_bytes());
+            write_name(&mut body, name);
+            body.extend_from_slice(&(stack.count as u16).to_le_bytes());
+            body.extend_from_slice(&stack.damage.to_le_bytes());
+            body.extend_from_slice(&[stack.modifiers.efficiency, stack.modifiers.unbreaking]);
+        }
+        let text = entity.text();
+        body.extend_from_slice(&(text.len() as u16).to_le_bytes());
+        for line in text {
+            write_name(&mut body, line);
+        }
+    }
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&MAGIC);
+    data.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    let compressed =
+        zstd::encode_all(body.as_slice(), COMPRESSION_LEVEL).context("Failed to compress chunk")?;
+    data.extend_from_slice(&compressed);
+    Ok(data)
+}
+
+/// Reads any version up to [`FORMAT_VERSION`], blocks that no longer exist become air and
+/// items that no longer exist are dropped
+pub fn decode(
+    data: &[u8],
+    registry: &BlockRegistry,
+    items: &ItemRegistry,
+) -> anyhow::Result<Chunk> {
+    let (version, mut decoded) = match data {
+        [first, second, low, high, body @ ..] if [*first, *second] == MAGIC => {
+            let version = u16::from_le_bytes([*low, *high]);
+            (version, decode_body(version, body)?)
+        }
+        _ => (1, decode_ids(data, registry)?),
+    };
+    for (since, migrate) in MIGRATIONS {
+        if version < *since {
+            migrate(&mut decoded.palette);
+        }
+    }
+
+    let ids: Vec<_> = decoded
+        .palette
+        .iter()
+        .map(|name| {
+            registry.id(name).unwrap_or_else(|| {
+                log::warn!("Unknown block '{name}' in a saved chunk, replacing it with air");
+                BlockId::AIR
+            })
+        })
+        .collect();
+    let mut chunk = Chunk::new();
+    let mut blocks = decoded.blocks.iter();
+    for_each_local(|local| {
+        if let Some(&slot) = blocks.next() {
+            chunk.set(local, ids[slot as usize]);
+        }
+    });
+
+    for saved in decoded.block_entities {
+        let Some(mut entity) = BlockEntity::load(&saved.kind, &saved.state) else {
+            log::warn!("Unknown block entity '{}' in a saved chunk", saved.kind);
+            continue;
+        };
+        for (index, name, count, damage, modifiers) in saved.slots {
+            let Some(item) = items.id(&name) else {
+                log::warn!(
+                    "Dropping {count} of unknown item {name} from a {}",
+                    saved.kind
+                );
+                continue;
+            };
+            let count = u32::from(count).min(items.max_stack(item));
+            if let Some(slot) = entity.slots_mut().get_mut(index as usize) {
+                *slot = (count > 0).then_some(ItemStack {
+                    item,
+                    count,
+                    damage,
+                    modifiers,
+                });
+            }
+        }
+        for (line, saved) in entity.text_mut().iter_mut().zip(saved.text) {
+            *line = saved;
+        }
+        chunk.insert_block_entity(saved.local, entity);
+    }
+    Ok(chunk)
+}
+
+fn decode_body(version: u16, body: &[u8]) -> anyhow::Result<Decoded> {
+    match version {
+        2..=6 => decode_runs(version, body),
+        _ if version > FORMAT_VERSION => {
+            bail!("Chunk was saved by a newer version of the game (format {version})")
+        }
+        _ => bail!("Unknown chunk format {version}"),
+    }
+}
+
+/// Version 1, every block id as a u16 with no header or compression. The ids meant whatever
+/// the registry says they mean now, nothing else was recorded.
+fn decode_ids(data: &[u8], registry: &BlockRegistry) -> anyhow::Result<Decoded> {
+    if data.len() != CHUNK_VOLUME * 2 {
+        bail!(
+            "Chunk data is {} bytes, expected {}",
+            data.len(),
+            CHUNK_VOLUME * 2
+        );
+    }
+    let mut palette = Vec::new();
+    let mut slots = HashMap::new();
+    let blocks = data
+        .chunks_exact(2)
+        .map(|bytes| {
+            let id = BlockId(u16::from_le_bytes([bytes[0], bytes[1]]));
+            *slots.entry(id).or_insert_with(|| {
+                let name = registry.get(id).map_or("air", |block| block.name.as_str());
+                palette.push(name.to_owned());
+                palette.len() as u16 - 1
+            })
+        })
+        .collect();
+    Ok(Decoded {
+        palette,
+        blocks,
+        block_entities: Vec::new(),
+    })
+}
+
+/// Version 6, the layout [`encode`] writes, version 5 which had no text in its block
+/// entities, version 4 which had no modifiers in its slots either, version 3 which had no
+/// damage either and version 2 which ended after the runs
+fn decode_runs(version: u16, body: &[u8]) -> anyhow::Result<Decoded> {
+    let body = zstd::decode_all(body).context("Failed to decompress chunk")?;
+    let mut reader = Reader { data: &body };
+
+    let palette_length = reader.u16()?;
+    let mut palette = Vec::with_capacity(palette_length as usize);
+    for _ in 0..palette_length {
+        palette.push(reader.name()?);
+    }
+
+    let mut blocks = Vec::with_capacity(CHUNK_VOLUME);
+    while blocks.len() < CHUNK_VOLUME {
+        let length = reader.u16()? as usize;
+        let slot = reader.u16()?;
+        if slot as usize >= palette.len() {
+            bail!("Palette index {slot} is out of range");
+        }
+        if blocks.len() + length > CHUNK_VOLUME {
+            bail!("Runs add up to more than a chunk");
+        }
+        blocks.resize(blocks.len() + length, slot);
+    }
+
+    let mut block_entities = Vec::new();
+    if version >= 3 {
+        for _ in 0..reader.u16()? {
+            let [x, y, z] = [reader.u8()?, reader.u8()?, reader.u8()?];
+            if [x, y, z].iter().any(|axis| i32::from(*axis) >= CHUNK_SIZE) {
+                bail!("Block entity at {x}, {y}, {z} is outside the chunk");
+            }
+            let kind = reader.name()?;
+            let state = (0..reader.u16()?)
+                .map(|_| reader.u32())
+                .collect::<anyhow::Result<_>>()?;
+            let slots = (0..reader.u16()?)
+                .map(|_| {
+                    let (index, name, count) = (reader.u16()?, reader.name()?, reader.u16()?);
+                    let damage = if version >= 4 { reader.u32()? } else { 0 };
+                    let modifiers = if version >= 5 {
+                        Modifiers {
+                            efficiency: reader.u8()?,
+                            unbreaking: reader.u8()?,
+                        }
+                    } else {
+                        Modifiers::default()
+                    };
+                    Ok((index, name, count, damage, modifiers))
+                })
+                .collect::<anyhow::Result<_>>()?;
+            let text = if version >= 6 {
+                (0..reader.u16()?)
+                    .map(|_| reader.name())
+                    .collect::<anyhow::Result<_>>()?
+            } else {
+                Vec::new()
+            };
+            block_entities.push(DecodedBlockEntity {
+                local: LocalPos::new(x, y, z),
+                kind,
+                state,
+                slots,
+                text,
+            });
+        }
+    }
+    Ok(Decoded {
+        palette,
+        blocks,
+        block_entities,
+    })
+}
+
+/// Length-prefixed, the way [`Reader::name`] reads it back
+fn write_name(body: &mut Vec<u8>, name: &str) {
+    body.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    body.extend_from_slice(name.as_bytes());
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> anyhow::Result<&'a [u8]> {
+        if count > self.data.len() {
+            bail!("Chunk data ends early");
+        }
+        let (taken, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> anyhow::Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// A block or item name or a line of text, UTF-8 after its length
+    fn name(&mut self) -> anyhow::Result<String> {
+        let length = self.u16()?;
+        let name = std::str::from_utf8(self.take(length as usize)?).context("Name isn't UTF-8")?;
+        Ok(name.to_owned())
+    }
+}
+
+fn for_each_local(mut f: impl FnMut(LocalPos)) {
+    let size = CHUNK_SIZE as u8;
+    for y in 0..size {
+        for z in 0..size {
+            for x in 0..size {
+                f(LocalPos::new(x, y, z));
+            }
+        }
+    }
+}
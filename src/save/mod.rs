@@ -0,0 +1,253 @@
+pub mod anvil;
+pub mod chunk_format;
+pub mod nbt;
+pub mod region;
+pub mod saver;
+pub mod schematic;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::game::components::GameMode;
+use crate::game::enchanting::Modifiers;
+use crate::game::health::MAX_HEALTH;
+use crate::game::hunger::{MAX_FOOD, START_SATURATION};
+use crate::game::weather::Weather;
+use crate::world::generator::GeneratorSettings;
+
+pub const SAVES_DIR: &str = "saves";
+const WORLD_INFO_FILE: &str = "world.json";
+const PLAYER_FILE: &str = "player.json";
+
+/// Metadata stored next to a world's data, enough to list it in the menu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldInfo {
+    /// Display name, free to change without moving the directory
+    pub name: String,
+    pub seed: u64,
+    /// Seconds since the Unix epoch
+    pub last_played: u64,
+    /// Ticks since the world was created, the time of day is this modulo a day's length
+    #[serde(default)]
+    pub time: u64,
+    #[serde(default)]
+    pub generator: GeneratorSettings,
+    #[serde(default)]
+    pub weather: Weather,
+    #[serde(skip)]
+    pub directory: PathBuf,
+}
+
+impl WorldInfo {
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = self.directory.join(WORLD_INFO_FILE);
+        let text = serde_json::to_string_pretty(self)?;
+        fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn touch(&mut self) {
+        self.last_played = now();
+    }
+}
+
+/// Where the player was when the world was last saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerData {
+    /// Feet position in blocks
+    pub position: [f32; 3],
+    /// Radians, the same as the player's look component
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Missing from saves older than flying
+    #[serde(default)]
+    pub flying: bool,
+    /// Only the filled slots, missing from saves older than the inventory
+    #[serde(default)]
+    pub inventory: Vec<SavedStack>,
+    /// Index into the hotbar
+    #[serde(default)]
+    pub selected_slot: usize,
+    /// Hit points, 0 if they quit on the death screen. Full in saves older than health.
+    #[serde(default = "full_health")]
+    pub health: u32,
+    /// Full in saves older than hunger
+    #[serde(default = "full_food")]
+    pub food: u32,
+    #[serde(default = "start_saturation")]
+    pub saturation: f32,
+    /// Survival in saves older than game modes
+    #[serde(default)]
+    pub game_mode: GameMode,
+    /// Experience points earned in total, none in saves older than experience
+    #[serde(default)]
+    pub experience: u32,
+    /// The bed they respawn on, None for the world spawn and in saves older than beds
+    #[serde(default)]
+    pub spawn: Option<[i32; 3]>,
+}
+
+fn full_health() -> u32 {
+    MAX_HEALTH
+}
+
+fn full_food() -> u32 {
+    MAX_FOOD
+}
+
+fn start_saturation() -> f32 {
+    START_SATURATION
+}
+
+/// One filled inventory slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedStack {
+    pub slot: usize,
+    /// Item name, ids change as items are added. Saves from before items stored the block's
+    /// name, which is also its item's.
+    #[serde(alias = "block")]
+    pub item: String,
+    pub count: u32,
+    /// Uses a tool has had
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub damage: u32,
+    /// What the enchanting table added to a tool
+    #[serde(default, skip_serializing_if = "Modifiers::is_empty")]
+    pub modifiers: Modifiers,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+/// None for a world the player was never saved in
+pub fn load_player(directory: &Path) -> anyhow::Result<Option<PlayerData>> {
+    let path = directory.join(PLAYER_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let player = serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(player))
+}
+
+pub fn save_player(directory: &Path, player: &PlayerData) -> anyhow::Result<()> {
+    let path = directory.join(PLAYER_FILE);
+    let text = serde_json::to_string_pretty(player)?;
+    fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Every readable world in [`SAVES_DIR`], most recently played first
+pub fn list_worlds() -> anyhow::Result<Vec<WorldInfo>> {
+    let saves = Path::new(SAVES_DIR);
+    if !saves.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut worlds = Vec::new();
+    let entries =
+        fs::read_dir(saves).with_context(|| format!("Failed to list {}", saves.display()))?;
+    for entry in entries.flatten() {
+        let directory = entry.path();
+        if !directory.is_dir() {
+            continue;
+        }
+        match load_info(&directory) {
+            Ok(info) => worlds.push(info),
+            Err(err) => log::warn!("Skipping world {}: {err:#}", directory.display()),
+        }
+    }
+    worlds.sort_by_key(|world| Reverse(world.last_played));
+    Ok(worlds)
+}
+
+pub fn load_info(directory: &Path) -> anyhow::Result<WorldInfo> {
+    let path = directory.join(WORLD_INFO_FILE);
+    let text =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut info: WorldInfo = serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    info.directory = directory.to_owned();
+    Ok(info)
+}
+
+pub fn create_world(name: &str, seed: u64) -> anyhow::Result<WorldInfo> {
+    let directory = unique_directory(name);
+    fs::create_dir_all(&directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+    let info = WorldInfo {
+        name: name.to_owned(),
+        seed,
+        last_played: now(),
+        time: 0,
+        generator: GeneratorSettings::default(),
+        weather: Weather::default(),
+        directory,
+    };
+    info.save()?;
+    Ok(info)
+}
+
+pub fn delete_world(info: &WorldInfo) -> anyhow::Result<()> {
+    fs::remove_dir_all(&info.directory)
+        .with_context(|| format!("Failed to delete {}", info.directory.display()))
+}
+
+/// Numbers are used as-is, any other text is hashed and nothing at all picks a random seed
+pub fn parse_seed(text: &str) -> u64 {
+    let text = text.trim();
+    if text.is_empty() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        return fnv1a(&nanos.to_le_bytes());
+    }
+    if let Ok(seed) = text.parse::<i64>() {
+        return seed as u64;
+    }
+    fnv1a(text.as_bytes())
+}
+
+/// Stable across runs and platforms, unlike the std hasher
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Directory names only keep characters that are safe on every file system
+fn unique_directory(name: &str) -> PathBuf {
+    let mut base: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if base.trim_matches('_').is_empty() {
+        base = String::from("world");
+    }
+
+    let saves = Path::new(SAVES_DIR);
+    let mut directory = saves.join(&base);
+    let mut counter = 1;
+    while directory.exists() {
+        directory = saves.join(format!("{base}-{counter}"));
+        counter += 1;
+    }
+    directory
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
@@ -0,0 +1,187 @@
+use anyhow::bail;
+use std::collections::HashMap;
+
+/// Compounds nested deeper than this are rejected rather than overflowing the stack
+const MAX_DEPTH: usize = 512;
+
+/// A value of Minecraft's Named Binary Tag format, big-endian throughout
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(HashMap<String, Tag>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    /// None unless this is a compound with that key
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(entries) => entries.get(key),
+            _ => None,
+        }
+    }
+
+    /// Any of the integer types, widened
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Tag::Byte(value) => Some(value.into()),
+            Tag::Short(value) => Some(value.into()),
+            Tag::Int(value) => Some(value.into()),
+            Tag::Long(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Either floating point type, widened
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Tag::Float(value) => Some(value.into()),
+            Tag::Double(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_long_array(&self) -> Option<&[i64]> {
+        match self {
+            Tag::LongArray(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// An uncompressed root tag: its type, a name that gets thrown away and the value
+pub fn read(data: &[u8]) -> anyhow::Result<Tag> {
+    let mut reader = Reader { data };
+    let id = reader.u8()?;
+    if id == END {
+        bail!("NBT data has no root tag");
+    }
+    reader.string()?;
+    reader.payload(id, 0)
+}
+
+const END: u8 = 0;
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        let Some((bytes, rest)) = self.data.split_first_chunk::<N>() else {
+            bail!("NBT data ends early");
+        };
+        self.data = rest;
+        Ok(*bytes)
+    }
+
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    /// Array and list lengths, negative ones count as empty like Minecraft reads them
+    fn length(&mut self) -> anyhow::Result<usize> {
+        let length = i32::from_be_bytes(self.take()?).max(0) as usize;
+        // Every element takes at least a byte, this stops a bad length from reserving
+        // gigabytes before running out of data
+        if length > self.data.len() {
+            bail!("NBT length {length} is past the end of the data");
+        }
+        Ok(length)
+    }
+
+    /// Java's modified UTF-8 is close enough to the real thing for block names
+    fn string(&mut self) -> anyhow::Result<String> {
+        let length = u16::from_be_bytes(self.take()?) as usize;
+        if length > self.data.len() {
+            bail!("NBT data ends early");
+        }
+        let (bytes, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> anyhow::Result<Tag> {
+        if depth > MAX_DEPTH {
+            bail!("NBT nests deeper than {MAX_DEPTH} levels");
+        }
+        Ok(match id {
+            1 => Tag::Byte(i8::from_be_bytes(self.take()?)),
+            2 => Tag::Short(i16::from_be_bytes(self.take()?)),
+            3 => Tag::Int(i32::from_be_bytes(self.take()?)),
+            4 => Tag::Long(i64::from_be_bytes(self.take()?)),
+            5 => Tag::Float(f32::from_be_bytes(self.take()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.take()?)),
+            7 => {
+                let length = self.length()?;
+                let values = (0..length)
+                    .map(|_| self.take().map(i8::from_be_bytes))
+                    .collect::<anyhow::Result<_>>()?;
+                Tag::ByteArray(values)
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let element = self.u8()?;
+                let length = self.length()?;
+                if element == END {
+                    Tag::List(Vec::new())
+                } else {
+                    let values = (0..length)
+                        .map(|_| self.payload(element, depth + 1))
+                        .collect::<anyhow::Result<_>>()?;
+                    Tag::List(values)
+                }
+            }
+            10 => {
+                let mut entries = HashMap::new();
+                loop {
+                    let id = self.u8()?;
+                    if id == END {
+                        break;
+                    }
+                    let name = self.string()?;
+                    entries.insert(name, self.payload(id, depth + 1)?);
+                }
+                Tag::Compound(entries)
+            }
+            11 => {
+                let length = self.length()?;
+                let values = (0..length)
+                    .map(|_| self.take().map(i32::from_be_bytes))
+                    .collect::<anyhow::Result<_>>()?;
+                Tag::IntArray(values)
+            }
+            12 => {
+                let length = self.length()?;
+                let values = (0..length)
+                    .map(|_| self.take().map(i64::from_be_bytes))
+                    .collect::<anyhow::Result<_>>()?;
+                Tag::LongArray(values)
+            }
+            _ => bail!("Unknown NBT tag type {id}"),
+        })
+    }
+}
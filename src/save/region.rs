@@ -0,0 +1,206 @@
+use anyhow::Context;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::chunk_format;
+use crate::game::item::ItemRegistry;
+use crate::world::chunk::{Chunk, ChunkPos};
+use crate::world::BlockRegistry;
+
+/// Chunks along each side of a region, every region is one file
+pub const REGION_SIZE: i32 = 16;
+const REGION_VOLUME: usize = (REGION_SIZE * REGION_SIZE * REGION_SIZE) as usize;
+/// Chunk data starts on these boundaries, so a chunk that grows a little usually still fits
+/// where it was
+const SECTOR_SIZE: u64 = 4096;
+/// First sector and length in bytes of a chunk, both u32
+const ENTRY_SIZE: usize = 8;
+const HEADER_SECTORS: u32 = ((REGION_VOLUME * ENTRY_SIZE) as u64 / SECTOR_SIZE) as u32;
+const REGION_DIR: &str = "region";
+
+/// Where a chunk is in its region file, a length of 0 means it was never saved
+#[derive(Debug, Copy, Clone, Default)]
+struct Entry {
+    sector: u32,
+    length: u32,
+}
+
+impl Entry {
+    fn sectors(self) -> u32 {
+        sectors_for(self.length as usize)
+    }
+}
+
+/// A table with an [`Entry`] for every chunk of the region, followed by the chunks' data in
+/// whole sectors
+pub struct RegionFile {
+    file: File,
+    entries: Vec<Entry>,
+    /// First sector past the last chunk, new data goes here
+    end: u32,
+}
+
+impl RegionFile {
+    /// Creates the file with an empty table if it doesn't exist
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut header = vec![0; REGION_VOLUME * ENTRY_SIZE];
+        if file.metadata()?.len() == 0 {
+            file.write_all(&header)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        } else {
+            file.read_exact(&mut header)
+                .with_context(|| format!("{} is too short for a region file", path.display()))?;
+        }
+
+        let entries: Vec<_> = header
+            .chunks_exact(ENTRY_SIZE)
+            .map(|bytes| Entry {
+                sector: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+                length: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            })
+            .collect();
+        let end = entries
+            .iter()
+            .filter(|entry| entry.length > 0)
+            .map(|entry| entry.sector + entry.sectors())
+            .max()
+            .unwrap_or(0)
+            .max(HEADER_SECTORS);
+        Ok(RegionFile { file, entries, end })
+    }
+
+    /// None if the chunk was never saved
+    pub fn read(&mut self, index: usize) -> io::Result<Option<Vec<u8>>> {
+        let entry = self.entries[index];
+        if entry.length == 0 {
+            return Ok(None);
+        }
+        self.file
+            .seek(SeekFrom::Start(entry.sector as u64 * SECTOR_SIZE))?;
+        let mut data = vec![0; entry.length as usize];
+        self.file.read_exact(&mut data)?;
+        Ok(Some(data))
+    }
+
+    /// Overwrites the old copy if the new one fits in its sectors, otherwise it goes to the
+    /// end of the file and the old sectors are left unused
+    pub fn write(&mut self, index: usize, data: &[u8]) -> io::Result<()> {
+        let mut entry = self.entries[index];
+        let sectors = sectors_for(data.len());
+        if entry.length == 0 || sectors > entry.sectors() {
+            entry.sector = self.end;
+            self.end += sectors;
+        }
+        entry.length = data.len() as u32;
+
+        self.file
+            .seek(SeekFrom::Start(entry.sector as u64 * SECTOR_SIZE))?;
+        self.file.write_all(data)?;
+        // Padding keeps the file a whole number of sectors long
+        let padding = sectors as u64 * SECTOR_SIZE - data.len() as u64;
+        self.file.write_all(&vec![0; padding as usize])?;
+
+        // The table goes last, if writing the data fails the old copy is still what it
+        // points at
+        self.file
+            .seek(SeekFrom::Start((index * ENTRY_SIZE) as u64))?;
+        let mut bytes = [0; ENTRY_SIZE];
+        bytes[..4].copy_from_slice(&entry.sector.to_le_bytes());
+        bytes[4..].copy_from_slice(&entry.length.to_le_bytes());
+        self.file.write_all(&bytes)?;
+        self.entries[index] = entry;
+        Ok(())
+    }
+}
+
+/// Every region file of a world, opened the first time one of their chunks is needed
+pub struct RegionStorage {
+    directory: PathBuf,
+    regions: HashMap<[i32; 3], RegionFile>,
+    /// Saves name their blocks, this maps the names to ids and back
+    blocks: Arc<BlockRegistry>,
+    /// Same for the items in block entities
+    items: Arc<ItemRegistry>,
+}
+
+impl RegionStorage {
+    /// `world_directory` is the one holding the world info, regions go in a folder inside
+    pub fn new(
+        world_directory: &Path,
+        blocks: Arc<BlockRegistry>,
+        items: Arc<ItemRegistry>,
+    ) -> Self {
+        RegionStorage {
+            directory: world_directory.join(REGION_DIR),
+            regions: HashMap::new(),
+            blocks,
+            items,
+        }
+    }
+
+    /// None if the chunk was never saved
+    pub fn load_chunk(&mut self, position: ChunkPos) -> anyhow::Result<Option<Chunk>> {
+        let (region, index) = locate(position);
+        // Reading shouldn't leave empty region files behind
+        if !self.regions.contains_key(&region) && !self.path(region).exists() {
+            return Ok(None);
+        }
+        let data = self
+            .region(region)?
+            .read(index)
+            .with_context(|| format!("Failed to read chunk {position:?}"))?;
+        data.map(|data| {
+            chunk_format::decode(&data, &self.blocks, &self.items)
+                .with_context(|| format!("Chunk {position:?} is corrupt"))
+        })
+        .transpose()
+    }
+
+    pub fn save_chunk(&mut self, position: ChunkPos, chunk: &Chunk) -> anyhow::Result<()> {
+        let (region, index) = locate(position);
+        let data = chunk_format::encode(chunk, &self.blocks, &self.items)?;
+        self.region(region)?
+            .write(index, &data)
+            .with_context(|| format!("Failed to write chunk {position:?}"))
+    }
+
+    fn region(&mut self, region: [i32; 3]) -> anyhow::Result<&mut RegionFile> {
+        if !self.regions.contains_key(&region) {
+            fs::create_dir_all(&self.directory)
+                .with_context(|| format!("Failed to create {}", self.directory.display()))?;
+            let file = RegionFile::open(&self.path(region))?;
+            self.regions.insert(region, file);
+        }
+        Ok(self
+            .regions
+            .get_mut(&region)
+            .expect("region was just opened"))
+    }
+
+    fn path(&self, [x, y, z]: [i32; 3]) -> PathBuf {
+        self.directory.join(format!("r.{x}.{y}.{z}.region"))
+    }
+}
+
+/// The region holding a chunk and the chunk's slot in its table
+fn locate(position: ChunkPos) -> ([i32; 3], usize) {
+    let region = [position.x, position.y, position.z].map(|value| value.div_euclid(REGION_SIZE));
+    let [x, y, z] =
+        [position.x, position.y, position.z].map(|value| value.rem_euclid(REGION_SIZE) as usize);
+    let size = REGION_SIZE as usize;
+    (region, (y * size + z) * size + x)
+}
+
+fn sectors_for(length: usize) -> u32 {
+    (length as u64).div_ceil(SECTOR_SIZE) as u32
+}
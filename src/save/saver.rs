@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+
+use super::region::RegionStorage;
+use super::{PlayerData, WorldInfo};
+use crate::world::chunk::{Chunk, ChunkPos};
+
+/// The region files plus chunks handed over for saving that aren't written yet. Loading goes
+/// through here so a chunk that was just unloaded never comes back as its older copy.
+pub struct ChunkStore {
+    storage: Mutex<RegionStorage>,
+    /// Always locked after `storage` when both are needed
+    unwritten: Mutex<HashMap<ChunkPos, Chunk>>,
+}
+
+impl ChunkStore {
+    pub fn new(storage: RegionStorage) -> Self {
+        ChunkStore {
+            storage: Mutex::new(storage),
+            unwritten: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// None if the chunk was never saved
+    pub fn load(&self, position: ChunkPos) -> anyhow::Result<Option<Chunk>> {
+        let mut storage = self.lock_storage();
+        if let Some(chunk) = self.lock_unwritten().get(&position) {
+            return Ok(Some(chunk.clone()));
+        }
+        storage.load_chunk(position)
+    }
+
+    fn queue(&self, chunks: Vec<(ChunkPos, Chunk)>) {
+        self.lock_unwritten().extend(chunks);
+    }
+
+    /// Everything queued so far, one chunk at a time so loads can get in between. Keeps going
+    /// past failures and returns the first one.
+    fn write_queued(&self) -> anyhow::Result<()> {
+        let mut result = Ok(());
+        loop {
+            // Holding the storage lock until the chunk is written means a load sees it
+            // either still queued or already on disk
+            let mut storage = self.lock_storage();
+            let next = {
+                let mut unwritten = self.lock_unwritten();
+                let position = unwritten.keys().next().copied();
+                position.and_then(|position| unwritten.remove_entry(&position))
+            };
+            let Some((position, chunk)) = next else {
+                return result;
+            };
+            if let Err(err) = storage.save_chunk(position, &chunk) {
+                log::error!("Failed to save chunk {position:?}: {err:#}");
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+        }
+    }
+
+    fn lock_storage(&self) -> MutexGuard<'_, RegionStorage> {
+        self.storage.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_unwritten(&self) -> MutexGuard<'_, HashMap<ChunkPos, Chunk>> {
+        self.unwritten
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+enum Job {
+    /// The chunks are already queued in the store
+    Chunks,
+    /// Chunks too, along with the world info and player
+    All { info: WorldInfo, player: PlayerData },
+    /// Answers with the first error since the last flush once everything before it is written
+    Flush(Sender<anyhow::Result<()>>),
+}
+
+/// Writes saves on a background thread so the game never waits on the disk
+pub struct Saver {
+    store: Arc<ChunkStore>,
+    jobs: Option<Sender<Job>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Saver {
+    pub fn new(store: Arc<ChunkStore>) -> Self {
+        let (jobs, receiver) = mpsc::channel();
+        let thread = {
+            let store = Arc::clone(&store);
+            thread::Builder::new()
+                .name(String::from("saver"))
+                .spawn(move || save(&store, receiver))
+                .expect("failed to spawn the saver thread")
+        };
+        Saver {
+            store,
+            jobs: Some(jobs),
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns right away, the chunks are written in the background
+    pub fn save_chunks(&self, chunks: Vec<(ChunkPos, Chunk)>) {
+        if chunks.is_empty() {
+            return;
+        }
+        self.store.queue(chunks);
+        self.send(Job::Chunks);
+    }
+
+    /// Returns right away, everything is written in the background
+    pub fn save_all(&self, info: WorldInfo, player: PlayerData, chunks: Vec<(ChunkPos, Chunk)>) {
+        self.store.queue(chunks);
+        self.send(Job::All { info, player });
+    }
+
+    /// Blocks until everything handed over so far is on disk, with the first error since the
+    /// last flush if anything failed
+    pub fn flush(&self) -> anyhow::Result<()> {
+        let (reply, answer) = mpsc::channel();
+        self.send(Job::Flush(reply));
+        answer
+            .recv()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("The saver thread stopped")))
+    }
+
+    fn send(&self, job: Job) {
+        if let Some(jobs) = &self.jobs {
+            // Only fails if the thread panicked, which it already reported
+            let _ = jobs.send(job);
+        }
+    }
+}
+
+impl Drop for Saver {
+    /// Whatever is still queued gets written before the thread exits
+    fn drop(&mut self) {
+        self.jobs = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn save(store: &ChunkStore, jobs: Receiver<Job>) {
+    let mut first_error = None;
+    for job in jobs {
+        let result = match job {
+            Job::Chunks => store.write_queued(),
+            Job::All { info, player } => {
+                let chunks = store.write_queued();
+                let rest = info
+                    .save()
+                    .and_then(|()| super::save_player(&info.directory, &player));
+                if let Err(err) = &rest {
+                    log::error!("Failed to save the world: {err:#}");
+                }
+                chunks.and(rest)
+            }
+            Job::Flush(reply) => {
+                let _ = reply.send(first_error.take().map_or(Ok(()), Err));
+                continue;
+            }
+        };
+        if let Err(err) = result {
+            first_error.get_or_insert(err);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use anyhow::{bail, Context};
+use cgmath::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::SavedStack;
+use crate::game::inventory::ItemStack;
+use crate::game::item::ItemRegistry;
+use crate::world::block_entity::BlockEntity;
+use crate::world::model::Rotation;
+use crate::world::{BlockId, BlockRegistry, World};
+
+pub const SCHEMATIC_DIR: &str = "schematics";
+/// Most blocks one schematic holds, a box 100 blocks on each side
+const MAX_VOLUME: u64 = 1_000_000;
+
+/// A box of blocks copied out of a world to be pasted somewhere else, along with whatever is
+/// in the chests and on the signs in it. Saved as `<name>.json` in [`SCHEMATIC_DIR`], blocks
+/// and items by name like chunks so it survives them being added or reordered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schematic {
+    /// Blocks along X, Y and Z
+    pub size: [u32; 3],
+    /// Names of the blocks in it, what `blocks` index into
+    palette: Vec<String>,
+    /// One palette index for every block, X first, then Z, then Y
+    blocks: Vec<u16>,
+    #[serde(default)]
+    block_entities: Vec<SchematicEntity>,
+}
+
+/// A block entity in a schematic, the same parts chunks save it as
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SchematicEntity {
+    /// From the lowest corner of the schematic
+    offset: [u32; 3],
+    kind: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    state: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    slots: Vec<SavedStack>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    text: Vec<String>,
+}
+
+impl Schematic {
+    /// Copy the blocks from one corner to the other, both included. Every block has to be
+    /// loaded.
+    pub fn capture(
+        world: &World,
+        blocks: &BlockRegistry,
+        items: &ItemRegistry,
+        corners: [Point3<i32>; 2],
+    ) -> anyhow::Result<Self> {
+        let [first, second] = corners;
+        let min = Point3::new(
+            first.x.min(second.x),
+            first.y.min(second.y),
+            first.z.min(second.z),
+        );
+        let size = [
+            first.x.abs_diff(second.x) + 1,
+            first.y.abs_diff(second.y) + 1,
+            first.z.abs_diff(second.z) + 1,
+        ];
+        let volume: u64 = size.iter().map(|side| u64::from(*side)).product();
+        if volume > MAX_VOLUME {
+            bail!("That's {volume} blocks, a schematic holds up to {MAX_VOLUME}");
+        }
+
+        let mut schematic = Schematic {
+            size,
+            palette: Vec::new(),
+            blocks: Vec::with_capacity(volume as usize),
+            block_entities: Vec::new(),
+        };
+        let mut slots = HashMap::new();
+        for offset in schematic.offsets() {
+            let position = min + offset.map(|value| value as i32);
+            let Some(id) = world.block(position) else {
+                bail!("{} {} {} isn't loaded", position.x, position.y, position.z);
+            };
+            // Ids the registry doesn't know came from a save with more blocks
+            let name = blocks.get(id).map_or("air", |block| block.name.as_str());
+            let slot = *slots.entry(name).or_insert_with(|| {
+                schematic.palette.push(name.to_owned());
+                schematic.palette.len() as u16 - 1
+            });
+            schematic.blocks.push(slot);
+            if let Some(entity) = world.block_entity(position) {
+                schematic.block_entities.push(SchematicEntity::capture(
+                    entity,
+                    offset.into(),
+                    items,
+                ));
+            }
+        }
+        Ok(schematic)
+    }
+
+    /// Put the blocks back with the lowest corner at `origin`, turned `turns` quarter turns
+    /// clockwise seen from above. Oriented blocks turn along with it. Blocks that no longer
+    /// exist become air. Returns how many blocks were set, every one of them has to be
+    /// loaded.
+    pub fn paste(
+        &self,
+        world: &mut World,
+        blocks: &BlockRegistry,
+        items: &ItemRegistry,
+        origin: Point3<i32>,
+        turns: u32,
+    ) -> anyhow::Result<usize> {
+        let rotation = Rotation::about_y(turns);
+        // Turning moves the box around the origin, this brings its lowest corner back to it
+        let shift = rotation.turned_min(self.size.map(|side| side as i32));
+        let target = |offset: [u32; 3]| {
+            let [x, y, z] = rotation.offset(offset.map(|value| value as i32));
+            origin + Vector3::new(x - shift[0], y - shift[1], z - shift[2])
+        };
+
+        let ids: Vec<_> = self
+            .palette
+            .iter()
+            .map(|name| {
+                let id = blocks.id(name).unwrap_or_else(|| {
+                    log::warn!("Unknown block '{name}' in a schematic, pasting air instead");
+                    BlockId::AIR
+                });
+                blocks.rotated(id, rotation)
+            })
+            .collect();
+        let offsets: Vec<_> = self.offsets().collect();
+        if self.blocks.len() != offsets.len() {
+            bail!(
+                "The schematic has {} blocks for its size of {}",
+                self.blocks.len(),
+                offsets.len()
+            );
+        }
+        if let Some(unloaded) = offsets
+            .iter()
+            .map(|offset| target((*offset).into()))
+            .find(|position| world.block(*position).is_none())
+        {
+            bail!("{} {} {} isn't loaded", unloaded.x, unloaded.y, unloaded.z);
+        }
+
+        for (offset, slot) in offsets.into_iter().zip(&self.blocks) {
+            let position = target(offset.into());
+            let id = ids.get(*slot as usize).copied().unwrap_or(BlockId::AIR);
+            // Whatever was in the block it replaces goes with it
+            world.take_block_entity(position);
+            world.set_block(position, id);
+        }
+        for saved in &self.block_entities {
+            let Some(entity) = saved.load(items) else {
+                log::warn!("Unknown block entity '{}' in a schematic", saved.kind);
+                continue;
+            };
+            world.insert_block_entity(target(saved.offset), entity);
+        }
+        Ok(self.blocks.len())
+    }
+
+    /// Write it as `<name>.json` in [`SCHEMATIC_DIR`] over any schematic of that name.
+    /// Returns where it went.
+    pub fn save(&self, name: &str) -> anyhow::Result<PathBuf> {
+        let path = path_of(name)?;
+        let directory = Path::new(SCHEMATIC_DIR);
+        fs::create_dir_all(directory)
+            .with_context(|| format!("Failed to create {}", directory.display()))?;
+        let text = serde_json::to_string(self)?;
+        fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn load(name: &str) -> anyhow::Result<Self> {
+        let path = path_of(name)?;
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Every block's offset from the lowest corner along with its name. Short schematics
+    /// leave out whatever is missing at the end.
+    pub fn block_names(&self) -> impl Iterator<Item = (Vector3<u32>, &str)> {
+        self.offsets().zip(&self.blocks).map(|(offset, slot)| {
+            let name = self
+                .palette
+                .get(*slot as usize)
+                .map_or("air", String::as_str);
+            (offset, name)
+        })
+    }
+
+    /// Every block's offset from the lowest corner, in the order of `blocks`
+    fn offsets(&self) -> impl Iterator<Item = Vector3<u32>> {
+        let [width, height, depth] = self.size;
+        (0..height).flat_map(move |y| {
+            (0..depth).flat_map(move |z| (0..width).map(move |x| Vector3::new(x, y, z)))
+        })
+    }
+}
+
+impl SchematicEntity {
+    fn capture(entity: &BlockEntity, offset: [u32; 3], items: &ItemRegistry) -> Self {
+        // Stacks of items the registry doesn't know are already gone
+        let slots = entity
+            .slots()
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, stack)| {
+                let stack = (*stack)?;
+                Some(SavedStack {
+                    slot,
+                    item: items.get(stack.item)?.name.clone(),
+                    count: stack.count,
+                    damage: stack.damage,
+                    modifiers: stack.modifiers,
+                })
+            })
+            .collect();
+        SchematicEntity {
+            offset,
+            kind: entity.kind().to_owned(),
+            state: entity.state(),
+            slots,
+            text: entity.text().to_vec(),
+        }
+    }
+
+    /// None for kinds that no longer exist, items that no longer exist are dropped
+    fn load(&self, items: &ItemRegistry) -> Option<BlockEntity> {
+        let mut entity = BlockEntity::load(&self.kind, &self.state)?;
+        for stack in &self.slots {
+            let Some(item) = items.id(&stack.item) else {
+                log::warn!("Dropping {} of unknown item {}", stack.count, stack.item);
+                continue;
+            };
+            if let Some(slot) = entity.slots_mut().get_mut(stack.slot) {
+                let count = stack.count.min(items.max_stack(item));
+                *slot = (count > 0).then_some(ItemStack {
+                    item,
+                    count,
+                    damage: stack.damage,
+                    modifiers: stack.modifiers,
+                });
+            }
+        }
+        for (line, saved) in entity.text_mut().iter_mut().zip(&self.text) {
+            line.clone_from(saved);
+        }
+        Some(entity)
+    }
+}
+
+/// Names go straight into a path, so they're kept to letters, digits, `-` and `_`
+fn path_of(name: &str) -> anyhow::Result<PathBuf> {
+    let allowed = |character: char| character.is_ascii_alphanumeric() || "-_".contains(character);
+    if name.is_empty() || !name.chars().all(allowed) {
+        bail!("Schematic names are letters, digits, - and _");
+    }
+    Ok(Path::new(SCHEMATIC_DIR).join(format!("{name}.json")))
+}
@@ -0,0 +1,59 @@
+use anyhow::Context;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::thread;
+
+use crate::i18n::tr_args;
+
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+/// Grab the back buffer and write it out as a PNG on a worker thread, the outcome is posted
+/// to `messages` once the file is written
+pub fn capture(width: u32, height: u32, messages: Sender<String>) -> anyhow::Result<()> {
+    let pixels = gl_lib::read_default_framebuffer(width, height)
+        .context("Failed to read back the framebuffer")?;
+
+    thread::spawn(move || {
+        let message = match save(width, height, pixels) {
+            Ok(path) => tr_args("message.screenshot_saved", &[("path", &path.display())]),
+            Err(err) => tr_args(
+                "message.screenshot_failed",
+                &[("error", &format!("{err:#}"))],
+            ),
+        };
+        let _ = messages.send(message);
+    });
+    Ok(())
+}
+
+fn save(width: u32, height: u32, mut pixels: Vec<u8>) -> anyhow::Result<PathBuf> {
+    // Whatever ended up in the alpha channel isn't meant to be seen
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel[3] = u8::MAX;
+    }
+    let mut image = image::RgbaImage::from_raw(width, height, pixels)
+        .context("Framebuffer size doesn't match its pixels")?;
+    image::imageops::flip_vertical_in_place(&mut image);
+
+    let directory = Path::new(SCREENSHOT_DIR);
+    fs::create_dir_all(directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+    let path = unique_path(directory);
+    image
+        .save(&path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Timestamped name, with a counter for several shots within the same second
+fn unique_path(directory: &Path) -> PathBuf {
+    let timestamp = chrono::Local::now().format("%Y-%m-%d_%H.%M.%S");
+    let mut path = directory.join(format!("{timestamp}.png"));
+    let mut counter = 1;
+    while path.exists() {
+        path = directory.join(format!("{timestamp}_{counter}.png"));
+        counter += 1;
+    }
+    path
+}
@@ -0,0 +1,154 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::display::FullscreenMode;
+use crate::i18n::DEFAULT_LANGUAGE;
+use crate::input::KeyBindings;
+use crate::render::deferred::SsaoQuality;
+use crate::render::mesher::MeshingMode;
+
+pub const SETTINGS_PATH: &str = "config/settings.json";
+
+/// Everything the player can configure, missing fields fall back to their defaults so old
+/// config files keep loading
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Names of entries in `resourcepacks/`, the first one wins when several packs provide
+    /// the same file
+    pub resource_packs: Vec<String>,
+    /// Code of a file in `lang/`, e.g. `en_us`
+    pub language: String,
+    pub fullscreen: bool,
+    /// What F11 switches to
+    pub fullscreen_mode: FullscreenMode,
+    /// Index into the connected monitors, out of range means the primary one
+    pub monitor: usize,
+    pub vsync: bool,
+    /// Frame cap while focused, None for uncapped
+    pub max_fps: Option<u32>,
+    /// Frame cap while the window is in the background
+    pub background_fps: Option<u32>,
+    /// Radius in chunks
+    pub render_distance: u32,
+    /// Framebuffer pixels per GUI unit, 0 picks one from the window size
+    pub gui_scale: u32,
+    /// Vertical field of view in degrees
+    pub fov: f32,
+    /// Samples per pixel for the world, 0 turns multisampling off
+    pub msaa_samples: u32,
+    /// Smooth edges in post-processing instead, cheaper than MSAA and works when deferred
+    pub fxaa: bool,
+    pub meshing: MeshingMode,
+    /// Average the light around each corner of a face instead of lighting it evenly
+    pub smooth_lighting: bool,
+    /// Shadows cast by the sun and moon
+    pub shadows: bool,
+    /// Light the terrain in a separate pass after drawing it, MSAA is skipped while it's on
+    pub deferred: bool,
+    /// Screen-space ambient occlusion, only drawn with deferred rendering
+    pub ssao: SsaoQuality,
+    /// Waves on water and reflections of the sky and terrain in it
+    pub fancy_water: bool,
+    /// Applied to the finished picture, 1 leaves it as it is and higher brightens dark parts
+    pub gamma: f32,
+    /// Glowing blocks light up the air around them
+    pub bloom: bool,
+    /// Chunks at least this far away are meshed coarser, 0 keeps them all at full detail
+    pub lod_distance: u32,
+    /// Height in blocks of the cloud layer
+    pub cloud_height: f32,
+    /// Fraction of the sky covered by clouds, 0 turns them off
+    pub cloud_density: f32,
+    /// Multiplier on the base mouse look speed
+    pub mouse_sensitivity: f32,
+    /// Read unscaled mouse counts where supported, bypassing the OS pointer acceleration
+    pub raw_mouse_motion: bool,
+    /// Extra turn for fast mouse motion, 0 keeps mouse look linear
+    pub mouse_acceleration: f32,
+    pub key_bindings: KeyBindings,
+    pub volume: Volume,
+}
+
+/// Volume per sound category, each from 0 to 1 and scaled by `master`
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Volume {
+    pub master: f32,
+    pub music: f32,
+    pub blocks: f32,
+    pub ambient: f32,
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Volume {
+            master: 1.0,
+            music: 0.5,
+            blocks: 1.0,
+            ambient: 1.0,
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            resource_packs: Vec::new(),
+            language: String::from(DEFAULT_LANGUAGE),
+            fullscreen: false,
+            fullscreen_mode: FullscreenMode::default(),
+            monitor: 0,
+            vsync: true,
+            max_fps: None,
+            background_fps: Some(15),
+            render_distance: 8,
+            gui_scale: 0,
+            fov: 70.0,
+            msaa_samples: 0,
+            fxaa: false,
+            meshing: MeshingMode::default(),
+            smooth_lighting: true,
+            shadows: true,
+            deferred: false,
+            ssao: SsaoQuality::default(),
+            fancy_water: true,
+            gamma: 1.0,
+            bloom: true,
+            lod_distance: 12,
+            cloud_height: 192.0,
+            cloud_density: 0.4,
+            mouse_sensitivity: 1.0,
+            raw_mouse_motion: true,
+            mouse_acceleration: 0.0,
+            key_bindings: KeyBindings::default(),
+            volume: Volume::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// A missing file is not an error, it just means the defaults
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Settings::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read {}", path.display()))
+            }
+        };
+        serde_json::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let text = serde_json::to_string_pretty(self)?;
+        fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
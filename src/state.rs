@@ -0,0 +1,65 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameState {
+    MainMenu,
+    Loading,
+    Playing,
+    Paused,
+    Settings,
+    Console,
+    Inventory,
+    /// Writing on a sign
+    EditSign,
+    /// Over the world, which waits until the player respawns
+    Dead,
+}
+
+impl GameState {
+    /// Mouse look needs the cursor locked to the window, menus need it free
+    pub fn captures_cursor(self) -> bool {
+        self == GameState::Playing
+    }
+
+    /// Whether the world moves forward in time
+    pub fn simulates(self) -> bool {
+        self == GameState::Playing
+    }
+}
+
+/// Overlays like the pause menu go on top of what they interrupt, the bottom state is never
+/// popped
+pub struct StateStack {
+    states: Vec<GameState>,
+}
+
+impl StateStack {
+    pub fn new(initial: GameState) -> Self {
+        StateStack {
+            states: vec![initial],
+        }
+    }
+
+    pub fn current(&self) -> GameState {
+        *self.states.last().expect("state stack is never empty")
+    }
+
+    pub fn push(&mut self, state: GameState) {
+        self.states.push(state);
+    }
+
+    pub fn pop(&mut self) {
+        if self.states.len() > 1 {
+            self.states.pop();
+        }
+    }
+
+    /// Whether the world is drawn, possibly behind one or more menus
+    pub fn shows_world(&self) -> bool {
+        self.states.contains(&GameState::Playing)
+    }
+
+    /// Drop everything and start over from `state`
+    pub fn reset(&mut self, state: GameState) {
+        self.states.clear();
+        self.states.push(state);
+    }
+}
@@ -0,0 +1,29 @@
+use super::Ui;
+use crate::i18n::tr;
+use crate::render::text::Color;
+
+const TITLE_COLOR: Color = [1.0, 0.35, 0.35, 1.0];
+/// Over the usual dimming, so the world shows through red
+const TINT_COLOR: Color = [0.5, 0.0, 0.0, 0.3];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DeathAction {
+    Respawn,
+    QuitToTitle,
+}
+
+pub fn draw(ui: &mut Ui) -> Option<DeathAction> {
+    ui.dim_background();
+    ui.rect([0.0, 0.0], ui.screen_size(), TINT_COLOR);
+    let top = ui.screen_size()[1] / 3.0;
+    ui.label_centered(&tr("death.title"), top - 48.0, TITLE_COLOR);
+
+    let mut action = None;
+    if ui.button(&tr("death.respawn"), ui.column_rect(top, 0)) {
+        action = Some(DeathAction::Respawn);
+    }
+    if ui.button(&tr("pause.quit"), ui.column_rect(top, 1)) {
+        action = Some(DeathAction::QuitToTitle);
+    }
+    action
+}
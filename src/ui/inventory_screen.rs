@@ -0,0 +1,644 @@
+use cgmath::Point3;
+use gl_lib::BlendMode;
+
+use super::{Rect, UiInput};
+use crate::game::crafting::{CraftingGrid, RecipeBook, MAX_GRID_SIZE};
+use crate::game::enchanting::{self, Modifier};
+use crate::game::inventory::{self, Inventory, ItemStack, HOTBAR_SLOTS, SLOTS};
+use crate::game::item::ItemRegistry;
+use crate::i18n::{tr, tr_args};
+use crate::input::{Binding, HOTBAR_KEYS};
+use crate::render::hud::{self, HudBatch, SLOT_SIZE};
+use crate::render::icons::IconAtlas;
+use crate::render::text::{Color, WHITE};
+use crate::world::block_entity::{
+    BlockEntity, CHEST_SLOTS, FURNACE_FUEL, FURNACE_INPUT, FURNACE_OUTPUT,
+};
+
+const COLUMNS: usize = HOTBAR_SLOTS;
+/// Between the three main rows and the hotbar below them
+const HOTBAR_GAP: f32 = 12.0;
+/// Rows above the inventory, enough for a crafting table's grid or a chest
+const CONTAINER_ROWS: usize = 3;
+/// Between the container's slots and the inventory below them
+const CONTAINER_GAP: f32 = 12.0;
+/// Column of the crafting result and the furnace output, with room for the arrow to its left
+const RESULT_COLUMN: usize = 6;
+/// Column of the furnace's input and fuel, the flame goes between them
+const FURNACE_COLUMN: usize = 2;
+/// Column of the tool being upgraded at an enchanting table, its upgrades start two further
+/// along and go to the right edge
+const ENCHANTING_COLUMN: usize = 1;
+const UPGRADE_COLUMN: usize = 3;
+const PADDING: f32 = 12.0;
+/// Room for the title above the slots
+const TITLE_HEIGHT: f32 = 28.0;
+const DIM_COLOR: Color = [0.0, 0.0, 0.0, 0.5];
+const PANEL_COLOR: Color = [0.2, 0.2, 0.2, 0.95];
+const SLOT_COLOR: Color = [0.1, 0.1, 0.1, 0.9];
+const HOVER_COLOR: Color = [1.0, 1.0, 1.0, 0.25];
+/// Around the hotbar slot that's selected for placing
+const SELECTED_COLOR: Color = [1.0, 1.0, 1.0, 0.6];
+/// Slots the held stack is being spread over
+const DRAGGED_COLOR: Color = [0.6, 0.6, 1.0, 0.35];
+/// Pointing from the crafting grid or the furnace input to the result
+const ARROW_COLOR: Color = [0.6, 0.6, 0.6, 1.0];
+/// How far along the furnace is with the item it's smelting
+const PROGRESS_COLOR: Color = [1.0, 1.0, 1.0, 1.0];
+/// What's left of the fuel burning in a furnace
+const FLAME_COLOR: Color = [1.0, 0.55, 0.1, 1.0];
+/// Upgrades at an enchanting table, and their text when there aren't enough levels for them
+const UPGRADE_COLOR: Color = [0.35, 0.3, 0.45, 1.0];
+const UPGRADE_HOVER_COLOR: Color = [0.5, 0.45, 0.65, 1.0];
+const UNAFFORDABLE_COLOR: Color = [0.6, 0.6, 0.6, 1.0];
+
+/// What the screen needs to know about items besides the player's own
+pub struct ItemContext<'a> {
+    pub icons: &'a IconAtlas,
+    pub items: &'a ItemRegistry,
+    pub recipes: &'a RecipeBook,
+    /// Experience levels the player has to spend at an enchanting table, None when upgrades
+    /// are free
+    pub levels: Option<u32>,
+}
+
+/// Whose slots come after the inventory's
+#[derive(Debug)]
+enum Container {
+    Crafting(CraftingGrid),
+    /// The block entity of the block here, a chest's or a furnace's
+    Block(Point3<i32>),
+    /// The tool on an enchanting table
+    Enchanting([Option<ItemStack>; 1]),
+}
+
+/// How the slots above the inventory are laid out, and what the screen is called
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Panel {
+    /// With the grid's size
+    Crafting(usize),
+    Chest,
+    Furnace,
+    Enchanting,
+}
+
+impl Panel {
+    /// Index of the slot that can only be taken from, the crafting result right after the
+    /// grid or the furnace output
+    fn output(self) -> Option<usize> {
+        match self {
+            Panel::Crafting(size) => Some(SLOTS + size * size),
+            Panel::Chest | Panel::Enchanting => None,
+            Panel::Furnace => Some(SLOTS + FURNACE_OUTPUT),
+        }
+    }
+
+    fn title(self) -> String {
+        match self {
+            Panel::Crafting(MAX_GRID_SIZE) => tr("crafting.title"),
+            Panel::Crafting(_) => tr("inventory.title"),
+            Panel::Chest => tr("container.chest"),
+            Panel::Furnace => tr("container.furnace"),
+            Panel::Enchanting => tr("container.enchanting"),
+        }
+    }
+}
+
+/// Every slot of the player's inventory and of a container above it, a crafting grid or a
+/// block's, with stacks moved around by mouse: left click picks up or puts down a whole
+/// stack, right click splits one in half or puts down one at a time, and dragging a held
+/// stack across slots spreads it evenly over them. Number keys swap the hovered slot with
+/// that hotbar slot. Clicking the crafting result takes what the grid makes and uses up one
+/// of each stack in it, the furnace output can only be taken from. At an enchanting table
+/// clicking one of the upgrades next to the tool buys it with experience levels.
+#[derive(Debug)]
+pub struct InventoryScreen {
+    cursor: Cursor,
+    /// Numbered after the inventory's slots, a crafting grid's result comes after it
+    container: Container,
+}
+
+impl InventoryScreen {
+    /// `grid_size` is 2 for the inventory's own grid and 3 at a crafting table
+    pub fn new(grid_size: usize) -> Self {
+        InventoryScreen {
+            cursor: Cursor::default(),
+            container: Container::Crafting(CraftingGrid::new(grid_size.min(MAX_GRID_SIZE))),
+        }
+    }
+
+    /// The slots of the block entity at `block` above the inventory
+    pub fn block(block: Point3<i32>) -> Self {
+        InventoryScreen {
+            cursor: Cursor::default(),
+            container: Container::Block(block),
+        }
+    }
+
+    /// An enchanting table's slot for a tool above the inventory
+    pub fn enchanting() -> Self {
+        InventoryScreen {
+            cursor: Cursor::default(),
+            container: Container::Enchanting([None]),
+        }
+    }
+
+    /// Where the block entity shown is, None for a crafting grid or an enchanting table
+    pub fn block_position(&self) -> Option<Point3<i32>> {
+        match self.container {
+            Container::Crafting(_) | Container::Enchanting(_) => None,
+            Container::Block(position) => Some(position),
+        }
+    }
+
+    /// Handle this frame's clicks and queue the screen. Counts and the title are labels, so
+    /// they end up on top of every icon. `block_entity` is the one at
+    /// [`InventoryScreen::block_position`], without it nothing is drawn. Returns the levels
+    /// the upgrades bought this frame cost, for the caller to take off the player.
+    pub fn draw(
+        &mut self,
+        batch: &mut HudBatch,
+        input: &UiInput,
+        screen_size: [f32; 2],
+        inventory: &mut Inventory,
+        mut block_entity: Option<&mut BlockEntity>,
+        context: &ItemContext,
+    ) -> u32 {
+        let (panel, furnace) = match (&self.container, block_entity.as_deref()) {
+            (Container::Crafting(grid), _) => (Panel::Crafting(grid.size), None),
+            (Container::Enchanting(_), _) => (Panel::Enchanting, None),
+            (Container::Block(_), Some(BlockEntity::Chest(_))) => (Panel::Chest, None),
+            (Container::Block(_), Some(BlockEntity::Furnace(furnace))) => (
+                Panel::Furnace,
+                Some((furnace.fuel_fraction(), furnace.progress_fraction())),
+            ),
+            (Container::Block(_), None | Some(BlockEntity::Sign(_))) => return 0,
+        };
+        let rects = layout(screen_size, panel);
+        let hovered = rects.iter().position(|rect| rect.contains(input.cursor));
+        let output = panel.output();
+
+        let held = &mut self.cursor.held;
+        if hovered.is_some() && hovered == output && (input.clicked || input.right_clicked) {
+            match &mut self.container {
+                Container::Crafting(grid) => take_result(grid, held, context),
+                Container::Enchanting(_) => {}
+                Container::Block(_) => {
+                    let slot = block_entity
+                        .as_deref_mut()
+                        .and_then(|entity| entity.slots_mut().get_mut(FURNACE_OUTPUT));
+                    if let Some(slot) = slot {
+                        if slot.is_some_and(|stack| pick_up(held, stack, context.items)) {
+                            *slot = None;
+                        }
+                    }
+                }
+            }
+        }
+        let result = match &self.container {
+            Container::Crafting(grid) => context.recipes.find(grid).map(|recipe| recipe.result),
+            Container::Block(_) | Container::Enchanting(_) => None,
+        };
+        let container: &mut [Option<ItemStack>] = match (&mut self.container, block_entity) {
+            (Container::Crafting(grid), _) => &mut grid.slots,
+            (Container::Enchanting(slot), _) => slot,
+            (Container::Block(_), Some(entity)) => entity.slots_mut(),
+            (Container::Block(_), None) => &mut [],
+        };
+        let mut slots = Slots {
+            inventory,
+            container,
+        };
+        self.cursor
+            .handle_input(input, hovered, output, &mut slots, context.items);
+
+        batch.set_blend(BlendMode::Alpha);
+        batch.rect([0.0, 0.0], screen_size, DIM_COLOR);
+        let first = rects[HOTBAR_SLOTS];
+        let last = rects[HOTBAR_SLOTS - 1];
+        let top = rects[SLOTS..]
+            .iter()
+            .map(|rect| rect.y)
+            .fold(first.y, f32::min);
+        let corner = [first.x - PADDING, top - PADDING - TITLE_HEIGHT];
+        batch.rect(
+            corner,
+            [
+                last.x + SLOT_SIZE + PADDING - corner[0],
+                last.y + SLOT_SIZE + PADDING - corner[1],
+            ],
+            PANEL_COLOR,
+        );
+        batch.label_centered(
+            panel.title(),
+            [screen_size[0] / 2.0, corner[1] + PADDING / 2.0],
+            WHITE,
+        );
+
+        let mut spent = 0;
+        if panel == Panel::Enchanting {
+            let tool = rects[SLOTS];
+            if let Some(stack) = slots.container.first_mut().and_then(Option::as_mut) {
+                spent = upgrades(batch, input, tool, stack, context);
+            }
+        }
+
+        if let Some(output) = output {
+            let (fuel, progress) = furnace.unwrap_or_default();
+            arrow(batch, rects[output], progress);
+            if panel == Panel::Furnace {
+                flame(batch, rects[SLOTS + FURNACE_INPUT], fuel);
+            }
+        }
+
+        let dragged = self.cursor.dragged.as_deref().unwrap_or_default();
+        for (index, rect) in rects.iter().enumerate() {
+            let position = [rect.x, rect.y];
+            batch.rect(
+                [rect.x + 1.0, rect.y + 1.0],
+                [SLOT_SIZE - 2.0, SLOT_SIZE - 2.0],
+                SLOT_COLOR,
+            );
+            if dragged.len() > 1 && dragged.contains(&index) {
+                batch.rect(position, [SLOT_SIZE, SLOT_SIZE], DRAGGED_COLOR);
+            } else if hovered == Some(index) {
+                batch.rect(position, [SLOT_SIZE, SLOT_SIZE], HOVER_COLOR);
+            }
+            let stack = match panel {
+                Panel::Crafting(_) if Some(index) == output => result,
+                _ => slots.get(index).flatten(),
+            };
+            if let Some(stack) = stack {
+                batch.stack(position, stack, context.icons, context.items);
+            }
+        }
+        if let Some(selected) = rects.get(slots.inventory.selected) {
+            let position = [selected.x, selected.y];
+            hud::outline(batch, position, SLOT_SIZE, 1.0, SELECTED_COLOR);
+        }
+        if let Some(held) = self.cursor.held {
+            let [x, y] = input.cursor;
+            batch.stack(
+                [x - SLOT_SIZE / 2.0, y - SLOT_SIZE / 2.0],
+                held,
+                context.icons,
+                context.items,
+            );
+        }
+        spent
+    }
+
+    /// Put the held stack, the crafting grid and the enchanting table's tool back when the
+    /// screen closes, returns what didn't fit. A block's slots keep what's in them.
+    pub fn close(&mut self, inventory: &mut Inventory, items: &ItemRegistry) -> Vec<ItemStack> {
+        self.cursor.dragged = None;
+        let mut stacks = match &mut self.container {
+            Container::Crafting(grid) => grid.take_all(),
+            Container::Enchanting([slot]) => slot.take().into_iter().collect(),
+            Container::Block(_) => Vec::new(),
+        };
+        stacks.extend(self.cursor.held.take());
+        stacks
+            .into_iter()
+            .filter_map(|stack| inventory.add(stack, items))
+            .collect()
+    }
+}
+
+/// The stack following the mouse and where it's being dragged
+#[derive(Debug, Default)]
+struct Cursor {
+    /// Picked up and following the cursor
+    held: Option<ItemStack>,
+    /// Slots the held stack was dragged across, while the left button is down
+    dragged: Option<Vec<usize>>,
+}
+
+impl Cursor {
+    /// `output` is the slot whose clicks [`InventoryScreen::draw`] already handled
+    fn handle_input(
+        &mut self,
+        input: &UiInput,
+        hovered: Option<usize>,
+        output: Option<usize>,
+        slots: &mut Slots,
+        items: &ItemRegistry,
+    ) {
+        if hovered != output {
+            self.handle_slot_input(input, hovered, slots, items);
+        }
+
+        let Some(dragged) = &self.dragged else {
+            return;
+        };
+        if input.mouse_down {
+            if let (Some(slot), Some(held)) = (hovered, self.held) {
+                let accepts = slots
+                    .get(slot)
+                    .is_some_and(|stack| inventory::accepts(stack, held, items));
+                if !dragged.contains(&slot) && Some(slot) != output && accepts {
+                    self.dragged.get_or_insert_default().push(slot);
+                }
+            }
+            return;
+        }
+        let dragged = self.dragged.take().unwrap_or_default();
+        match dragged.as_slice() {
+            [slot] => {
+                if let Some(target) = slots.get_mut(*slot) {
+                    inventory::click(target, &mut self.held, items);
+                }
+            }
+            dragged => {
+                let mut all: Vec<Option<&mut Option<ItemStack>>> =
+                    slots.all_mut().map(Some).collect();
+                // In the order they were dragged across, the first ones get any extra
+                let targets = dragged
+                    .iter()
+                    .filter_map(|slot| all.get_mut(*slot)?.take())
+                    .collect();
+                inventory::spread(targets, &mut self.held, items);
+            }
+        }
+    }
+
+    /// Clicks and number keys over the inventory and the container
+    fn handle_slot_input(
+        &mut self,
+        input: &UiInput,
+        hovered: Option<usize>,
+        slots: &mut Slots,
+        items: &ItemRegistry,
+    ) {
+        let Some(slot) = hovered else {
+            return;
+        };
+        if input.clicked {
+            let accepts = |held| {
+                slots
+                    .get(slot)
+                    .is_some_and(|stack| inventory::accepts(stack, held, items))
+            };
+            match self.held {
+                // Only a click until the cursor reaches a second slot
+                Some(held) if accepts(held) => self.dragged = Some(vec![slot]),
+                _ => {
+                    if let Some(target) = slots.get_mut(slot) {
+                        inventory::click(target, &mut self.held, items);
+                    }
+                }
+            }
+        }
+        if input.right_clicked {
+            if let Some(target) = slots.get_mut(slot) {
+                inventory::right_click(target, &mut self.held, items);
+            }
+        }
+        let hotbar = match input.pressed {
+            Some(Binding::Key(key)) => HOTBAR_KEYS.iter().position(|hotbar| *hotbar == key),
+            _ => None,
+        };
+        if let Some(hotbar) = hotbar {
+            slots.swap_with_hotbar(slot, hotbar);
+        }
+    }
+}
+
+/// The inventory's slots followed by the container's, numbered like [`layout`]'s rects
+struct Slots<'a> {
+    inventory: &'a mut Inventory,
+    container: &'a mut [Option<ItemStack>],
+}
+
+impl Slots<'_> {
+    /// What's in slot `index`, None past the last one like the crafting result
+    fn get(&self, index: usize) -> Option<Option<ItemStack>> {
+        match index.checked_sub(SLOTS) {
+            None => self.inventory.slots.get(index).copied(),
+            Some(cell) => self.container.get(cell).copied(),
+        }
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut Option<ItemStack>> {
+        match index.checked_sub(SLOTS) {
+            None => self.inventory.slots.get_mut(index),
+            Some(cell) => self.container.get_mut(cell),
+        }
+    }
+
+    fn all_mut(&mut self) -> impl Iterator<Item = &mut Option<ItemStack>> {
+        self.inventory
+            .slots
+            .iter_mut()
+            .chain(self.container.iter_mut())
+    }
+
+    fn swap_with_hotbar(&mut self, index: usize, hotbar: usize) {
+        match index.checked_sub(SLOTS) {
+            None => self.inventory.slots.swap(index, hotbar),
+            Some(cell) => {
+                if let Some(cell) = self.container.get_mut(cell) {
+                    std::mem::swap(cell, &mut self.inventory.slots[hotbar]);
+                }
+            }
+        }
+    }
+}
+
+/// What the grid makes onto the held stack, using up one of each stack in the grid
+fn take_result(grid: &mut CraftingGrid, held: &mut Option<ItemStack>, context: &ItemContext) {
+    let Some(recipe) = context.recipes.find(grid) else {
+        return;
+    };
+    if pick_up(held, recipe.result, context.items) {
+        grid.consume();
+    }
+}
+
+/// Onto the held stack if it's empty or the same item with room for all of `stack`, returns
+/// whether it went
+fn pick_up(held: &mut Option<ItemStack>, stack: ItemStack, items: &ItemRegistry) -> bool {
+    let combined = match *held {
+        None => stack,
+        Some(current)
+            if current.item == stack.item
+                && current.count + stack.count <= items.max_stack(current.item) =>
+        {
+            current.with_count(current.count + stack.count)
+        }
+        Some(_) => return false,
+    };
+    *held = Some(combined);
+    true
+}
+
+/// Pointing right at the result slot `result`, from the column before it, lit up from the
+/// left as far as `progress` goes from 0 to 1
+fn arrow(batch: &mut HudBatch, result: Rect, progress: f32) {
+    let left = result.x - SLOT_SIZE * 1.5;
+    let middle = result.y + SLOT_SIZE / 2.0;
+    batch.rect([left, middle - 2.0], [SLOT_SIZE, 4.0], ARROW_COLOR);
+    // The head narrows towards the tip in steps of two units
+    for step in 0..4 {
+        let inset = step as f32 * 2.0;
+        let x = left + SLOT_SIZE - 8.0 + inset;
+        batch.rect(
+            [x, middle - 8.0 + inset],
+            [2.0, 16.0 - 2.0 * inset],
+            ARROW_COLOR,
+        );
+    }
+    if progress > 0.0 {
+        let width = (SLOT_SIZE * progress.min(1.0)).round();
+        batch.rect([left, middle - 2.0], [width, 4.0], PROGRESS_COLOR);
+    }
+}
+
+/// A button for each [`Modifier`] right of the enchanting table's `tool` slot, naming the
+/// level it takes `stack` to and what that costs. Clicking one the player has the levels for
+/// upgrades the stack, returns the levels that cost.
+fn upgrades(
+    batch: &mut HudBatch,
+    input: &UiInput,
+    tool: Rect,
+    stack: &mut ItemStack,
+    context: &ItemContext,
+) -> u32 {
+    let left = tool.x + (UPGRADE_COLUMN - ENCHANTING_COLUMN) as f32 * SLOT_SIZE;
+    let width = (COLUMNS - UPGRADE_COLUMN) as f32 * SLOT_SIZE;
+    let count = Modifier::ALL.len() as f32;
+    let first = tool.y + SLOT_SIZE / 2.0 - count * SLOT_SIZE / 2.0;
+    let mut spent = 0;
+    for (index, modifier) in Modifier::ALL.into_iter().enumerate() {
+        let button = Rect::new(
+            left,
+            first + index as f32 * SLOT_SIZE + 2.0,
+            width,
+            SLOT_SIZE - 4.0,
+        );
+        let level = stack.modifiers.level(modifier);
+        let name = tr(modifier.key());
+        let cost = enchanting::upgrade_cost(*stack, modifier, context.items);
+        let affordable =
+            cost.is_some_and(|cost| context.levels.is_none_or(|levels| cost + spent <= levels));
+        let hovered = affordable && button.contains(input.cursor);
+        let color = if hovered {
+            UPGRADE_HOVER_COLOR
+        } else {
+            UPGRADE_COLOR
+        };
+        batch.rect([button.x, button.y], [button.width, button.height], color);
+        let text = match cost {
+            Some(cost) => tr_args(
+                "enchanting.upgrade",
+                &[
+                    ("modifier", &name),
+                    ("level", &roman(level + 1)),
+                    ("cost", &cost),
+                ],
+            ),
+            None if context.items.tool(stack.item).is_some() => tr_args(
+                "enchanting.maxed",
+                &[("modifier", &name), ("level", &roman(level))],
+            ),
+            None => name,
+        };
+        let text_color = if affordable {
+            WHITE
+        } else {
+            UNAFFORDABLE_COLOR
+        };
+        let center = [button.x + button.width / 2.0, button.y + 8.0];
+        batch.label_centered(text, center, text_color);
+        if hovered && input.clicked {
+            if let Some(cost) = cost {
+                enchanting::upgrade(stack, modifier, context.items);
+                spent += cost;
+            }
+        }
+    }
+    spent
+}
+
+/// Levels of modifiers are written the old-fashioned way
+fn roman(level: u8) -> &'static str {
+    match level {
+        1 => "I",
+        2 => "II",
+        3 => "III",
+        _ => "",
+    }
+}
+
+/// In the row between the furnace's `input` slot and the fuel under it, as tall as `fuel`
+/// goes from 0 to 1
+fn flame(batch: &mut HudBatch, input: Rect, fuel: f32) {
+    let bottom = input.y + SLOT_SIZE * 2.0 - 4.0;
+    let left = input.x + SLOT_SIZE / 2.0 - 6.0;
+    let full = SLOT_SIZE - 8.0;
+    batch.rect([left, bottom - full], [12.0, full], SLOT_COLOR);
+    if fuel > 0.0 {
+        let height = (full * fuel.min(1.0)).round().max(1.0);
+        batch.rect([left, bottom - height], [12.0, height], FLAME_COLOR);
+    }
+}
+
+/// Where each slot goes, in the order of [`Inventory::slots`] then the container's and a
+/// crafting grid's result: the three main rows centered on screen with the hotbar under them
+/// and the container above
+fn layout(screen_size: [f32; 2], panel: Panel) -> Vec<Rect> {
+    let rows = (SLOTS / COLUMNS) as f32;
+    let container_height = CONTAINER_ROWS as f32 * SLOT_SIZE + CONTAINER_GAP;
+    let width = COLUMNS as f32 * SLOT_SIZE;
+    let height = container_height + rows * SLOT_SIZE + HOTBAR_GAP;
+    let left = ((screen_size[0] - width) / 2.0).floor();
+    let top = ((screen_size[1] - height + TITLE_HEIGHT) / 2.0).floor();
+    let main_top = top + container_height;
+    let mut slots: Vec<Rect> = (0..SLOTS)
+        .map(|index| {
+            let column = index % COLUMNS;
+            let y = match index / COLUMNS {
+                0 => main_top + (rows - 1.0) * SLOT_SIZE + HOTBAR_GAP,
+                row => main_top + (row - 1) as f32 * SLOT_SIZE,
+            };
+            Rect::new(left + column as f32 * SLOT_SIZE, y, SLOT_SIZE, SLOT_SIZE)
+        })
+        .collect();
+
+    let cell = |column: f32, row: f32| {
+        let (x, y) = (left + column * SLOT_SIZE, top + row * SLOT_SIZE);
+        Rect::new(x, y, SLOT_SIZE, SLOT_SIZE)
+    };
+    // Level with the middle row
+    let result = cell(RESULT_COLUMN as f32, (CONTAINER_ROWS - 1) as f32 / 2.0);
+    match panel {
+        Panel::Crafting(grid_size) => {
+            // Ends two columns short of the result, leaving room for the arrow
+            let grid_left = (RESULT_COLUMN - 2 - grid_size) as f32;
+            let grid_top = (CONTAINER_ROWS - grid_size) as f32 / 2.0;
+            for index in 0..grid_size * grid_size {
+                let (column, row) = (index % grid_size, index / grid_size);
+                slots.push(cell(grid_left + column as f32, grid_top + row as f32));
+            }
+            slots.push(result);
+        }
+        Panel::Chest => {
+            for index in 0..CHEST_SLOTS {
+                slots.push(cell((index % COLUMNS) as f32, (index / COLUMNS) as f32));
+            }
+        }
+        Panel::Furnace => {
+            let mut furnace = [result; 3];
+            furnace[FURNACE_INPUT] = cell(FURNACE_COLUMN as f32, 0.0);
+            furnace[FURNACE_FUEL] = cell(FURNACE_COLUMN as f32, (CONTAINER_ROWS - 1) as f32);
+            slots.extend(furnace);
+        }
+        Panel::Enchanting => {
+            slots.push(cell(
+                ENCHANTING_COLUMN as f32,
+                (CONTAINER_ROWS - 1) as f32 / 2.0,
+            ));
+        }
+    }
+    slots
+}
@@ -0,0 +1,365 @@
+use std::path::PathBuf;
+
+use super::{Rect, Ui, BUTTON_HEIGHT, BUTTON_WIDTH, SPACING};
+use crate::i18n::{tr, tr_args};
+use crate::render::text::{Color, WHITE};
+use crate::save::{self, WorldInfo};
+
+const ERROR_COLOR: Color = [1.0, 0.4, 0.4, 1.0];
+const DETAIL_COLOR: Color = [0.7, 0.7, 0.7, 1.0];
+const LIST_TOP: f32 = 64.0;
+const ENTRY_HEIGHT: f32 = 56.0;
+
+#[derive(Debug, Clone)]
+pub enum MainMenuAction {
+    Play(WorldInfo),
+    /// Create a world out of the Minecraft save in `source`
+    Import {
+        name: String,
+        source: PathBuf,
+    },
+    OpenSettings,
+    Quit,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Screen {
+    Title,
+    Worlds,
+    Create,
+    Rename,
+    ConfirmDelete,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Field {
+    Name,
+    Seed,
+    Import,
+}
+
+/// Title screen and everything reachable from it before a world is open
+pub struct MainMenu {
+    screen: Screen,
+    worlds: Vec<WorldInfo>,
+    selected: Option<usize>,
+    /// Index of the first visible world
+    scroll: usize,
+    name: String,
+    seed: String,
+    /// Minecraft save folder to import, the seed comes from there instead
+    import: String,
+    focused: Field,
+    error: Option<String>,
+}
+
+impl MainMenu {
+    pub fn new() -> Self {
+        MainMenu {
+            screen: Screen::Title,
+            worlds: Vec::new(),
+            selected: None,
+            scroll: 0,
+            name: String::new(),
+            seed: String::new(),
+            import: String::new(),
+            focused: Field::Name,
+            error: None,
+        }
+    }
+
+    pub fn draw(&mut self, ui: &mut Ui) -> Option<MainMenuAction> {
+        let action = match self.screen {
+            Screen::Title => self.draw_title(ui),
+            Screen::Worlds => self.draw_worlds(ui),
+            Screen::Create => self.draw_create(ui),
+            Screen::Rename => self.draw_rename(ui),
+            Screen::ConfirmDelete => self.draw_confirm_delete(ui),
+        };
+
+        if let Some(error) = &self.error {
+            let y = ui.screen_size()[1] - 2.0 * BUTTON_HEIGHT;
+            ui.label_centered(error, y, ERROR_COLOR);
+        }
+        action
+    }
+
+    fn draw_title(&mut self, ui: &mut Ui) -> Option<MainMenuAction> {
+        let top = ui.screen_size()[1] / 3.0;
+        ui.label_centered("RustCraft", top - 64.0, WHITE);
+
+        if ui.button(&tr("menu.singleplayer"), ui.column_rect(top, 0)) {
+            self.open(Screen::Worlds);
+        }
+        if ui.button(&tr("menu.settings"), ui.column_rect(top, 1)) {
+            return Some(MainMenuAction::OpenSettings);
+        }
+        if ui.button(&tr("menu.quit"), ui.column_rect(top, 2)) {
+            return Some(MainMenuAction::Quit);
+        }
+        None
+    }
+
+    fn draw_worlds(&mut self, ui: &mut Ui) -> Option<MainMenuAction> {
+        let [_, screen_height] = ui.screen_size();
+        ui.label_centered(&tr("worlds.title"), LIST_TOP - 40.0, WHITE);
+
+        let buttons_top = screen_height - 2.0 * (BUTTON_HEIGHT + SPACING) - 3.0 * BUTTON_HEIGHT;
+        let visible =
+            (((buttons_top - LIST_TOP) / (ENTRY_HEIGHT + SPACING)).floor() as usize).max(1);
+        let max_scroll = self.worlds.len().saturating_sub(visible);
+        let scroll = ui.input().scroll;
+        if scroll > 0.0 {
+            self.scroll = self.scroll.saturating_sub(1);
+        } else if scroll < 0.0 {
+            self.scroll = (self.scroll + 1).min(max_scroll);
+        }
+        self.scroll = self.scroll.min(max_scroll);
+
+        if self.worlds.is_empty() {
+            ui.label_centered(&tr("worlds.empty"), LIST_TOP + SPACING, DETAIL_COLOR);
+        }
+        let list_rect = ui.column_rect(LIST_TOP, 0);
+        for (row, index) in (self.scroll..self.worlds.len()).take(visible).enumerate() {
+            let world = &self.worlds[index];
+            let rect = Rect::new(
+                list_rect.x,
+                LIST_TOP + row as f32 * (ENTRY_HEIGHT + SPACING),
+                BUTTON_WIDTH,
+                ENTRY_HEIGHT,
+            );
+            let detail = tr_args(
+                "worlds.detail",
+                &[
+                    ("last_played", &last_played(world)),
+                    ("seed", &(world.seed as i64)),
+                ],
+            );
+            if ui.list_entry(&world.name, &detail, rect, self.selected == Some(index)) {
+                self.selected = Some(index);
+            }
+        }
+
+        let half = (BUTTON_WIDTH - SPACING) / 2.0;
+        let third = (BUTTON_WIDTH - 2.0 * SPACING) / 3.0;
+        let x = list_rect.x;
+        let second_row = buttons_top + BUTTON_HEIGHT + SPACING;
+
+        let selected = self.selected.filter(|index| *index < self.worlds.len());
+        if ui.button(
+            &tr("worlds.play"),
+            Rect::new(x, buttons_top, half, BUTTON_HEIGHT),
+        ) {
+            if let Some(index) = selected {
+                return Some(MainMenuAction::Play(self.worlds[index].clone()));
+            }
+        }
+        if ui.button(
+            &tr("worlds.create"),
+            Rect::new(x + half + SPACING, buttons_top, half, BUTTON_HEIGHT),
+        ) {
+            self.name = tr("worlds.default_name");
+            self.seed.clear();
+            self.import.clear();
+            self.focused = Field::Name;
+            self.open(Screen::Create);
+        }
+        if ui.button(
+            &tr("worlds.rename"),
+            Rect::new(x, second_row, third, BUTTON_HEIGHT),
+        ) {
+            if let Some(index) = selected {
+                self.name = self.worlds[index].name.clone();
+                self.focused = Field::Name;
+                self.open(Screen::Rename);
+            }
+        }
+        if ui.button(
+            &tr("worlds.delete"),
+            Rect::new(x + third + SPACING, second_row, third, BUTTON_HEIGHT),
+        ) && selected.is_some()
+        {
+            self.open(Screen::ConfirmDelete);
+        }
+        if ui.button(
+            &tr("gui.back"),
+            Rect::new(
+                x + 2.0 * (third + SPACING),
+                second_row,
+                third,
+                BUTTON_HEIGHT,
+            ),
+        ) {
+            self.open(Screen::Title);
+        }
+        None
+    }
+
+    fn draw_create(&mut self, ui: &mut Ui) -> Option<MainMenuAction> {
+        let top = ui.screen_size()[1] / 4.0;
+        ui.label_centered(&tr("worlds.create"), top - 48.0, WHITE);
+
+        let name_rect = ui.column_rect(top + 24.0, 0);
+        ui.label(&tr("worlds.name"), [name_rect.x, top], DETAIL_COLOR);
+        if ui.text_field(
+            &mut self.name,
+            &tr("worlds.name"),
+            name_rect,
+            self.focused == Field::Name,
+        ) {
+            self.focused = Field::Name;
+        }
+
+        let seed_top = top + 24.0 + 2.0 * (BUTTON_HEIGHT + SPACING);
+        let seed_rect = ui.column_rect(seed_top + 24.0, 0);
+        ui.label(&tr("worlds.seed"), [seed_rect.x, seed_top], DETAIL_COLOR);
+        if ui.text_field(
+            &mut self.seed,
+            &tr("worlds.seed_hint"),
+            seed_rect,
+            self.focused == Field::Seed,
+        ) {
+            self.focused = Field::Seed;
+        }
+
+        let import_top = seed_top + 24.0 + 2.0 * (BUTTON_HEIGHT + SPACING);
+        let import_rect = ui.column_rect(import_top + 24.0, 0);
+        ui.label(
+            &tr("worlds.import"),
+            [import_rect.x, import_top],
+            DETAIL_COLOR,
+        );
+        if ui.text_field(
+            &mut self.import,
+            &tr("worlds.import_hint"),
+            import_rect,
+            self.focused == Field::Import,
+        ) {
+            self.focused = Field::Import;
+        }
+
+        let buttons_top = import_top + 24.0 + 2.0 * (BUTTON_HEIGHT + SPACING);
+        let create = ui.button(&tr("worlds.create"), ui.column_rect(buttons_top, 0));
+        if create || ui.input().enter {
+            let name = self.name.trim();
+            if name.is_empty() {
+                self.error = Some(tr("worlds.needs_name"));
+                return None;
+            }
+            let source = self.import.trim();
+            if !source.is_empty() {
+                self.screen = Screen::Worlds;
+                return Some(MainMenuAction::Import {
+                    name: name.to_owned(),
+                    source: PathBuf::from(source),
+                });
+            }
+            return match save::create_world(name, save::parse_seed(&self.seed)) {
+                Ok(world) => {
+                    self.screen = Screen::Worlds;
+                    Some(MainMenuAction::Play(world))
+                }
+                Err(err) => {
+                    self.error = Some(format!("{err:#}"));
+                    None
+                }
+            };
+        }
+        if ui.button(&tr("gui.cancel"), ui.column_rect(buttons_top, 1)) {
+            self.open(Screen::Worlds);
+        }
+        None
+    }
+
+    fn draw_rename(&mut self, ui: &mut Ui) -> Option<MainMenuAction> {
+        let top = ui.screen_size()[1] / 3.0;
+        ui.label_centered(&tr("worlds.rename_title"), top - 48.0, WHITE);
+        ui.text_field(
+            &mut self.name,
+            &tr("worlds.name"),
+            ui.column_rect(top, 0),
+            true,
+        );
+
+        let done = ui.button(&tr("gui.done"), ui.column_rect(top, 2));
+        if done || ui.input().enter {
+            let name = self.name.trim().to_owned();
+            if name.is_empty() {
+                self.error = Some(tr("worlds.needs_name"));
+                return None;
+            }
+            if let Some(world) = self.selected.and_then(|index| self.worlds.get_mut(index)) {
+                world.name = name;
+                if let Err(err) = world.save() {
+                    self.error = Some(format!("{err:#}"));
+                    return None;
+                }
+            }
+            self.open(Screen::Worlds);
+        } else if ui.button(&tr("gui.cancel"), ui.column_rect(top, 3)) {
+            self.open(Screen::Worlds);
+        }
+        None
+    }
+
+    fn draw_confirm_delete(&mut self, ui: &mut Ui) -> Option<MainMenuAction> {
+        let Some(world) = self.selected.and_then(|index| self.worlds.get(index)) else {
+            self.open(Screen::Worlds);
+            return None;
+        };
+        let top = ui.screen_size()[1] / 3.0;
+        ui.label_centered(
+            &tr_args("worlds.delete_question", &[("name", &world.name)]),
+            top - 48.0,
+            WHITE,
+        );
+        ui.label_centered(&tr("worlds.delete_warning"), top - 24.0, ERROR_COLOR);
+
+        if ui.button(&tr("worlds.delete"), ui.column_rect(top, 0)) {
+            let result = save::delete_world(world);
+            self.selected = None;
+            self.open(Screen::Worlds);
+            if let Err(err) = result {
+                self.error = Some(format!("{err:#}"));
+            }
+        } else if ui.button(&tr("gui.cancel"), ui.column_rect(top, 1)) {
+            self.open(Screen::Worlds);
+        }
+        None
+    }
+
+    /// Switch screens, clearing any error from the previous one. The world list is re-read
+    /// every time it's shown so it never goes stale.
+    fn open(&mut self, screen: Screen) {
+        self.screen = screen;
+        self.error = None;
+        if screen == Screen::Worlds {
+            match save::list_worlds() {
+                Ok(worlds) => self.worlds = worlds,
+                Err(err) => self.error = Some(format!("{err:#}")),
+            }
+            if self
+                .selected
+                .is_some_and(|index| index >= self.worlds.len())
+            {
+                self.selected = None;
+            }
+        }
+    }
+}
+
+impl Default for MainMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn last_played(world: &WorldInfo) -> String {
+    chrono::DateTime::from_timestamp(world.last_played as i64, 0)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,314 @@
+pub mod death_screen;
+pub mod inventory_screen;
+pub mod main_menu;
+pub mod pause_menu;
+pub mod settings_menu;
+pub mod sign_editor;
+
+use glfw::Key;
+
+use crate::input::Binding;
+use crate::render::text::{Color, TextRenderer, WHITE};
+
+pub const BUTTON_WIDTH: f32 = 400.0;
+pub const BUTTON_HEIGHT: f32 = 40.0;
+pub const SPACING: f32 = 8.0;
+/// Narrower than [`BUTTON_WIDTH`] so two columns fit the smallest screen
+pub const GRID_BUTTON_WIDTH: f32 = 360.0;
+
+const BUTTON_COLOR: Color = [0.35, 0.35, 0.35, 0.9];
+const BUTTON_HOVER_COLOR: Color = [0.45, 0.45, 0.6, 0.9];
+const DIM_COLOR: Color = [0.0, 0.0, 0.0, 0.5];
+const FIELD_COLOR: Color = [0.05, 0.05, 0.05, 0.9];
+const FIELD_FOCUSED_COLOR: Color = [0.15, 0.15, 0.15, 0.9];
+const SELECTED_COLOR: Color = [0.3, 0.3, 0.5, 0.9];
+const SLIDER_FILL_COLOR: Color = [0.45, 0.45, 0.6, 0.9];
+const PLACEHOLDER_COLOR: Color = [0.6, 0.6, 0.6, 1.0];
+const PADDING: f32 = 8.0;
+pub const MAX_FIELD_LENGTH: usize = 32;
+/// Smallest screen the menus are laid out for, in GUI units
+const MIN_SCREEN_SIZE: [f32; 2] = [800.0, 600.0];
+
+/// Framebuffer pixels per GUI unit. `setting` 0 picks the largest scale that still leaves
+/// room for the menus, bigger settings are capped to that as well.
+pub fn gui_scale(framebuffer_size: [f32; 2], setting: u32) -> f32 {
+    let fits = (framebuffer_size[0] / MIN_SCREEN_SIZE[0])
+        .min(framebuffer_size[1] / MIN_SCREEN_SIZE[1])
+        .floor()
+        .max(1.0);
+    match setting {
+        0 => fits,
+        scale => (scale as f32).min(fits),
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        point[0] >= self.x
+            && point[0] < self.x + self.width
+            && point[1] >= self.y
+            && point[1] < self.y + self.height
+    }
+}
+
+/// Mouse and keyboard input for one frame, positions in GUI units
+#[derive(Debug, Clone, Default)]
+pub struct UiInput {
+    pub cursor: [f32; 2],
+    /// Left button went down this frame
+    pub clicked: bool,
+    /// Left button is held
+    pub mouse_down: bool,
+    /// Right button went down this frame
+    pub right_clicked: bool,
+    /// First key or mouse button pressed this frame, for rebinding controls
+    pub pressed: Option<Binding>,
+    /// Wheel notches, positive is up
+    pub scroll: f32,
+    /// Characters typed this frame
+    pub text: String,
+    pub backspace: u32,
+    pub enter: bool,
+    /// Keys pressed or repeated this frame
+    pub keys: Vec<Key>,
+}
+
+impl UiInput {
+    /// Forget everything that only lasts a frame
+    pub fn end_frame(&mut self) {
+        self.clicked = false;
+        self.right_clicked = false;
+        self.pressed = None;
+        self.scroll = 0.0;
+        self.text.clear();
+        self.backspace = 0;
+        self.enter = false;
+        self.keys.clear();
+    }
+}
+
+/// Immediate mode helpers on top of the text batch, widgets report interaction the moment
+/// they're drawn
+pub struct Ui<'a> {
+    text: &'a mut TextRenderer,
+    input: &'a UiInput,
+    screen_size: [f32; 2],
+    click_consumed: bool,
+}
+
+impl<'a> Ui<'a> {
+    pub fn new(text: &'a mut TextRenderer, input: &'a UiInput, screen_size: [f32; 2]) -> Self {
+        Ui {
+            text,
+            input,
+            screen_size,
+            click_consumed: false,
+        }
+    }
+
+    pub fn screen_size(&self) -> [f32; 2] {
+        self.screen_size
+    }
+
+    pub fn input(&self) -> &UiInput {
+        self.input
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.text.line_height()
+    }
+
+    /// Stop widgets drawn after this from reacting to the mouse this frame, for when the
+    /// click already meant something else
+    pub fn consume_click(&mut self) {
+        self.click_consumed = true;
+    }
+
+    fn clicked(&self, rect: Rect) -> bool {
+        !self.click_consumed && self.input.clicked && rect.contains(self.input.cursor)
+    }
+
+    /// Darken whatever is behind the menu
+    pub fn dim_background(&mut self) {
+        self.text
+            .queue_rect([0.0, 0.0], self.screen_size, DIM_COLOR);
+    }
+
+    pub fn rect(&mut self, position: [f32; 2], size: [f32; 2], color: Color) {
+        self.text.queue_rect(position, size, color);
+    }
+
+    pub fn label_centered(&mut self, label: &str, y: f32, color: Color) {
+        let [width, _] = self.text.measure(label);
+        let x = ((self.screen_size[0] - width) / 2.0).round();
+        self.text.queue(label, [x, y], color);
+    }
+
+    /// Returns true when clicked
+    pub fn button(&mut self, label: &str, rect: Rect) -> bool {
+        let hovered = rect.contains(self.input.cursor);
+        let color = if hovered {
+            BUTTON_HOVER_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+        self.text
+            .queue_rect([rect.x, rect.y], [rect.width, rect.height], color);
+
+        let [width, height] = self.text.measure(label);
+        let position = [
+            (rect.x + (rect.width - width) / 2.0).round(),
+            (rect.y + (rect.height - height) / 2.0).round(),
+        ];
+        self.text.queue(label, position, WHITE);
+        self.clicked(rect)
+    }
+
+    /// Single line text input, typing only reaches it while `focused`. Returns true when
+    /// clicked so the caller can move focus.
+    pub fn text_field(
+        &mut self,
+        value: &mut String,
+        placeholder: &str,
+        rect: Rect,
+        focused: bool,
+    ) -> bool {
+        if focused {
+            for character in self.input.text.chars() {
+                if !character.is_control() && value.chars().count() < MAX_FIELD_LENGTH {
+                    value.push(character);
+                }
+            }
+            for _ in 0..self.input.backspace {
+                value.pop();
+            }
+        }
+
+        let color = if focused {
+            FIELD_FOCUSED_COLOR
+        } else {
+            FIELD_COLOR
+        };
+        self.text
+            .queue_rect([rect.x, rect.y], [rect.width, rect.height], color);
+        let y = (rect.y + (rect.height - self.text.line_height()) / 2.0).round();
+        if value.is_empty() && !focused {
+            self.text
+                .queue(placeholder, [rect.x + PADDING, y], PLACEHOLDER_COLOR);
+        } else if focused {
+            self.text
+                .queue(&format!("{value}_"), [rect.x + PADDING, y], WHITE);
+        } else {
+            self.text.queue(value, [rect.x + PADDING, y], WHITE);
+        }
+        self.clicked(rect)
+    }
+
+    /// Two line entry for lists, returns true when clicked
+    pub fn list_entry(&mut self, title: &str, detail: &str, rect: Rect, selected: bool) -> bool {
+        let hovered = rect.contains(self.input.cursor);
+        let color = if selected {
+            SELECTED_COLOR
+        } else if hovered {
+            BUTTON_HOVER_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+        self.text
+            .queue_rect([rect.x, rect.y], [rect.width, rect.height], color);
+        let line_height = self.text.line_height();
+        self.text
+            .queue(title, [rect.x + PADDING, rect.y + PADDING], WHITE);
+        self.text.queue(
+            detail,
+            [rect.x + PADDING, rect.y + PADDING + line_height],
+            PLACEHOLDER_COLOR,
+        );
+        self.clicked(rect)
+    }
+
+    /// Horizontal slider with `label` centered on it. Returns the new value while it's being
+    /// dragged, snapped to whole multiples of `step`.
+    pub fn slider(
+        &mut self,
+        label: &str,
+        value: f32,
+        range: (f32, f32),
+        step: f32,
+        rect: Rect,
+    ) -> Option<f32> {
+        let (min, max) = range;
+        let hovered = rect.contains(self.input.cursor);
+        let color = if hovered {
+            BUTTON_HOVER_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+        self.text
+            .queue_rect([rect.x, rect.y], [rect.width, rect.height], color);
+        let fraction = ((value - min) / (max - min)).clamp(0.0, 1.0);
+        self.text.queue_rect(
+            [rect.x, rect.y],
+            [(rect.width * fraction).round(), rect.height],
+            SLIDER_FILL_COLOR,
+        );
+
+        let [width, height] = self.text.measure(label);
+        let position = [
+            (rect.x + (rect.width - width) / 2.0).round(),
+            (rect.y + (rect.height - height) / 2.0).round(),
+        ];
+        self.text.queue(label, position, WHITE);
+
+        if self.click_consumed || !self.input.mouse_down || !hovered {
+            return None;
+        }
+        let fraction = ((self.input.cursor[0] - rect.x) / rect.width).clamp(0.0, 1.0);
+        let dragged = (min + fraction * (max - min)) / step;
+        let dragged = (dragged.round() * step).clamp(min, max);
+        (dragged != value).then_some(dragged)
+    }
+
+    pub fn label(&mut self, label: &str, position: [f32; 2], color: Color) {
+        self.text.queue(label, position, color);
+    }
+
+    /// Rect for the `index`th button of two columns centered on screen, starting at `top` and
+    /// filled row by row, for pages with too many buttons for one column
+    pub fn grid_rect(&self, top: f32, index: usize) -> Rect {
+        let left = ((self.screen_size[0] - 2.0 * GRID_BUTTON_WIDTH - SPACING) / 2.0).round();
+        Rect::new(
+            left + (index % 2) as f32 * (GRID_BUTTON_WIDTH + SPACING),
+            top + (index / 2) as f32 * (BUTTON_HEIGHT + SPACING),
+            GRID_BUTTON_WIDTH,
+            BUTTON_HEIGHT,
+        )
+    }
+
+    /// Rect for the `index`th button of a column centered on screen, starting at `top`
+    pub fn column_rect(&self, top: f32, index: usize) -> Rect {
+        Rect::new(
+            ((self.screen_size[0] - BUTTON_WIDTH) / 2.0).round(),
+            top + index as f32 * (BUTTON_HEIGHT + SPACING),
+            BUTTON_WIDTH,
+            BUTTON_HEIGHT,
+        )
+    }
+}
@@ -0,0 +1,28 @@
+use super::Ui;
+use crate::i18n::tr;
+use crate::render::text::WHITE;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PauseAction {
+    Resume,
+    OpenSettings,
+    QuitToTitle,
+}
+
+pub fn draw(ui: &mut Ui) -> Option<PauseAction> {
+    ui.dim_background();
+    let top = ui.screen_size()[1] / 3.0;
+    ui.label_centered(&tr("pause.title"), top - 48.0, WHITE);
+
+    let mut action = None;
+    if ui.button(&tr("pause.resume"), ui.column_rect(top, 0)) {
+        action = Some(PauseAction::Resume);
+    }
+    if ui.button(&tr("pause.options"), ui.column_rect(top, 1)) {
+        action = Some(PauseAction::OpenSettings);
+    }
+    if ui.button(&tr("pause.quit"), ui.column_rect(top, 2)) {
+        action = Some(PauseAction::QuitToTitle);
+    }
+    action
+}
@@ -0,0 +1,489 @@
+use super::{Rect, Ui, BUTTON_HEIGHT, BUTTON_WIDTH, SPACING};
+use crate::assets::RESOURCE_PACK_DIR;
+use crate::display::FullscreenMode;
+use crate::i18n::{tr, tr_args, Language};
+use crate::input::InputAction;
+use crate::render::deferred::SsaoQuality;
+use crate::render::mesher::MeshingMode;
+use crate::render::text::{Color, WHITE};
+use crate::settings::Settings;
+
+const CONFLICT_COLOR: Color = [1.0, 0.4, 0.4, 1.0];
+const TABS_TOP: f32 = 48.0;
+const CONTENT_TOP: f32 = TABS_TOP + BUTTON_HEIGHT + 2.0 * SPACING;
+/// What the antialiasing button cycles through, as FXAA on or off and MSAA samples
+const ANTIALIASING_OPTIONS: [(bool, u32); 5] =
+    [(false, 0), (true, 0), (false, 2), (false, 4), (false, 8)];
+/// The GUI scale button cycles through auto and 1 up to this
+const MAX_GUI_SCALE: u32 = 3;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SettingsAction {
+    Done,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Tab {
+    Video,
+    Controls,
+    Audio,
+    Packs,
+}
+
+impl Tab {
+    const ALL: [(Tab, &'static str); 4] = [
+        (Tab::Video, "options.tab.video"),
+        (Tab::Controls, "options.tab.controls"),
+        (Tab::Audio, "options.tab.audio"),
+        (Tab::Packs, "options.tab.packs"),
+    ];
+}
+
+/// Options screen, shared by the title screen and the pause menu. Edits go straight into the
+/// live settings, the caller applies and saves them.
+pub struct SettingsMenu {
+    tab: Tab,
+    /// Action waiting for the next key or mouse button
+    awaiting_binding: Option<InputAction>,
+    /// Index of the first visible row of the key bindings or resource packs
+    scroll: usize,
+    languages: Vec<Language>,
+    /// Resource packs that can be turned on, see [`crate::assets::available_packs`]
+    packs: Vec<String>,
+}
+
+impl SettingsMenu {
+    /// `languages` are the ones the language button cycles through, `packs` the resource
+    /// packs listed on their tab
+    pub fn new(languages: Vec<Language>, packs: Vec<String>) -> Self {
+        SettingsMenu {
+            tab: Tab::Video,
+            awaiting_binding: None,
+            scroll: 0,
+            languages,
+            packs,
+        }
+    }
+
+    /// Returns false when there was nothing to cancel, so Escape can close the menu instead
+    pub fn cancel_rebind(&mut self) -> bool {
+        self.awaiting_binding.take().is_some()
+    }
+
+    pub fn draw(&mut self, ui: &mut Ui, settings: &mut Settings) -> Option<SettingsAction> {
+        // Grab the key before any widget sees the click that might have produced it
+        if let Some(action) = self.awaiting_binding {
+            if let Some(binding) = ui.input().pressed.filter(|binding| binding.is_nameable()) {
+                settings.key_bindings.set(action, binding);
+                self.awaiting_binding = None;
+                ui.consume_click();
+            }
+        }
+
+        ui.dim_background();
+        ui.label_centered(&tr("options.title"), TABS_TOP - 32.0, WHITE);
+
+        let x = ui.column_rect(0.0, 0).x;
+        let count = Tab::ALL.len() as f32;
+        let tab_width = (BUTTON_WIDTH - (count - 1.0) * SPACING) / count;
+        for (index, (tab, label)) in Tab::ALL.into_iter().enumerate() {
+            let rect = Rect::new(
+                x + index as f32 * (tab_width + SPACING),
+                TABS_TOP,
+                tab_width,
+                BUTTON_HEIGHT,
+            );
+            if ui.list_entry(&tr(label), "", rect, self.tab == tab) && self.tab != tab {
+                self.tab = tab;
+                self.awaiting_binding = None;
+                self.scroll = 0;
+            }
+        }
+
+        let done_top = ui.screen_size()[1] - BUTTON_HEIGHT - 2.0 * SPACING;
+        match self.tab {
+            Tab::Video => self.draw_video(ui, settings),
+            Tab::Controls => self.draw_controls(ui, settings, done_top),
+            Tab::Audio => draw_audio(ui, settings),
+            Tab::Packs => self.draw_packs(ui, settings, done_top),
+        }
+
+        let done_rect = if self.tab == Tab::Controls {
+            let half = (BUTTON_WIDTH - SPACING) / 2.0;
+            if ui.button(
+                &tr("options.reset_keys"),
+                Rect::new(x, done_top, half, BUTTON_HEIGHT),
+            ) {
+                settings.key_bindings.reset();
+                self.awaiting_binding = None;
+            }
+            Rect::new(x + half + SPACING, done_top, half, BUTTON_HEIGHT)
+        } else {
+            ui.column_rect(done_top, 0)
+        };
+        if ui.button(&tr("gui.done"), done_rect) {
+            self.awaiting_binding = None;
+            return Some(SettingsAction::Done);
+        }
+        None
+    }
+
+    fn draw_controls(&mut self, ui: &mut Ui, settings: &mut Settings, bottom: f32) {
+        let label = tr_args(
+            "options.sensitivity",
+            &[("percent", &(settings.mouse_sensitivity * 100.0).round())],
+        );
+        if let Some(value) = ui.slider(
+            &label,
+            settings.mouse_sensitivity,
+            (0.1, 3.0),
+            0.05,
+            ui.column_rect(CONTENT_TOP, 0),
+        ) {
+            settings.mouse_sensitivity = value;
+        }
+
+        let x = ui.column_rect(0.0, 0).x;
+        let half = (BUTTON_WIDTH - SPACING) / 2.0;
+        let mouse_row = CONTENT_TOP + BUTTON_HEIGHT + SPACING;
+        let state = tr(if settings.raw_mouse_motion {
+            "gui.on"
+        } else {
+            "gui.off"
+        });
+        let label = tr_args("options.raw_mouse", &[("state", &state)]);
+        if ui.button(&label, Rect::new(x, mouse_row, half, BUTTON_HEIGHT)) {
+            settings.raw_mouse_motion = !settings.raw_mouse_motion;
+        }
+        let label = if settings.mouse_acceleration > 0.0 {
+            tr_args(
+                "options.acceleration",
+                &[("amount", &format!("{:.1}", settings.mouse_acceleration))],
+            )
+        } else {
+            tr("options.acceleration_off")
+        };
+        if let Some(value) = ui.slider(
+            &label,
+            settings.mouse_acceleration,
+            (0.0, 2.0),
+            0.1,
+            Rect::new(x + half + SPACING, mouse_row, half, BUTTON_HEIGHT),
+        ) {
+            settings.mouse_acceleration = value;
+        }
+
+        let list_top = mouse_row + BUTTON_HEIGHT + SPACING;
+        let visible = self.scroll_rows(ui, InputAction::ALL.len(), list_top, bottom);
+        for (row, action) in InputAction::ALL
+            .into_iter()
+            .skip(self.scroll)
+            .take(visible)
+            .enumerate()
+        {
+            let y = list_top + row as f32 * (BUTTON_HEIGHT + SPACING);
+            let binding = settings.key_bindings.get(action);
+            let conflicts = settings.key_bindings.actions_for(binding).count() > 1;
+            let label_y = (y + (BUTTON_HEIGHT - ui.line_height()) / 2.0).round();
+            ui.label(
+                &action.label(),
+                [x, label_y],
+                if conflicts { CONFLICT_COLOR } else { WHITE },
+            );
+
+            let text = if self.awaiting_binding == Some(action) {
+                String::from("> ??? <")
+            } else {
+                binding.to_string()
+            };
+            if ui.button(&text, Rect::new(x + half + SPACING, y, half, BUTTON_HEIGHT)) {
+                self.awaiting_binding = Some(action);
+            }
+        }
+    }
+
+    /// The turned on packs in priority order, then the rest. Clicking one turns it on at the
+    /// top or off, the arrow next to a turned on one moves it up a place.
+    fn draw_packs(&mut self, ui: &mut Ui, settings: &mut Settings, bottom: f32) {
+        let mut names = settings.resource_packs.clone();
+        names.extend(
+            self.packs
+                .iter()
+                .filter(|name| !settings.resource_packs.contains(name))
+                .cloned(),
+        );
+        if names.is_empty() {
+            let label = tr_args("options.packs_none", &[("folder", &RESOURCE_PACK_DIR)]);
+            ui.label_centered(&label, CONTENT_TOP, WHITE);
+            return;
+        }
+
+        let x = ui.column_rect(0.0, 0).x;
+        let visible = self.scroll_rows(ui, names.len(), CONTENT_TOP, bottom);
+        let toggle_width = BUTTON_WIDTH - BUTTON_HEIGHT - SPACING;
+        for (row, name) in names.iter().skip(self.scroll).take(visible).enumerate() {
+            let y = CONTENT_TOP + row as f32 * (BUTTON_HEIGHT + SPACING);
+            let priority = settings.resource_packs.iter().position(|pack| pack == name);
+            let label = match priority {
+                Some(index) => tr_args(
+                    "options.pack_on",
+                    &[("name", name), ("priority", &(index + 1))],
+                ),
+                None => tr_args("options.pack_off", &[("name", name)]),
+            };
+            if ui.button(&label, Rect::new(x, y, toggle_width, BUTTON_HEIGHT)) {
+                match priority {
+                    Some(index) => {
+                        settings.resource_packs.remove(index);
+                    }
+                    None => settings.resource_packs.insert(0, name.clone()),
+                }
+            }
+            let up = Rect::new(x + toggle_width + SPACING, y, BUTTON_HEIGHT, BUTTON_HEIGHT);
+            if let Some(index) = priority.filter(|index| *index > 0) {
+                if ui.button("^", up) {
+                    settings.resource_packs.swap(index, index - 1);
+                }
+            }
+        }
+    }
+
+    /// Scroll a list of `rows` from `top` down to `bottom` with the mouse wheel, returns how
+    /// many rows fit
+    fn scroll_rows(&mut self, ui: &Ui, rows: usize, top: f32, bottom: f32) -> usize {
+        let visible = (((bottom - top) / (BUTTON_HEIGHT + SPACING)).floor() as usize).max(1);
+        let max_scroll = rows.saturating_sub(visible);
+        let scroll = ui.input().scroll;
+        if scroll > 0.0 {
+            self.scroll = self.scroll.saturating_sub(1);
+        } else if scroll < 0.0 {
+            self.scroll = (self.scroll + 1).min(max_scroll);
+        }
+        self.scroll = self.scroll.min(max_scroll);
+        visible
+    }
+
+    fn draw_video(&self, ui: &mut Ui, settings: &mut Settings) {
+        let label = tr_args(
+            "options.render_distance",
+            &[("chunks", &settings.render_distance)],
+        );
+        if let Some(value) = ui.slider(
+            &label,
+            settings.render_distance as f32,
+            (2.0, 48.0),
+            1.0,
+            ui.grid_rect(CONTENT_TOP, 0),
+        ) {
+            settings.render_distance = value as u32;
+        }
+
+        let label = tr_args("options.fov", &[("fov", &settings.fov.round())]);
+        if let Some(value) = ui.slider(
+            &label,
+            settings.fov,
+            (30.0, 110.0),
+            1.0,
+            ui.grid_rect(CONTENT_TOP, 1),
+        ) {
+            settings.fov = value;
+        }
+
+        let state = tr(if settings.vsync { "gui.on" } else { "gui.off" });
+        let label = tr_args("options.vsync", &[("state", &state)]);
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 2)) {
+            settings.vsync = !settings.vsync;
+        }
+
+        let label = match (settings.fxaa, settings.msaa_samples) {
+            (true, _) => tr("options.fxaa"),
+            (false, 0) => tr("options.msaa_off"),
+            (false, samples) => tr_args("options.msaa", &[("samples", &samples)]),
+        };
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 3)) {
+            let current = ANTIALIASING_OPTIONS
+                .iter()
+                .position(|option| *option == (settings.fxaa, settings.msaa_samples))
+                .unwrap_or(0);
+            (settings.fxaa, settings.msaa_samples) =
+                ANTIALIASING_OPTIONS[(current + 1) % ANTIALIASING_OPTIONS.len()];
+        }
+
+        let label = tr(match settings.fullscreen_mode {
+            FullscreenMode::Exclusive => "options.fullscreen_exclusive",
+            FullscreenMode::Borderless => "options.fullscreen_borderless",
+        });
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 4)) {
+            settings.fullscreen_mode = match settings.fullscreen_mode {
+                FullscreenMode::Exclusive => FullscreenMode::Borderless,
+                FullscreenMode::Borderless => FullscreenMode::Exclusive,
+            };
+        }
+
+        let label = tr(match settings.meshing {
+            MeshingMode::Naive => "options.meshing_naive",
+            MeshingMode::Greedy => "options.meshing_greedy",
+        });
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 5)) {
+            settings.meshing = match settings.meshing {
+                MeshingMode::Naive => MeshingMode::Greedy,
+                MeshingMode::Greedy => MeshingMode::Naive,
+            };
+        }
+
+        let state = tr(if settings.smooth_lighting {
+            "gui.on"
+        } else {
+            "gui.off"
+        });
+        let label = tr_args("options.smooth_lighting", &[("state", &state)]);
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 6)) {
+            settings.smooth_lighting = !settings.smooth_lighting;
+        }
+
+        let state = tr(if settings.shadows {
+            "gui.on"
+        } else {
+            "gui.off"
+        });
+        let label = tr_args("options.shadows", &[("state", &state)]);
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 7)) {
+            settings.shadows = !settings.shadows;
+        }
+
+        let state = tr(if settings.deferred {
+            "gui.on"
+        } else {
+            "gui.off"
+        });
+        let label = tr_args("options.deferred", &[("state", &state)]);
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 8)) {
+            settings.deferred = !settings.deferred;
+        }
+
+        let label = tr_args(
+            "options.gamma",
+            &[("gamma", &format!("{:.2}", settings.gamma))],
+        );
+        if let Some(value) = ui.slider(
+            &label,
+            settings.gamma,
+            (0.5, 2.0),
+            0.05,
+            ui.grid_rect(CONTENT_TOP, 9),
+        ) {
+            settings.gamma = value;
+        }
+
+        let state = tr(if settings.bloom { "gui.on" } else { "gui.off" });
+        let label = tr_args("options.bloom", &[("state", &state)]);
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 10)) {
+            settings.bloom = !settings.bloom;
+        }
+
+        let label = tr(match settings.ssao {
+            SsaoQuality::Off => "options.ssao_off",
+            SsaoQuality::Low => "options.ssao_low",
+            SsaoQuality::Medium => "options.ssao_medium",
+            SsaoQuality::High => "options.ssao_high",
+        });
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 11)) {
+            let index = SsaoQuality::ALL
+                .iter()
+                .position(|quality| *quality == settings.ssao)
+                .map_or(0, |index| (index + 1) % SsaoQuality::ALL.len());
+            settings.ssao = SsaoQuality::ALL[index];
+        }
+
+        let state = tr(if settings.fancy_water {
+            "gui.on"
+        } else {
+            "gui.off"
+        });
+        let label = tr_args("options.fancy_water", &[("state", &state)]);
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 12)) {
+            settings.fancy_water = !settings.fancy_water;
+        }
+
+        let label = match settings.lod_distance {
+            0 => tr("options.lod_off"),
+            chunks => tr_args("options.lod", &[("chunks", &chunks)]),
+        };
+        if let Some(value) = ui.slider(
+            &label,
+            settings.lod_distance as f32,
+            (0.0, 32.0),
+            1.0,
+            ui.grid_rect(CONTENT_TOP, 13),
+        ) {
+            settings.lod_distance = value as u32;
+        }
+
+        let label = if settings.cloud_density > 0.0 {
+            let percent = (settings.cloud_density * 100.0).round();
+            tr_args("options.clouds", &[("percent", &percent)])
+        } else {
+            tr("options.clouds_off")
+        };
+        if let Some(value) = ui.slider(
+            &label,
+            settings.cloud_density,
+            (0.0, 1.0),
+            0.05,
+            ui.grid_rect(CONTENT_TOP, 14),
+        ) {
+            settings.cloud_density = value;
+        }
+
+        let label = match settings.gui_scale {
+            0 => tr("options.gui_scale_auto"),
+            scale => tr_args("options.gui_scale", &[("scale", &scale)]),
+        };
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 15)) {
+            settings.gui_scale = (settings.gui_scale + 1) % (MAX_GUI_SCALE + 1);
+        }
+
+        let current = self
+            .languages
+            .iter()
+            .position(|language| language.code == settings.language);
+        let name = current.map_or(settings.language.as_str(), |index| {
+            self.languages[index].name.as_str()
+        });
+        let label = tr_args("options.language", &[("language", &name)]);
+        if ui.button(&label, ui.grid_rect(CONTENT_TOP, 16)) && !self.languages.is_empty() {
+            let next = current.map_or(0, |index| (index + 1) % self.languages.len());
+            settings.language = self.languages[next].code.clone();
+        }
+    }
+}
+
+fn draw_audio(ui: &mut Ui, settings: &mut Settings) {
+    let volume = &mut settings.volume;
+    for (index, (name, value)) in [
+        ("options.volume.master", &mut volume.master),
+        ("options.volume.music", &mut volume.music),
+        ("options.volume.blocks", &mut volume.blocks),
+        ("options.volume.ambient", &mut volume.ambient),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let label = tr_args(
+            "options.volume",
+            &[
+                ("category", &tr(name)),
+                ("percent", &(*value * 100.0).round()),
+            ],
+        );
+        if let Some(new) = ui.slider(
+            &label,
+            *value,
+            (0.0, 1.0),
+            0.01,
+            ui.column_rect(CONTENT_TOP, index),
+        ) {
+            *value = new;
+        }
+    }
+}
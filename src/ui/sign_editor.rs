@@ -0,0 +1,69 @@
+use cgmath::Point3;
+use glfw::Key;
+
+use super::{Ui, BUTTON_HEIGHT, SPACING};
+use crate::i18n::tr;
+use crate::render::text::WHITE;
+use crate::world::block_entity::{SIGN_LINES, SIGN_LINE_LENGTH};
+
+/// Writing on the sign at `position`. The lines are a copy, they only go on the sign once
+/// the player is done.
+pub struct SignEditor {
+    position: Point3<i32>,
+    pub lines: [String; SIGN_LINES],
+    /// The line typing goes to
+    focused: usize,
+}
+
+impl SignEditor {
+    pub fn new(position: Point3<i32>, lines: &[String]) -> Self {
+        let mut editor = SignEditor {
+            position,
+            lines: Default::default(),
+            focused: 0,
+        };
+        for (line, text) in editor.lines.iter_mut().zip(lines) {
+            line.clone_from(text);
+        }
+        editor
+    }
+
+    pub fn position(&self) -> Point3<i32> {
+        self.position
+    }
+
+    /// Returns true once the player is done. Enter and the arrow keys move between lines,
+    /// enter on the last one finishes.
+    pub fn draw(&mut self, ui: &mut Ui) -> bool {
+        ui.dim_background();
+        let top = ui.screen_size()[1] / 4.0;
+        ui.label_centered(&tr("sign.title"), top - 48.0, WHITE);
+
+        for (index, line) in self.lines.iter_mut().enumerate() {
+            let rect = ui.column_rect(top, index);
+            if ui.text_field(line, "", rect, self.focused == index) {
+                self.focused = index;
+            }
+            if let Some((end, _)) = line.char_indices().nth(SIGN_LINE_LENGTH) {
+                line.truncate(end);
+            }
+        }
+
+        let last = SIGN_LINES - 1;
+        for key in &ui.input().keys {
+            match key {
+                Key::Up => self.focused = self.focused.saturating_sub(1),
+                Key::Down | Key::Tab => self.focused = (self.focused + 1).min(last),
+                _ => {}
+            }
+        }
+        if ui.input().enter {
+            if self.focused == last {
+                return true;
+            }
+            self.focused += 1;
+        }
+        let done_top = top + SIGN_LINES as f32 * (BUTTON_HEIGHT + SPACING) + SPACING;
+        ui.button(&tr("gui.done"), ui.column_rect(done_top, 0))
+    }
+}
@@ -0,0 +1,197 @@
+use serde::Deserialize;
+
+use super::noise::{self, Fractal};
+
+/// Climate differences smaller than this blend the terrain shape of two biomes
+const BLEND_WIDTH: f64 = 0.25;
+/// Climate noise rarely reaches past ±0.6, stretch it so the corners get used too
+const CLIMATE_SCALE: f64 = 1.6;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Biome {
+    Ocean,
+    Plains,
+    Desert,
+    Forest,
+    Mountains,
+}
+
+/// What falls from the sky in a biome while it's raining
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Precipitation {
+    None,
+    Rain,
+    Snow,
+}
+
+/// Terrain shape and cover for one biome
+#[derive(Debug, Copy, Clone)]
+pub struct BiomeParameters {
+    /// Surface height where the height noise is zero
+    pub base_height: f64,
+    /// Furthest the surface gets from the base height
+    pub height_variation: f64,
+    /// Block names for the top block and the few below it
+    pub surface: &'static str,
+    pub soil: &'static str,
+    /// Chance for each column to grow a tree
+    pub tree_density: f64,
+    /// Multiplied into grass and foliage tinted faces, from 0 to 1
+    pub grass_color: [f32; 3],
+    pub foliage_color: [f32; 3],
+}
+
+impl Biome {
+    pub const ALL: [Biome; 5] = [
+        Biome::Ocean,
+        Biome::Plains,
+        Biome::Desert,
+        Biome::Forest,
+        Biome::Mountains,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Biome::Ocean => "ocean",
+            Biome::Plains => "plains",
+            Biome::Desert => "desert",
+            Biome::Forest => "forest",
+            Biome::Mountains => "mountains",
+        }
+    }
+
+    pub fn parameters(self) -> BiomeParameters {
+        match self {
+            Biome::Ocean => BiomeParameters {
+                base_height: 46.0,
+                height_variation: 10.0,
+                surface: "sand",
+                soil: "sand",
+                tree_density: 0.0,
+                grass_color: [0.56, 0.73, 0.35],
+                foliage_color: [0.47, 0.67, 0.24],
+            },
+            Biome::Plains => BiomeParameters {
+                base_height: 68.0,
+                height_variation: 8.0,
+                surface: "grass_block",
+                soil: "dirt",
+                tree_density: 0.002,
+                grass_color: [0.57, 0.74, 0.35],
+                foliage_color: [0.47, 0.65, 0.19],
+            },
+            Biome::Desert => BiomeParameters {
+                base_height: 66.0,
+                height_variation: 6.0,
+                surface: "sand",
+                soil: "sand",
+                tree_density: 0.0,
+                grass_color: [0.75, 0.72, 0.33],
+                foliage_color: [0.68, 0.64, 0.16],
+            },
+            Biome::Forest => BiomeParameters {
+                base_height: 70.0,
+                height_variation: 14.0,
+                surface: "grass_block",
+                soil: "dirt",
+                tree_density: 0.03,
+                grass_color: [0.47, 0.69, 0.36],
+                foliage_color: [0.35, 0.68, 0.19],
+            },
+            Biome::Mountains => BiomeParameters {
+                base_height: 96.0,
+                height_variation: 48.0,
+                surface: "stone",
+                soil: "stone",
+                tree_density: 0.001,
+                grass_color: [0.54, 0.71, 0.54],
+                foliage_color: [0.43, 0.64, 0.42],
+            },
+        }
+    }
+
+    pub fn precipitation(self) -> Precipitation {
+        match self {
+            Biome::Desert => Precipitation::None,
+            Biome::Mountains => Precipitation::Snow,
+            Biome::Ocean | Biome::Plains | Biome::Forest => Precipitation::Rain,
+        }
+    }
+
+    /// Where the biome sits in temperature and humidity, each from -1 to 1
+    fn climate(self) -> [f64; 2] {
+        match self {
+            Biome::Ocean => [0.0, 0.9],
+            Biome::Plains => [0.2, -0.1],
+            Biome::Desert => [0.8, -0.7],
+            Biome::Forest => [0.0, 0.45],
+            Biome::Mountains => [-0.7, -0.2],
+        }
+    }
+}
+
+/// Picks biomes from two large scale noise maps, temperature and humidity
+pub struct BiomeSource {
+    temperature: Fractal,
+    humidity: Fractal,
+}
+
+impl BiomeSource {
+    pub fn new(seed: u64) -> Self {
+        BiomeSource {
+            temperature: Fractal::new(noise::hash(seed, &[1]), 3, 1.0 / 1024.0),
+            humidity: Fractal::new(noise::hash(seed, &[2]), 3, 1.0 / 1024.0),
+        }
+    }
+
+    /// How much each biome in [`Biome::ALL`] shapes the column, adding up to 1. Far from a
+    /// border one biome has nearly all of it.
+    pub fn weights(&self, x: i32, z: i32) -> [f64; 5] {
+        let climate = [&self.temperature, &self.humidity]
+            .map(|noise| (noise.sample2(x as f64, z as f64) * CLIMATE_SCALE).clamp(-1.0, 1.0));
+        let mut weights = Biome::ALL.map(|biome| {
+            let [temperature, humidity] = biome.climate();
+            let distance_squared =
+                (climate[0] - temperature).powi(2) + (climate[1] - humidity).powi(2);
+            (-distance_squared / (BLEND_WIDTH * BLEND_WIDTH)).exp()
+        });
+        let total: f64 = weights.iter().sum();
+        for weight in &mut weights {
+            *weight /= total.max(f64::MIN_POSITIVE);
+        }
+        weights
+    }
+
+    /// The biome with the largest weight
+    pub fn biome(&self, x: i32, z: i32) -> Biome {
+        dominant(&self.weights(x, z))
+    }
+}
+
+/// Grass and foliage colors of a column, each biome's weighted like the terrain shape so
+/// they fade into each other at borders
+pub fn tint_colors(weights: &[f64; 5]) -> [[f32; 3]; 2] {
+    let mut colors = [[0.0; 3]; 2];
+    for (biome, weight) in Biome::ALL.iter().zip(weights) {
+        let parameters = biome.parameters();
+        for (color, biome_color) in colors
+            .iter_mut()
+            .zip([parameters.grass_color, parameters.foliage_color])
+        {
+            for (channel, value) in color.iter_mut().zip(biome_color) {
+                *channel += value * *weight as f32;
+            }
+        }
+    }
+    colors
+}
+
+pub fn dominant(weights: &[f64; 5]) -> Biome {
+    let (index, _) = weights
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap_or((0, &0.0));
+    Biome::ALL[index]
+}
@@ -0,0 +1,1223 @@
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::circuit::MAX_POWER;
+use super::model::{BlockModel, Rotation};
+use crate::assets::Assets;
+use crate::game::item::{ToolKind, ToolTier};
+
+/// Block names in id order, id 0 is always air and isn't listed
+const INDEX_PATH: &str = "blocks/index.json";
+
+/// Numeric block type as stored in chunks, what each id means comes from the [`BlockRegistry`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct BlockId(pub u16);
+
+impl BlockId {
+    pub const AIR: BlockId = BlockId(0);
+}
+
+/// Side of a block, named like compass directions with north towards negative Z
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Face {
+    East,
+    West,
+    Up,
+    Down,
+    South,
+    North,
+}
+
+impl Face {
+    pub const ALL: [Face; 6] = [
+        Face::East,
+        Face::West,
+        Face::Up,
+        Face::Down,
+        Face::South,
+        Face::North,
+    ];
+
+    /// Unit vector pointing out of the face
+    pub fn normal(self) -> [i32; 3] {
+        match self {
+            Face::East => [1, 0, 0],
+            Face::West => [-1, 0, 0],
+            Face::Up => [0, 1, 0],
+            Face::Down => [0, -1, 0],
+            Face::South => [0, 0, 1],
+            Face::North => [0, 0, -1],
+        }
+    }
+
+    /// The face whose [`Face::normal`] is `normal`
+    pub fn from_normal(normal: [i32; 3]) -> Option<Face> {
+        Face::ALL.into_iter().find(|face| face.normal() == normal)
+    }
+
+    /// The face on the other side of the block
+    pub fn opposite(self) -> Face {
+        match self {
+            Face::East => Face::West,
+            Face::West => Face::East,
+            Face::Up => Face::Down,
+            Face::Down => Face::Up,
+            Face::South => Face::North,
+            Face::North => Face::South,
+        }
+    }
+
+    /// Lowercase, as in block files and variant names
+    pub fn name(self) -> &'static str {
+        match self {
+            Face::East => "east",
+            Face::West => "west",
+            Face::Up => "up",
+            Face::Down => "down",
+            Face::South => "south",
+            Face::North => "north",
+        }
+    }
+}
+
+/// Everything the game knows about one kind of block
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub name: String,
+    /// Texture names in [`Face::ALL`] order
+    pub textures: [String; 6],
+    /// Collides with entities
+    pub solid: bool,
+    /// Faces behind it still get drawn (glass, leaves)
+    pub transparent: bool,
+    /// Blended over what's behind it instead of cut out, in a separate sorted pass (water)
+    pub translucent: bool,
+    /// Breaking it by hand takes 1.5 times this many seconds, or 5 times when it needs a
+    /// tool to drop anything. Negative can't be broken at all.
+    pub hardness: f32,
+    /// The kind of tool that breaks it faster
+    pub tool: Option<ToolKind>,
+    /// Breaking it drops nothing without a [`Block::tool`] of at least this tier
+    pub tier: Option<ToolTier>,
+    /// Light level it gives off, from 0 to 15
+    pub light: u8,
+    /// How much its faces glow past the light they're lit with, from 0 to 1. Glowing faces
+    /// bloom when that's on.
+    pub emissive: f32,
+    pub fluid: Option<Fluid>,
+    /// Shape for blocks that aren't a full cube, they have to be transparent
+    pub model: Option<Arc<BlockModel>>,
+    /// Which way it was placed, for blocks with a [`Placement`] rule
+    pub orientation: Option<Orientation>,
+    /// Biome color multiplied into each face in [`Face::ALL`] order, None keeps the texture's
+    /// own color
+    pub tints: [Option<Tint>; 6],
+    /// Shown in inventories as its texture laid flat instead of a 3D miniature, for thin
+    /// shapes like torches and crops
+    pub flat_icon: bool,
+    /// Names of the items it leaves behind when broken, None for its own item
+    pub drops: Option<Vec<String>>,
+    /// Least and most experience points breaking it leaves behind, only when it drops
+    /// anything
+    pub experience: [u32; 2],
+    /// What using it does instead of placing against it, None for nothing
+    pub interaction: Option<Interaction>,
+    /// Where it is in a circuit, None for blocks that don't take part in any
+    pub circuit: Option<Circuit>,
+    /// How far it has grown, for crops
+    pub crop: Option<Crop>,
+    /// Whether it's watered, for farmland that crops grow on
+    pub soil: Option<Soil>,
+    /// What a hoe turns it into, None if it can't be tilled
+    pub tilled: Option<Tilled>,
+    /// Whether it's open, for doors, trapdoors and gates
+    pub openable: Option<Openable>,
+    /// The other half of a block two high like a door, None for blocks of their own
+    pub pair: Option<Pair>,
+    /// What it sounds like to break, place and walk on
+    pub sound: SoundMaterial,
+}
+
+/// Which set of sounds a block makes, the `sound` of its file
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SoundMaterial {
+    #[default]
+    Stone,
+    Wood,
+    Grass,
+    Sand,
+}
+
+impl SoundMaterial {
+    /// As it appears in sound event names, like `block.stone.break`
+    pub fn name(self) -> &'static str {
+        match self {
+            SoundMaterial::Stone => "stone",
+            SoundMaterial::Wood => "wood",
+            SoundMaterial::Grass => "grass",
+            SoundMaterial::Sand => "sand",
+        }
+    }
+}
+
+/// What happens when the player uses a block, the `interaction` of its file
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interaction {
+    /// Opens a 3x3 crafting grid
+    Crafting,
+    /// Keeps a [`crate::world::block_entity::Chest`] of items
+    Chest,
+    /// Keeps a [`crate::world::block_entity::Furnace`] smelting items
+    Furnace,
+    /// Spends experience on modifiers for a tool, see [`crate::game::enchanting`]
+    Enchanting,
+    /// Lights it to blow up a few seconds later, see [`crate::game::explosion`]
+    Ignite,
+    /// Flips a lever or presses a button, see [`crate::world::circuit`]
+    Switch,
+    /// Lies down for the night and moves the player's spawn point, see
+    /// [`crate::game::sleeping`]
+    Sleep,
+    /// Keeps a [`crate::world::block_entity::Sign`] of text that using it edits
+    Sign,
+    /// Opens or closes a door, trapdoor or gate, see [`crate::game::doors`]
+    Open,
+}
+
+/// What a block does in a circuit, the `circuit` of its file. See [`crate::world::circuit`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitKind {
+    /// Carries power from block to block, one level weaker each block
+    Wire,
+    /// Powered or not, switched over by using it
+    Lever,
+    /// Powered for a moment after using it
+    Button,
+    /// Powered while something stands on it
+    PressurePlate,
+    /// Turns into its powered variant while powered, like a lamp lighting up
+    Receiver,
+    /// Pushes the blocks in front of it along while powered
+    Piston,
+}
+
+impl CircuitKind {
+    /// How many power levels it has a variant for, wire has every one and the rest are just
+    /// on or off
+    pub fn levels(self) -> u8 {
+        match self {
+            CircuitKind::Wire => MAX_POWER + 1,
+            _ => 2,
+        }
+    }
+
+    /// Which of its [`CircuitKind::levels`] `power` falls in
+    fn level(self, power: u8) -> u8 {
+        match self {
+            CircuitKind::Wire => power.min(MAX_POWER),
+            _ => (power > 0) as u8,
+        }
+    }
+
+    /// Power of the variant for `level`, on is full power
+    fn power(self, level: u8) -> u8 {
+        match self {
+            CircuitKind::Wire => level,
+            _ => level * MAX_POWER,
+        }
+    }
+
+    /// Gives power off by itself rather than passing it on or reacting to it
+    pub fn is_source(self) -> bool {
+        matches!(
+            self,
+            CircuitKind::Lever | CircuitKind::Button | CircuitKind::PressurePlate
+        )
+    }
+}
+
+/// Which power variant of a circuit block this is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Circuit {
+    pub kind: CircuitKind,
+    /// From 0 to [`MAX_POWER`]
+    pub power: u8,
+    /// The unpowered variant, its first orientation for oriented blocks
+    pub base: BlockId,
+    /// Ids from each power level's variants to the next, one for each orientation
+    pub stride: u16,
+}
+
+/// Which of the biome's colors a face takes on, see [`crate::world::biome::tint_colors`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tint {
+    Grass,
+    Foliage,
+}
+
+/// The box of a full block, for blocks without a model
+pub const FULL_BLOCK: [([f32; 3], [f32; 3]); 1] = [([0.0; 3], [1.0; 3])];
+
+impl Block {
+    pub fn texture(&self, face: Face) -> &str {
+        // Variants are declared in the same order as ALL
+        &self.textures[face as usize]
+    }
+
+    /// Min and max corners of the boxes making up the block, in block units
+    pub fn boxes(&self) -> &[([f32; 3], [f32; 3])] {
+        self.model
+            .as_ref()
+            .map_or(&FULL_BLOCK, |model| &model.boxes)
+    }
+}
+
+/// How a block picks its orientation when placed, the `placement` of its file. Each
+/// orientation is a block of its own, see [`BlockRegistry`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Placement {
+    /// Always the same way up
+    #[default]
+    Fixed,
+    /// Lies along the axis of the clicked face (logs)
+    Axis,
+    /// Turned to face the way the player looks (stairs)
+    Horizontal,
+    /// Stands on the floor or hangs on the clicked wall, can't go on ceilings (torches)
+    Wall,
+    /// Points away from the clicked face, any of the six ways (pistons)
+    Facing,
+}
+
+impl Placement {
+    /// Facing of each variant in id order, the first one is the block as its file describes
+    /// it. Axis variants face the positive end of their axis.
+    pub fn facings(self) -> &'static [Face] {
+        match self {
+            Placement::Fixed => &[],
+            Placement::Axis => &[Face::Up, Face::East, Face::South],
+            Placement::Horizontal => &[Face::South, Face::West, Face::North, Face::East],
+            Placement::Wall => &[Face::Up, Face::South, Face::West, Face::North, Face::East],
+            Placement::Facing => &[
+                Face::Up,
+                Face::Down,
+                Face::South,
+                Face::West,
+                Face::North,
+                Face::East,
+            ],
+        }
+    }
+
+    /// How the variant facing `facing` is turned from the first one
+    fn rotation(self, facing: Face) -> Rotation {
+        match (self, facing) {
+            (Placement::Axis, Face::East) => Rotation::UP_TO_EAST,
+            (Placement::Axis, Face::South) => Rotation::UP_TO_SOUTH,
+            (Placement::Horizontal | Placement::Wall, facing) => {
+                let horizontal = [Face::South, Face::West, Face::North, Face::East];
+                let turns = horizontal.iter().position(|face| *face == facing);
+                Rotation::about_y(turns.unwrap_or(0) as u32)
+            }
+            (Placement::Facing, Face::Down) => Rotation::UP_TO_SOUTH.then(Rotation::UP_TO_SOUTH),
+            (Placement::Facing, Face::South) => Rotation::UP_TO_SOUTH,
+            (Placement::Facing, Face::East) => Rotation::UP_TO_EAST,
+            (Placement::Facing, Face::North) => Rotation::UP_TO_SOUTH.then(Rotation::about_y(2)),
+            (Placement::Facing, Face::West) => Rotation::UP_TO_EAST.then(Rotation::about_y(2)),
+            _ => Rotation::IDENTITY,
+        }
+    }
+}
+
+/// Which growth stage of a crop this is, see [`crate::game::farming`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Crop {
+    /// From 0 just planted to one less than `stages` ripe
+    pub stage: u8,
+    pub stages: u8,
+    /// The first stage, shared by all of them
+    pub base: BlockId,
+    /// Least light it grows in, from the sky or from blocks
+    pub light: u8,
+}
+
+impl Crop {
+    pub fn is_ripe(&self) -> bool {
+        self.stage + 1 == self.stages
+    }
+}
+
+/// Which variant of farmland this is, crops grow faster on moist farmland
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Soil {
+    pub moist: bool,
+    /// The dry variant
+    pub base: BlockId,
+}
+
+/// Which variant of a door, trapdoor or gate this is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Openable {
+    pub open: bool,
+    /// The closed variant, its first orientation for oriented blocks
+    pub base: BlockId,
+    /// Ids from the closed variants to the open ones, one for each orientation
+    pub stride: u16,
+}
+
+/// The other half of a block two high, the `pair` section of its file. Both halves are
+/// placed, opened and broken together, see [`crate::game::doors`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pair {
+    /// Name of the block the other half is
+    pub with: String,
+    /// Whether the other half goes on top of this one rather than under it
+    pub above: bool,
+}
+
+/// What tilling a block with a hoe does, the `tilled` section of its file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tilled {
+    /// Name of the block it turns into
+    pub into: String,
+    /// Names of items it might turn up, one of each
+    #[serde(default)]
+    pub drops: Vec<String>,
+    /// Chance from 0 to 1 that it turns up `drops`
+    #[serde(default)]
+    pub chance: f64,
+}
+
+/// Which variant of an oriented block this is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Orientation {
+    /// The first variant, shared by all of them
+    pub base: BlockId,
+    pub placement: Placement,
+    /// One of [`Placement::facings`]. For wall blocks it points away from the wall.
+    pub facing: Face,
+}
+
+/// How a fluid behaves, the `fluid` section of its source block's file
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FluidProperties {
+    /// Blocks it spreads sideways from a source
+    pub reach: u8,
+    /// Ticks between one block of flow and the next
+    pub delay: u64,
+    /// Fraction of the usual speed entities move at inside it
+    pub speed: f32,
+    /// Flowing blocks between two sources become a source themselves
+    pub renewable: bool,
+    /// What the view is tinted and fogged with while the camera is inside it
+    pub fog_color: [f32; 3],
+    /// Blocks that can be seen through it
+    pub fog_distance: f32,
+    /// Hit points it takes off whatever is in it, as often as that can be hurt
+    #[serde(default)]
+    pub damage: u32,
+}
+
+/// Where a fluid block is in its flow
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Fluid {
+    /// Shared by every level of the same fluid
+    pub source: BlockId,
+    /// 0 for a source or falling fluid, one more for each block it spread sideways
+    pub level: u8,
+    /// Pouring down from the fluid above it
+    pub falling: bool,
+    pub properties: FluidProperties,
+}
+
+impl Fluid {
+    pub fn is_source(&self) -> bool {
+        self.level == 0 && !self.falling
+    }
+}
+
+/// Contents of `blocks/<name>.json`
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BlockFile {
+    /// Keys are `all`, `side`, `top`, `bottom` or a face name, the most specific one wins
+    textures: HashMap<String, String>,
+    /// Same keys as `textures`, faces without one aren't tinted
+    tint: HashMap<String, Tint>,
+    solid: bool,
+    transparent: bool,
+    translucent: bool,
+    hardness: f32,
+    tool: Option<ToolKind>,
+    /// Needs `tool` as well
+    tier: Option<ToolTier>,
+    light: u8,
+    emissive: f32,
+    fluid: Option<FluidProperties>,
+    /// Name of a file in `models/`, a full cube if left out
+    model: Option<String>,
+    placement: Placement,
+    /// Model of the variants of a wall block that hang on a wall, facing south. The same as
+    /// `model` if left out.
+    wall_model: Option<String>,
+    flat_icon: bool,
+    /// Empty drops nothing
+    drops: Option<Vec<String>>,
+    experience: [u32; 2],
+    interaction: Option<Interaction>,
+    circuit: Option<CircuitKind>,
+    /// What's different about its powered variants, they look the same if left out
+    powered: Option<PoweredFile>,
+    /// Makes it a crop that grows through these stages, its own `drops` are for the ones
+    /// that aren't ripe yet
+    crop: Option<CropFile>,
+    /// Makes it farmland, with a moist variant
+    soil: Option<SoilFile>,
+    tilled: Option<Tilled>,
+    /// Makes it a door, trapdoor or gate that using it opens, this is what's different about
+    /// its open variants
+    open: Option<OpenFile>,
+    pair: Option<Pair>,
+    sound: SoundMaterial,
+}
+
+/// The `crop` section of a crop's file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CropFile {
+    /// Texture of each stage in order, in place of `all`
+    stages: Vec<String>,
+    light: u8,
+    /// Names of the items the ripe stage leaves behind
+    harvest: Vec<String>,
+}
+
+/// The `soil` section of farmland's file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SoilFile {
+    /// Added over the block's own `textures` for the moist variant
+    moist_textures: HashMap<String, String>,
+}
+
+/// The `powered` section of a circuit block's file, anything left out is the same as
+/// unpowered
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PoweredFile {
+    /// Added over the block's own `textures`
+    textures: HashMap<String, String>,
+    solid: Option<bool>,
+    light: Option<u8>,
+    emissive: Option<f32>,
+    model: Option<String>,
+    wall_model: Option<String>,
+}
+
+/// The `open` section of a door's file, anything left out is the same as closed
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OpenFile {
+    /// Added over the block's own `textures`
+    textures: HashMap<String, String>,
+    solid: Option<bool>,
+    model: Option<String>,
+}
+
+impl Default for BlockFile {
+    fn default() -> Self {
+        BlockFile {
+            textures: HashMap::new(),
+            tint: HashMap::new(),
+            solid: true,
+            transparent: false,
+            translucent: false,
+            hardness: 1.0,
+            tool: None,
+            tier: None,
+            light: 0,
+            emissive: 0.0,
+            fluid: None,
+            model: None,
+            placement: Placement::Fixed,
+            wall_model: None,
+            flat_icon: false,
+            drops: None,
+            experience: [0, 0],
+            interaction: None,
+            circuit: None,
+            powered: None,
+            crop: None,
+            soil: None,
+            tilled: None,
+            open: None,
+            pair: None,
+            sound: SoundMaterial::Stone,
+        }
+    }
+}
+
+/// A block file turned into the block and what the registry needs to add its variants
+struct BlockDefinition {
+    block: Block,
+    fluid: Option<FluidProperties>,
+    placement: Placement,
+    wall_model: Option<Arc<BlockModel>>,
+    circuit: Option<CircuitKind>,
+    /// The block and its wall model as its powered variants are, for circuit blocks
+    powered: Option<(Block, Option<Arc<BlockModel>>)>,
+    /// Every stage in order for crops, the first is `block`
+    stages: Vec<Block>,
+    /// The moist variant of farmland
+    moist: Option<Block>,
+    /// The block as its open variants are, for doors and the like
+    open: Option<Block>,
+}
+
+/// Maps block ids to their definitions, loaded from `blocks/` in the resource packs. Ids
+/// follow the order of `blocks/index.json`. Saves store block names, so the order is free to
+/// change but renaming a block needs a migration in the chunk format.
+///
+/// A fluid is registered as its source block followed by `<name>_flowing_<level>` for each
+/// level it can spread to and then `<name>_falling`. A block with a [`Placement`] rule is
+/// registered as its first facing followed by `<name>_<facing>` for each other one. A
+/// circuit block is registered unpowered and then as `<name>_powered`, or `<name>_<power>`
+/// for each power level of wire, with every orientation of each. A crop is registered as its
+/// first stage and then `<name>_<stage>` for each other one, and farmland as dry and then
+/// `<name>_moist`. A door, trapdoor or gate is registered closed and then as `<name>_open`,
+/// with every orientation of each.
+#[derive(Debug)]
+pub struct BlockRegistry {
+    blocks: Vec<Block>,
+    ids: HashMap<String, BlockId>,
+}
+
+impl BlockRegistry {
+    pub fn load(assets: &Assets) -> anyhow::Result<Self> {
+        let index = assets.read(INDEX_PATH)?;
+        let names: Vec<String> = serde_json::from_slice(&index)
+            .with_context(|| format!("Failed to parse {INDEX_PATH}"))?;
+
+        let mut registry = BlockRegistry {
+            blocks: Vec::new(),
+            ids: HashMap::new(),
+        };
+        registry.insert(Block {
+            name: String::from("air"),
+            textures: Default::default(),
+            solid: false,
+            transparent: true,
+            translucent: false,
+            hardness: 0.0,
+            tool: None,
+            tier: None,
+            light: 0,
+            emissive: 0.0,
+            fluid: None,
+            model: None,
+            orientation: None,
+            tints: [None; 6],
+            flat_icon: false,
+            drops: None,
+            experience: [0, 0],
+            interaction: None,
+            circuit: None,
+            crop: None,
+            soil: None,
+            tilled: None,
+            openable: None,
+            pair: None,
+            sound: SoundMaterial::Stone,
+        })?;
+        for name in names {
+            let definition = load_block(assets, &name)?;
+            match (definition.fluid, definition.placement) {
+                (Some(properties), _) => registry.insert_fluid(definition.block, properties)?,
+                _ if definition.circuit.is_some() => registry.insert_circuit(definition)?,
+                _ if !definition.stages.is_empty() => registry.insert_crop(definition.stages)?,
+                _ if definition.moist.is_some() => registry.insert_soil(definition)?,
+                _ if definition.open.is_some() => registry.insert_openable(definition)?,
+                (None, Placement::Fixed) => registry.insert(definition.block)?,
+                (None, placement) => {
+                    registry.insert_oriented(definition.block, placement, definition.wall_model)?
+                }
+            }
+        }
+        for block in &registry.blocks {
+            if let Some(pair) = &block.pair {
+                if registry.id(&pair.with).is_none() {
+                    bail!(
+                        "The other half of {} is {}, which isn't a block",
+                        block.name,
+                        pair.with
+                    );
+                }
+            }
+        }
+        Ok(registry)
+    }
+
+    /// None for ids that no block uses, e.g. from a save made with more blocks
+    pub fn get(&self, id: BlockId) -> Option<&Block> {
+        self.blocks.get(id.0 as usize)
+    }
+
+    pub fn id(&self, name: &str) -> Option<BlockId> {
+        self.ids.get(name).copied()
+    }
+
+    /// The block for `level` of the fluid whose source is `source`, None if it doesn't spread
+    /// that far or isn't a fluid
+    pub fn fluid_block(&self, source: BlockId, level: u8, falling: bool) -> Option<BlockId> {
+        let reach = self.get(source)?.fluid?.properties.reach;
+        let offset = match (falling, level) {
+            (true, _) => reach + 1,
+            (false, level) if level <= reach => level,
+            (false, _) => return None,
+        };
+        Some(BlockId(source.0 + u16::from(offset)))
+    }
+
+    /// The variant of an oriented block facing `facing`, None if it has no such variant
+    pub fn oriented(&self, block: BlockId, facing: Face) -> Option<BlockId> {
+        let orientation = self.get(block)?.orientation?;
+        let index = orientation
+            .placement
+            .facings()
+            .iter()
+            .position(|candidate| *candidate == facing)?;
+        Some(BlockId(orientation.base.0 + index as u16))
+    }
+
+    /// First variant of an oriented block, unpowered for circuit blocks, the first stage of
+    /// crops and dry farmland, the block itself for anything else
+    pub fn base(&self, block: BlockId) -> BlockId {
+        let unpowered = self
+            .powered(block, 0)
+            .or_else(|| self.grown(block, 0))
+            .or_else(|| self.moistened(block, false))
+            .or_else(|| self.opened(block, false))
+            .unwrap_or(block);
+        self.get(unpowered)
+            .and_then(|block| block.orientation)
+            .map_or(unpowered, |orientation| orientation.base)
+    }
+
+    /// The variant of a circuit block with `power`, turned the same way. Blocks that are only
+    /// on or off are on for any power at all. None if it isn't a circuit block.
+    pub fn powered(&self, block: BlockId, power: u8) -> Option<BlockId> {
+        let circuit = self.get(block)?.circuit?;
+        let current = circuit.kind.level(circuit.power) as u16 * circuit.stride;
+        let orientation = block.0 - circuit.base.0 - current;
+        let level = circuit.kind.level(power) as u16;
+        Some(BlockId(
+            circuit.base.0 + level * circuit.stride + orientation,
+        ))
+    }
+
+    /// `stage` of the crop `block` is a stage of, None if it isn't a crop or doesn't have
+    /// that many stages
+    pub fn grown(&self, block: BlockId, stage: u8) -> Option<BlockId> {
+        let crop = self.get(block)?.crop?;
+        (stage < crop.stages).then(|| BlockId(crop.base.0 + u16::from(stage)))
+    }
+
+    /// The moist or dry variant of farmland, None if it isn't farmland
+    pub fn moistened(&self, block: BlockId, moist: bool) -> Option<BlockId> {
+        let soil = self.get(block)?.soil?;
+        Some(BlockId(soil.base.0 + u16::from(moist)))
+    }
+
+    /// The open or closed variant of a door, trapdoor or gate, turned the same way. None if
+    /// it doesn't open.
+    pub fn opened(&self, block: BlockId, open: bool) -> Option<BlockId> {
+        let openable = self.get(block)?.openable?;
+        let orientation = (block.0 - openable.base.0) % openable.stride;
+        Some(BlockId(
+            openable.base.0 + u16::from(open) * openable.stride + orientation,
+        ))
+    }
+
+    /// The variant of `block` turned by `rotation`, the same block if it isn't oriented.
+    /// Blocks that lie along an axis only have one facing for each, the opposite one stands
+    /// in for the other.
+    pub fn rotated(&self, block: BlockId, rotation: Rotation) -> BlockId {
+        let Some(orientation) = self.get(block).and_then(|block| block.orientation) else {
+            return block;
+        };
+        let facing = rotation.face(orientation.facing);
+        self.oriented(block, facing)
+            .or_else(|| self.oriented(block, facing.opposite()))
+            .unwrap_or(block)
+    }
+
+    /// Which variant of `block` to place against the `clicked` face of another block while
+    /// looking towards `looking`, None if it can't go there
+    pub fn placed(&self, block: BlockId, clicked: Face, looking: Face) -> Option<BlockId> {
+        let Some(orientation) = self.get(block)?.orientation else {
+            return Some(block);
+        };
+        let facing = match (orientation.placement, clicked) {
+            (Placement::Fixed, _) => return Some(block),
+            (Placement::Axis, Face::Up | Face::Down) => Face::Up,
+            (Placement::Axis, Face::East | Face::West) => Face::East,
+            (Placement::Axis, Face::South | Face::North) => Face::South,
+            (Placement::Horizontal, _) => looking,
+            (Placement::Wall, Face::Down) => return None,
+            (Placement::Wall, clicked) => clicked,
+            (Placement::Facing, clicked) => clicked,
+        };
+        self.oriented(block, facing)
+    }
+
+    /// Every block in id order, air included
+    pub fn iter(&self) -> impl Iterator<Item = (BlockId, &Block)> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .map(|(id, block)| (BlockId(id as u16), block))
+    }
+
+    fn insert(&mut self, block: Block) -> anyhow::Result<()> {
+        let Ok(id) = u16::try_from(self.blocks.len()) else {
+            bail!("Too many blocks");
+        };
+        if self.ids.insert(block.name.clone(), BlockId(id)).is_some() {
+            bail!("Block {} is listed twice in {INDEX_PATH}", block.name);
+        }
+        self.blocks.push(block);
+        Ok(())
+    }
+
+    fn insert_fluid(&mut self, block: Block, properties: FluidProperties) -> anyhow::Result<()> {
+        let source = BlockId(self.blocks.len() as u16);
+        let variant = |name: String, level: u8, falling: bool| Block {
+            name,
+            fluid: Some(Fluid {
+                source,
+                level,
+                falling,
+                properties,
+            }),
+            ..block.clone()
+        };
+        self.insert(variant(block.name.clone(), 0, false))?;
+        for level in 1..=properties.reach {
+            self.insert(variant(
+                format!("{}_flowing_{level}", block.name),
+                level,
+                false,
+            ))?;
+        }
+        self.insert(variant(format!("{}_falling", block.name), 0, true))
+    }
+
+    /// Every power level of a circuit block, each one turned every way its placement allows
+    fn insert_circuit(&mut self, definition: BlockDefinition) -> anyhow::Result<()> {
+        let Some(kind) = definition.circuit else {
+            return self.insert(definition.block);
+        };
+        let base = BlockId(self.blocks.len() as u16);
+        let stride = definition.placement.facings().len().max(1) as u16;
+        let name = definition.block.name.clone();
+        for level in 0..kind.levels() {
+            let power = kind.power(level);
+            let (block, wall_model) = match &definition.powered {
+                Some(powered) if power > 0 => powered.clone(),
+                _ => (definition.block.clone(), definition.wall_model.clone()),
+            };
+            let name = match (power, kind.levels()) {
+                (0, _) => name.clone(),
+                (_, 2) => format!("{name}_powered"),
+                (power, _) => format!("{name}_{power}"),
+            };
+            let block = Block {
+                name,
+                circuit: Some(Circuit {
+                    kind,
+                    power,
+                    base,
+                    stride,
+                }),
+                ..block
+            };
+            match definition.placement {
+                Placement::Fixed => self.insert(block)?,
+                placement => self.insert_oriented(block, placement, wall_model)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn insert_crop(&mut self, stages: Vec<Block>) -> anyhow::Result<()> {
+        let base = BlockId(self.blocks.len() as u16);
+        for block in stages {
+            self.insert(Block {
+                crop: block.crop.map(|crop| Crop { base, ..crop }),
+                ..block
+            })?;
+        }
+        Ok(())
+    }
+
+    fn insert_soil(&mut self, definition: BlockDefinition) -> anyhow::Result<()> {
+        let base = BlockId(self.blocks.len() as u16);
+        let variants = std::iter::once(definition.block).chain(definition.moist);
+        for (moist, block) in variants.enumerate() {
+            self.insert(Block {
+                soil: Some(Soil {
+                    moist: moist == 1,
+                    base,
+                }),
+                ..block
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Closed and then open, each turned every way its placement allows
+    fn insert_openable(&mut self, definition: BlockDefinition) -> anyhow::Result<()> {
+        let base = BlockId(self.blocks.len() as u16);
+        let stride = definition.placement.facings().len().max(1) as u16;
+        let open = definition.open.map(|block| Block {
+            name: format!("{}_open", block.name),
+            ..block
+        });
+        for (index, block) in std::iter::once(definition.block).chain(open).enumerate() {
+            let block = Block {
+                openable: Some(Openable {
+                    open: index == 1,
+                    base,
+                    stride,
+                }),
+                ..block
+            };
+            match definition.placement {
+                Placement::Fixed => self.insert(block)?,
+                placement => {
+                    self.insert_oriented(block, placement, definition.wall_model.clone())?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `block` as it's described and then turned to each other facing of `placement`.
+    /// Models are rotated with the block, full cubes just move their textures around.
+    fn insert_oriented(
+        &mut self,
+        block: Block,
+        placement: Placement,
+        wall_model: Option<Arc<BlockModel>>,
+    ) -> anyhow::Result<()> {
+        let base = BlockId(self.blocks.len() as u16);
+        for (index, facing) in placement.facings().iter().enumerate() {
+            let model = if placement == Placement::Wall && *facing != Face::Up {
+                wall_model.as_ref().or(block.model.as_ref())
+            } else {
+                block.model.as_ref()
+            };
+            let rotation = placement.rotation(*facing);
+            let mut textures = block.textures.clone();
+            let mut tints = block.tints;
+            for face in Face::ALL {
+                let turned = rotation.face(face) as usize;
+                textures[turned] = block.texture(face).to_owned();
+                tints[turned] = block.tints[face as usize];
+            }
+            let name = match index {
+                0 => block.name.clone(),
+                _ => format!("{}_{}", block.name, facing.name()),
+            };
+            self.insert(Block {
+                name,
+                textures,
+                tints,
+                model: model.map(|model| Arc::new(model.rotated(rotation))),
+                orientation: Some(Orientation {
+                    base,
+                    placement,
+                    facing: *facing,
+                }),
+                ..block.clone()
+            })?;
+        }
+        Ok(())
+    }
+}
+
+fn load_block(assets: &Assets, name: &str) -> anyhow::Result<BlockDefinition> {
+    let path = format!("blocks/{name}.json");
+    let bytes = assets.read(&path)?;
+    let file: BlockFile =
+        serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {path}"))?;
+    if file.light > 15 {
+        bail!("{path}: light goes up to 15");
+    }
+    if !(0.0..=1.0).contains(&file.emissive) {
+        bail!("{path}: emissive goes from 0 to 1");
+    }
+    if file.model.is_some() && !file.transparent {
+        bail!("{path}: a block with a model has to be transparent");
+    }
+    if file.translucent && !file.transparent {
+        bail!("{path}: a translucent block has to be transparent too");
+    }
+    if file.fluid.is_some() && file.placement != Placement::Fixed {
+        bail!("{path}: fluids can't be placed facing different ways");
+    }
+    if file.tier.is_some() && file.tool.is_none() {
+        bail!("{path}: a tier only means something with a tool");
+    }
+    if file.wall_model.is_some() && file.placement != Placement::Wall {
+        bail!("{path}: only blocks with wall placement have a wall model");
+    }
+    if file.circuit.is_some() && file.fluid.is_some() {
+        bail!("{path}: fluids can't be part of a circuit");
+    }
+    if file.powered.is_some() && file.circuit.is_none() {
+        bail!("{path}: only circuit blocks have a powered variant");
+    }
+    let switched = matches!(file.circuit, Some(CircuitKind::Lever | CircuitKind::Button));
+    if file.interaction == Some(Interaction::Switch) && !switched {
+        bail!("{path}: only levers and buttons can be switched");
+    }
+    if let Some(powered) = &file.powered {
+        if powered.light.is_some_and(|light| light > 15) {
+            bail!("{path}: light goes up to 15");
+        }
+        if powered.model.is_some() && !file.transparent {
+            bail!("{path}: a block with a model has to be transparent");
+        }
+        if powered.wall_model.is_some() && file.placement != Placement::Wall {
+            bail!("{path}: only blocks with wall placement have a wall model");
+        }
+        if powered
+            .emissive
+            .is_some_and(|emissive| !(0.0..=1.0).contains(&emissive))
+        {
+            bail!("{path}: emissive goes from 0 to 1");
+        }
+    }
+    if let Some(crop) = &file.crop {
+        if file.placement != Placement::Fixed || file.fluid.is_some() || file.circuit.is_some() {
+            bail!("{path}: crops can't be turned, fluids or part of a circuit");
+        }
+        if crop.stages.len() < 2 || crop.stages.len() > u8::MAX as usize {
+            bail!("{path}: a crop has from 2 to 255 stages");
+        }
+        if crop.light > 15 {
+            bail!("{path}: light goes up to 15");
+        }
+    }
+    if file.soil.is_some()
+        && (file.placement != Placement::Fixed
+            || file.fluid.is_some()
+            || file.circuit.is_some()
+            || file.crop.is_some())
+    {
+        bail!("{path}: farmland can't be turned, fluids, crops or part of a circuit");
+    }
+    if file.open.is_some()
+        && (file.fluid.is_some()
+            || file.circuit.is_some()
+            || file.crop.is_some()
+            || file.soil.is_some())
+    {
+        bail!("{path}: doors can't be fluids, crops, farmland or part of a circuit");
+    }
+    if file.open.is_some() != (file.interaction == Some(Interaction::Open)) {
+        bail!("{path}: blocks with an open variant are opened by using them, and only those");
+    }
+    if file
+        .open
+        .as_ref()
+        .is_some_and(|open| open.model.is_some() && !file.transparent)
+    {
+        bail!("{path}: a block with a model has to be transparent");
+    }
+    if file.pair.as_ref().is_some_and(|pair| pair.with == name) {
+        bail!("{path}: the other half has to be a block of its own");
+    }
+    if file
+        .tilled
+        .as_ref()
+        .is_some_and(|tilled| !(0.0..=1.0).contains(&tilled.chance))
+    {
+        bail!("{path}: the chance of tilling something up goes from 0 to 1");
+    }
+    if file.experience[0] > file.experience[1] {
+        bail!("{path}: the experience has to go from small to large");
+    }
+    if let Some(fluid) = file.fluid {
+        if !(1..=15).contains(&fluid.reach) || fluid.delay == 0 {
+            bail!("{path}: a fluid's reach goes from 1 to 15 and its delay starts at 1");
+        }
+        if fluid.fog_distance <= 0.0 {
+            bail!("{path}: a fluid's fog distance has to be positive");
+        }
+    }
+
+    // The most specific key for each face goes first
+    let keys = Face::ALL.map(|face| match face {
+        Face::East => ["east", "side", "all"],
+        Face::West => ["west", "side", "all"],
+        Face::Up => ["up", "top", "all"],
+        Face::Down => ["down", "bottom", "all"],
+        Face::South => ["south", "side", "all"],
+        Face::North => ["north", "side", "all"],
+    });
+    let face_textures = |textures: &HashMap<String, String>| -> anyhow::Result<[String; 6]> {
+        let found = keys.map(|keys| keys.iter().find_map(|key| textures.get(*key)).cloned());
+        if found.iter().any(Option::is_none) {
+            bail!("{path}: every face needs a texture, \"all\" covers the rest");
+        }
+        Ok(found.map(Option::unwrap_or_default))
+    };
+    let tints = keys.map(|keys| keys.iter().find_map(|key| file.tint.get(*key)).copied());
+    let load_model =
+        |model: &Option<String>, textures: &HashMap<String, String>| -> anyhow::Result<_> {
+            let Some(model) = model else {
+                return Ok(None);
+            };
+            let texture = |key: &str| textures.get(key).or(textures.get("all")).cloned();
+            let model = BlockModel::load(assets, model, texture)
+                .with_context(|| format!("Failed to load the model of {name}"))?;
+            Ok(Some(Arc::new(model)))
+        };
+    let model = load_model(&file.model, &file.textures)?;
+    let wall_model = load_model(&file.wall_model, &file.textures)?;
+
+    let block = Block {
+        name: name.to_owned(),
+        textures: face_textures(&file.textures)?,
+        solid: file.solid,
+        transparent: file.transparent,
+        translucent: file.translucent,
+        hardness: file.hardness,
+        tool: file.tool,
+        tier: file.tier,
+        light: file.light,
+        emissive: file.emissive,
+        fluid: None,
+        model,
+        orientation: None,
+        tints,
+        flat_icon: file.flat_icon,
+        drops: file.drops,
+        experience: file.experience,
+        interaction: file.interaction,
+        circuit: None,
+        crop: None,
+        soil: None,
+        tilled: file.tilled,
+        openable: None,
+        pair: file.pair,
+        sound: file.sound,
+    };
+    let powered = match file.powered {
+        Some(powered) => {
+            let mut textures = file.textures.clone();
+            textures.extend(powered.textures);
+            let model = match powered.model {
+                Some(model) => load_model(&Some(model), &textures)?,
+                None => load_model(&file.model, &textures)?,
+            };
+            let wall_model = match powered.wall_model {
+                Some(model) => load_model(&Some(model), &textures)?,
+                None => load_model(&file.wall_model, &textures)?,
+            };
+            let block = Block {
+                textures: face_textures(&textures)?,
+                solid: powered.solid.unwrap_or(block.solid),
+                light: powered.light.unwrap_or(block.light),
+                emissive: powered.emissive.unwrap_or(block.emissive),
+                model,
+                ..block.clone()
+            };
+            Some((block, wall_model))
+        }
+        None => None,
+    };
+    let mut stages = Vec::new();
+    if let Some(crop) = &file.crop {
+        let count = crop.stages.len() as u8;
+        for (stage, texture) in crop.stages.iter().enumerate() {
+            let mut textures = file.textures.clone();
+            textures.insert(String::from("all"), texture.clone());
+            let ripe = stage + 1 == crop.stages.len();
+            stages.push(Block {
+                name: match stage {
+                    0 => name.to_owned(),
+                    stage => format!("{name}_{stage}"),
+                },
+                textures: face_textures(&textures)?,
+                model: load_model(&file.model, &textures)?,
+                drops: if ripe {
+                    Some(crop.harvest.clone())
+                } else {
+                    block.drops.clone()
+                },
+                crop: Some(Crop {
+                    stage: stage as u8,
+                    stages: count,
+                    base: BlockId::AIR,
+                    light: crop.light,
+                }),
+                ..block.clone()
+            });
+        }
+    }
+    let moist = match &file.soil {
+        Some(soil) => {
+            let mut textures = file.textures.clone();
+            textures.extend(soil.moist_textures.clone());
+            Some(Block {
+                name: format!("{name}_moist"),
+                textures: face_textures(&textures)?,
+                model: load_model(&file.model, &textures)?,
+                ..block.clone()
+            })
+        }
+        None => None,
+    };
+    let open = match file.open {
+        Some(open) => {
+            let mut textures = file.textures.clone();
+            textures.extend(open.textures);
+            let model = match open.model {
+                Some(model) => load_model(&Some(model), &textures)?,
+                None => load_model(&file.model, &textures)?,
+            };
+            Some(Block {
+                textures: face_textures(&textures)?,
+                solid: open.solid.unwrap_or(block.solid),
+                model,
+                ..block.clone()
+            })
+        }
+        None => None,
+    };
+    Ok(BlockDefinition {
+        block,
+        fluid: file.fluid,
+        placement: file.placement,
+        wall_model,
+        circuit: file.circuit,
+        powered,
+        stages,
+        moist,
+        open,
+    })
+}
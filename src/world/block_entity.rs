@@ -0,0 +1,157 @@
+use super::block::Interaction;
+use crate::game::inventory::ItemStack;
+
+/// Three rows like the inventory's main ones
+pub const CHEST_SLOTS: usize = 27;
+/// Indices into [`Furnace::slots`]
+pub const FURNACE_INPUT: usize = 0;
+pub const FURNACE_FUEL: usize = 1;
+pub const FURNACE_OUTPUT: usize = 2;
+/// Ticks to smelt one item, ten seconds
+pub const SMELT_TICKS: u32 = 200;
+/// Lines of text on a sign and characters on each
+pub const SIGN_LINES: usize = 4;
+pub const SIGN_LINE_LENGTH: usize = 15;
+
+/// State a block keeps beyond its id, stored and saved with its chunk. Which blocks get one
+/// comes from their [`Interaction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockEntity {
+    /// Boxed, it's a lot bigger than the rest
+    Chest(Box<Chest>),
+    Furnace(Furnace),
+    Sign(Sign),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chest {
+    pub slots: [Option<ItemStack>; CHEST_SLOTS],
+}
+
+/// Burns fuel to smelt its input into its output, see [`crate::game::smelting`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Furnace {
+    /// The input, the fuel and the output
+    pub slots: [Option<ItemStack>; 3],
+    /// Ticks until the fuel burning now runs out, 0 when nothing is burning
+    pub burn_left: u32,
+    /// How long the fuel burning now lasts in all
+    pub burn_total: u32,
+    /// Ticks spent smelting the input so far, up to [`SMELT_TICKS`]
+    pub progress: u32,
+}
+
+/// Text written on a sign, see [`crate::render::signs`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sign {
+    pub lines: [String; SIGN_LINES],
+}
+
+impl Furnace {
+    pub fn is_burning(&self) -> bool {
+        self.burn_left > 0
+    }
+
+    /// How much of the burning fuel is left, from 0 to 1
+    pub fn fuel_fraction(&self) -> f32 {
+        match self.burn_total {
+            0 => 0.0,
+            total => self.burn_left as f32 / total as f32,
+        }
+    }
+
+    /// How far the item being smelted is, from 0 to 1
+    pub fn progress_fraction(&self) -> f32 {
+        self.progress as f32 / SMELT_TICKS as f32
+    }
+}
+
+impl BlockEntity {
+    /// A new empty one for a block that was just placed, None if the block doesn't keep any
+    /// state
+    pub fn new(interaction: Interaction) -> Option<Self> {
+        match interaction {
+            Interaction::Crafting
+            | Interaction::Enchanting
+            | Interaction::Ignite
+            | Interaction::Switch
+            | Interaction::Sleep
+            | Interaction::Open => None,
+            Interaction::Chest => Some(BlockEntity::Chest(Box::new(Chest {
+                slots: [None; CHEST_SLOTS],
+            }))),
+            Interaction::Furnace => Some(BlockEntity::Furnace(Furnace::default())),
+            Interaction::Sign => Some(BlockEntity::Sign(Sign::default())),
+        }
+    }
+
+    /// What saves call it
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BlockEntity::Chest(_) => "chest",
+            BlockEntity::Furnace(_) => "furnace",
+            BlockEntity::Sign(_) => "sign",
+        }
+    }
+
+    /// An empty one of the `kind` a save names, with the numbers [`BlockEntity::state`] gave.
+    /// Its slots and text get filled in afterwards.
+    /// None for kinds that no longer exist.
+    pub fn load(kind: &str, state: &[u32]) -> Option<Self> {
+        match kind {
+            "chest" => BlockEntity::new(Interaction::Chest),
+            "sign" => BlockEntity::new(Interaction::Sign),
+            "furnace" => {
+                let number = |index: usize| state.get(index).copied().unwrap_or(0);
+                Some(BlockEntity::Furnace(Furnace {
+                    slots: [None; 3],
+                    burn_left: number(0),
+                    burn_total: number(1),
+                    progress: number(2).min(SMELT_TICKS),
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Everything but the slots as numbers for saving
+    pub fn state(&self) -> Vec<u32> {
+        match self {
+            BlockEntity::Chest(_) | BlockEntity::Sign(_) => Vec::new(),
+            BlockEntity::Furnace(furnace) => {
+                vec![furnace.burn_left, furnace.burn_total, furnace.progress]
+            }
+        }
+    }
+
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        match self {
+            BlockEntity::Chest(chest) => &chest.slots,
+            BlockEntity::Furnace(furnace) => &furnace.slots,
+            BlockEntity::Sign(_) => &[],
+        }
+    }
+
+    pub fn slots_mut(&mut self) -> &mut [Option<ItemStack>] {
+        match self {
+            BlockEntity::Chest(chest) => &mut chest.slots,
+            BlockEntity::Furnace(furnace) => &mut furnace.slots,
+            BlockEntity::Sign(_) => &mut [],
+        }
+    }
+
+    /// Lines of text written on it, none for anything but signs
+    pub fn text(&self) -> &[String] {
+        match self {
+            BlockEntity::Sign(sign) => &sign.lines,
+            BlockEntity::Chest(_) | BlockEntity::Furnace(_) => &[],
+        }
+    }
+
+    pub fn text_mut(&mut self) -> &mut [String] {
+        match self {
+            BlockEntity::Sign(sign) => &mut sign.lines,
+            BlockEntity::Chest(_) | BlockEntity::Furnace(_) => &mut [],
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use super::noise::{self, Fractal};
+
+/// Half width of the noise band that becomes a tunnel, at a density of 1
+const TUNNEL_WIDTH: f64 = 0.06;
+/// Cavern noise above this is hollow, at a density of 1
+const CAVERN_THRESHOLD: f64 = 0.55;
+/// Caverns only open up this far below the surface, tunnels reach all the way up
+const CAVERN_DEPTH: i32 = 20;
+/// Vertical noise coordinates are stretched by this, so caves run more sideways than up
+const VERTICAL_SQUASH: f64 = 2.0;
+
+/// Hollows out the underground. Tunnels follow the lines where two noise fields both cross
+/// zero, caverns are the peaks of a third one.
+pub struct CaveCarver {
+    tunnel_a: Fractal,
+    tunnel_b: Fractal,
+    caverns: Fractal,
+    density: f64,
+}
+
+impl CaveCarver {
+    /// A `density` of 0 turns caves off, above 1 there are more and wider ones
+    pub fn new(seed: u64, density: f64) -> Self {
+        CaveCarver {
+            tunnel_a: Fractal::new(noise::hash(seed, &[10]), 2, 1.0 / 64.0),
+            tunnel_b: Fractal::new(noise::hash(seed, &[11]), 2, 1.0 / 64.0),
+            caverns: Fractal::new(noise::hash(seed, &[12]), 3, 1.0 / 96.0),
+            density: density.max(0.0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.density > 0.0
+    }
+
+    /// Whether the block at this position is carved out, `surface` is the column's height
+    pub fn is_cave(&self, x: i32, y: i32, z: i32, surface: i32) -> bool {
+        if !self.enabled() {
+            return false;
+        }
+        let (x, z) = (x as f64, z as f64);
+        let squashed = y as f64 * VERTICAL_SQUASH;
+
+        let width = TUNNEL_WIDTH * self.density;
+        if self.tunnel_a.sample3(x, squashed, z).abs() < width
+            && self.tunnel_b.sample3(x, squashed, z).abs() < width
+        {
+            return true;
+        }
+
+        let threshold = 1.0 - (1.0 - CAVERN_THRESHOLD) * self.density;
+        y <= surface - CAVERN_DEPTH && self.caverns.sample3(x, squashed, z) > threshold
+    }
+}
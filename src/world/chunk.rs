@@ -0,0 +1,161 @@
+use cgmath::Point3;
+use std::collections::HashMap;
+
+use super::block_entity::BlockEntity;
+use super::light::Light;
+use super::palette::PalettedStorage;
+use super::BlockId;
+
+/// Edge length of a chunk in blocks, chunks are cubes so the world can go as deep and high
+/// as it likes
+pub const CHUNK_SIZE: i32 = 16;
+pub const CHUNK_VOLUME: usize = (CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+/// Position of a chunk, in chunks
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl ChunkPos {
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        ChunkPos { x, y, z }
+    }
+
+    /// The chunk holding `block`, and where in it the block is
+    pub fn of_block(block: Point3<i32>) -> (Self, LocalPos) {
+        let chunk = ChunkPos::new(
+            block.x.div_euclid(CHUNK_SIZE),
+            block.y.div_euclid(CHUNK_SIZE),
+            block.z.div_euclid(CHUNK_SIZE),
+        );
+        let local = LocalPos::new(
+            block.x.rem_euclid(CHUNK_SIZE) as u8,
+            block.y.rem_euclid(CHUNK_SIZE) as u8,
+            block.z.rem_euclid(CHUNK_SIZE) as u8,
+        );
+        (chunk, local)
+    }
+
+    /// World position of the block at local 0, 0, 0
+    pub fn origin(self) -> Point3<i32> {
+        Point3::new(
+            self.x * CHUNK_SIZE,
+            self.y * CHUNK_SIZE,
+            self.z * CHUNK_SIZE,
+        )
+    }
+}
+
+/// Position of a block inside its chunk, each axis below [`CHUNK_SIZE`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LocalPos {
+    pub x: u8,
+    pub y: u8,
+    pub z: u8,
+}
+
+impl LocalPos {
+    pub const fn new(x: u8, y: u8, z: u8) -> Self {
+        LocalPos { x, y, z }
+    }
+
+    /// Layers of Y on the outside so a column of blocks is spread out but a horizontal
+    /// slice, which is what terrain and meshing walk, is contiguous
+    fn index(self) -> usize {
+        let size = CHUNK_SIZE as usize;
+        debug_assert!(
+            (self.x as usize) < size && (self.y as usize) < size && (self.z as usize) < size
+        );
+        (self.y as usize * size + self.z as usize) * size + self.x as usize
+    }
+}
+
+/// A cube of [`CHUNK_SIZE`] blocks on each side
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    blocks: PalettedStorage,
+    /// Sky light in the high nibble and block light in the low one, worked out by the world
+    /// once the chunk is loaded so it's never saved
+    light: Box<[u8]>,
+    block_entities: HashMap<LocalPos, BlockEntity>,
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        Self::filled(BlockId::AIR)
+    }
+
+    pub fn filled(block: BlockId) -> Self {
+        Chunk {
+            blocks: PalettedStorage::filled(block),
+            light: vec![0; CHUNK_VOLUME].into_boxed_slice(),
+            block_entities: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, local: LocalPos) -> BlockId {
+        self.blocks.get(local.index())
+    }
+
+    /// Returns the block that was there before
+    pub fn set(&mut self, local: LocalPos, block: BlockId) -> BlockId {
+        self.blocks.set(local.index(), block)
+    }
+
+    pub fn light(&self, local: LocalPos) -> Light {
+        let packed = self.light[local.index()];
+        Light {
+            sky: packed >> 4,
+            block: packed & 0xf,
+        }
+    }
+
+    pub fn set_light(&mut self, local: LocalPos, light: Light) {
+        self.light[local.index()] = light.sky << 4 | light.block;
+    }
+
+    /// Nothing but air
+    pub fn is_empty(&self) -> bool {
+        self.blocks.count(BlockId::AIR) == CHUNK_VOLUME
+    }
+
+    pub fn block_entity(&self, local: LocalPos) -> Option<&BlockEntity> {
+        self.block_entities.get(&local)
+    }
+
+    pub fn block_entity_mut(&mut self, local: LocalPos) -> Option<&mut BlockEntity> {
+        self.block_entities.get_mut(&local)
+    }
+
+    /// Returns the one that was there before
+    pub fn insert_block_entity(
+        &mut self,
+        local: LocalPos,
+        entity: BlockEntity,
+    ) -> Option<BlockEntity> {
+        self.block_entities.insert(local, entity)
+    }
+
+    pub fn remove_block_entity(&mut self, local: LocalPos) -> Option<BlockEntity> {
+        self.block_entities.remove(&local)
+    }
+
+    pub fn block_entities(&self) -> impl Iterator<Item = (LocalPos, &BlockEntity)> {
+        self.block_entities
+            .iter()
+            .map(|(local, entity)| (*local, entity))
+    }
+
+    pub fn block_entities_mut(&mut self) -> impl Iterator<Item = &mut BlockEntity> {
+        self.block_entities.values_mut()
+    }
+}
+
+impl Default for Chunk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
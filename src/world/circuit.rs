@@ -0,0 +1,458 @@
+use cgmath::{Point3, Vector3};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use super::block::{Block, Circuit, CircuitKind, Face};
+use super::{BlockId, BlockRegistry, World};
+
+/// Strongest a signal gets, sources give off this much and wire loses one level per block
+pub const MAX_POWER: u8 = 15;
+/// A second a button stays pressed
+pub const BUTTON_TICKS: u64 = 20;
+/// Most blocks a piston pushes in front of it
+const MAX_PUSH: i32 = 12;
+/// Wire networks stop growing past this many blocks, so a huge one can't stall a tick
+const MAX_NETWORK: usize = 4096;
+/// What pistons put in front of them when they extend, oriented the same way
+const PISTON_HEAD: &str = "piston_head";
+const HORIZONTAL: [Face; 4] = [Face::East, Face::West, Face::South, Face::North];
+
+/// Works out which circuit blocks are powered after the blocks around them change, in a fixed
+/// order each tick: pressure plates first, then the wire, then everything the wire and the
+/// sources drive. Whatever the last step moves or switches is looked at the tick after, like
+/// any other change.
+///
+/// Power comes from levers, buttons and pressure plates and runs along wire, losing a level
+/// each block. Solid full blocks pass it on to whatever is around them: a source next to one
+/// powers wire and receivers on its other sides, wire only powers receivers that way.
+#[derive(Default)]
+pub struct CircuitSimulation {
+    /// Pressure plates something stood on last tick
+    pressed: HashSet<Point3<i32>>,
+}
+
+impl CircuitSimulation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `changed` are the blocks changed since the last tick, `occupied` the blocks entities
+    /// have their feet in now
+    pub fn tick(
+        &mut self,
+        world: &mut World,
+        blocks: &BlockRegistry,
+        changed: &[Point3<i32>],
+        occupied: &[Point3<i32>],
+    ) {
+        let mut touched = changed.to_vec();
+        touched.extend(self.update_plates(world, blocks, occupied));
+
+        let mut visited = HashSet::new();
+        let mut rewired = Vec::new();
+        for position in around(&touched) {
+            if wire_at(world, blocks, position).is_some() && !visited.contains(&position) {
+                rewired.extend(update_network(world, blocks, position, &mut visited));
+            }
+        }
+
+        touched.extend(rewired);
+        for position in around(&touched) {
+            let Some((id, block)) = block_at(world, blocks, position) else {
+                continue;
+            };
+            match block.circuit.map(|circuit| circuit.kind) {
+                Some(CircuitKind::Receiver) => update_receiver(world, blocks, position, id),
+                Some(CircuitKind::Piston) => update_piston(world, blocks, position, id),
+                _ if is_piston_head(blocks, id) => {
+                    remove_loose_head(world, blocks, position, block);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Press the plates in `occupied` and let go of the ones nothing stands on anymore.
+    /// Returns the plates that changed.
+    fn update_plates(
+        &mut self,
+        world: &mut World,
+        blocks: &BlockRegistry,
+        occupied: &[Point3<i32>],
+    ) -> Vec<Point3<i32>> {
+        let mut pressed = HashSet::new();
+        let mut changed = Vec::new();
+        for position in occupied {
+            let Some((id, circuit)) = circuit_at(world, blocks, *position) else {
+                continue;
+            };
+            if circuit.kind != CircuitKind::PressurePlate || !pressed.insert(*position) {
+                continue;
+            }
+            if circuit.power == 0 && set_power(world, blocks, *position, id, MAX_POWER) {
+                changed.push(*position);
+            }
+        }
+        let mut released: Vec<_> = self.pressed.difference(&pressed).copied().collect();
+        released.sort_by_key(|position| (position.x, position.y, position.z));
+        for position in released {
+            let Some((id, circuit)) = circuit_at(world, blocks, position) else {
+                continue;
+            };
+            if circuit.kind == CircuitKind::PressurePlate
+                && circuit.power > 0
+                && set_power(world, blocks, position, id, 0)
+            {
+                changed.push(position);
+            }
+        }
+        self.pressed = pressed;
+        changed
+    }
+}
+
+/// Use the lever or button at `position`: levers flip, buttons get pressed. Returns the ticks
+/// until a pressed button should be let go of again with [`release`].
+pub fn switch(world: &mut World, blocks: &BlockRegistry, position: Point3<i32>) -> Option<u64> {
+    let (id, circuit) = circuit_at(world, blocks, position)?;
+    match circuit.kind {
+        CircuitKind::Lever => {
+            let power = if circuit.power > 0 { 0 } else { MAX_POWER };
+            set_power(world, blocks, position, id, power);
+            None
+        }
+        CircuitKind::Button if circuit.power == 0 => {
+            set_power(world, blocks, position, id, MAX_POWER);
+            Some(BUTTON_TICKS)
+        }
+        _ => None,
+    }
+}
+
+/// Let go of the button at `position`, if it's still there and pressed
+pub fn release(world: &mut World, blocks: &BlockRegistry, position: Point3<i32>) {
+    if let Some((id, circuit)) = circuit_at(world, blocks, position) {
+        if circuit.kind == CircuitKind::Button && circuit.power > 0 {
+            set_power(world, blocks, position, id, 0);
+        }
+    }
+}
+
+/// Turn `id` at `position` into its variant with `power`, returns whether that's different
+fn set_power(
+    world: &mut World,
+    blocks: &BlockRegistry,
+    position: Point3<i32>,
+    id: BlockId,
+    power: u8,
+) -> bool {
+    match blocks.powered(id, power) {
+        Some(powered) if powered != id => world.set_block(position, powered).is_some(),
+        _ => false,
+    }
+}
+
+/// Recompute the power of every wire connected to `start`, each one gets the strongest of
+/// what feeds it directly and one less than its strongest neighbour. Returns the wire that
+/// changed.
+fn update_network(
+    world: &mut World,
+    blocks: &BlockRegistry,
+    start: Point3<i32>,
+    visited: &mut HashSet<Point3<i32>>,
+) -> Vec<Point3<i32>> {
+    let mut wires = vec![start];
+    let mut indices = HashMap::from([(start, 0)]);
+    let mut links = Vec::new();
+    visited.insert(start);
+    let mut next = 0;
+    while next < wires.len() {
+        let mut linked = Vec::new();
+        for neighbor in connected_wires(world, blocks, wires[next]) {
+            let index = match indices.get(&neighbor) {
+                Some(index) => *index,
+                None if wires.len() < MAX_NETWORK => {
+                    visited.insert(neighbor);
+                    indices.insert(neighbor, wires.len());
+                    wires.push(neighbor);
+                    wires.len() - 1
+                }
+                None => continue,
+            };
+            linked.push(index);
+        }
+        links.push(linked);
+        next += 1;
+    }
+
+    // Strongest first, so each wire is settled by the first level that reaches it
+    let mut power: Vec<u8> = wires
+        .iter()
+        .map(|wire| wire_input(world, blocks, *wire))
+        .collect();
+    let mut queue: BinaryHeap<(u8, usize)> = power
+        .iter()
+        .enumerate()
+        .filter(|(_, power)| **power > 1)
+        .map(|(index, power)| (*power, index))
+        .collect();
+    while let Some((level, index)) = queue.pop() {
+        if level != power[index] {
+            continue;
+        }
+        for &neighbor in &links[index] {
+            if level - 1 > power[neighbor] {
+                power[neighbor] = level - 1;
+                if level - 1 > 1 {
+                    queue.push((level - 1, neighbor));
+                }
+            }
+        }
+    }
+
+    let mut changed = Vec::new();
+    for (wire, power) in wires.into_iter().zip(power) {
+        let Some((id, _)) = wire_at(world, blocks, wire) else {
+            continue;
+        };
+        if set_power(world, blocks, wire, id, power) {
+            changed.push(wire);
+        }
+    }
+    changed
+}
+
+/// Wire next to `position` on the same level, or a step up or down where nothing solid cuts
+/// the corner
+fn connected_wires(
+    world: &World,
+    blocks: &BlockRegistry,
+    position: Point3<i32>,
+) -> Vec<Point3<i32>> {
+    let up = Vector3::unit_y();
+    let covered = is_conductor_at(world, blocks, position + up);
+    let mut connected = Vec::new();
+    for face in HORIZONTAL {
+        let side = offset(position, face);
+        if wire_at(world, blocks, side).is_some() {
+            connected.push(side);
+        } else if !covered && wire_at(world, blocks, side + up).is_some() {
+            connected.push(side + up);
+        } else if !is_conductor_at(world, blocks, side)
+            && wire_at(world, blocks, side - up).is_some()
+        {
+            connected.push(side - up);
+        }
+    }
+    connected
+}
+
+/// Power fed into the wire at `position` from outside the wire: full from a source right next
+/// to it or to a solid block next to it
+fn wire_input(world: &World, blocks: &BlockRegistry, position: Point3<i32>) -> u8 {
+    let fed = neighbors(position).any(|neighbor| {
+        source_power(world, blocks, neighbor) > 0 || strongly_powered(world, blocks, neighbor)
+    });
+    if fed {
+        MAX_POWER
+    } else {
+        0
+    }
+}
+
+/// Whether anything around `position` powers it: a source, powered wire, or a solid block
+/// either of those power
+fn receives_power(world: &World, blocks: &BlockRegistry, position: Point3<i32>) -> bool {
+    neighbors(position).any(|neighbor| {
+        source_power(world, blocks, neighbor) > 0
+            || wire_at(world, blocks, neighbor).is_some_and(|(_, circuit)| circuit.power > 0)
+            || strongly_powered(world, blocks, neighbor)
+            || (is_conductor_at(world, blocks, neighbor)
+                && neighbors(neighbor).any(|around| {
+                    wire_at(world, blocks, around).is_some_and(|(_, circuit)| circuit.power > 0)
+                }))
+    })
+}
+
+/// A solid block with a source right next to it, it powers wire as well as receivers
+fn strongly_powered(world: &World, blocks: &BlockRegistry, position: Point3<i32>) -> bool {
+    is_conductor_at(world, blocks, position)
+        && neighbors(position).any(|neighbor| source_power(world, blocks, neighbor) > 0)
+}
+
+/// Switch a receiver to its powered variant or back to match what's around it
+fn update_receiver(world: &mut World, blocks: &BlockRegistry, position: Point3<i32>, id: BlockId) {
+    let power = if receives_power(world, blocks, position) {
+        MAX_POWER
+    } else {
+        0
+    };
+    set_power(world, blocks, position, id, power);
+}
+
+/// Extend a powered piston, pushing the blocks in front of it along and putting its head in
+/// the gap, or pull the head back in once it isn't powered. It doesn't extend while something
+/// it can't push is in the way, an unbreakable block, a fluid or too long a row of blocks.
+fn update_piston(world: &mut World, blocks: &BlockRegistry, position: Point3<i32>, id: BlockId) {
+    let Some(block) = blocks.get(id) else {
+        return;
+    };
+    let (Some(circuit), Some(orientation)) = (block.circuit, block.orientation) else {
+        return;
+    };
+    let facing = orientation.facing;
+    let front = offset(position, facing);
+    let powered = receives_power(world, blocks, position);
+    if powered == (circuit.power > 0) {
+        return;
+    }
+    if !powered {
+        let head = blocks
+            .id(PISTON_HEAD)
+            .and_then(|head| blocks.oriented(head, facing));
+        if head.is_some() && world.block(front) == head {
+            world.set_block(front, BlockId::AIR);
+        }
+        set_power(world, blocks, position, id, 0);
+        return;
+    }
+
+    let Some(head) = blocks
+        .id(PISTON_HEAD)
+        .and_then(|head| blocks.oriented(head, facing))
+    else {
+        return;
+    };
+    let [x, y, z] = facing.normal();
+    let step = Vector3::new(x, y, z);
+    let mut row = Vec::new();
+    let mut end = None;
+    for distance in 1..=MAX_PUSH + 1 {
+        let at = position + step * distance;
+        let Some((id, block)) = block_at(world, blocks, at) else {
+            return;
+        };
+        if id == BlockId::AIR {
+            end = Some(at);
+            break;
+        }
+        let extended = block
+            .circuit
+            .is_some_and(|circuit| circuit.kind == CircuitKind::Piston && circuit.power > 0);
+        if block.hardness < 0.0 || block.fluid.is_some() || extended || is_piston_head(blocks, id) {
+            return;
+        }
+        row.push((at, id));
+    }
+    if end.is_none() {
+        return;
+    }
+    for (at, id) in row.into_iter().rev() {
+        let entity = world.take_block_entity(at);
+        world.set_block(at + step, id);
+        if let Some(entity) = entity {
+            world.insert_block_entity(at + step, entity);
+        }
+    }
+    world.set_block(front, head);
+    set_power(world, blocks, position, id, MAX_POWER);
+}
+
+/// Take away a piston head whose piston is gone or pulled back
+fn remove_loose_head(
+    world: &mut World,
+    blocks: &BlockRegistry,
+    position: Point3<i32>,
+    head: &Block,
+) {
+    let Some(orientation) = head.orientation else {
+        return;
+    };
+    let [x, y, z] = orientation.facing.normal();
+    let behind = position - Vector3::new(x, y, z);
+    let attached = block_at(world, blocks, behind).is_some_and(|(_, piston)| {
+        piston
+            .circuit
+            .is_some_and(|circuit| circuit.kind == CircuitKind::Piston && circuit.power > 0)
+            && piston
+                .orientation
+                .is_some_and(|piston| piston.facing == orientation.facing)
+    });
+    if !attached {
+        world.set_block(position, BlockId::AIR);
+    }
+}
+
+fn is_piston_head(blocks: &BlockRegistry, id: BlockId) -> bool {
+    blocks
+        .id(PISTON_HEAD)
+        .is_some_and(|head| blocks.base(id) == head)
+}
+
+/// Power a lever, button or pressure plate at `position` gives off, 0 for anything else
+fn source_power(world: &World, blocks: &BlockRegistry, position: Point3<i32>) -> u8 {
+    circuit_at(world, blocks, position)
+        .filter(|(_, circuit)| circuit.kind.is_source())
+        .map_or(0, |(_, circuit)| circuit.power)
+}
+
+/// Solid full blocks pass power through, unless they're part of a circuit themselves
+fn is_conductor_at(world: &World, blocks: &BlockRegistry, position: Point3<i32>) -> bool {
+    block_at(world, blocks, position).is_some_and(|(_, block)| {
+        block.solid && block.model.is_none() && block.circuit.is_none() && block.fluid.is_none()
+    })
+}
+
+fn wire_at(
+    world: &World,
+    blocks: &BlockRegistry,
+    position: Point3<i32>,
+) -> Option<(BlockId, Circuit)> {
+    circuit_at(world, blocks, position).filter(|(_, circuit)| circuit.kind == CircuitKind::Wire)
+}
+
+fn circuit_at(
+    world: &World,
+    blocks: &BlockRegistry,
+    position: Point3<i32>,
+) -> Option<(BlockId, Circuit)> {
+    let (id, block) = block_at(world, blocks, position)?;
+    Some((id, block.circuit?))
+}
+
+fn block_at<'a>(
+    world: &World,
+    blocks: &'a BlockRegistry,
+    position: Point3<i32>,
+) -> Option<(BlockId, &'a Block)> {
+    let id = world.block(position)?;
+    Some((id, blocks.get(id)?))
+}
+
+/// `positions`, their neighbours and the neighbours of those, each once and in the order
+/// they're first reached. Power goes through one block in between, so that's as far as a
+/// change can reach within a step.
+fn around(positions: &[Point3<i32>]) -> Vec<Point3<i32>> {
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+    for position in positions {
+        for near in std::iter::once(*position).chain(neighbors(*position)) {
+            for position in std::iter::once(near).chain(neighbors(near)) {
+                if seen.insert(position) {
+                    found.push(position);
+                }
+            }
+        }
+    }
+    found
+}
+
+fn neighbors(position: Point3<i32>) -> impl Iterator<Item = Point3<i32>> {
+    Face::ALL
+        .into_iter()
+        .map(move |face| offset(position, face))
+}
+
+fn offset(position: Point3<i32>, face: Face) -> Point3<i32> {
+    let [x, y, z] = face.normal();
+    position + Vector3::new(x, y, z)
+}
@@ -0,0 +1,215 @@
+use cgmath::{Point3, Vector3};
+use std::sync::Arc;
+
+use super::biome::Biome;
+use super::block::{BlockRegistry, Face};
+use super::chunk::{ChunkPos, CHUNK_SIZE};
+use super::generator::{Features, TerrainGenerator, SEA_LEVEL};
+use super::noise;
+use super::pipeline::Region;
+use super::structures::StructurePlanner;
+use super::BlockId;
+
+/// Chance per column in a desert to grow a cactus
+const CACTUS_DENSITY: f64 = 0.004;
+/// Chance per column in plains and mountains to have a boulder
+const BOULDER_DENSITY: f64 = 0.001;
+/// Trees, cacti and boulders keep this many blocks away from the pieces of structures
+const STRUCTURE_CLEARANCE: i32 = 3;
+
+/// Blocks structures are built from
+pub struct Decorator {
+    seed: u64,
+    features: Arc<Features>,
+    planner: StructurePlanner,
+    stone: BlockId,
+    grass: BlockId,
+    sand: BlockId,
+    log: BlockId,
+    leaves: BlockId,
+    cactus: BlockId,
+    cobblestone: BlockId,
+}
+
+impl Decorator {
+    pub fn new(seed: u64, blocks: &BlockRegistry, features: Arc<Features>) -> Self {
+        let id = |name: &str| {
+            blocks.id(name).unwrap_or_else(|| {
+                log::warn!("Structures need a block called {name}, using air");
+                BlockId::AIR
+            })
+        };
+        Decorator {
+            seed,
+            features,
+            planner: StructurePlanner::new(seed),
+            stone: id("stone"),
+            grass: id("grass_block"),
+            sand: id("sand"),
+            log: id("oak_log"),
+            leaves: id("oak_leaves"),
+            cactus: id("cactus"),
+            cobblestone: id("cobblestone"),
+        }
+    }
+
+    /// Place the structures rooted in this chunk's columns. Each stays within one chunk of
+    /// its root, see [`super::pipeline::GenerationPipeline`]. Pieces of the structures from
+    /// `worldgen/structures.json` are the exception, they can span any number of chunks and
+    /// every chunk only builds its own part of them, last so nothing grows into them.
+    pub fn decorate(&self, generator: &TerrainGenerator, position: ChunkPos, region: &mut Region) {
+        self.place_ores(position, region);
+        let structures = &self.features.structures;
+        let pieces = self
+            .planner
+            .pieces_near(structures, generator, position, STRUCTURE_CLEARANCE);
+        let origin = position.origin();
+        for z in origin.z..origin.z + CHUNK_SIZE {
+            for x in origin.x..origin.x + CHUNK_SIZE {
+                if pieces
+                    .iter()
+                    .any(|piece| piece.covers_column(x, z, STRUCTURE_CLEARANCE))
+                {
+                    continue;
+                }
+                let column = generator.column(x, z);
+                let surface = column.height;
+                if surface < origin.y || surface >= origin.y + CHUNK_SIZE || surface < SEA_LEVEL {
+                    continue;
+                }
+                let ground = Point3::new(x, surface, z);
+                // Caves may have taken the surface block, or the column is under water
+                let Some(top) = region.get(ground) else {
+                    continue;
+                };
+                if region.get(ground + Vector3::unit_y()) != Some(BlockId::AIR) {
+                    continue;
+                }
+
+                let roll = noise::hash_unit(self.seed, &[x as i64, z as i64, 100]);
+                let tree_density = column.biome.parameters().tree_density;
+                if top == self.grass && roll < tree_density {
+                    self.place_tree(ground, region);
+                } else if column.biome == Biome::Desert && top == self.sand && roll < CACTUS_DENSITY
+                {
+                    self.place_cactus(ground, region);
+                } else if matches!(column.biome, Biome::Plains | Biome::Mountains)
+                    && top != BlockId::AIR
+                    && roll > 1.0 - BOULDER_DENSITY
+                {
+                    self.place_boulder(ground, region);
+                }
+            }
+        }
+        for piece in &pieces {
+            structures.build(generator, piece, position, region);
+        }
+    }
+
+    /// Every ore gets its veins in turn, each a random walk through stone from a random
+    /// start inside the chunk
+    fn place_ores(&self, position: ChunkPos, region: &mut Region) {
+        let origin = position.origin();
+        let top = origin.y + CHUNK_SIZE - 1;
+        for (index, ore) in self.features.ores.iter().enumerate() {
+            let (low, high) = (ore.min_height.max(origin.y), ore.max_height.min(top));
+            if low > high {
+                continue;
+            }
+            let random = |vein: u32, step: u32, range: i32| {
+                let values = [
+                    position.x as i64,
+                    position.y as i64,
+                    position.z as i64,
+                    index as i64,
+                    vein as i64,
+                    step as i64,
+                ];
+                (noise::hash(self.seed, &values) % range as u64) as i32
+            };
+
+            let chance = noise::hash_unit(
+                self.seed,
+                &[
+                    position.x as i64,
+                    position.y as i64,
+                    position.z as i64,
+                    index as i64,
+                ],
+            );
+            let veins = ore.frequency as u32 + u32::from(chance < ore.frequency.fract());
+            for vein in 0..veins {
+                let mut block = Point3::new(
+                    origin.x + random(vein, 0, CHUNK_SIZE),
+                    low + random(vein, 1, high - low + 1),
+                    origin.z + random(vein, 2, CHUNK_SIZE),
+                );
+                for step in 0..ore.size {
+                    if region.get(block) == Some(self.stone) {
+                        region.set(block, ore.block);
+                    }
+                    let face = Face::ALL[random(vein, step + 3, 6) as usize];
+                    block += Vector3::from(face.normal());
+                }
+            }
+        }
+    }
+
+    /// An oak: a trunk of 4 to 6 logs in a blob of leaves, corners trimmed at random
+    fn place_tree(&self, ground: Point3<i32>, region: &mut Region) {
+        let height = 4 + self.random(ground, 1, 3) as i32;
+        let top = ground.y + height;
+        for y in top - 2..=top + 1 {
+            let radius: i32 = if y >= top { 1 } else { 2 };
+            for dz in -radius..=radius {
+                for dx in -radius..=radius {
+                    let corner = dx.abs() == radius && dz.abs() == radius;
+                    let position = Point3::new(ground.x + dx, y, ground.z + dz);
+                    if corner && (y == top + 1 || self.random(position, 2, 2) == 0) {
+                        continue;
+                    }
+                    if region.get(position) == Some(BlockId::AIR) {
+                        region.set(position, self.leaves);
+                    }
+                }
+            }
+        }
+        for y in ground.y + 1..=top {
+            region.set(Point3::new(ground.x, y, ground.z), self.log);
+        }
+    }
+
+    fn place_cactus(&self, ground: Point3<i32>, region: &mut Region) {
+        let height = 1 + self.random(ground, 3, 3) as i32;
+        for y in ground.y + 1..=ground.y + height {
+            region.set(Point3::new(ground.x, y, ground.z), self.cactus);
+        }
+    }
+
+    /// A lumpy ball of cobblestone half sunk into the ground
+    fn place_boulder(&self, ground: Point3<i32>, region: &mut Region) {
+        let radius = 1 + self.random(ground, 4, 2) as i32;
+        for dy in -radius..=radius {
+            for dz in -radius..=radius {
+                for dx in -radius..=radius {
+                    let position = ground + Vector3::new(dx, dy, dz);
+                    let lump = self.random(position, 5, 2) as i32;
+                    if dx * dx + dy * dy + dz * dz <= radius * radius + lump {
+                        region.set(position, self.cobblestone);
+                    }
+                }
+            }
+        }
+    }
+
+    /// A number below `range` that's always the same for this position and purpose
+    fn random(&self, position: Point3<i32>, purpose: i64, range: u64) -> u64 {
+        let values = [
+            position.x as i64,
+            position.y as i64,
+            position.z as i64,
+            purpose,
+        ];
+        noise::hash(self.seed, &values) % range
+    }
+}
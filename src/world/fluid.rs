@@ -0,0 +1,173 @@
+use cgmath::{Point3, Vector3};
+use std::collections::{BTreeMap, HashSet};
+
+use super::block::{Face, Fluid};
+use super::{BlockId, BlockRegistry, World};
+
+/// Fluid blocks updated in one tick at most, the rest wait for the next so a flood can't stall
+/// the game
+const MAX_UPDATES_PER_TICK: usize = 4096;
+const HORIZONTAL: [Face; 4] = [Face::East, Face::West, Face::South, Face::North];
+
+/// Spreads and drains fluids as a cellular automaton. Changing a block wakes the fluid next to
+/// it, which settles its own level after its fluid's delay and spreads from there.
+#[derive(Default)]
+pub struct FluidSimulation {
+    /// Blocks waiting to update, by the tick they're due on
+    due: BTreeMap<u64, Vec<Point3<i32>>>,
+    /// Everything in `due`, so a block never waits twice
+    queued: HashSet<Point3<i32>>,
+}
+
+impl FluidSimulation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue up the fluid around the blocks that `changed` since the last tick, then update
+    /// whatever is due by `now`. Blocks in chunks that got unloaded in the meantime are dropped.
+    pub fn tick(
+        &mut self,
+        world: &mut World,
+        blocks: &BlockRegistry,
+        changed: &[Point3<i32>],
+        now: u64,
+    ) {
+        for &changed in changed {
+            let neighbors = Face::ALL.map(|face| offset(changed, face));
+            for position in std::iter::once(changed).chain(neighbors) {
+                let Some(fluid) = fluid_at(world, blocks, position) else {
+                    continue;
+                };
+                if self.queued.insert(position) {
+                    let due = now + fluid.properties.delay;
+                    self.due.entry(due).or_default().push(position);
+                }
+            }
+        }
+
+        for _ in 0..MAX_UPDATES_PER_TICK {
+            let Some(mut entry) = self.due.first_entry() else {
+                break;
+            };
+            if *entry.key() > now {
+                break;
+            }
+            let Some(position) = entry.get_mut().pop() else {
+                entry.remove();
+                continue;
+            };
+            self.queued.remove(&position);
+            update(world, blocks, position);
+        }
+    }
+}
+
+/// Settle one fluid block's level, then let it flow down or, failing that, out to the sides
+fn update(world: &mut World, blocks: &BlockRegistry, position: Point3<i32>) {
+    let Some(fluid) = fluid_at(world, blocks, position) else {
+        return;
+    };
+    let Some(settled) = settle(world, blocks, position, fluid) else {
+        world.set_block(position, BlockId::AIR);
+        return;
+    };
+    world.set_block(position, settled);
+    let Some(fluid) = blocks.get(settled).and_then(|block| block.fluid) else {
+        return;
+    };
+
+    let below = offset(position, Face::Down);
+    let flows_down = world
+        .block(below)
+        .is_some_and(|block| block == BlockId::AIR || is_flowing(blocks, block, fluid.source));
+    if flows_down {
+        if let Some(falling) = blocks.fluid_block(fluid.source, 0, true) {
+            world.set_block(below, falling);
+        }
+        return;
+    }
+
+    let level = fluid.level + 1;
+    let Some(flowing) = blocks.fluid_block(fluid.source, level, false) else {
+        return;
+    };
+    for face in HORIZONTAL {
+        let neighbor = offset(position, face);
+        let Some(block) = world.block(neighbor) else {
+            continue;
+        };
+        let replaceable = block == BlockId::AIR
+            || fluid_of(blocks, block).is_some_and(|other| {
+                other.source == fluid.source
+                    && !other.is_source()
+                    && !other.falling
+                    && other.level > level
+            });
+        if replaceable {
+            world.set_block(neighbor, flowing);
+        }
+    }
+}
+
+/// The block this fluid should be now: sources stay, fluid under the same fluid falls and
+/// anything else is one level past its lowest neighbour. None once nothing feeds it.
+fn settle(
+    world: &World,
+    blocks: &BlockRegistry,
+    position: Point3<i32>,
+    fluid: Fluid,
+) -> Option<BlockId> {
+    if fluid.is_source() {
+        return blocks.fluid_block(fluid.source, 0, false);
+    }
+    let same =
+        |position| fluid_at(world, blocks, position).filter(|other| other.source == fluid.source);
+    if same(offset(position, Face::Up)).is_some() {
+        return blocks.fluid_block(fluid.source, 0, true);
+    }
+
+    let mut sources = 0;
+    let mut lowest = None;
+    for face in HORIZONTAL {
+        let Some(neighbor) = same(offset(position, face)) else {
+            continue;
+        };
+        if neighbor.is_source() {
+            sources += 1;
+        }
+        // Falling fluid is level 0 too, it spreads like a source wherever it lands
+        lowest = Some(lowest.map_or(neighbor.level, |lowest: u8| lowest.min(neighbor.level)));
+    }
+    if fluid.properties.renewable && sources >= 2 {
+        let below = offset(position, Face::Down);
+        let supported = same(below).is_some_and(|below| below.is_source())
+            || world
+                .block(below)
+                .is_some_and(|block| blocks.get(block).is_some_and(|block| block.solid));
+        if supported {
+            return blocks.fluid_block(fluid.source, 0, false);
+        }
+    }
+    blocks.fluid_block(fluid.source, lowest? + 1, false)
+}
+
+fn fluid_of(blocks: &BlockRegistry, block: BlockId) -> Option<Fluid> {
+    blocks.get(block).and_then(|block| block.fluid)
+}
+
+fn fluid_at(world: &World, blocks: &BlockRegistry, position: Point3<i32>) -> Option<Fluid> {
+    world
+        .block(position)
+        .and_then(|block| fluid_of(blocks, block))
+}
+
+/// Any level of the fluid other than its source
+fn is_flowing(blocks: &BlockRegistry, block: BlockId, source: BlockId) -> bool {
+    fluid_of(blocks, block).is_some_and(|fluid| fluid.source == source && !fluid.is_source())
+}
+
+fn offset(position: Point3<i32>, face: Face) -> Point3<i32> {
+    let [x, y, z] = face.normal();
+    position + Vector3::new(x, y, z)
+}
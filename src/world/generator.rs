@@ -0,0 +1,240 @@
+use super::biome::{self, Biome, BiomeSource};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::block::BlockRegistry;
+use super::caves::CaveCarver;
+use super::chunk::{Chunk, ChunkPos, LocalPos, CHUNK_SIZE};
+use super::decoration::Decorator;
+use super::noise::{self, Fractal};
+use super::ores::OreVein;
+use super::pipeline::Region;
+use super::structures::StructureSet;
+use super::BlockId;
+
+/// Water fills every column up to here
+pub const SEA_LEVEL: i32 = 62;
+/// The lowest block of the world, always bedrock. Chunks below stay empty.
+pub const BEDROCK_LEVEL: i32 = 0;
+/// Bedrock thins out randomly over this many blocks above the floor
+const BEDROCK_LAYERS: i32 = 4;
+/// Blocks of dirt or sand between the surface and stone
+const SOIL_DEPTH: i32 = 3;
+/// Surfaces this close above sea level are beaches
+const BEACH_HEIGHT: i32 = 2;
+/// Caves stay this far below the floor of anything under water, so oceans don't drain into
+/// them
+const SEA_FLOOR_SEAL: i32 = 4;
+
+/// Options chosen per world, stored with it
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneratorSettings {
+    /// 0 turns caves off, 1 is the usual amount and more makes them wider and more common
+    pub cave_density: f64,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        GeneratorSettings { cave_density: 1.0 }
+    }
+}
+
+/// What the files in `worldgen/` add on top of the terrain, loaded once and shared by
+/// every world
+pub struct Features {
+    pub ores: Vec<OreVein>,
+    pub structures: StructureSet,
+}
+
+/// The blocks terrain is made of, looked up once so generating doesn't go through names
+struct TerrainBlocks {
+    stone: BlockId,
+    sand: BlockId,
+    water: BlockId,
+    bedrock: BlockId,
+    /// Surface and soil blocks per biome, in [`Biome::ALL`] order
+    cover: [(BlockId, BlockId); 5],
+}
+
+/// The surface of one column of terrain
+#[derive(Debug, Copy, Clone)]
+pub struct Column {
+    /// Y of the topmost solid block
+    pub height: i32,
+    pub biome: Biome,
+}
+
+/// Builds chunks from the world seed alone, any chunk can be generated in any order and comes
+/// out the same
+pub struct TerrainGenerator {
+    seed: u64,
+    height: Fractal,
+    biomes: BiomeSource,
+    caves: CaveCarver,
+    decorator: Decorator,
+    blocks: TerrainBlocks,
+}
+
+impl TerrainGenerator {
+    pub fn new(
+        seed: u64,
+        settings: GeneratorSettings,
+        blocks: &BlockRegistry,
+        features: Arc<Features>,
+    ) -> Self {
+        let id = |name: &str| {
+            blocks.id(name).unwrap_or_else(|| {
+                log::warn!("Terrain needs a block called {name}, using air");
+                BlockId::AIR
+            })
+        };
+        TerrainGenerator {
+            seed,
+            height: Fractal::new(noise::hash(seed, &[0]), 5, 1.0 / 256.0),
+            biomes: BiomeSource::new(seed),
+            caves: CaveCarver::new(seed, settings.cave_density),
+            decorator: Decorator::new(seed, blocks, features),
+            blocks: TerrainBlocks {
+                stone: id("stone"),
+                sand: id("sand"),
+                water: id("water"),
+                bedrock: id("bedrock"),
+                cover: Biome::ALL.map(|biome| {
+                    let parameters = biome.parameters();
+                    (id(parameters.surface), id(parameters.soil))
+                }),
+            },
+        }
+    }
+
+    /// The terrain shape is a weighted mix of the biomes around the column, so heights
+    /// flow into each other at biome borders instead of stepping
+    pub fn column(&self, x: i32, z: i32) -> Column {
+        let weights = self.biomes.weights(x, z);
+        let (mut base, mut variation) = (0.0, 0.0);
+        for (biome, weight) in Biome::ALL.iter().zip(weights) {
+            let parameters = biome.parameters();
+            base += parameters.base_height * weight;
+            variation += parameters.height_variation * weight;
+        }
+        let noise = self.height.sample2(x as f64, z as f64);
+        Column {
+            height: (base + noise * variation).floor() as i32,
+            biome: biome::dominant(&weights),
+        }
+    }
+
+    /// The column's biome without working out its height, see [`BiomeSource::biome`]
+    pub fn biome(&self, x: i32, z: i32) -> Biome {
+        self.biomes.biome(x, z)
+    }
+
+    /// Biome colors of the column in [`Tint`](super::block::Tint) order, see [`biome::tint_colors`]
+    pub fn tint_colors(&self, x: i32, z: i32) -> [[f32; 3]; 2] {
+        biome::tint_colors(&self.biomes.weights(x, z))
+    }
+
+    /// Y of the topmost solid block in the column
+    pub fn surface_height(&self, x: i32, z: i32) -> i32 {
+        self.column(x, z).height
+    }
+
+    /// The first stage, everything that only depends on the chunk itself. Structures come
+    /// later from [`super::pipeline::GenerationPipeline`].
+    pub fn generate_terrain(&self, position: ChunkPos) -> Chunk {
+        let origin = position.origin();
+        let size = CHUNK_SIZE as usize;
+        let mut columns = Vec::with_capacity(size * size);
+        for z in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                columns.push(self.column(origin.x + x, origin.z + z));
+            }
+        }
+        let lowest = columns
+            .iter()
+            .map(|column| column.height)
+            .min()
+            .unwrap_or(0);
+        let highest = columns
+            .iter()
+            .map(|column| column.height)
+            .max()
+            .unwrap_or(0);
+        let top = origin.y + CHUNK_SIZE - 1;
+
+        // Skip the per block work for chunks that are all air or, without caves, all stone
+        if top < BEDROCK_LEVEL || origin.y > highest.max(SEA_LEVEL) {
+            return Chunk::new();
+        }
+        if !self.caves.enabled()
+            && origin.y >= BEDROCK_LEVEL + BEDROCK_LAYERS
+            && top < lowest - SOIL_DEPTH
+        {
+            return Chunk::filled(self.blocks.stone);
+        }
+
+        let mut chunk = Chunk::new();
+        for y in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    let column = columns[(z * CHUNK_SIZE + x) as usize];
+                    let block = self.block_at(origin.x + x, origin.y + y, origin.z + z, column);
+                    if block != BlockId::AIR {
+                        chunk.set(LocalPos::new(x as u8, y as u8, z as u8), block);
+                    }
+                }
+            }
+        }
+        chunk
+    }
+
+    /// The second stage, ores, trees and other structures that may reach into neighbouring
+    /// chunks
+    pub fn decorate(&self, position: ChunkPos, region: &mut Region) {
+        self.decorator.decorate(self, position, region);
+    }
+
+    fn block_at(&self, x: i32, y: i32, z: i32, column: Column) -> BlockId {
+        let surface = column.height;
+        let blocks = &self.blocks;
+        if y < BEDROCK_LEVEL {
+            return BlockId::AIR;
+        }
+        if y < BEDROCK_LEVEL + BEDROCK_LAYERS {
+            // Always at the floor, then less likely with every layer up
+            let depth = (y - BEDROCK_LEVEL) as f64;
+            let chance = 1.0 - depth / BEDROCK_LAYERS as f64;
+            let roll = noise::hash_unit(self.seed, &[x as i64, y as i64, z as i64]);
+            if roll < chance {
+                return blocks.bedrock;
+            }
+        }
+        if y > surface {
+            return if y <= SEA_LEVEL {
+                blocks.water
+            } else {
+                BlockId::AIR
+            };
+        }
+
+        // Tunnels that reach the surface leave an entrance behind
+        let sealed = surface <= SEA_LEVEL && y > surface - SEA_FLOOR_SEAL;
+        if !sealed && y >= BEDROCK_LEVEL + BEDROCK_LAYERS && self.caves.is_cave(x, y, z, surface) {
+            return BlockId::AIR;
+        }
+
+        let (top, soil) = if surface <= SEA_LEVEL + BEACH_HEIGHT {
+            (blocks.sand, blocks.sand)
+        } else {
+            blocks.cover[column.biome as usize]
+        };
+        if y == surface {
+            top
+        } else if y > surface - SOIL_DEPTH {
+            soil
+        } else {
+            blocks.stone
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use cgmath::{Point3, Vector3};
+use std::collections::VecDeque;
+
+use super::block::Face;
+use super::chunk::{ChunkPos, LocalPos, CHUNK_SIZE};
+use super::{BlockId, BlockRegistry, World};
+
+pub const MAX_LIGHT: u8 = 15;
+
+/// Light levels at a block, each from 0 to [`MAX_LIGHT`]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Light {
+    /// Reaching down from the open sky
+    pub sky: u8,
+    /// Given off by blocks like glowstone and lava
+    pub block: u8,
+}
+
+impl Light {
+    /// What faces towards unloaded chunks are lit with
+    pub const OPEN: Light = Light {
+        sky: MAX_LIGHT,
+        block: 0,
+    };
+}
+
+/// Sky and block light spread independently, the same way apart from the sky staying at full
+/// strength on its way straight down
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Channel {
+    Sky,
+    Block,
+}
+
+impl Channel {
+    const ALL: [Channel; 2] = [Channel::Sky, Channel::Block];
+
+    fn get(self, light: Light) -> u8 {
+        match self {
+            Channel::Sky => light.sky,
+            Channel::Block => light.block,
+        }
+    }
+
+    fn with(self, light: Light, level: u8) -> Light {
+        match self {
+            Channel::Sky => Light {
+                sky: level,
+                ..light
+            },
+            Channel::Block => Light {
+                block: level,
+                ..light
+            },
+        }
+    }
+
+    /// Light `block` gives off by itself
+    fn emitted(self, blocks: &BlockRegistry, block: BlockId) -> u8 {
+        match self {
+            Channel::Sky => 0,
+            Channel::Block => blocks.get(block).map_or(0, |block| block.light),
+        }
+    }
+
+    /// What a block at `level` gives the neighbour on its `face` side
+    fn passed_on(self, level: u8, face: Face) -> u8 {
+        if self == Channel::Sky && face == Face::Down && level == MAX_LIGHT {
+            MAX_LIGHT
+        } else {
+            level.saturating_sub(1)
+        }
+    }
+}
+
+/// Light up a chunk that was just loaded: sky straight down from above, whatever its blocks
+/// give off and whatever shines in from the loaded chunks around it. A chunk above that isn't
+/// loaded counts as open sky, so the chunk below gets darkened again if this one turns out to
+/// be in the way.
+pub(super) fn light_chunk(world: &mut World, blocks: &BlockRegistry, position: ChunkPos) {
+    let Some(chunk) = world.chunks.get(&position) else {
+        return;
+    };
+    let origin = position.origin();
+    let mut emitters = Vec::new();
+    let mut columns = Vec::new();
+    for x in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            let above = origin + Vector3::new(x, CHUNK_SIZE, z);
+            let open = world
+                .light(above)
+                .is_none_or(|light| light.sky == MAX_LIGHT);
+            let mut lit = open;
+            for y in (0..CHUNK_SIZE).rev() {
+                let block = chunk.get(LocalPos::new(x as u8, y as u8, z as u8));
+                let block_position = origin + Vector3::new(x, y, z);
+                lit &= !is_opaque(blocks, block);
+                if lit {
+                    columns.push(block_position);
+                }
+                let own = Channel::Block.emitted(blocks, block);
+                if own > 0 {
+                    emitters.push((block_position, own));
+                }
+            }
+        }
+    }
+
+    let mut sky = VecDeque::new();
+    for block_position in columns {
+        world.set_light_level(block_position, Channel::Sky, MAX_LIGHT);
+        sky.push_back(block_position);
+    }
+    let mut block_light = VecDeque::new();
+    for (block_position, level) in emitters {
+        world.set_light_level(block_position, Channel::Block, level);
+        block_light.push_back(block_position);
+    }
+    // The neighbours' border blocks shine in
+    for face in Face::ALL {
+        for border in border_blocks(position, face) {
+            sky.push_back(border);
+            block_light.push_back(border);
+        }
+    }
+    spread(world, blocks, Channel::Sky, sky);
+    spread(world, blocks, Channel::Block, block_light);
+
+    // The chunk below was lit as if this one was open sky
+    let below: Vec<_> = (0..CHUNK_SIZE)
+        .flat_map(|x| (0..CHUNK_SIZE).map(move |z| origin + Vector3::new(x, -1, z)))
+        .filter(|below| {
+            let inside = below + Vector3::unit_y();
+            world
+                .light(*below)
+                .is_some_and(|light| light.sky == MAX_LIGHT)
+                && world
+                    .light(inside)
+                    .is_some_and(|light| light.sky < MAX_LIGHT)
+        })
+        .collect();
+    for below in below {
+        relight(world, blocks, Channel::Sky, below, 0);
+    }
+}
+
+/// Bring the light around a block that just changed up to date
+pub(super) fn relight_block(world: &mut World, blocks: &BlockRegistry, position: Point3<i32>) {
+    let Some(block) = world.block(position) else {
+        return;
+    };
+    for channel in Channel::ALL {
+        relight(
+            world,
+            blocks,
+            channel,
+            position,
+            channel.emitted(blocks, block),
+        );
+    }
+}
+
+/// Darken everything `position` was lighting, set it to `own`, then spread light back in from
+/// the blocks around
+fn relight(
+    world: &mut World,
+    blocks: &BlockRegistry,
+    channel: Channel,
+    position: Point3<i32>,
+    own: u8,
+) {
+    let Some(old) = world.light(position).map(|light| channel.get(light)) else {
+        return;
+    };
+    world.set_light_level(position, channel, 0);
+    let mut relit = darken(world, blocks, channel, position, old);
+    if own > 0 {
+        world.set_light_level(position, channel, own);
+        relit.push_back(position);
+    }
+    for face in Face::ALL {
+        relit.push_back(offset(position, face));
+    }
+    spread(world, blocks, channel, relit);
+}
+
+/// Breadth first removal of light that came from `start`, which was at `level`. Returns the
+/// blocks at the edge that got their light elsewhere, spreading from them fills the hole back
+/// in.
+fn darken(
+    world: &mut World,
+    blocks: &BlockRegistry,
+    channel: Channel,
+    start: Point3<i32>,
+    level: u8,
+) -> VecDeque<Point3<i32>> {
+    let mut relit = VecDeque::new();
+    let mut queue = VecDeque::from([(start, level)]);
+    while let Some((position, level)) = queue.pop_front() {
+        for face in Face::ALL {
+            let neighbor = offset(position, face);
+            let Some(light) = world.light(neighbor) else {
+                continue;
+            };
+            let neighbor_level = channel.get(light);
+            if neighbor_level == 0 {
+                continue;
+            }
+            let from_here = neighbor_level < level
+                || channel == Channel::Sky && face == Face::Down && level == MAX_LIGHT;
+            if !from_here {
+                relit.push_back(neighbor);
+                continue;
+            }
+            world.set_light_level(neighbor, channel, 0);
+            queue.push_back((neighbor, neighbor_level));
+            // Glowing blocks keep their own light no matter what reached them before
+            let own = world
+                .block(neighbor)
+                .map_or(0, |block| channel.emitted(blocks, block));
+            if own > 0 {
+                world.set_light_level(neighbor, channel, own);
+                relit.push_back(neighbor);
+            }
+        }
+    }
+    relit
+}
+
+/// Breadth first flood from every block in `queue` at its current level, through anything
+/// light passes
+fn spread(
+    world: &mut World,
+    blocks: &BlockRegistry,
+    channel: Channel,
+    mut queue: VecDeque<Point3<i32>>,
+) {
+    while let Some(position) = queue.pop_front() {
+        let Some(level) = world.light(position).map(|light| channel.get(light)) else {
+            continue;
+        };
+        // Nothing left to pass on
+        if level <= 1 {
+            continue;
+        }
+        for face in Face::ALL {
+            let neighbor = offset(position, face);
+            let Some(block) = world.block(neighbor) else {
+                continue;
+            };
+            if is_opaque(blocks, block) {
+                continue;
+            }
+            let passed = channel.passed_on(level, face);
+            let current = world.light(neighbor).map_or(0, |light| channel.get(light));
+            if passed > current {
+                world.set_light_level(neighbor, channel, passed);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+}
+
+impl World {
+    /// Changes one channel of a loaded block's light and has it remeshed, along with any
+    /// neighbouring chunk that shows it
+    fn set_light_level(&mut self, position: Point3<i32>, channel: Channel, level: u8) {
+        let (chunk_position, local) = ChunkPos::of_block(position);
+        let Some(chunk) = self.chunks.get_mut(&chunk_position) else {
+            return;
+        };
+        let light = chunk.light(local);
+        if channel.get(light) != level {
+            chunk.set_light(local, channel.with(light, level));
+            self.mark_block_dirty(position);
+        }
+    }
+}
+
+/// Blocks of the neighbour on the `face` side that touch the chunk at `position`
+fn border_blocks(position: ChunkPos, face: Face) -> impl Iterator<Item = Point3<i32>> {
+    let normal = face.normal();
+    let axis = normal.iter().position(|value| *value != 0).unwrap_or(0);
+    let (first, second) = ((axis + 1) % 3, (axis + 2) % 3);
+    let origin = position.origin();
+    (0..CHUNK_SIZE).flat_map(move |a| {
+        (0..CHUNK_SIZE).map(move |b| {
+            let mut local = [0; 3];
+            local[axis] = if normal[axis] > 0 { CHUNK_SIZE } else { -1 };
+            local[first] = a;
+            local[second] = b;
+            origin + Vector3::new(local[0], local[1], local[2])
+        })
+    })
+}
+
+/// Light doesn't get into or through it. Unknown ids block it too.
+fn is_opaque(blocks: &BlockRegistry, block: BlockId) -> bool {
+    blocks.get(block).is_none_or(|block| !block.transparent)
+}
+
+fn offset(position: Point3<i32>, face: Face) -> Point3<i32> {
+    let [x, y, z] = face.normal();
+    position + Vector3::new(x, y, z)
+}
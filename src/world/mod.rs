@@ -0,0 +1,277 @@
+pub mod biome;
+pub mod block;
+pub mod block_entity;
+mod caves;
+pub mod chunk;
+pub mod circuit;
+mod decoration;
+pub mod fluid;
+pub mod generator;
+pub mod light;
+pub mod model;
+pub mod noise;
+pub mod ores;
+mod palette;
+pub mod pipeline;
+pub mod raycast;
+pub mod streaming;
+pub mod structures;
+
+use cgmath::{Point3, Vector3};
+use std::collections::{HashMap, HashSet};
+
+pub use block::{BlockId, BlockRegistry};
+use block_entity::BlockEntity;
+use chunk::{Chunk, ChunkPos, CHUNK_SIZE};
+use light::Light;
+
+/// Every loaded chunk of a world. Anything outside them reads as unloaded rather than air so
+/// callers can tell "nothing there" from "not known yet".
+pub struct World {
+    chunks: HashMap<ChunkPos, Chunk>,
+    /// Chunks added, removed or edited since the renderer last looked, along with loaded
+    /// neighbours whose border faces may have changed
+    dirty: HashSet<ChunkPos>,
+    /// Loaded chunks that differ from what's on disk, or were never written at all
+    unsaved: HashSet<ChunkPos>,
+    /// Blocks changed since the last [`World::take_block_updates`], for anything that
+    /// reacts to its surroundings
+    block_updates: Vec<Point3<i32>>,
+    /// Chunks inserted and blocks changed that [`World::update_light`] hasn't got to yet
+    unlit_chunks: Vec<ChunkPos>,
+    light_changes: Vec<Point3<i32>>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        World {
+            chunks: HashMap::new(),
+            dirty: HashSet::new(),
+            unsaved: HashSet::new(),
+            block_updates: Vec::new(),
+            unlit_chunks: Vec::new(),
+            light_changes: Vec::new(),
+        }
+    }
+
+    pub fn chunk(&self, position: ChunkPos) -> Option<&Chunk> {
+        self.chunks.get(&position)
+    }
+
+    /// Returns the chunk it replaced, if any. The chunk counts as unsaved until
+    /// [`World::mark_saved`] says otherwise.
+    pub fn insert_chunk(&mut self, position: ChunkPos, chunk: Chunk) -> Option<Chunk> {
+        self.dirty.insert(position);
+        self.unsaved.insert(position);
+        self.unlit_chunks.push(position);
+        self.mark_neighbors_dirty(position);
+        self.chunks.insert(position, chunk)
+    }
+
+    pub fn remove_chunk(&mut self, position: ChunkPos) -> Option<Chunk> {
+        self.dirty.insert(position);
+        self.unsaved.remove(&position);
+        self.mark_neighbors_dirty(position);
+        self.chunks.remove(&position)
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// None if the block's chunk isn't loaded
+    pub fn block(&self, position: Point3<i32>) -> Option<BlockId> {
+        let (chunk, local) = ChunkPos::of_block(position);
+        self.chunks.get(&chunk).map(|chunk| chunk.get(local))
+    }
+
+    /// Returns the block that was replaced, or None without changing anything if the chunk
+    /// isn't loaded. A block entity goes along with the block it belonged to, take it first
+    /// to keep what's in it.
+    pub fn set_block(&mut self, position: Point3<i32>, block: BlockId) -> Option<BlockId> {
+        let (chunk_position, local) = ChunkPos::of_block(position);
+        let chunk = self.chunks.get_mut(&chunk_position)?;
+        let previous = chunk.set(local, block);
+        if previous != block {
+            chunk.remove_block_entity(local);
+            self.unsaved.insert(chunk_position);
+            self.block_updates.push(position);
+            self.light_changes.push(position);
+            self.mark_block_dirty(position);
+        }
+        Some(previous)
+    }
+
+    pub fn block_entity(&self, position: Point3<i32>) -> Option<&BlockEntity> {
+        let (chunk, local) = ChunkPos::of_block(position);
+        self.chunks.get(&chunk)?.block_entity(local)
+    }
+
+    /// The chunk counts as unsaved, whether or not anything gets changed
+    pub fn block_entity_mut(&mut self, position: Point3<i32>) -> Option<&mut BlockEntity> {
+        let (chunk_position, local) = ChunkPos::of_block(position);
+        let entity = self
+            .chunks
+            .get_mut(&chunk_position)?
+            .block_entity_mut(local)?;
+        self.unsaved.insert(chunk_position);
+        Some(entity)
+    }
+
+    /// Give the block at `position` its state, e.g. once it's placed. Nothing happens if the
+    /// chunk isn't loaded.
+    pub fn insert_block_entity(&mut self, position: Point3<i32>, entity: BlockEntity) {
+        let (chunk_position, local) = ChunkPos::of_block(position);
+        if let Some(chunk) = self.chunks.get_mut(&chunk_position) {
+            chunk.insert_block_entity(local, entity);
+            self.unsaved.insert(chunk_position);
+        }
+    }
+
+    /// Remove the state of the block at `position` and return it, e.g. to drop what was in
+    /// it before breaking the block
+    pub fn take_block_entity(&mut self, position: Point3<i32>) -> Option<BlockEntity> {
+        let (chunk_position, local) = ChunkPos::of_block(position);
+        let entity = self
+            .chunks
+            .get_mut(&chunk_position)?
+            .remove_block_entity(local)?;
+        self.unsaved.insert(chunk_position);
+        Some(entity)
+    }
+
+    /// Run `update` on every block entity in the loaded chunks, the chunks where it returns
+    /// true for any of them count as unsaved
+    pub fn update_block_entities(&mut self, mut update: impl FnMut(&mut BlockEntity) -> bool) {
+        for (position, chunk) in &mut self.chunks {
+            let mut changed = false;
+            for entity in chunk.block_entities_mut() {
+                changed |= update(entity);
+            }
+            if changed {
+                self.unsaved.insert(*position);
+            }
+        }
+    }
+
+    /// Every block entity in the loaded chunks up to `radius` chunks away from the one
+    /// `center` is in, with its position
+    pub fn block_entities_near(
+        &self,
+        center: Point3<i32>,
+        radius: i32,
+    ) -> impl Iterator<Item = (Point3<i32>, &BlockEntity)> {
+        let (center, _) = ChunkPos::of_block(center);
+        self.chunks
+            .iter()
+            .filter(move |(position, _)| {
+                (position.x - center.x).abs() <= radius
+                    && (position.y - center.y).abs() <= radius
+                    && (position.z - center.z).abs() <= radius
+            })
+            .flat_map(|(position, chunk)| {
+                let origin = position.origin();
+                chunk.block_entities().map(move |(local, entity)| {
+                    let offset = Vector3::new(local.x, local.y, local.z).map(i32::from);
+                    (origin + offset, entity)
+                })
+            })
+    }
+
+    /// None if the block's chunk isn't loaded
+    pub fn light(&self, position: Point3<i32>) -> Option<Light> {
+        let (chunk, local) = ChunkPos::of_block(position);
+        self.chunks.get(&chunk).map(|chunk| chunk.light(local))
+    }
+
+    /// Light the chunks inserted and relight around the blocks changed since the last call
+    pub fn update_light(&mut self, blocks: &BlockRegistry) {
+        for position in std::mem::take(&mut self.unlit_chunks) {
+            light::light_chunk(self, blocks, position);
+        }
+        for position in std::mem::take(&mut self.light_changes) {
+            light::relight_block(self, blocks, position);
+        }
+    }
+
+    pub fn chunk_positions(&self) -> impl Iterator<Item = ChunkPos> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    /// Have every loaded chunk come out of the next [`World::take_dirty`], e.g. to remesh
+    /// everything after a render setting changed
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty.extend(self.chunks.keys().copied());
+    }
+
+    /// Chunks that changed since the last call, including ones that got unloaded
+    pub fn take_dirty(&mut self) -> Vec<ChunkPos> {
+        self.dirty.drain().collect()
+    }
+
+    /// Blocks changed with [`World::set_block`] since the last call, loading chunks doesn't
+    /// count
+    pub fn take_block_updates(&mut self) -> Vec<Point3<i32>> {
+        std::mem::take(&mut self.block_updates)
+    }
+
+    pub fn is_unsaved(&self, position: ChunkPos) -> bool {
+        self.unsaved.contains(&position)
+    }
+
+    /// The chunk matches what's on disk now
+    pub fn mark_saved(&mut self, position: ChunkPos) {
+        self.unsaved.remove(&position);
+    }
+
+    pub fn unsaved_chunks(&self) -> impl Iterator<Item = ChunkPos> + '_ {
+        self.unsaved.iter().copied()
+    }
+
+    /// The block's chunk and whichever neighbouring chunks it touches, diagonally too, since
+    /// the mesher looks one block past the border for faces and shading
+    fn mark_block_dirty(&mut self, position: Point3<i32>) {
+        let (chunk_position, local) = ChunkPos::of_block(position);
+        self.dirty.insert(chunk_position);
+        let range = |local: u8| match i32::from(local) {
+            0 => -1..=0,
+            value if value == CHUNK_SIZE - 1 => 0..=1,
+            _ => 0..=0,
+        };
+        for x in range(local.x) {
+            for y in range(local.y) {
+                for z in range(local.z) {
+                    let neighbor = ChunkPos::new(
+                        chunk_position.x + x,
+                        chunk_position.y + y,
+                        chunk_position.z + z,
+                    );
+                    if neighbor != chunk_position && self.chunks.contains_key(&neighbor) {
+                        self.dirty.insert(neighbor);
+                    }
+                }
+            }
+        }
+    }
+
+    /// All 26 around the chunk, the mesher looks into the diagonal ones for shading.
+    /// Unloaded neighbours are skipped, they get meshed when they're inserted.
+    fn mark_neighbors_dirty(&mut self, position: ChunkPos) {
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    let neighbor = ChunkPos::new(position.x + x, position.y + y, position.z + z);
+                    if neighbor != position && self.chunks.contains_key(&neighbor) {
+                        self.dirty.insert(neighbor);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
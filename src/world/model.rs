@@ -0,0 +1,277 @@
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::block::Face;
+use crate::assets::Assets;
+
+const MODEL_DIR: &str = "models";
+
+/// Corners of each face of a unit cube in [`Face::ALL`] order, counter-clockwise seen from
+/// outside and starting at the bottom left of the texture
+pub const FACE_CORNERS: [[[f32; 3]; 4]; 6] = [
+    // East
+    [
+        [1.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [1.0, 1.0, 1.0],
+    ],
+    // West
+    [
+        [0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0],
+    ],
+    // Up
+    [
+        [0.0, 1.0, 1.0],
+        [1.0, 1.0, 1.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+    ],
+    // Down
+    [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0],
+    ],
+    // South
+    [
+        [0.0, 0.0, 1.0],
+        [1.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0],
+        [0.0, 1.0, 1.0],
+    ],
+    // North
+    [
+        [1.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 1.0, 0.0],
+    ],
+];
+/// Texture corner of each of [`FACE_CORNERS`]
+pub const CORNER_UVS: [[f32; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+/// A turn of a block about its centre by whole quarter turns, the rows of its matrix
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rotation([[i32; 3]; 3]);
+
+impl Rotation {
+    pub const IDENTITY: Rotation = Rotation([[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+    /// Tips the top over towards +X
+    pub const UP_TO_EAST: Rotation = Rotation([[0, 1, 0], [-1, 0, 0], [0, 0, 1]]);
+    /// Tips the top over towards +Z
+    pub const UP_TO_SOUTH: Rotation = Rotation([[1, 0, 0], [0, 0, -1], [0, 1, 0]]);
+
+    /// `turns` quarter turns about the vertical axis, each one from south to west
+    pub fn about_y(turns: u32) -> Self {
+        let [sin, cos] = match turns % 4 {
+            0 => [0, 1],
+            1 => [1, 0],
+            2 => [0, -1],
+            _ => [-1, 0],
+        };
+        Rotation([[cos, 0, -sin], [0, 1, 0], [sin, 0, cos]])
+    }
+
+    /// This turn followed by `next`
+    pub fn then(self, next: Rotation) -> Rotation {
+        Rotation(
+            next.0.map(|row| {
+                [0, 1, 2].map(|column| (0..3).map(|k| row[k] * self.0[k][column]).sum())
+            }),
+        )
+    }
+
+    /// Where `face` ends up
+    pub fn face(self, face: Face) -> Face {
+        // Turning a unit axis gives another one
+        Face::from_normal(self.offset(face.normal())).unwrap_or(face)
+    }
+
+    /// Where an offset of whole blocks ends up, turned about the origin rather than a block's
+    /// middle
+    pub fn offset(self, offset: [i32; 3]) -> [i32; 3] {
+        self.0
+            .map(|row| (0..3).map(|axis| row[axis] * offset[axis]).sum())
+    }
+
+    /// The lowest corner of a box `size` blocks big with its own lowest corner at the origin,
+    /// once it's turned
+    pub fn turned_min(self, size: [i32; 3]) -> [i32; 3] {
+        let [x, y, z] = size.map(|side| side - 1);
+        let corners =
+            [[0, 0, 0], [x, y, z], [x, 0, 0], [0, 0, z]].map(|corner| self.offset(corner));
+        [0, 1, 2].map(|axis| corners.iter().map(|corner| corner[axis]).min().unwrap_or(0))
+    }
+
+    /// Where a point in block units ends up
+    fn point(self, point: [f32; 3]) -> [f32; 3] {
+        self.0.map(|row| {
+            let turned: f32 = (0..3)
+                .map(|axis| row[axis] as f32 * (point[axis] - 0.5))
+                .sum();
+            turned + 0.5
+        })
+    }
+}
+
+/// One face of a model element, laid out for the mesher
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelQuad {
+    pub face: Face,
+    /// In block units and [`FACE_CORNERS`] order
+    pub corners: [[f32; 3]; 4],
+    /// Texture coordinates of each corner, 1 spans the whole texture
+    pub uvs: [[f32; 2]; 4],
+    pub texture: String,
+    /// Hidden when the neighbour on that side is an opaque block
+    pub cullface: Option<Face>,
+}
+
+/// A block shape made of cuboids instead of a full cube, loaded from `models/<name>.json`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockModel {
+    pub quads: Vec<ModelQuad>,
+    /// Min and max corner of each element in block units, for collision and selection
+    pub boxes: Vec<([f32; 3], [f32; 3])>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ModelFile {
+    elements: Vec<ElementFile>,
+}
+
+/// A cuboid, in sixteenths of a block like the texture pixels
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ElementFile {
+    from: [f32; 3],
+    to: [f32; 3],
+    /// Faces left out aren't drawn
+    faces: HashMap<Face, FaceFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FaceFile {
+    /// `#key` looks the texture up in the block's `textures`, anything else is a texture name
+    texture: String,
+    /// Pixels `[u1, v1, u2, v2]` from the top left, defaults to the part of the texture
+    /// where the face would be on a full block
+    #[serde(default)]
+    uv: Option<[f32; 4]>,
+    #[serde(default)]
+    cullface: Option<Face>,
+}
+
+impl BlockModel {
+    /// `resolve` looks up `#key` textures in the block using the model
+    pub fn load(
+        assets: &Assets,
+        name: &str,
+        resolve: impl Fn(&str) -> Option<String>,
+    ) -> anyhow::Result<Self> {
+        let path = format!("{MODEL_DIR}/{name}.json");
+        let bytes = assets.read(&path)?;
+        let file: ModelFile =
+            serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {path}"))?;
+        if file.elements.is_empty() {
+            bail!("{path}: a model needs at least one element");
+        }
+
+        let mut model = BlockModel {
+            quads: Vec::new(),
+            boxes: Vec::new(),
+        };
+        for element in &file.elements {
+            let in_block = |value: &f32| (0.0..=16.0).contains(value);
+            let ordered = (0..3).all(|axis| element.from[axis] <= element.to[axis]);
+            if !element.from.iter().chain(&element.to).all(in_block) || !ordered {
+                bail!("{path}: elements go from a smaller to a larger corner within 0 to 16");
+            }
+            let from = element.from.map(|value| value / 16.0);
+            let to = element.to.map(|value| value / 16.0);
+            model.boxes.push((from, to));
+
+            for face in Face::ALL {
+                let Some(face_file) = element.faces.get(&face) else {
+                    continue;
+                };
+                let texture = match face_file.texture.strip_prefix('#') {
+                    Some(key) => resolve(key)
+                        .with_context(|| format!("{path}: the block has no texture {key}"))?,
+                    None => face_file.texture.clone(),
+                };
+                let unit = FACE_CORNERS[face as usize];
+                let corners = unit.map(|corner| {
+                    [0, 1, 2].map(|axis| from[axis] + corner[axis] * (to[axis] - from[axis]))
+                });
+                let uvs = match face_file.uv {
+                    Some([u1, v1, u2, v2]) => CORNER_UVS.map(|[u, v]| {
+                        // The file counts rows from the top, textures here from the bottom
+                        [
+                            (u1 + u * (u2 - u1)) / 16.0,
+                            1.0 - (v2 + v * (v1 - v2)) / 16.0,
+                        ]
+                    }),
+                    None => default_uvs(&unit, &corners),
+                };
+                model.quads.push(ModelQuad {
+                    face,
+                    corners,
+                    uvs,
+                    texture,
+                    cullface: face_file.cullface,
+                });
+            }
+        }
+        Ok(model)
+    }
+
+    /// The same shape turned by `rotation`, textures stay on the faces they were on
+    pub fn rotated(&self, rotation: Rotation) -> BlockModel {
+        let quads = self
+            .quads
+            .iter()
+            .map(|quad| ModelQuad {
+                face: rotation.face(quad.face),
+                corners: quad.corners.map(|corner| rotation.point(corner)),
+                cullface: quad.cullface.map(|face| rotation.face(face)),
+                ..quad.clone()
+            })
+            .collect();
+        let boxes = self
+            .boxes
+            .iter()
+            .map(|(min, max)| {
+                let (a, b) = (rotation.point(*min), rotation.point(*max));
+                (
+                    [0, 1, 2].map(|axis| a[axis].min(b[axis])),
+                    [0, 1, 2].map(|axis| a[axis].max(b[axis])),
+                )
+            })
+            .collect();
+        BlockModel { quads, boxes }
+    }
+}
+
+/// Where each corner would sample the texture on a full block's face, so a half height side
+/// shows the bottom half of the texture and so on
+fn default_uvs(unit: &[[f32; 3]; 4], corners: &[[f32; 3]; 4]) -> [[f32; 2]; 4] {
+    // U runs from the first corner toward the second, V toward the fourth
+    let axis_between = |a: [f32; 3], b: [f32; 3]| (0..3).find(|axis| a[*axis] != b[*axis]);
+    let u_axis = axis_between(unit[0], unit[1]).unwrap_or(0);
+    let v_axis = axis_between(unit[0], unit[3]).unwrap_or(1);
+    corners.map(|corner| {
+        [
+            (corner[u_axis] - unit[0][u_axis]).abs(),
+            (corner[v_axis] - unit[0][v_axis]).abs(),
+        ]
+    })
+}
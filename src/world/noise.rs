@@ -0,0 +1,156 @@
+/// Improved Perlin noise over a shuffled permutation table, the same seed always gives the
+/// same values
+pub struct Perlin {
+    permutation: [u8; 512],
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut table: [u8; 256] = std::array::from_fn(|index| index as u8);
+        let mut state = seed;
+        for index in (1..table.len()).rev() {
+            let other = (splitmix64(&mut state) % (index as u64 + 1)) as usize;
+            table.swap(index, other);
+        }
+        Perlin {
+            permutation: std::array::from_fn(|index| table[index % 256]),
+        }
+    }
+
+    /// Roughly -1 to 1, zero on every integer coordinate
+    pub fn noise2(&self, x: f64, y: f64) -> f64 {
+        self.noise3(x, y, 0.0)
+    }
+
+    /// Roughly -1 to 1, zero on every integer coordinate
+    pub fn noise3(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (xf, yf, zf) = (x.floor(), y.floor(), z.floor());
+        let (x, y, z) = (x - xf, y - yf, z - zf);
+        let [xi, yi, zi] = [xf, yf, zf].map(|value| (value as i64 & 255) as usize);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let p = &self.permutation;
+        let a = p[xi] as usize + yi;
+        let (aa, ab) = (p[a] as usize + zi, p[a + 1] as usize + zi);
+        let b = p[xi + 1] as usize + yi;
+        let (ba, bb) = (p[b] as usize + zi, p[b + 1] as usize + zi);
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+                lerp(
+                    u,
+                    grad(p[ab], x, y - 1.0, z),
+                    grad(p[bb], x - 1.0, y - 1.0, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.0),
+                    grad(p[ba + 1], x - 1.0, y, z - 1.0),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1.0, z - 1.0),
+                    grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+}
+
+/// Octaves of Perlin noise added up, each at twice the frequency and half the amplitude of
+/// the one before
+pub struct Fractal {
+    octaves: Vec<Perlin>,
+    /// Of the first octave, in cycles per block
+    frequency: f64,
+}
+
+impl Fractal {
+    pub fn new(seed: u64, octaves: u32, frequency: f64) -> Self {
+        Fractal {
+            octaves: (0..octaves)
+                .map(|octave| Perlin::new(hash(seed, &[octave as i64])))
+                .collect(),
+            frequency,
+        }
+    }
+
+    /// Scaled back to roughly -1 to 1
+    pub fn sample2(&self, x: f64, y: f64) -> f64 {
+        self.sum(|noise, frequency| noise.noise2(x * frequency, y * frequency))
+    }
+
+    /// Scaled back to roughly -1 to 1
+    pub fn sample3(&self, x: f64, y: f64, z: f64) -> f64 {
+        self.sum(|noise, frequency| noise.noise3(x * frequency, y * frequency, z * frequency))
+    }
+
+    fn sum(&self, sample: impl Fn(&Perlin, f64) -> f64) -> f64 {
+        let (mut total, mut range) = (0.0, 0.0);
+        let (mut frequency, mut amplitude) = (self.frequency, 1.0);
+        for noise in &self.octaves {
+            total += sample(noise, frequency) * amplitude;
+            range += amplitude;
+            frequency *= 2.0;
+            amplitude *= 0.5;
+        }
+        if range > 0.0 {
+            total / range
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Well mixed bits for a seed and some coordinates, for decisions that have to come out the
+/// same every time a chunk is generated
+pub fn hash(seed: u64, values: &[i64]) -> u64 {
+    let mut state = seed;
+    let mut hash = splitmix64(&mut state);
+    for &value in values {
+        state = hash ^ value as u64;
+        hash = splitmix64(&mut state);
+    }
+    hash
+}
+
+/// [`hash`] as a number from 0 up to but not including 1
+pub fn hash_unit(seed: u64, values: &[i64]) -> f64 {
+    (hash(seed, values) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Dot product with one of twelve edge directions picked by the hash
+fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
@@ -0,0 +1,67 @@
+use anyhow::{bail, Context};
+use serde::Deserialize;
+
+use super::block::BlockRegistry;
+use super::chunk::CHUNK_SIZE;
+use super::BlockId;
+use crate::assets::Assets;
+
+const ORES_PATH: &str = "worldgen/ores.json";
+
+/// One kind of ore and where it shows up
+#[derive(Debug, Clone)]
+pub struct OreVein {
+    pub block: BlockId,
+    /// Lowest and highest Y a vein can start at, both included
+    pub min_height: i32,
+    pub max_height: i32,
+    /// Blocks of ore in a vein at most, some may land outside stone
+    pub size: u32,
+    /// Veins per chunk inside the height range, the fraction is a chance for one more
+    pub frequency: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OreFile {
+    block: String,
+    min_height: i32,
+    max_height: i32,
+    vein_size: u32,
+    veins_per_chunk: f64,
+}
+
+/// Ore veins from `worldgen/ores.json`, in the order they're placed
+pub fn load_ores(assets: &Assets, blocks: &BlockRegistry) -> anyhow::Result<Vec<OreVein>> {
+    let bytes = assets.read(ORES_PATH)?;
+    let files: Vec<OreFile> =
+        serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {ORES_PATH}"))?;
+    files
+        .into_iter()
+        .map(|file| {
+            let Some(block) = blocks.id(&file.block) else {
+                bail!("Unknown block {} in {ORES_PATH}", file.block);
+            };
+            if file.min_height > file.max_height {
+                bail!(
+                    "{} in {ORES_PATH} has min_height above max_height",
+                    file.block
+                );
+            }
+            // A vein has to stay within the neighbouring chunks, see GenerationPipeline
+            if file.vein_size > CHUNK_SIZE as u32 {
+                bail!(
+                    "{} in {ORES_PATH} has veins over {CHUNK_SIZE} blocks",
+                    file.block
+                );
+            }
+            Ok(OreVein {
+                block,
+                min_height: file.min_height,
+                max_height: file.max_height,
+                size: file.vein_size,
+                frequency: file.veins_per_chunk.max(0.0),
+            })
+        })
+        .collect()
+}
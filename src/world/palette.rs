@@ -0,0 +1,108 @@
+use super::chunk::CHUNK_VOLUME;
+use super::BlockId;
+
+/// A chunk's blocks as indices into a palette of the ids actually used, packed into u64 words
+/// with as few bits per block as the palette allows. A single block type takes no words at
+/// all, two take one bit each.
+#[derive(Debug, Clone)]
+pub struct PalettedStorage {
+    palette: Vec<BlockId>,
+    /// Blocks using each palette slot, a slot at 0 is free to take another id
+    counts: Vec<u16>,
+    /// 0 or a power of two so entries never straddle two words
+    bits: u32,
+    words: Vec<u64>,
+}
+
+impl PalettedStorage {
+    pub fn filled(block: BlockId) -> Self {
+        PalettedStorage {
+            palette: vec![block],
+            counts: vec![CHUNK_VOLUME as u16],
+            bits: 0,
+            words: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, index: usize) -> BlockId {
+        self.palette[self.slot(index)]
+    }
+
+    /// Returns the block that was there before
+    pub fn set(&mut self, index: usize, block: BlockId) -> BlockId {
+        let old_slot = self.slot(index);
+        let previous = self.palette[old_slot];
+        if previous == block {
+            return previous;
+        }
+        let new_slot = self.slot_for(block);
+        self.counts[old_slot] -= 1;
+        self.counts[new_slot] += 1;
+        self.write(index, new_slot);
+        previous
+    }
+
+    /// How many blocks are `block`
+    pub fn count(&self, block: BlockId) -> usize {
+        self.palette
+            .iter()
+            .zip(&self.counts)
+            .filter(|(entry, _)| **entry == block)
+            .map(|(_, count)| *count as usize)
+            .sum()
+    }
+
+    fn slot(&self, index: usize) -> usize {
+        if self.bits == 0 {
+            return 0;
+        }
+        let per_word = 64 / self.bits as usize;
+        let shift = (index % per_word) as u32 * self.bits;
+        ((self.words[index / per_word] >> shift) & self.mask()) as usize
+    }
+
+    fn write(&mut self, index: usize, slot: usize) {
+        let per_word = 64 / self.bits as usize;
+        let shift = (index % per_word) as u32 * self.bits;
+        let mask = self.mask();
+        let word = &mut self.words[index / per_word];
+        *word = (*word & !(mask << shift)) | ((slot as u64) << shift);
+    }
+
+    fn mask(&self) -> u64 {
+        (1 << self.bits) - 1
+    }
+
+    /// Palette slot holding `block`, taking a free slot or adding one if it has none yet
+    fn slot_for(&mut self, block: BlockId) -> usize {
+        if let Some(slot) = self
+            .palette
+            .iter()
+            .zip(&self.counts)
+            .position(|(entry, count)| *entry == block && *count > 0)
+        {
+            return slot;
+        }
+        if let Some(slot) = self.counts.iter().position(|count| *count == 0) {
+            self.palette[slot] = block;
+            return slot;
+        }
+        self.palette.push(block);
+        self.counts.push(0);
+        if self.palette.len() > 1 << self.bits {
+            self.grow();
+        }
+        self.palette.len() - 1
+    }
+
+    /// Repack every entry with twice the bits
+    fn grow(&mut self) {
+        let slots: Vec<usize> = (0..CHUNK_VOLUME).map(|index| self.slot(index)).collect();
+        self.bits = if self.bits == 0 { 1 } else { self.bits * 2 };
+        let per_word = 64 / self.bits as usize;
+        self.words = vec![0; CHUNK_VOLUME.div_ceil(per_word)];
+        for (index, slot) in slots.into_iter().enumerate() {
+            self.write(index, slot);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use cgmath::Point3;
+use std::collections::{HashMap, HashSet};
+
+use super::chunk::{Chunk, ChunkPos};
+use super::generator::TerrainGenerator;
+use super::BlockId;
+
+/// How far a chunk has come through generation
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Stage {
+    /// Terrain, caves and nothing else
+    Terrain,
+    /// Its own structures are placed, neighbours may still add theirs
+    Decorated,
+}
+
+/// Chunks that are still being generated. Structures reach up to one chunk past the one they
+/// start in, so a chunk is only decorated once all 26 neighbours have their terrain, and only
+/// finished once all of them are decorated too. After that nothing can write into it anymore.
+pub struct GenerationPipeline {
+    chunks: HashMap<ChunkPos, (Stage, Chunk)>,
+    /// Handed out already, their structures are in place and nothing writes to them anymore
+    finished: HashSet<ChunkPos>,
+}
+
+impl GenerationPipeline {
+    pub fn new() -> Self {
+        GenerationPipeline {
+            chunks: HashMap::new(),
+            finished: HashSet::new(),
+        }
+    }
+
+    /// Run whatever stages `position` and its neighbours still need and hand over the
+    /// finished chunk
+    pub fn generate(&mut self, generator: &TerrainGenerator, position: ChunkPos) -> Chunk {
+        if self.finished.remove(&position) {
+            // Asked for again, e.g. after being unloaded. The neighbours have to place their
+            // structures into the new copy as well.
+            for neighbor in neighborhood(position) {
+                self.finished.remove(&neighbor);
+            }
+        }
+        for neighbor in neighborhood(position) {
+            self.decorate(generator, neighbor);
+        }
+        self.finished.insert(position);
+        self.chunks
+            .remove(&position)
+            .map(|(_, chunk)| chunk)
+            .unwrap_or_else(|| generator.generate_terrain(position))
+    }
+
+    /// Drop everything further than `radius` chunks from `center` on any axis, it gets
+    /// generated again if it's ever needed
+    pub fn forget_far(&mut self, center: ChunkPos, radius: i32) {
+        let near = |position: &ChunkPos| {
+            (position.x - center.x).abs() <= radius
+                && (position.y - center.y).abs() <= radius
+                && (position.z - center.z).abs() <= radius
+        };
+        self.chunks.retain(|position, _| near(position));
+        self.finished.retain(near);
+    }
+
+    fn decorate(&mut self, generator: &TerrainGenerator, position: ChunkPos) {
+        if self.finished.contains(&position)
+            || matches!(self.chunks.get(&position), Some((Stage::Decorated, _)))
+        {
+            return;
+        }
+        for neighbor in neighborhood(position) {
+            // Finished ones already have everything their neighbours could place
+            if !self.finished.contains(&neighbor) {
+                self.chunks
+                    .entry(neighbor)
+                    .or_insert_with(|| (Stage::Terrain, generator.generate_terrain(neighbor)));
+            }
+        }
+        generator.decorate(position, &mut Region { pipeline: self });
+        if let Some((stage, _)) = self.chunks.get_mut(&position) {
+            *stage = Stage::Decorated;
+        }
+    }
+}
+
+impl Default for GenerationPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Block access across the chunks in the pipeline, for placing structures
+pub struct Region<'a> {
+    pipeline: &'a mut GenerationPipeline,
+}
+
+impl Region<'_> {
+    /// None outside the chunks being generated
+    pub fn get(&self, position: Point3<i32>) -> Option<BlockId> {
+        let (chunk, local) = ChunkPos::of_block(position);
+        self.pipeline
+            .chunks
+            .get(&chunk)
+            .map(|(_, chunk)| chunk.get(local))
+    }
+
+    /// Ignored outside the chunks being generated
+    pub fn set(&mut self, position: Point3<i32>, block: BlockId) {
+        let (chunk, local) = ChunkPos::of_block(position);
+        if let Some((_, chunk)) = self.pipeline.chunks.get_mut(&chunk) {
+            chunk.set(local, block);
+        }
+    }
+}
+
+/// The chunk itself and the 26 around it
+fn neighborhood(center: ChunkPos) -> impl Iterator<Item = ChunkPos> {
+    (-1..=1).flat_map(move |x| {
+        (-1..=1).flat_map(move |y| {
+            (-1..=1).map(move |z| ChunkPos::new(center.x + x, center.y + y, center.z + z))
+        })
+    })
+}
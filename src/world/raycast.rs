@@ -0,0 +1,180 @@
+use cgmath::{InnerSpace, Point3, Vector3};
+
+use super::block::{Face, FULL_BLOCK};
+use super::{BlockRegistry, World};
+
+/// A block a ray ran into
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RayHit {
+    pub block: Point3<i32>,
+    /// Side the ray came in through, None if it started inside the block
+    pub face: Option<Face>,
+    /// Where the ray met the block
+    pub position: Point3<f32>,
+    /// From the ray's origin to `position`
+    pub distance: f32,
+    /// Min and max corner around everything in the block, in block units. The whole block
+    /// unless it has a model.
+    pub bounds: ([f32; 3], [f32; 3]),
+}
+
+impl RayHit {
+    /// The block on the other side of the hit face, where a placed block would go
+    pub fn adjacent(&self) -> Point3<i32> {
+        let [x, y, z] = self.face.map_or([0; 3], Face::normal);
+        self.block + Vector3::new(x, y, z)
+    }
+}
+
+/// First solid block or block model along the ray within `reach` blocks, stepping through
+/// the grid one voxel boundary at a time. Models only count where the ray meets one of their
+/// boxes. Unloaded chunks stop the ray, nothing behind them counts.
+pub fn raycast(
+    world: &World,
+    blocks: &BlockRegistry,
+    origin: Point3<f32>,
+    direction: Vector3<f32>,
+    reach: f32,
+) -> Option<RayHit> {
+    if direction.magnitude2() == 0.0 {
+        return None;
+    }
+    let direction = direction.normalize();
+    let origin_array = [origin.x, origin.y, origin.z];
+    let direction_array = [direction.x, direction.y, direction.z];
+
+    let mut block = origin_array.map(|value| value.floor() as i32);
+    let step = direction_array.map(|value| {
+        if value > 0.0 {
+            1
+        } else if value < 0.0 {
+            -1
+        } else {
+            0
+        }
+    });
+    // Distance along the ray to cross one whole block on each axis, and to reach the next
+    // boundary on each axis
+    let delta = direction_array.map(|value| (1.0 / value).abs());
+    let mut next = [0, 1, 2].map(|axis| {
+        let origin = origin_array[axis];
+        let start = block[axis] as f32;
+        match step[axis] {
+            1 => (start + 1.0 - origin) * delta[axis],
+            -1 => (origin - start) * delta[axis],
+            _ => f32::INFINITY,
+        }
+    });
+
+    let mut face = None;
+    let mut distance = 0.0;
+    loop {
+        let position = Point3::new(block[0], block[1], block[2]);
+        let id = world.block(position)?;
+        match blocks.get(id) {
+            Some(block) if block.model.is_some() => {
+                let corner = position.map(|value| value as f32);
+                let hit = block
+                    .boxes()
+                    .iter()
+                    .filter_map(|(min, max)| {
+                        let min = corner + Vector3::from(*min);
+                        let max = corner + Vector3::from(*max);
+                        hit_box(origin, direction, min, max)
+                    })
+                    .min_by(|a, b| a.0.total_cmp(&b.0));
+                if let Some((distance, face)) = hit.filter(|(distance, _)| *distance <= reach) {
+                    return Some(RayHit {
+                        block: position,
+                        face,
+                        position: origin + direction * distance,
+                        distance,
+                        bounds: bounds(block.boxes()),
+                    });
+                }
+            }
+            Some(block) if block.solid => {
+                return Some(RayHit {
+                    block: position,
+                    face,
+                    position: origin + direction * distance,
+                    distance,
+                    bounds: FULL_BLOCK[0],
+                });
+            }
+            _ => {}
+        }
+
+        let axis = (0..3)
+            .min_by(|a, b| next[*a].total_cmp(&next[*b]))
+            .unwrap_or(0);
+        distance = next[axis];
+        if distance > reach {
+            return None;
+        }
+        block[axis] += step[axis];
+        next[axis] += delta[axis];
+        face = Some(entered_face(axis, step[axis]));
+    }
+}
+
+/// Distance along the ray to where it enters the box and the face it comes in through, None
+/// for the face if the ray starts inside. Slab test, one axis at a time.
+fn hit_box(
+    origin: Point3<f32>,
+    direction: Vector3<f32>,
+    min: Point3<f32>,
+    max: Point3<f32>,
+) -> Option<(f32, Option<Face>)> {
+    let mut enter = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut face = None;
+    for axis in 0..3 {
+        if direction[axis] == 0.0 {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let a = (min[axis] - origin[axis]) / direction[axis];
+        let b = (max[axis] - origin[axis]) / direction[axis];
+        let (near, far) = if a < b { (a, b) } else { (b, a) };
+        if near > enter {
+            enter = near;
+            face = Some(entered_face(axis, direction[axis].signum() as i32));
+        }
+        exit = exit.min(far);
+    }
+    if enter > exit || exit < 0.0 {
+        return None;
+    }
+    if enter < 0.0 {
+        return Some((0.0, None));
+    }
+    Some((enter, face))
+}
+
+/// The box around all of `boxes`
+fn bounds(boxes: &[([f32; 3], [f32; 3])]) -> ([f32; 3], [f32; 3]) {
+    boxes.iter().fold(
+        ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]),
+        |(min, max), (box_min, box_max)| {
+            (
+                [0, 1, 2].map(|axis| min[axis].min(box_min[axis])),
+                [0, 1, 2].map(|axis| max[axis].max(box_max[axis])),
+            )
+        },
+    )
+}
+
+/// Moving along +X enters a block through its west side and so on
+fn entered_face(axis: usize, step: i32) -> Face {
+    match (axis, step > 0) {
+        (0, true) => Face::West,
+        (0, false) => Face::East,
+        (1, true) => Face::Down,
+        (1, false) => Face::Up,
+        (_, true) => Face::North,
+        (_, false) => Face::South,
+    }
+}
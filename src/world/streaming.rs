@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+
+use super::chunk::{Chunk, ChunkPos};
+use super::generator::TerrainGenerator;
+use super::pipeline::GenerationPipeline;
+use crate::save::saver::ChunkStore;
+
+/// Loads or generates chunks on a background thread, in whatever order they were last asked
+/// for. The generation pipeline lives on that thread since its stages depend on each other.
+pub struct ChunkStreamer {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+    results: Receiver<StreamedChunk>,
+}
+
+pub struct StreamedChunk {
+    pub position: ChunkPos,
+    pub chunk: Chunk,
+    /// Read from the save rather than generated, so there's nothing new to write back
+    pub loaded: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    available: Condvar,
+    shutdown: AtomicBool,
+}
+
+struct State {
+    /// Most urgent last
+    queue: Vec<ChunkPos>,
+    /// Being generated right now
+    busy: Option<ChunkPos>,
+    /// Sent back but not received yet, so they don't get asked for twice
+    delivered: HashSet<ChunkPos>,
+    /// Pipeline chunks further than this from the center are dropped
+    center: ChunkPos,
+    keep_radius: i32,
+}
+
+impl ChunkStreamer {
+    /// `pipeline` may already have finished chunks, e.g. around spawn. Chunks saved in
+    /// `store` are loaded instead of generated.
+    pub fn new(
+        generator: Arc<TerrainGenerator>,
+        pipeline: GenerationPipeline,
+        store: Arc<ChunkStore>,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                queue: Vec::new(),
+                busy: None,
+                delivered: HashSet::new(),
+                center: ChunkPos::new(0, 0, 0),
+                keep_radius: i32::MAX,
+            }),
+            available: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+        });
+        let (sender, results) = mpsc::channel();
+        let thread = {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name(String::from("chunk generator"))
+                .spawn(move || generate(&shared, &generator, pipeline, &store, &sender))
+                .expect("failed to spawn the chunk generator thread")
+        };
+        ChunkStreamer {
+            shared,
+            thread: Some(thread),
+            results,
+        }
+    }
+
+    /// Replace everything still waiting with `positions`, most urgent first. Chunks in the
+    /// works or waiting to be received are skipped. Pipeline leftovers further than
+    /// `keep_radius` chunks from `center` get dropped.
+    pub fn request(&self, positions: &[ChunkPos], center: ChunkPos, keep_radius: i32) {
+        let mut state = self.shared.lock_state();
+        state.queue.clear();
+        for &position in positions.iter().rev() {
+            if state.busy != Some(position) && !state.delivered.contains(&position) {
+                state.queue.push(position);
+            }
+        }
+        state.center = center;
+        state.keep_radius = keep_radius;
+        drop(state);
+        self.shared.available.notify_one();
+    }
+
+    /// Chunks finished since the last call, never blocks
+    pub fn receive(&self) -> Vec<StreamedChunk> {
+        let finished: Vec<_> = self.results.try_iter().collect();
+        if !finished.is_empty() {
+            let mut state = self.shared.lock_state();
+            for streamed in &finished {
+                state.delivered.remove(&streamed.position);
+            }
+        }
+        finished
+    }
+
+    /// Chunks asked for that aren't done yet
+    pub fn queued(&self) -> usize {
+        let state = self.shared.lock_state();
+        state.queue.len() + usize::from(state.busy.is_some())
+    }
+}
+
+impl Drop for ChunkStreamer {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::Relaxed);
+        self.shared.available.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Shared {
+    fn lock_state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn generate(
+    shared: &Shared,
+    generator: &TerrainGenerator,
+    mut pipeline: GenerationPipeline,
+    store: &ChunkStore,
+    results: &Sender<StreamedChunk>,
+) {
+    loop {
+        let (position, center, keep_radius) = {
+            let mut state = shared.lock_state();
+            loop {
+                if shared.shutdown.load(Ordering::Relaxed) {
+                    return;
+                }
+                if let Some(position) = state.queue.pop() {
+                    state.busy = Some(position);
+                    break (position, state.center, state.keep_radius);
+                }
+                state = shared
+                    .available
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        };
+
+        pipeline.forget_far(center, keep_radius);
+        let saved = store.load(position).unwrap_or_else(|err| {
+            log::warn!("Generating chunk {position:?} again: {err:#}");
+            None
+        });
+        let loaded = saved.is_some();
+        let chunk = saved.unwrap_or_else(|| pipeline.generate(generator, position));
+
+        let mut state = shared.lock_state();
+        state.busy = None;
+        state.delivered.insert(position);
+        drop(state);
+        let streamed = StreamedChunk {
+            position,
+            chunk,
+            loaded,
+        };
+        if results.send(streamed).is_err() {
+            return;
+        }
+    }
+}
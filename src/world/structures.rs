@@ -0,0 +1,610 @@
+use anyhow::{bail, Context};
+use cgmath::{Point3, Vector3};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use super::biome::Biome;
+use super::block::{BlockRegistry, Face};
+use super::chunk::{ChunkPos, CHUNK_SIZE};
+use super::generator::{TerrainGenerator, SEA_LEVEL};
+use super::model::Rotation;
+use super::noise;
+use super::pipeline::Region;
+use super::BlockId;
+use crate::assets::Assets;
+use crate::save::schematic::Schematic;
+
+const STRUCTURES_PATH: &str = "worldgen/structures.json";
+/// Pieces stay within this many blocks of their structure's start sideways, so a chunk only
+/// has to plan the structures starting in the cells around it
+const MAX_REACH: i32 = 96;
+/// Most pieces a structure grows to, however many connectors are still open
+const MAX_PIECES: usize = 48;
+/// Surface pieces stand on a foundation reaching down this far at most to the terrain
+const FOUNDATION_DEPTH: i32 = 8;
+/// Layouts kept for the chunks after, one structure covers a lot of them
+const CACHED_LAYOUTS: usize = 256;
+/// Keeps the structures' numbers apart from anything else hashed from the seed
+const STRUCTURE_PURPOSE: i64 = 0x7374_7275;
+
+/// Contents of `worldgen/structures.json`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StructuresFile {
+    structures: Vec<StructureFile>,
+    /// Names of the templates in each pool, listing one more than once makes it likelier
+    pools: HashMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StructureFile {
+    name: String,
+    /// The pool its first piece comes from
+    start: String,
+    /// Where its first piece can go
+    biomes: Vec<Biome>,
+    /// Chunks along X and Z of the cells it gets one chance to start in each
+    spacing: i32,
+    /// From 0 to 1 that it starts in a cell
+    chance: f64,
+    /// Most pieces from the first one to any other
+    depth: u32,
+    /// Lowest and highest Y its first piece goes at underground, without one every piece
+    /// stands on the surface
+    height: Option<[i32; 2]>,
+    /// Fills in under surface pieces down to the terrain, nothing does if left out
+    foundation: Option<String>,
+}
+
+/// `structures/<name>.json`, a schematic along with where other pieces join onto it. Saving
+/// one with `/schem` and adding the connectors by hand is the easiest way to make one. The
+/// chests and signs in it aren't placed, chests fill in empty once they're opened.
+#[derive(Deserialize)]
+struct TemplateFile {
+    #[serde(flatten)]
+    schematic: Schematic,
+    #[serde(default)]
+    connectors: Vec<ConnectorFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConnectorFile {
+    /// A block on the side of the template that `facing` points out of
+    at: [i32; 3],
+    facing: Face,
+    /// The pool the piece joined on here comes from
+    pool: String,
+}
+
+/// One kind of structure and where it shows up
+struct Structure {
+    name: String,
+    start: usize,
+    biomes: Vec<Biome>,
+    spacing: i32,
+    chance: f64,
+    depth: u32,
+    height: Option<[i32; 2]>,
+    foundation: Option<BlockId>,
+}
+
+/// A piece structures are made of, with its blocks turned each way it can go
+struct Template {
+    size: [i32; 3],
+    /// Offset from the lowest corner and the block there after 0 to 3 quarter turns
+    blocks: Vec<([i32; 3], [BlockId; 4])>,
+    connectors: Vec<Connector>,
+}
+
+/// Where another piece can join onto a template, jigsaw style: the other piece's own
+/// connector goes right in front of it, facing back
+#[derive(Debug, Copy, Clone)]
+struct Connector {
+    at: [i32; 3],
+    facing: Face,
+    pool: usize,
+}
+
+/// The structures from `worldgen/structures.json` and the templates they're built from,
+/// `structures/<name>.json`. Each structure gets one chance to start in every cell of a
+/// grid over the world. From its first piece it grows one piece at a time, each joined onto
+/// an open connector of one before it, as long as it doesn't overlap any other.
+pub struct StructureSet {
+    structures: Vec<Structure>,
+    /// Templates in each pool, by index
+    pools: Vec<Vec<usize>>,
+    templates: Vec<Template>,
+}
+
+/// A template placed in the world
+#[derive(Debug, Copy, Clone)]
+pub struct Piece {
+    template: usize,
+    /// Quarter turns clockwise seen from above
+    turns: u32,
+    /// Lowest corner
+    min: Point3<i32>,
+    /// Blocks along X, Y and Z once turned
+    size: [i32; 3],
+    /// Follows the height of the terrain rather than staying underground
+    surface: bool,
+    /// Stands on a foundation of this, for pieces on the surface
+    foundation: Option<BlockId>,
+}
+
+/// Pieces of each structure by its index and the cell it starts in
+type Layouts = HashMap<(usize, i32, i32), Arc<[Piece]>>;
+
+/// Plans the structures of one world, keeping the latest layouts around since a structure
+/// spans many chunks
+pub struct StructurePlanner {
+    seed: u64,
+    layouts: Mutex<Layouts>,
+}
+
+pub fn load_structures(assets: &Assets, blocks: &BlockRegistry) -> anyhow::Result<StructureSet> {
+    let bytes = assets.read(STRUCTURES_PATH)?;
+    let file: StructuresFile = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Failed to parse {STRUCTURES_PATH}"))?;
+    // Sorted so the pool ids don't depend on the order of a hash map
+    let mut pool_names: Vec<&String> = file.pools.keys().collect();
+    pool_names.sort();
+    let pool_id = |name: &str| pool_names.iter().position(|pool| *pool == name);
+
+    let mut set = StructureSet {
+        structures: Vec::new(),
+        pools: Vec::new(),
+        templates: Vec::new(),
+    };
+    let mut template_ids = HashMap::new();
+    for name in &pool_names {
+        let mut pool = Vec::new();
+        for template in &file.pools[*name] {
+            if !template_ids.contains_key(template) {
+                set.templates
+                    .push(load_template(assets, blocks, template, pool_id)?);
+                template_ids.insert(template, set.templates.len() - 1);
+            }
+            pool.push(template_ids[template]);
+        }
+        if pool.is_empty() {
+            bail!("Pool {name} in {STRUCTURES_PATH} is empty");
+        }
+        set.pools.push(pool);
+    }
+
+    for structure in file.structures {
+        let name = structure.name;
+        let Some(start) = pool_id(&structure.start) else {
+            bail!(
+                "{name} in {STRUCTURES_PATH} starts from {}, which isn't a pool",
+                structure.start
+            );
+        };
+        if structure.spacing < 1 {
+            bail!("{name} in {STRUCTURES_PATH} needs a spacing of at least one chunk");
+        }
+        if !(0.0..=1.0).contains(&structure.chance) {
+            bail!("{name} in {STRUCTURES_PATH} has a chance outside 0 to 1");
+        }
+        if structure.height.is_some_and(|[low, high]| low > high) {
+            bail!("{name} in {STRUCTURES_PATH} has its lowest height above its highest");
+        }
+        let foundation = match structure.foundation {
+            Some(block) => Some(
+                blocks
+                    .id(&block)
+                    .with_context(|| format!("Unknown block {block} in {STRUCTURES_PATH}"))?,
+            ),
+            None => None,
+        };
+        set.structures.push(Structure {
+            name,
+            start,
+            biomes: structure.biomes,
+            spacing: structure.spacing,
+            chance: structure.chance,
+            depth: structure.depth,
+            height: structure.height,
+            foundation,
+        });
+    }
+    Ok(set)
+}
+
+fn load_template(
+    assets: &Assets,
+    blocks: &BlockRegistry,
+    name: &str,
+    pool_id: impl Fn(&str) -> Option<usize>,
+) -> anyhow::Result<Template> {
+    let path = format!("structures/{name}.json");
+    let bytes = assets.read(&path)?;
+    let file: TemplateFile =
+        serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {path}"))?;
+    let size = file.schematic.size.map(|side| side as i32);
+    let rotations = [0, 1, 2, 3].map(Rotation::about_y);
+    let mut template_blocks = Vec::new();
+    for (offset, block) in file.schematic.block_names() {
+        let Some(id) = blocks.id(block) else {
+            bail!("{path}: unknown block {block}");
+        };
+        let turned = rotations.map(|rotation| blocks.rotated(id, rotation));
+        template_blocks.push((offset.map(|value| value as i32).into(), turned));
+    }
+    let volume: i32 = size.iter().product();
+    if template_blocks.len() != volume as usize {
+        bail!(
+            "{path}: {} blocks for a size of {volume}",
+            template_blocks.len()
+        );
+    }
+    if size[0].max(size[2]) > MAX_REACH {
+        bail!("{path}: templates are {MAX_REACH} blocks across at most");
+    }
+
+    let mut connectors = Vec::new();
+    for connector in file.connectors {
+        let Some(pool) = pool_id(&connector.pool) else {
+            bail!(
+                "{path}: a connector joins {}, which isn't a pool",
+                connector.pool
+            );
+        };
+        let [x, y, z] = connector.at;
+        let inside = (0..3).all(|axis| (0..size[axis]).contains(&connector.at[axis]));
+        let on_side = match connector.facing {
+            Face::East => x == size[0] - 1,
+            Face::West => x == 0,
+            Face::South => z == size[2] - 1,
+            Face::North => z == 0,
+            Face::Up | Face::Down => false,
+        };
+        if !inside || !on_side {
+            bail!("{path}: connector at {x} {y} {z} isn't on the side it faces, which can't be up or down");
+        }
+        connectors.push(Connector {
+            at: connector.at,
+            facing: connector.facing,
+            pool,
+        });
+    }
+    Ok(Template {
+        size,
+        blocks: template_blocks,
+        connectors,
+    })
+}
+
+impl StructureSet {
+    /// The pieces of structure `index` starting in `cell`, none if it doesn't start there.
+    /// Only depends on the seed and the shape of the terrain, so every chunk comes up with
+    /// the same.
+    fn plan(
+        &self,
+        seed: u64,
+        generator: &TerrainGenerator,
+        index: usize,
+        cell: [i32; 2],
+    ) -> Vec<Piece> {
+        let structure = &self.structures[index];
+        let key = [
+            index as i64,
+            cell[0] as i64,
+            cell[1] as i64,
+            STRUCTURE_PURPOSE,
+        ];
+        let random = |values: &[i64]| noise::hash(noise::hash(seed, &key), values);
+        if noise::hash_unit(seed, &key) >= structure.chance {
+            return Vec::new();
+        }
+
+        let spacing = structure.spacing as u64;
+        let chunk = [0, 1]
+            .map(|axis| cell[axis] * structure.spacing + (random(&[axis as i64]) % spacing) as i32);
+        let (x, z) = (
+            chunk[0] * CHUNK_SIZE + CHUNK_SIZE / 2,
+            chunk[1] * CHUNK_SIZE + CHUNK_SIZE / 2,
+        );
+        let column = generator.column(x, z);
+        if !structure.biomes.contains(&column.biome) {
+            return Vec::new();
+        }
+        let y = match structure.height {
+            Some([low, high]) => low + (random(&[2]) % (high - low + 1) as u64) as i32,
+            None if column.height < SEA_LEVEL => return Vec::new(),
+            None => column.height,
+        };
+        let start = Point3::new(x, y, z);
+        let pool = &self.pools[structure.start];
+        let template = pool[(random(&[3]) % pool.len() as u64) as usize];
+        let turns = (random(&[4]) % 4) as u32;
+        let size = turned_size(self.templates[template].size, turns);
+        let mut pieces = vec![Piece {
+            template,
+            turns,
+            min: Point3::new(x - size[0] / 2, y, z - size[2] / 2),
+            size,
+            surface: structure.height.is_none(),
+            foundation: structure.foundation.filter(|_| structure.height.is_none()),
+        }];
+        log::debug!("Planning a {} at {x} {y} {z}", structure.name);
+
+        // Every piece with how far it is from the first and the connector it was joined by,
+        // which stays closed
+        let mut open = VecDeque::from([(0, 0, None)]);
+        while let Some((parent, depth, joined)) = open.pop_front() {
+            if depth >= structure.depth {
+                continue;
+            }
+            let piece = pieces[parent];
+            let connectors = &self.templates[piece.template].connectors;
+            for (index, connector) in connectors.iter().enumerate() {
+                if Some(index) == joined || pieces.len() >= MAX_PIECES {
+                    continue;
+                }
+                let (at, facing) = piece.connector(&self.templates[piece.template], connector);
+                let seed = random(&[5, parent as i64, index as i64]);
+                let target = at + Vector3::from(facing.normal());
+                let joining = Joining {
+                    structure,
+                    start,
+                    pool: connector.pool,
+                    target,
+                    facing,
+                };
+                if let Some((child, entry)) = self.join(generator, &pieces, &joining, seed) {
+                    pieces.push(child);
+                    open.push_back((pieces.len() - 1, depth + 1, Some(entry)));
+                }
+            }
+        }
+        pieces
+    }
+
+    /// A piece from the pool joined on at the target, turned so one of its connectors is
+    /// there facing back. Templates are tried from a place in the pool `seed` picks, the
+    /// first that fits without overlapping the other pieces wins. Returns it along with the
+    /// connector it was joined by.
+    fn join(
+        &self,
+        generator: &TerrainGenerator,
+        pieces: &[Piece],
+        joining: &Joining,
+        seed: u64,
+    ) -> Option<(Piece, usize)> {
+        let structure = joining.structure;
+        let on_surface = structure.height.is_none();
+        let pool = &self.pools[joining.pool];
+        let first = (seed % pool.len() as u64) as usize;
+        for attempt in 0..pool.len() {
+            let index = pool[(first + attempt) % pool.len()];
+            let template = &self.templates[index];
+            for (entry, connector) in template.connectors.iter().enumerate() {
+                let facing = joining.facing.opposite();
+                let Some(turns) =
+                    (0..4).find(|turns| Rotation::about_y(*turns).face(connector.facing) == facing)
+                else {
+                    continue;
+                };
+                let mut piece = Piece {
+                    template: index,
+                    turns,
+                    min: Point3::new(0, 0, 0),
+                    size: turned_size(template.size, turns),
+                    surface: on_surface,
+                    foundation: structure.foundation.filter(|_| on_surface),
+                };
+                let [x, y, z] = piece.offset(template, connector.at);
+                piece.min = joining.target - Vector3::new(x, y, z);
+                if on_surface {
+                    let [width, _, depth] = piece.size;
+                    let ground =
+                        generator.surface_height(piece.min.x + width / 2, piece.min.z + depth / 2);
+                    if ground < SEA_LEVEL {
+                        continue;
+                    }
+                    piece.min.y = ground;
+                }
+                let fits = piece.within(joining.start, MAX_REACH)
+                    && !pieces
+                        .iter()
+                        .any(|other| other.overlaps(&piece, on_surface));
+                if fits {
+                    return Some((piece, entry));
+                }
+            }
+        }
+        None
+    }
+
+    /// Set the blocks of `piece` that fall in the chunk at `position`, along with its
+    /// foundation
+    pub fn build(
+        &self,
+        generator: &TerrainGenerator,
+        piece: &Piece,
+        position: ChunkPos,
+        region: &mut Region,
+    ) {
+        let origin = position.origin();
+        let bottom = piece.min.y - piece.foundation.map_or(0, |_| FOUNDATION_DEPTH);
+        if bottom >= origin.y + CHUNK_SIZE || piece.min.y + piece.size[1] <= origin.y {
+            return;
+        }
+        let template = &self.templates[piece.template];
+        let inside = |block: Point3<i32>| ChunkPos::of_block(block).0 == position;
+        for (offset, turned) in &template.blocks {
+            let block = piece.position(template, *offset);
+            if inside(block) {
+                region.set(block, turned[piece.turns as usize]);
+            }
+        }
+
+        let Some(foundation) = piece.foundation else {
+            return;
+        };
+        let floor = template
+            .blocks
+            .iter()
+            .filter(|(offset, turned)| offset[1] == 0 && turned[0] != BlockId::AIR);
+        for (offset, _) in floor {
+            let top = piece.position(template, *offset);
+            let ground = generator.surface_height(top.x, top.z);
+            for y in (top.y - FOUNDATION_DEPTH).max(ground + 1)..top.y {
+                let block = Point3::new(top.x, y, top.z);
+                if inside(block) {
+                    region.set(block, foundation);
+                }
+            }
+        }
+    }
+}
+
+/// What a piece is being joined onto
+struct Joining<'a> {
+    structure: &'a Structure,
+    /// Where the structure started
+    start: Point3<i32>,
+    pool: usize,
+    /// Where the new piece's connector has to go
+    target: Point3<i32>,
+    /// Which way the connector it joins onto faces
+    facing: Face,
+}
+
+impl Piece {
+    /// Whether it stands on the surface with any of its columns within `margin` blocks of
+    /// the column at `x`, `z`
+    pub fn covers_column(&self, x: i32, z: i32, margin: i32) -> bool {
+        self.surface
+            && self.min.x - margin <= x
+            && x < self.min.x + self.size[0] + margin
+            && self.min.z - margin <= z
+            && z < self.min.z + self.size[2] + margin
+    }
+
+    /// Where `offset` from the template's lowest corner ends up relative to the piece's
+    fn offset(&self, template: &Template, offset: [i32; 3]) -> [i32; 3] {
+        let rotation = Rotation::about_y(self.turns);
+        let shift = rotation.turned_min(template.size);
+        let turned = rotation.offset(offset);
+        [0, 1, 2].map(|axis| turned[axis] - shift[axis])
+    }
+
+    fn position(&self, template: &Template, offset: [i32; 3]) -> Point3<i32> {
+        let [x, y, z] = self.offset(template, offset);
+        self.min + Vector3::new(x, y, z)
+    }
+
+    /// Where a connector of its template is in the world and which way it faces there
+    fn connector(&self, template: &Template, connector: &Connector) -> (Point3<i32>, Face) {
+        let facing = Rotation::about_y(self.turns).face(connector.facing);
+        (self.position(template, connector.at), facing)
+    }
+
+    /// Whether its columns all stay within `reach` blocks of the column of `start`
+    fn within(&self, start: Point3<i32>, reach: i32) -> bool {
+        let [width, _, depth] = self.size;
+        (start.x - self.min.x)
+            .abs()
+            .max((self.min.x + width - 1 - start.x).abs())
+            <= reach
+            && (start.z - self.min.z)
+                .abs()
+                .max((self.min.z + depth - 1 - start.z).abs())
+                <= reach
+    }
+
+    /// Whether the boxes of the two pieces share any block, or only any column when
+    /// `columns_only`
+    fn overlaps(&self, other: &Piece, columns_only: bool) -> bool {
+        (0..3)
+            .filter(|axis| !columns_only || *axis != 1)
+            .all(|axis| {
+                self.min[axis] < other.min[axis] + other.size[axis]
+                    && other.min[axis] < self.min[axis] + self.size[axis]
+            })
+    }
+}
+
+impl StructurePlanner {
+    pub fn new(seed: u64) -> Self {
+        StructurePlanner {
+            seed,
+            layouts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Pieces of every structure with a column within `margin` blocks of the chunk at
+    /// `position`, in the order they're built
+    pub fn pieces_near(
+        &self,
+        set: &StructureSet,
+        generator: &TerrainGenerator,
+        position: ChunkPos,
+        margin: i32,
+    ) -> Vec<Piece> {
+        let origin = position.origin();
+        let mut pieces = Vec::new();
+        for (index, structure) in set.structures.iter().enumerate() {
+            let cell_size = structure.spacing * CHUNK_SIZE;
+            let reach = MAX_REACH + margin;
+            let cells = |low: i32| {
+                (low - reach).div_euclid(cell_size)
+                    ..=(low + CHUNK_SIZE - 1 + reach).div_euclid(cell_size)
+            };
+            for cell_z in cells(origin.z) {
+                for cell_x in cells(origin.x) {
+                    let layout = self.layout(set, generator, index, [cell_x, cell_z]);
+                    pieces.extend(layout.iter().filter(|piece| {
+                        let [width, _, depth] = piece.size;
+                        piece.min.x - margin < origin.x + CHUNK_SIZE
+                            && origin.x < piece.min.x + width + margin
+                            && piece.min.z - margin < origin.z + CHUNK_SIZE
+                            && origin.z < piece.min.z + depth + margin
+                    }));
+                }
+            }
+        }
+        pieces
+    }
+
+    fn layout(
+        &self,
+        set: &StructureSet,
+        generator: &TerrainGenerator,
+        index: usize,
+        cell: [i32; 2],
+    ) -> Arc<[Piece]> {
+        let key = (index, cell[0], cell[1]);
+        let cached = self.lock().get(&key).cloned();
+        if let Some(layout) = cached {
+            return layout;
+        }
+        let layout: Arc<[Piece]> = set.plan(self.seed, generator, index, cell).into();
+        let mut layouts = self.lock();
+        if layouts.len() >= CACHED_LAYOUTS {
+            layouts.clear();
+        }
+        layouts.insert(key, Arc::clone(&layout));
+        layout
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Layouts> {
+        self.layouts.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Blocks along X, Y and Z of a box `size` blocks big after `turns` quarter turns
+fn turned_size(size: [i32; 3], turns: u32) -> [i32; 3] {
+    let [x, y, z] = size;
+    if turns % 2 == 1 {
+        [z, y, x]
+    } else {
+        [x, y, z]
+    }
+}